@@ -13,7 +13,10 @@ mod server;
 // 公开接口
 pub use client::{LogClient, LogError, LogResult, get_log_client, init_log_client, log_message};
 pub use message::{LogLevel, LogMessage};
-pub use transport::{LogTransport, PipeTransport, SyscallReadFn, SyscallWriteFn};
+pub use transport::{
+    IpcTransport, LogTransport, PipeTransport, SyscallIpcRecvFn, SyscallIpcSendFn, SyscallPollFn,
+    SyscallReadFn, SyscallWriteFn,
+};
 
 #[cfg(feature = "server")]
 pub use server::LogServer;
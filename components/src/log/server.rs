@@ -1,6 +1,7 @@
 //! # 日志服务器实现
 //!
 //! 独立的日志服务进程，接收并处理来自其他进程的日志消息。
+//! 没有消息时通过 `ppoll` 阻塞在管道上，客户端全部关闭后退出。
 
 use crate::log::{LogMessage, LogTransport, PipeTransport};
 use alloc::boxed::Box;
@@ -27,7 +28,19 @@ pub struct LogServer {
 impl LogServer {
     /// 创建新的日志服务器
     pub fn new(read_fd: usize) -> Self {
-        let transport = Box::new(PipeTransport::new_server(read_fd));
+        let transport = Box::new(PipeTransport::new_server(
+            read_fd,
+            syscall_read,
+            syscall_poll,
+        ));
+        Self {
+            transport,
+            running: false,
+        }
+    }
+
+    /// 使用指定的传输创建日志服务器，例如 [`IpcTransport`](crate::log::IpcTransport)
+    pub fn with_transport(transport: Box<dyn LogTransport>) -> Self {
         Self {
             transport,
             running: false,
@@ -48,6 +61,12 @@ impl LogServer {
     /// 主运行循环
     fn run_loop(&mut self) -> ServerResult<()> {
         while self.running {
+            // 阻塞到管道可读，而不是反复调用 try_receive 空转
+            match self.transport.wait_readable() {
+                Ok(true) => {}
+                Ok(false) => break, // 客户端全部关闭
+                Err(_) => return Err(ServerError::TransportError),
+            }
             match self.process_messages() {
                 Ok(_) => continue,
                 Err(ServerError::TransportError) => {
@@ -180,11 +199,60 @@ pub fn spawn_log_service() -> ServerResult<usize> {
     }
 }
 
+/// `ppoll` 使用的 `struct pollfd`
+#[repr(C)]
+struct PollFd {
+    fd: i32,
+    events: i16,
+    revents: i16,
+}
+
+/// `ppoll` 使用的 `struct timespec`
+#[repr(C)]
+struct TimeSpec {
+    tv_sec: usize,
+    tv_nsec: usize,
+}
+
+fn syscall_read(fd: usize, buffer: &mut [u8]) -> isize {
+    unsafe { sys_read(fd, buffer.as_mut_ptr(), buffer.len()) }
+}
+
+fn syscall_poll(fd: usize, timeout_ms: isize) -> isize {
+    let mut pollfd = PollFd {
+        fd: fd as i32,
+        events: 0x001, // POLLIN
+        revents: 0,
+    };
+    let timeout = TimeSpec {
+        tv_sec: timeout_ms.max(0) as usize / 1000,
+        tv_nsec: timeout_ms.max(0) as usize % 1000 * 1_000_000,
+    };
+    let timeout_ptr = if timeout_ms < 0 {
+        core::ptr::null()
+    } else {
+        &timeout as *const TimeSpec
+    };
+    let ready = unsafe { sys_ppoll(&mut pollfd, 1, timeout_ptr, core::ptr::null()) };
+    if ready < 0 {
+        ready
+    } else {
+        pollfd.revents as isize
+    }
+}
+
 // 系统调用声明
-extern "C" {
+unsafe extern "C" {
     fn sys_pipe(pipe_fd: *mut usize) -> isize;
     fn sys_fork() -> isize;
     fn sys_close(fd: usize) -> isize;
     fn sys_exit(exit_code: i32) -> !;
     fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize;
+    fn sys_read(fd: usize, buf: *mut u8, len: usize) -> isize;
+    fn sys_ppoll(
+        fds: *mut PollFd,
+        nfds: usize,
+        timeout: *const TimeSpec,
+        sigmask: *const u64,
+    ) -> isize;
 }
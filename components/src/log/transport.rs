@@ -1,9 +1,14 @@
 //! # 日志传输层
 //!
-//! 提供基于管道的IPC传输机制。
+//! 提供基于管道与基于同步 IPC 端点的两种传输机制。
+//!
+//! 管道传输的服务器端通过 `ppoll` 等待管道可读，没有消息时阻塞而不是轮询；
+//! IPC 传输的服务器端直接阻塞在端点的接收上。
 
 use crate::log::message::LogMessage;
+use alloc::boxed::Box;
 use alloc::vec::Vec;
+use spin::Mutex;
 
 /// 传输层错误类型
 #[derive(Debug)]
@@ -27,11 +32,24 @@ pub trait LogTransport {
     fn receive(&self) -> TransportResult<LogMessage>;
     /// 尝试非阻塞接收
     fn try_receive(&self) -> TransportResult<Option<LogMessage>>;
+    /// 阻塞直到有消息可接收
+    ///
+    /// 返回 `Ok(false)` 表示发送端已全部关闭，不会再有新消息。
+    /// 默认实现立即返回，适用于无法等待的传输。
+    fn wait_readable(&self) -> TransportResult<bool> {
+        Ok(true)
+    }
 }
 
 /// 系统调用函数类型
 pub type SyscallWriteFn = fn(fd: usize, buf: &[u8]) -> isize;
 pub type SyscallReadFn = fn(fd: usize, buf: &mut [u8]) -> isize;
+/// 等待 `fd` 可读：`timeout_ms` 为负数时一直等待；
+/// 返回就绪事件（`revents`），0 表示超时，负数表示错误
+pub type SyscallPollFn = fn(fd: usize, timeout_ms: isize) -> isize;
+
+/// 有数据可读
+const POLLIN: isize = 0x001;
 
 /// 基于管道的传输实现
 pub struct PipeTransport {
@@ -42,6 +60,7 @@ pub struct PipeTransport {
     /// 系统调用函数指针
     sys_write: Option<SyscallWriteFn>,
     sys_read: Option<SyscallReadFn>,
+    sys_poll: Option<SyscallPollFn>,
 }
 
 impl PipeTransport {
@@ -52,16 +71,30 @@ impl PipeTransport {
             read_fd: None,
             sys_write: Some(sys_write),
             sys_read: None,
+            sys_poll: None,
         }
     }
 
     /// 创建新的管道传输（服务器模式）
-    pub fn new_server(read_fd: usize, sys_read: SyscallReadFn) -> Self {
+    pub fn new_server(read_fd: usize, sys_read: SyscallReadFn, sys_poll: SyscallPollFn) -> Self {
         Self {
             write_fd: None,
             read_fd: Some(read_fd),
             sys_write: None,
             sys_read: Some(sys_read),
+            sys_poll: Some(sys_poll),
+        }
+    }
+
+    /// 等待读端就绪，返回就绪事件
+    fn poll_read(&self, timeout_ms: isize) -> TransportResult<isize> {
+        let fd = self.read_fd.ok_or(TransportError::NotConnected)?;
+        let sys_poll = self.sys_poll.ok_or(TransportError::NotConnected)?;
+        let revents = sys_poll(fd, timeout_ms);
+        if revents < 0 {
+            Err(TransportError::ReadFailed)
+        } else {
+            Ok(revents)
         }
     }
 
@@ -129,13 +162,183 @@ impl LogTransport for PipeTransport {
     }
 
     fn try_receive(&self) -> TransportResult<Option<LogMessage>> {
-        // 简化实现 - 实际需要非阻塞读取
+        // 没有数据时不进入会阻塞的 read
+        if self.sys_poll.is_some() && self.poll_read(0)? & POLLIN == 0 {
+            return Ok(None);
+        }
         match self.receive() {
             Ok(msg) => Ok(Some(msg)),
             Err(TransportError::ReadFailed) => Ok(None),
             Err(e) => Err(e),
         }
     }
+
+    fn wait_readable(&self) -> TransportResult<bool> {
+        // 只有挂断而没有数据时，写端已全部关闭
+        Ok(self.poll_read(-1)? & POLLIN != 0)
+    }
+}
+
+/// 发送 IPC 消息：`data` 为空时只发送消息字，否则按页转移，`data` 的起始地址按页对齐，
+/// 发送后其所在的页被移交给接收方；返回 0 或负的错误码
+pub type SyscallIpcSendFn = fn(handle: usize, words: &[usize; 4], data: &mut [u8]) -> isize;
+/// 接收 IPC 消息：`buf` 的起始地址按页对齐，`nonblock` 时没有消息立即返回 `-EAGAIN`；
+/// 返回发送方 PID 或负的错误码，按页收到的数据放在 `buf` 的开头
+pub type SyscallIpcRecvFn =
+    fn(handle: usize, words: &mut [usize; 4], buf: &mut [u8], nonblock: bool) -> isize;
+
+/// 没有消息（`EAGAIN`）
+const EAGAIN: isize = 11;
+/// 端点没有其他句柄（`EPIPE`）
+const EPIPE: isize = 32;
+
+/// IPC 按页转移的页大小
+const IPC_PAGE_SIZE: usize = 4096;
+/// 能放进消息字（`words[1..4]`）的最大消息长度
+const IPC_INLINE_LEN: usize = 3 * core::mem::size_of::<usize>();
+
+/// 按页对齐的 IPC 数据页
+#[repr(C, align(4096))]
+struct IpcPage([u8; IPC_PAGE_SIZE]);
+
+/// 基于同步 IPC 端点的传输实现
+///
+/// `words[0]` 是序列化后的消息长度：不超过 [`IPC_INLINE_LEN`] 字节的消息直接放在
+/// `words[1..4]` 中，更长的消息按页转移，最长一页。
+pub struct IpcTransport {
+    /// 端点句柄
+    handle: usize,
+    /// 系统调用函数指针
+    sys_send: Option<SyscallIpcSendFn>,
+    sys_recv: Option<SyscallIpcRecvFn>,
+    /// 收发按页转移数据的缓冲区
+    page: Mutex<Box<IpcPage>>,
+    /// `wait_readable` 等到的消息，下一次接收时返回
+    pending: Mutex<Option<LogMessage>>,
+}
+
+impl IpcTransport {
+    /// 创建新的 IPC 传输（客户端模式）
+    pub fn new_client(handle: usize, sys_send: SyscallIpcSendFn) -> Self {
+        Self::new(handle, Some(sys_send), None)
+    }
+
+    /// 创建新的 IPC 传输（服务器模式）
+    pub fn new_server(handle: usize, sys_recv: SyscallIpcRecvFn) -> Self {
+        Self::new(handle, None, Some(sys_recv))
+    }
+
+    fn new(
+        handle: usize,
+        sys_send: Option<SyscallIpcSendFn>,
+        sys_recv: Option<SyscallIpcRecvFn>,
+    ) -> Self {
+        Self {
+            handle,
+            sys_send,
+            sys_recv,
+            page: Mutex::new(Box::new(IpcPage([0; IPC_PAGE_SIZE]))),
+            pending: Mutex::new(None),
+        }
+    }
+
+    /// 从端点接收一条消息
+    ///
+    /// 没有消息（非阻塞）时返回 `Ok(None)`，端点的发送方全部关闭时返回
+    /// [`TransportError::NotConnected`]。
+    fn recv_message(&self, nonblock: bool) -> TransportResult<Option<LogMessage>> {
+        let sys_recv = self.sys_recv.ok_or(TransportError::NotConnected)?;
+        let mut words = [0usize; 4];
+        let mut page = self.page.lock();
+        match sys_recv(self.handle, &mut words, &mut page.0, nonblock) {
+            ret if ret >= 0 => {}
+            ret if ret == -EAGAIN => return Ok(None),
+            ret if ret == -EPIPE => return Err(TransportError::NotConnected),
+            _ => return Err(TransportError::ReadFailed),
+        }
+        let len = words[0];
+        let message = if len <= IPC_INLINE_LEN {
+            let mut inline = [0u8; IPC_INLINE_LEN];
+            for (chunk, word) in inline
+                .chunks_mut(core::mem::size_of::<usize>())
+                .zip(&words[1..])
+            {
+                chunk.copy_from_slice(&word.to_le_bytes());
+            }
+            LogMessage::deserialize(&inline[..len])
+        } else if len <= IPC_PAGE_SIZE {
+            LogMessage::deserialize(&page.0[..len])
+        } else {
+            None
+        };
+        message.map(Some).ok_or(TransportError::ReadFailed)
+    }
+}
+
+impl LogTransport for IpcTransport {
+    fn send(&self, message: &LogMessage) -> TransportResult<()> {
+        let sys_send = self.sys_send.ok_or(TransportError::NotConnected)?;
+        let data = message.serialize();
+        let mut words = [data.len(), 0, 0, 0];
+        let ret = if data.len() <= IPC_INLINE_LEN {
+            let mut inline = [0u8; IPC_INLINE_LEN];
+            inline[..data.len()].copy_from_slice(&data);
+            for (word, chunk) in words[1..]
+                .iter_mut()
+                .zip(inline.chunks(core::mem::size_of::<usize>()))
+            {
+                *word = usize::from_le_bytes(chunk.try_into().unwrap());
+            }
+            sys_send(self.handle, &words, &mut [])
+        } else if data.len() <= IPC_PAGE_SIZE {
+            let mut page = self.page.lock();
+            page.0[..data.len()].copy_from_slice(&data);
+            sys_send(self.handle, &words, &mut page.0[..data.len()])
+        } else {
+            return Err(TransportError::WriteFailed);
+        };
+        match ret {
+            0 => Ok(()),
+            ret if ret == -EPIPE => Err(TransportError::NotConnected),
+            _ => Err(TransportError::WriteFailed),
+        }
+    }
+
+    fn receive(&self) -> TransportResult<LogMessage> {
+        if let Some(message) = self.pending.lock().take() {
+            return Ok(message);
+        }
+        loop {
+            if let Some(message) = self.recv_message(false)? {
+                return Ok(message);
+            }
+        }
+    }
+
+    fn try_receive(&self) -> TransportResult<Option<LogMessage>> {
+        if let Some(message) = self.pending.lock().take() {
+            return Ok(Some(message));
+        }
+        match self.recv_message(true) {
+            Err(TransportError::NotConnected) => Ok(None),
+            result => result,
+        }
+    }
+
+    fn wait_readable(&self) -> TransportResult<bool> {
+        // 端点无法只等待而不取走消息，等到的消息暂存到下一次接收
+        if self.pending.lock().is_some() {
+            return Ok(true);
+        }
+        match self.recv_message(false) {
+            Ok(message) => {
+                *self.pending.lock() = message;
+                Ok(true)
+            }
+            Err(TransportError::NotConnected) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
 }
 
 // 为了与系统调用交互，我们需要定义系统调用接口
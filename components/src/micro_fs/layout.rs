@@ -162,13 +162,17 @@ pub struct DiskInode {
 
 /// 磁盘 inode 类型
 ///
-/// 标识 inode 对应的文件类型，用于区分普通文件和目录。
+/// 标识 inode 对应的文件类型，用于区分普通文件、目录、命名管道和套接字。
 #[derive(PartialEq)]
 pub enum DiskInodeType {
     /// 普通文件
     File,
     /// 目录文件
     Dir,
+    /// 命名管道（FIFO），不占用数据块，数据只存在于内核的管道缓冲区中
+    Fifo,
+    /// Unix 域套接字的绑定地址，不占用数据块
+    Socket,
 }
 
 impl SuperBlock {
@@ -220,7 +224,7 @@ impl DiskInode {
     /// 将 inode 重置为初始状态，清空所有数据块引用并设置文件类型。
     ///
     /// ## Arguments
-    /// * `type_` - 文件类型（文件或目录）
+    /// * `type_` - 文件类型（文件、目录、命名管道或套接字）
     pub fn initialize(&mut self, type_: DiskInodeType) {
         self.size = 0;
         self.direct.iter_mut().for_each(|v| *v = 0);
@@ -246,6 +250,22 @@ impl DiskInode {
         self.type_ == DiskInodeType::File
     }
 
+    /// 检查是否为命名管道
+    ///
+    /// ## Returns
+    /// 如果是命名管道返回 `true`，否则返回 `false`
+    pub fn fifo(&self) -> bool {
+        self.type_ == DiskInodeType::Fifo
+    }
+
+    /// 检查是否为套接字
+    ///
+    /// ## Returns
+    /// 如果是套接字返回 `true`，否则返回 `false`
+    pub fn socket(&self) -> bool {
+        self.type_ == DiskInodeType::Socket
+    }
+
     /// 获取指定逻辑块号对应的物理块 ID
     ///
     /// 根据三级索引结构查找逻辑块号对应的物理块 ID。
@@ -235,6 +235,22 @@ impl BlockManager {
         )
     }
 
+    /// 根据磁盘 inode 的位置反求 inode ID
+    ///
+    /// [`disk_inode_pos`](Self::disk_inode_pos) 的逆运算。
+    ///
+    /// ## Arguments
+    /// * `block_id` - inode 所在的块号
+    /// * `offset` - inode 在块内的偏移量
+    ///
+    /// ## Returns
+    /// 该位置上 inode 的 ID
+    pub fn inode_id(&self, block_id: u32, offset: usize) -> u32 {
+        let inode_size = core::mem::size_of::<DiskInode>();
+        let inodes_per_block = (BLOCK_SZ / inode_size) as u32;
+        (block_id - self.inode_area_start_block) * inodes_per_block + (offset / inode_size) as u32
+    }
+
     /// 获取数据块在磁盘上的实际块号
     ///
     /// 将逻辑数据块 ID 转换为在块设备上的实际块号。
@@ -236,7 +236,7 @@ impl Inode {
             let inode_id = block_cache(block_id as usize, self.block_device.clone())
                 .lock()
                 .read(block_offset, |disk_inode: &DiskInode| {
-                    if disk_inode.file() {
+                    if !disk_inode.dir() {
                         return None;
                     }
                     self.find_inode_id(name, disk_inode)
@@ -330,7 +330,7 @@ impl Inode {
         self.read_disk_inode(|disk_inode| {
             let file_count = (disk_inode.size as usize) / DIRENT_SZ;
             let mut v: Vec<String> = Vec::new();
-            if disk_inode.file() {
+            if !disk_inode.dir() {
                 return v;
             }
             for i in 0..file_count {
@@ -431,6 +431,35 @@ impl Inode {
         self.create_inode(name, DiskInodeType::Dir)
     }
 
+    /// 创建命名管道，文件已存在时返回 `None`
+    pub fn create_fifo(&self, name: &str) -> Option<Arc<Inode>> {
+        self.create_inode(name, DiskInodeType::Fifo)
+    }
+
+    /// 创建套接字文件，文件已存在时返回 `None`
+    pub fn create_socket(&self, name: &str) -> Option<Arc<Inode>> {
+        self.create_inode(name, DiskInodeType::Socket)
+    }
+
+    /// 是否为命名管道
+    pub fn is_fifo(&self) -> bool {
+        let _fs = self.fs.lock();
+        self.read_disk_inode(|disk_inode| disk_inode.fifo())
+    }
+
+    /// 是否为套接字
+    pub fn is_socket(&self) -> bool {
+        let _fs = self.fs.lock();
+        self.read_disk_inode(|disk_inode| disk_inode.socket())
+    }
+
+    /// inode 编号，在文件系统内唯一标识该文件
+    pub fn inode_number(&self) -> u32 {
+        self.fs
+            .lock()
+            .inode_id(self.block_id as u32, self.block_offset)
+    }
+
     /// 扩展文件大小
     ///
     /// 将文件扩展到指定大小，并分配必要的数据块。
@@ -509,6 +538,16 @@ impl Inode {
         block_cache_sync_all();
     }
 
+    /// 获取文件大小
+    ///
+    /// ## Returns
+    ///
+    /// 文件当前的字节数
+    pub fn size(&self) -> usize {
+        let _fs = self.fs.lock();
+        self.read_disk_inode(|disk_inode| disk_inode.size as usize)
+    }
+
     /// 从指定偏移量读取文件数据
     ///
     /// 从文件的指定偏移量开始读取数据到缓冲区中。
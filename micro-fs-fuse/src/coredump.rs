@@ -0,0 +1,307 @@
+//! Reader for the ELF core files the kernel writes as `core.<pid>`.
+//!
+//! The kernel emits one `PT_NOTE` segment holding an `NT_PRSTATUS` record
+//! (Linux riscv64 `elf_prstatus` layout, registers `pc, x1..x31`) followed by
+//! one `PT_LOAD` segment per user memory area.
+
+use std::fmt;
+
+const ET_CORE: u16 = 4;
+const EM_RISCV: u16 = 243;
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const NT_PRSTATUS: u32 = 1;
+const PF_X: u32 = 1;
+const PF_W: u32 = 2;
+const PF_R: u32 = 4;
+
+const EHDR_SIZE: usize = 64;
+const PHENT_SIZE: usize = 56;
+const PRSTATUS_SIZE: usize = 376;
+const PRSTATUS_REG_OFFSET: usize = 112;
+
+const REG_NAMES: [&str; 32] = [
+    "pc", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4", "a5",
+    "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4", "t5",
+    "t6",
+];
+
+/// A `PT_LOAD` segment: one user memory area of the crashed process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub vaddr: u64,
+    pub offset: u64,
+    pub size: u64,
+    pub flags: u32,
+}
+
+/// The parts of a core file worth showing to a human.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreDump {
+    pub pid: u32,
+    pub ppid: u32,
+    pub signal: u32,
+    /// `pc` followed by `x1..x31`
+    pub regs: [u64; 32],
+    pub segments: Vec<Segment>,
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        data.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+fn signal_name(signal: u32) -> &'static str {
+    match signal {
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        5 => "SIGTRAP",
+        6 => "SIGABRT",
+        7 => "SIGBUS",
+        8 => "SIGFPE",
+        11 => "SIGSEGV",
+        24 => "SIGXCPU",
+        25 => "SIGXFSZ",
+        31 => "SIGSYS",
+        _ => "unknown signal",
+    }
+}
+
+impl CoreDump {
+    /// Parse a core file, rejecting anything that is not a 64-bit RISC-V core.
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        let malformed = || String::from("truncated core file");
+        if data.len() < EHDR_SIZE || data[..4] != [0x7f, b'E', b'L', b'F'] {
+            return Err(String::from("not an ELF file"));
+        }
+        if data[4] != 2 || data[5] != 1 {
+            return Err(String::from("not a 64-bit little-endian ELF file"));
+        }
+        if read_u16(data, 16) != Some(ET_CORE) || read_u16(data, 18) != Some(EM_RISCV) {
+            return Err(String::from("not a RISC-V core file"));
+        }
+        let phoff = read_u64(data, 32).ok_or_else(malformed)? as usize;
+        let phentsize = read_u16(data, 54).ok_or_else(malformed)? as usize;
+        let phnum = read_u16(data, 56).ok_or_else(malformed)? as usize;
+        if phentsize != PHENT_SIZE {
+            return Err(format!("unexpected program header size {}", phentsize));
+        }
+
+        let mut prstatus = None;
+        let mut segments = Vec::new();
+        for idx in 0..phnum {
+            let ph = phoff + idx * PHENT_SIZE;
+            let p_type = read_u32(data, ph).ok_or_else(malformed)?;
+            let offset = read_u64(data, ph + 8).ok_or_else(malformed)?;
+            let filesz = read_u64(data, ph + 32).ok_or_else(malformed)?;
+            let end = offset.checked_add(filesz).ok_or_else(malformed)?;
+            if end > data.len() as u64 {
+                return Err(malformed());
+            }
+            match p_type {
+                PT_NOTE => {
+                    let notes = &data[offset as usize..end as usize];
+                    prstatus = find_note(notes, NT_PRSTATUS).or(prstatus);
+                }
+                PT_LOAD => segments.push(Segment {
+                    vaddr: read_u64(data, ph + 16).ok_or_else(malformed)?,
+                    offset,
+                    size: filesz,
+                    flags: read_u32(data, ph + 4).ok_or_else(malformed)?,
+                }),
+                _ => {}
+            }
+        }
+
+        let prstatus = prstatus.ok_or_else(|| String::from("no NT_PRSTATUS note"))?;
+        if prstatus.len() < PRSTATUS_SIZE {
+            return Err(String::from("NT_PRSTATUS note is too short"));
+        }
+        let mut regs = [0u64; 32];
+        for (idx, reg) in regs.iter_mut().enumerate() {
+            *reg = read_u64(prstatus, PRSTATUS_REG_OFFSET + idx * 8).ok_or_else(malformed)?;
+        }
+        Ok(Self {
+            pid: read_u32(prstatus, 32).ok_or_else(malformed)?,
+            ppid: read_u32(prstatus, 36).ok_or_else(malformed)?,
+            signal: read_u16(prstatus, 12).ok_or_else(malformed)? as u32,
+            regs,
+            segments,
+        })
+    }
+
+    /// The bytes at `vaddr..vaddr + len` in the crashed process, if they were dumped.
+    pub fn memory<'a>(&self, data: &'a [u8], vaddr: u64, len: usize) -> Option<&'a [u8]> {
+        let segment = self
+            .segments
+            .iter()
+            .find(|seg| seg.vaddr <= vaddr && vaddr + len as u64 <= seg.vaddr + seg.size)?;
+        let start = (segment.offset + vaddr - segment.vaddr) as usize;
+        data.get(start..start + len)
+    }
+}
+
+/// Find the descriptor of the first note of type `n_type` owned by `CORE`.
+fn find_note(mut notes: &[u8], n_type: u32) -> Option<&[u8]> {
+    while notes.len() >= 12 {
+        let namesz = read_u32(notes, 0)? as usize;
+        let descsz = read_u32(notes, 4)? as usize;
+        let kind = read_u32(notes, 8)?;
+        let desc_start = 12 + namesz.next_multiple_of(4);
+        let desc_end = desc_start + descsz;
+        let name = notes.get(12..12 + namesz)?;
+        let desc = notes.get(desc_start..desc_end)?;
+        if kind == n_type && name.strip_suffix(b"\0") == Some(b"CORE") {
+            return Some(desc);
+        }
+        notes = notes.get(desc_end.next_multiple_of(4)..)?;
+    }
+    None
+}
+
+impl fmt::Display for CoreDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "pid {} (parent {}) killed by signal {} ({})",
+            self.pid,
+            self.ppid,
+            self.signal,
+            signal_name(self.signal)
+        )?;
+        for (idx, (name, value)) in REG_NAMES.iter().zip(self.regs.iter()).enumerate() {
+            write!(f, "{:>4} {:#018x}", name, value)?;
+            if idx % 4 == 3 {
+                writeln!(f)?;
+            } else {
+                write!(f, "  ")?;
+            }
+        }
+        writeln!(f, "segments:")?;
+        for seg in &self.segments {
+            let perm = |flag, c| if seg.flags & flag != 0 { c } else { '-' };
+            writeln!(
+                f,
+                "  {:#010x}-{:#010x} {}{}{} at file offset {:#x}",
+                seg.vaddr,
+                seg.vaddr + seg.size,
+                perm(PF_R, 'r'),
+                perm(PF_W, 'w'),
+                perm(PF_X, 'x'),
+                seg.offset
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn put(out: &mut Vec<u8>, value: u64, size: usize) {
+    out.extend_from_slice(&value.to_le_bytes()[..size]);
+}
+
+/// Build a core file the way the kernel lays it out: one note, one 4 KiB `rw-` segment.
+#[cfg(test)]
+fn sample_core(signal: u64, pc: u64, page: &[u8; 4096]) -> Vec<u8> {
+    let mut out = vec![0x7f, b'E', b'L', b'F', 2, 1, 1, 0];
+    out.resize(16, 0);
+    put(&mut out, ET_CORE as u64, 2);
+    put(&mut out, EM_RISCV as u64, 2);
+    put(&mut out, 1, 4);
+    put(&mut out, 0, 8);
+    put(&mut out, EHDR_SIZE as u64, 8);
+    out.resize(52, 0);
+    put(&mut out, EHDR_SIZE as u64, 2);
+    put(&mut out, PHENT_SIZE as u64, 2);
+    put(&mut out, 2, 2);
+    put(&mut out, 0, 6);
+    let note_offset = EHDR_SIZE + 2 * PHENT_SIZE;
+    let note_size = 20 + PRSTATUS_SIZE;
+    for (p_type, flags, offset, vaddr, size) in [
+        (PT_NOTE, 0, note_offset, 0, note_size),
+        (PT_LOAD, PF_R | PF_W, 4096, 0x20000, 4096),
+    ] {
+        put(&mut out, p_type as u64, 4);
+        put(&mut out, flags as u64, 4);
+        put(&mut out, offset as u64, 8);
+        put(&mut out, vaddr, 8);
+        put(&mut out, 0, 8);
+        put(&mut out, size as u64, 8);
+        put(&mut out, size as u64, 8);
+        put(&mut out, 4096, 8);
+    }
+    put(&mut out, 5, 4);
+    put(&mut out, PRSTATUS_SIZE as u64, 4);
+    put(&mut out, NT_PRSTATUS as u64, 4);
+    out.extend_from_slice(b"CORE\0\0\0\0");
+    let desc = out.len();
+    put(&mut out, signal, 4);
+    put(&mut out, 0, 8);
+    put(&mut out, signal, 2);
+    out.resize(desc + 32, 0);
+    put(&mut out, 7, 4);
+    put(&mut out, 1, 4);
+    out.resize(desc + PRSTATUS_REG_OFFSET, 0);
+    put(&mut out, pc, 8);
+    for idx in 1..32 {
+        put(&mut out, 0x100 + idx, 8);
+    }
+    out.resize(4096, 0);
+    out.extend_from_slice(page);
+    out
+}
+
+#[test]
+fn core_parse_test() {
+    let mut page = [0u8; 4096];
+    page[16..21].copy_from_slice(b"hello");
+    let data = sample_core(11, 0x10abc, &page);
+    let core = CoreDump::parse(&data).unwrap();
+    assert_eq!((core.pid, core.ppid, core.signal), (7, 1, 11));
+    assert_eq!(core.regs[0], 0x10abc);
+    assert_eq!(core.regs[2], 0x102);
+    assert_eq!(
+        core.segments,
+        vec![Segment {
+            vaddr: 0x20000,
+            offset: 4096,
+            size: 4096,
+            flags: PF_R | PF_W,
+        }]
+    );
+    assert_eq!(core.memory(&data, 0x20010, 5), Some(&b"hello"[..]));
+    assert_eq!(core.memory(&data, 0x20ffc, 8), None);
+    let summary = core.to_string();
+    assert!(summary.starts_with("pid 7 (parent 1) killed by signal 11 (SIGSEGV)\n"));
+    assert!(summary.contains("  0x00020000-0x00021000 rw- at file offset 0x1000\n"));
+}
+
+#[test]
+fn core_reject_test() {
+    let data = sample_core(4, 0, &[0; 4096]);
+    assert!(CoreDump::parse(&data[..40]).is_err());
+    assert!(CoreDump::parse(&data[..4200]).is_err());
+    let mut exec = data.clone();
+    exec[16] = 2;
+    assert_eq!(
+        CoreDump::parse(&exec),
+        Err(String::from("not a RISC-V core file"))
+    );
+    assert_eq!(
+        CoreDump::parse(b"#!/bin/sh\n"),
+        Err(String::from("not an ELF file"))
+    );
+}
@@ -1,5 +1,8 @@
-use clap::{App, Arg};
-use components::micro_fs::{BlockDevice, Inode, BlockManager};
+mod coredump;
+
+use clap::{App, Arg, ArgMatches};
+use components::micro_fs::{BlockDevice, BlockManager, Inode};
+use coredump::CoreDump;
 use std::fs::{File, OpenOptions, read_dir};
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::sync::Arc;
@@ -26,10 +29,6 @@ impl BlockDevice for BlockFile {
 }
 
 fn main() {
-    micro_fs_pack().expect("Error when packing micro-fs!");
-}
-
-fn micro_fs_pack() -> std::io::Result<()> {
     let matches = App::new("BlockManager packer")
         .arg(
             Arg::with_name("source")
@@ -45,7 +44,63 @@ fn micro_fs_pack() -> std::io::Result<()> {
                 .takes_value(true)
                 .help("Executable target dir(with backslash)"),
         )
+        .arg(
+            Arg::with_name("extract")
+                .short("x")
+                .long("extract")
+                .takes_value(true)
+                .help("Copy a file out of fs.img instead of packing, e.g. core.<pid>"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .requires("extract")
+                .help("Where to write the extracted file (default: ./<name>)"),
+        )
         .get_matches();
+    if matches.is_present("extract") {
+        micro_fs_extract(&matches).expect("Error when extracting from micro-fs!");
+    } else {
+        micro_fs_pack(&matches).expect("Error when packing micro-fs!");
+    }
+}
+
+fn micro_fs_extract(matches: &ArgMatches) -> std::io::Result<()> {
+    let target_path = matches.value_of("target").unwrap();
+    let name = matches.value_of("extract").unwrap();
+    let output = matches.value_of("output").unwrap_or(name);
+    let block_file = Arc::new(BlockFile(Mutex::new(
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(format!("{}{}", target_path, "fs.img"))?,
+    )));
+    let mfs = BlockManager::open(block_file);
+    let root_inode = BlockManager::root_inode(&mfs);
+    let inode = root_inode.find(name).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("{} not in fs.img", name),
+        )
+    })?;
+    let mut all_data = vec![0u8; inode.size()];
+    let len = inode.read_at(0, &mut all_data);
+    all_data.truncate(len);
+    File::create(output)?.write_all(&all_data)?;
+    println!("{} -> {} ({} bytes)", name, output, len);
+    // core files get a summary of why and where the process died
+    if let Ok(core) = CoreDump::parse(&all_data) {
+        print!("{}", core);
+        if let Some(inst) = core.memory(&all_data, core.regs[0], 4) {
+            println!("instruction at pc: {:02x?}", inst);
+        }
+    }
+    Ok(())
+}
+
+fn micro_fs_pack(matches: &ArgMatches) -> std::io::Result<()> {
     let src_path = matches.value_of("source").unwrap();
     let target_path = matches.value_of("target").unwrap();
     println!("src_path = {}\ntarget_path = {}", src_path, target_path);
@@ -5,7 +5,22 @@ pub const MEMORY_END: usize = 0x8100_0000;
 
 pub const MMIO: &[(usize, usize)] = &[
     (0x0010_0000, 0x00_2000), // VIRT_TEST/RTC in virt machine
+    (0x0C00_0000, 0x21_0000), // VIRT_PLIC in virt machine
+    (0x1000_0000, 0x00_1000), // UART0 (ns16550a) in virt machine
     (0x1000_1000, 0x00_1000), // Virtio Block in virt machine
+    (0x1000_2000, 0x00_1000), // Virtio Net in virt machine
 ];
 
+pub const VIRT_PLIC: usize = 0x0C00_0000;
+pub const VIRT_UART: usize = 0x1000_0000;
+
+/// virtio-mmio 设备 0（块设备）的 PLIC 中断源编号
+pub const VIRTIO0_IRQ: usize = 1;
+/// virtio-mmio 设备 1（网卡）的 PLIC 中断源编号
+pub const VIRTIO1_IRQ: usize = 2;
+/// UART0 的 PLIC 中断源编号
+pub const UART0_IRQ: usize = 10;
+
 pub type BlockDeviceImpl = crate::drivers::block::VirtIOBlock;
+pub type NetDeviceImpl = crate::drivers::net::VirtIONetDevice;
+pub type CharDeviceImpl = crate::drivers::serial::NS16550a;
@@ -9,6 +9,38 @@
 /// 局部变量和函数参数。
 pub const USER_STACK_SIZE: usize = 4096 * 2;
 
+/// 用户栈大小上限 (256KB)
+///
+/// `RLIMIT_STACK` 的默认硬限制。用户栈在 `exec` 时一次性分配物理页帧，
+/// 因此即使软限制被设为无限，实际栈大小也不会超过该值。
+pub const USER_STACK_SIZE_MAX: usize = 4096 * 64;
+
+/// 用户地址空间上界
+///
+/// SV39 下用户态可用的低半部分虚拟地址（256GB）。ELF 段、保护页与
+/// 最大用户栈都必须位于该地址之下。
+pub const USER_SPACE_END: usize = 0x40_0000_0000;
+
+/// 位置无关可执行文件（ET_DYN）的加载基址
+///
+/// 与 Linux 的 `ELF_ET_DYN_BASE` 作用相同，远离常规可执行文件的
+/// `0x10000` 起始地址，避免 PIE 与空指针附近的低地址重叠。
+pub const ELF_DYN_BASE: usize = 0x1000_0000;
+
+/// 共享内存段的默认附加地址
+///
+/// `shmat` 未指定地址时从这里向上寻找第一段空闲的虚拟地址，远离程序段、
+/// 用户栈与堆。
+pub const SHM_BASE: usize = 0x20_0000_0000;
+
+/// 单个共享内存段的最大字节数 (4MB)
+///
+/// 段的页帧在 `shmget` 时一次性分配，过大的段会很快耗尽物理内存。
+pub const SHMMAX: usize = 0x40_0000;
+
+/// 系统中共享内存段数量的上限
+pub const SHMMNI: usize = 64;
+
 /// 内核栈大小 (8KB)
 ///
 /// 每个进程在内核态执行时使用的栈大小，用于处理系统调用、
@@ -90,3 +122,18 @@ pub const MLFQ_QUEUE_COUNT: usize = 4;
 /// 最高优先级队列的时间片长度，低优先级队列的时间片
 /// 会按倍数递增，保证响应性和吞吐量的平衡。
 pub const MLFQ_BASE_TIME_SLICE: usize = CLOCK_FREQ / 100; // 10ms
+
+/// 默认可打开的文件描述符数
+///
+/// 初始进程 `RLIMIT_NOFILE` 的软限制，子进程通过 fork 继承。
+pub const DEFAULT_RLIMIT_NOFILE: usize = 256;
+
+/// 可打开的文件描述符数上限
+///
+/// 初始进程 `RLIMIT_NOFILE` 的硬限制。
+pub const MAX_RLIMIT_NOFILE: usize = 1024;
+
+/// 默认可同时存活的进程数
+///
+/// 初始进程 `RLIMIT_NPROC` 的软硬限制，用于阻止 fork 炸弹耗尽内存。
+pub const DEFAULT_RLIMIT_NPROC: usize = 128;
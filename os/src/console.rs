@@ -1,12 +1,13 @@
 //! # 控制台输出模块
 //!
 //! 提供格式化文本输出功能，实现类似标准库的 `print!` 和 `println!` 宏。
-//! 通过 SBI 接口与底层硬件交互，将文本输出到控制台。
+//! 串口驱动初始化之前通过 SBI 接口输出，之后改由 [`SERIAL`] 输出。
 //!
 //! ## 功能特性
 //!
 //! - **格式化输出**: 支持 Rust 标准的格式化字符串语法
-//! - **SBI 集成**: 通过 SBI 调用实现跨平台控制台输出
+//! - **SBI 回退**: 内存管理初始化之前通过 SBI 调用输出
+//! - **串口输出**: 经串口发送缓冲区写出，每次打印结束前同步排空
 //! - **宏接口**: 提供便捷的 `print!` 和 `println!` 宏
 //! - **UTF-8 支持**: 完全支持 Unicode 字符输出
 //!
@@ -18,20 +19,21 @@
 //! println!("Debug info: {:?}", some_struct);
 //! ```
 
+use crate::drivers::{SERIAL, serial};
 use crate::sbi::console_putchar;
 use core::fmt::{self, Write};
 
 /// 标准输出结构体
 ///
-/// 实现了 `Write` trait，将格式化的文本通过 SBI 接口输出到控制台。
+/// 实现了 `Write` trait，将格式化的文本输出到控制台。
 /// 这是一个零大小类型 (ZST)，不占用内存空间。
 struct Stdout;
 
 impl Write for Stdout {
     /// 将字符串写入标准输出
     ///
-    /// 遍历字符串中的每个字符，通过 SBI 的 `console_putchar` 函数
-    /// 逐个输出到控制台。
+    /// 串口就绪时写入串口发送缓冲区，否则通过 SBI 的 `console_putchar`
+    /// 逐字节输出到控制台。
     ///
     /// ## Arguments
     ///
@@ -39,18 +41,34 @@ impl Write for Stdout {
     ///
     /// ## Returns
     ///
-    /// 总是返回 `Ok(())`，因为 SBI 调用不会失败
+    /// 总是返回 `Ok(())`，因为串口与 SBI 输出都不会失败
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        for c in s.chars() {
-            console_putchar(c as usize);
-        }
+        write_bytes(s.as_bytes());
         Ok(())
     }
 }
 
+/// 输出原始字节
+///
+/// 串口就绪时把数据放入发送缓冲区，否则通过 SBI 同步输出。
+///
+/// ## Arguments
+///
+/// * `data` - 要输出的字节
+fn write_bytes(data: &[u8]) {
+    if serial::is_ready() {
+        SERIAL.write(data);
+    } else {
+        for &ch in data {
+            console_putchar(ch as usize);
+        }
+    }
+}
+
 /// 格式化输出函数
 ///
 /// 接受格式化参数并输出到控制台，是 `print!` 和 `println!` 宏的底层实现。
+/// 返回前同步排空串口发送缓冲区，保证内核信息在关机或 panic 前完整输出。
 ///
 /// ## Arguments
 ///
@@ -61,6 +79,9 @@ impl Write for Stdout {
 /// 如果格式化或写入过程中发生错误会 panic，但在正常情况下不会发生
 pub fn print(args: fmt::Arguments) {
     Stdout.write_fmt(args).unwrap();
+    if serial::is_ready() {
+        SERIAL.flush();
+    }
 }
 
 /// 格式化打印宏（不换行）
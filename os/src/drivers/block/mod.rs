@@ -56,9 +56,15 @@ use lazy_static::*;
 
 mod virtio_blk;
 
-pub use virtio_blk::VirtIOBlock;
+pub use virtio_blk::{VirtIOBlock, VirtioHal};
 
 lazy_static! {
+    /// 块设备驱动实例
+    ///
+    /// 以具体类型保存，便于把同一实例登记为中断处理者；
+    /// 文件系统通过 [`BLOCK_DEVICE`] 以 trait 对象的形式访问它。
+    pub static ref BLOCK_DRIVER: Arc<BlockDeviceImpl> = Arc::new(BlockDeviceImpl::new());
+
     /// 全局块设备实例
     ///
     /// 使用 `lazy_static` 实现全局单例模式，确保整个系统共享同一个块设备实例。
@@ -66,8 +72,8 @@ lazy_static! {
     ///
     /// ## 初始化过程
     ///
-    /// 1. 创建 `BlockDeviceImpl` 实例（通常是 VirtIO 块设备）
-    /// 2. 包装在 `Arc` 中以支持多线程共享访问
+    /// 1. 取得 [`BLOCK_DRIVER`]（通常是 VirtIO 块设备）
+    /// 2. 转换为 `Arc<dyn BlockDevice>` 供文件系统使用
     /// 3. 注册为全局块设备实例
     ///
     /// ## 使用场景
@@ -94,8 +100,5 @@ lazy_static! {
     /// let data = [0x42u8; 512];
     /// BLOCK_DEVICE.write_block(1, &data);
     /// ```
-    pub static ref BLOCK_DEVICE: Arc<dyn BlockDevice> = {
-        let block_device = Arc::new(BlockDeviceImpl::new());
-        block_device
-    };
+    pub static ref BLOCK_DEVICE: Arc<dyn BlockDevice> = BLOCK_DRIVER.clone();
 }
@@ -16,6 +16,13 @@
 //! - [`VirtioHal`] - 硬件抽象层实现，提供内存管理接口
 //! - [`QUEUE_FRAMES`] - 队列帧管理，用于 DMA 缓冲区
 //!
+//! ## 中断驱动 I/O
+//!
+//! 有当前进程时，读写请求以非阻塞方式提交（`read_block_nb`/`write_block_nb`），
+//! 调用进程在该请求描述符对应的 [`WaitQueue`] 上睡眠，CPU 转而运行其他进程；
+//! 设备完成请求后触发 PLIC 外部中断，[`VirtIOBlock::handle_irq`] 回收已完成的
+//! 描述符并唤醒对应的等待者。内核启动阶段没有可睡眠的进程，仍使用轮询方式。
+//!
 //! ## 内存管理
 //!
 //! - **DMA 分配**: 通过 `dma_alloc` 分配连续的物理页面
@@ -40,14 +47,17 @@
 //! ```
 
 use super::BlockDevice;
+use crate::drivers::IrqHandler;
 use crate::mm::{
     FrameTracker, PageTable, PhysAddr, PhysPageNum, StepByOne, VirtAddr, frame_alloc,
     frame_dealloc, kernel_token,
 };
-use crate::sync::UPSafeCell;
+use crate::process::current_process;
+use crate::sync::{UPSafeCell, WaitQueue};
+use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
 use lazy_static::*;
-use virtio_drivers::{Hal, VirtIOBlk, VirtIOHeader};
+use virtio_drivers::{BlkResp, Hal, RespStatus, VirtIOBlk, VirtIOHeader};
 
 /// VirtIO 块设备在内存映射 I/O 中的基地址
 ///
@@ -63,8 +73,10 @@ const VIRTIO0: usize = 0x10001000;
 ///
 /// ## 内部结构
 ///
-/// 包含一个 `VirtIOBlk` 实例，该实例实现了 VirtIO 块设备的具体功能。
-/// 通过 `UPSafeCell` 提供内部可变性，允许在不可变引用上修改内部状态。
+/// - `virtio_blk`：`VirtIOBlk` 实例，实现 VirtIO 块设备的具体功能
+/// - `wait_queues`：以请求描述符编号（token）为键的等待队列，
+///   每个在途请求的发起者在对应队列上睡眠
+/// - `completed`：中断处理程序已回收、但发起者尚未取走的描述符编号
 ///
 /// ## 线程安全
 ///
@@ -75,7 +87,11 @@ const VIRTIO0: usize = 0x10001000;
 ///
 /// 设备实例的生命周期与系统运行时间相同，在系统启动时初始化，
 /// 在系统关闭时自动清理。
-pub struct VirtIOBlock(UPSafeCell<VirtIOBlk<'static, VirtioHal>>);
+pub struct VirtIOBlock {
+    virtio_blk: UPSafeCell<VirtIOBlk<'static, VirtioHal>>,
+    wait_queues: BTreeMap<u16, WaitQueue>,
+    completed: UPSafeCell<Vec<u16>>,
+}
 
 lazy_static! {
     /// 队列帧管理器
@@ -122,12 +138,29 @@ impl BlockDevice for VirtIOBlock {
     /// ## 性能说明
     ///
     /// 该操作通过 VirtIO 协议进行，支持 DMA 传输，具有较高的性能。
-    /// 读取操作是同步的，会阻塞直到数据传输完成。
+    /// 有当前进程时调用者睡眠直到完成中断到来，期间 CPU 运行其他进程；
+    /// 否则轮询等待传输完成。
     fn read_block(&self, block_id: usize, buf: &mut [u8]) {
-        self.0
-            .exclusive_access()
-            .read_block(block_id, buf)
-            .expect("Error when reading VirtIOBlk");
+        if current_process().is_none() {
+            self.virtio_blk
+                .exclusive_access()
+                .read_block(block_id, buf)
+                .expect("Error when reading VirtIOBlk");
+            return;
+        }
+        let mut resp = BlkResp::default();
+        let token = unsafe {
+            self.virtio_blk
+                .exclusive_access()
+                .read_block_nb(block_id, buf, &mut resp)
+                .expect("Error when reading VirtIOBlk")
+        };
+        self.wait_for(token);
+        assert_eq!(
+            resp.status(),
+            RespStatus::Ok,
+            "Error when reading VirtIOBlk"
+        );
     }
 
     /// 向块设备写入数据
@@ -156,11 +189,44 @@ impl BlockDevice for VirtIOBlock {
     ///
     /// 写入的数据会立即持久化到存储设备，在系统重启后仍然可用。
     /// 写入操作会刷新设备缓存，确保数据安全。
+    ///
+    /// 与 [`read_block`](Self::read_block) 相同，有当前进程时调用者睡眠等待完成中断。
     fn write_block(&self, block_id: usize, buf: &[u8]) {
-        self.0
-            .exclusive_access()
-            .write_block(block_id, buf)
-            .expect("Error when writing VirtIOBlk");
+        if current_process().is_none() {
+            self.virtio_blk
+                .exclusive_access()
+                .write_block(block_id, buf)
+                .expect("Error when writing VirtIOBlk");
+            return;
+        }
+        let mut resp = BlkResp::default();
+        let token = unsafe {
+            self.virtio_blk
+                .exclusive_access()
+                .write_block_nb(block_id, buf, &mut resp)
+                .expect("Error when writing VirtIOBlk")
+        };
+        self.wait_for(token);
+        assert_eq!(
+            resp.status(),
+            RespStatus::Ok,
+            "Error when writing VirtIOBlk"
+        );
+    }
+}
+
+impl IrqHandler for VirtIOBlock {
+    /// 处理 VirtIO 块设备中断
+    ///
+    /// 应答设备中断后回收所有已完成的请求描述符，记录完成状态并唤醒
+    /// 在对应描述符上睡眠的进程。
+    fn handle_irq(&self) {
+        let mut blk = self.virtio_blk.exclusive_access();
+        blk.ack_interrupt();
+        while let Ok(token) = blk.pop_used() {
+            self.completed.exclusive_access().push(token);
+            self.wait_queues[&token].wake_one();
+        }
     }
 }
 
@@ -201,10 +267,36 @@ impl VirtIOBlock {
     /// ```
     #[allow(unused)]
     pub fn new() -> Self {
-        unsafe {
-            Self(UPSafeCell::new(
+        let virtio_blk = unsafe {
+            UPSafeCell::new(
                 VirtIOBlk::<VirtioHal>::new(&mut *(VIRTIO0 as *mut VirtIOHeader)).unwrap(),
-            ))
+            )
+        };
+        let queue_size = virtio_blk.exclusive_access().virt_queue_size();
+        let wait_queues = (0..queue_size)
+            .map(|token| (token, WaitQueue::new()))
+            .collect();
+        Self {
+            virtio_blk,
+            wait_queues,
+            completed: unsafe { UPSafeCell::new(Vec::new()) },
+        }
+    }
+
+    /// 睡眠直到编号为 `token` 的请求完成
+    ///
+    /// 中断可能在进程被唤醒前回收多个描述符，因此以 `completed` 中是否出现
+    /// 该编号作为完成条件，而不是仅依赖一次唤醒。
+    fn wait_for(&self, token: u16) {
+        loop {
+            {
+                let mut completed = self.completed.exclusive_access();
+                if let Some(idx) = completed.iter().position(|done| *done == token) {
+                    completed.swap_remove(idx);
+                    return;
+                }
+            }
+            self.wait_queues[&token].wait();
         }
     }
 }
@@ -0,0 +1,83 @@
+//! # 外部中断分发
+//!
+//! 设备驱动通过 [`register_irq_handler`] 把自己登记为某个 PLIC 中断源的
+//! 处理者，陷入处理程序在收到 `SupervisorExternal` 中断时调用
+//! [`handle_external_interrupt`]，由 PLIC claim 出中断源编号后分发给
+//! 对应的 [`IrqHandler`]，处理完毕再 complete。
+
+use super::plic::{IntrTargetPriority, PLIC};
+use crate::board::VIRT_PLIC;
+use crate::println;
+use crate::sync::UPSafeCell;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use lazy_static::*;
+use riscv::register::sie;
+
+/// 本内核只在 hart 0 上运行
+const HART_ID: usize = 0;
+
+/// 设备中断处理接口
+pub trait IrqHandler: Send + Sync {
+    /// 处理一次设备中断；返回前应向设备应答中断
+    fn handle_irq(&self);
+}
+
+lazy_static! {
+    /// 中断源编号到处理者的映射
+    static ref IRQ_HANDLERS: UPSafeCell<BTreeMap<usize, Arc<dyn IrqHandler>>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+}
+
+fn plic() -> PLIC {
+    unsafe { PLIC::new(VIRT_PLIC) }
+}
+
+/// 初始化 PLIC 并打开 S 态外部中断
+///
+/// 设置 hart 0 的 S 态阈值为 0（接收所有优先级非零的中断），
+/// M 态阈值为 1（外部中断不再打扰 M 态固件）。
+pub fn init() {
+    let mut plic = plic();
+    plic.set_threshold(HART_ID, IntrTargetPriority::Supervisor, 0);
+    plic.set_threshold(HART_ID, IntrTargetPriority::Machine, 1);
+    unsafe {
+        sie::set_sext();
+    }
+}
+
+/// 登记中断源的处理者并在 PLIC 中使能该中断源
+///
+/// ## Arguments
+///
+/// * `irq` - PLIC 中断源编号
+/// * `handler` - 中断处理者
+pub fn register_irq_handler(irq: usize, handler: Arc<dyn IrqHandler>) {
+    IRQ_HANDLERS.exclusive_access().insert(irq, handler);
+    let mut plic = plic();
+    plic.set_priority(irq, 1);
+    plic.enable(HART_ID, IntrTargetPriority::Supervisor, irq);
+}
+
+/// 处理所有待处理的外部中断
+///
+/// 反复 claim 直到 PLIC 不再有待处理的中断；没有登记处理者的中断源
+/// 只打印告警并 complete，避免其一直阻塞后续中断。
+pub fn handle_external_interrupt() {
+    let mut plic = plic();
+    loop {
+        let irq = plic.claim(HART_ID, IntrTargetPriority::Supervisor);
+        if irq == 0 {
+            break;
+        }
+        let handler = IRQ_HANDLERS
+            .exclusive_access()
+            .get(&(irq as usize))
+            .cloned();
+        match handler {
+            Some(handler) => handler.handle_irq(),
+            None => println!("[kernel] unhandled external interrupt {}", irq),
+        }
+        plic.complete(HART_ID, IntrTargetPriority::Supervisor, irq);
+    }
+}
@@ -6,7 +6,11 @@
 //! ## 模块组织
 //!
 //! - [`block`] - 块设备驱动，支持磁盘、存储设备等块级 I/O 操作
-//! - 其他设备驱动模块（待扩展）
+//! - [`net`] - 网络设备驱动，为协议栈收发以太网帧
+//! - [`serial`] - 串口驱动，提供控制台与标准输入输出
+//! - [`tty`] - 终端行规程，控制台与伪终端共用
+//! - [`plic`] - PLIC 平台级中断控制器
+//! - [`irq`] - 外部中断分发，把 PLIC 中断源路由到登记的设备驱动
 //!
 //! ## 设计目标
 //!
@@ -37,5 +41,29 @@
 //! ```
 
 pub mod block;
+pub mod irq;
+pub mod net;
+pub mod plic;
+pub mod serial;
+pub mod tty;
 
 pub use block::BLOCK_DEVICE;
+pub use irq::{IrqHandler, handle_external_interrupt, register_irq_handler};
+pub use net::{NET_DEVICE, NetDevice};
+pub use serial::SERIAL;
+pub use tty::{TTY, Tty, TtyDriver};
+
+use crate::board::{UART0_IRQ, VIRTIO0_IRQ};
+use alloc::sync::Arc;
+use block::BLOCK_DRIVER;
+use tty::ConsoleIrqHandler;
+
+/// 初始化设备中断
+///
+/// 打开 PLIC 与 S 态外部中断，并把各设备驱动登记为对应中断源的处理者。
+/// 须在 `trap::init` 之后、第一次访问文件系统之前调用。
+pub fn init() {
+    irq::init();
+    register_irq_handler(VIRTIO0_IRQ, BLOCK_DRIVER.clone());
+    register_irq_handler(UART0_IRQ, Arc::new(ConsoleIrqHandler));
+}
@@ -0,0 +1,42 @@
+//! # 网络设备驱动
+//!
+//! 定义网卡的统一接口 [`NetDevice`]，协议栈通过它收发以太网帧，
+//! 不关心具体的设备类型。
+//!
+//! ## 设备探测
+//!
+//! 网卡是可选的：QEMU 启动时没有挂载 virtio-net 设备则 [`NET_DEVICE`] 为 `None`，
+//! 协议栈只保留回环接口。
+
+use crate::board::NetDeviceImpl;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use lazy_static::*;
+
+mod virtio_net;
+
+pub use virtio_net::VirtIONetDevice;
+
+/// 网卡接口
+///
+/// 帧的收发都不阻塞：发送队列已满时丢弃帧，由上层协议负责重传；
+/// 接收时没有到达的帧则返回 `None`。
+pub trait NetDevice: Send + Sync {
+    /// 网卡的 MAC 地址
+    fn mac(&self) -> [u8; 6];
+
+    /// 发送一个以太网帧（不含前导码与校验序列）
+    fn transmit(&self, frame: &[u8]);
+
+    /// 取出一个已到达的以太网帧
+    fn receive(&self) -> Option<Vec<u8>>;
+
+    /// 应答设备中断
+    fn ack_interrupt(&self);
+}
+
+lazy_static! {
+    /// 全局网卡实例，没有探测到网卡时为 `None`
+    pub static ref NET_DEVICE: Option<Arc<dyn NetDevice>> = NetDeviceImpl::probe()
+        .map(|device| Arc::new(device) as Arc<dyn NetDevice>);
+}
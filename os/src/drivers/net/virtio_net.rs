@@ -0,0 +1,74 @@
+//! # VirtIO 网卡驱动
+//!
+//! 基于 `virtio-drivers` 的 `VirtIONet`，为协议栈提供以太网帧的收发。
+//! DMA 内存的分配与地址转换沿用块设备驱动的 [`VirtioHal`]。
+//!
+//! ## 中断
+//!
+//! 网卡收到帧后触发 PLIC 外部中断（[`VIRTIO1_IRQ`](crate::board::VIRTIO1_IRQ)），
+//! 中断处理者应答中断后由协议栈通过 [`NetDevice::receive`] 取走所有已到达的帧。
+
+use super::NetDevice;
+use crate::drivers::block::VirtioHal;
+use crate::sync::UPSafeCell;
+use alloc::vec;
+use alloc::vec::Vec;
+use virtio_drivers::{DeviceType, VirtIOHeader, VirtIONet};
+
+/// VirtIO 网卡在内存映射 I/O 中的基地址（virtio-mmio 总线 1）
+const VIRTIO1: usize = 0x10002000;
+
+/// 以太网帧的最大长度（1500 字节 MTU 加 14 字节帧头）
+const MAX_FRAME_LEN: usize = 1514;
+
+/// VirtIO 网卡驱动
+pub struct VirtIONetDevice {
+    virtio_net: UPSafeCell<VirtIONet<'static, VirtioHal>>,
+}
+
+impl VirtIONetDevice {
+    /// 探测并初始化网卡
+    ///
+    /// ## Returns
+    ///
+    /// 该地址上没有 virtio-net 设备或初始化失败时返回 `None`
+    pub fn probe() -> Option<Self> {
+        let header = unsafe { &mut *(VIRTIO1 as *mut VirtIOHeader) };
+        if !header.verify() || !matches!(header.device_type(), DeviceType::Network) {
+            return None;
+        }
+        let virtio_net = VirtIONet::<VirtioHal>::new(header).ok()?;
+        Some(Self {
+            virtio_net: unsafe { UPSafeCell::new(virtio_net) },
+        })
+    }
+}
+
+impl NetDevice for VirtIONetDevice {
+    fn mac(&self) -> [u8; 6] {
+        self.virtio_net.exclusive_access().mac()
+    }
+
+    fn transmit(&self, frame: &[u8]) {
+        let mut net = self.virtio_net.exclusive_access();
+        if net.can_send() {
+            // 发送失败的帧直接丢弃，由上层协议重传
+            let _ = net.send(frame);
+        }
+    }
+
+    fn receive(&self) -> Option<Vec<u8>> {
+        let mut net = self.virtio_net.exclusive_access();
+        if !net.can_recv() {
+            return None;
+        }
+        let mut frame = vec![0u8; MAX_FRAME_LEN];
+        let len = net.recv(&mut frame).ok()?;
+        frame.truncate(len);
+        Some(frame)
+    }
+
+    fn ack_interrupt(&self) {
+        self.virtio_net.exclusive_access().ack_interrupt();
+    }
+}
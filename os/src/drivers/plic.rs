@@ -0,0 +1,120 @@
+//! # PLIC 平台级中断控制器驱动
+//!
+//! RISC-V PLIC（Platform-Level Interrupt Controller）把外部设备的中断源
+//! 汇聚后分发给各个 hart 的各特权级上下文。本驱动只使用 hart 0 的
+//! S 态上下文。
+//!
+//! ## 寄存器布局
+//!
+//! ```text
+//! base + 4 * src                        中断源优先级（0 表示屏蔽）
+//! base + 0x1000                         中断挂起位图
+//! base + 0x2000 + 0x80 * ctx            上下文 ctx 的中断使能位图
+//! base + 0x20_0000 + 0x1000 * ctx       上下文 ctx 的优先级阈值
+//! base + 0x20_0004 + 0x1000 * ctx       上下文 ctx 的 claim/complete 寄存器
+//! ```
+//!
+//! 其中上下文编号 `ctx = 2 * hart + mode`，`mode` 为 0（M 态）或 1（S 态）。
+
+/// 中断目标的特权级
+#[derive(Copy, Clone)]
+pub enum IntrTargetPriority {
+    Machine = 0,
+    Supervisor = 1,
+}
+
+/// PLIC 寄存器访问封装
+pub struct PLIC {
+    base_addr: usize,
+}
+
+impl PLIC {
+    /// 以 MMIO 基地址创建 PLIC 访问对象
+    ///
+    /// ## Safety
+    ///
+    /// `base_addr` 必须是已在内核地址空间中恒等映射的 PLIC 基地址
+    pub unsafe fn new(base_addr: usize) -> Self {
+        Self { base_addr }
+    }
+
+    fn context_id(hart_id: usize, target: IntrTargetPriority) -> usize {
+        2 * hart_id + target as usize
+    }
+
+    fn priority_ptr(&self, intr_source_id: usize) -> *mut u32 {
+        assert!(intr_source_id > 0 && intr_source_id <= 132);
+        (self.base_addr + intr_source_id * 4) as *mut u32
+    }
+
+    fn enable_ptr(
+        &self,
+        hart_id: usize,
+        target: IntrTargetPriority,
+        intr_source_id: usize,
+    ) -> (*mut u32, usize) {
+        let context = Self::context_id(hart_id, target);
+        let (reg_id, reg_shift) = (intr_source_id / 32, intr_source_id % 32);
+        (
+            (self.base_addr + 0x2000 + 0x80 * context + 0x4 * reg_id) as *mut u32,
+            reg_shift,
+        )
+    }
+
+    fn threshold_ptr(&self, hart_id: usize, target: IntrTargetPriority) -> *mut u32 {
+        let context = Self::context_id(hart_id, target);
+        (self.base_addr + 0x20_0000 + 0x1000 * context) as *mut u32
+    }
+
+    fn claim_comp_ptr(&self, hart_id: usize, target: IntrTargetPriority) -> *mut u32 {
+        let context = Self::context_id(hart_id, target);
+        (self.base_addr + 0x20_0004 + 0x1000 * context) as *mut u32
+    }
+
+    /// 设置中断源优先级（0~7，0 表示屏蔽）
+    pub fn set_priority(&mut self, intr_source_id: usize, priority: u32) {
+        assert!(priority < 8);
+        unsafe {
+            self.priority_ptr(intr_source_id).write_volatile(priority);
+        }
+    }
+
+    /// 为指定上下文使能中断源
+    pub fn enable(&mut self, hart_id: usize, target: IntrTargetPriority, intr_source_id: usize) {
+        let (reg_ptr, shift) = self.enable_ptr(hart_id, target, intr_source_id);
+        unsafe {
+            reg_ptr.write_volatile(reg_ptr.read_volatile() | (1 << shift));
+        }
+    }
+
+    /// 为指定上下文屏蔽中断源
+    #[allow(unused)]
+    pub fn disable(&mut self, hart_id: usize, target: IntrTargetPriority, intr_source_id: usize) {
+        let (reg_ptr, shift) = self.enable_ptr(hart_id, target, intr_source_id);
+        unsafe {
+            reg_ptr.write_volatile(reg_ptr.read_volatile() & !(1 << shift));
+        }
+    }
+
+    /// 设置上下文的优先级阈值，只有优先级高于阈值的中断才会被投递
+    pub fn set_threshold(&mut self, hart_id: usize, target: IntrTargetPriority, threshold: u32) {
+        assert!(threshold < 8);
+        unsafe {
+            self.threshold_ptr(hart_id, target)
+                .write_volatile(threshold);
+        }
+    }
+
+    /// 领取（claim）一个待处理的中断，返回中断源编号；没有待处理中断时返回 0
+    pub fn claim(&mut self, hart_id: usize, target: IntrTargetPriority) -> u32 {
+        unsafe { self.claim_comp_ptr(hart_id, target).read_volatile() }
+    }
+
+    /// 通知 PLIC 该中断源已处理完毕（complete）
+    pub fn complete(&mut self, hart_id: usize, target: IntrTargetPriority, completion: u32) {
+        unsafe {
+            self.claim_comp_ptr(hart_id, target)
+                .write_volatile(completion);
+        }
+    }
+}
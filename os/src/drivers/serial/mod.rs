@@ -0,0 +1,46 @@
+//! # 串口驱动模块
+//!
+//! 提供内核控制台与标准输入输出所用的 UART 驱动，取代 SBI 的
+//! `console_putchar` / `console_getchar` 遗留接口。
+//!
+//! ## 模块组织
+//!
+//! - [`ns16550a`] - NS16550A UART 驱动，中断驱动接收、缓冲发送
+//! - 全局串口实例 [`SERIAL`]
+//!
+//! ## 启用时机
+//!
+//! UART 寄存器位于 MMIO 区域，必须等 `mm::init` 建立内核页表后才能访问。
+//! 在 [`init`] 调用之前，控制台输出仍经由 SBI 完成；[`is_ready`] 用于判断
+//! 是否已切换到本驱动。
+
+use crate::board::{CharDeviceImpl, VIRT_UART};
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+use lazy_static::*;
+
+mod ns16550a;
+
+pub use ns16550a::NS16550a;
+
+/// 串口硬件是否已完成初始化
+static SERIAL_READY: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    /// 全局串口实例
+    pub static ref SERIAL: Arc<CharDeviceImpl> = Arc::new(CharDeviceImpl::new(VIRT_UART));
+}
+
+/// 初始化串口硬件并把控制台切换到串口
+///
+/// 须在 `mm::init` 之后调用；接收中断要等 `drivers::init` 在 PLIC
+/// 中登记后才会真正送达。
+pub fn init() {
+    SERIAL.init();
+    SERIAL_READY.store(true, Ordering::Release);
+}
+
+/// 控制台是否已切换到串口驱动
+pub fn is_ready() -> bool {
+    SERIAL_READY.load(Ordering::Acquire)
+}
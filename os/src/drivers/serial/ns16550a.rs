@@ -0,0 +1,221 @@
+//! # NS16550A UART 驱动
+//!
+//! QEMU virt 平台在 `0x1000_0000` 处提供一个兼容 16550 的 UART，
+//! 寄存器间距为 1 字节，中断源编号为 10。
+//!
+//! ## 寄存器布局
+//!
+//! ```text
+//! 偏移  读                  写                 DLAB=1
+//! 0     RBR 接收缓冲        THR 发送保持       DLL 除数低字节
+//! 1     IER 中断使能        IER 中断使能       DLM 除数高字节
+//! 2     IIR 中断标识        FCR FIFO 控制
+//! 3     LCR 线路控制        LCR 线路控制
+//! 4     MCR 调制解调器控制  MCR 调制解调器控制
+//! 5     LSR 线路状态
+//! ```
+//!
+//! ## 收发策略
+//!
+//! - **接收**: 打开“接收数据可用”中断，中断处理程序把 RBR 中的字节全部
+//!   搬入接收环形缓冲区，再由终端行规程（`drivers::tty`）取走加工
+//! - **发送**: 写入的数据先进入发送环形缓冲区，THR 为空时一次填满硬件 FIFO；
+//!   缓冲区未排空时打开“发送保持寄存器空”中断，由中断继续搬运。
+//!   内核自身的输出通过 [`NS16550a::flush`] 同步排空，保证 panic、关机前的
+//!   信息不会丢失，并与用户输出保持先后顺序
+
+use crate::drivers::IrqHandler;
+use crate::sync::UPSafeCell;
+use alloc::collections::VecDeque;
+use bitflags::bitflags;
+
+/// 硬件发送 FIFO 深度
+const FIFO_DEPTH: usize = 16;
+/// 接收环形缓冲区容量，满后新到达的字节被丢弃
+const RX_BUFFER_SIZE: usize = 1024;
+/// 发送环形缓冲区容量，超出时写者同步等待硬件发送
+const TX_BUFFER_SIZE: usize = 4096;
+
+bitflags! {
+    /// 中断使能寄存器 (IER)
+    struct IER: u8 {
+        /// 接收数据可用
+        const RX_AVAILABLE = 1 << 0;
+        /// 发送保持寄存器空
+        const TX_EMPTY = 1 << 1;
+    }
+
+    /// 线路状态寄存器 (LSR)
+    struct LSR: u8 {
+        /// 接收缓冲区有数据
+        const DATA_AVAILABLE = 1 << 0;
+        /// 发送保持寄存器（及 FIFO）为空
+        const THR_EMPTY = 1 << 5;
+    }
+
+    /// 调制解调器控制寄存器 (MCR)
+    struct MCR: u8 {
+        const DATA_TERMINAL_READY = 1 << 0;
+        const REQUEST_TO_SEND = 1 << 1;
+        /// 在真实硬件上控制中断线输出
+        const AUX_OUTPUT2 = 1 << 3;
+    }
+}
+
+/// UART 寄存器的原始访问
+struct NS16550aRaw {
+    base_addr: usize,
+}
+
+impl NS16550aRaw {
+    const RBR_THR_DLL: usize = 0;
+    const IER_DLM: usize = 1;
+    const FCR: usize = 2;
+    const LCR: usize = 3;
+    const MCR: usize = 4;
+    const LSR: usize = 5;
+
+    fn read_reg(&self, offset: usize) -> u8 {
+        unsafe { ((self.base_addr + offset) as *const u8).read_volatile() }
+    }
+
+    fn write_reg(&mut self, offset: usize, value: u8) {
+        unsafe { ((self.base_addr + offset) as *mut u8).write_volatile(value) }
+    }
+
+    /// 配置为 38400 波特率、8N1，打开并清空 FIFO，仅使能接收中断
+    fn init(&mut self) {
+        self.write_reg(Self::IER_DLM, 0);
+        self.write_reg(Self::LCR, 0x80);
+        self.write_reg(Self::RBR_THR_DLL, 3);
+        self.write_reg(Self::IER_DLM, 0);
+        self.write_reg(Self::LCR, 0x03);
+        self.write_reg(Self::FCR, 0x07);
+        self.write_reg(
+            Self::MCR,
+            (MCR::DATA_TERMINAL_READY | MCR::REQUEST_TO_SEND | MCR::AUX_OUTPUT2).bits(),
+        );
+        self.set_ier(IER::RX_AVAILABLE);
+    }
+
+    fn lsr(&self) -> LSR {
+        LSR::from_bits_truncate(self.read_reg(Self::LSR))
+    }
+
+    fn set_ier(&mut self, ier: IER) {
+        self.write_reg(Self::IER_DLM, ier.bits());
+    }
+
+    fn read(&self) -> Option<u8> {
+        if self.lsr().contains(LSR::DATA_AVAILABLE) {
+            Some(self.read_reg(Self::RBR_THR_DLL))
+        } else {
+            None
+        }
+    }
+
+    /// THR 为空时把 `tx` 中至多一个 FIFO 深度的字节写入硬件
+    fn fill_fifo(&mut self, tx: &mut VecDeque<u8>) {
+        if !self.lsr().contains(LSR::THR_EMPTY) {
+            return;
+        }
+        for _ in 0..FIFO_DEPTH {
+            match tx.pop_front() {
+                Some(ch) => self.write_reg(Self::RBR_THR_DLL, ch),
+                None => break,
+            }
+        }
+    }
+}
+
+struct NS16550aInner {
+    raw: NS16550aRaw,
+    rx_buffer: VecDeque<u8>,
+    tx_buffer: VecDeque<u8>,
+}
+
+impl NS16550aInner {
+    /// 尽量发送缓冲区中的数据，并按缓冲区是否排空开关发送中断
+    fn kick_tx(&mut self) {
+        self.raw.fill_fifo(&mut self.tx_buffer);
+        if self.tx_buffer.is_empty() {
+            self.raw.set_ier(IER::RX_AVAILABLE);
+        } else {
+            self.raw.set_ier(IER::RX_AVAILABLE | IER::TX_EMPTY);
+        }
+    }
+
+    /// 轮询直到发送缓冲区排空
+    fn drain_tx(&mut self) {
+        while !self.tx_buffer.is_empty() {
+            self.raw.fill_fifo(&mut self.tx_buffer);
+        }
+        self.raw.set_ier(IER::RX_AVAILABLE);
+    }
+}
+
+/// 中断驱动的 NS16550A UART
+pub struct NS16550a {
+    inner: UPSafeCell<NS16550aInner>,
+}
+
+impl NS16550a {
+    /// 以 MMIO 基地址创建 UART 驱动，此时不访问硬件
+    pub fn new(base_addr: usize) -> Self {
+        Self {
+            inner: unsafe {
+                UPSafeCell::new(NS16550aInner {
+                    raw: NS16550aRaw { base_addr },
+                    rx_buffer: VecDeque::with_capacity(RX_BUFFER_SIZE),
+                    tx_buffer: VecDeque::with_capacity(TX_BUFFER_SIZE),
+                })
+            },
+        }
+    }
+
+    /// 初始化 UART 硬件
+    ///
+    /// 须在内核页表映射 UART 的 MMIO 区域之后调用。
+    pub fn init(&self) {
+        self.inner.exclusive_access().raw.init();
+    }
+
+    /// 从接收缓冲区取出一个字节，缓冲区为空时返回 `None`
+    pub fn read(&self) -> Option<u8> {
+        self.inner.exclusive_access().rx_buffer.pop_front()
+    }
+
+    /// 把数据放入发送缓冲区并启动发送
+    ///
+    /// 缓冲区已满时同步等待硬件腾出空间，因此写入不会丢失数据。
+    pub fn write(&self, data: &[u8]) {
+        let mut inner = self.inner.exclusive_access();
+        for &ch in data {
+            if inner.tx_buffer.len() >= TX_BUFFER_SIZE {
+                inner.drain_tx();
+            }
+            inner.tx_buffer.push_back(ch);
+        }
+        inner.kick_tx();
+    }
+
+    /// 同步发送缓冲区中的全部数据
+    pub fn flush(&self) {
+        self.inner.exclusive_access().drain_tx();
+    }
+}
+
+impl IrqHandler for NS16550a {
+    /// 处理 UART 中断
+    ///
+    /// 读空硬件接收 FIFO，然后继续发送缓冲区中的数据。
+    fn handle_irq(&self) {
+        let mut inner = self.inner.exclusive_access();
+        while let Some(ch) = inner.raw.read() {
+            if inner.rx_buffer.len() < RX_BUFFER_SIZE {
+                inner.rx_buffer.push_back(ch);
+            }
+        }
+        inner.kick_tx();
+    }
+}
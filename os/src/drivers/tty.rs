@@ -0,0 +1,722 @@
+//! # 终端（TTY）行规程
+//!
+//! 位于终端驱动与读写终端的进程之间，负责把驱动送来的原始字节加工成
+//! 程序读取的输入，并对程序输出做必要的转换后交给驱动。
+//!
+//! ## 终端驱动
+//!
+//! 行规程通过 [`TtyDriver`] 与具体设备解耦：
+//! - 控制台终端 [`TTY`] 的驱动是串口，输入来自串口接收中断（[`ConsoleIrqHandler`]）
+//! - 伪终端（`fs::pty`）的从端终端以主端为驱动，输入来自主端的写入，
+//!   输出进入主端的读缓冲区
+//!
+//! ## 输入处理
+//!
+//! - **规范模式** (`ICANON`)：按行缓冲，支持擦除（`VERASE`）、删行（`VKILL`）
+//!   与文件结束（`VEOF`），读者只有在整行完成后才能读到数据，每次最多读一行
+//! - **原始模式**：字节到达即可读，`VMIN` 决定读者至少等待的字节数
+//!   （`VTIME` 暂不支持）
+//! - **回显** (`ECHO`)：由行规程负责回显，程序不再需要自己处理退格
+//! - **信号** (`ISIG`)：`VINTR`/`VQUIT`/`VSUSP`（默认 `^C`/`^\`/`^Z`）分别向
+//!   前台进程发送 `SIGINT`/`SIGQUIT`/`SIGTSTP`
+//!
+//! ## 前台进程
+//!
+//! 内核尚无进程组，`TIOCSPGRP` 设置的“前台进程组”就是一个进程号；
+//! 终端产生的信号发送给该进程及其全部后代，以近似进程组的语义。
+//! 未设置前台进程时，这些控制字符只清空输入而不发送信号。
+//!
+//! ## 控制接口
+//!
+//! 通过 `ioctl` 访问，命令编号与 Linux 一致：
+//! `TCGETS`/`TCSETS`/`TCSETSW`/`TCSETSF` 读写 [`Termios`]，
+//! `TIOCGPGRP`/`TIOCSPGRP` 读写前台进程，`TIOCGWINSZ`/`TIOCSWINSZ` 读写窗口大小，
+//! 窗口大小改变时向前台进程发送 `SIGWINCH`。
+//!
+//! ## 就绪状态
+//!
+//! [`Tty::poll`] 报告读者是否可以立即读到数据（规范模式下有完整的行）、
+//! 驱动是否还能接受输出以及驱动是否已挂断，供 `ppoll`/`pselect6` 使用。
+
+use super::serial::NS16550a;
+use super::{IrqHandler, SERIAL};
+use crate::errno::Errno::{EAGAIN, EFAULT, EINTR, EINVAL, ENOTTY, ESRCH};
+use crate::fs::{PollEvents, PollTable};
+use crate::mm::{UserBuffer, copy_from_user, copy_to_user};
+use crate::process::{
+    ProcessControlBlock, SignalFlags, current_signal_pending, current_user_token, pid2process,
+    signal_process,
+};
+use crate::sync::{UPSafeCell, WaitQueue};
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use lazy_static::*;
+
+/// 控制字符数组长度
+pub const NCCS: usize = 19;
+
+/// `c_cc` 下标
+const VINTR: usize = 0;
+const VQUIT: usize = 1;
+const VERASE: usize = 2;
+const VKILL: usize = 3;
+const VEOF: usize = 4;
+const VMIN: usize = 6;
+const VSUSP: usize = 10;
+const VEOL: usize = 11;
+
+/// `c_iflag`
+const INLCR: u32 = 0o100;
+const IGNCR: u32 = 0o200;
+const ICRNL: u32 = 0o400;
+
+/// `c_oflag`
+const OPOST: u32 = 0o1;
+const ONLCR: u32 = 0o4;
+
+/// `c_cflag`：B38400 | CS8 | CREAD
+const DEFAULT_CFLAG: u32 = 0o17 | 0o60 | 0o200;
+
+/// `c_lflag`
+const ISIG: u32 = 0o1;
+const ICANON: u32 = 0o2;
+const ECHO: u32 = 0o10;
+const ECHOE: u32 = 0o20;
+const ECHOK: u32 = 0o40;
+const ECHONL: u32 = 0o100;
+const NOFLSH: u32 = 0o200;
+const ECHOCTL: u32 = 0o1000;
+
+/// `ioctl` 命令
+const TCGETS: usize = 0x5401;
+const TCSETS: usize = 0x5402;
+const TCSETSW: usize = 0x5403;
+const TCSETSF: usize = 0x5404;
+const TIOCGPGRP: usize = 0x540F;
+const TIOCSPGRP: usize = 0x5410;
+const TIOCGWINSZ: usize = 0x5413;
+const TIOCSWINSZ: usize = 0x5414;
+
+/// 规范模式下一行的最大长度，超出的字符被丢弃
+const MAX_CANON: usize = 4096;
+/// 待读数据的最大长度，超出的输入被丢弃
+const MAX_INPUT: usize = 4096;
+
+const BS: u8 = 0x08;
+
+/// 终端属性，内存布局与 Linux 的 `struct termios` 一致
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Termios {
+    pub c_iflag: u32,
+    pub c_oflag: u32,
+    pub c_cflag: u32,
+    pub c_lflag: u32,
+    pub c_line: u8,
+    pub c_cc: [u8; NCCS],
+}
+
+impl Default for Termios {
+    /// 规范模式、回显、产生信号，输入 CR 转 LF，输出 LF 转 CRLF
+    fn default() -> Self {
+        let mut c_cc = [0u8; NCCS];
+        c_cc[VINTR] = 0x03;
+        c_cc[VQUIT] = 0x1c;
+        c_cc[VERASE] = 0x7f;
+        c_cc[VKILL] = 0x15;
+        c_cc[VEOF] = 0x04;
+        c_cc[VMIN] = 1;
+        c_cc[VSUSP] = 0x1a;
+        Self {
+            c_iflag: ICRNL,
+            c_oflag: OPOST | ONLCR,
+            c_cflag: DEFAULT_CFLAG,
+            c_lflag: ISIG | ICANON | ECHO | ECHOE | ECHOK | ECHOCTL,
+            c_line: 0,
+            c_cc,
+        }
+    }
+}
+
+impl Termios {
+    fn has_lflag(&self, flag: u32) -> bool {
+        self.c_lflag & flag != 0
+    }
+
+    /// `ch` 是否为下标 `index` 处的控制字符；值为 0 的控制字符视为禁用
+    fn is_cc(&self, ch: u8, index: usize) -> bool {
+        self.c_cc[index] != 0 && self.c_cc[index] == ch
+    }
+}
+
+/// 终端窗口大小，与 Linux 的 `struct winsize` 布局一致
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Winsize {
+    pub ws_row: u16,
+    pub ws_col: u16,
+    pub ws_xpixel: u16,
+    pub ws_ypixel: u16,
+}
+
+impl Default for Winsize {
+    /// 24 行 80 列
+    fn default() -> Self {
+        Self {
+            ws_row: 24,
+            ws_col: 80,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        }
+    }
+}
+
+/// 终端驱动：接收行规程加工后的输出
+pub trait TtyDriver: Send + Sync {
+    /// 输出数据，不能阻塞（回显可能发生在中断上下文中）
+    fn output(&self, data: &[u8]);
+
+    /// 当前还能接受多少字节的进程输出，0 表示写者需要等待
+    fn write_room(&self) -> usize {
+        usize::MAX
+    }
+
+    /// 阻塞当前进程直到 [`write_room`](Self::write_room) 可能不为 0
+    fn wait_write_room(&self) {}
+
+    /// 等待已输出的数据全部送达设备
+    fn drain(&self) {}
+}
+
+impl TtyDriver for NS16550a {
+    fn output(&self, data: &[u8]) {
+        self.write(data);
+    }
+
+    fn drain(&self) {
+        self.flush();
+    }
+}
+
+struct TtyInner {
+    driver: Arc<dyn TtyDriver>,
+    termios: Termios,
+    /// 规范模式下正在编辑、尚未完成的行
+    line: Vec<u8>,
+    /// 读者可以取走的数据
+    ready: VecDeque<u8>,
+    /// 规范模式下 `ready` 中各行的结束位置（相对队首的字节数）；
+    /// 位置为 0 的项表示空行上的 `VEOF`，读者读到它时返回 0
+    line_ends: VecDeque<usize>,
+    /// 接收终端信号的前台进程
+    foreground: Option<usize>,
+    /// 窗口大小，由终端模拟器设置
+    winsize: Winsize,
+    /// 驱动已挂断（如伪终端主端关闭），读者不再等待新输入
+    hung_up: bool,
+}
+
+impl TtyInner {
+    fn canonical(&self) -> bool {
+        self.termios.has_lflag(ICANON)
+    }
+
+    /// 本次读取可以取走的字节数，`None` 表示需要继续等待
+    fn readable_len(&mut self, want: usize) -> Option<usize> {
+        if self.canonical() {
+            match self.line_ends.front() {
+                Some(0) => {
+                    self.line_ends.pop_front();
+                    Some(0)
+                }
+                Some(&end) => Some(end.min(want)),
+                None if self.hung_up => Some(0),
+                None => None,
+            }
+        } else {
+            let min = (self.termios.c_cc[VMIN] as usize).min(want);
+            if min == 0 || self.ready.len() >= min || self.hung_up {
+                Some(self.ready.len().min(want))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// 读者能否立即读到数据（或读到文件结束）
+    fn input_available(&self) -> bool {
+        if self.hung_up {
+            return true;
+        }
+        if self.canonical() {
+            !self.line_ends.is_empty()
+        } else {
+            self.ready.len() >= (self.termios.c_cc[VMIN] as usize).max(1)
+        }
+    }
+
+    /// 从 `ready` 队首取走 `n` 字节后更新行结束位置
+    fn consume(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        for end in self.line_ends.iter_mut() {
+            *end -= n;
+        }
+        if self.line_ends.front() == Some(&0) {
+            self.line_ends.pop_front();
+        }
+    }
+
+    /// 把正在编辑的行提交给读者
+    ///
+    /// 待读数据放不下整行时丢弃该行，与原始模式丢弃超出 `MAX_INPUT` 的输入一致。
+    ///
+    /// ## Returns
+    ///
+    /// 行被提交时返回 `true`，被丢弃时返回 `false`
+    fn commit_line(&mut self) -> bool {
+        if self.ready.len() + self.line.len() > MAX_INPUT || self.line_ends.len() >= MAX_INPUT {
+            self.line.clear();
+            return false;
+        }
+        self.ready.extend(self.line.drain(..));
+        self.line_ends.push_back(self.ready.len());
+        true
+    }
+
+    fn flush_input(&mut self) {
+        self.line.clear();
+        self.ready.clear();
+        self.line_ends.clear();
+    }
+
+    /// 切换终端属性，必要时在规范/原始模式之间迁移已缓冲的输入
+    fn set_termios(&mut self, termios: Termios) {
+        let was_canonical = self.canonical();
+        self.termios = termios;
+        match (was_canonical, self.canonical()) {
+            (true, false) => {
+                let room = MAX_INPUT.saturating_sub(self.ready.len());
+                self.ready.extend(self.line.drain(..).take(room));
+                self.line_ends.clear();
+            }
+            (false, true) if !self.ready.is_empty() => {
+                self.line_ends.push_back(self.ready.len());
+            }
+            _ => {}
+        }
+    }
+
+    /// `data` 按 `c_oflag` 转换后不超过 `room` 字节的最长前缀长度
+    fn output_fit(&self, data: &[u8], room: usize) -> usize {
+        let oflag = self.termios.c_oflag;
+        let onlcr = oflag & OPOST != 0 && oflag & ONLCR != 0;
+        let mut used = 0;
+        for (idx, &ch) in data.iter().enumerate() {
+            used += if onlcr && ch == b'\n' { 2 } else { 1 };
+            if used > room {
+                return idx;
+            }
+        }
+        data.len()
+    }
+
+    /// 按 `c_oflag` 转换输出并交给驱动
+    fn output(&self, data: &[u8]) {
+        let oflag = self.termios.c_oflag;
+        if oflag & OPOST == 0 || oflag & ONLCR == 0 || !data.contains(&b'\n') {
+            self.driver.output(data);
+            return;
+        }
+        let mut converted = Vec::with_capacity(data.len() + 8);
+        for &ch in data {
+            if ch == b'\n' {
+                converted.push(b'\r');
+            }
+            converted.push(ch);
+        }
+        self.driver.output(&converted);
+    }
+
+    /// 回显一个输入字符；`ECHOCTL` 时控制字符显示为 `^X`
+    fn echo(&self, ch: u8) {
+        if !self.termios.has_lflag(ECHO) {
+            return;
+        }
+        if self.termios.has_lflag(ECHOCTL)
+            && (ch < 0x20 || ch == 0x7f)
+            && ch != b'\n'
+            && ch != b'\t'
+        {
+            self.output(&[b'^', ch ^ 0x40]);
+        } else {
+            self.output(&[ch]);
+        }
+    }
+
+    /// 在屏幕上擦除一个已回显的字符
+    fn echo_erase(&self) {
+        if self.termios.has_lflag(ECHO) && self.termios.has_lflag(ECHOE) {
+            self.output(&[BS, b' ', BS]);
+        }
+    }
+
+    /// 识别产生信号的控制字符
+    fn signal_of(&self, ch: u8) -> Option<SignalFlags> {
+        if !self.termios.has_lflag(ISIG) {
+            return None;
+        }
+        let termios = &self.termios;
+        if termios.is_cc(ch, VINTR) {
+            Some(SignalFlags::SIGINT)
+        } else if termios.is_cc(ch, VQUIT) {
+            Some(SignalFlags::SIGQUIT)
+        } else if termios.is_cc(ch, VSUSP) {
+            Some(SignalFlags::SIGTSTP)
+        } else {
+            None
+        }
+    }
+}
+
+/// 终端：行规程状态与等待输入的读者
+pub struct Tty {
+    inner: UPSafeCell<TtyInner>,
+    /// 等待输入的读者
+    readers: WaitQueue,
+}
+
+lazy_static! {
+    /// 系统唯一的控制台终端，所有进程的标准输入输出都指向它
+    pub static ref TTY: Arc<Tty> = Arc::new(Tty::new(SERIAL.clone()));
+}
+
+impl Tty {
+    /// 以默认终端属性创建终端
+    ///
+    /// ## Arguments
+    ///
+    /// * `driver` - 接收终端输出的驱动
+    pub fn new(driver: Arc<dyn TtyDriver>) -> Self {
+        Self {
+            inner: unsafe {
+                UPSafeCell::new(TtyInner {
+                    driver,
+                    termios: Termios::default(),
+                    line: Vec::new(),
+                    ready: VecDeque::new(),
+                    line_ends: VecDeque::new(),
+                    foreground: None,
+                    winsize: Winsize::default(),
+                    hung_up: false,
+                })
+            },
+            readers: WaitQueue::new(),
+        }
+    }
+
+    /// 把驱动收到的输入交给行规程
+    ///
+    /// 可在中断上下文中调用，不会阻塞。
+    pub fn input(&self, data: &[u8]) {
+        for &ch in data {
+            self.receive(ch);
+        }
+    }
+
+    /// 驱动挂断：唤醒所有读者，此后没有数据可读时读取返回 0
+    pub fn hangup(&self) {
+        self.inner.exclusive_access().hung_up = true;
+        self.readers.wake_all();
+    }
+
+    /// 行规程处理一个输入字节
+    fn receive(&self, ch: u8) {
+        let mut inner = self.inner.exclusive_access();
+        let iflag = inner.termios.c_iflag;
+        let ch = match ch {
+            b'\r' if iflag & IGNCR != 0 => return,
+            b'\r' if iflag & ICRNL != 0 => b'\n',
+            b'\n' if iflag & INLCR != 0 => b'\r',
+            _ => ch,
+        };
+
+        if let Some(signal) = inner.signal_of(ch) {
+            if !inner.termios.has_lflag(NOFLSH) {
+                inner.flush_input();
+            }
+            inner.echo(ch);
+            let foreground = inner.foreground;
+            drop(inner);
+            if let Some(process) = foreground.and_then(pid2process) {
+                signal_process_tree(&process, signal);
+            }
+            self.readers.wake_all();
+            return;
+        }
+
+        if !inner.canonical() {
+            if inner.ready.len() < MAX_INPUT {
+                inner.ready.push_back(ch);
+                inner.echo(ch);
+            }
+            drop(inner);
+            self.readers.wake_all();
+            return;
+        }
+
+        let termios = inner.termios;
+        // 兼容退格键发送 ^H 的终端
+        if termios.is_cc(ch, VERASE) || ch == BS {
+            if inner.line.pop().is_some() {
+                inner.echo_erase();
+            }
+        } else if termios.is_cc(ch, VKILL) {
+            while inner.line.pop().is_some() {
+                if inner.termios.has_lflag(ECHOK) {
+                    inner.echo_erase();
+                }
+            }
+        } else if termios.is_cc(ch, VEOF) {
+            if inner.commit_line() {
+                drop(inner);
+                self.readers.wake_all();
+            }
+        } else if ch == b'\n' || termios.is_cc(ch, VEOL) {
+            inner.line.push(ch);
+            // 被丢弃的行不回显换行，与原始模式不回显丢弃的字符一致
+            if !inner.commit_line() {
+                return;
+            }
+            if ch == b'\n' && inner.termios.has_lflag(ECHONL) {
+                inner.output(b"\n");
+            } else {
+                inner.echo(ch);
+            }
+            drop(inner);
+            self.readers.wake_all();
+        } else if inner.line.len() < MAX_CANON - 1 {
+            inner.line.push(ch);
+            inner.echo(ch);
+        }
+    }
+
+    /// 读取终端输入
+    ///
+    /// 规范模式下每次最多返回一行；原始模式下至少等到 `VMIN` 个字节。
+    ///
+    /// ## Returns
+    ///
+    /// 读取的字节数。空行上输入 `VEOF` 或终端挂断时返回 0；等待期间收到会
+    /// 终止进程或需要进入用户处理函数的信号时返回 `-EINTR`，由陷阱返回路径
+    /// 处理信号。非阻塞模式下没有可读数据时返回 `-EAGAIN`。
+    pub fn read(&self, buf: UserBuffer, nonblocking: bool) -> isize {
+        let want = buf.len();
+        if want == 0 {
+            return 0;
+        }
+        loop {
+            let mut inner = self.inner.exclusive_access();
+            if let Some(n) = inner.readable_len(want) {
+                for (dst, ch) in buf.into_iter().zip(inner.ready.drain(..n)) {
+                    unsafe {
+                        *dst = ch;
+                    }
+                }
+                inner.consume(n);
+                return n as isize;
+            }
+            drop(inner);
+            if nonblocking {
+                return -EAGAIN;
+            }
+            if current_signal_pending() {
+                return -EINTR;
+            }
+            self.readers.wait();
+        }
+    }
+
+    /// 向终端输出，按 `c_oflag` 做换行转换
+    ///
+    /// 驱动暂时无法接受更多输出时写者睡眠；等待期间收到信号则返回已写入的字节数，
+    /// 一个字节都没有写入时返回 `-EINTR`。非阻塞模式下不等待，一个字节都没有写入时
+    /// 返回 `-EAGAIN`。
+    pub fn write(&self, buf: UserBuffer, nonblocking: bool) -> isize {
+        let driver = self.inner.exclusive_access().driver.clone();
+        let mut written = 0;
+        for buffer in buf.buffers.iter() {
+            let mut rest: &[u8] = buffer;
+            while !rest.is_empty() {
+                let room = driver.write_room();
+                // 换行转换后的输出也必须放得下，否则驱动只能丢弃
+                let fit = match room {
+                    0 => 0,
+                    _ => self.inner.exclusive_access().output_fit(rest, room),
+                };
+                if fit == 0 {
+                    if nonblocking && written == 0 {
+                        return -EAGAIN;
+                    }
+                    if nonblocking {
+                        return written as isize;
+                    }
+                    if current_signal_pending() {
+                        return if written == 0 {
+                            -EINTR
+                        } else {
+                            written as isize
+                        };
+                    }
+                    driver.wait_write_room();
+                    continue;
+                }
+                let (chunk, remain) = rest.split_at(fit);
+                self.inner.exclusive_access().output(chunk);
+                written += chunk.len();
+                rest = remain;
+            }
+        }
+        written as isize
+    }
+
+    /// 查询终端的就绪状态
+    ///
+    /// 有完整的输入可读时报告 `POLLIN`，驱动还能接受输出时报告 `POLLOUT`，
+    /// 驱动挂断后报告 `POLLHUP`（此时读取会立即返回，因此同时报告 `POLLIN`）。
+    pub fn poll(&self) -> PollEvents {
+        let inner = self.inner.exclusive_access();
+        let mut events = PollEvents::empty();
+        if inner.input_available() {
+            events |= PollEvents::POLLIN;
+        }
+        if inner.hung_up {
+            events |= PollEvents::POLLHUP;
+        }
+        if inner.driver.write_room() > 0 {
+            events |= PollEvents::POLLOUT;
+        }
+        events
+    }
+
+    /// 登记等待输入的队列
+    ///
+    /// 输出空间的变化由驱动负责唤醒，需要时调用者另行登记驱动的队列。
+    pub fn poll_wait<'a>(&'a self, table: &mut PollTable<'a>) {
+        table.add(&self.readers);
+    }
+
+    /// 终端控制命令
+    ///
+    /// ## Arguments
+    ///
+    /// * `cmd` - `TCGETS`/`TCSETS`/`TCSETSW`/`TCSETSF`/`TIOCGPGRP`/`TIOCSPGRP`/
+    ///   `TIOCGWINSZ`/`TIOCSWINSZ`
+    /// * `arg` - 指向用户空间 [`Termios`]、进程号（`i32`）或 [`Winsize`] 的指针
+    ///
+    /// ## Returns
+    ///
+    /// - 0：成功
+    /// - `-EINVAL`：进程号非法
+    /// - `-ESRCH`：进程不存在
+    /// - `-EFAULT`：`arg` 不可访问
+    /// - `-ENOTTY`：不支持的命令
+    pub fn ioctl(&self, cmd: usize, arg: usize) -> isize {
+        let token = current_user_token();
+        match cmd {
+            TCGETS => {
+                let termios = self.inner.exclusive_access().termios;
+                if copy_to_user(token, arg as *mut Termios, &termios).is_err() {
+                    return -EFAULT;
+                }
+                0
+            }
+            TCSETS | TCSETSW | TCSETSF => {
+                let Ok(termios) = copy_from_user(token, arg as *const Termios) else {
+                    return -EFAULT;
+                };
+                if cmd != TCSETS {
+                    let driver = self.inner.exclusive_access().driver.clone();
+                    driver.drain();
+                }
+                let mut inner = self.inner.exclusive_access();
+                if cmd == TCSETSF {
+                    inner.flush_input();
+                }
+                inner.set_termios(termios);
+                drop(inner);
+                self.readers.wake_all();
+                0
+            }
+            TIOCGPGRP => {
+                let foreground = self.inner.exclusive_access().foreground;
+                let pgrp = foreground.unwrap_or(0) as i32;
+                if copy_to_user(token, arg as *mut i32, &pgrp).is_err() {
+                    return -EFAULT;
+                }
+                0
+            }
+            TIOCSPGRP => {
+                let Ok(pid) = copy_from_user(token, arg as *const i32) else {
+                    return -EFAULT;
+                };
+                if pid <= 0 {
+                    return -EINVAL;
+                }
+                if pid2process(pid as usize).is_none() {
+                    return -ESRCH;
+                }
+                self.inner.exclusive_access().foreground = Some(pid as usize);
+                0
+            }
+            TIOCGWINSZ => {
+                let winsize = self.inner.exclusive_access().winsize;
+                if copy_to_user(token, arg as *mut Winsize, &winsize).is_err() {
+                    return -EFAULT;
+                }
+                0
+            }
+            TIOCSWINSZ => {
+                let Ok(winsize) = copy_from_user(token, arg as *const Winsize) else {
+                    return -EFAULT;
+                };
+                let mut inner = self.inner.exclusive_access();
+                if inner.winsize == winsize {
+                    return 0;
+                }
+                inner.winsize = winsize;
+                let foreground = inner.foreground;
+                drop(inner);
+                if let Some(process) = foreground.and_then(pid2process) {
+                    signal_process_tree(&process, SignalFlags::SIGWINCH);
+                }
+                0
+            }
+            _ => -ENOTTY,
+        }
+    }
+}
+
+/// 控制台终端的串口中断处理者
+pub struct ConsoleIrqHandler;
+
+impl IrqHandler for ConsoleIrqHandler {
+    /// 处理串口中断，并把收到的字节交给控制台终端的行规程
+    fn handle_irq(&self) {
+        SERIAL.handle_irq();
+        while let Some(ch) = SERIAL.read() {
+            TTY.input(&[ch]);
+        }
+    }
+}
+
+/// 向进程及其全部后代发送信号
+fn signal_process_tree(process: &Arc<ProcessControlBlock>, signal: SignalFlags) {
+    signal_process(process, signal.lowest_signum().unwrap(), 0);
+    let children = process.inner_exclusive_access().children.clone();
+    for child in children.iter() {
+        signal_process_tree(child, signal);
+    }
+}
@@ -0,0 +1,173 @@
+//! # 错误码
+//!
+//! 系统调用失败时返回负的错误码，编号与 Linux 的 `errno` 一致，
+//! 用户态可以直接按 Linux 的含义解释返回值。
+//!
+//! ## 使用方式
+//!
+//! 各模块按需引入错误码，取负即得到系统调用的返回值：
+//!
+//! ```rust
+//! use crate::errno::Errno::{EBADF, EINVAL};
+//!
+//! fn check_fd(fd: usize) -> Result<usize, isize> {
+//!     if fd > 1024 {
+//!         return Err(-EINVAL);
+//!     }
+//!     Ok(fd)
+//! }
+//! ```
+
+use core::ops::Neg;
+
+/// Linux 错误码
+///
+/// 只列出内核实际会返回的错误码；判别值即 Linux 的编号。
+#[repr(isize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum Errno {
+    /// 操作不允许
+    EPERM = 1,
+    /// 文件不存在
+    ENOENT = 2,
+    /// 进程不存在
+    ESRCH = 3,
+    /// 被信号中断
+    EINTR = 4,
+    /// 输入输出错误
+    EIO = 5,
+    /// 设备不存在
+    ENXIO = 6,
+    /// 参数列表过长
+    E2BIG = 7,
+    /// 可执行文件格式错误
+    ENOEXEC = 8,
+    /// 文件描述符无效
+    EBADF = 9,
+    /// 没有子进程
+    ECHILD = 10,
+    /// 资源暂时不可用
+    EAGAIN = 11,
+    /// 内存不足
+    ENOMEM = 12,
+    /// 地址无效
+    EFAULT = 14,
+    /// 资源忙
+    EBUSY = 16,
+    /// 文件已存在
+    EEXIST = 17,
+    /// 参数无效
+    EINVAL = 22,
+    /// 文件描述符已达上限
+    EMFILE = 24,
+    /// 不是终端
+    ENOTTY = 25,
+    /// 文件过大
+    EFBIG = 27,
+    /// 设备空间不足
+    ENOSPC = 28,
+    /// 管道或连接的读端已关闭
+    EPIPE = 32,
+    /// 文件名过长
+    ENAMETOOLONG = 36,
+    /// 系统调用未实现
+    ENOSYS = 38,
+    /// 文件描述符不是套接字
+    ENOTSOCK = 88,
+    /// 需要目的地址
+    EDESTADDRREQ = 89,
+    /// 消息过长
+    EMSGSIZE = 90,
+    /// 套接字类型不匹配
+    EPROTOTYPE = 91,
+    /// 不支持的协议
+    EPROTONOSUPPORT = 93,
+    /// 套接字不支持该操作
+    EOPNOTSUPP = 95,
+    /// 不支持的地址族
+    EAFNOSUPPORT = 97,
+    /// 地址已被使用
+    EADDRINUSE = 98,
+    /// 地址不可用
+    EADDRNOTAVAIL = 99,
+    /// 网络不可达
+    ENETUNREACH = 101,
+    /// 连接被对端重置
+    ECONNRESET = 104,
+    /// 套接字已连接
+    EISCONN = 106,
+    /// 套接字未连接
+    ENOTCONN = 107,
+    /// 连接超时
+    ETIMEDOUT = 110,
+    /// 连接被拒绝
+    ECONNREFUSED = 111,
+    /// 连接已在进行中
+    EALREADY = 114,
+    /// 非阻塞连接正在建立
+    EINPROGRESS = 115,
+}
+
+impl Errno {
+    /// 由编号查找错误码
+    ///
+    /// ## Returns
+    ///
+    /// 对应的错误码；未列出的编号返回 `None`
+    pub fn from_code(code: isize) -> Option<Self> {
+        use Errno::*;
+        match code {
+            1 => Some(EPERM),
+            2 => Some(ENOENT),
+            3 => Some(ESRCH),
+            4 => Some(EINTR),
+            5 => Some(EIO),
+            6 => Some(ENXIO),
+            7 => Some(E2BIG),
+            8 => Some(ENOEXEC),
+            9 => Some(EBADF),
+            10 => Some(ECHILD),
+            11 => Some(EAGAIN),
+            12 => Some(ENOMEM),
+            14 => Some(EFAULT),
+            16 => Some(EBUSY),
+            17 => Some(EEXIST),
+            22 => Some(EINVAL),
+            24 => Some(EMFILE),
+            25 => Some(ENOTTY),
+            27 => Some(EFBIG),
+            28 => Some(ENOSPC),
+            32 => Some(EPIPE),
+            36 => Some(ENAMETOOLONG),
+            38 => Some(ENOSYS),
+            88 => Some(ENOTSOCK),
+            89 => Some(EDESTADDRREQ),
+            90 => Some(EMSGSIZE),
+            91 => Some(EPROTOTYPE),
+            93 => Some(EPROTONOSUPPORT),
+            95 => Some(EOPNOTSUPP),
+            97 => Some(EAFNOSUPPORT),
+            98 => Some(EADDRINUSE),
+            99 => Some(EADDRNOTAVAIL),
+            101 => Some(ENETUNREACH),
+            104 => Some(ECONNRESET),
+            106 => Some(EISCONN),
+            107 => Some(ENOTCONN),
+            110 => Some(ETIMEDOUT),
+            111 => Some(ECONNREFUSED),
+            114 => Some(EALREADY),
+            115 => Some(EINPROGRESS),
+            _ => None,
+        }
+    }
+}
+
+impl Neg for Errno {
+    type Output = isize;
+
+    /// 系统调用返回值：负的错误码
+    fn neg(self) -> isize {
+        -(self as isize)
+    }
+}
@@ -0,0 +1,361 @@
+//! # epoll
+//!
+//! epoll 实例是一个 [`File`]：兴趣集合跟随文件描述符在 `fork` 时共享、
+//! 在 `dup` 时复制，最后一个引用关闭时释放。
+//!
+//! ## 就绪列表
+//!
+//! 每个兴趣项 ([`EpollItem`]) 在加入时通过 `poll_wait` 找到文件的等待队列，
+//! 并在这些队列上挂接自己作为监听者。文件状态改变（管道写入、终端输入等）
+//! 唤醒队列时，兴趣项把自己放入实例的就绪列表并唤醒 `epoll_pwait` 的等待者。
+//! 等待者只检查就绪列表中的项，不会逐个扫描全部兴趣项。
+//!
+//! ## 触发模式
+//!
+//! - **水平触发**（默认）：报告后兴趣项留在就绪列表中，下次等待时重新检查，
+//!   只要文件仍然就绪就会再次报告
+//! - **边沿触发** (`EPOLLET`)：报告后离开就绪列表，直到文件的队列再次被唤醒
+//! - **一次性** (`EPOLLONESHOT`)：报告一次后停用，直到 `EPOLL_CTL_MOD` 重新设置
+//!
+//! ## 生命周期
+//!
+//! 兴趣项只持有文件的弱引用，不影响文件的关闭（例如不会阻止管道读端看到挂断）。
+//! 文件的最后一个引用被释放后，兴趣项在下次检查时自动移除。
+
+use super::{File, PollEvents, PollTable};
+use crate::errno::Errno::{EEXIST, EINTR, EINVAL, ENOENT};
+use crate::process::current_signal_pending;
+use crate::sync::{UPSafeCell, WaitQueue, WakeWatcher};
+use crate::timer::time_ms;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+
+/// 添加兴趣项
+pub const EPOLL_CTL_ADD: usize = 1;
+/// 删除兴趣项
+pub const EPOLL_CTL_DEL: usize = 2;
+/// 修改兴趣项关心的事件与用户数据
+pub const EPOLL_CTL_MOD: usize = 3;
+
+/// 报告一次后停用兴趣项
+pub const EPOLLONESHOT: u32 = 1 << 30;
+/// 边沿触发
+pub const EPOLLET: u32 = 1 << 31;
+
+/// 用户空间的 `struct epoll_event`（RISC-V 上不是紧凑布局）
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EpollEvent {
+    /// 关心的事件（`EPOLLIN` 等与 `POLL*` 取值相同）及 `EPOLLET` 等标志；
+    /// 返回时为就绪的事件
+    pub events: u32,
+    /// 用户数据，原样返回
+    pub data: u64,
+}
+
+/// 兴趣项的可变状态
+struct EpollItemState {
+    /// 关心的事件与触发标志
+    events: u32,
+    data: u64,
+    /// 是否在就绪列表中
+    queued: bool,
+}
+
+/// 兴趣项：被监视的文件及其关心的事件
+struct EpollItem {
+    fd: usize,
+    file: Weak<dyn File + Send + Sync>,
+    epoll: Weak<Epoll>,
+    state: UPSafeCell<EpollItemState>,
+}
+
+impl EpollItem {
+    /// 关心的事件，错误与挂断总是报告；一次性兴趣项报告后不再关心任何事件
+    fn interest(&self) -> PollEvents {
+        let events = self.state.exclusive_access().events;
+        let interest = PollEvents::from_bits_truncate(events as u16);
+        if interest.is_empty() && events & EPOLLONESHOT != 0 {
+            interest
+        } else {
+            interest | PollEvents::always()
+        }
+    }
+
+    /// 检查文件当前就绪的事件；文件已释放时返回 `None`
+    fn poll(&self) -> Option<PollEvents> {
+        let file = self.file.upgrade()?;
+        let interest = self.interest();
+        Some(file.poll() & interest)
+    }
+}
+
+impl WakeWatcher for EpollItem {
+    /// 文件的队列被唤醒：把兴趣项放入就绪列表
+    fn notify(self: Arc<Self>) {
+        if let Some(epoll) = self.epoll.upgrade() {
+            epoll.enqueue(&self);
+        }
+    }
+}
+
+struct EpollInner {
+    /// 按文件描述符索引的兴趣集合
+    interest: BTreeMap<usize, Arc<EpollItem>>,
+    /// 可能就绪、等待检查的兴趣项
+    ready: VecDeque<Arc<EpollItem>>,
+}
+
+/// epoll 实例
+pub struct Epoll {
+    inner: UPSafeCell<EpollInner>,
+    /// `epoll_pwait` 中的等待者，以及 `poll` 该实例的调用者
+    waiters: WaitQueue,
+    me: Weak<Epoll>,
+}
+
+impl Epoll {
+    /// 创建空的 epoll 实例
+    pub fn new() -> Arc<Self> {
+        Arc::new_cyclic(|me| Self {
+            inner: unsafe {
+                UPSafeCell::new(EpollInner {
+                    interest: BTreeMap::new(),
+                    ready: VecDeque::new(),
+                })
+            },
+            waiters: WaitQueue::new(),
+            me: me.clone(),
+        })
+    }
+
+    /// 把仍在兴趣集合中的项放入就绪列表并唤醒等待者
+    fn enqueue(&self, item: &Arc<EpollItem>) {
+        let mut inner = self.inner.exclusive_access();
+        let registered = inner
+            .interest
+            .get(&item.fd)
+            .is_some_and(|registered| Arc::ptr_eq(registered, item));
+        let mut state = item.state.exclusive_access();
+        if !registered || state.queued {
+            return;
+        }
+        state.queued = true;
+        drop(state);
+        inner.ready.push_back(item.clone());
+        drop(inner);
+        self.waiters.wake_all();
+    }
+
+    /// 从兴趣集合中移除文件已释放的项
+    fn forget(&self, item: &Arc<EpollItem>) {
+        let mut inner = self.inner.exclusive_access();
+        if inner
+            .interest
+            .get(&item.fd)
+            .is_some_and(|registered| Arc::ptr_eq(registered, item))
+        {
+            inner.interest.remove(&item.fd);
+        }
+    }
+
+    /// 修改兴趣集合
+    ///
+    /// ## Arguments
+    ///
+    /// * `op` - `EPOLL_CTL_ADD` / `EPOLL_CTL_DEL` / `EPOLL_CTL_MOD`
+    /// * `fd` / `file` - 被监视的文件描述符及其文件
+    /// * `event` - 关心的事件与用户数据，`EPOLL_CTL_DEL` 时忽略
+    ///
+    /// ## Returns
+    ///
+    /// - 0：成功
+    /// - `-EEXIST`：添加已在集合中的描述符
+    /// - `-ENOENT`：修改或删除不在集合中的描述符
+    /// - `-EINVAL`：不支持的操作
+    pub fn ctl(
+        &self,
+        op: usize,
+        fd: usize,
+        file: &Arc<dyn File + Send + Sync>,
+        event: EpollEvent,
+    ) -> isize {
+        let mut inner = self.inner.exclusive_access();
+        // 描述符号被复用时，旧文件已释放的兴趣项不再算作已存在
+        let existing = inner
+            .interest
+            .get(&fd)
+            .filter(|item| item.file.strong_count() > 0)
+            .cloned();
+        match op {
+            EPOLL_CTL_ADD => {
+                if existing.is_some() {
+                    return -EEXIST;
+                }
+                let item = Arc::new(EpollItem {
+                    fd,
+                    file: Arc::downgrade(file),
+                    epoll: self.me.clone(),
+                    state: unsafe {
+                        UPSafeCell::new(EpollItemState {
+                            events: event.events,
+                            data: event.data,
+                            queued: false,
+                        })
+                    },
+                });
+                inner.interest.insert(fd, item.clone());
+                drop(inner);
+                let mut table = PollTable::new();
+                file.poll_wait(&mut table);
+                table.watch(Arc::downgrade(&item) as Weak<dyn WakeWatcher>);
+                if item.poll().is_some_and(|events| !events.is_empty()) {
+                    self.enqueue(&item);
+                }
+                0
+            }
+            EPOLL_CTL_MOD => {
+                let Some(item) = existing else {
+                    return -ENOENT;
+                };
+                drop(inner);
+                let mut state = item.state.exclusive_access();
+                state.events = event.events;
+                state.data = event.data;
+                drop(state);
+                if item.poll().is_some_and(|events| !events.is_empty()) {
+                    self.enqueue(&item);
+                }
+                0
+            }
+            EPOLL_CTL_DEL => {
+                if existing.is_none() {
+                    return -ENOENT;
+                }
+                // 已释放的兴趣项随之失效，文件队列上的弱引用会被自动清理
+                inner.interest.remove(&fd);
+                inner.ready.retain(|item| item.fd != fd);
+                0
+            }
+            _ => -EINVAL,
+        }
+    }
+
+    /// 取出就绪列表中确实就绪的事件，最多 `max` 个
+    ///
+    /// 检查文件状态时不持有实例的锁：文件可能在检查后被释放，
+    /// 其 `Drop` 唤醒队列时会回调 [`enqueue`](Self::enqueue)。
+    fn harvest(&self, max: usize) -> Vec<EpollEvent> {
+        let candidates = core::mem::take(&mut self.inner.exclusive_access().ready);
+        for item in candidates.iter() {
+            item.state.exclusive_access().queued = false;
+        }
+        let mut events = Vec::new();
+        // 超出 `max` 未检查的项排在重新入队的水平触发项之前，避免后者独占结果
+        let mut unchecked = Vec::new();
+        let mut requeue = Vec::new();
+        for item in candidates {
+            if events.len() == max {
+                unchecked.push(item);
+                continue;
+            }
+            let Some(revents) = item.poll() else {
+                self.forget(&item);
+                continue;
+            };
+            if revents.is_empty() {
+                continue;
+            }
+            let mut state = item.state.exclusive_access();
+            events.push(EpollEvent {
+                events: revents.bits() as u32,
+                data: state.data,
+            });
+            if state.events & EPOLLONESHOT != 0 {
+                state.events &= EPOLLONESHOT | EPOLLET;
+            } else if state.events & EPOLLET == 0 {
+                drop(state);
+                requeue.push(item);
+            }
+        }
+        for item in unchecked.into_iter().chain(requeue) {
+            self.enqueue(&item);
+        }
+        events
+    }
+
+    /// 等待兴趣集合中的文件就绪
+    ///
+    /// ## Arguments
+    ///
+    /// * `max` - 最多返回的事件数，大于 0
+    /// * `timeout_ms` - 超时毫秒数，`None` 表示一直等待
+    ///
+    /// ## Returns
+    ///
+    /// 就绪的事件，超时返回空；等待期间收到需要处理的信号时返回 `Err(-EINTR)`
+    pub fn wait(&self, max: usize, timeout_ms: Option<usize>) -> Result<Vec<EpollEvent>, isize> {
+        let deadline = timeout_ms.map(|timeout_ms| time_ms() + timeout_ms);
+        loop {
+            let events = self.harvest(max);
+            if !events.is_empty() {
+                return Ok(events);
+            }
+            if deadline.is_some_and(|deadline| time_ms() >= deadline) {
+                return Ok(events);
+            }
+            if current_signal_pending() {
+                return Err(-EINTR);
+            }
+            let mut table = PollTable::new();
+            table.add(&self.waiters);
+            table.wait(deadline);
+        }
+    }
+}
+
+impl File for Epoll {
+    fn read(&self, _buf: crate::mm::UserBuffer) -> isize {
+        -EINVAL
+    }
+
+    fn write(&self, _buf: crate::mm::UserBuffer) -> isize {
+        -EINVAL
+    }
+
+    fn readable(&self) -> bool {
+        false
+    }
+
+    fn writable(&self) -> bool {
+        false
+    }
+
+    /// 就绪列表中有确实就绪的兴趣项时可读，用于嵌套在 `poll` 或其他 epoll 中
+    fn poll(&self) -> PollEvents {
+        let candidates: Vec<_> = self
+            .inner
+            .exclusive_access()
+            .ready
+            .iter()
+            .cloned()
+            .collect();
+        if candidates
+            .iter()
+            .any(|item| item.poll().is_some_and(|events| !events.is_empty()))
+        {
+            PollEvents::POLLIN
+        } else {
+            PollEvents::empty()
+        }
+    }
+
+    fn poll_wait<'a>(&'a self, table: &mut PollTable<'a>) {
+        table.add(&self.waiters);
+    }
+
+    fn as_epoll(&self) -> Option<&Epoll> {
+        Some(self)
+    }
+}
@@ -0,0 +1,80 @@
+//! # 命名管道（FIFO）
+//!
+//! 命名管道在 Micro-FS 中只是一个类型为 FIFO 的 inode，不保存任何数据。
+//! 打开时按 inode 编号在 [`FIFO_TABLE`] 中找到共享的 [`PipeRingBuffer`]，
+//! 没有则新建；之后的读写与匿名管道完全相同。所有端点关闭后缓冲区随之释放，
+//! 未读的数据被丢弃。
+//!
+//! ## 打开语义
+//!
+//! - 只读打开：阻塞直到有写端打开；`O_NONBLOCK` 时立即返回
+//! - 只写打开：阻塞直到有读端打开；`O_NONBLOCK` 且没有读端时返回 `-ENXIO`
+//! - 读写打开：立即返回，端点自身既是读端也是写端
+//! - 阻塞期间收到需要处理的信号时返回 `-EINTR`
+
+use super::OpenFlags;
+use super::pipe::{PIPE_DEFAULT_PAGES, Pipe, PipeRingBuffer};
+use crate::errno::Errno::{ENOMEM, ENXIO};
+use crate::sync::{UPSafeCell, WaitQueue};
+use alloc::collections::BTreeMap;
+use alloc::sync::{Arc, Weak};
+use lazy_static::*;
+
+/// 命名管道的共享缓冲区及其等待队列
+struct FifoBuffer {
+    buffer: Weak<UPSafeCell<PipeRingBuffer>>,
+    pollers: Weak<WaitQueue>,
+}
+
+lazy_static! {
+    /// inode 编号到共享缓冲区的映射，只包含仍有端点打开的命名管道
+    static ref FIFO_TABLE: UPSafeCell<BTreeMap<u32, FifoBuffer>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+}
+
+/// 取得 inode 对应的共享缓冲区，没有端点打开时新建
+fn fifo_buffer(inode_id: u32) -> Option<(Arc<UPSafeCell<PipeRingBuffer>>, Arc<WaitQueue>)> {
+    let mut table = FIFO_TABLE.exclusive_access();
+    if let Some(fifo) = table.get(&inode_id) {
+        if let (Some(buffer), Some(pollers)) = (fifo.buffer.upgrade(), fifo.pollers.upgrade()) {
+            return Some((buffer, pollers));
+        }
+    }
+    table.retain(|_, fifo| fifo.buffer.strong_count() > 0);
+    let buffer = Arc::new(unsafe { UPSafeCell::new(PipeRingBuffer::new(PIPE_DEFAULT_PAGES)?) });
+    let pollers = Arc::new(WaitQueue::new());
+    table.insert(
+        inode_id,
+        FifoBuffer {
+            buffer: Arc::downgrade(&buffer),
+            pollers: Arc::downgrade(&pollers),
+        },
+    );
+    Some((buffer, pollers))
+}
+
+/// 打开命名管道
+///
+/// 调用者不能持有文件系统锁：打开可能阻塞到对端出现。
+///
+/// ## Arguments
+/// * `inode_id` - 命名管道的 inode 编号
+/// * `flags` - 打开标志，决定端点的读写方向与是否阻塞
+///
+/// ## Returns
+/// - `Err(-ENXIO)`：非阻塞只写打开时没有读端
+/// - `Err(-EINTR)`：等待对端时被信号打断
+/// - `Err(-ENOMEM)`：物理页帧不足
+pub fn open_fifo(inode_id: u32, flags: OpenFlags) -> Result<Arc<Pipe>, isize> {
+    let (buffer, pollers) = fifo_buffer(inode_id).ok_or(-ENOMEM)?;
+    let (readable, writable) = flags.read_write();
+    let nonblocking = flags.contains(OpenFlags::NONBLOCK);
+    if nonblocking && writable && !readable && buffer.exclusive_access().all_read_ends_closed() {
+        return Err(-ENXIO);
+    }
+    let pipe = Arc::new(Pipe::with_buffer(buffer, pollers, flags));
+    if !nonblocking {
+        pipe.wait_for_peer()?;
+    }
+    Ok(pipe)
+}
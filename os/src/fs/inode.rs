@@ -9,7 +9,15 @@
 //! - [`OSInodeInner`] - inode 的内部状态管理
 //! - [`OpenFlags`] - 文件打开标志位，控制文件的打开模式
 //! - [`ROOT_INODE`] - 全局根目录 inode 实例
+//! - [`FS_LOCK`] - 串行化所有文件系统访问的睡眠锁
+//! - [`open_path`] / [`make_node`] - 打开、创建普通文件或命名管道
+//! - [`bind_socket_node`] / [`find_socket_node`] - Unix 域套接字的地址文件
 //!
+//! ## 并发访问
+//!
+//! 块设备读写会让调用进程睡眠等待中断，而 Micro-FS 内部使用自旋锁。
+//! 为避免其他进程在持锁者睡眠时自旋，所有进入 Micro-FS 的操作都先获取
+//! [`FS_LOCK`]，竞争者在其上睡眠。
 //! ## 文件操作特性
 //!
 //! - **读写权限**: 支持独立的读写权限控制
@@ -32,15 +40,18 @@
 //! list_apps();
 //! ```
 
-use super::File;
+use super::fifo::open_fifo;
+use super::{File, FileStatus};
 use crate::drivers::BLOCK_DEVICE;
+use crate::errno::Errno::{EADDRINUSE, ECONNREFUSED, EEXIST, EFBIG, EINVAL, ENOENT, ENXIO, EPERM};
 use crate::mm::UserBuffer;
 use crate::println;
-use crate::sync::UPSafeCell;
+use crate::process::{RLIMIT_FSIZE, SignalFlags, current_rlimit, current_send_signal};
+use crate::sync::{SleepLock, UPSafeCell};
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use bitflags::*;
-use components::micro_fs::{Inode, BlockManager};
+use components::micro_fs::{BlockManager, Inode};
 use lazy_static::*;
 
 /// OSInode 的内部状态结构
@@ -84,19 +95,19 @@ pub struct OSInodeInner {
 pub struct OSInode {
     readable: bool,
     writable: bool,
+    status: FileStatus,
     inner: UPSafeCell<OSInodeInner>,
 }
 
 impl OSInode {
     /// 创建新的 OSInode 实例
     ///
-    /// 根据指定的读写权限和底层 inode 创建新的文件实例。
+    /// 根据打开标志和底层 inode 创建新的文件实例。
     /// 新创建的文件偏移量初始化为 0。
     ///
     /// ## Arguments
     ///
-    /// * `readable` - 文件是否可读
-    /// * `writable` - 文件是否可写
+    /// * `flags` - 打开标志，决定读写权限与初始的文件状态标志
     /// * `inode` - 底层 Micro-FS inode 的引用
     ///
     /// ## Returns
@@ -106,19 +117,21 @@ impl OSInode {
     /// ## 初始化状态
     ///
     /// - 文件偏移量设置为 0
-    /// - 读写权限根据参数设置
+    /// - 读写权限与状态标志根据打开标志设置
     /// - 内部状态通过 `UPSafeCell` 保护
     ///
     /// ## Examples
     ///
     /// ```
     /// let inode = get_some_inode();
-    /// let file = OSInode::new(true, false, inode); // 只读文件
+    /// let file = OSInode::new(OpenFlags::RDONLY, inode); // 只读文件
     /// ```
-    pub fn new(readable: bool, writable: bool, inode: Arc<Inode>) -> Self {
+    pub fn new(flags: OpenFlags, inode: Arc<Inode>) -> Self {
+        let (readable, writable) = flags.read_write();
         Self {
             readable,
             writable,
+            status: FileStatus::new(flags),
             inner: unsafe { UPSafeCell::new(OSInodeInner { offset: 0, inode }) },
         }
     }
@@ -156,6 +169,7 @@ impl OSInode {
     /// println!("文件大小: {} 字节", content.len());
     /// ```
     pub fn read_all(&self) -> Vec<u8> {
+        let _fs = FS_LOCK.lock();
         let mut inner = self.inner.exclusive_access();
         let mut buffer = [0u8; 512];
         let mut v: Vec<u8> = Vec::new();
@@ -169,6 +183,39 @@ impl OSInode {
         }
         v
     }
+
+    /// 写入文件的实现，见 [`File::write`] 与 [`File::write_nowait`]
+    ///
+    /// `sigxfsz` 为 `true` 时，超出 `RLIMIT_FSIZE` 而无法写入会向当前进程投递 `SIGXFSZ`。
+    fn write_limited(&self, buf: UserBuffer, sigxfsz: bool) -> isize {
+        let fsize_limit = current_rlimit(RLIMIT_FSIZE);
+        let _fs = FS_LOCK.lock();
+        let mut inner = self.inner.exclusive_access();
+        if self.status.append() {
+            inner.offset = inner.inode.size();
+        }
+        if buf.len() > 0 && inner.offset >= fsize_limit {
+            drop(inner);
+            if sigxfsz {
+                current_send_signal(SignalFlags::SIGXFSZ);
+            }
+            return -EFBIG;
+        }
+        let mut total_write_size = 0usize;
+        for slice in buf.buffers.iter() {
+            let allowed = fsize_limit.saturating_sub(inner.offset).min(slice.len());
+            if allowed > 0 {
+                let write_size = inner.inode.write_at(inner.offset, &slice[..allowed]);
+                assert_eq!(write_size, allowed);
+                inner.offset += write_size;
+                total_write_size += write_size;
+            }
+            if allowed < slice.len() {
+                break;
+            }
+        }
+        total_write_size as isize
+    }
 }
 
 impl File for OSInode {
@@ -197,7 +244,8 @@ impl File for OSInode {
     ///
     /// 如果文件不可读，行为由具体实现定义。
     /// 读取过程中如果遇到错误，会返回已读取的字节数。
-    fn read(&self, mut buf: UserBuffer) -> usize {
+    fn read(&self, mut buf: UserBuffer) -> isize {
+        let _fs = FS_LOCK.lock();
         let mut inner = self.inner.exclusive_access();
         let mut total_read_size = 0usize;
         for slice in buf.buffers.iter_mut() {
@@ -208,7 +256,7 @@ impl File for OSInode {
             inner.offset += read_size;
             total_read_size += read_size;
         }
-        total_read_size
+        total_read_size as isize
     }
 
     /// 向文件写入数据
@@ -222,7 +270,7 @@ impl File for OSInode {
     ///
     /// ## Returns
     ///
-    /// 实际写入的字节数
+    /// 实际写入的字节数；超出 `RLIMIT_FSIZE` 而无法写入时返回 `-EFBIG`
     ///
     /// ## 写入过程
     ///
@@ -236,16 +284,23 @@ impl File for OSInode {
     ///
     /// 如果文件不可写，会触发 panic。
     /// 如果写入的字节数与缓冲区大小不一致，会触发 panic。
-    fn write(&self, buf: UserBuffer) -> usize {
-        let mut inner = self.inner.exclusive_access();
-        let mut total_write_size = 0usize;
-        for slice in buf.buffers.iter() {
-            let write_size = inner.inode.write_at(inner.offset, *slice);
-            assert_eq!(write_size, slice.len());
-            inner.offset += write_size;
-            total_write_size += write_size;
-        }
-        total_write_size
+    ///
+    /// ## 文件大小限制
+    ///
+    /// 写入不会越过当前进程的 `RLIMIT_FSIZE`：限制内还能写入部分数据时
+    /// 只写入这一部分并返回较短的字节数；一个字节都不能写入时向当前进程
+    /// 投递 `SIGXFSZ` 并返回 `-EFBIG`。
+    ///
+    /// ## 追加模式
+    ///
+    /// 设置了 `O_APPEND` 时，写入前先把偏移量移到文件末尾。
+    fn write(&self, buf: UserBuffer) -> isize {
+        self.write_limited(buf, true)
+    }
+
+    /// 内核写入：超出 `RLIMIT_FSIZE` 时只返回 `-EFBIG`，不投递 `SIGXFSZ`
+    fn write_nowait(&self, buf: UserBuffer) -> isize {
+        self.write_limited(buf, false)
     }
 
     /// 检查文件是否可读
@@ -265,9 +320,20 @@ impl File for OSInode {
     fn writable(&self) -> bool {
         self.writable
     }
+
+    /// 读取或修改文件状态标志
+    fn fcntl(&self, cmd: usize, arg: usize) -> isize {
+        self.status.fcntl(cmd, arg, self.readable, self.writable)
+    }
 }
 
 lazy_static! {
+    /// 文件系统睡眠锁
+    ///
+    /// 访问 [`ROOT_INODE`] 或任何 [`OSInode`] 的底层 inode 之前必须持有，
+    /// 持有期间允许因块设备 I/O 睡眠。
+    pub static ref FS_LOCK: SleepLock<()> = SleepLock::new(());
+
     /// 全局根目录 Inode 实例
     ///
     /// 使用 `lazy_static` 实现全局单例模式，确保整个系统共享同一个根目录 inode。
@@ -355,6 +421,7 @@ lazy_static! {
 /// //       /**************/
 /// ```
 pub fn list_apps() {
+    let _fs = FS_LOCK.lock();
     println!("/**** APPS ****/");
     for app in ROOT_INODE.ls() {
         println!("{}", app);
@@ -375,6 +442,11 @@ bitflags! {
     /// - `RDWR` - 读写模式，文件既可以读取也可以写入
     /// - `CREATE` - 创建标志，如果文件不存在则创建新文件
     /// - `TRUNC` - 截断标志，如果文件存在则清空文件内容
+    /// - `APPEND` - 追加模式，每次写入前把偏移量移到文件末尾
+    /// - `NONBLOCK` - 非阻塞模式，读写需要等待时返回 `EAGAIN`
+    /// - `CLOEXEC` - 为新文件描述符设置 `FD_CLOEXEC`，`exec` 时自动关闭
+    ///
+    /// `APPEND` 与 `NONBLOCK` 是文件状态标志，打开后可以通过 `F_SETFL` 修改。
     ///
     /// ## 组合使用
     ///
@@ -385,12 +457,16 @@ bitflags! {
     /// ## 默认行为
     ///
     /// 如果不指定任何标志位（空标志），默认为只读模式。
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct OpenFlags: u32 {
         const RDONLY = 0;
         const WRONLY = 1 << 0;
         const RDWR = 1 << 1;
         const CREATE = 1 << 9;
         const TRUNC = 1 << 10;
+        const NONBLOCK = 1 << 11;
+        const APPEND = 1 << 12;
+        const CLOEXEC = 1 << 19;
     }
 }
 
@@ -411,6 +487,8 @@ impl OpenFlags {
     /// - `WRONLY`：`(false, true)` - 只写模式
     /// - `RDWR` 或其他组合：`(true, true)` - 读写模式
     ///
+    /// 文件状态标志与 `CLOEXEC` 不参与解析。
+    ///
     /// ## Examples
     ///
     /// ```
@@ -420,14 +498,20 @@ impl OpenFlags {
     /// assert_eq!(writable, true);
     /// ```
     pub fn read_write(&self) -> (bool, bool) {
-        if self.is_empty() {
+        let mode = self.difference(Self::status() | Self::CLOEXEC);
+        if mode.is_empty() {
             (true, false)
-        } else if self.contains(Self::WRONLY) {
+        } else if mode.contains(Self::WRONLY) {
             (false, true)
         } else {
             (true, true)
         }
     }
+
+    /// 打开后可以通过 `F_SETFL` 修改的文件状态标志
+    pub fn status() -> Self {
+        Self::APPEND | Self::NONBLOCK
+    }
 }
 
 /// 打开文件
@@ -443,7 +527,8 @@ impl OpenFlags {
 /// ## Returns
 ///
 /// - `Some(file)` - 成功打开文件，返回文件操作接口
-/// - `None` - 文件不存在且未指定 `CREATE` 标志，或创建失败
+/// - `None` - 文件不存在且未指定 `CREATE` 标志，或创建失败；
+///   同名文件是命名管道或套接字时也返回 `None`，命名管道由 [`open_path`] 打开
 ///
 /// ## 打开流程
 ///
@@ -480,22 +565,135 @@ impl OpenFlags {
 /// let file = open_file("log.txt", OpenFlags::WRONLY | OpenFlags::TRUNC);
 /// ```
 pub fn open_file(name: &str, flags: OpenFlags) -> Option<Arc<OSInode>> {
-    let (readable, writable) = flags.read_write();
+    let _fs = FS_LOCK.lock();
     if flags.contains(OpenFlags::CREATE) {
         if let Some(inode) = ROOT_INODE.find(name) {
+            if is_special(&inode) {
+                return None;
+            }
             inode.clear();
-            Some(Arc::new(OSInode::new(readable, writable, inode)))
+            Some(Arc::new(OSInode::new(flags, inode)))
         } else {
             ROOT_INODE
                 .create(name)
-                .map(|inode| Arc::new(OSInode::new(readable, writable, inode)))
+                .map(|inode| Arc::new(OSInode::new(flags, inode)))
         }
     } else {
-        ROOT_INODE.find(name).map(|inode| {
+        let inode = ROOT_INODE.find(name).filter(|inode| !is_special(inode));
+        inode.map(|inode| {
             if flags.contains(OpenFlags::TRUNC) {
                 inode.clear();
             }
-            Arc::new(OSInode::new(readable, writable, inode))
+            Arc::new(OSInode::new(flags, inode))
         })
     }
 }
+
+/// 文件类型掩码
+const S_IFMT: u32 = 0o170000;
+/// 文件类型：命名管道
+const S_IFIFO: u32 = 0o010000;
+/// 文件类型：字符设备
+const S_IFCHR: u32 = 0o020000;
+/// 文件类型：块设备
+const S_IFBLK: u32 = 0o060000;
+/// 文件类型：普通文件
+const S_IFREG: u32 = 0o100000;
+/// 文件类型：套接字
+const S_IFSOCK: u32 = 0o140000;
+
+/// 命名管道与套接字不能作为普通文件打开
+fn is_special(inode: &Inode) -> bool {
+    inode.is_fifo() || inode.is_socket()
+}
+
+/// 打开磁盘上的文件
+///
+/// 与 [`open_file`] 相同，但同名文件是命名管道时按 [`open_fifo`] 的语义打开，
+/// 可能阻塞到对端出现；等待期间不持有 [`FS_LOCK`]。
+///
+/// ## Returns
+/// 文件不存在或创建失败时返回 `Err(-ENOENT)`，同名文件是套接字时返回 `Err(-ENXIO)`，
+/// 命名管道的错误见 [`open_fifo`]
+pub fn open_path(name: &str, flags: OpenFlags) -> Result<Arc<dyn File + Send + Sync>, isize> {
+    let fs = FS_LOCK.lock();
+    let inode = ROOT_INODE.find(name);
+    if inode.as_ref().is_some_and(|inode| inode.is_socket()) {
+        return Err(-ENXIO);
+    }
+    let fifo = inode
+        .filter(|inode| inode.is_fifo())
+        .map(|inode| inode.inode_number());
+    drop(fs);
+    match fifo {
+        Some(inode_id) => {
+            open_fifo(inode_id, flags).map(|pipe| pipe as Arc<dyn File + Send + Sync>)
+        }
+        None => open_file(name, flags)
+            .map(|inode| inode as Arc<dyn File + Send + Sync>)
+            .ok_or(-ENOENT),
+    }
+}
+
+/// 在根目录下创建文件节点
+///
+/// ## Arguments
+/// * `name` - 文件名
+/// * `mode` - 文件类型与权限位，只使用其中的文件类型；类型为 0 时视为普通文件
+///
+/// ## Returns
+/// - 0：成功
+/// - `-EEXIST`：同名文件已存在
+/// - `-EPERM`：设备文件与套接字不能在磁盘上创建
+/// - `-EINVAL`：无效的文件类型
+pub fn make_node(name: &str, mode: u32) -> isize {
+    let _fs = FS_LOCK.lock();
+    let inode = match mode & S_IFMT {
+        S_IFIFO => ROOT_INODE.create_fifo(name),
+        0 | S_IFREG => ROOT_INODE.create(name),
+        S_IFCHR | S_IFBLK | S_IFSOCK => return -EPERM,
+        _ => return -EINVAL,
+    };
+    if inode.is_some() { 0 } else { -EEXIST }
+}
+
+/// 为绑定的 Unix 域套接字在根目录下创建套接字文件
+///
+/// 没有 `unlink`，套接字文件在绑定者关闭后仍然存在；`in_use` 报告其不再被
+/// 任何套接字绑定时，允许新的套接字复用它。
+///
+/// ## Arguments
+/// * `name` - 文件名
+/// * `in_use` - 判断 inode 编号是否仍被某个套接字绑定
+///
+/// ## Returns
+/// 套接字文件的 inode 编号；同名文件已存在且不可复用时返回 `Err(-EADDRINUSE)`
+pub fn bind_socket_node(name: &str, in_use: impl Fn(u32) -> bool) -> Result<u32, isize> {
+    let _fs = FS_LOCK.lock();
+    if let Some(inode) = ROOT_INODE.find(name) {
+        let inode_id = inode.inode_number();
+        return if inode.is_socket() && !in_use(inode_id) {
+            Ok(inode_id)
+        } else {
+            Err(-EADDRINUSE)
+        };
+    }
+    ROOT_INODE
+        .create_socket(name)
+        .map(|inode| inode.inode_number())
+        .ok_or(-EADDRINUSE)
+}
+
+/// 查找套接字文件的 inode 编号，供 `connect`/`sendto` 使用
+///
+/// ## Returns
+/// - `Err(-ENOENT)`：文件不存在
+/// - `Err(-ECONNREFUSED)`：文件不是套接字
+pub fn find_socket_node(name: &str) -> Result<u32, isize> {
+    let _fs = FS_LOCK.lock();
+    let inode = ROOT_INODE.find(name).ok_or(-ENOENT)?;
+    if !inode.is_socket() {
+        return Err(-ECONNREFUSED);
+    }
+    Ok(inode.inode_number())
+}
@@ -7,6 +7,10 @@
 //!
 //! - [`inode`] - 文件 inode 管理，提供文件读写和元数据操作
 //! - [`stdio`] - 标准输入输出设备，包括 stdin 和 stdout
+//! - [`pty`] - 伪终端，`/dev/ptmx` 与 `/dev/pts/N`
+//! - [`poll`] - 文件就绪状态与多路等待，供 `ppoll`/`pselect6` 使用
+//! - [`epoll`] - epoll 实例，兴趣集合与就绪列表
+//! - [`fifo`] - 命名管道，按 inode 共享管道缓冲区
 //!
 //! ## 设计目标
 //!
@@ -23,9 +27,16 @@
 //! - [`Stdin`] / [`Stdout`] - 标准输入输出设备
 //!
 //! ### 文件操作
-//! - [`open_file`] - 打开文件的统一接口
+//! - [`open_file`] - 打开普通文件
+//! - [`open_path`] - 打开磁盘上的普通文件或命名管道，供 `openat` 使用
+//! - [`make_node`] - 在磁盘上创建命名管道或普通文件，供 `mknodat` 使用
+//! - [`open_device`] - 打开 `/dev` 下的设备文件
 //! - [`list_apps`] - 列出应用程序列表
+//! - [`write_kernel_bytes`] - 把内核数据写入文件（核心转储、系统调用跟踪）
 //! - [`OpenFlags`] - 文件打开标志位
+//! - [`FileStatus`] - 文件状态标志（追加、非阻塞），配合 `fcntl` 使用
+//! - [`PollEvents`] / [`PollTable`] - 文件就绪事件与多路等待
+//! - [`Epoll`] - epoll 实例，本身也是一个文件
 //!
 //! ## 文件系统特性
 //!
@@ -53,16 +64,49 @@
 //! let bytes_written = file.write(user_buf);
 //! ```
 
+use crate::errno::Errno::{EINVAL, ENOTTY, EOPNOTSUPP};
 use crate::mm::UserBuffer;
+use crate::net::Socket;
+use crate::sync::UPSafeCell;
+use alloc::sync::Arc;
+use alloc::vec;
 
+mod epoll;
+mod fifo;
 mod inode;
 mod pipe;
+mod poll;
+mod pty;
 mod stdio;
 
-pub use inode::{OpenFlags, list_apps, open_file};
+pub use epoll::{Epoll, EpollEvent};
+pub use inode::{
+    OpenFlags, bind_socket_node, find_socket_node, list_apps, make_node, open_file, open_path,
+};
 pub use pipe::make_pipe;
+pub use poll::{FD_SETSIZE, FdSet, PollEvents, PollFd, PollTable};
+pub use pty::{PtyMaster, PtySlave};
 pub use stdio::{Stderr, Stdin, Stdout};
 
+/// 复制文件描述符，新描述符不小于参数
+pub const F_DUPFD: usize = 0;
+/// 读取文件描述符标志
+pub const F_GETFD: usize = 1;
+/// 设置文件描述符标志
+pub const F_SETFD: usize = 2;
+/// 读取文件状态标志与访问模式
+pub const F_GETFL: usize = 3;
+/// 设置文件状态标志
+pub const F_SETFL: usize = 4;
+/// 同 `F_DUPFD`，并为新描述符设置 `FD_CLOEXEC`
+pub const F_DUPFD_CLOEXEC: usize = 1030;
+/// 设置管道容量
+pub const F_SETPIPE_SZ: usize = 1031;
+/// 读取管道容量
+pub const F_GETPIPE_SZ: usize = 1032;
+/// 文件描述符标志：`exec` 时关闭
+pub const FD_CLOEXEC: usize = 1;
+
 /// 文件抽象接口
 ///
 /// 定义文件的基本操作接口，为不同类型的文件提供统一的抽象。
@@ -80,7 +124,11 @@ pub use stdio::{Stderr, Stdin, Stdout};
 ///
 /// - `read`: 从文件读取数据到用户缓冲区
 /// - `write`: 将用户缓冲区数据写入文件
+/// - `write_nowait`: 内核写入，不阻塞也不投递信号，默认不支持
 /// - `readable` / `writable`: 检查文件的读写权限
+/// - `ioctl`: 设备控制，默认不支持
+/// - `fcntl`: 文件级的 `fcntl` 命令，默认只支持读取访问模式
+/// - `poll` / `poll_wait`: 就绪状态查询与等待，默认总是就绪（普通文件）
 ///
 /// ## 性能考虑
 ///
@@ -103,7 +151,8 @@ pub trait File: Send + Sync {
     /// * `buf` - 用户缓冲区，用于存储读取的数据
     ///
     /// ## Returns
-    /// 实际读取的字节数，0 表示已到达文件末尾
+    /// 实际读取的字节数，0 表示已到达文件末尾；出错时返回负的错误码，
+    /// 如非阻塞模式下没有数据可读时返回 `-EAGAIN`
     ///
     /// ## 行为
     /// - 从当前文件偏移量开始读取数据
@@ -115,7 +164,7 @@ pub trait File: Send + Sync {
     /// - 如果文件不可读，行为由具体实现定义
     /// - 如果缓冲区为空，返回 0
     /// - 如果到达文件末尾，返回 0
-    fn read(&self, buf: UserBuffer) -> usize;
+    fn read(&self, buf: UserBuffer) -> isize;
 
     /// 向文件写入数据
     ///
@@ -126,7 +175,7 @@ pub trait File: Send + Sync {
     /// * `buf` - 用户缓冲区，包含要写入的数据
     ///
     /// ## Returns
-    /// 实际写入的字节数
+    /// 实际写入的字节数；出错时返回负的错误码
     ///
     /// ## 行为
     /// - 从当前文件偏移量开始写入数据
@@ -138,7 +187,18 @@ pub trait File: Send + Sync {
     /// - 如果文件不可写，行为由具体实现定义
     /// - 如果缓冲区为空，返回 0
     /// - 如果磁盘空间不足，返回已写入的字节数
-    fn write(&self, buf: UserBuffer) -> usize;
+    fn write(&self, buf: UserBuffer) -> isize;
+
+    /// 由内核写入数据：不阻塞，也不向当前进程投递信号
+    ///
+    /// 用于系统调用跟踪这类替当前进程之外的一方写出数据的场景：需要等待时返回
+    /// `-EAGAIN`，对端关闭或超出文件大小限制时只返回错误码。
+    ///
+    /// ## Returns
+    /// 实际写入的字节数或负的错误码；默认实现不支持，返回 `-EOPNOTSUPP`
+    fn write_nowait(&self, _buf: UserBuffer) -> isize {
+        -EOPNOTSUPP
+    }
 
     /// 检查文件是否可读
     ///
@@ -151,4 +211,170 @@ pub trait File: Send + Sync {
     /// ## Returns
     /// 如果文件可写返回 `true`，否则返回 `false`
     fn writable(&self) -> bool;
+
+    /// 设备控制
+    ///
+    /// ## Arguments
+    /// * `cmd` - 控制命令
+    /// * `arg` - 命令参数，通常是指向用户空间的指针
+    ///
+    /// ## Returns
+    /// 命令的返回值；默认实现表示该文件不是终端等可控制设备，返回 `-ENOTTY`
+    fn ioctl(&self, _cmd: usize, _arg: usize) -> isize {
+        -ENOTTY
+    }
+
+    /// 文件级的 `fcntl` 命令
+    ///
+    /// 描述符级的命令（`F_DUPFD`、`F_GETFD`、`F_SETFD`）由 `sys_fcntl` 在
+    /// 文件描述符表上完成，其余命令交给文件本身。持有 [`FileStatus`] 的实现
+    /// 应委托给 [`FileStatus::fcntl`]。
+    ///
+    /// ## Arguments
+    /// * `cmd` - 命令，如 `F_GETFL`、`F_SETFL`
+    /// * `arg` - 命令参数
+    ///
+    /// ## Returns
+    /// 默认实现只支持 `F_GETFL`，返回访问模式；其余命令返回 `-EINVAL`
+    fn fcntl(&self, cmd: usize, _arg: usize) -> isize {
+        match cmd {
+            F_GETFL => access_mode(self.readable(), self.writable()).bits() as isize,
+            _ => -EINVAL,
+        }
+    }
+
+    /// 查询当前就绪的事件
+    ///
+    /// 读写不会阻塞的文件（如磁盘上的普通文件）使用默认实现：
+    /// 可读时总是报告 `POLLIN`，可写时总是报告 `POLLOUT`。
+    ///
+    /// ## Returns
+    /// 当前就绪的全部事件，由调用者按关心的事件过滤
+    fn poll(&self) -> PollEvents {
+        let mut events = PollEvents::empty();
+        if self.readable() {
+            events |= PollEvents::POLLIN;
+        }
+        if self.writable() {
+            events |= PollEvents::POLLOUT;
+        }
+        events
+    }
+
+    /// 登记就绪状态改变时会被唤醒的等待队列
+    ///
+    /// 与 [`poll`](Self::poll) 配对实现：`poll` 报告的任一事件可能发生变化时，
+    /// 登记的某个队列必须被唤醒。默认实现不登记，适用于总是就绪的文件。
+    ///
+    /// ## Arguments
+    /// * `table` - 本次等待的队列表
+    fn poll_wait<'a>(&'a self, _table: &mut PollTable<'a>) {}
+
+    /// 若文件是 epoll 实例则返回它，供 `epoll_ctl`/`epoll_pwait` 使用
+    fn as_epoll(&self) -> Option<&Epoll> {
+        None
+    }
+
+    /// 若文件是套接字则返回它，供 `bind`/`connect`/`sendto` 等系统调用使用
+    fn as_socket(&self) -> Option<&dyn Socket> {
+        None
+    }
+}
+
+/// 根据读写权限构造访问模式标志
+fn access_mode(readable: bool, writable: bool) -> OpenFlags {
+    match (readable, writable) {
+        (true, true) => OpenFlags::RDWR,
+        (false, true) => OpenFlags::WRONLY,
+        _ => OpenFlags::RDONLY,
+    }
+}
+
+/// 打开文件的状态标志
+///
+/// 保存可由 `F_SETFL` 修改的 [`OpenFlags::status`]，供各 [`File`] 实现内嵌，
+/// 在读写时查询是否为非阻塞或追加模式。
+pub struct FileStatus {
+    flags: UPSafeCell<OpenFlags>,
+}
+
+impl FileStatus {
+    /// 从打开标志中取出状态标志
+    pub fn new(flags: OpenFlags) -> Self {
+        Self {
+            flags: unsafe { UPSafeCell::new(flags & OpenFlags::status()) },
+        }
+    }
+
+    /// 是否为非阻塞模式
+    pub fn nonblocking(&self) -> bool {
+        self.flags.exclusive_access().contains(OpenFlags::NONBLOCK)
+    }
+
+    /// 是否为追加模式
+    pub fn append(&self) -> bool {
+        self.flags.exclusive_access().contains(OpenFlags::APPEND)
+    }
+
+    /// 处理 `F_GETFL` 与 `F_SETFL`
+    ///
+    /// ## Arguments
+    /// * `cmd` - `fcntl` 命令
+    /// * `arg` - `F_SETFL` 的新标志，其中状态标志以外的位被忽略
+    /// * `readable` / `writable` - 文件的读写权限，用于 `F_GETFL` 返回访问模式
+    pub fn fcntl(&self, cmd: usize, arg: usize, readable: bool, writable: bool) -> isize {
+        match cmd {
+            F_GETFL => {
+                let flags = *self.flags.exclusive_access() | access_mode(readable, writable);
+                flags.bits() as isize
+            }
+            F_SETFL => {
+                *self.flags.exclusive_access() =
+                    OpenFlags::from_bits_truncate(arg as u32) & OpenFlags::status();
+                0
+            }
+            _ => -EINVAL,
+        }
+    }
+}
+
+impl Default for FileStatus {
+    fn default() -> Self {
+        Self::new(OpenFlags::empty())
+    }
+}
+
+/// 把内核中的一段字节写入文件
+///
+/// 通过 [`File::write_nowait`] 写入，不阻塞，也不向当前进程投递信号。
+/// [`UserBuffer`] 只接受 `'static` 切片；写入期间 `bytes` 一直有效，且文件只从
+/// 缓冲区中读取，因此可以临时把它当作用户缓冲区传入。供核心转储与系统调用跟踪使用。
+///
+/// ## Returns
+///
+/// `bytes` 全部写入时返回 `true`
+pub fn write_kernel_bytes(file: &dyn File, bytes: &mut [u8]) -> bool {
+    let len = bytes.len();
+    let slice = unsafe { core::slice::from_raw_parts_mut(bytes.as_mut_ptr(), len) };
+    file.write_nowait(UserBuffer::new(vec![slice])) == len as isize
+}
+
+/// 打开设备文件
+///
+/// 设备不在磁盘文件系统中，按路径名直接分派：
+/// - `/dev/ptmx` - 分配新的伪终端并返回其主端
+/// - `/dev/pts/N` - 打开编号为 `N` 的伪终端从端
+///
+/// ## Arguments
+/// * `path` - 以 `/dev/` 开头的设备路径
+/// * `flags` - 打开标志，其中的文件状态标志作用于打开的设备文件
+///
+/// ## Returns
+/// 设备不存在时返回 `None`
+pub fn open_device(path: &str, flags: OpenFlags) -> Option<Arc<dyn File + Send + Sync>> {
+    if path == "/dev/ptmx" {
+        return Some(pty::open_ptmx(flags));
+    }
+    let index = path.strip_prefix("/dev/pts/")?.parse().ok()?;
+    pty::open_pts(index, flags).map(|slave| slave as Arc<dyn File + Send + Sync>)
 }
@@ -2,157 +2,362 @@
 //!
 //! 提供用户态进程间通过字节流进行通信的管道实现。管道由一段环形缓冲区
 //! 和两个端点组成：只读端与只写端。读端从缓冲区取字节，写端向缓冲区写入
-//! 字节，配合调度器实现阻塞式读写与生产者-消费者语义。
+//! 字节，配合等待队列实现阻塞式读写与生产者-消费者语义。
 //!
 //! ## 设计要点
-//! - **环形缓冲区**：固定容量（`RING_BUFFER_SIZE`）的字节数组，利用 `head/tail` 指针
-//!   与状态位实现无额外拷贝的顺序读写。
+//! - **环形缓冲区**：由若干物理页帧组成，默认 [`PIPE_DEFAULT_PAGES`] 页，可通过
+//!   `fcntl(F_SETPIPE_SZ)` 调整；读写按页内连续片段整块拷贝。
 //! - **阻塞语义**：
-//!   - 当读端在缓冲区为空时阻塞（让出 CPU），直至有数据可读或写端全部关闭。
-//!   - 当写端在缓冲区满时阻塞（让出 CPU），直至有空间可写。
-//! - **端点生命周期**：通过 `Arc`/`Weak` 追踪写端是否仍存活，读端可在写端全部关闭且
-//!   缓冲区为空时返回 EOF。
+//!   - 当读端在缓冲区为空时睡眠，直至有数据可读或写端全部关闭；有数据时返回
+//!     已有的数据，不等待填满用户缓冲区。
+//!   - 当写端在缓冲区满时睡眠，直至有空间可写。不超过 [`PIPE_BUF`] 字节的写入
+//!     是原子的：等到空间足够时一次写入。
+//!   - 非阻塞模式（`O_NONBLOCK`）下需要等待时返回 `-EAGAIN`。
+//! - **端点生命周期**：缓冲区记录仍打开的读端、写端数量，端点创建时增加、释放时减少。
+//!   读端在写端全部关闭且缓冲区为空时返回 EOF；写端在读端全部关闭后向写者发送
+//!   `SIGPIPE` 并返回 `-EPIPE`。
+//! - **命名管道**：同一个 FIFO 的多次打开共享一个缓冲区（见 [`super::fifo`]），
+//!   以读写方式打开的端点同时是读端和写端。
 //! - **并发安全**：内部通过 `UPSafeCell` 提供独占访问；临界区应尽量缩短，阻塞前先释放锁。
 //!
 //! ## 与文件接口的关系
 //! 本模块中的 `Pipe` 实现了内核抽象 `File`，可与标准文件描述符框架无缝协作：
-//! - `read(&self, UserBuffer) -> usize`
-//! - `write(&self, UserBuffer) -> usize`
+//! - `read(&self, UserBuffer) -> isize`
+//! - `write(&self, UserBuffer) -> isize`
 //! - `readable()` / `writable()`
+//! - `fcntl()`：`F_GETFL`/`F_SETFL` 与 `F_GETPIPE_SZ`/`F_SETPIPE_SZ`
+//! - `poll()` / `poll_wait()`：读端有数据时可读、写端全部关闭后挂断；
+//!   写端有 [`PIPE_BUF`] 字节空闲时可写，读端全部关闭后报告错误
 //!
 //! ## 使用示例
 //! 通过系统调用层包装：
-//! 1. 进程 A 调用 `pipe()` 获得一对 `fd[0]`（读端）、`fd[1]`（写端）。
+//! 1. 进程 A 调用 `pipe2()` 获得一对 `fd[0]`（读端）、`fd[1]`（写端）。
 //! 2. 父进程 `fork()` 后将写端 `dup`/重定向给子进程标准输出，读端给另一个子进程标准输入。
 //! 3. 两个子进程之间即可通过管道字节流进行通信。
 
-use crate::{fs::File, process::suspend_current_and_run_next, sync::UPSafeCell};
-use alloc::sync::{Arc, Weak};
+use super::{F_GETPIPE_SZ, F_SETPIPE_SZ, File, FileStatus, OpenFlags, PollEvents, PollTable};
+use crate::config::PAGE_SIZE;
+use crate::errno::Errno::{EAGAIN, EBUSY, EINTR, ENOMEM, EPERM, EPIPE};
+use crate::mm::{FrameTracker, UserBuffer, frame_alloc};
+use crate::process::{SignalFlags, current_send_signal, current_signal_pending};
+use crate::sync::{UPSafeCell, WaitQueue};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
 
-const RING_BUFFER_SIZE: usize = 32;
+/// 新建管道的缓冲区页数（64 KiB，与 Linux 默认值相同）
+pub const PIPE_DEFAULT_PAGES: usize = 16;
+/// `F_SETPIPE_SZ` 允许的最大页数（1 MiB）
+const PIPE_MAX_PAGES: usize = 256;
+/// 不超过该长度的写入是原子的，不会与其他写者的数据交错
+pub const PIPE_BUF: usize = PAGE_SIZE;
 
 /// 管道端点
 ///
-/// - `readable = true` 表示该端点为读端；`writable = true` 表示该端点为写端。
+/// - `readable = true` 表示该端点为读端；`writable = true` 表示该端点为写端；
+///   以读写方式打开的命名管道两者皆是。
 /// - 端点通过共享的 `PipeRingBuffer` 进行读写。
+/// - `pollers` 由两端共享，缓冲区内容变化或端点关闭时唤醒其中阻塞的读写者与 `poll` 调用者。
+/// - `status` 保存该端点的状态标志（如 `O_NONBLOCK`），两端各自独立。
 pub struct Pipe {
     readable: bool,
     writable: bool,
     buffer: Arc<UPSafeCell<PipeRingBuffer>>,
-}
-
-#[derive(Copy, Clone, PartialEq)]
-enum RingBufferStatus {
-    Full,
-    Empty,
-    Normal,
+    pollers: Arc<WaitQueue>,
+    status: FileStatus,
 }
 
 /// 管道环形缓冲区
 ///
-/// 使用固定大小的数组作为底层存储，`head` 指向下一个可读位置，`tail` 指向下一个可写位置。
-/// 通过 `status` 区分满/空/正常，避免歧义。`write_end` 记录写端是否仍存活，用于 EOF 判断。
+/// 存储由物理页帧组成，逻辑上首尾相接；`head` 指向下一个可读位置，`len` 为已缓存的
+/// 字节数。`readers`/`writers` 为仍打开的读端、写端数量，用于 EOF 与 `EPIPE` 判断；
+/// `read_opens`/`write_opens` 只增不减，命名管道的阻塞打开据此发现对端曾经出现过。
 pub struct PipeRingBuffer {
-    arr: [u8; RING_BUFFER_SIZE],
+    pages: Vec<FrameTracker>,
     head: usize,
-    tail: usize,
-    status: RingBufferStatus,
-    write_end: Option<Weak<Pipe>>,
+    len: usize,
+    readers: usize,
+    writers: usize,
+    read_opens: usize,
+    write_opens: usize,
 }
 
 impl PipeRingBuffer {
-    /// 创建空的环形缓冲区
-    pub fn new() -> Self {
-        Self {
-            arr: [0; RING_BUFFER_SIZE],
+    /// 创建容量为 `pages` 页的空缓冲区，物理页帧不足时返回 `None`
+    pub fn new(pages: usize) -> Option<Self> {
+        let pages = (0..pages)
+            .map(|_| frame_alloc())
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            pages,
             head: 0,
-            tail: 0,
-            status: RingBufferStatus::Empty,
-            write_end: None,
+            len: 0,
+            readers: 0,
+            writers: 0,
+            read_opens: 0,
+            write_opens: 0,
+        })
+    }
+
+    /// 登记新打开的端点
+    fn attach(&mut self, readable: bool, writable: bool) {
+        if readable {
+            self.readers += 1;
+            self.read_opens = self.read_opens.wrapping_add(1);
+        }
+        if writable {
+            self.writers += 1;
+            self.write_opens = self.write_opens.wrapping_add(1);
         }
     }
 
-    /// 在缓冲区中记录写端弱引用（用于 EOF 判定）
-    pub fn set_write_end(&mut self, write_end: &Arc<Pipe>) {
-        self.write_end = Some(Arc::downgrade(write_end));
+    /// 注销关闭的端点
+    fn detach(&mut self, readable: bool, writable: bool) {
+        if readable {
+            self.readers -= 1;
+        }
+        if writable {
+            self.writers -= 1;
+        }
+    }
+
+    /// 缓冲区容量（字节）
+    pub fn capacity(&self) -> usize {
+        self.pages.len() * PAGE_SIZE
+    }
+
+    /// 从逻辑位置 `pos` 开始、到所在页末尾为止的一段存储
+    fn segment(&self, pos: usize) -> &'static mut [u8] {
+        let pos = pos % self.capacity();
+        &mut self.pages[pos / PAGE_SIZE].ppn.bytes_array()[pos % PAGE_SIZE..]
     }
 
-    /// 从缓冲区读取一个字节（不做空检查，调用方需确保可读）
-    pub fn read_byte(&mut self) -> u8 {
-        self.status = RingBufferStatus::Normal;
-        let c = self.arr[self.head];
-        self.head = (self.head + 1) % RING_BUFFER_SIZE;
-        if self.head == self.tail {
-            self.status = RingBufferStatus::Empty;
+    /// 取出缓冲区开头的数据填入 `dst`
+    ///
+    /// ## Returns
+    /// 读取的字节数，不超过 `dst.len()` 与当前缓存的字节数
+    pub fn read(&mut self, dst: &mut [u8]) -> usize {
+        let n = dst.len().min(self.len);
+        let mut copied = 0;
+        while copied < n {
+            let src = self.segment(self.head);
+            let chunk = src.len().min(n - copied);
+            dst[copied..copied + chunk].copy_from_slice(&src[..chunk]);
+            self.head = (self.head + chunk) % self.capacity();
+            self.len -= chunk;
+            copied += chunk;
         }
-        c
+        n
     }
 
-    /// 向缓冲区写入一个字节（不做满检查，调用方需确保可写）
-    pub fn write_byte(&mut self, byte: u8) {
-        self.status = RingBufferStatus::Normal;
-        self.arr[self.tail] = byte;
-        self.tail = (self.tail + 1) % RING_BUFFER_SIZE;
-        if self.tail == self.head {
-            self.status = RingBufferStatus::Full;
+    /// 把 `src` 追加到缓冲区末尾
+    ///
+    /// ## Returns
+    /// 写入的字节数，不超过 `src.len()` 与当前空闲空间
+    pub fn write(&mut self, src: &[u8]) -> usize {
+        let n = src.len().min(self.available_write());
+        let mut copied = 0;
+        while copied < n {
+            let dst = self.segment(self.head + self.len);
+            let chunk = dst.len().min(n - copied);
+            dst[..chunk].copy_from_slice(&src[copied..copied + chunk]);
+            self.len += chunk;
+            copied += chunk;
         }
+        n
+    }
+
+    /// 把容量调整为 `pages` 页，已缓存的数据保持不变
+    ///
+    /// ## Returns
+    /// - `Err(-EBUSY)`：已缓存的数据超过新容量
+    /// - `Err(-ENOMEM)`：物理页帧不足
+    pub fn resize(&mut self, pages: usize) -> Result<(), isize> {
+        if self.len > pages * PAGE_SIZE {
+            return Err(-EBUSY);
+        }
+        let mut resized = Self::new(pages).ok_or(-ENOMEM)?;
+        let mut len = 0;
+        for page in resized.pages.iter() {
+            len += self.read(page.ppn.bytes_array());
+        }
+        self.pages = core::mem::take(&mut resized.pages);
+        self.head = 0;
+        self.len = len;
+        Ok(())
     }
 
     /// 当前可读字节数
     pub fn available_read(&self) -> usize {
-        if self.status == RingBufferStatus::Empty {
-            0
-        } else if self.tail > self.head {
-            self.tail - self.head
-        } else {
-            self.tail + RING_BUFFER_SIZE - self.head
-        }
+        self.len
     }
 
     /// 当前可写空闲空间大小
     pub fn available_write(&self) -> usize {
-        if self.status == RingBufferStatus::Full {
-            0
-        } else {
-            RING_BUFFER_SIZE - self.available_read()
-        }
+        self.capacity() - self.len
+    }
+
+    /// 是否所有读端均已关闭（用于写端返回 `EPIPE`）
+    pub fn all_read_ends_closed(&self) -> bool {
+        self.readers == 0
     }
 
     /// 是否所有写端均已关闭（用于读端在空时返回 EOF）
     pub fn all_write_ends_closed(&self) -> bool {
-        self.write_end.as_ref().unwrap().upgrade().is_none()
+        self.writers == 0
     }
 }
 
 impl Pipe {
-    /// 基于共享缓冲区创建读端
-    pub fn read_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>) -> Self {
+    /// 基于共享缓冲区创建端点
+    ///
+    /// 读写权限由 `flags` 中的访问模式决定；新端点计入缓冲区的读端、写端数量，
+    /// 并唤醒等待对端出现的打开者。
+    pub fn with_buffer(
+        buffer: Arc<UPSafeCell<PipeRingBuffer>>,
+        pollers: Arc<WaitQueue>,
+        flags: OpenFlags,
+    ) -> Self {
+        let (readable, writable) = flags.read_write();
+        buffer.exclusive_access().attach(readable, writable);
+        pollers.wake_all();
         Self {
-            readable: true,
-            writable: false,
+            readable,
+            writable,
             buffer,
+            pollers,
+            status: FileStatus::new(flags),
         }
     }
 
-    /// 基于共享缓冲区创建写端
-    pub fn write_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>) -> Self {
-        Self {
-            readable: false,
-            writable: true,
-            buffer,
+    /// 命名管道的阻塞打开：只读端等待写端出现，只写端等待读端出现
+    ///
+    /// 对端在等待期间打开后又关闭也算出现过，不会让打开者继续等待。
+    ///
+    /// ## Returns
+    /// 等待期间收到需要处理的信号时返回 `Err(-EINTR)`
+    pub fn wait_for_peer(&self) -> Result<(), isize> {
+        if self.readable && self.writable {
+            return Ok(());
+        }
+        let peer_opens = |ring_buffer: &PipeRingBuffer| {
+            if self.readable {
+                ring_buffer.write_opens
+            } else {
+                ring_buffer.read_opens
+            }
+        };
+        let seen = peer_opens(&*self.buffer.exclusive_access());
+        loop {
+            let ring_buffer = self.buffer.exclusive_access();
+            let peers = if self.readable {
+                ring_buffer.writers
+            } else {
+                ring_buffer.readers
+            };
+            if peers > 0 || peer_opens(&*ring_buffer) != seen {
+                return Ok(());
+            }
+            drop(ring_buffer);
+            if current_signal_pending() {
+                return Err(-EINTR);
+            }
+            self.pollers.wait();
         }
     }
+
+    /// 写入管道的实现，见 [`File::write`] 与 [`File::write_nowait`]
+    ///
+    /// ## Arguments
+    ///
+    /// * `nonblocking` - 需要等待时不睡眠，返回已写入的字节数或 `-EAGAIN`
+    /// * `sigpipe` - 读端全部关闭时是否向当前进程发送 `SIGPIPE`
+    fn write_buffer(&self, buf: UserBuffer, nonblocking: bool, sigpipe: bool) -> isize {
+        assert!(self.writable);
+        let want_to_write = buf.len();
+        let atomic = want_to_write <= PIPE_BUF;
+        let mut already_write = 0usize;
+        let partial = |already_write: usize, errno: isize| {
+            if already_write > 0 {
+                already_write as isize
+            } else {
+                errno
+            }
+        };
+        for src in buf.buffers.iter() {
+            let mut offset = 0;
+            while offset < src.len() {
+                let mut ring_buffer = self.buffer.exclusive_access();
+                if ring_buffer.all_read_ends_closed() {
+                    drop(ring_buffer);
+                    if sigpipe {
+                        current_send_signal(SignalFlags::SIGPIPE);
+                    }
+                    return partial(already_write, -EPIPE);
+                }
+                let need = if atomic {
+                    want_to_write - already_write
+                } else {
+                    1
+                };
+                if ring_buffer.available_write() >= need {
+                    let n = ring_buffer.write(&src[offset..]);
+                    offset += n;
+                    already_write += n;
+                    drop(ring_buffer);
+                    self.pollers.wake_all();
+                    continue;
+                }
+                drop(ring_buffer);
+                if nonblocking {
+                    return partial(already_write, -EAGAIN);
+                }
+                if current_signal_pending() {
+                    return partial(already_write, -EINTR);
+                }
+                self.pollers.wait();
+            }
+        }
+        already_write as isize
+    }
+
+    /// 处理 `F_SETPIPE_SZ`：按页向上取整调整容量
+    ///
+    /// ## Returns
+    /// 调整后的容量（字节）；超过上限返回 `-EPERM`，其余错误见 [`PipeRingBuffer::resize`]
+    fn set_size(&self, size: usize) -> isize {
+        let pages = size.div_ceil(PAGE_SIZE).max(1);
+        if pages > PIPE_MAX_PAGES {
+            return -EPERM;
+        }
+        let mut ring_buffer = self.buffer.exclusive_access();
+        if let Err(errno) = ring_buffer.resize(pages) {
+            return errno;
+        }
+        let capacity = ring_buffer.capacity();
+        drop(ring_buffer);
+        self.pollers.wake_all();
+        capacity as isize
+    }
 }
 
 /// 创建一对管道端点（读端、写端）
 ///
-/// 返回 `(read_end, write_end)`，二者共享同一环形缓冲区。
-pub fn make_pipe() -> (Arc<Pipe>, Arc<Pipe>) {
-    let buffer = Arc::new(unsafe { UPSafeCell::new(PipeRingBuffer::new()) });
-    let read_end = Arc::new(Pipe::read_end_with_buffer(buffer.clone()));
-    let write_end = Arc::new(Pipe::write_end_with_buffer(buffer.clone()));
-    buffer.exclusive_access().set_write_end(&write_end);
-    (read_end, write_end)
+/// 返回 `(read_end, write_end)`，二者共享同一环形缓冲区；`flags` 中的状态标志
+/// （`O_NONBLOCK`）作用于两端。物理页帧不足时返回 `None`。
+pub fn make_pipe(flags: OpenFlags) -> Option<(Arc<Pipe>, Arc<Pipe>)> {
+    let buffer = Arc::new(unsafe { UPSafeCell::new(PipeRingBuffer::new(PIPE_DEFAULT_PAGES)?) });
+    let pollers = Arc::new(WaitQueue::new());
+    let read_end = Arc::new(Pipe::with_buffer(
+        buffer.clone(),
+        pollers.clone(),
+        flags | OpenFlags::RDONLY,
+    ));
+    let write_end = Arc::new(Pipe::with_buffer(
+        buffer,
+        pollers,
+        flags | OpenFlags::WRONLY,
+    ));
+    Some((read_end, write_end))
 }
 
 impl File for Pipe {
@@ -168,67 +373,104 @@ impl File for Pipe {
 
     /// 从管道读取到用户缓冲区
     ///
-    /// - 若缓冲区为空且写端仍存活：释放锁并让出 CPU，直到有数据或写端关闭。
-    /// - 若缓冲区为空且写端全部关闭：返回已读字节数（可能为 0，表示 EOF）。
-    fn read(&self, buf: crate::mm::UserBuffer) -> usize {
+    /// - 缓冲区有数据：读取已有的数据后立即返回。
+    /// - 缓冲区为空且写端全部关闭：返回 0（EOF）。
+    /// - 缓冲区为空且写端仍存活：睡眠直到有数据或写端关闭；非阻塞模式下返回
+    ///   `-EAGAIN`，收到信号时返回 `-EINTR`。
+    fn read(&self, buf: UserBuffer) -> isize {
         assert!(self.readable);
-        let want_to_read = buf.len();
-        let mut buf_iter = buf.into_iter();
-        let mut already_read = 0usize;
+        if buf.len() == 0 {
+            return 0;
+        }
         loop {
             let mut ring_buffer = self.buffer.exclusive_access();
-            let loop_read = ring_buffer.available_read();
-            if loop_read == 0 {
-                if ring_buffer.all_write_ends_closed() {
-                    return already_read;
+            if ring_buffer.available_read() > 0 {
+                let mut already_read = 0usize;
+                for dst in buf.buffers {
+                    let n = ring_buffer.read(dst);
+                    already_read += n;
+                    if n < dst.len() {
+                        break;
+                    }
                 }
                 drop(ring_buffer);
-                suspend_current_and_run_next();
-                continue;
+                self.pollers.wake_all();
+                return already_read as isize;
             }
-            for _ in 0..loop_read {
-                if let Some(byte_ref) = buf_iter.next() {
-                    unsafe {
-                        *byte_ref = ring_buffer.read_byte();
-                    }
-                    already_read += 1;
-                    if already_read == want_to_read {
-                        return want_to_read;
-                    }
-                } else {
-                    return already_read;
-                }
+            if ring_buffer.all_write_ends_closed() {
+                return 0;
             }
+            drop(ring_buffer);
+            if self.status.nonblocking() {
+                return -EAGAIN;
+            }
+            if current_signal_pending() {
+                return -EINTR;
+            }
+            self.pollers.wait();
         }
     }
 
     /// 将用户缓冲区写入到管道
     ///
-    /// - 若缓冲区已满：释放锁并让出 CPU，直到有空间可写。
-    fn write(&self, buf: crate::mm::UserBuffer) -> usize {
-        assert!(self.writable);
-        let want_to_write = buf.len();
-        let mut buf_iter = buf.into_iter();
-        let mut already_write = 0usize;
-        loop {
-            let mut ring_buffer = self.buffer.exclusive_access();
-            let loop_write = ring_buffer.available_write();
-            if loop_write == 0 {
-                drop(ring_buffer);
-                suspend_current_and_run_next();
-                continue;
+    /// - 缓冲区空间不足：睡眠直到有空间可写；不超过 [`PIPE_BUF`] 的写入等到
+    ///   能一次写完时才写入。
+    /// - 读端全部关闭：发送 `SIGPIPE`，返回 `-EPIPE`。
+    /// - 非阻塞模式下需要等待、或等待期间收到信号时，返回已写入的字节数；
+    ///   尚未写入时分别返回 `-EAGAIN`、`-EINTR`。
+    fn write(&self, buf: UserBuffer) -> isize {
+        self.write_buffer(buf, self.status.nonblocking(), true)
+    }
+
+    /// 内核写入：需要等待时返回 `-EAGAIN`，读端全部关闭时只返回 `-EPIPE`，不发送 `SIGPIPE`
+    fn write_nowait(&self, buf: UserBuffer) -> isize {
+        self.write_buffer(buf, true, false)
+    }
+
+    /// 状态标志由端点各自保存；管道容量由两端共享
+    fn fcntl(&self, cmd: usize, arg: usize) -> isize {
+        match cmd {
+            F_GETPIPE_SZ => self.buffer.exclusive_access().capacity() as isize,
+            F_SETPIPE_SZ => self.set_size(arg),
+            _ => self.status.fcntl(cmd, arg, self.readable, self.writable),
+        }
+    }
+
+    /// 读端：有数据时可读，写端全部关闭后挂断；
+    /// 写端：能原子写入 [`PIPE_BUF`] 字节时可写，读端全部关闭后报告错误
+    fn poll(&self) -> PollEvents {
+        let ring_buffer = self.buffer.exclusive_access();
+        let mut events = PollEvents::empty();
+        if self.readable {
+            if ring_buffer.available_read() > 0 {
+                events |= PollEvents::POLLIN;
             }
-            for _ in 0..loop_write {
-                if let Some(byte_ref) = buf_iter.next() {
-                    ring_buffer.write_byte(unsafe { *byte_ref });
-                    already_write += 1;
-                    if already_write == want_to_write {
-                        return want_to_write;
-                    }
-                } else {
-                    return already_write;
-                }
+            if ring_buffer.all_write_ends_closed() {
+                events |= PollEvents::POLLHUP;
+            }
+        }
+        if self.writable {
+            if ring_buffer.available_write() >= PIPE_BUF {
+                events |= PollEvents::POLLOUT;
+            }
+            if ring_buffer.all_read_ends_closed() {
+                events |= PollEvents::POLLERR;
             }
         }
+        events
+    }
+
+    fn poll_wait<'a>(&'a self, table: &mut PollTable<'a>) {
+        table.add(&self.pollers);
+    }
+}
+
+impl Drop for Pipe {
+    /// 一端关闭后另一端阻塞的读写者与 `poll` 调用者需要看到挂断或错误
+    fn drop(&mut self) {
+        self.buffer
+            .exclusive_access()
+            .detach(self.readable, self.writable);
+        self.pollers.wake_all();
     }
 }
@@ -0,0 +1,153 @@
+//! # 文件就绪状态与多路等待
+//!
+//! 为 `ppoll`/`pselect6` 提供文件就绪状态的统一描述：
+//! - [`PollEvents`] - 就绪事件位（可读、可写、挂断等），取值与 Linux 一致
+//! - [`PollTable`] - 一次等待中登记的全部等待队列
+//! - [`PollFd`] / [`FdSet`] - 与用户空间交换的 `struct pollfd` 与 `fd_set`
+//!
+//! ## 等待协议
+//!
+//! 每个 [`File`](super::File) 通过 `poll` 报告当前就绪的事件，通过 `poll_wait`
+//! 把“状态可能改变时会被唤醒”的等待队列加入 [`PollTable`]。调用者在没有
+//! 文件就绪时调用 [`PollTable::wait`]：在所有队列上登记后阻塞，任一队列
+//! 唤醒或超时后撤销全部登记，再重新查询各文件的状态。
+//!
+//! 内核态运行时中断关闭，“查询状态 → 登记 → 阻塞”之间不会错过唤醒。
+//!
+//! epoll 不阻塞在文件的队列上，而是通过 [`PollTable::watch`] 在这些队列上
+//! 长期挂接监听者，由唤醒回调把就绪的兴趣项放入就绪列表。
+
+use crate::process::{block_current_and_run_next, current_process};
+use crate::sync::{WaitQueue, WakeWatcher};
+use crate::timer::{add_timer, remove_timer};
+use alloc::sync::Weak;
+use alloc::vec::Vec;
+use bitflags::*;
+
+bitflags! {
+    /// 就绪事件，取值与 Linux 的 `POLL*` 常量一致
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct PollEvents: u16 {
+        /// 有数据可读
+        const POLLIN = 0x001;
+        /// 有紧急数据可读
+        const POLLPRI = 0x002;
+        /// 可以写入而不阻塞
+        const POLLOUT = 0x004;
+        /// 发生错误，总是报告
+        const POLLERR = 0x008;
+        /// 对端已关闭，总是报告
+        const POLLHUP = 0x010;
+        /// 文件描述符无效，总是报告
+        const POLLNVAL = 0x020;
+    }
+}
+
+impl PollEvents {
+    /// 无论是否请求都会报告的事件
+    pub fn always() -> Self {
+        Self::POLLERR | Self::POLLHUP | Self::POLLNVAL
+    }
+}
+
+/// 用户空间的 `struct pollfd`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PollFd {
+    /// 文件描述符，负数表示忽略该项
+    pub fd: i32,
+    /// 关心的事件
+    pub events: i16,
+    /// 返回时填写的已就绪事件
+    pub revents: i16,
+}
+
+/// 文件描述符集合的容量
+pub const FD_SETSIZE: usize = 1024;
+
+/// 用户空间的 `fd_set`：每个文件描述符占一位，按字节小端排列
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FdSet {
+    bits: [u8; FD_SETSIZE / 8],
+}
+
+impl FdSet {
+    /// 空集合
+    pub fn empty() -> Self {
+        Self {
+            bits: [0; FD_SETSIZE / 8],
+        }
+    }
+
+    /// 集合中是否包含 `fd`
+    pub fn contains(&self, fd: usize) -> bool {
+        self.bits[fd / 8] & (1 << (fd % 8)) != 0
+    }
+
+    /// 把 `fd` 加入集合
+    pub fn insert(&mut self, fd: usize) {
+        self.bits[fd / 8] |= 1 << (fd % 8);
+    }
+
+    /// 底层字节，用于与用户空间拷贝
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bits
+    }
+
+    /// 底层字节的可变引用，用于从用户空间拷贝
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.bits
+    }
+}
+
+/// 一次多路等待中登记的等待队列
+///
+/// 生命周期 `'a` 保证队列所属的文件在等待期间不会被释放。
+#[derive(Default)]
+pub struct PollTable<'a> {
+    queues: Vec<&'a WaitQueue>,
+}
+
+impl<'a> PollTable<'a> {
+    /// 创建空表
+    pub fn new() -> Self {
+        Self { queues: Vec::new() }
+    }
+
+    /// 登记一个等待队列，由 `File::poll_wait` 调用
+    pub fn add(&mut self, queue: &'a WaitQueue) {
+        self.queues.push(queue);
+    }
+
+    /// 在登记的每个队列上挂接监听者
+    pub fn watch(&self, watcher: Weak<dyn WakeWatcher>) {
+        for queue in self.queues.iter() {
+            queue.watch(watcher.clone());
+        }
+    }
+
+    /// 阻塞当前进程，直到任一登记的队列被唤醒或到达截止时间
+    ///
+    /// ## Arguments
+    ///
+    /// * `deadline_ms` - 截止时刻（以 `time_ms` 为基准），`None` 表示不限时
+    ///
+    /// 返回后调用者需要重新查询文件状态，唤醒可能是虚假的。
+    pub fn wait(&self, deadline_ms: Option<usize>) {
+        let process = current_process().unwrap();
+        for queue in self.queues.iter() {
+            queue.register(process.clone());
+        }
+        if let Some(deadline_ms) = deadline_ms {
+            add_timer(deadline_ms, process.clone());
+        }
+        block_current_and_run_next();
+        for queue in self.queues.iter() {
+            queue.remove(&process);
+        }
+        if deadline_ms.is_some() {
+            remove_timer(&process);
+        }
+    }
+}
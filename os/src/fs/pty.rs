@@ -0,0 +1,318 @@
+//! # 伪终端（PTY）
+//!
+//! 伪终端由一对文件组成：
+//! - **主端** ([`PtyMaster`])：打开 `/dev/ptmx` 得到；写入主端的数据作为
+//!   从端终端的输入交给行规程，读主端得到从端终端的输出
+//! - **从端** ([`PtySlave`])：打开 `/dev/pts/N` 得到，`N` 通过主端的
+//!   `TIOCGPTN` ioctl 查询；从端与控制台一样是一个完整的终端，
+//!   与控制台共用行规程（[`Tty`]），支持规范模式、回显与终端信号
+//!
+//! 典型用法是测试驱动或终端复用器在从端上运行 shell，再通过主端向它
+//! “键入”命令并读取输出。
+//!
+//! ## 生命周期
+//!
+//! - 主端关闭时从端终端挂断：从端读者读到 0，此后写入从端的数据被丢弃；
+//!   该编号随即可被新的伪终端复用
+//! - 从端全部关闭且输出已读完时，主端读取返回 0
+//!
+//! ## 就绪状态
+//!
+//! 主端在有输出可读时可读，写入主端从不阻塞；从端全部关闭后主端报告
+//! `POLLHUP`。从端的就绪状态由终端决定，另外登记主端读缓冲区的空间变化。
+//!
+//! 主端读缓冲区最多 [`PTY_BUFFER_SIZE`] 字节：写满后从端写者睡眠，非阻塞模式下
+//! 返回 `-EAGAIN`；回显不能阻塞，放不下时被丢弃。
+
+use super::{File, FileStatus, OpenFlags, PollEvents, PollTable};
+use crate::drivers::{Tty, TtyDriver};
+use crate::errno::Errno::{EAGAIN, EFAULT, EINTR};
+use crate::mm::{UserBuffer, copy_to_user};
+use crate::process::{current_signal_pending, current_user_token};
+use crate::sync::{UPSafeCell, WaitQueue};
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use lazy_static::*;
+
+/// 获取伪终端编号
+const TIOCGPTN: usize = 0x8004_5430;
+/// 锁定/解锁从端；本实现从端总是解锁的，只为兼容而接受该命令
+const TIOCSPTLCK: usize = 0x4004_5431;
+
+/// 主端读缓冲区的容量，满后从端的写者等待
+const PTY_BUFFER_SIZE: usize = 4096;
+
+/// 伪终端的主端一侧，作为从端终端的驱动
+struct PtyOutputInner {
+    /// 从端输出、等待主端读取的数据
+    buffer: VecDeque<u8>,
+    /// 打开着的从端数目
+    slaves: usize,
+    /// 是否曾经打开过从端；从未打开时主端读取不会因从端数为 0 而返回
+    slave_opened: bool,
+    /// 主端已关闭，输出直接丢弃
+    master_closed: bool,
+}
+
+struct PtyOutput {
+    inner: UPSafeCell<PtyOutputInner>,
+    /// 等待输出的主端读者
+    readers: WaitQueue,
+    /// 等待缓冲区空间的从端写者
+    writers: WaitQueue,
+}
+
+impl TtyDriver for PtyOutput {
+    /// 追加到主端读缓冲区，超出 [`PTY_BUFFER_SIZE`] 的部分被丢弃
+    ///
+    /// 进程写入已按 [`write_room`](TtyDriver::write_room) 限流，只有不能阻塞的
+    /// 回显会在缓冲区满时丢失。
+    fn output(&self, data: &[u8]) {
+        let mut inner = self.inner.exclusive_access();
+        if inner.master_closed {
+            return;
+        }
+        let room = PTY_BUFFER_SIZE.saturating_sub(inner.buffer.len());
+        inner.buffer.extend(data.iter().take(room).copied());
+        drop(inner);
+        self.readers.wake_all();
+    }
+
+    fn write_room(&self) -> usize {
+        let inner = self.inner.exclusive_access();
+        if inner.master_closed {
+            usize::MAX
+        } else {
+            PTY_BUFFER_SIZE.saturating_sub(inner.buffer.len())
+        }
+    }
+
+    fn wait_write_room(&self) {
+        self.writers.wait();
+    }
+}
+
+/// 一对伪终端共享的状态
+struct Pty {
+    index: usize,
+    tty: Tty,
+    output: Arc<PtyOutput>,
+}
+
+lazy_static! {
+    /// 编号到伪终端的映射，只包含主端仍然打开的伪终端
+    static ref PTY_TABLE: UPSafeCell<BTreeMap<usize, Weak<Pty>>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+}
+
+/// 伪终端主端
+pub struct PtyMaster {
+    pty: Arc<Pty>,
+    status: FileStatus,
+}
+
+/// 伪终端从端
+pub struct PtySlave {
+    pty: Arc<Pty>,
+    status: FileStatus,
+}
+
+/// 分配一个新的伪终端并返回其主端
+///
+/// 使用最小的未占用编号，`flags` 中的文件状态标志作用于返回的主端。
+pub fn open_ptmx(flags: OpenFlags) -> Arc<PtyMaster> {
+    let mut table = PTY_TABLE.exclusive_access();
+    let index = (0..).find(|index| !table.contains_key(index)).unwrap();
+    let output = Arc::new(PtyOutput {
+        inner: unsafe {
+            UPSafeCell::new(PtyOutputInner {
+                buffer: VecDeque::new(),
+                slaves: 0,
+                slave_opened: false,
+                master_closed: false,
+            })
+        },
+        readers: WaitQueue::new(),
+        writers: WaitQueue::new(),
+    });
+    let pty = Arc::new(Pty {
+        index,
+        tty: Tty::new(output.clone()),
+        output,
+    });
+    table.insert(index, Arc::downgrade(&pty));
+    Arc::new(PtyMaster {
+        pty,
+        status: FileStatus::new(flags),
+    })
+}
+
+/// 打开编号为 `index` 的伪终端从端
+///
+/// ## Returns
+///
+/// 主端已关闭或编号不存在时返回 `None`
+pub fn open_pts(index: usize, flags: OpenFlags) -> Option<Arc<PtySlave>> {
+    let pty = PTY_TABLE
+        .exclusive_access()
+        .get(&index)
+        .and_then(Weak::upgrade)?;
+    let mut output = pty.output.inner.exclusive_access();
+    output.slaves += 1;
+    output.slave_opened = true;
+    drop(output);
+    Some(Arc::new(PtySlave {
+        pty,
+        status: FileStatus::new(flags),
+    }))
+}
+
+impl File for PtyMaster {
+    /// 读取从端终端的输出
+    ///
+    /// 没有数据时睡眠，非阻塞模式下返回 `-EAGAIN`；
+    /// 从端全部关闭且数据已读完时返回 0，等待期间收到信号时返回 `-EINTR`。
+    fn read(&self, buf: UserBuffer) -> isize {
+        let want = buf.len();
+        if want == 0 {
+            return 0;
+        }
+        let output = &self.pty.output;
+        loop {
+            let mut inner = output.inner.exclusive_access();
+            if !inner.buffer.is_empty() {
+                let n = want.min(inner.buffer.len());
+                let data: Vec<u8> = inner.buffer.drain(..n).collect();
+                drop(inner);
+                for (dst, ch) in buf.into_iter().zip(data) {
+                    unsafe {
+                        *dst = ch;
+                    }
+                }
+                output.writers.wake_all();
+                return n as isize;
+            }
+            if inner.slave_opened && inner.slaves == 0 {
+                return 0;
+            }
+            drop(inner);
+            if self.status.nonblocking() {
+                return -EAGAIN;
+            }
+            if current_signal_pending() {
+                return -EINTR;
+            }
+            output.readers.wait();
+        }
+    }
+
+    /// 把数据作为从端终端的输入交给行规程
+    fn write(&self, buf: UserBuffer) -> isize {
+        for buffer in buf.buffers.iter() {
+            self.pty.tty.input(buffer);
+        }
+        buf.len() as isize
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    /// `TIOCGPTN` 查询编号，`TIOCSPTLCK` 直接成功，其余命令作用于从端终端
+    fn ioctl(&self, cmd: usize, arg: usize) -> isize {
+        match cmd {
+            TIOCGPTN => {
+                let index = self.pty.index as u32;
+                if copy_to_user(current_user_token(), arg as *mut u32, &index).is_err() {
+                    return -EFAULT;
+                }
+                0
+            }
+            TIOCSPTLCK => 0,
+            _ => self.pty.tty.ioctl(cmd, arg),
+        }
+    }
+
+    fn fcntl(&self, cmd: usize, arg: usize) -> isize {
+        self.status.fcntl(cmd, arg, true, true)
+    }
+
+    fn poll(&self) -> PollEvents {
+        let inner = self.pty.output.inner.exclusive_access();
+        let mut events = PollEvents::POLLOUT;
+        if !inner.buffer.is_empty() {
+            events |= PollEvents::POLLIN;
+        }
+        if inner.slave_opened && inner.slaves == 0 {
+            events |= PollEvents::POLLHUP;
+        }
+        events
+    }
+
+    fn poll_wait<'a>(&'a self, table: &mut PollTable<'a>) {
+        table.add(&self.pty.output.readers);
+    }
+}
+
+impl Drop for PtyMaster {
+    fn drop(&mut self) {
+        PTY_TABLE.exclusive_access().remove(&self.pty.index);
+        let output = &self.pty.output;
+        output.inner.exclusive_access().master_closed = true;
+        output.writers.wake_all();
+        self.pty.tty.hangup();
+    }
+}
+
+impl File for PtySlave {
+    fn read(&self, buf: UserBuffer) -> isize {
+        self.pty.tty.read(buf, self.status.nonblocking())
+    }
+
+    fn write(&self, buf: UserBuffer) -> isize {
+        self.pty.tty.write(buf, self.status.nonblocking())
+    }
+
+    /// 内核写入：主端读缓冲区满时不等待
+    fn write_nowait(&self, buf: UserBuffer) -> isize {
+        self.pty.tty.write(buf, true)
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn ioctl(&self, cmd: usize, arg: usize) -> isize {
+        self.pty.tty.ioctl(cmd, arg)
+    }
+
+    fn fcntl(&self, cmd: usize, arg: usize) -> isize {
+        self.status.fcntl(cmd, arg, true, true)
+    }
+
+    fn poll(&self) -> PollEvents {
+        self.pty.tty.poll()
+    }
+
+    fn poll_wait<'a>(&'a self, table: &mut PollTable<'a>) {
+        self.pty.tty.poll_wait(table);
+        table.add(&self.pty.output.writers);
+    }
+}
+
+impl Drop for PtySlave {
+    fn drop(&mut self) {
+        let output = &self.pty.output;
+        output.inner.exclusive_access().slaves -= 1;
+        output.readers.wake_all();
+    }
+}
@@ -1,7 +1,8 @@
 //! # 标准输入输出模块
 //!
 //! 提供标准输入输出设备的实现，包括标准输入 (stdin)、标准输出 (stdout) 和标准错误 (stderr)。
-//! 这些设备实现了 `File` trait，可以像普通文件一样进行读写操作。
+//! 这些设备实现了 `File` trait，可以像普通文件一样进行读写操作；
+//! 三者都是控制台终端 [`TTY`] 的句柄，输入经过行规程加工，输出按终端属性转换。
 //!
 //! ## 核心组件
 //!
@@ -11,8 +12,11 @@
 //!
 //! ## 设备特性
 //!
-//! - **阻塞读取**: 标准输入在没有数据时会让出 CPU
-//! - **实时输出**: 标准输出和标准错误立即显示到控制台
+//! - **行缓冲读取**: 规范模式下标准输入按行返回，回显与退格由终端处理
+//! - **缓冲输出**: 标准输出和标准错误写入串口发送缓冲区，由发送中断异步写出
+//! - **终端控制**: 三者都支持 `ioctl` 读写终端属性与前台进程
+//! - **非阻塞模式**: 通过 `fcntl(F_SETFL, O_NONBLOCK)` 使读写在需要等待时返回 `EAGAIN`
+//! - **就绪查询**: 就绪状态委托给终端，可与管道等其他文件一起用 `ppoll` 等待
 //! - **权限控制**: 标准输入只读，标准输出和标准错误只写
 //! - **字符处理**: 支持 UTF-8 编码的文本处理
 //! - **错误区分**: 标准错误用于输出错误信息，便于与正常输出区分
@@ -29,53 +33,53 @@
 //! ```rust
 //! use crate::fs::{Stdin, Stdout, Stderr, File};
 //!
-//! // 从标准输入读取字符
-//! let stdin = Stdin;
-//! let mut buf = [0u8; 1];
+//! // 从标准输入读取一行
+//! let stdin = Stdin::default();
+//! let mut buf = [0u8; 128];
 //! let user_buf = UserBuffer::new(&mut buf);
 //! let bytes_read = stdin.read(user_buf);
 //!
 //! // 向标准输出写入文本
-//! let stdout = Stdout;
+//! let stdout = Stdout::default();
 //! let data = b"Hello, World!";
 //! let user_buf = UserBuffer::new(data);
 //! let bytes_written = stdout.write(user_buf);
 //!
 //! // 向标准错误写入错误信息
-//! let stderr = Stderr;
+//! let stderr = Stderr::default();
 //! let error_msg = b"Error: File not found";
 //! let user_buf = UserBuffer::new(error_msg);
 //! let bytes_written = stderr.write(user_buf);
 //! ```
 
-use super::File;
+use super::{File, FileStatus, PollEvents, PollTable};
+use crate::drivers::TTY;
 use crate::mm::UserBuffer;
-use crate::print;
-use crate::process::suspend_current_and_run_next;
-use crate::sbi::console_getchar;
 
 /// 标准输入设备
 ///
-/// 实现从控制台读取字符的功能，支持阻塞式读取。
-/// 当没有输入数据时，会主动让出 CPU 以节省系统资源。
+/// 实现从控制台读取输入的功能，支持阻塞式读取。
+/// 当没有输入数据时，进程睡眠直到终端有数据可读。
 ///
 /// ## 读取特性
 ///
-/// - **单字符读取**: 每次读取一个字符
-/// - **阻塞等待**: 没有输入时会让出 CPU
-/// - **实时响应**: 一旦有输入立即返回
+/// - **按行读取**: 规范模式下每次最多返回一行，原始模式下按 `VMIN` 返回
+/// - **阻塞等待**: 没有输入时在终端的等待队列上睡眠
+/// - **可被信号打断**: 等待期间收到致命或被捕捉的信号时提前返回
 /// - **权限控制**: 只支持读取操作，不支持写入
 ///
 /// ## 实现原理
 ///
-/// 通过 SBI 接口 `console_getchar()` 从控制台读取字符。
-/// 当没有可用字符时，调用 `suspend_current_and_run_next()` 让出 CPU。
+/// 委托给终端 [`TTY`]，由行规程完成回显、擦除和信号字符的处理。
 ///
 /// ## 线程安全
 ///
 /// 该结构是线程安全的，多个线程可以同时从标准输入读取。
-/// 具体的并发控制由底层的 SBI 接口实现。
-pub struct Stdin;
+/// 具体的并发控制由终端实现。
+#[derive(Default)]
+pub struct Stdin {
+    status: FileStatus,
+}
 
 /// 标准输出设备
 ///
@@ -84,21 +88,25 @@ pub struct Stdin;
 ///
 /// ## 输出特性
 ///
-/// - **实时输出**: 文本立即显示到控制台
-/// - **UTF-8 支持**: 完全支持 Unicode 字符输出
+/// - **缓冲输出**: 文本进入串口发送缓冲区后立即返回
+/// - **原始字节**: 按字节原样输出，不要求 UTF-8 编码
 /// - **批量处理**: 支持跨页面的用户缓冲区
 /// - **权限控制**: 只支持写入操作，不支持读取
 ///
 /// ## 实现原理
 ///
-/// 通过 `print!` 宏将用户缓冲区的内容输出到控制台。
+/// 通过 [`TTY`] 将用户缓冲区的内容放入串口发送缓冲区，
+/// 输出时按终端属性把换行转换为回车换行。
 /// 支持跨页面的用户缓冲区，自动处理页面边界。
 ///
 /// ## 线程安全
 ///
 /// 该结构是线程安全的，多个线程可以同时向标准输出写入。
 /// 输出操作是原子的，不会出现字符交错的情况。
-pub struct Stdout;
+#[derive(Default)]
+pub struct Stdout {
+    status: FileStatus,
+}
 
 /// 标准错误设备
 ///
@@ -107,15 +115,15 @@ pub struct Stdout;
 ///
 /// ## 输出特性
 ///
-/// - **实时输出**: 错误信息立即显示到控制台
-/// - **UTF-8 支持**: 完全支持 Unicode 字符输出
+/// - **缓冲输出**: 错误信息进入串口发送缓冲区后立即返回
+/// - **原始字节**: 按字节原样输出，不要求 UTF-8 编码
 /// - **批量处理**: 支持跨页面的用户缓冲区
 /// - **权限控制**: 只支持写入操作，不支持读取
 /// - **错误区分**: 专门用于输出错误信息
 ///
 /// ## 实现原理
 ///
-/// 通过 `print!` 宏将用户缓冲区的内容输出到控制台。
+/// 通过 [`TTY`] 将用户缓冲区的内容放入串口发送缓冲区。
 /// 支持跨页面的用户缓冲区，自动处理页面边界。
 /// 与标准输出使用相同的输出机制，但在语义上区分用途。
 ///
@@ -151,13 +159,16 @@ pub struct Stdout;
 /// ## Examples
 ///
 /// ```rust
-/// let stderr = Stderr;
+/// let stderr = Stderr::default();
 /// let error_msg = b"Error: File not found\n";
 /// let user_buf = UserBuffer::new(error_msg);
 /// let bytes_written = stderr.write(user_buf);
 /// assert_eq!(bytes_written, 22);
 /// ```
-pub struct Stderr;
+#[derive(Default)]
+pub struct Stderr {
+    status: FileStatus,
+}
 
 impl File for Stdin {
     /// 检查标准输入是否可读
@@ -178,72 +189,41 @@ impl File for Stdin {
         false
     }
 
-    /// 从标准输入读取字符
+    /// 从标准输入读取数据
     ///
-    /// 从控制台读取一个字符到用户缓冲区中。该操作是阻塞的，
-    /// 当没有输入数据时会主动让出 CPU。
+    /// 从终端读取输入到用户缓冲区中。该操作是阻塞的，
+    /// 当没有可读数据时进程睡眠。
     ///
     /// ## Arguments
     ///
-    /// * `user_buf` - 用户缓冲区，用于存储读取的字符
+    /// * `user_buf` - 用户缓冲区，用于存储读取的数据
     ///
     /// ## Returns
     ///
-    /// 总是返回 1，表示读取了一个字符
-    ///
-    /// ## 读取过程
-    ///
-    /// 1. **缓冲区检查**: 验证缓冲区大小为 1 字节
-    /// 2. **字符等待**: 循环调用 `console_getchar()` 等待输入
-    /// 3. **CPU 让出**: 当没有输入时让出 CPU
-    /// 4. **字符处理**: 将读取的字符写入用户缓冲区
-    /// 5. **返回结果**: 返回读取的字节数（总是 1）
+    /// 读取的字节数；空行上输入 `^D` 或等待被信号打断时返回 0
     ///
     /// ## 阻塞行为
     ///
-    /// 当控制台没有可用字符时，函数会进入忙等待循环：
-    /// - 调用 `console_getchar()` 检查是否有输入
-    /// - 如果没有输入（返回 0），调用 `suspend_current_and_run_next()`
-    /// - 让出 CPU 给其他进程执行
-    /// - 当进程重新调度时，继续检查输入
-    ///
-    /// ## 错误处理
-    ///
-    /// - 如果缓冲区大小不是 1 字节，会触发 panic
-    /// - 如果写入用户缓冲区失败，会触发 panic
+    /// 当终端没有可读数据时：
+    /// - 在终端的等待队列上睡眠，不占用 CPU
+    /// - 串口接收中断经行规程产生可读数据后唤醒进程
+    /// - 终端发出信号（如 `^C`）时唤醒进程，以便及时处理信号
     ///
     /// ## 性能说明
     ///
-    /// 该操作是阻塞的，会等待用户输入。在等待期间会主动让出 CPU，
+    /// 该操作是阻塞的，会等待用户输入。等待期间进程处于阻塞态，
     /// 不会浪费系统资源。
     ///
     /// ## Examples
     ///
     /// ```
-    /// let stdin = Stdin;
-    /// let mut buf = [0u8; 1];
+    /// let stdin = Stdin::default();
+    /// let mut buf = [0u8; 128];
     /// let user_buf = UserBuffer::new(&mut buf);
     /// let bytes_read = stdin.read(user_buf);
-    /// assert_eq!(bytes_read, 1);
     /// ```
-    fn read(&self, mut user_buf: UserBuffer) -> usize {
-        assert_eq!(user_buf.len(), 1);
-
-        let mut c: usize;
-        loop {
-            c = console_getchar();
-            if c == 0 {
-                suspend_current_and_run_next();
-                continue;
-            } else {
-                break;
-            }
-        }
-        let ch = c as u8;
-        unsafe {
-            user_buf.buffers[0].as_mut_ptr().write_volatile(ch);
-        }
-        1
+    fn read(&self, user_buf: UserBuffer) -> isize {
+        TTY.read(user_buf, self.status.nonblocking())
     }
 
     /// 向标准输入写入数据
@@ -260,9 +240,29 @@ impl File for Stdin {
     ///
     /// 标准输入是只读设备，不支持写入操作。如果尝试写入，
     /// 会触发 panic 以明确表示操作不被支持。
-    fn write(&self, _user_buf: UserBuffer) -> usize {
+    fn write(&self, _user_buf: UserBuffer) -> isize {
         panic!("Cannot write to stdin!");
     }
+
+    /// 终端控制，委托给 [`TTY`]
+    fn ioctl(&self, cmd: usize, arg: usize) -> isize {
+        TTY.ioctl(cmd, arg)
+    }
+
+    /// 读取或修改文件状态标志，`O_NONBLOCK` 对终端读写生效
+    fn fcntl(&self, cmd: usize, arg: usize) -> isize {
+        self.status
+            .fcntl(cmd, arg, self.readable(), self.writable())
+    }
+
+    /// 有完整的输入行（或原始模式下有数据）时可读
+    fn poll(&self) -> PollEvents {
+        TTY.poll() - PollEvents::POLLOUT
+    }
+
+    fn poll_wait<'a>(&'a self, table: &mut PollTable<'a>) {
+        TTY.poll_wait(table);
+    }
 }
 
 impl File for Stdout {
@@ -298,14 +298,14 @@ impl File for Stdout {
     ///
     /// 标准输出是只写设备，不支持读取操作。如果尝试读取，
     /// 会触发 panic 以明确表示操作不被支持。
-    fn read(&self, _user_buf: UserBuffer) -> usize {
+    fn read(&self, _user_buf: UserBuffer) -> isize {
         panic!("Cannot read from stdout!");
     }
 
     /// 向标准输出写入文本
     ///
     /// 将用户缓冲区中的文本输出到控制台。支持跨页面的用户缓冲区，
-    /// 自动处理页面边界和 UTF-8 编码。
+    /// 自动处理页面边界。
     ///
     /// ## Arguments
     ///
@@ -318,24 +318,23 @@ impl File for Stdout {
     /// ## 输出过程
     ///
     /// 1. **缓冲区遍历**: 遍历用户缓冲区的所有页面
-    /// 2. **文本转换**: 将每个页面的字节转换为 UTF-8 字符串
-    /// 3. **控制台输出**: 使用 `print!` 宏输出到控制台
+    /// 2. **缓冲写入**: 将每个页面的字节放入串口发送缓冲区
+    /// 3. **异步发送**: 由串口发送中断把数据写出
     /// 4. **结果统计**: 统计所有输出的字节数
     ///
     /// ## 文本处理
     ///
-    /// - **UTF-8 支持**: 完全支持 Unicode 字符输出
+    /// - **原始字节**: 按字节原样输出，不做编码检查
     /// - **页面边界**: 自动处理跨页面的用户缓冲区
-    /// - **实时显示**: 文本立即显示到控制台，无需缓冲
+    /// - **缓冲发送**: 文本经串口发送缓冲区异步显示到控制台
     ///
     /// ## 错误处理
     ///
-    /// - 如果用户缓冲区包含无效的 UTF-8 序列，会触发 panic
-    /// - 如果控制台输出失败，会触发 panic
+    /// - 发送缓冲区已满时同步等待硬件发送，不会丢失数据
     ///
     /// ## 性能说明
     ///
-    /// 该操作是同步的，会立即输出到控制台。对于大量文本，
+    /// 该操作只把数据放入发送缓冲区即返回；缓冲区满时才等待硬件。对于大量文本，
     /// 输出速度取决于控制台的性能。
     ///
     /// ## 线程安全
@@ -346,17 +345,35 @@ impl File for Stdout {
     /// ## Examples
     ///
     /// ```
-    /// let stdout = Stdout;
+    /// let stdout = Stdout::default();
     /// let data = b"Hello, World!";
     /// let user_buf = UserBuffer::new(data);
     /// let bytes_written = stdout.write(user_buf);
     /// assert_eq!(bytes_written, 13);
     /// ```
-    fn write(&self, user_buf: UserBuffer) -> usize {
-        for buffer in user_buf.buffers.iter() {
-            print!("{}", core::str::from_utf8(*buffer).unwrap());
-        }
-        user_buf.len()
+    fn write(&self, user_buf: UserBuffer) -> isize {
+        TTY.write(user_buf, self.status.nonblocking())
+    }
+
+    /// 内核写入：终端暂时无法接受输出时不等待
+    fn write_nowait(&self, user_buf: UserBuffer) -> isize {
+        TTY.write(user_buf, true)
+    }
+
+    /// 终端控制，委托给 [`TTY`]
+    fn ioctl(&self, cmd: usize, arg: usize) -> isize {
+        TTY.ioctl(cmd, arg)
+    }
+
+    /// 读取或修改文件状态标志，`O_NONBLOCK` 对终端读写生效
+    fn fcntl(&self, cmd: usize, arg: usize) -> isize {
+        self.status
+            .fcntl(cmd, arg, self.readable(), self.writable())
+    }
+
+    /// 串口输出不会长时间阻塞，总是可写
+    fn poll(&self) -> PollEvents {
+        TTY.poll() - PollEvents::POLLIN
     }
 }
 
@@ -393,14 +410,14 @@ impl File for Stderr {
     ///
     /// 标准错误是只写设备，不支持读取操作。如果尝试读取，
     /// 会触发 panic 以明确表示操作不被支持。
-    fn read(&self, _user_buf: UserBuffer) -> usize {
+    fn read(&self, _user_buf: UserBuffer) -> isize {
         panic!("Cannot read from stderr!");
     }
 
     /// 向标准错误写入文本
     ///
     /// 将用户缓冲区中的错误信息输出到控制台。支持跨页面的用户缓冲区，
-    /// 自动处理页面边界和 UTF-8 编码。
+    /// 自动处理页面边界。
     ///
     /// ## Arguments
     ///
@@ -413,24 +430,23 @@ impl File for Stderr {
     /// ## 输出过程
     ///
     /// 1. **缓冲区遍历**: 遍历用户缓冲区的所有页面
-    /// 2. **文本转换**: 将每个页面的字节转换为 UTF-8 字符串
-    /// 3. **控制台输出**: 使用 `print!` 宏输出到控制台
+    /// 2. **缓冲写入**: 将每个页面的字节放入串口发送缓冲区
+    /// 3. **异步发送**: 由串口发送中断把数据写出
     /// 4. **结果统计**: 统计所有输出的字节数
     ///
     /// ## 文本处理
     ///
-    /// - **UTF-8 支持**: 完全支持 Unicode 字符输出
+    /// - **原始字节**: 按字节原样输出，不做编码检查
     /// - **页面边界**: 自动处理跨页面的用户缓冲区
-    /// - **实时显示**: 错误信息立即显示到控制台，无需缓冲
+    /// - **缓冲发送**: 错误信息经串口发送缓冲区异步显示到控制台
     ///
     /// ## 错误处理
     ///
-    /// - 如果用户缓冲区包含无效的 UTF-8 序列，会触发 panic
-    /// - 如果控制台输出失败，会触发 panic
+    /// - 发送缓冲区已满时同步等待硬件发送，不会丢失数据
     ///
     /// ## 性能说明
     ///
-    /// 该操作是同步的，会立即输出到控制台。对于大量错误信息，
+    /// 该操作只把数据放入发送缓冲区即返回；缓冲区满时才等待硬件。对于大量错误信息，
     /// 输出速度取决于控制台的性能。
     ///
     /// ## 线程安全
@@ -448,16 +464,34 @@ impl File for Stderr {
     /// ## Examples
     ///
     /// ```
-    /// let stderr = Stderr;
+    /// let stderr = Stderr::default();
     /// let error_msg = b"Error: File not found";
     /// let user_buf = UserBuffer::new(error_msg);
     /// let bytes_written = stderr.write(user_buf);
     /// assert_eq!(bytes_written, 20);
     /// ```
-    fn write(&self, user_buf: UserBuffer) -> usize {
-        for buffer in user_buf.buffers.iter() {
-            print!("{}", core::str::from_utf8(*buffer).unwrap());
-        }
-        user_buf.len()
+    fn write(&self, user_buf: UserBuffer) -> isize {
+        TTY.write(user_buf, self.status.nonblocking())
+    }
+
+    /// 内核写入：终端暂时无法接受输出时不等待
+    fn write_nowait(&self, user_buf: UserBuffer) -> isize {
+        TTY.write(user_buf, true)
+    }
+
+    /// 终端控制，委托给 [`TTY`]
+    fn ioctl(&self, cmd: usize, arg: usize) -> isize {
+        TTY.ioctl(cmd, arg)
+    }
+
+    /// 读取或修改文件状态标志，`O_NONBLOCK` 对终端读写生效
+    fn fcntl(&self, cmd: usize, arg: usize) -> isize {
+        self.status
+            .fcntl(cmd, arg, self.readable(), self.writable())
+    }
+
+    /// 串口输出不会长时间阻塞，总是可写
+    fn poll(&self) -> PollEvents {
+        TTY.poll() - PollEvents::POLLIN
     }
 }
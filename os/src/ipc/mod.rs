@@ -0,0 +1,314 @@
+//! # 同步 IPC 端点
+//!
+//! 微内核风格的同步消息传递。进程通过端点句柄（保存在进程的 `ipc_table` 中，
+//! 与 `fd_table` 并列）访问 [`Endpoint`]，句柄在 `fork` 时被继承。
+//!
+//! ## 操作
+//!
+//! - `send`：把消息交给端点上的接收者，阻塞到消息被取走
+//! - `recv`：阻塞到有消息到达，取得消息与发送方
+//! - `call`：发送消息并阻塞到接收者应答
+//! - `reply`：应答最近一次 `recv` 取得的 `call` 消息
+//!
+//! ## 消息
+//!
+//! 每条消息包含 [`IPC_MSG_WORDS`] 个字，直接在发送方与接收方的寄存器之间传递；
+//! 更大的数据以整页的形式转移：发送方缓冲区的页帧被移出并替换为清零的新页帧，
+//! 在接收方缓冲区处替换掉原有的页帧，数据本身不被复制。
+//!
+//! ## 对端关闭
+//!
+//! 端点只剩一个句柄时不会再有对端：阻塞的 `recv` 与排队中的 `send`/`call`
+//! 以 `EPIPE` 失败。接收者在应答前丢弃应答权（再次 `recv` 或退出）时，
+//! 等待应答的调用者同样以 `EPIPE` 失败。
+
+use crate::errno::Errno::{EAGAIN, EINTR, EMSGSIZE, EPIPE};
+use crate::mm::FrameTracker;
+use crate::process::current_signal_pending;
+use crate::sync::{UPSafeCell, WaitQueue};
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// 在寄存器中传递的消息字数
+pub const IPC_MSG_WORDS: usize = 4;
+/// 单条消息最多转移的页数
+pub const IPC_MAX_PAGES: usize = 16;
+
+/// 用户空间中按页转移数据的缓冲区描述
+///
+/// - `addr`：缓冲区起始地址，须按页对齐
+/// - `len`：发送时为数据字节数；接收时由内核写回收到的字节数
+/// - `cap`：接收时缓冲区能容纳的字节数
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IpcBuffer {
+    pub addr: usize,
+    pub len: usize,
+    pub cap: usize,
+}
+
+/// 一条消息
+pub struct IpcMessage {
+    /// 寄存器中传递的字
+    pub words: [usize; IPC_MSG_WORDS],
+    /// 按页转移的数据，最后一页中 `len` 之后的内容没有意义
+    pub frames: Vec<FrameTracker>,
+    /// 按页转移的数据字节数
+    pub len: usize,
+}
+
+enum TransactionState {
+    /// 在端点队列中等待接收者
+    Pending,
+    /// 已被接收，`call` 等待应答
+    Received,
+    /// 已应答
+    Replied(IpcMessage),
+    /// 失败；消息未被接收时仍留在事务中，由发送方取回页帧
+    Failed(isize),
+}
+
+struct TransactionInner {
+    message: Option<IpcMessage>,
+    state: TransactionState,
+}
+
+/// 一次 `send`/`call`
+struct Transaction {
+    sender: usize,
+    /// `call` 时调用者能接收的应答数据字节数；`send` 为 `None`
+    reply_cap: Option<usize>,
+    inner: UPSafeCell<TransactionInner>,
+    /// 发送方在此等待
+    waiters: WaitQueue,
+}
+
+impl Transaction {
+    fn fail(&self, errno: isize) {
+        self.inner.exclusive_access().state = TransactionState::Failed(errno);
+        self.waiters.wake_all();
+    }
+}
+
+struct EndpointInner {
+    queue: VecDeque<Arc<Transaction>>,
+    /// 存活的句柄数
+    handles: usize,
+}
+
+/// IPC 端点
+pub struct Endpoint {
+    inner: UPSafeCell<EndpointInner>,
+    /// 等待消息的接收者
+    receivers: WaitQueue,
+}
+
+/// 端点句柄，克隆与丢弃时维护端点的句柄计数
+pub struct EndpointHandle(Arc<Endpoint>);
+
+impl Endpoint {
+    /// 发送消息并阻塞到它被接收；`reply_cap` 非空时（`call`）继续阻塞到应答
+    ///
+    /// ## Arguments
+    ///
+    /// * `sender` - 发送方 PID
+    /// * `message` - 要发送的消息
+    /// * `reply_cap` - `call` 时能接收的应答数据字节数
+    ///
+    /// ## Returns
+    ///
+    /// `call` 时返回应答；失败时返回错误码，消息未被接收时一并交还
+    pub fn send(
+        &self,
+        sender: usize,
+        message: IpcMessage,
+        reply_cap: Option<usize>,
+    ) -> Result<Option<IpcMessage>, (isize, Option<IpcMessage>)> {
+        if self.inner.exclusive_access().handles <= 1 {
+            return Err((-EPIPE, Some(message)));
+        }
+        let transaction = Arc::new(Transaction {
+            sender,
+            reply_cap,
+            inner: unsafe {
+                UPSafeCell::new(TransactionInner {
+                    message: Some(message),
+                    state: TransactionState::Pending,
+                })
+            },
+            waiters: WaitQueue::new(),
+        });
+        self.inner
+            .exclusive_access()
+            .queue
+            .push_back(transaction.clone());
+        self.receivers.wake_one();
+        loop {
+            let mut inner = transaction.inner.exclusive_access();
+            match core::mem::replace(&mut inner.state, TransactionState::Pending) {
+                TransactionState::Pending => {}
+                TransactionState::Received if reply_cap.is_none() => return Ok(None),
+                TransactionState::Received => inner.state = TransactionState::Received,
+                TransactionState::Replied(reply) => return Ok(Some(reply)),
+                TransactionState::Failed(errno) => return Err((errno, inner.message.take())),
+            }
+            if current_signal_pending() {
+                let message = inner.message.take();
+                // 未被接收时撤回消息；已被接收时放弃应答，之后的 reply 以 EPIPE 失败
+                inner.state = TransactionState::Failed(-EPIPE);
+                drop(inner);
+                self.inner
+                    .exclusive_access()
+                    .queue
+                    .retain(|queued| !Arc::ptr_eq(queued, &transaction));
+                return Err((-EINTR, message));
+            }
+            drop(inner);
+            transaction.waiters.wait();
+        }
+    }
+
+    /// 阻塞到有消息到达并取走它
+    ///
+    /// ## Arguments
+    ///
+    /// * `cap` - 能接收的数据字节数，更大的消息以 `EMSGSIZE` 失败并退回发送方
+    /// * `nonblocking` - 没有消息时立即返回 `EAGAIN`
+    ///
+    /// ## Returns
+    ///
+    /// 发送方 PID、消息，以及消息来自 `call` 时的应答权
+    pub fn recv(
+        &self,
+        cap: usize,
+        nonblocking: bool,
+    ) -> Result<(usize, IpcMessage, Option<ReplyCap>), isize> {
+        loop {
+            let mut inner = self.inner.exclusive_access();
+            if let Some(transaction) = inner.queue.pop_front() {
+                drop(inner);
+                let mut tx_inner = transaction.inner.exclusive_access();
+                if tx_inner.message.as_ref().unwrap().len > cap {
+                    drop(tx_inner);
+                    transaction.fail(-EMSGSIZE);
+                    return Err(-EMSGSIZE);
+                }
+                let message = tx_inner.message.take().unwrap();
+                tx_inner.state = TransactionState::Received;
+                drop(tx_inner);
+                transaction.waiters.wake_all();
+                let sender = transaction.sender;
+                let reply = transaction.reply_cap.map(|_| ReplyCap(transaction));
+                return Ok((sender, message, reply));
+            }
+            if inner.handles <= 1 {
+                return Err(-EPIPE);
+            }
+            drop(inner);
+            if nonblocking {
+                return Err(-EAGAIN);
+            }
+            if current_signal_pending() {
+                return Err(-EINTR);
+            }
+            self.receivers.wait();
+        }
+    }
+}
+
+impl EndpointHandle {
+    /// 创建新的端点并返回它的第一个句柄
+    pub fn new() -> Self {
+        Self(Arc::new(Endpoint {
+            inner: unsafe {
+                UPSafeCell::new(EndpointInner {
+                    queue: VecDeque::new(),
+                    handles: 1,
+                })
+            },
+            receivers: WaitQueue::new(),
+        }))
+    }
+
+    /// 句柄指向的端点
+    ///
+    /// 阻塞的操作通过它进行，不占用句柄计数。
+    pub fn endpoint(&self) -> Arc<Endpoint> {
+        self.0.clone()
+    }
+}
+
+impl Default for EndpointHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for EndpointHandle {
+    fn clone(&self) -> Self {
+        self.0.inner.exclusive_access().handles += 1;
+        Self(self.0.clone())
+    }
+}
+
+impl Drop for EndpointHandle {
+    /// 只剩一个句柄时，排队的发送方与阻塞的接收者都不会再有对端
+    fn drop(&mut self) {
+        let orphaned = {
+            let mut inner = self.0.inner.exclusive_access();
+            inner.handles -= 1;
+            if inner.handles > 1 {
+                return;
+            }
+            core::mem::take(&mut inner.queue)
+        };
+        for transaction in orphaned {
+            transaction.fail(-EPIPE);
+        }
+        self.0.receivers.wake_all();
+    }
+}
+
+/// 应答一次 `call` 的权利，只能使用一次
+///
+/// 未应答就被丢弃时，等待应答的调用者以 `EPIPE` 失败。
+pub struct ReplyCap(Arc<Transaction>);
+
+impl ReplyCap {
+    /// 把应答交给调用者
+    ///
+    /// ## Returns
+    ///
+    /// 失败时返回错误码并交还应答：调用者已放弃等待时为 `EPIPE`；
+    /// 应答数据超出调用者的缓冲区时为 `EMSGSIZE`，调用者同样以它失败
+    pub fn reply(self, message: IpcMessage) -> Result<(), (isize, IpcMessage)> {
+        let transaction = &self.0;
+        let mut inner = transaction.inner.exclusive_access();
+        if !matches!(inner.state, TransactionState::Received) {
+            return Err((-EPIPE, message));
+        }
+        if message.len > transaction.reply_cap.unwrap() {
+            drop(inner);
+            transaction.fail(-EMSGSIZE);
+            return Err((-EMSGSIZE, message));
+        }
+        inner.state = TransactionState::Replied(message);
+        drop(inner);
+        transaction.waiters.wake_all();
+        Ok(())
+    }
+}
+
+impl Drop for ReplyCap {
+    fn drop(&mut self) {
+        let transaction = &self.0;
+        let unanswered = matches!(
+            transaction.inner.exclusive_access().state,
+            TransactionState::Received
+        );
+        if unanswered {
+            transaction.fail(-EPIPE);
+        }
+    }
+}
@@ -14,12 +14,15 @@
 //!
 //! - [`process`] - 进程管理和调度系统
 //! - [`mm`] - 内存管理系统（页表、页帧分配、地址空间）
+//! - [`net`] - 套接字（Unix 域套接字与 TCP/IP 协议栈）
+//! - [`ipc`] - 微内核风格的同步 IPC 端点
 //! - [`syscall`] - 系统调用处理和分发
 //! - [`trap`] - 陷阱处理（异常、中断、系统调用）
 //! - [`timer`] - 时钟管理和定时中断
 //! - [`console`] - 控制台输入输出
 //! - [`sync`] - 同步原语（UPSafeCell 等）
 //! - [`config`] - 系统配置常量
+//! - [`errno`] - 与 Linux 编号一致的错误码
 //! - [`sbi`] - SBI 接口封装
 //!
 //! ## 系统架构
@@ -47,10 +50,13 @@
 mod config;
 mod console;
 mod drivers;
+mod errno;
 mod fs;
+mod ipc;
 mod lang_items;
 mod log;
 mod mm;
+mod net;
 mod process;
 mod sbi;
 mod stack_trace;
@@ -80,10 +86,13 @@ global_asm!(include_str!("entry.asm"));
 /// 2. [`log::init`] - 初始化日志系统
 /// 3. [`mm::init`] - 初始化内存管理系统
 /// 4. [`mm::remap_test`] - 测试内存重映射功能
-/// 5. [`process::add_initproc`] - 注册初始用户进程
-/// 6. [`trap::init`] - 初始化陷阱处理系统
-/// 7. [`timer::next_trigger`] - 设置第一次时钟中断
-/// 8. [`process::run_process`] - 进入主调度循环
+/// 5. [`drivers::serial::init`] - 初始化串口并将控制台切换到串口
+/// 6. [`process::add_initproc`] - 注册初始用户进程
+/// 7. [`trap::init`] - 初始化陷阱处理系统
+/// 8. [`timer::next_trigger`] - 设置第一次时钟中断
+/// 9. [`drivers::init`] - 初始化 PLIC 并注册设备中断
+/// 10. [`net::init`] - 登记网卡中断
+/// 11. [`process::run_process`] - 进入主调度循环
 ///
 /// ## Panics
 ///
@@ -94,8 +103,11 @@ pub fn rust_main() -> ! {
     log::init();
     ::log::info!("[kernel] Hello, world!");
     mm::init();
+    drivers::serial::init();
     trap::init();
     timer::next_trigger();
+    drivers::init();
+    net::init();
     fs::list_apps();
     process::add_initproc();
     process::run_process();
@@ -0,0 +1,349 @@
+//! # ELF 可执行文件解析与校验
+//!
+//! 在建立用户地址空间之前，对 ELF 文件做完整的合法性检查，
+//! 任何不符合要求的文件都以 [`ElfError`] 的形式返回给 `exec`，
+//! 而不是在内核中触发断言。
+//!
+//! ## 校验内容
+//!
+//! - 文件头：64 位、小端、RISC-V 机器类型，类型为 `ET_EXEC` 或 `ET_DYN`
+//! - 程序头表：表项大小正确且整张表位于文件之内
+//! - `PT_LOAD` 段：文件范围不越界、`p_filesz <= p_memsz`、对齐合法、
+//!   映射后位于用户地址范围内且按页互不重叠
+//! - 入口地址：必须落在可执行的 `PT_LOAD` 段内
+//! - 不支持动态链接：存在 `PT_INTERP` 时拒绝加载
+//!
+//! ## 位置无关可执行文件
+//!
+//! `ET_DYN` 类型的文件（静态 PIE）被加载到 [`ELF_DYN_BASE`]，
+//! 其 `PT_DYNAMIC` 中的 `DT_RELA` 重定位表只允许包含
+//! `R_RISCV_RELATIVE`（及 `R_RISCV_NONE`），由 [`relocations`] 计算出
+//! 需要写入的地址与值。
+
+use super::MapPermission;
+use crate::config::{ELF_DYN_BASE, PAGE_SIZE, USER_SPACE_END, USER_STACK_SIZE_MAX};
+use alloc::vec::Vec;
+use xmas_elf::ElfFile;
+use xmas_elf::header::{self, Class, Data, Machine};
+use xmas_elf::program::Type;
+
+/// ELF 文件头魔数
+pub const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+
+/// `PT_GNU_STACK`：描述用户栈是否需要可执行权限
+const PT_GNU_STACK: u32 = 0x6474_e551;
+
+/// 64 位程序头表项大小
+pub const PHENT_SIZE: usize = 56;
+
+/// 动态段表项大小（`Elf64_Dyn`）
+const DYN_SIZE: usize = 16;
+/// 重定位表项大小（`Elf64_Rela`）
+const RELA_SIZE: usize = 24;
+
+const DT_NULL: u64 = 0;
+const DT_RELA: u64 = 7;
+const DT_RELASZ: u64 = 8;
+const DT_RELAENT: u64 = 9;
+const DT_REL: u64 = 17;
+
+const R_RISCV_NONE: u32 = 0;
+const R_RISCV_RELATIVE: u32 = 3;
+
+/// ELF 加载失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElfError {
+    /// 文件头或程序头无法解析
+    Malformed(&'static str),
+    /// 不是 64 位小端 RISC-V 文件
+    WrongArch,
+    /// 既不是 `ET_EXEC` 也不是 `ET_DYN`
+    UnsupportedType,
+    /// 需要动态链接器（存在 `PT_INTERP`）
+    Interpreter,
+    /// 段的文件范围超出文件大小
+    SegmentOutOfFile,
+    /// 段映射后超出用户地址范围
+    SegmentOutOfRange,
+    /// 两个段映射到同一页
+    SegmentOverlap,
+    /// 段对齐不合法
+    BadAlignment,
+    /// 入口地址不在可执行段内
+    BadEntry,
+    /// 动态段或重定位表格式错误
+    BadDynamic,
+    /// 不支持的重定位类型
+    UnsupportedRelocation(u32),
+}
+
+/// 一个待映射的 `PT_LOAD` 段（地址已加上加载基址）
+#[derive(Debug, Clone, Copy)]
+pub struct LoadSegment {
+    pub vaddr: usize,
+    pub mem_size: usize,
+    pub offset: usize,
+    pub file_size: usize,
+    pub perm: MapPermission,
+}
+
+/// 校验通过的 ELF 映像
+pub struct ElfImage {
+    /// 加载基址：`ET_EXEC` 为 0，`ET_DYN` 为 [`ELF_DYN_BASE`]
+    pub base: usize,
+    /// 程序入口（已加上加载基址）
+    pub entry: usize,
+    /// 需要映射的段，按地址升序排列
+    pub segments: Vec<LoadSegment>,
+    /// 程序头表的虚拟地址（无法确定时为 0）
+    pub phdr: usize,
+    pub phnum: usize,
+    /// `PT_GNU_STACK` 要求用户栈可执行
+    pub stack_exec: bool,
+    /// `PT_DYNAMIC` 段在文件中的范围（仅 `ET_DYN`）
+    dynamic: Option<(usize, usize)>,
+}
+
+impl ElfImage {
+    /// 映射全部段所需的字节数（每段按页向外取整）
+    pub fn mapped_size(&self) -> usize {
+        self.segments
+            .iter()
+            .map(|seg| {
+                let pages = (seg.vaddr + seg.mem_size).div_ceil(PAGE_SIZE) - seg.vaddr / PAGE_SIZE;
+                pages * PAGE_SIZE
+            })
+            .sum()
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset.checked_add(8)?)?;
+    Some(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// 解析并校验 ELF 文件
+///
+/// ## Arguments
+///
+/// * `elf_data` - 完整的 ELF 文件内容
+///
+/// ## Returns
+///
+/// 校验通过时返回 [`ElfImage`]，否则返回具体的 [`ElfError`]
+pub fn parse(elf_data: &[u8]) -> Result<ElfImage, ElfError> {
+    if !elf_data.starts_with(&ELF_MAGIC) {
+        return Err(ElfError::Malformed("bad magic"));
+    }
+    let elf = ElfFile::new(elf_data).map_err(ElfError::Malformed)?;
+    let pt1 = &elf.header.pt1;
+    let pt2 = &elf.header.pt2;
+    if !matches!(pt1.class(), Class::SixtyFour)
+        || !matches!(pt1.data(), Data::LittleEndian)
+        || !matches!(pt2.machine().as_machine(), Machine::RISC_V)
+    {
+        return Err(ElfError::WrongArch);
+    }
+    let base = match pt2.type_().as_type() {
+        header::Type::Executable => 0,
+        header::Type::SharedObject => ELF_DYN_BASE,
+        _ => return Err(ElfError::UnsupportedType),
+    };
+
+    let ph_offset = pt2.ph_offset() as usize;
+    let phnum = pt2.ph_count() as usize;
+    if pt2.ph_entry_size() as usize != PHENT_SIZE {
+        return Err(ElfError::Malformed("bad program header size"));
+    }
+    let ph_end = phnum
+        .checked_mul(PHENT_SIZE)
+        .and_then(|size| size.checked_add(ph_offset))
+        .ok_or(ElfError::Malformed("program header table overflow"))?;
+    if ph_end > elf_data.len() {
+        return Err(ElfError::SegmentOutOfFile);
+    }
+
+    // 用户栈位于最高段之上，需为保护页和最大栈预留空间
+    let load_limit = USER_SPACE_END - PAGE_SIZE - USER_STACK_SIZE_MAX;
+    let mut segments = Vec::new();
+    let mut phdr = 0;
+    let mut stack_exec = false;
+    let mut dynamic = None;
+    for i in 0..phnum {
+        let ph = elf.program_header(i as u16).map_err(ElfError::Malformed)?;
+        let offset = ph.offset() as usize;
+        let file_size = ph.file_size() as usize;
+        let vaddr = ph.virtual_addr() as usize;
+        let mem_size = ph.mem_size() as usize;
+        let file_end = offset
+            .checked_add(file_size)
+            .ok_or(ElfError::SegmentOutOfFile)?;
+        match ph.get_type() {
+            Ok(Type::Load) => {
+                if file_size > mem_size {
+                    return Err(ElfError::Malformed("p_filesz larger than p_memsz"));
+                }
+                if file_end > elf_data.len() {
+                    return Err(ElfError::SegmentOutOfFile);
+                }
+                let align = ph.align() as usize;
+                if align > 1 && (!align.is_power_of_two() || vaddr % align != offset % align) {
+                    return Err(ElfError::BadAlignment);
+                }
+                if mem_size == 0 {
+                    continue;
+                }
+                let start = vaddr.checked_add(base).ok_or(ElfError::SegmentOutOfRange)?;
+                let end = start
+                    .checked_add(mem_size)
+                    .ok_or(ElfError::SegmentOutOfRange)?;
+                if start < PAGE_SIZE || end > load_limit {
+                    return Err(ElfError::SegmentOutOfRange);
+                }
+                // 没有 PT_PHDR 时，由覆盖程序头表的 LOAD 段推算其虚拟地址
+                if phdr == 0 && offset <= ph_offset && ph_end <= file_end {
+                    phdr = start + (ph_offset - offset);
+                }
+                let mut perm = MapPermission::U;
+                let flags = ph.flags();
+                if flags.is_read() {
+                    perm |= MapPermission::R;
+                }
+                if flags.is_write() {
+                    perm |= MapPermission::W;
+                }
+                if flags.is_execute() {
+                    perm |= MapPermission::X;
+                }
+                segments.push(LoadSegment {
+                    vaddr: start,
+                    mem_size,
+                    offset,
+                    file_size,
+                    perm,
+                });
+            }
+            Ok(Type::Phdr) => phdr = vaddr + base,
+            Ok(Type::Interp) => return Err(ElfError::Interpreter),
+            Ok(Type::Dynamic) if base != 0 => {
+                if file_end > elf_data.len() {
+                    return Err(ElfError::SegmentOutOfFile);
+                }
+                dynamic = Some((offset, file_size));
+            }
+            Ok(Type::OsSpecific(PT_GNU_STACK)) => stack_exec = ph.flags().is_execute(),
+            // 其余类型（含未知类型）与加载无关，直接忽略
+            _ => {}
+        }
+    }
+    if segments.is_empty() {
+        return Err(ElfError::Malformed("no loadable segment"));
+    }
+
+    // 按页检查段之间是否重叠
+    segments.sort_by_key(|seg| seg.vaddr);
+    for pair in segments.windows(2) {
+        let prev_end_page = (pair[0].vaddr + pair[0].mem_size).div_ceil(PAGE_SIZE);
+        if pair[1].vaddr / PAGE_SIZE < prev_end_page {
+            return Err(ElfError::SegmentOverlap);
+        }
+    }
+
+    let entry = (pt2.entry_point() as usize)
+        .checked_add(base)
+        .ok_or(ElfError::BadEntry)?;
+    if !segments.iter().any(|seg| {
+        seg.perm.contains(MapPermission::X)
+            && (seg.vaddr..seg.vaddr + seg.mem_size).contains(&entry)
+    }) {
+        return Err(ElfError::BadEntry);
+    }
+
+    Ok(ElfImage {
+        base,
+        entry,
+        segments,
+        phdr,
+        phnum,
+        stack_exec,
+        dynamic,
+    })
+}
+
+/// 计算 PIE 的重定位结果
+///
+/// 从 `PT_DYNAMIC` 中找到 `DT_RELA` 表，逐项计算 `R_RISCV_RELATIVE`
+/// 重定位：`*(base + r_offset) = base + r_addend`。
+///
+/// ## Arguments
+///
+/// * `elf_data` - 完整的 ELF 文件内容
+/// * `image` - [`parse`] 返回的映像
+///
+/// ## Returns
+///
+/// 需要写入用户地址空间的 `(地址, 值)` 列表；每个地址都已确认 8 字节对齐
+/// 且位于某个 `PT_LOAD` 段内
+pub fn relocations(elf_data: &[u8], image: &ElfImage) -> Result<Vec<(usize, usize)>, ElfError> {
+    let Some((dyn_offset, dyn_size)) = image.dynamic else {
+        return Ok(Vec::new());
+    };
+    let (mut rela, mut rela_size, mut rela_ent) = (None, 0, RELA_SIZE as u64);
+    for entry in (dyn_offset..dyn_offset + dyn_size).step_by(DYN_SIZE) {
+        let tag = read_u64(elf_data, entry).ok_or(ElfError::BadDynamic)?;
+        let value = read_u64(elf_data, entry + 8).ok_or(ElfError::BadDynamic)?;
+        match tag {
+            DT_NULL => break,
+            DT_RELA => rela = Some(value as usize),
+            DT_RELASZ => rela_size = value as usize,
+            DT_RELAENT => rela_ent = value,
+            DT_REL if value != 0 => return Err(ElfError::BadDynamic),
+            _ => {}
+        }
+    }
+    let Some(rela) = rela else {
+        return Ok(Vec::new());
+    };
+    if rela_ent != RELA_SIZE as u64 || rela_size % RELA_SIZE != 0 {
+        return Err(ElfError::BadDynamic);
+    }
+
+    // 重定位表以虚拟地址给出，换算为文件偏移
+    let rela_va = rela + image.base;
+    let table = image
+        .segments
+        .iter()
+        .find(|seg| {
+            rela_va >= seg.vaddr
+                && rela_va
+                    .checked_add(rela_size)
+                    .is_some_and(|end| end <= seg.vaddr + seg.file_size)
+        })
+        .map(|seg| seg.offset + (rela_va - seg.vaddr))
+        .ok_or(ElfError::BadDynamic)?;
+
+    let mut result = Vec::with_capacity(rela_size / RELA_SIZE);
+    for entry in (table..table + rela_size).step_by(RELA_SIZE) {
+        let r_offset = read_u64(elf_data, entry).ok_or(ElfError::BadDynamic)? as usize;
+        let r_info = read_u64(elf_data, entry + 8).ok_or(ElfError::BadDynamic)?;
+        let r_addend = read_u64(elf_data, entry + 16).ok_or(ElfError::BadDynamic)? as usize;
+        match r_info as u32 {
+            R_RISCV_NONE => {}
+            R_RISCV_RELATIVE => {
+                let addr = r_offset.wrapping_add(image.base);
+                let in_segment = image.segments.iter().any(|seg| {
+                    addr >= seg.vaddr
+                        && addr
+                            .checked_add(8)
+                            .is_some_and(|end| end <= seg.vaddr + seg.mem_size)
+                });
+                if addr % 8 != 0 || !in_segment {
+                    return Err(ElfError::BadDynamic);
+                }
+                result.push((addr, r_addend.wrapping_add(image.base)));
+            }
+            other => return Err(ElfError::UnsupportedRelocation(other)),
+        }
+    }
+    Ok(result)
+}
@@ -7,7 +7,7 @@
 //!
 //! - [`MemorySet`] - 完整的地址空间，包含页表和多个内存映射区域
 //! - [`MapArea`] - 单个连续的内存映射区域，具有统一的映射类型和权限
-//! - [`MapType`] - 映射类型：恒等映射或帧映射
+//! - [`MapType`] - 映射类型：恒等映射、帧映射或共享内存映射
 //! - [`MapPermission`] - 内存访问权限：读、写、执行、用户态访问
 //!
 //! ## 地址空间布局
@@ -61,6 +61,11 @@
 //! - **优势**: 灵活、安全、支持地址空间隔离
 //! - **成本**: 需要额外的页表和页帧管理
 //!
+//! ### Shared 映射 (共享内存映射)
+//! - **特点**: 虚拟页映射到共享内存段的页帧，页帧由段而不是区域拥有
+//! - **用途**: System V 共享内存（`shmat`）
+//! - **fork**: 子进程与父进程映射同一组页帧，不复制数据
+//!
 //! ## 使用示例
 //!
 //! ```rust
@@ -80,11 +85,16 @@
 //! memory_set.activate();
 //! ```
 
+use super::elf::{self, ElfError};
+use super::shm::ShmSegment;
 use super::{
     FrameTracker, PhysAddr, PhysPageNum, StepByOne, VPNRange, VirtAddr, VirtPageNum, frame_alloc,
     page_table::{PTEFlags, PageTable, PageTableEntry},
 };
-use crate::config::{MEMORY_END, MMIO, PAGE_SIZE, TRAMPOLINE, TRAP_CONTEXT, USER_STACK_SIZE};
+use crate::config::{
+    MEMORY_END, MMIO, PAGE_SIZE, SHM_BASE, TRAMPOLINE, TRAP_CONTEXT, USER_SPACE_END,
+    USER_STACK_SIZE_MAX,
+};
 use crate::println;
 use crate::sync::UPSafeCell;
 use alloc::collections::btree_map::BTreeMap;
@@ -140,6 +150,7 @@ lazy_static! {
 /// - `data_frames`: 虚拟页号到物理页帧的映射表（仅用于 Framed 映射）
 /// - `map_type`: 映射类型（恒等映射或帧映射）
 /// - `map_perm`: 访问权限（读/写/执行/用户态）
+/// - `shm`: 映射的共享内存段（仅用于 Shared 映射）
 ///
 /// ## 设计原理
 ///
@@ -151,6 +162,39 @@ pub struct MapArea {
     data_frames: BTreeMap<VirtPageNum, FrameTracker>,
     map_type: MapType,
     map_perm: MapPermission,
+    shm: Option<Arc<ShmSegment>>,
+}
+
+/// 从 ELF 文件建立用户地址空间失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadError {
+    /// 文件未通过校验
+    BadElf(ElfError),
+    /// 程序段与用户栈的总大小超过 `RLIMIT_AS`
+    AddressSpaceLimit,
+    /// 物理页帧不足
+    OutOfMemory,
+}
+
+impl From<ElfError> for LoadError {
+    fn from(err: ElfError) -> Self {
+        LoadError::BadElf(err)
+    }
+}
+
+/// ELF 程序头表信息
+///
+/// 由 [`MemorySet::from_elf`] 解析得到，`exec` 据此在初始用户栈上填写
+/// `AT_PHDR`/`AT_PHENT`/`AT_PHNUM` 辅助向量项。
+///
+/// - `phdr`：程序头表在用户地址空间中的虚拟地址（未映射时为 0）
+/// - `phent`：单个程序头的字节数
+/// - `phnum`：程序头数量
+#[derive(Debug, Clone, Copy)]
+pub struct ElfAuxInfo {
+    pub phdr: usize,
+    pub phent: usize,
+    pub phnum: usize,
 }
 
 /// 内存集合（地址空间）
@@ -187,6 +231,7 @@ pub struct MemorySet {
 /// |------|----------|------|------|------|
 /// | Identical | VA = PA | 内核段、设备 | 简单、高效 | 无隔离 |
 /// | Framed | VA ≠ PA | 用户程序、堆 | 灵活、安全 | 复杂、开销 |
+/// | Shared | VA ≠ PA | 共享内存段 | 跨地址空间共享 | 需要引用计数 |
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum MapType {
     /// 恒等映射
@@ -210,6 +255,16 @@ pub enum MapType {
     /// - 可以实现写时复制、懒加载等高级功能
     /// - 需要维护虚拟页到物理页的映射表
     Framed,
+
+    /// 共享内存映射
+    ///
+    /// 每个虚拟页面映射到区域所附加的共享内存段中对应的页帧。
+    /// 页帧归段所有，取消映射时不会释放。
+    ///
+    /// ## 特点
+    /// - 多个地址空间可以映射同一组页帧
+    /// - 区域存在期间计为段的一次附加
+    Shared,
 }
 
 bitflags! {
@@ -325,6 +380,34 @@ impl MapArea {
             data_frames: BTreeMap::new(),
             map_type,
             map_perm,
+            shm: None,
+        }
+    }
+
+    /// 创建映射整个共享内存段的区域
+    ///
+    /// 区域从 `start_va` 开始，长度为段的页数；创建即计为段的一次附加，
+    /// 区域被丢弃时自动解除附加。
+    ///
+    /// ## Arguments
+    ///
+    /// * `start_va` - 区域起始虚拟地址（按页对齐）
+    /// * `segment` - 要映射的共享内存段
+    /// * `map_perm` - 内存访问权限
+    pub fn new_shared(
+        start_va: VirtAddr,
+        segment: Arc<ShmSegment>,
+        map_perm: MapPermission,
+    ) -> Self {
+        let start_vpn: VirtPageNum = start_va.floor();
+        let end_vpn = VirtPageNum(start_vpn.0 + segment.page_count());
+        segment.attach();
+        Self {
+            vpn_range: VPNRange::new(start_vpn, end_vpn),
+            data_frames: BTreeMap::new(),
+            map_type: MapType::Shared,
+            map_perm,
+            shm: Some(segment),
         }
     }
 
@@ -359,9 +442,21 @@ impl MapArea {
     /// area.map(&mut page_table); // 建立所有页面映射
     /// ```
     pub fn map(&mut self, page_table: &mut PageTable) {
+        self.try_map(page_table).unwrap();
+    }
+
+    /// 映射整个内存区域，物理页帧不足时返回错误
+    ///
+    /// 失败时已分配的页帧仍记录在本区域中，随区域一起释放。
+    ///
+    /// ## Returns
+    ///
+    /// 成功返回 `Ok(())`；页帧分配失败时返回 `-ENOMEM`
+    pub fn try_map(&mut self, page_table: &mut PageTable) -> Result<(), isize> {
         for vpn in self.vpn_range {
-            self.map_one(page_table, vpn);
+            self.try_map_one(page_table, vpn)?;
         }
+        Ok(())
     }
 
     /// 从页表中取消整个内存区域的映射
@@ -398,6 +493,7 @@ impl MapArea {
     ///
     /// * `page_table` - 用于地址转换的页表引用
     /// * `data` - 要复制的源数据
+    /// * `page_offset` - 数据在区域首页内的起始偏移（段起始地址未按页对齐时非零）
     ///
     /// ## 复制过程
     ///
@@ -426,25 +522,25 @@ impl MapArea {
     ///
     /// // 复制 ELF 文件数据
     /// let elf_data = &[0x7f, 0x45, 0x4c, 0x46, /* ... */];
-    /// area.copy_data(&page_table, elf_data);
+    /// area.copy_data(&page_table, elf_data, 0);
     /// ```
-    pub fn copy_data(&mut self, page_table: &PageTable, data: &[u8]) {
+    pub fn copy_data(&mut self, page_table: &PageTable, data: &[u8], page_offset: usize) {
         assert_eq!(self.map_type, MapType::Framed);
         let mut start: usize = 0;
+        let mut page_offset = page_offset;
         let mut current_vpn = self.vpn_range.start();
         let len = data.len();
-        loop {
-            let src = &data[start..len.min(start + PAGE_SIZE)];
+        while start < len {
+            let chunk = (PAGE_SIZE - page_offset).min(len - start);
+            let src = &data[start..start + chunk];
             let dst = &mut page_table
                 .translate(current_vpn)
                 .unwrap()
                 .ppn()
-                .bytes_array()[..src.len()];
+                .bytes_array()[page_offset..page_offset + chunk];
             dst.copy_from_slice(src);
-            start += PAGE_SIZE;
-            if start >= len {
-                break;
-            }
+            start += chunk;
+            page_offset = 0;
             current_vpn.step();
         }
     }
@@ -484,19 +580,36 @@ impl MapArea {
     /// area.map_one(&mut page_table, vpn);
     /// ```
     pub fn map_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
+        self.try_map_one(page_table, vpn).unwrap();
+    }
+
+    /// 映射单个虚拟页面，物理页帧不足时返回错误
+    ///
+    /// ## Returns
+    ///
+    /// 成功返回 `Ok(())`；数据页帧或中间页表分配失败时返回 `-ENOMEM`
+    pub fn try_map_one(
+        &mut self,
+        page_table: &mut PageTable,
+        vpn: VirtPageNum,
+    ) -> Result<(), isize> {
         let ppn: PhysPageNum;
         match self.map_type {
             MapType::Identical => {
                 ppn = PhysPageNum(vpn.0);
             }
             MapType::Framed => {
-                let frame = frame_alloc().unwrap();
+                let frame = frame_alloc().ok_or(-ENOMEM)?;
                 ppn = frame.ppn;
                 self.data_frames.insert(vpn, frame);
             }
+            MapType::Shared => {
+                let index = vpn.0 - self.vpn_range.start().0;
+                ppn = self.shm.as_ref().unwrap().frame(index).ppn;
+            }
         }
         let pte_flags = PTEFlags::from_bits(self.map_perm.bits()).unwrap();
-        page_table.map(vpn, ppn, pte_flags);
+        page_table.try_map(vpn, ppn, pte_flags)
     }
 
     /// 取消单个虚拟页面的映射
@@ -531,7 +644,7 @@ impl MapArea {
     #[allow(unused)]
     pub fn unmap_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
         match self.map_type {
-            MapType::Identical => {}
+            MapType::Identical | MapType::Shared => {}
             MapType::Framed => {
                 let frame = self.data_frames.remove(&vpn).unwrap();
                 // frame_dealloc(frame.ppn);
@@ -680,13 +793,27 @@ impl MapArea {
     /// ## 内存安全
     ///
     /// 新创建的区域与源区域完全独立，不会共享任何物理页帧，
-    /// 确保地址空间隔离和内存安全。
+    /// 确保地址空间隔离和内存安全。共享内存区域例外：新区域映射同一个段，
+    /// 并计为段的一次新的附加。
     pub fn from_another(another: &Self) -> Self {
+        if let Some(segment) = another.shm.as_ref() {
+            segment.attach();
+        }
         Self {
             vpn_range: VPNRange::new(another.vpn_range.start(), another.vpn_range.end()),
             data_frames: BTreeMap::new(),
             map_type: another.map_type,
             map_perm: another.map_perm,
+            shm: another.shm.clone(),
+        }
+    }
+}
+
+impl Drop for MapArea {
+    /// 共享内存区域被丢弃（`shmdt`、`exec`、进程退出）时解除对段的附加
+    fn drop(&mut self) {
+        if let Some(segment) = self.shm.take() {
+            segment.detach();
         }
     }
 }
@@ -756,12 +883,37 @@ impl MemorySet {
     /// - 添加 ELF 文件的代码段和数据段
     /// - 添加用户栈区域
     /// - 添加内核的各个逻辑段
-    fn push(&mut self, mut map_area: MapArea, data: Option<&[u8]>) {
-        map_area.map(&mut self.page_table);
+    fn push(&mut self, map_area: MapArea, data: Option<&[u8]>) {
+        self.push_at(map_area, data, 0);
+    }
+
+    /// 将内存区域添加到地址空间，初始化数据从首页内 `page_offset` 处开始写入
+    ///
+    /// 用于起始地址未按页对齐的 ELF 段，其余行为与 [`MemorySet::push`] 相同。
+    fn push_at(&mut self, map_area: MapArea, data: Option<&[u8]>, page_offset: usize) {
+        self.try_push_at(map_area, data, page_offset).unwrap();
+    }
+
+    /// 将内存区域添加到地址空间，物理页帧不足时返回错误
+    ///
+    /// 用于 `exec` 建立用户地址空间：失败时区域不会加入地址空间，
+    /// 已分配的页帧随区域释放。
+    ///
+    /// ## Returns
+    ///
+    /// 成功返回 `Ok(())`；页帧分配失败时返回 `-ENOMEM`
+    fn try_push_at(
+        &mut self,
+        mut map_area: MapArea,
+        data: Option<&[u8]>,
+        page_offset: usize,
+    ) -> Result<(), isize> {
+        map_area.try_map(&mut self.page_table)?;
         if let Some(data) = data {
-            map_area.copy_data(&self.page_table, data);
+            map_area.copy_data(&self.page_table, data, page_offset);
         }
         self.areas.push(map_area);
+        Ok(())
     }
 
     /// 插入帧映射内存区域
@@ -837,11 +989,16 @@ impl MemorySet {
     ///
     /// Trampoline 代码经过精心设计，不会泄露内核信息给用户程序。
     fn map_trampoline(&mut self) {
-        self.page_table.map(
+        self.try_map_trampoline().unwrap();
+    }
+
+    /// 映射 Trampoline 页面，中间页表分配失败时返回 `-ENOMEM`
+    fn try_map_trampoline(&mut self) -> Result<(), isize> {
+        self.page_table.try_map(
             VirtAddr::from(TRAMPOLINE).into(),
             PhysAddr::from(strampoline as usize).into(),
             PTEFlags::R | PTEFlags::X,
-        );
+        )
     }
 
     /// 创建内核地址空间
@@ -962,6 +1119,21 @@ impl MemorySet {
         memory_set
     }
 
+    /// 按 `RLIMIT_STACK` 计算 [`MemorySet::from_elf`] 实际映射的用户栈大小
+    ///
+    /// ## Arguments
+    ///
+    /// * `limit` - 栈大小上限（字节）
+    ///
+    /// ## Returns
+    ///
+    /// 向上取整到页，并限制在 [`PAGE_SIZE`, `USER_STACK_SIZE_MAX`] 之间的栈大小
+    pub fn user_stack_size(limit: usize) -> usize {
+        limit
+            .clamp(PAGE_SIZE, USER_STACK_SIZE_MAX)
+            .next_multiple_of(PAGE_SIZE)
+    }
+
     /// 从 ELF 文件创建用户地址空间
     ///
     /// 解析 ELF 文件并构建相应的用户程序地址空间，包括程序的各个段、用户栈、
@@ -970,13 +1142,21 @@ impl MemorySet {
     /// ## Arguments
     ///
     /// * `elf_data` - ELF 文件的二进制数据
+    /// * `user_stack_size` - 用户栈大小上限（字节），实际大小见 [`MemorySet::user_stack_size`]
+    /// * `as_limit` - 地址空间大小上限（字节），即 `RLIMIT_AS`
     ///
     /// ## Returns
     ///
-    /// 返回一个三元组：
+    /// 成功时返回一个四元组：
     /// - `MemorySet`: 构建好的用户地址空间
     /// - `usize`: 用户栈顶地址
-    /// - `usize`: 程序入口点地址
+    /// - `usize`: 程序入口点地址（PIE 已加上加载基址）
+    /// - [`ElfAuxInfo`]: 程序头表信息，供 `exec` 填写辅助向量
+    ///
+    /// 失败时返回 [`LoadError`]：
+    /// - 文件未通过校验，或按页取整后的段总大小加上用户栈超过 `as_limit`，
+    ///   此时不会分配任何物理页帧
+    /// - 映射过程中物理页帧不足，已分配的页帧随未完成的地址空间一并释放
     ///
     /// ## 地址空间布局
     ///
@@ -1003,12 +1183,12 @@ impl MemorySet {
     ///
     /// ## ELF 解析过程
     ///
-    /// 1. **验证 ELF 魔数**: 确保文件格式正确
-    /// 2. **解析程序头**: 遍历所有 `LOAD` 类型的程序段
-    /// 3. **权限转换**: 将 ELF 段标志转换为 `MapPermission`
-    /// 4. **段映射**: 为每个段创建 Framed 映射并复制数据
-    /// 5. **用户栈**: 在程序段之上分配用户栈空间
-    /// 6. **系统区域**: 映射 Trap Context 和 Trampoline
+    /// 1. **校验**: 由 [`elf::parse`] 检查文件头、机器类型与各段边界，
+    ///    `ET_DYN` 文件选定加载基址 `ELF_DYN_BASE`
+    /// 2. **段映射**: 为每个 `LOAD` 段创建 Framed 映射，按段内页偏移复制数据
+    /// 3. **重定位**: PIE 按 `R_RISCV_RELATIVE` 修正 `base + r_offset` 处的值
+    /// 4. **用户栈**: 在程序段之上分配用户栈空间，`PT_GNU_STACK` 要求时可执行
+    /// 5. **系统区域**: 映射 Trap Context 和 Trampoline
     ///
     /// ## 权限映射
     ///
@@ -1024,56 +1204,55 @@ impl MemorySet {
     /// - 用户栈与程序段之间有保护页面防止栈溢出
     /// - Trap Context 仅内核可写，用户只读
     ///
-    /// ## Panics
-    ///
-    /// - ELF 魔数验证失败
-    /// - ELF 文件格式错误
-    /// - 物理页帧分配失败
-    ///
     /// ## Examples
     ///
     /// ```rust
     /// let app_data = app_data(0); // 获取应用程序 ELF 数据
-    /// let (memory_set, user_stack_top, entry_point) = MemorySet::from_elf(app_data);
+    /// let (memory_set, user_stack_top, entry_point, _aux) =
+    ///     MemorySet::from_elf(app_data, USER_STACK_SIZE, RLIM_INFINITY).unwrap();
     ///
     /// println!("Entry point: {:#x}", entry_point);
     /// println!("User stack top: {:#x}", user_stack_top);
     /// ```
-    pub fn from_elf(elf_data: &[u8]) -> (Self, usize, usize) {
-        let mut memory_set = Self::new_bare();
+    pub fn from_elf(
+        elf_data: &[u8],
+        user_stack_size: usize,
+        as_limit: usize,
+    ) -> Result<(Self, usize, usize, ElfAuxInfo), LoadError> {
+        let image = elf::parse(elf_data)?;
+        let relocations = elf::relocations(elf_data, &image)?;
+        let user_stack_size = Self::user_stack_size(user_stack_size);
+        if image.mapped_size().saturating_add(user_stack_size) > as_limit {
+            return Err(LoadError::AddressSpaceLimit);
+        }
+        let oom = |_: isize| LoadError::OutOfMemory;
 
-        memory_set.map_trampoline();
+        let mut memory_set = Self {
+            page_table: PageTable::try_new().map_err(oom)?,
+            areas: Vec::new(),
+        };
+        memory_set.try_map_trampoline().map_err(oom)?;
 
-        let elf = xmas_elf::ElfFile::new(elf_data).unwrap();
-        let elf_header = elf.header;
-        // elf.header.pt1：固定格式部分，pt2：可变格式部分
-        let magic = elf_header.pt1.magic;
-        assert_eq!(magic, [0x7f, 0x45, 0x4c, 0x46], "invalid elf!");
-        let ph_count = elf_header.pt2.ph_count();
         let mut max_end_vpn = VirtPageNum(0);
-        for i in 0..ph_count {
-            let ph = elf.program_header(i).unwrap();
-            if ph.get_type().unwrap() == xmas_elf::program::Type::Load {
-                let start_va: VirtAddr = (ph.virtual_addr() as usize).into();
-                let end_va: VirtAddr = ((ph.virtual_addr() + ph.mem_size()) as usize).into();
-                let mut map_perm = MapPermission::U;
-                let ph_flags = ph.flags();
-                if ph_flags.is_read() {
-                    map_perm |= MapPermission::R;
-                }
-                if ph_flags.is_write() {
-                    map_perm |= MapPermission::W;
-                }
-                if ph_flags.is_execute() {
-                    map_perm |= MapPermission::X;
-                }
-                let map_area = MapArea::new(start_va, end_va, MapType::Framed, map_perm);
-                max_end_vpn = map_area.vpn_range.end();
-                memory_set.push(
+        for seg in image.segments.iter() {
+            let start_va: VirtAddr = seg.vaddr.into();
+            let end_va: VirtAddr = (seg.vaddr + seg.mem_size).into();
+            let map_area = MapArea::new(start_va, end_va, MapType::Framed, seg.perm);
+            max_end_vpn = map_area.vpn_range.end();
+            memory_set
+                .try_push_at(
                     map_area,
-                    Some(&elf.input[ph.offset() as usize..(ph.offset() + ph.file_size()) as usize]),
-                );
-            }
+                    Some(&elf_data[seg.offset..seg.offset + seg.file_size]),
+                    start_va.page_offset(),
+                )
+                .map_err(oom)?;
+        }
+        for (addr, value) in relocations {
+            *memory_set
+                .page_table
+                .translate_va(addr.into())
+                .unwrap()
+                .mut_ref::<usize>() = value;
         }
 
         let max_end_va: VirtAddr = max_end_vpn.into();
@@ -1081,16 +1260,23 @@ impl MemorySet {
 
         // Guard Page
         user_stack_bottom += PAGE_SIZE;
-        let user_stack_top: usize = user_stack_bottom + USER_STACK_SIZE;
-        memory_set.push(
-            MapArea::new(
-                user_stack_bottom.into(),
-                user_stack_top.into(),
-                MapType::Framed,
-                MapPermission::R | MapPermission::W | MapPermission::U,
-            ),
-            None,
-        );
+        let user_stack_top: usize = user_stack_bottom + user_stack_size;
+        let mut stack_perm = MapPermission::R | MapPermission::W | MapPermission::U;
+        if image.stack_exec {
+            stack_perm |= MapPermission::X;
+        }
+        memory_set
+            .try_push_at(
+                MapArea::new(
+                    user_stack_bottom.into(),
+                    user_stack_top.into(),
+                    MapType::Framed,
+                    stack_perm,
+                ),
+                None,
+                0,
+            )
+            .map_err(oom)?;
 
         // 堆空间的初始内存区域，sbrk
         memory_set.push(
@@ -1104,20 +1290,28 @@ impl MemorySet {
         );
 
         // TrapContext
-        memory_set.push(
-            MapArea::new(
-                TRAP_CONTEXT.into(),
-                TRAMPOLINE.into(),
-                MapType::Framed,
-                MapPermission::R | MapPermission::W,
-            ),
-            None,
-        );
-        (
+        memory_set
+            .try_push_at(
+                MapArea::new(
+                    TRAP_CONTEXT.into(),
+                    TRAMPOLINE.into(),
+                    MapType::Framed,
+                    MapPermission::R | MapPermission::W,
+                ),
+                None,
+                0,
+            )
+            .map_err(oom)?;
+        Ok((
             memory_set,
             user_stack_top,
-            elf.header.pt2.entry_point() as usize,
-        )
+            image.entry,
+            ElfAuxInfo {
+                phdr: image.phdr,
+                phent: elf::PHENT_SIZE,
+                phnum: image.phnum,
+            },
+        ))
     }
 
     /// 激活地址空间
@@ -1263,6 +1457,21 @@ impl MemorySet {
         self.page_table.token()
     }
 
+    /// 用户可访问的区域及其权限，按起始页号排序
+    ///
+    /// 即所有带 `U` 权限的区域（程序段、用户栈、堆），不含 Trap Context 与 Trampoline。
+    /// 用于生成核心转储。
+    pub fn user_areas(&self) -> Vec<(VPNRange, MapPermission)> {
+        let mut areas: Vec<_> = self
+            .areas
+            .iter()
+            .filter(|area| area.map_perm.contains(MapPermission::U))
+            .map(|area| (area.vpn_range, area.map_perm))
+            .collect();
+        areas.sort_by_key(|(range, _)| range.start().0);
+        areas
+    }
+
     /// 移除指定起始虚拟页号的内存区域
     ///
     /// 查找并移除地址空间中以指定虚拟页号开始的内存映射区域。
@@ -1351,6 +1560,116 @@ impl MemorySet {
         }
     }
 
+    /// `[start, end)` 是否与已有的区域都不重叠
+    fn is_free(&self, start: VirtPageNum, end: VirtPageNum) -> bool {
+        self.areas
+            .iter()
+            .all(|area| end <= area.vpn_range.start() || area.vpn_range.end() <= start)
+    }
+
+    /// 从 `start` 开始向上寻找第一段长为 `pages` 页的空闲地址
+    fn find_free_area(&self, mut start: VirtPageNum, pages: usize) -> VirtPageNum {
+        // 与某个区域重叠时跳到它的末尾继续寻找
+        while let Some(area) = self
+            .areas
+            .iter()
+            .find(|area| start.0 + pages > area.vpn_range.start().0 && area.vpn_range.end() > start)
+        {
+            start = area.vpn_range.end();
+        }
+        start
+    }
+
+    /// 把共享内存段附加到地址空间
+    ///
+    /// ## Arguments
+    ///
+    /// * `start_va` - 附加地址（按页对齐）；为 `None` 时从 [`SHM_BASE`] 向上
+    ///   选择第一段足够大的空闲地址
+    /// * `segment` - 要附加的段
+    /// * `perm` - 区域权限
+    ///
+    /// ## Returns
+    ///
+    /// 附加的起始地址；指定的地址与已有区域重叠或超出用户地址空间时返回 `None`
+    pub fn attach_shm(
+        &mut self,
+        start_va: Option<VirtAddr>,
+        segment: Arc<ShmSegment>,
+        perm: MapPermission,
+    ) -> Option<VirtAddr> {
+        let pages = segment.page_count();
+        let start = match start_va {
+            Some(start_va) => start_va.floor(),
+            None => self.find_free_area(VirtAddr::from(SHM_BASE).floor(), pages),
+        };
+        let end = VirtPageNum(start.0 + pages);
+        if end > VirtAddr::from(USER_SPACE_END).floor() || !self.is_free(start, end) {
+            return None;
+        }
+        self.push(MapArea::new_shared(start.into(), segment, perm), None);
+        Some(start.into())
+    }
+
+    /// 解除附加在 `start_va` 处的共享内存段
+    ///
+    /// ## Returns
+    ///
+    /// 被解除附加的段；该地址处没有附加共享内存段时返回 `None`
+    pub fn detach_shm(&mut self, start_va: VirtAddr) -> Option<Arc<ShmSegment>> {
+        let start: VirtPageNum = start_va.floor();
+        let idx = self
+            .areas
+            .iter()
+            .position(|area| area.map_type == MapType::Shared && area.vpn_range.start() == start)?;
+        let mut area = self.areas.remove(idx);
+        area.unmap(&mut self.page_table);
+        area.shm.clone()
+    }
+
+    /// 从 `start_va` 开始的 `pages` 页是否都位于同一个用户可写的 Framed 区域中
+    ///
+    /// IPC 按页转移数据前用它校验缓冲区，只有这样的页帧才能被换出。
+    pub fn framed_pages(&self, start_va: VirtAddr, pages: usize) -> bool {
+        let start: VirtPageNum = start_va.floor();
+        let end = VirtPageNum(start.0 + pages);
+        let perm = MapPermission::U | MapPermission::W;
+        self.areas.iter().any(|area| {
+            area.map_type == MapType::Framed
+                && area.map_perm.contains(perm)
+                && area.vpn_range.start() <= start
+                && end <= area.vpn_range.end()
+        })
+    }
+
+    /// 用 `frames` 替换从 `start_va` 开始的页帧，返回被换出的页帧
+    ///
+    /// 调用者须先用 [`MemorySet::framed_pages`] 校验这些页。
+    pub fn exchange_frames(
+        &mut self,
+        start_va: VirtAddr,
+        frames: Vec<FrameTracker>,
+    ) -> Vec<FrameTracker> {
+        let start: VirtPageNum = start_va.floor();
+        let area = self
+            .areas
+            .iter_mut()
+            .find(|area| area.vpn_range.start() <= start && start < area.vpn_range.end())
+            .unwrap();
+        let pte_flags = PTEFlags::from_bits(area.map_perm.bits()).unwrap();
+        let mut old = Vec::with_capacity(frames.len());
+        for (i, frame) in frames.into_iter().enumerate() {
+            let vpn = VirtPageNum(start.0 + i);
+            self.page_table.unmap(vpn);
+            self.page_table.map(vpn, frame.ppn, pte_flags);
+            old.push(area.data_frames.insert(vpn, frame).unwrap());
+        }
+        unsafe {
+            asm!("sfence.vma");
+        }
+        old
+    }
+
     /// 从现有用户地址空间创建完全独立的副本
     ///
     /// 深度复制一个已存在的用户地址空间，创建具有相同内存布局和数据内容
@@ -1503,6 +1822,9 @@ impl MemorySet {
         for area in user_space.areas.iter() {
             let new_area = MapArea::from_another(area);
             memory_set.push(new_area, None);
+            if area.map_type == MapType::Shared {
+                continue;
+            }
             for vpn in area.vpn_range {
                 let src_ppn = user_space.translate(vpn).unwrap().ppn();
                 let dst_ppn = memory_set.translate(vpn).unwrap().ppn();
@@ -6,10 +6,13 @@
 //! ## 模块组织
 //!
 //! - [`address`] - 地址和页号的类型安全封装，支持地址对齐和转换
+//! - [`elf`] - ELF 可执行文件的解析、校验与 PIE 重定位
 //! - [`frame_allocator`] - 物理页帧分配器，管理物理内存页面
 //! - [`heap_allocator`] - 内核堆分配器，支持动态内存分配
 //! - [`memory_set`] - 地址空间管理，支持内存映射和地址空间切换
 //! - [`page_table`] - 页表管理，实现虚拟地址到物理地址的转换
+//! - [`shm`] - System V 共享内存段
+//! - [`uaccess`] - 系统调用读写用户指针，地址无效时返回 `EFAULT`
 //!
 //! ## 初始化流程
 //!
@@ -58,17 +61,23 @@
 //! ```
 
 mod address;
+mod elf;
 mod frame_allocator;
 mod heap_allocator;
 mod memory_set;
 mod page_table;
+mod shm;
+mod uaccess;
 
 pub use address::{PhysAddr, PhysPageNum, StepByOne, VPNRange, VirtAddr, VirtPageNum};
+pub use elf::{ELF_MAGIC, ElfError, PHENT_SIZE};
 pub use frame_allocator::{FrameTracker, frame_alloc, frame_dealloc};
-pub use memory_set::{KERNEL_SPACE, MapPermission, MemorySet, kernel_token};
-pub use page_table::{
-    PageTable, PageTableEntry, UserBuffer, translated_byte_buffer, translated_ref,
-    translated_refmut, translated_str,
+pub use memory_set::{ElfAuxInfo, KERNEL_SPACE, LoadError, MapPermission, MemorySet, kernel_token};
+pub use page_table::{PageTable, PageTableEntry, UserBuffer, translated_refmut};
+pub use shm::{ShmIdDs, ShmSegment, shm_find, shm_get};
+pub use uaccess::{
+    copy_from_user, copy_from_user_bytes, copy_str_from_user, copy_to_user, copy_to_user_bytes,
+    poke_user_bytes, user_buffer, user_buffer_mut,
 };
 
 /// 初始化内存管理系统
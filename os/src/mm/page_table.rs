@@ -8,7 +8,7 @@
 //! - [`PTEFlags`] - 页表项标志位，控制页面的访问权限和属性
 //! - [`PageTableEntry`] - 页表项，存储物理页号和标志位
 //! - [`PageTable`] - 页表结构，管理三级页表的层次结构
-//! - [`translated_byte_buffer()`] - 跨页面缓冲区地址转换
+//! - [`UserBuffer`] - 跨页面的用户缓冲区，由 [`user_buffer`](super::user_buffer) 构造
 //!
 //! ## SV39 分页机制
 //!
@@ -76,12 +76,11 @@
 //! - 兼容硬件页表遍历机制
 //! - 提供高效的地址转换功能
 
+use crate::errno::Errno::ENOMEM;
 use crate::mm::{
     PhysAddr, PhysPageNum, VirtAddr, VirtPageNum,
-    address::StepByOne,
     frame_allocator::{FrameTracker, frame_alloc},
 };
-use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 use bitflags::*;
@@ -461,11 +460,20 @@ impl PageTable {
     /// // 此时页表为空，所有地址转换都会失败
     /// ```
     pub fn new() -> Self {
-        let frame = frame_alloc().unwrap();
-        PageTable {
+        Self::try_new().unwrap()
+    }
+
+    /// 创建新的页表，内存不足时返回错误而不是 panic
+    ///
+    /// ## Returns
+    ///
+    /// 新页表；根页表的页帧分配失败时返回 `-ENOMEM`
+    pub fn try_new() -> Result<Self, isize> {
+        let frame = frame_alloc().ok_or(-ENOMEM)?;
+        Ok(PageTable {
             root_ppn: frame.ppn,
             frames: vec![frame],
-        }
+        })
     }
 
     /// 查找页表项（按需创建中间页表）
@@ -495,9 +503,7 @@ impl PageTable {
     /// - 设置页表项指向新页表，标志位为 V（仅有效）
     /// - 将新页帧加入 RAII 管理列表
     ///
-    /// ## Panics
-    ///
-    /// 如果页帧分配失败会触发 panic
+    /// 页帧分配失败时返回 `None`，已创建的中间页表保留在本页表中
     fn find_pte_create(&mut self, vpn: VirtPageNum) -> Option<&mut PageTableEntry> {
         let idxs = vpn.indexes();
         let mut ppn = self.root_ppn;
@@ -509,7 +515,7 @@ impl PageTable {
                 break;
             }
             if !pte.is_valid() {
-                let frame = frame_alloc().unwrap();
+                let frame = frame_alloc()?;
                 *pte = PageTableEntry::new(frame.ppn, PTEFlags::V);
                 self.frames.push(frame);
             }
@@ -599,9 +605,28 @@ impl PageTable {
     /// ```
     #[allow(unused)]
     pub fn map(&mut self, vpn: VirtPageNum, ppn: PhysPageNum, flags: PTEFlags) {
-        let pte = self.find_pte_create(vpn).unwrap();
+        self.try_map(vpn, ppn, flags).unwrap();
+    }
+
+    /// 建立虚拟页到物理页的映射，中间页表分配失败时返回错误
+    ///
+    /// ## Returns
+    ///
+    /// 成功返回 `Ok(())`；中间页表的页帧分配失败时返回 `-ENOMEM`
+    ///
+    /// ## Panics
+    ///
+    /// 虚拟页已经被映射
+    pub fn try_map(
+        &mut self,
+        vpn: VirtPageNum,
+        ppn: PhysPageNum,
+        flags: PTEFlags,
+    ) -> Result<(), isize> {
+        let pte = self.find_pte_create(vpn).ok_or(-ENOMEM)?;
         assert!(!pte.is_valid(), "vpn {:?} is mapped before mapping", vpn);
         *pte = PageTableEntry::new(ppn, flags | PTEFlags::V);
+        Ok(())
     }
 
     /// 取消虚拟页的映射
@@ -805,147 +830,12 @@ impl PageTable {
     }
 }
 
-/// 转换跨页面字节缓冲区
-///
-/// 将一个可能跨越多个页面的虚拟地址缓冲区转换为对应的物理页面切片列表。
-/// 主要用于系统调用中访问用户态传递的缓冲区数据。
-///
-/// ## Arguments
-///
-/// * `token` - 页表令牌（satp 寄存器值），指定要使用的页表
-/// * `ptr` - 缓冲区起始虚拟地址指针
-/// * `len` - 缓冲区长度（字节数）
-///
-/// ## Returns
-///
-/// 返回物理页面切片的向量，每个切片对应缓冲区在一个物理页面中的部分
-///
-/// ## 转换过程
-///
-/// 1. 根据页表令牌创建页表实例
-/// 2. 按页面边界分割缓冲区范围
-/// 3. 逐个转换每个页面的虚拟地址到物理地址
-/// 4. 返回每个页面中对应部分的可变切片
-///
-/// ## 跨页面处理
-///
-/// ```text
-/// Virtual Buffer:  [████████████████████████████]
-///                    │            │           │
-/// Page Boundary:  ┌──┴──┐    ┌────┴────┐   ┌──┴──┐
-/// Physical Page:  Page A     Page B       Page C
-/// Return Slice:   slice[0]   slice[1]     slice[2]
-/// ```
-///
-/// ## 使用场景
-///
-/// - 系统调用中访问用户态缓冲区
-/// - DMA 操作的地址转换
-/// - 跨页面数据的零拷贝访问
-///
-/// ## Safety
-///
-/// 返回的切片具有 `'static` 生命周期，调用者需要确保：
-/// - 页表在使用期间保持有效
-/// - 物理页面没有被其他代码同时修改
-/// - 虚拟地址确实已正确映射
-///
-/// ## Panics
-///
-/// 如果缓冲区中任何页面未被映射会触发 panic
-///
-/// ## Examples
-///
-/// ```rust
-/// // 转换用户态缓冲区以供内核访问
-/// let user_buffer_ptr = 0x10001000 as *const u8;
-/// let len = 8192; // 跨越 3 个页面
-/// let token = current_user_token();
-///
-/// let slices = translated_byte_buffer(token, user_buffer_ptr, len);
-/// for slice in slices {
-///     // 访问每个物理页面中的数据
-///     process_data(slice);
-/// }
-/// ```
-pub fn translated_byte_buffer(token: usize, ptr: *const u8, len: usize) -> Vec<&'static mut [u8]> {
-    let page_table = PageTable::from_token(token);
-    let mut start = ptr as usize;
-    let end = start + len;
-    let mut v = Vec::new();
-    while start < end {
-        let start_va = VirtAddr::from(start);
-        let mut vpn = start_va.floor();
-        let ppn = page_table.translate(vpn).unwrap().ppn();
-        vpn.step();
-        let mut end_va: VirtAddr = vpn.into();
-        end_va = end_va.min(VirtAddr::from(end));
-        if end_va.page_offset() == 0 {
-            v.push(&mut ppn.bytes_array()[start_va.page_offset()..]);
-        } else {
-            v.push(&mut ppn.bytes_array()[start_va.page_offset()..end_va.page_offset()]);
-        }
-        start = end_va.into();
-    }
-    v
-}
-
-/// 转换以 0 结尾的用户字符串为内核字符串
-///
-/// 在给定页表令牌（satp 值）下，从用户虚拟地址空间读取
-/// 以 `\0` 结尾的字节序列，并构造一个内核态的 [`String`] 返回。
-/// 该函数会按字节跨页面地读取，直到遇到终止符 `\0` 为止。
-///
-/// ## Arguments
-///
-/// * `token` - 页表令牌（satp 寄存器值），指定用户地址空间
-/// * `ptr` - 用户空间的 C 风格字符串起始指针
-///
-/// ## Returns
-///
-/// - 返回对应内容的内核态 [`String`]
-///
-/// ## 行为特征
-///
-/// - 支持跨页面读取
-/// - 逐字节读取直到遇到 `\0`
-/// - 如果中途地址未映射将触发 `unwrap()` panic（未来可改为错误返回）
-///
-/// ## Safety
-///
-/// - 假设 `ptr` 指向的是有效的、可读的用户地址
-/// - 调用者需确保字符串以 `\0` 终止，否则会一直向后读取直到出错
-///
-/// ## Examples
-///
-/// ```rust
-/// // 在系统调用中，将用户提供的路径参数转换为内核字符串
-/// let path = translated_str(current_user_token(), user_ptr);
-/// println!("open path: {}", path);
-/// ```
-pub fn translated_str(token: usize, ptr: *const u8) -> String {
-    let page_table = PageTable::from_token(token);
-    let mut string = String::new();
-    let mut va = ptr as usize;
-    loop {
-        let ch: u8 = *(page_table
-            .translate_va(VirtAddr::from(va))
-            .unwrap()
-            .mut_ref());
-        if ch == 0 {
-            break;
-        } else {
-            string.push(ch as char);
-            va += 1;
-        }
-    }
-    string
-}
-
 /// 将用户虚拟地址转换为内核可写引用
 ///
-/// 在给定的页表令牌（satp 值）下，将用户空间的指针转换为
-/// 内核态可写引用，便于在系统调用中直接修改用户缓冲区数据。
+/// 在给定的页表令牌（satp 值）下，将用户空间的指针转换为内核态可写引用。
+/// 只用于内核自己建立的映射（例如 `exec` 时填写新用户栈）；
+/// 系统调用访问用户传入的指针应使用 [`copy_to_user`](super::copy_to_user)，
+/// 它会检查权限并在地址无效时返回 `EFAULT`。
 ///
 /// ## Type Parameters
 ///
@@ -982,14 +872,6 @@ pub fn translated_refmut<T>(token: usize, ptr: *mut T) -> &'static mut T {
         .mut_ref()
 }
 
-pub fn translated_ref<T>(token: usize, ptr: *const T) -> &'static T {
-    let page_table = PageTable::from_token(token);
-    page_table
-        .translate_va(VirtAddr::from(ptr as usize))
-        .unwrap()
-        ._ref()
-}
-
 /// 用户缓冲区抽象
 ///
 /// 表示一个可能跨越多个物理页面的用户态缓冲区，用于系统调用中
@@ -1045,8 +927,7 @@ impl UserBuffer {
     /// ## Examples
     ///
     /// ```rust
-    /// let slices = translated_byte_buffer(token, ptr, len);
-    /// let user_buffer = UserBuffer::new(slices);
+    /// let user_buffer = user_buffer_mut(token, ptr, len)?;
     /// ```
     pub fn new(buffers: Vec<&'static mut [u8]>) -> Self {
         Self { buffers }
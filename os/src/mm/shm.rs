@@ -0,0 +1,247 @@
+//! # System V 共享内存段
+//!
+//! 共享内存段是一组预先分配的物理页帧，登记在全局的 [`SHM_SEGMENTS`] 中。
+//! 段以 [`MapType::Shared`](super::MapType::Shared) 区域的形式映射进多个地址空间，
+//! 这些区域直接使用段的页帧，因此对段的写入在所有附加者之间可见。
+//!
+//! ## 生命周期
+//!
+//! - 每个映射该段的 `MapArea` 计为一次附加（`shm_nattch`）：`shmat` 与 `fork`
+//!   时增加，`shmdt`、`exec` 与进程退出丢弃区域时减少
+//! - `shmctl(IPC_RMID)` 只把段标记为待删除，之后不能再通过键找到它
+//! - 段被标记删除且最后一个附加者离开时从登记表中移除，页帧随之释放
+
+use super::{FrameTracker, frame_alloc};
+use crate::config::{PAGE_SIZE, SHMMAX, SHMMNI};
+use crate::errno::Errno::{EEXIST, EINVAL, ENOENT, ENOMEM, ENOSPC};
+use crate::sync::UPSafeCell;
+use crate::timer::time_ms;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use lazy_static::*;
+
+/// 私有键：总是创建新的段
+pub const IPC_PRIVATE: i32 = 0;
+
+/// `struct ipc64_perm`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IpcPerm {
+    pub key: i32,
+    pub uid: u32,
+    pub gid: u32,
+    pub cuid: u32,
+    pub cgid: u32,
+    pub mode: u32,
+    pub seq: u16,
+    pub pad: u16,
+    pub unused: [usize; 2],
+}
+
+/// `struct shmid64_ds`，`shmctl(IPC_STAT)` 的结果
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShmIdDs {
+    pub shm_perm: IpcPerm,
+    pub shm_segsz: usize,
+    pub shm_atime: usize,
+    pub shm_dtime: usize,
+    pub shm_ctime: usize,
+    pub shm_cpid: i32,
+    pub shm_lpid: i32,
+    pub shm_nattch: usize,
+    pub unused: [usize; 2],
+}
+
+struct ShmSegmentInner {
+    key: i32,
+    /// 权限位（低 9 位）
+    mode: u32,
+    nattch: usize,
+    /// 已被 `IPC_RMID` 标记删除
+    removed: bool,
+    cpid: usize,
+    lpid: usize,
+    atime: usize,
+    dtime: usize,
+    ctime: usize,
+}
+
+/// 共享内存段
+pub struct ShmSegment {
+    id: usize,
+    size: usize,
+    frames: Vec<FrameTracker>,
+    inner: UPSafeCell<ShmSegmentInner>,
+}
+
+lazy_static! {
+    /// 段标识符到段
+    static ref SHM_SEGMENTS: UPSafeCell<BTreeMap<usize, Arc<ShmSegment>>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+    /// 下一个段标识符，标识符不复用
+    static ref NEXT_SHM_ID: UPSafeCell<usize> = unsafe { UPSafeCell::new(0) };
+}
+
+fn now() -> usize {
+    time_ms() / 1000
+}
+
+impl ShmSegment {
+    /// 段占用的页数
+    pub fn page_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// 段中第 `index` 页的页帧
+    pub fn frame(&self, index: usize) -> &FrameTracker {
+        &self.frames[index]
+    }
+
+    /// 新的区域映射了该段
+    pub fn attach(&self) {
+        self.inner.exclusive_access().nattch += 1;
+    }
+
+    /// 映射该段的区域被丢弃；标记删除的段在最后一个附加者离开时移除
+    pub fn detach(&self) {
+        let mut inner = self.inner.exclusive_access();
+        inner.nattch -= 1;
+        inner.dtime = now();
+        if inner.nattch == 0 && inner.removed {
+            drop(inner);
+            SHM_SEGMENTS.exclusive_access().remove(&self.id);
+        }
+    }
+
+    /// 记录 `shmat`/`shmdt` 的调用者
+    pub fn touch(&self, pid: usize, attached: bool) {
+        let mut inner = self.inner.exclusive_access();
+        inner.lpid = pid;
+        if attached {
+            inner.atime = now();
+        }
+    }
+
+    /// 段的状态
+    pub fn stat(&self) -> ShmIdDs {
+        let inner = self.inner.exclusive_access();
+        ShmIdDs {
+            shm_perm: IpcPerm {
+                key: inner.key,
+                mode: inner.mode,
+                ..Default::default()
+            },
+            shm_segsz: self.size,
+            shm_atime: inner.atime,
+            shm_dtime: inner.dtime,
+            shm_ctime: inner.ctime,
+            shm_cpid: inner.cpid as i32,
+            shm_lpid: inner.lpid as i32,
+            shm_nattch: inner.nattch,
+            unused: [0; 2],
+        }
+    }
+
+    /// `IPC_SET`：只更新权限位
+    pub fn set(&self, ds: &ShmIdDs) {
+        let mut inner = self.inner.exclusive_access();
+        inner.mode = ds.shm_perm.mode & 0o777;
+        inner.ctime = now();
+    }
+
+    /// `IPC_RMID`：标记删除，没有附加者时立即移除
+    pub fn remove(&self) {
+        let mut inner = self.inner.exclusive_access();
+        inner.removed = true;
+        inner.key = IPC_PRIVATE;
+        inner.ctime = now();
+        if inner.nattch == 0 {
+            drop(inner);
+            SHM_SEGMENTS.exclusive_access().remove(&self.id);
+        }
+    }
+}
+
+/// 按键查找或创建段
+///
+/// ## Arguments
+///
+/// * `key` - 段的键，[`IPC_PRIVATE`] 总是创建新段
+/// * `size` - 段的字节数，查找已有段时不得超过它的大小
+/// * `mode` - 新段的权限位
+/// * `create` / `exclusive` - 即 `IPC_CREAT` / `IPC_EXCL`
+/// * `pid` - 调用者
+///
+/// ## Returns
+///
+/// 段标识符；失败时返回 `ENOENT`、`EEXIST`、`EINVAL`、`ENOSPC` 或 `ENOMEM`
+pub fn shm_get(
+    key: i32,
+    size: usize,
+    mode: u32,
+    create: bool,
+    exclusive: bool,
+    pid: usize,
+) -> Result<usize, isize> {
+    let mut segments = SHM_SEGMENTS.exclusive_access();
+    if key != IPC_PRIVATE {
+        let existing = segments.values().find(|segment| {
+            let inner = segment.inner.exclusive_access();
+            !inner.removed && inner.key == key
+        });
+        if let Some(segment) = existing {
+            if create && exclusive {
+                return Err(-EEXIST);
+            }
+            if size > segment.size {
+                return Err(-EINVAL);
+            }
+            return Ok(segment.id);
+        }
+        if !create {
+            return Err(-ENOENT);
+        }
+    }
+    if size == 0 || size > SHMMAX {
+        return Err(-EINVAL);
+    }
+    if segments.len() >= SHMMNI {
+        return Err(-ENOSPC);
+    }
+    let mut frames = Vec::new();
+    for _ in 0..size.div_ceil(PAGE_SIZE) {
+        frames.push(frame_alloc().ok_or(-ENOMEM)?);
+    }
+    let id = {
+        let mut next = NEXT_SHM_ID.exclusive_access();
+        *next += 1;
+        *next - 1
+    };
+    let segment = ShmSegment {
+        id,
+        size,
+        frames,
+        inner: unsafe {
+            UPSafeCell::new(ShmSegmentInner {
+                key,
+                mode: mode & 0o777,
+                nattch: 0,
+                removed: false,
+                cpid: pid,
+                lpid: 0,
+                atime: 0,
+                dtime: 0,
+                ctime: now(),
+            })
+        },
+    };
+    segments.insert(id, Arc::new(segment));
+    Ok(id)
+}
+
+/// 按标识符查找段；标记删除但仍有附加者的段也能找到
+pub fn shm_find(id: usize) -> Option<Arc<ShmSegment>> {
+    SHM_SEGMENTS.exclusive_access().get(&id).cloned()
+}
@@ -0,0 +1,183 @@
+//! # 用户内存访问
+//!
+//! 系统调用通过这里读写用户传入的指针。与直接转换地址不同，这些函数逐页检查：
+//!
+//! - 地址范围位于用户地址空间（`USER_SPACE_END` 以下）且不回绕
+//! - 页已映射且带有 `U` 位
+//! - 读取的页可读（`R`），写入的页可写（`W`）
+//!
+//! 任何一项不满足时返回 `EFAULT`，而不是让内核 panic。
+//!
+//! ## 函数
+//!
+//! - [`copy_from_user`] / [`copy_to_user`] - 按值读写一个对象，对象可以跨页
+//! - [`copy_from_user_bytes`] / [`copy_to_user_bytes`] - 读写一段字节
+//! - [`copy_str_from_user`] - 读取以 `\0` 结尾的字符串
+//! - [`poke_user_bytes`] - 忽略写权限写入，供调试器在代码段设置断点
+//! - [`user_buffer`] / [`user_buffer_mut`] - 把用户缓冲区转换为 [`UserBuffer`]，供文件读写使用
+
+use super::page_table::{PTEFlags, PageTable, UserBuffer};
+use super::{PhysPageNum, StepByOne, VirtAddr};
+use crate::config::{PAGE_SIZE, USER_SPACE_END};
+use crate::errno::Errno::{EFAULT, ENAMETOOLONG};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem::{MaybeUninit, size_of};
+
+/// 从用户空间读取的字符串的最大长度（不含 `\0`）
+pub const USER_STR_MAX: usize = PAGE_SIZE;
+
+/// 检查 `va` 所在的页可由用户访问，返回它的物理页号
+fn user_page(page_table: &PageTable, va: usize, writable: bool) -> Result<PhysPageNum, isize> {
+    let pte = page_table
+        .translate(VirtAddr::from(va).floor())
+        .filter(|pte| pte.is_valid())
+        .ok_or(-EFAULT)?;
+    let required = if writable {
+        PTEFlags::U | PTEFlags::W
+    } else {
+        PTEFlags::U | PTEFlags::R
+    };
+    if !pte.flags().contains(required) {
+        return Err(-EFAULT);
+    }
+    Ok(pte.ppn())
+}
+
+/// 把 `[ptr, ptr + len)` 转换为各页中的切片
+fn user_slices(
+    token: usize,
+    ptr: usize,
+    len: usize,
+    writable: bool,
+) -> Result<Vec<&'static mut [u8]>, isize> {
+    let end = ptr.checked_add(len).ok_or(-EFAULT)?;
+    if end > USER_SPACE_END {
+        return Err(-EFAULT);
+    }
+    let page_table = PageTable::from_token(token);
+    let mut slices = Vec::new();
+    let mut start = ptr;
+    while start < end {
+        let start_va = VirtAddr::from(start);
+        let ppn = user_page(&page_table, start, writable)?;
+        let mut vpn = start_va.floor();
+        vpn.step();
+        let page_end = usize::from(VirtAddr::from(vpn)).min(end);
+        let offset = start_va.page_offset();
+        slices.push(&mut ppn.bytes_array()[offset..offset + (page_end - start)]);
+        start = page_end;
+    }
+    Ok(slices)
+}
+
+/// 把用户缓冲区转换为只读的 [`UserBuffer`]（例如 `write` 的数据）
+///
+/// ## Returns
+///
+/// 缓冲区；任何一页不可读时返回 `-EFAULT`
+pub fn user_buffer(token: usize, ptr: *const u8, len: usize) -> Result<UserBuffer, isize> {
+    user_slices(token, ptr as usize, len, false).map(UserBuffer::new)
+}
+
+/// 把用户缓冲区转换为可写的 [`UserBuffer`]（例如 `read` 的目标）
+///
+/// ## Returns
+///
+/// 缓冲区；任何一页不可写时返回 `-EFAULT`
+pub fn user_buffer_mut(token: usize, ptr: *mut u8, len: usize) -> Result<UserBuffer, isize> {
+    user_slices(token, ptr as usize, len, true).map(UserBuffer::new)
+}
+
+/// 从用户空间读取 `dst.len()` 个字节
+pub fn copy_from_user_bytes(token: usize, ptr: *const u8, dst: &mut [u8]) -> Result<(), isize> {
+    let mut copied = 0;
+    for src in user_slices(token, ptr as usize, dst.len(), false)? {
+        dst[copied..copied + src.len()].copy_from_slice(src);
+        copied += src.len();
+    }
+    Ok(())
+}
+
+/// 把 `src` 写入用户空间
+pub fn copy_to_user_bytes(token: usize, ptr: *mut u8, src: &[u8]) -> Result<(), isize> {
+    let mut copied = 0;
+    for dst in user_slices(token, ptr as usize, src.len(), true)? {
+        dst.copy_from_slice(&src[copied..copied + dst.len()]);
+        copied += dst.len();
+    }
+    Ok(())
+}
+
+/// 调试器修改被跟踪进程的内存
+///
+/// 与 [`copy_to_user_bytes`] 不同，只要求页可读而忽略 `W` 位，
+/// 以便在只读的代码段中设置断点；写入后执行 `fence.i` 使指令缓存生效。
+///
+/// ## Returns
+///
+/// 任何一页不可读时返回 `-EFAULT`，此时内存不会被部分修改
+pub fn poke_user_bytes(token: usize, ptr: *mut u8, src: &[u8]) -> Result<(), isize> {
+    let mut copied = 0;
+    for dst in user_slices(token, ptr as usize, src.len(), false)? {
+        dst.copy_from_slice(&src[copied..copied + dst.len()]);
+        copied += dst.len();
+    }
+    unsafe { core::arch::asm!("fence.i") };
+    Ok(())
+}
+
+/// 从用户空间按值读取一个对象
+///
+/// `T` 须是任意位模式都合法的纯数据类型（整数、`#[repr(C)]` 结构体等）。
+///
+/// ## Returns
+///
+/// 读到的对象；地址不可读时返回 `-EFAULT`
+pub fn copy_from_user<T: Copy>(token: usize, ptr: *const T) -> Result<T, isize> {
+    let mut value = MaybeUninit::<T>::uninit();
+    let bytes =
+        unsafe { core::slice::from_raw_parts_mut(value.as_mut_ptr() as *mut u8, size_of::<T>()) };
+    copy_from_user_bytes(token, ptr as *const u8, bytes)?;
+    Ok(unsafe { value.assume_init() })
+}
+
+/// 把一个对象按值写入用户空间
+///
+/// ## Returns
+///
+/// 地址不可写时返回 `-EFAULT`，此时用户内存不会被部分修改
+pub fn copy_to_user<T: Copy>(token: usize, ptr: *mut T, value: &T) -> Result<(), isize> {
+    let bytes =
+        unsafe { core::slice::from_raw_parts(value as *const T as *const u8, size_of::<T>()) };
+    copy_to_user_bytes(token, ptr as *mut u8, bytes)
+}
+
+/// 从用户空间读取以 `\0` 结尾的字符串
+///
+/// ## Returns
+///
+/// 读到的字符串；地址不可读时返回 `-EFAULT`，超过 [`USER_STR_MAX`] 字节仍未结束时
+/// 返回 `-ENAMETOOLONG`
+pub fn copy_str_from_user(token: usize, ptr: *const u8) -> Result<String, isize> {
+    let page_table = PageTable::from_token(token);
+    let mut string = String::new();
+    let mut va = ptr as usize;
+    loop {
+        if va >= USER_SPACE_END {
+            return Err(-EFAULT);
+        }
+        let ppn = user_page(&page_table, va, false)?;
+        let offset = VirtAddr::from(va).page_offset();
+        for &ch in &ppn.bytes_array()[offset..] {
+            if ch == 0 {
+                return Ok(string);
+            }
+            if string.len() >= USER_STR_MAX {
+                return Err(-ENAMETOOLONG);
+            }
+            string.push(ch as char);
+        }
+        va += PAGE_SIZE - offset;
+    }
+}
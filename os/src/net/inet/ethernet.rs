@@ -0,0 +1,145 @@
+//! # 以太网与 ARP
+//!
+//! 以太网接口建立在 [`NET_DEVICE`] 之上：发送 IP 报文前通过 ARP 缓存查找下一跳的
+//! MAC 地址，缓存中没有时广播 ARP 请求，并暂存报文直到收到应答。
+//!
+//! ARP 缓存只会增长与更新，不会老化；对于静态配置的小型网络已经足够。
+
+use super::{ETH_ADDR, Ipv4Addr, ip_input};
+use crate::drivers::{NET_DEVICE, NetDevice};
+use crate::sync::UPSafeCell;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use lazy_static::*;
+
+const ETH_HEADER_LEN: usize = 14;
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_ARP: u16 = 0x0806;
+const BROADCAST_MAC: [u8; 6] = [0xff; 6];
+
+/// 以太网上 IPv4 的 ARP 报文长度
+const ARP_PACKET_LEN: usize = 28;
+const ARP_REQUEST: u16 = 1;
+const ARP_REPLY: u16 = 2;
+/// 等待 ARP 应答时每个地址最多暂存的报文数，更多的报文被丢弃
+const ARP_PENDING_MAX: usize = 8;
+
+struct ArpCache {
+    /// IP 地址到 MAC 地址
+    table: BTreeMap<Ipv4Addr, [u8; 6]>,
+    /// 等待 ARP 应答的 IP 报文
+    pending: BTreeMap<Ipv4Addr, Vec<Vec<u8>>>,
+}
+
+lazy_static! {
+    static ref ARP_CACHE: UPSafeCell<ArpCache> = unsafe {
+        UPSafeCell::new(ArpCache {
+            table: BTreeMap::new(),
+            pending: BTreeMap::new(),
+        })
+    };
+}
+
+fn device() -> Option<&'static Arc<dyn NetDevice>> {
+    NET_DEVICE.as_ref()
+}
+
+/// 是否存在以太网接口
+pub(super) fn present() -> bool {
+    device().is_some()
+}
+
+/// 取出网卡上一个已到达的帧
+pub(super) fn receive() -> Option<Vec<u8>> {
+    device()?.receive()
+}
+
+fn transmit(dst: [u8; 6], ethertype: u16, payload: &[u8]) {
+    let Some(device) = device() else {
+        return;
+    };
+    let mut frame = Vec::with_capacity(ETH_HEADER_LEN + payload.len());
+    frame.extend_from_slice(&dst);
+    frame.extend_from_slice(&device.mac());
+    frame.extend_from_slice(&ethertype.to_be_bytes());
+    frame.extend_from_slice(payload);
+    device.transmit(&frame);
+}
+
+/// 构造以太网上 IPv4 的 ARP 报文
+fn arp_packet(op: u16, target_mac: [u8; 6], target: Ipv4Addr) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(ARP_PACKET_LEN);
+    packet.extend_from_slice(&1u16.to_be_bytes());
+    packet.extend_from_slice(&ETHERTYPE_IPV4.to_be_bytes());
+    packet.extend_from_slice(&[6, 4]);
+    packet.extend_from_slice(&op.to_be_bytes());
+    packet.extend_from_slice(&device().unwrap().mac());
+    packet.extend_from_slice(&ETH_ADDR.0);
+    packet.extend_from_slice(&target_mac);
+    packet.extend_from_slice(&target.0);
+    packet
+}
+
+/// 经以太网把 IP 报文发往下一跳
+///
+/// 下一跳的 MAC 地址未知时广播 ARP 请求，报文暂存到收到应答为止。
+pub(super) fn output(next_hop: Ipv4Addr, packet: Vec<u8>) {
+    if next_hop == Ipv4Addr::BROADCAST {
+        transmit(BROADCAST_MAC, ETHERTYPE_IPV4, &packet);
+        return;
+    }
+    let mut cache = ARP_CACHE.exclusive_access();
+    if let Some(mac) = cache.table.get(&next_hop).copied() {
+        drop(cache);
+        transmit(mac, ETHERTYPE_IPV4, &packet);
+        return;
+    }
+    let pending = cache.pending.entry(next_hop).or_default();
+    if pending.len() < ARP_PENDING_MAX {
+        pending.push(packet);
+    }
+    drop(cache);
+    let request = arp_packet(ARP_REQUEST, [0; 6], next_hop);
+    transmit(BROADCAST_MAC, ETHERTYPE_ARP, &request);
+}
+
+/// 处理网卡收到的以太网帧
+pub(super) fn input(frame: &[u8]) {
+    if frame.len() < ETH_HEADER_LEN {
+        return;
+    }
+    let payload = &frame[ETH_HEADER_LEN..];
+    match u16::from_be_bytes([frame[12], frame[13]]) {
+        ETHERTYPE_IPV4 => ip_input(payload),
+        ETHERTYPE_ARP => arp_input(payload),
+        _ => {}
+    }
+}
+
+/// 记录发送方的地址、发出为它暂存的报文，并回应询问本机地址的请求
+fn arp_input(packet: &[u8]) {
+    if packet.len() < ARP_PACKET_LEN || packet[0..4] != [0, 1, 0x08, 0x00] || packet[4..6] != [6, 4]
+    {
+        return;
+    }
+    let op = u16::from_be_bytes([packet[6], packet[7]]);
+    let sender_mac: [u8; 6] = packet[8..14].try_into().unwrap();
+    let sender = Ipv4Addr(packet[14..18].try_into().unwrap());
+    let target = Ipv4Addr(packet[24..28].try_into().unwrap());
+    if target != ETH_ADDR {
+        return;
+    }
+    let pending = {
+        let mut cache = ARP_CACHE.exclusive_access();
+        cache.table.insert(sender, sender_mac);
+        cache.pending.remove(&sender).unwrap_or_default()
+    };
+    for packet in pending {
+        transmit(sender_mac, ETHERTYPE_IPV4, &packet);
+    }
+    if op == ARP_REQUEST {
+        let reply = arp_packet(ARP_REPLY, sender_mac, sender);
+        transmit(sender_mac, ETHERTYPE_ARP, &reply);
+    }
+}
@@ -0,0 +1,337 @@
+//! # TCP/IP 协议栈
+//!
+//! 内核内置的 IPv4 协议栈，为 `AF_INET` 提供流式（[`tcp`]）与数据报（[`udp`]）套接字。
+//!
+//! ## 网络接口
+//!
+//! - **回环接口**：`127.0.0.0/8` 以及本机的其他地址，发出的报文放入回环队列，
+//!   由 [`poll`] 重新交给 IP 层
+//! - **以太网接口**：VirtIO 网卡（若存在），地址按 QEMU 用户态网络的默认配置静态设置为
+//!   `10.0.2.15/24`，网关为 `10.0.2.2`
+//!
+//! ## 报文处理
+//!
+//! 收到的报文集中在 [`poll`] 中处理：它依次取出回环队列与网卡上的报文交给各层的
+//! `input`。协议处理中产生的回应（如 TCP 的 ACK）只放入回环队列或直接由网卡发出，
+//! 不会递归地进入 `input`，处理期间不会重复借用套接字的状态。
+//!
+//! 套接字系统调用在发出报文之后、阻塞之前调用 [`poll`]；网卡中断与时钟中断也调用它，
+//! 后者同时驱动 TCP 的超时重传。
+//!
+//! ## 限制
+//!
+//! - 不支持 IP 分片，分片的报文被丢弃
+//! - 地址静态配置，不支持 DHCP
+//! - ICMP 只回应回显请求（`ping`）
+
+mod ethernet;
+mod tcp;
+mod udp;
+
+use super::SockType;
+use crate::drivers::{IrqHandler, NET_DEVICE};
+use crate::errno::Errno::{EADDRNOTAVAIL, ENETUNREACH};
+use crate::fs::{File, OpenFlags};
+use crate::sync::UPSafeCell;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use lazy_static::*;
+
+pub use tcp::TcpSocket;
+pub use udp::UdpSocket;
+
+/// IPv4 地址
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Ipv4Addr(pub [u8; 4]);
+
+impl Ipv4Addr {
+    /// 未指定地址 `0.0.0.0`
+    pub const UNSPECIFIED: Self = Self([0, 0, 0, 0]);
+    /// 回环地址 `127.0.0.1`
+    pub const LOOPBACK: Self = Self([127, 0, 0, 1]);
+    /// 受限广播地址 `255.255.255.255`
+    pub const BROADCAST: Self = Self([255, 255, 255, 255]);
+
+    pub fn is_unspecified(&self) -> bool {
+        *self == Self::UNSPECIFIED
+    }
+
+    pub fn is_loopback(&self) -> bool {
+        self.0[0] == 127
+    }
+
+    fn to_u32(self) -> u32 {
+        u32::from_be_bytes(self.0)
+    }
+}
+
+/// 传输层的端点：地址与端口
+type Endpoint = (Ipv4Addr, u16);
+
+/// 以太网接口的地址（QEMU 用户态网络分配给客户机的地址）
+const ETH_ADDR: Ipv4Addr = Ipv4Addr([10, 0, 2, 15]);
+/// 以太网接口的子网掩码
+const ETH_NETMASK: Ipv4Addr = Ipv4Addr([255, 255, 255, 0]);
+/// 默认网关
+const ETH_GATEWAY: Ipv4Addr = Ipv4Addr([10, 0, 2, 2]);
+
+const IPV4_HEADER_LEN: usize = 20;
+/// IP 报文的生存时间
+const IPV4_TTL: u8 = 64;
+/// 不分片标志
+const IPV4_DONT_FRAGMENT: u16 = 0x4000;
+
+const IPPROTO_ICMP: u8 = 1;
+const IPPROTO_TCP: u8 = 6;
+const IPPROTO_UDP: u8 = 17;
+
+const ICMP_ECHO_REPLY: u8 = 0;
+const ICMP_ECHO_REQUEST: u8 = 8;
+
+/// 临时端口的范围
+const EPHEMERAL_PORT_MIN: u16 = 49152;
+
+lazy_static! {
+    /// 回环接口上等待交给 IP 层的报文
+    static ref LOOPBACK_QUEUE: UPSafeCell<VecDeque<Vec<u8>>> =
+        unsafe { UPSafeCell::new(VecDeque::new()) };
+
+    /// 下一个 IP 报文的标识
+    static ref IP_IDENT: UPSafeCell<u16> = unsafe { UPSafeCell::new(0) };
+
+    /// 下一个尝试分配的临时端口
+    static ref NEXT_EPHEMERAL_PORT: UPSafeCell<u16> =
+        unsafe { UPSafeCell::new(EPHEMERAL_PORT_MIN) };
+}
+
+/// 报文的出口
+enum Route {
+    Loopback,
+    /// 经以太网发往下一跳
+    Ethernet(Ipv4Addr),
+}
+
+/// `addr` 是否是本机的地址
+pub(super) fn is_local(addr: Ipv4Addr) -> bool {
+    addr.is_loopback() || (ethernet::present() && addr == ETH_ADDR)
+}
+
+/// 把 `connect`/`sendto` 的目的地址规范化：与 Linux 相同，`0.0.0.0` 表示本机
+fn destination(addr: Ipv4Addr) -> Ipv4Addr {
+    if addr.is_unspecified() {
+        Ipv4Addr::LOOPBACK
+    } else {
+        addr
+    }
+}
+
+/// 检查 `bind` 的本地地址
+fn check_bind_addr(addr: Ipv4Addr) -> Result<(), isize> {
+    if addr.is_unspecified() || is_local(addr) {
+        Ok(())
+    } else {
+        Err(-EADDRNOTAVAIL)
+    }
+}
+
+fn route(dst: Ipv4Addr) -> Result<Route, isize> {
+    if is_local(dst) {
+        return Ok(Route::Loopback);
+    }
+    if !ethernet::present() {
+        return Err(-ENETUNREACH);
+    }
+    let mask = ETH_NETMASK.to_u32();
+    if dst == Ipv4Addr::BROADCAST || dst.to_u32() & mask == ETH_ADDR.to_u32() & mask {
+        Ok(Route::Ethernet(dst))
+    } else {
+        Ok(Route::Ethernet(ETH_GATEWAY))
+    }
+}
+
+/// 发往 `dst` 的报文使用的源地址
+///
+/// ## Returns
+/// 没有可达的路由时返回 `Err(-ENETUNREACH)`
+fn source_addr(dst: Ipv4Addr) -> Result<Ipv4Addr, isize> {
+    match route(dst)? {
+        Route::Loopback => Ok(dst),
+        Route::Ethernet(_) => Ok(ETH_ADDR),
+    }
+}
+
+/// 分配一个未被占用的临时端口
+///
+/// ## Returns
+/// 临时端口全部被占用时返回 `None`
+fn alloc_ephemeral_port(in_use: impl Fn(u16) -> bool) -> Option<u16> {
+    let mut next = NEXT_EPHEMERAL_PORT.exclusive_access();
+    for _ in EPHEMERAL_PORT_MIN..=u16::MAX {
+        let port = *next;
+        *next = if port == u16::MAX {
+            EPHEMERAL_PORT_MIN
+        } else {
+            port + 1
+        };
+        if !in_use(port) {
+            return Some(port);
+        }
+    }
+    None
+}
+
+/// 按 16 位大端字累加，用于计算互联网校验和
+fn checksum_add(mut sum: u32, data: &[u8]) -> u32 {
+    let mut chunks = data.chunks_exact(2);
+    for chunk in &mut chunks {
+        sum += u16::from_be_bytes([chunk[0], chunk[1]]) as u32;
+    }
+    if let [last] = chunks.remainder() {
+        sum += (*last as u32) << 8;
+    }
+    sum
+}
+
+fn checksum_fold(mut sum: u32) -> u16 {
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// 互联网校验和（RFC 1071）；对含校验和字段的完整数据计算结果为 0 表示校验通过
+fn checksum(data: &[u8]) -> u16 {
+    checksum_fold(checksum_add(0, data))
+}
+
+/// TCP/UDP 的校验和，包含由源地址、目的地址、协议与长度组成的伪首部
+fn pseudo_checksum(src: Ipv4Addr, dst: Ipv4Addr, protocol: u8, data: &[u8]) -> u16 {
+    let mut sum = checksum_add(0, &src.0);
+    sum = checksum_add(sum, &dst.0);
+    sum += protocol as u32 + data.len() as u32;
+    checksum_fold(checksum_add(sum, data))
+}
+
+/// 封装 IP 报文并发送
+///
+/// ## Arguments
+/// * `src` / `dst` - 源地址与目的地址
+/// * `protocol` - 上层协议号
+/// * `payload` - 上层协议的报文
+///
+/// ## Returns
+/// 没有可达的路由时返回 `Err(-ENETUNREACH)`
+fn ip_output(src: Ipv4Addr, dst: Ipv4Addr, protocol: u8, payload: &[u8]) -> Result<(), isize> {
+    let route = route(dst)?;
+    let ident = {
+        let mut ident = IP_IDENT.exclusive_access();
+        *ident = ident.wrapping_add(1);
+        *ident
+    };
+    let total_len = (IPV4_HEADER_LEN + payload.len()) as u16;
+    let mut packet = Vec::with_capacity(IPV4_HEADER_LEN + payload.len());
+    packet.extend_from_slice(&[0x45, 0]);
+    packet.extend_from_slice(&total_len.to_be_bytes());
+    packet.extend_from_slice(&ident.to_be_bytes());
+    packet.extend_from_slice(&IPV4_DONT_FRAGMENT.to_be_bytes());
+    packet.extend_from_slice(&[IPV4_TTL, protocol, 0, 0]);
+    packet.extend_from_slice(&src.0);
+    packet.extend_from_slice(&dst.0);
+    let sum = checksum(&packet);
+    packet[10..12].copy_from_slice(&sum.to_be_bytes());
+    packet.extend_from_slice(payload);
+    match route {
+        Route::Loopback => LOOPBACK_QUEUE.exclusive_access().push_back(packet),
+        Route::Ethernet(next_hop) => ethernet::output(next_hop, packet),
+    }
+    Ok(())
+}
+
+/// 处理收到的 IP 报文，按协议号交给上层
+fn ip_input(packet: &[u8]) {
+    if packet.len() < IPV4_HEADER_LEN || packet[0] >> 4 != 4 {
+        return;
+    }
+    let header_len = (packet[0] & 0xf) as usize * 4;
+    let total_len = u16::from_be_bytes([packet[2], packet[3]]) as usize;
+    if header_len < IPV4_HEADER_LEN
+        || total_len < header_len
+        || total_len > packet.len()
+        || checksum(&packet[..header_len]) != 0
+    {
+        return;
+    }
+    // 分片：设置了 MF 或片偏移不为 0
+    if u16::from_be_bytes([packet[6], packet[7]]) & 0x3fff != 0 {
+        return;
+    }
+    let src = Ipv4Addr(packet[12..16].try_into().unwrap());
+    let dst = Ipv4Addr(packet[16..20].try_into().unwrap());
+    if !is_local(dst) && dst != Ipv4Addr::BROADCAST {
+        return;
+    }
+    let payload = &packet[header_len..total_len];
+    match packet[9] {
+        IPPROTO_ICMP => icmp_input(src, dst, payload),
+        IPPROTO_TCP => tcp::input(src, dst, payload),
+        IPPROTO_UDP => udp::input(src, dst, payload),
+        _ => {}
+    }
+}
+
+/// 回应 ICMP 回显请求，其余 ICMP 报文被忽略
+fn icmp_input(src: Ipv4Addr, dst: Ipv4Addr, message: &[u8]) {
+    if message.len() < 8 || message[0] != ICMP_ECHO_REQUEST || checksum(message) != 0 {
+        return;
+    }
+    if dst == Ipv4Addr::BROADCAST {
+        return;
+    }
+    let mut reply = Vec::from(message);
+    reply[0] = ICMP_ECHO_REPLY;
+    reply[2..4].fill(0);
+    let sum = checksum(&reply);
+    reply[2..4].copy_from_slice(&sum.to_be_bytes());
+    let _ = ip_output(dst, src, IPPROTO_ICMP, &reply);
+}
+
+/// 处理所有已到达的报文，并检查 TCP 的重传定时器
+///
+/// 在套接字系统调用、网卡中断与时钟中断中调用；调用者不能持有任何套接字状态的借用。
+pub fn poll() {
+    tcp::check_timers();
+    loop {
+        let packet = LOOPBACK_QUEUE.exclusive_access().pop_front();
+        if let Some(packet) = packet {
+            ip_input(&packet);
+            continue;
+        }
+        match ethernet::receive() {
+            Some(frame) => ethernet::input(&frame),
+            None => break,
+        }
+    }
+}
+
+/// 网卡的中断处理者
+///
+/// 应答网卡中断后处理收到的帧。
+pub struct NetIrqHandler;
+
+impl IrqHandler for NetIrqHandler {
+    fn handle_irq(&self) {
+        if let Some(device) = NET_DEVICE.as_ref() {
+            device.ack_interrupt();
+        }
+        poll();
+    }
+}
+
+/// 创建 `AF_INET` 套接字：流式套接字使用 TCP，数据报套接字使用 UDP
+pub fn socket(type_: SockType, flags: OpenFlags) -> Arc<dyn File + Send + Sync> {
+    match type_ {
+        SockType::Stream => TcpSocket::new(flags),
+        SockType::Dgram => UdpSocket::new(flags),
+    }
+}
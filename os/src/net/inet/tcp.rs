@@ -0,0 +1,1148 @@
+//! # TCP
+//!
+//! 每条连接由一个传输控制块 [`Tcb`] 描述，按（本地端口、对端地址、对端端口）登记在
+//! [`CONNECTIONS`] 中。套接字关闭后连接仍留在表中，直到 FIN 交换完成或连接被重置，
+//! 因此已写入的数据在 `close` 之后仍会被可靠地送达。
+//!
+//! ## 被动打开
+//!
+//! 监听套接字在 [`LISTENERS`] 中登记一个 [`Listener`]。收到 SYN 时为连接创建
+//! `SYN-RECEIVED` 状态的控制块，握手完成后放入监听者的连接队列等待 `accept`。
+//! 握手中与已完成的连接数达到 `backlog` 时，新的 SYN 被丢弃，由对端重传。
+//!
+//! ## 可靠传输
+//!
+//! - 发送缓冲区保存从 `SND.UNA` 开始的全部数据，按对端通告的窗口以 [`MSS`] 为单位发送
+//! - 超时后从 `SND.UNA` 起全部重传（回退 N 步），超时时间每次加倍，
+//!   超过 [`MAX_RETRIES`] 次后连接以 `-ETIMEDOUT` 失败
+//! - 对端窗口为 0 时定时发送 1 字节的窗口探测
+//! - 只接受按序到达的报文段，乱序的报文段被丢弃并回应重复的 ACK
+//!
+//! ## 限制
+//!
+//! - 不解析 TCP 选项，不支持窗口扩大因子、SACK 与时间戳
+//! - 省略 `TIME-WAIT` 状态，连接在 FIN 交换完成后直接关闭
+//! - 不支持同时打开
+
+use super::{
+    Endpoint, IPPROTO_TCP, Ipv4Addr, alloc_ephemeral_port, check_bind_addr, destination, ip_output,
+    poll, pseudo_checksum, source_addr,
+};
+use crate::errno::Errno::{
+    EADDRINUSE, EAFNOSUPPORT, EAGAIN, EALREADY, ECONNREFUSED, ECONNRESET, EINPROGRESS, EINTR,
+    EINVAL, EISCONN, ENOTCONN, EPIPE, ETIMEDOUT,
+};
+use crate::fs::{File, FileStatus, OpenFlags, PollEvents, PollTable};
+use crate::mm::UserBuffer;
+use crate::net::{MSG_DONTWAIT, MSG_NOSIGNAL, SockAddr, Socket};
+use crate::process::{SignalFlags, current_send_signal, current_signal_pending};
+use crate::sync::{UPSafeCell, WaitQueue};
+use crate::timer::{time, time_ms};
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use lazy_static::*;
+
+const TCP_HEADER_LEN: usize = 20;
+
+const FIN: u8 = 0x01;
+const SYN: u8 = 0x02;
+const RST: u8 = 0x04;
+const PSH: u8 = 0x08;
+const ACK: u8 = 0x10;
+
+/// 最大报文段长度（以太网 MTU 1500 减去 IP 与 TCP 首部）
+const MSS: usize = 1460;
+/// 发送缓冲区容量
+const SEND_CAPACITY: usize = 64 * 1024;
+/// 接收缓冲区容量，即通告的最大窗口
+const RECV_CAPACITY: usize = u16::MAX as usize;
+/// 初始的重传超时时间
+const INITIAL_RTO_MS: usize = 200;
+/// 重传超时时间的上限
+const MAX_RTO_MS: usize = 8000;
+/// 连续超时的次数上限
+const MAX_RETRIES: usize = 8;
+/// `listen` 积压连接数的上限
+const SOMAXCONN: usize = 128;
+
+lazy_static! {
+    /// 以（本地端口、对端地址、对端端口）为键的连接
+    static ref CONNECTIONS: UPSafeCell<BTreeMap<(u16, Ipv4Addr, u16), Arc<Tcb>>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+
+    /// 本地端口到在其上监听的监听者
+    static ref LISTENERS: UPSafeCell<BTreeMap<u16, Arc<Listener>>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+
+    /// 本地端口到显式绑定它的套接字
+    static ref TCP_PORTS: UPSafeCell<BTreeMap<u16, Weak<TcpSocket>>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+}
+
+fn port_in_use(port: u16) -> bool {
+    TCP_PORTS
+        .exclusive_access()
+        .get(&port)
+        .is_some_and(|socket| socket.strong_count() > 0)
+        || CONNECTIONS
+            .exclusive_access()
+            .keys()
+            .any(|(local, _, _)| *local == port)
+}
+
+/// 序号比较：`a` 在 `b` 之前（按 32 位回绕）
+fn seq_lt(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) < 0
+}
+
+fn seq_le(a: u32, b: u32) -> bool {
+    a == b || seq_lt(a, b)
+}
+
+/// 初始序号，取自时钟以避免与旧连接的报文段混淆
+fn initial_seq() -> u32 {
+    (time() as u32).wrapping_mul(2654435761)
+}
+
+/// 已写入部分数据时返回写入的字节数，否则返回错误码
+fn partial(done: usize, errno: isize) -> isize {
+    if done > 0 { done as isize } else { errno }
+}
+
+/// 收到的报文段
+struct Segment<'a> {
+    seq: u32,
+    ack: u32,
+    flags: u8,
+    window: u16,
+    payload: &'a [u8],
+}
+
+impl Segment<'_> {
+    /// 报文段占用的序号空间：数据长度，SYN 与 FIN 各占一个序号
+    fn len(&self) -> u32 {
+        self.payload.len() as u32 + (self.flags & SYN != 0) as u32 + (self.flags & FIN != 0) as u32
+    }
+}
+
+/// 构造并发送一个 TCP 报文段
+fn send_segment(
+    local: Endpoint,
+    remote: Endpoint,
+    seq: u32,
+    ack: u32,
+    flags: u8,
+    window: u16,
+    payload: &[u8],
+) {
+    let mut segment = Vec::with_capacity(TCP_HEADER_LEN + payload.len());
+    segment.extend_from_slice(&local.1.to_be_bytes());
+    segment.extend_from_slice(&remote.1.to_be_bytes());
+    segment.extend_from_slice(&seq.to_be_bytes());
+    segment.extend_from_slice(&ack.to_be_bytes());
+    segment.extend_from_slice(&[(TCP_HEADER_LEN as u8 / 4) << 4, flags]);
+    segment.extend_from_slice(&window.to_be_bytes());
+    segment.extend_from_slice(&[0, 0, 0, 0]);
+    segment.extend_from_slice(payload);
+    let sum = pseudo_checksum(local.0, remote.0, IPPROTO_TCP, &segment);
+    segment[16..18].copy_from_slice(&sum.to_be_bytes());
+    // 路由在连接建立时已经确定，这里不会失败
+    let _ = ip_output(local.0, remote.0, IPPROTO_TCP, &segment);
+}
+
+/// 回应不属于任何连接的报文段（RFC 793 的复位生成规则）
+fn send_reset(local: Endpoint, remote: Endpoint, seg: &Segment) {
+    if seg.flags & RST != 0 {
+        return;
+    }
+    if seg.flags & ACK != 0 {
+        send_segment(local, remote, seg.ack, 0, RST, 0, &[]);
+    } else {
+        let ack = seg.seq.wrapping_add(seg.len());
+        send_segment(local, remote, 0, ack, RST | ACK, 0, &[]);
+    }
+}
+
+/// TCP 连接状态（RFC 793），省略了 `LISTEN`（由 [`Listener`] 表示）与 `TIME-WAIT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TcpState {
+    SynSent,
+    SynReceived,
+    Established,
+    FinWait1,
+    FinWait2,
+    CloseWait,
+    Closing,
+    LastAck,
+    Closed,
+}
+
+struct TcbInner {
+    state: TcpState,
+    local: Endpoint,
+    remote: Endpoint,
+    iss: u32,
+    snd_una: u32,
+    snd_nxt: u32,
+    /// 对端通告的窗口
+    snd_wnd: usize,
+    /// 从 `SND.UNA` 开始尚未被确认的数据，包括还没有发出的部分
+    send_buf: VecDeque<u8>,
+    /// 发送方向已关闭，数据发完后发送 FIN
+    fin_queued: bool,
+    rcv_nxt: u32,
+    recv_buf: VecDeque<u8>,
+    /// 已收到对端的 FIN
+    fin_received: bool,
+    /// 最近一次通告的接收窗口
+    rcv_wnd_advertised: usize,
+    /// 套接字已关闭，之后收到的数据直接丢弃
+    user_closed: bool,
+    /// 连接失败的原因（被拒绝、被重置或超时）
+    error: Option<isize>,
+    rto: usize,
+    /// 重传定时器的到期时刻
+    retransmit_at: Option<usize>,
+    retries: usize,
+}
+
+impl TcbInner {
+    fn new(state: TcpState, local: Endpoint, remote: Endpoint) -> Self {
+        let iss = initial_seq();
+        Self {
+            state,
+            local,
+            remote,
+            iss,
+            snd_una: iss,
+            snd_nxt: iss.wrapping_add(1),
+            snd_wnd: 0,
+            send_buf: VecDeque::new(),
+            fin_queued: false,
+            rcv_nxt: 0,
+            recv_buf: VecDeque::new(),
+            fin_received: false,
+            rcv_wnd_advertised: RECV_CAPACITY,
+            user_closed: false,
+            error: None,
+            rto: INITIAL_RTO_MS,
+            retransmit_at: Some(time_ms() + INITIAL_RTO_MS),
+            retries: 0,
+        }
+    }
+
+    fn window(&self) -> usize {
+        RECV_CAPACITY - self.recv_buf.len()
+    }
+
+    /// 以当前的 `RCV.NXT` 与接收窗口发送报文段
+    fn send(&mut self, seq: u32, flags: u8, payload: &[u8]) {
+        self.rcv_wnd_advertised = self.window();
+        send_segment(
+            self.local,
+            self.remote,
+            seq,
+            self.rcv_nxt,
+            flags,
+            self.rcv_wnd_advertised as u16,
+            payload,
+        );
+    }
+
+    fn send_ack(&mut self) {
+        self.send(self.snd_nxt, ACK, &[]);
+    }
+
+    /// 发送握手阶段的 SYN 或 SYN-ACK
+    fn send_syn(&mut self) {
+        match self.state {
+            TcpState::SynSent => self.send(self.iss, SYN, &[]),
+            TcpState::SynReceived => self.send(self.iss, SYN | ACK, &[]),
+            _ => {}
+        }
+    }
+
+    /// 还有已发出但未被确认的序号时确保重传定时器在运行，否则停止它
+    fn update_timer(&mut self) {
+        let probing = self.snd_wnd == 0 && !self.send_buf.is_empty();
+        if self.snd_nxt == self.snd_una && !probing {
+            self.retransmit_at = None;
+        } else if self.retransmit_at.is_none() {
+            self.retransmit_at = Some(time_ms() + self.rto);
+        }
+    }
+
+    /// 在 `window` 允许的范围内发送未发出的数据，数据发完后发送排队的 FIN
+    fn transmit(&mut self, window: usize) {
+        if !matches!(
+            self.state,
+            TcpState::Established
+                | TcpState::CloseWait
+                | TcpState::FinWait1
+                | TcpState::Closing
+                | TcpState::LastAck
+        ) {
+            return;
+        }
+        loop {
+            let sent = self.snd_nxt.wrapping_sub(self.snd_una) as usize;
+            if sent >= self.send_buf.len() || sent >= window {
+                break;
+            }
+            let len = (self.send_buf.len() - sent).min(window - sent).min(MSS);
+            let payload: Vec<u8> = self.send_buf.range(sent..sent + len).copied().collect();
+            self.send(self.snd_nxt, ACK | PSH, &payload);
+            self.snd_nxt = self.snd_nxt.wrapping_add(len as u32);
+        }
+        let sent = self.snd_nxt.wrapping_sub(self.snd_una) as usize;
+        if self.fin_queued && sent == self.send_buf.len() {
+            self.state = match self.state {
+                TcpState::Established => TcpState::FinWait1,
+                TcpState::CloseWait => TcpState::LastAck,
+                state => state,
+            };
+            self.send(self.snd_nxt, FIN | ACK, &[]);
+            self.snd_nxt = self.snd_nxt.wrapping_add(1);
+        }
+        self.update_timer();
+    }
+
+    fn output(&mut self) {
+        self.transmit(self.snd_wnd);
+    }
+
+    /// 重传定时器到期
+    fn on_timeout(&mut self) {
+        // 窗口探测不计入重传次数，对端可以任意长时间地保持零窗口
+        let probing =
+            self.snd_wnd == 0 && !matches!(self.state, TcpState::SynSent | TcpState::SynReceived);
+        if !probing {
+            self.retries += 1;
+            if self.retries > MAX_RETRIES {
+                self.error = Some(-ETIMEDOUT);
+                self.state = TcpState::Closed;
+                self.retransmit_at = None;
+                return;
+            }
+        }
+        self.rto = (self.rto * 2).min(MAX_RTO_MS);
+        self.retransmit_at = Some(time_ms() + self.rto);
+        match self.state {
+            TcpState::SynSent | TcpState::SynReceived => self.send_syn(),
+            _ => {
+                self.snd_nxt = self.snd_una;
+                self.transmit(self.snd_wnd.max(1));
+            }
+        }
+    }
+
+    /// 中止连接，向对端发送 RST
+    fn abort(&mut self) {
+        if !matches!(self.state, TcpState::SynSent | TcpState::Closed) {
+            self.send(self.snd_nxt, RST | ACK, &[]);
+        }
+        self.state = TcpState::Closed;
+        self.retransmit_at = None;
+    }
+
+    /// 处理 `SYN-SENT` 状态下收到的报文段
+    fn process_syn_sent(&mut self, seg: &Segment) {
+        if seg.flags & ACK != 0 && seg.ack != self.iss.wrapping_add(1) {
+            if seg.flags & RST == 0 {
+                send_segment(self.local, self.remote, seg.ack, 0, RST, 0, &[]);
+            }
+            return;
+        }
+        if seg.flags & RST != 0 {
+            if seg.flags & ACK != 0 {
+                self.error = Some(-ECONNREFUSED);
+                self.state = TcpState::Closed;
+                self.retransmit_at = None;
+            }
+            return;
+        }
+        if seg.flags & (SYN | ACK) == SYN | ACK {
+            self.rcv_nxt = seg.seq.wrapping_add(1);
+            self.snd_una = seg.ack;
+            self.snd_wnd = seg.window as usize;
+            self.state = TcpState::Established;
+            self.retries = 0;
+            self.rto = INITIAL_RTO_MS;
+            self.retransmit_at = None;
+            self.send_ack();
+            self.output();
+        }
+    }
+
+    /// 处理收到的报文段
+    fn process(&mut self, seg: &Segment) {
+        if self.state == TcpState::SynSent {
+            self.process_syn_sent(seg);
+            return;
+        }
+        let in_order = seg.seq == self.rcv_nxt;
+        if seg.flags & RST != 0 {
+            if in_order {
+                if self.state != TcpState::SynReceived {
+                    self.error = Some(-ECONNRESET);
+                }
+                self.state = TcpState::Closed;
+                self.retransmit_at = None;
+            }
+            return;
+        }
+        if seg.flags & SYN != 0 {
+            // 对端重传的 SYN：SYN-ACK 丢失了，再发一次
+            if self.state == TcpState::SynReceived && seg.seq.wrapping_add(1) == self.rcv_nxt {
+                self.send_syn();
+            } else {
+                self.send_ack();
+            }
+            return;
+        }
+        if seg.flags & ACK == 0 {
+            return;
+        }
+
+        if self.state == TcpState::SynReceived {
+            if seg.ack != self.iss.wrapping_add(1) {
+                send_segment(self.local, self.remote, seg.ack, 0, RST, 0, &[]);
+                return;
+            }
+            self.state = TcpState::Established;
+            self.snd_una = seg.ack;
+            self.retries = 0;
+            self.rto = INITIAL_RTO_MS;
+            self.retransmit_at = None;
+        }
+        if seq_lt(self.snd_nxt, seg.ack) {
+            // 确认了尚未发送的数据
+            self.send_ack();
+            return;
+        }
+        if seq_le(self.snd_una, seg.ack) {
+            let acked = seg.ack.wrapping_sub(self.snd_una) as usize;
+            if acked > 0 {
+                let data_acked = acked.min(self.send_buf.len());
+                self.send_buf.drain(..data_acked);
+                self.snd_una = seg.ack;
+                self.retries = 0;
+                self.rto = INITIAL_RTO_MS;
+                self.retransmit_at = None;
+                // FIN 也被确认了
+                if acked > data_acked {
+                    match self.state {
+                        TcpState::FinWait1 => self.state = TcpState::FinWait2,
+                        TcpState::Closing | TcpState::LastAck => {
+                            self.state = TcpState::Closed;
+                            return;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            self.snd_wnd = seg.window as usize;
+        }
+
+        let mut need_ack = false;
+        let receiving = matches!(
+            self.state,
+            TcpState::Established | TcpState::FinWait1 | TcpState::FinWait2
+        );
+        let mut accepted = 0;
+        if !seg.payload.is_empty() {
+            need_ack = true;
+            if in_order && receiving {
+                accepted = if self.user_closed {
+                    seg.payload.len()
+                } else {
+                    let len = seg.payload.len().min(self.window());
+                    self.recv_buf.extend(&seg.payload[..len]);
+                    len
+                };
+                self.rcv_nxt = self.rcv_nxt.wrapping_add(accepted as u32);
+            }
+        }
+        if seg.flags & FIN != 0 {
+            need_ack = true;
+            if in_order && receiving && accepted == seg.payload.len() {
+                self.rcv_nxt = self.rcv_nxt.wrapping_add(1);
+                self.fin_received = true;
+                self.state = match self.state {
+                    TcpState::Established => TcpState::CloseWait,
+                    TcpState::FinWait1 => TcpState::Closing,
+                    _ => TcpState::Closed,
+                };
+            }
+        }
+        if need_ack {
+            self.send_ack();
+        }
+        if self.state == TcpState::Closed {
+            self.retransmit_at = None;
+            return;
+        }
+        self.output();
+    }
+
+    /// 套接字被关闭：发送方向排队 FIN，尚未建立的连接直接放弃
+    fn close(&mut self) {
+        self.user_closed = true;
+        self.recv_buf.clear();
+        match self.state {
+            TcpState::SynSent => {
+                self.state = TcpState::Closed;
+                self.retransmit_at = None;
+            }
+            TcpState::SynReceived => self.abort(),
+            TcpState::Established | TcpState::CloseWait => {
+                self.fin_queued = true;
+                self.output();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 传输控制块
+struct Tcb {
+    inner: UPSafeCell<TcbInner>,
+    /// 连接状态变化时唤醒；主动打开时与套接字共用
+    waiters: Arc<WaitQueue>,
+    /// 被动打开的连接所属的监听者，握手完成后放入其连接队列
+    listener: Weak<Listener>,
+}
+
+impl Tcb {
+    fn key(&self) -> (u16, Ipv4Addr, u16) {
+        let inner = self.inner.exclusive_access();
+        (inner.local.1, inner.remote.0, inner.remote.1)
+    }
+
+    fn state(&self) -> TcpState {
+        self.inner.exclusive_access().state
+    }
+
+    /// 连接已关闭时从 [`CONNECTIONS`] 中移除
+    fn release(self: &Arc<Self>) {
+        if self.state() != TcpState::Closed {
+            return;
+        }
+        let key = self.key();
+        let mut connections = CONNECTIONS.exclusive_access();
+        if connections
+            .get(&key)
+            .is_some_and(|tcb| Arc::ptr_eq(tcb, self))
+        {
+            connections.remove(&key);
+        }
+    }
+
+    fn input(self: &Arc<Self>, seg: &Segment) {
+        let mut inner = self.inner.exclusive_access();
+        let handshaking = inner.state == TcpState::SynReceived;
+        inner.process(seg);
+        let established = handshaking && inner.state != TcpState::SynReceived;
+        drop(inner);
+        if established && self.state() != TcpState::Closed {
+            match self.listener.upgrade() {
+                Some(listener) => {
+                    listener
+                        .inner
+                        .exclusive_access()
+                        .accept_queue
+                        .push_back(self.clone());
+                    listener.waiters.wake_all();
+                }
+                None => self.inner.exclusive_access().abort(),
+            }
+        }
+        self.release();
+        self.waiters.wake_all();
+    }
+
+    fn abort(self: &Arc<Self>) {
+        self.inner.exclusive_access().abort();
+        self.release();
+        self.waiters.wake_all();
+    }
+}
+
+struct ListenerInner {
+    backlog: usize,
+    /// 握手已完成、等待 `accept` 的连接
+    accept_queue: VecDeque<Arc<Tcb>>,
+}
+
+/// 监听者
+struct Listener {
+    local: Endpoint,
+    inner: UPSafeCell<ListenerInner>,
+    /// 监听套接字的等待队列，新连接进入队列时唤醒
+    waiters: Arc<WaitQueue>,
+}
+
+impl Listener {
+    /// 握手尚未完成的连接数
+    fn half_open(self: &Arc<Self>) -> usize {
+        CONNECTIONS
+            .exclusive_access()
+            .values()
+            .filter(|tcb| {
+                tcb.listener.as_ptr() == Arc::as_ptr(self) && tcb.state() == TcpState::SynReceived
+            })
+            .count()
+    }
+
+    /// 收到发往监听端口的 SYN，积压的连接未满时回应 SYN-ACK
+    fn on_syn(self: &Arc<Self>, local: Endpoint, remote: Endpoint, seg: &Segment) {
+        let queued = self.inner.exclusive_access().accept_queue.len();
+        if queued + self.half_open() >= self.inner.exclusive_access().backlog {
+            return;
+        }
+        let mut inner = TcbInner::new(TcpState::SynReceived, local, remote);
+        inner.rcv_nxt = seg.seq.wrapping_add(1);
+        inner.snd_wnd = seg.window as usize;
+        inner.send_syn();
+        let tcb = Arc::new(Tcb {
+            inner: unsafe { UPSafeCell::new(inner) },
+            waiters: Arc::new(WaitQueue::new()),
+            listener: Arc::downgrade(self),
+        });
+        CONNECTIONS
+            .exclusive_access()
+            .insert((local.1, remote.0, remote.1), tcb);
+    }
+
+    /// 监听套接字关闭：重置所有尚未被取走和尚未完成握手的连接
+    fn close(self: &Arc<Self>) {
+        let queued = core::mem::take(&mut self.inner.exclusive_access().accept_queue);
+        for tcb in queued {
+            tcb.abort();
+        }
+        let half_open: Vec<Arc<Tcb>> = CONNECTIONS
+            .exclusive_access()
+            .values()
+            .filter(|tcb| {
+                tcb.listener.as_ptr() == Arc::as_ptr(self) && tcb.state() == TcpState::SynReceived
+            })
+            .cloned()
+            .collect();
+        for tcb in half_open {
+            tcb.abort();
+        }
+    }
+}
+
+/// 处理收到的 TCP 报文段
+pub(super) fn input(src: Ipv4Addr, dst: Ipv4Addr, segment: &[u8]) {
+    if segment.len() < TCP_HEADER_LEN || pseudo_checksum(src, dst, IPPROTO_TCP, segment) != 0 {
+        return;
+    }
+    let src_port = u16::from_be_bytes([segment[0], segment[1]]);
+    let dst_port = u16::from_be_bytes([segment[2], segment[3]]);
+    let data_offset = (segment[12] >> 4) as usize * 4;
+    if data_offset < TCP_HEADER_LEN || data_offset > segment.len() {
+        return;
+    }
+    let seg = Segment {
+        seq: u32::from_be_bytes(segment[4..8].try_into().unwrap()),
+        ack: u32::from_be_bytes(segment[8..12].try_into().unwrap()),
+        flags: segment[13],
+        window: u16::from_be_bytes([segment[14], segment[15]]),
+        payload: &segment[data_offset..],
+    };
+    let local = (dst, dst_port);
+    let remote = (src, src_port);
+    let tcb = CONNECTIONS
+        .exclusive_access()
+        .get(&(dst_port, src, src_port))
+        .cloned();
+    if let Some(tcb) = tcb {
+        tcb.input(&seg);
+        return;
+    }
+    if seg.flags & (SYN | ACK | RST) == SYN {
+        let listener = LISTENERS
+            .exclusive_access()
+            .get(&dst_port)
+            .filter(|listener| listener.local.0.is_unspecified() || listener.local.0 == dst)
+            .cloned();
+        if let Some(listener) = listener {
+            listener.on_syn(local, remote, &seg);
+            return;
+        }
+    }
+    send_reset(local, remote, &seg);
+}
+
+/// 处理到期的重传定时器
+pub(super) fn check_timers() {
+    let now = time_ms();
+    let expired: Vec<Arc<Tcb>> = CONNECTIONS
+        .exclusive_access()
+        .values()
+        .filter(|tcb| {
+            tcb.inner
+                .exclusive_access()
+                .retransmit_at
+                .is_some_and(|at| at <= now)
+        })
+        .cloned()
+        .collect();
+    for tcb in expired {
+        tcb.inner.exclusive_access().on_timeout();
+        tcb.release();
+        tcb.waiters.wake_all();
+    }
+}
+
+/// 套接字的状态
+enum State {
+    /// 未连接（可能已绑定）
+    Unconnected,
+    Listening(Arc<Listener>),
+    /// 已发起连接，连接可能仍在握手或已经关闭
+    Connected(Arc<Tcb>),
+}
+
+struct TcpSocketInner {
+    /// 绑定的本地端点
+    local: Option<Endpoint>,
+    state: State,
+}
+
+/// TCP 套接字
+pub struct TcpSocket {
+    status: FileStatus,
+    inner: UPSafeCell<TcpSocketInner>,
+    waiters: Arc<WaitQueue>,
+    me: Weak<TcpSocket>,
+}
+
+impl TcpSocket {
+    /// 创建未绑定、未连接的套接字
+    pub fn new(flags: OpenFlags) -> Arc<Self> {
+        Self::with_state(flags, None, State::Unconnected, Arc::new(WaitQueue::new()))
+    }
+
+    fn with_state(
+        flags: OpenFlags,
+        local: Option<Endpoint>,
+        state: State,
+        waiters: Arc<WaitQueue>,
+    ) -> Arc<Self> {
+        Arc::new_cyclic(|me| Self {
+            status: FileStatus::new(flags | OpenFlags::RDWR),
+            inner: unsafe { UPSafeCell::new(TcpSocketInner { local, state }) },
+            waiters,
+            me: me.clone(),
+        })
+    }
+
+    fn nonblocking(&self, flags: u32) -> bool {
+        self.status.nonblocking() || flags & MSG_DONTWAIT != 0
+    }
+
+    /// 绑定到 `addr:port`，`port` 为 0 时分配临时端口
+    fn bind_to(&self, inner: &mut TcpSocketInner, addr: Ipv4Addr, port: u16) -> isize {
+        let port = if port == 0 {
+            match alloc_ephemeral_port(port_in_use) {
+                Some(port) => port,
+                None => return -EADDRINUSE,
+            }
+        } else if port_in_use(port) {
+            return -EADDRINUSE;
+        } else {
+            port
+        };
+        let mut ports = TCP_PORTS.exclusive_access();
+        ports.retain(|_, socket| socket.strong_count() > 0);
+        ports.insert(port, self.me.clone());
+        inner.local = Some((addr, port));
+        0
+    }
+
+    fn connection(&self) -> Result<Arc<Tcb>, isize> {
+        match &self.inner.exclusive_access().state {
+            State::Connected(tcb) => Ok(tcb.clone()),
+            State::Listening(_) => Err(-EINVAL),
+            State::Unconnected => Err(-ENOTCONN),
+        }
+    }
+
+    /// 等待主动打开的连接完成握手
+    fn wait_established(&self, tcb: &Arc<Tcb>, nonblocking: bool) -> isize {
+        loop {
+            let mut inner = tcb.inner.exclusive_access();
+            match inner.state {
+                TcpState::SynSent => {}
+                TcpState::Closed if inner.error.is_some() => {
+                    let errno = inner.error.take().unwrap();
+                    drop(inner);
+                    // 连接失败后套接字可以重新发起连接
+                    self.inner.exclusive_access().state = State::Unconnected;
+                    return errno;
+                }
+                _ => return 0,
+            }
+            drop(inner);
+            if nonblocking {
+                return -EINPROGRESS;
+            }
+            if current_signal_pending() {
+                return -EINTR;
+            }
+            self.waiters.wait();
+        }
+    }
+}
+
+impl Socket for TcpSocket {
+    fn bind(&self, addr: SockAddr) -> isize {
+        let SockAddr::Inet(addr, port) = addr else {
+            return -EAFNOSUPPORT;
+        };
+        let mut inner = self.inner.exclusive_access();
+        if inner.local.is_some() {
+            return -EINVAL;
+        }
+        if let Err(errno) = check_bind_addr(addr) {
+            return errno;
+        }
+        self.bind_to(&mut inner, addr, port)
+    }
+
+    fn listen(&self, backlog: usize) -> isize {
+        let backlog = backlog.clamp(1, SOMAXCONN);
+        let mut inner = self.inner.exclusive_access();
+        match &inner.state {
+            State::Listening(listener) => {
+                listener.inner.exclusive_access().backlog = backlog;
+                return 0;
+            }
+            State::Connected(_) => return -EINVAL,
+            State::Unconnected => {}
+        }
+        if inner.local.is_none() {
+            let errno = self.bind_to(&mut inner, Ipv4Addr::UNSPECIFIED, 0);
+            if errno != 0 {
+                return errno;
+            }
+        }
+        let local = inner.local.unwrap();
+        let listener = Arc::new(Listener {
+            local,
+            inner: unsafe {
+                UPSafeCell::new(ListenerInner {
+                    backlog,
+                    accept_queue: VecDeque::new(),
+                })
+            },
+            waiters: self.waiters.clone(),
+        });
+        LISTENERS
+            .exclusive_access()
+            .insert(local.1, listener.clone());
+        inner.state = State::Listening(listener);
+        0
+    }
+
+    fn accept(&self) -> Result<(Arc<dyn File + Send + Sync>, SockAddr), isize> {
+        let listener = match &self.inner.exclusive_access().state {
+            State::Listening(listener) => listener.clone(),
+            _ => return Err(-EINVAL),
+        };
+        loop {
+            let tcb = listener.inner.exclusive_access().accept_queue.pop_front();
+            if let Some(tcb) = tcb {
+                let (local, (addr, port)) = {
+                    let inner = tcb.inner.exclusive_access();
+                    (inner.local, inner.remote)
+                };
+                let waiters = tcb.waiters.clone();
+                let socket = Self::with_state(
+                    OpenFlags::empty(),
+                    Some(local),
+                    State::Connected(tcb),
+                    waiters,
+                );
+                return Ok((
+                    socket as Arc<dyn File + Send + Sync>,
+                    SockAddr::Inet(addr, port),
+                ));
+            }
+            if self.status.nonblocking() {
+                return Err(-EAGAIN);
+            }
+            if current_signal_pending() {
+                return Err(-EINTR);
+            }
+            self.waiters.wait();
+        }
+    }
+
+    /// 发起连接；非阻塞套接字返回 `-EINPROGRESS`，连接完成后可写
+    fn connect(&self, addr: SockAddr) -> isize {
+        let SockAddr::Inet(addr, port) = addr else {
+            return -EAFNOSUPPORT;
+        };
+        let nonblocking = self.status.nonblocking();
+        let mut inner = self.inner.exclusive_access();
+        match &inner.state {
+            State::Unconnected => {}
+            State::Listening(_) => return -EINVAL,
+            State::Connected(tcb) => {
+                let tcb = tcb.clone();
+                let (state, failed) = {
+                    let tcb = tcb.inner.exclusive_access();
+                    (tcb.state, tcb.error.is_some())
+                };
+                drop(inner);
+                return match state {
+                    TcpState::SynSent if nonblocking => -EALREADY,
+                    TcpState::SynSent => self.wait_established(&tcb, false),
+                    // 取回非阻塞连接失败的原因
+                    TcpState::Closed if failed => self.wait_established(&tcb, nonblocking),
+                    _ => -EISCONN,
+                };
+            }
+        }
+        let dst = destination(addr);
+        let src = match source_addr(dst) {
+            Ok(src) => src,
+            Err(errno) => return errno,
+        };
+        if inner.local.is_none() {
+            let errno = self.bind_to(&mut inner, Ipv4Addr::UNSPECIFIED, 0);
+            if errno != 0 {
+                return errno;
+            }
+        }
+        let (local_addr, local_port) = inner.local.unwrap();
+        let local = if local_addr.is_unspecified() {
+            (src, local_port)
+        } else {
+            (local_addr, local_port)
+        };
+        let key = (local_port, dst, port);
+        if CONNECTIONS.exclusive_access().contains_key(&key) {
+            return -EADDRINUSE;
+        }
+        let mut tcb_inner = TcbInner::new(TcpState::SynSent, local, (dst, port));
+        tcb_inner.send_syn();
+        let tcb = Arc::new(Tcb {
+            inner: unsafe { UPSafeCell::new(tcb_inner) },
+            waiters: self.waiters.clone(),
+            listener: Weak::new(),
+        });
+        CONNECTIONS.exclusive_access().insert(key, tcb.clone());
+        inner.state = State::Connected(tcb.clone());
+        drop(inner);
+        poll();
+        self.wait_established(&tcb, nonblocking)
+    }
+
+    /// 发送数据；连接仍在握手时等待握手完成
+    fn sendto(&self, buf: UserBuffer, flags: u32, _addr: Option<SockAddr>) -> isize {
+        let tcb = match self.connection() {
+            Ok(tcb) => tcb,
+            Err(_) => return -ENOTCONN,
+        };
+        let nonblocking = self.nonblocking(flags);
+        let mut sent = 0usize;
+        for src in buf.buffers.iter() {
+            let mut offset = 0;
+            while offset < src.len() {
+                let mut inner = tcb.inner.exclusive_access();
+                let writable = match inner.state {
+                    TcpState::SynSent | TcpState::SynReceived => false,
+                    TcpState::Established | TcpState::CloseWait if !inner.fin_queued => true,
+                    _ => {
+                        let errno = inner.error.take().unwrap_or(-EPIPE);
+                        drop(inner);
+                        if errno == -EPIPE && flags & MSG_NOSIGNAL == 0 {
+                            current_send_signal(SignalFlags::SIGPIPE);
+                        }
+                        return partial(sent, errno);
+                    }
+                };
+                let room = SEND_CAPACITY - inner.send_buf.len();
+                if writable && room > 0 {
+                    let n = room.min(src.len() - offset);
+                    inner.send_buf.extend(src[offset..offset + n].iter());
+                    inner.output();
+                    offset += n;
+                    sent += n;
+                    drop(inner);
+                    poll();
+                    continue;
+                }
+                drop(inner);
+                if nonblocking {
+                    return partial(sent, -EAGAIN);
+                }
+                if current_signal_pending() {
+                    return partial(sent, -EINTR);
+                }
+                self.waiters.wait();
+            }
+        }
+        sent as isize
+    }
+
+    /// 接收数据；对端关闭后返回 0，连接被重置时返回一次 `-ECONNRESET`
+    fn recvfrom(&self, buf: UserBuffer, flags: u32) -> Result<(usize, SockAddr), isize> {
+        let tcb = self.connection()?;
+        let peer = {
+            let (addr, port) = tcb.inner.exclusive_access().remote;
+            SockAddr::Inet(addr, port)
+        };
+        if buf.len() == 0 {
+            return Ok((0, peer));
+        }
+        loop {
+            let mut inner = tcb.inner.exclusive_access();
+            if !inner.recv_buf.is_empty() {
+                let mut received = 0usize;
+                for dst in buf.buffers {
+                    let n = dst.len().min(inner.recv_buf.len());
+                    for (byte, data) in dst.iter_mut().zip(inner.recv_buf.drain(..n)) {
+                        *byte = data;
+                    }
+                    received += n;
+                    if n < dst.len() {
+                        break;
+                    }
+                }
+                // 窗口从很小重新打开时主动通告，否则对端要等到窗口探测才能继续发送
+                if inner.rcv_wnd_advertised < MSS
+                    && inner.window() >= MSS
+                    && inner.state != TcpState::Closed
+                {
+                    inner.send_ack();
+                }
+                drop(inner);
+                poll();
+                return Ok((received, peer));
+            }
+            if inner.fin_received {
+                return Ok((0, peer));
+            }
+            if let Some(errno) = inner.error.take() {
+                return Err(errno);
+            }
+            if inner.state == TcpState::Closed {
+                return Ok((0, peer));
+            }
+            drop(inner);
+            if self.nonblocking(flags) {
+                return Err(-EAGAIN);
+            }
+            if current_signal_pending() {
+                return Err(-EINTR);
+            }
+            self.waiters.wait();
+        }
+    }
+}
+
+impl File for TcpSocket {
+    fn read(&self, buf: UserBuffer) -> isize {
+        match self.recvfrom(buf, 0) {
+            Ok((len, _)) => len as isize,
+            Err(errno) => errno,
+        }
+    }
+
+    fn write(&self, buf: UserBuffer) -> isize {
+        self.sendto(buf, 0, None)
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn fcntl(&self, cmd: usize, arg: usize) -> isize {
+        self.status.fcntl(cmd, arg, true, true)
+    }
+
+    /// 监听者有连接等待 `accept` 时可读；连接有数据或对端关闭时可读，
+    /// 已建立且发送缓冲区有空间时可写，连接出错或关闭时报告 `POLLERR`/`POLLHUP`
+    fn poll(&self) -> PollEvents {
+        let inner = self.inner.exclusive_access();
+        let mut events = PollEvents::empty();
+        match &inner.state {
+            State::Unconnected => events |= PollEvents::POLLOUT | PollEvents::POLLHUP,
+            State::Listening(listener) => {
+                if !listener.inner.exclusive_access().accept_queue.is_empty() {
+                    events |= PollEvents::POLLIN;
+                }
+            }
+            State::Connected(tcb) => {
+                let tcb = tcb.inner.exclusive_access();
+                let closed = tcb.state == TcpState::Closed;
+                if !tcb.recv_buf.is_empty() || tcb.fin_received || closed {
+                    events |= PollEvents::POLLIN;
+                }
+                if matches!(tcb.state, TcpState::Established | TcpState::CloseWait)
+                    && !tcb.fin_queued
+                    && tcb.send_buf.len() < SEND_CAPACITY
+                {
+                    events |= PollEvents::POLLOUT;
+                }
+                if closed || (tcb.fin_received && tcb.fin_queued) {
+                    events |= PollEvents::POLLHUP;
+                }
+                if tcb.error.is_some() {
+                    events |= PollEvents::POLLERR;
+                }
+            }
+        }
+        events
+    }
+
+    fn poll_wait<'a>(&'a self, table: &mut PollTable<'a>) {
+        table.add(&self.waiters);
+    }
+
+    fn as_socket(&self) -> Option<&dyn Socket> {
+        Some(self)
+    }
+}
+
+impl Drop for TcpSocket {
+    /// 关闭连接的发送方向或关闭监听者，并释放绑定的端口
+    fn drop(&mut self) {
+        let state =
+            core::mem::replace(&mut self.inner.exclusive_access().state, State::Unconnected);
+        match state {
+            State::Listening(listener) => {
+                let mut listeners = LISTENERS.exclusive_access();
+                if listeners
+                    .get(&listener.local.1)
+                    .is_some_and(|l| Arc::ptr_eq(l, &listener))
+                {
+                    listeners.remove(&listener.local.1);
+                }
+                drop(listeners);
+                listener.close();
+            }
+            State::Connected(tcb) => {
+                tcb.inner.exclusive_access().close();
+                tcb.release();
+                tcb.waiters.wake_all();
+            }
+            State::Unconnected => {}
+        }
+        TCP_PORTS
+            .exclusive_access()
+            .retain(|_, socket| socket.strong_count() > 0);
+        poll();
+    }
+}
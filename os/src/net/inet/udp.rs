@@ -0,0 +1,316 @@
+//! # UDP
+//!
+//! 数据报套接字按本地端口登记在 [`UDP_PORTS`] 中，收到的数据报按目的端口投递到
+//! 套接字的接收队列；队列已满时数据报被丢弃。发送从不阻塞。
+//!
+//! 未绑定的套接字在第一次 `connect`/`sendto` 时自动绑定一个临时端口。
+
+use super::{
+    Endpoint, IPPROTO_UDP, Ipv4Addr, alloc_ephemeral_port, check_bind_addr, destination, ip_output,
+    poll, pseudo_checksum, source_addr,
+};
+use crate::errno::Errno::{
+    EADDRINUSE, EAFNOSUPPORT, EAGAIN, EDESTADDRREQ, EINTR, EINVAL, EMSGSIZE, EOPNOTSUPP,
+};
+use crate::fs::{File, FileStatus, OpenFlags, PollEvents, PollTable};
+use crate::mm::UserBuffer;
+use crate::net::{MSG_DONTWAIT, SockAddr, Socket};
+use crate::process::current_signal_pending;
+use crate::sync::{UPSafeCell, WaitQueue};
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use lazy_static::*;
+
+const UDP_HEADER_LEN: usize = 8;
+/// 单个数据报载荷的最大长度（IPv4 报文最长 65535 字节）
+const UDP_MAX_PAYLOAD: usize = 65535 - 20 - UDP_HEADER_LEN;
+/// 接收队列的最大长度
+const RECV_QUEUE_LEN: usize = 64;
+
+lazy_static! {
+    /// 本地端口到绑定在其上的套接字
+    static ref UDP_PORTS: UPSafeCell<BTreeMap<u16, Weak<UdpSocket>>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+}
+
+fn port_in_use(port: u16) -> bool {
+    UDP_PORTS
+        .exclusive_access()
+        .get(&port)
+        .is_some_and(|socket| socket.strong_count() > 0)
+}
+
+/// 一个数据报及其发送方
+struct Datagram {
+    data: Vec<u8>,
+    from: Endpoint,
+}
+
+struct UdpSocketInner {
+    /// 绑定的本地端点，未绑定时为 `None`
+    local: Option<Endpoint>,
+    /// `connect` 设置的默认目的端点，同时只接收来自它的数据报
+    peer: Option<Endpoint>,
+    datagrams: VecDeque<Datagram>,
+}
+
+/// UDP 套接字
+pub struct UdpSocket {
+    status: FileStatus,
+    inner: UPSafeCell<UdpSocketInner>,
+    /// 数据报到达时唤醒
+    waiters: WaitQueue,
+    me: Weak<UdpSocket>,
+}
+
+impl UdpSocket {
+    /// 创建未绑定的套接字
+    pub fn new(flags: OpenFlags) -> Arc<Self> {
+        Arc::new_cyclic(|me| Self {
+            status: FileStatus::new(flags | OpenFlags::RDWR),
+            inner: unsafe {
+                UPSafeCell::new(UdpSocketInner {
+                    local: None,
+                    peer: None,
+                    datagrams: VecDeque::new(),
+                })
+            },
+            waiters: WaitQueue::new(),
+            me: me.clone(),
+        })
+    }
+
+    /// 绑定到 `addr:port`，`port` 为 0 时分配临时端口
+    fn bind_to(&self, inner: &mut UdpSocketInner, addr: Ipv4Addr, port: u16) -> isize {
+        let port = if port == 0 {
+            match alloc_ephemeral_port(port_in_use) {
+                Some(port) => port,
+                None => return -EADDRINUSE,
+            }
+        } else if port_in_use(port) {
+            return -EADDRINUSE;
+        } else {
+            port
+        };
+        let mut ports = UDP_PORTS.exclusive_access();
+        ports.retain(|_, socket| socket.strong_count() > 0);
+        ports.insert(port, self.me.clone());
+        inner.local = Some((addr, port));
+        0
+    }
+
+    /// 取得本地端点，未绑定时自动绑定临时端口
+    fn local_or_bind(&self, inner: &mut UdpSocketInner) -> Result<Endpoint, isize> {
+        if inner.local.is_none() {
+            let errno = self.bind_to(inner, Ipv4Addr::UNSPECIFIED, 0);
+            if errno != 0 {
+                return Err(errno);
+            }
+        }
+        Ok(inner.local.unwrap())
+    }
+
+    fn nonblocking(&self, flags: u32) -> bool {
+        self.status.nonblocking() || flags & MSG_DONTWAIT != 0
+    }
+}
+
+impl Socket for UdpSocket {
+    fn bind(&self, addr: SockAddr) -> isize {
+        let SockAddr::Inet(addr, port) = addr else {
+            return -EAFNOSUPPORT;
+        };
+        let mut inner = self.inner.exclusive_access();
+        if inner.local.is_some() {
+            return -EINVAL;
+        }
+        if let Err(errno) = check_bind_addr(addr) {
+            return errno;
+        }
+        self.bind_to(&mut inner, addr, port)
+    }
+
+    fn listen(&self, _backlog: usize) -> isize {
+        -EOPNOTSUPP
+    }
+
+    fn accept(&self) -> Result<(Arc<dyn File + Send + Sync>, SockAddr), isize> {
+        Err(-EOPNOTSUPP)
+    }
+
+    fn connect(&self, addr: SockAddr) -> isize {
+        let SockAddr::Inet(addr, port) = addr else {
+            return -EAFNOSUPPORT;
+        };
+        let mut inner = self.inner.exclusive_access();
+        if let Err(errno) = self.local_or_bind(&mut inner) {
+            return errno;
+        }
+        inner.peer = Some((destination(addr), port));
+        0
+    }
+
+    fn sendto(&self, buf: UserBuffer, _flags: u32, addr: Option<SockAddr>) -> isize {
+        let mut inner = self.inner.exclusive_access();
+        let (dst, dst_port) = match addr {
+            Some(SockAddr::Inet(addr, port)) => (destination(addr), port),
+            Some(_) => return -EAFNOSUPPORT,
+            None => match inner.peer {
+                Some(peer) => peer,
+                None => return -EDESTADDRREQ,
+            },
+        };
+        let len = buf.len();
+        if len > UDP_MAX_PAYLOAD {
+            return -EMSGSIZE;
+        }
+        let (local, port) = match self.local_or_bind(&mut inner) {
+            Ok(local) => local,
+            Err(errno) => return errno,
+        };
+        drop(inner);
+        let src = if local.is_unspecified() {
+            match source_addr(dst) {
+                Ok(src) => src,
+                Err(errno) => return errno,
+            }
+        } else {
+            local
+        };
+        let mut datagram = Vec::with_capacity(UDP_HEADER_LEN + len);
+        datagram.extend_from_slice(&port.to_be_bytes());
+        datagram.extend_from_slice(&dst_port.to_be_bytes());
+        datagram.extend_from_slice(&((UDP_HEADER_LEN + len) as u16).to_be_bytes());
+        datagram.extend_from_slice(&[0, 0]);
+        for src in buf.buffers.iter() {
+            datagram.extend_from_slice(src);
+        }
+        // 校验和为 0 表示未计算，计算结果恰为 0 时按 RFC 768 写作全 1
+        let sum = match pseudo_checksum(src, dst, IPPROTO_UDP, &datagram) {
+            0 => 0xffff,
+            sum => sum,
+        };
+        datagram[6..8].copy_from_slice(&sum.to_be_bytes());
+        if let Err(errno) = ip_output(src, dst, IPPROTO_UDP, &datagram) {
+            return errno;
+        }
+        poll();
+        len as isize
+    }
+
+    fn recvfrom(&self, buf: UserBuffer, flags: u32) -> Result<(usize, SockAddr), isize> {
+        loop {
+            let datagram = self.inner.exclusive_access().datagrams.pop_front();
+            if let Some(datagram) = datagram {
+                let mut received = 0usize;
+                for dst in buf.buffers {
+                    let n = dst.len().min(datagram.data.len() - received);
+                    dst[..n].copy_from_slice(&datagram.data[received..received + n]);
+                    received += n;
+                }
+                let (addr, port) = datagram.from;
+                return Ok((received, SockAddr::Inet(addr, port)));
+            }
+            if self.nonblocking(flags) {
+                return Err(-EAGAIN);
+            }
+            if current_signal_pending() {
+                return Err(-EINTR);
+            }
+            self.waiters.wait();
+        }
+    }
+}
+
+impl File for UdpSocket {
+    fn read(&self, buf: UserBuffer) -> isize {
+        match self.recvfrom(buf, 0) {
+            Ok((len, _)) => len as isize,
+            Err(errno) => errno,
+        }
+    }
+
+    fn write(&self, buf: UserBuffer) -> isize {
+        self.sendto(buf, 0, None)
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn fcntl(&self, cmd: usize, arg: usize) -> isize {
+        self.status.fcntl(cmd, arg, true, true)
+    }
+
+    /// 有数据报时可读；发送从不阻塞，总是可写
+    fn poll(&self) -> PollEvents {
+        let mut events = PollEvents::POLLOUT;
+        if !self.inner.exclusive_access().datagrams.is_empty() {
+            events |= PollEvents::POLLIN;
+        }
+        events
+    }
+
+    fn poll_wait<'a>(&'a self, table: &mut PollTable<'a>) {
+        table.add(&self.waiters);
+    }
+
+    fn as_socket(&self) -> Option<&dyn Socket> {
+        Some(self)
+    }
+}
+
+impl Drop for UdpSocket {
+    fn drop(&mut self) {
+        UDP_PORTS
+            .exclusive_access()
+            .retain(|_, socket| socket.strong_count() > 0);
+    }
+}
+
+/// 把收到的 UDP 数据报投递给绑定在目的端口上的套接字
+pub(super) fn input(src: Ipv4Addr, dst: Ipv4Addr, datagram: &[u8]) {
+    if datagram.len() < UDP_HEADER_LEN {
+        return;
+    }
+    let src_port = u16::from_be_bytes([datagram[0], datagram[1]]);
+    let dst_port = u16::from_be_bytes([datagram[2], datagram[3]]);
+    let len = u16::from_be_bytes([datagram[4], datagram[5]]) as usize;
+    if len < UDP_HEADER_LEN || len > datagram.len() {
+        return;
+    }
+    let datagram = &datagram[..len];
+    let sum = u16::from_be_bytes([datagram[6], datagram[7]]);
+    if sum != 0 && pseudo_checksum(src, dst, IPPROTO_UDP, datagram) != 0 {
+        return;
+    }
+    let Some(socket) = UDP_PORTS
+        .exclusive_access()
+        .get(&dst_port)
+        .and_then(Weak::upgrade)
+    else {
+        return;
+    };
+    let mut inner = socket.inner.exclusive_access();
+    let (local, _) = inner.local.unwrap();
+    if !local.is_unspecified() && local != dst {
+        return;
+    }
+    if inner.peer.is_some_and(|peer| peer != (src, src_port)) {
+        return;
+    }
+    if inner.datagrams.len() >= RECV_QUEUE_LEN {
+        return;
+    }
+    inner.datagrams.push_back(Datagram {
+        data: Vec::from(&datagram[UDP_HEADER_LEN..]),
+        from: (src, src_port),
+    });
+    drop(inner);
+    socket.waiters.wake_all();
+}
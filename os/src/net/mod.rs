@@ -0,0 +1,194 @@
+//! # 套接字
+//!
+//! 套接字是一种 [`File`]：`read`/`write`、`dup`、`fork` 继承、`poll` 与 epoll
+//! 都沿用文件描述符框架；`bind`、`connect` 等套接字特有的操作通过
+//! [`File::as_socket`] 取得的 [`Socket`] 完成。
+//!
+//! ## 模块组织
+//!
+//! - [`unix`] - Unix 域套接字（`AF_UNIX`），地址是 Micro-FS 中的套接字文件
+//! - [`inet`] - TCP/IP 协议栈（`AF_INET`），包括回环接口与 VirtIO 网卡上的以太网接口
+//!
+//! ## 地址
+//!
+//! 系统调用层只负责在用户空间的 `struct sockaddr` 与 [`SockAddr`] 之间转换，
+//! 地址族的解析由 [`SockAddr::from_bytes`] / [`SockAddr::to_bytes`] 完成。
+
+mod inet;
+mod unix;
+
+use crate::board::VIRTIO1_IRQ;
+use crate::drivers::{NET_DEVICE, register_irq_handler};
+use crate::errno::Errno::{EAFNOSUPPORT, EINVAL};
+use crate::fs::{File, OpenFlags};
+use crate::mm::UserBuffer;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+pub use inet::{Ipv4Addr, poll};
+
+/// Unix 域地址族
+pub const AF_UNIX: usize = 1;
+/// IPv4 地址族
+pub const AF_INET: usize = 2;
+
+/// 面向连接的字节流
+pub const SOCK_STREAM: usize = 1;
+/// 保留消息边界的数据报
+pub const SOCK_DGRAM: usize = 2;
+
+/// 本次收发不阻塞
+pub const MSG_DONTWAIT: u32 = 0x40;
+/// 对端关闭时不发送 `SIGPIPE`，只返回 `-EPIPE`
+pub const MSG_NOSIGNAL: u32 = 0x4000;
+
+/// `sockaddr_un` 中路径的最大长度（含结尾的 `\0`）
+const UNIX_PATH_MAX: usize = 108;
+/// `sockaddr_in` 的长度
+const SOCKADDR_IN_LEN: usize = 16;
+
+/// 套接字类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SockType {
+    /// 面向连接的字节流（`SOCK_STREAM`）
+    Stream,
+    /// 保留消息边界的数据报（`SOCK_DGRAM`）
+    Dgram,
+}
+
+/// 套接字地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SockAddr {
+    /// Unix 域地址：套接字文件的路径，空串表示未绑定的匿名套接字
+    Unix(String),
+    /// IPv4 地址与端口
+    Inet(Ipv4Addr, u16),
+}
+
+impl SockAddr {
+    /// 从用户空间的 `struct sockaddr` 解析地址
+    ///
+    /// ## Returns
+    /// - `Err(-EINVAL)`：长度不足、Unix 域地址的路径为空或过长
+    /// - `Err(-EAFNOSUPPORT)`：不支持的地址族
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, isize> {
+        if bytes.len() < 2 {
+            return Err(-EINVAL);
+        }
+        match u16::from_ne_bytes([bytes[0], bytes[1]]) as usize {
+            AF_UNIX => {
+                let path = &bytes[2..bytes.len().min(2 + UNIX_PATH_MAX)];
+                let len = path.iter().position(|&b| b == 0).unwrap_or(path.len());
+                // 不支持以 `\0` 开头的抽象地址
+                if len == 0 || len == UNIX_PATH_MAX {
+                    return Err(-EINVAL);
+                }
+                let path = core::str::from_utf8(&path[..len]).map_err(|_| -EINVAL)?;
+                Ok(Self::Unix(String::from(path)))
+            }
+            AF_INET => {
+                if bytes.len() < SOCKADDR_IN_LEN {
+                    return Err(-EINVAL);
+                }
+                let port = u16::from_be_bytes([bytes[2], bytes[3]]);
+                Ok(Self::Inet(Ipv4Addr(bytes[4..8].try_into().unwrap()), port))
+            }
+            _ => Err(-EAFNOSUPPORT),
+        }
+    }
+
+    /// 编码为用户空间的 `struct sockaddr`；匿名的 Unix 域地址只包含地址族
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Unix(path) => {
+                let mut bytes = Vec::from((AF_UNIX as u16).to_ne_bytes());
+                if !path.is_empty() {
+                    bytes.extend_from_slice(path.as_bytes());
+                    bytes.push(0);
+                }
+                bytes
+            }
+            Self::Inet(addr, port) => {
+                let mut bytes = Vec::from((AF_INET as u16).to_ne_bytes());
+                bytes.extend_from_slice(&port.to_be_bytes());
+                bytes.extend_from_slice(&addr.0);
+                bytes.resize(SOCKADDR_IN_LEN, 0);
+                bytes
+            }
+        }
+    }
+}
+
+/// 套接字操作
+///
+/// 阻塞的操作在文件设置了 `O_NONBLOCK`（或 `flags` 含 [`MSG_DONTWAIT`]）时
+/// 返回 `-EAGAIN`，等待期间收到需要处理的信号时返回 `-EINTR`。
+pub trait Socket {
+    /// 绑定本地地址
+    fn bind(&self, addr: SockAddr) -> isize;
+
+    /// 开始接受连接，`backlog` 为等待 `accept` 的连接数上限
+    fn listen(&self, backlog: usize) -> isize;
+
+    /// 取出一个已建立的连接
+    ///
+    /// ## Returns
+    /// 连接对应的新套接字及对端地址
+    fn accept(&self) -> Result<(Arc<dyn File + Send + Sync>, SockAddr), isize>;
+
+    /// 连接到 `addr`；数据报套接字只设置默认的目的地址
+    fn connect(&self, addr: SockAddr) -> isize;
+
+    /// 发送数据，`addr` 为数据报的目的地址
+    ///
+    /// ## Returns
+    /// 发送的字节数
+    fn sendto(&self, buf: UserBuffer, flags: u32, addr: Option<SockAddr>) -> isize;
+
+    /// 接收数据
+    ///
+    /// ## Returns
+    /// 接收的字节数及发送方地址；数据报超出缓冲区的部分被丢弃
+    fn recvfrom(&self, buf: UserBuffer, flags: u32) -> Result<(usize, SockAddr), isize>;
+}
+
+/// 创建套接字
+///
+/// ## Arguments
+/// * `domain` - 地址族
+/// * `type_` - 套接字类型
+/// * `flags` - 文件状态标志（`O_NONBLOCK`）
+///
+/// ## Returns
+/// 不支持的地址族返回 `Err(-EAFNOSUPPORT)`
+pub fn socket(
+    domain: usize,
+    type_: SockType,
+    flags: OpenFlags,
+) -> Result<Arc<dyn File + Send + Sync>, isize> {
+    match domain {
+        AF_UNIX => Ok(unix::UnixSocket::new(type_, flags)),
+        AF_INET => Ok(inet::socket(type_, flags)),
+        _ => Err(-EAFNOSUPPORT),
+    }
+}
+
+/// 初始化网络：探测到网卡时登记网卡中断
+///
+/// 须在 [`drivers::init`](crate::drivers::init) 之后调用。
+pub fn init() {
+    if let Some(device) = NET_DEVICE.as_ref() {
+        let mac = device.mac();
+        ::log::info!(
+            "[kernel] virtio-net {:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            mac[0],
+            mac[1],
+            mac[2],
+            mac[3],
+            mac[4],
+            mac[5]
+        );
+        register_irq_handler(VIRTIO1_IRQ, Arc::new(inet::NetIrqHandler));
+    }
+}
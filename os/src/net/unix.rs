@@ -0,0 +1,681 @@
+//! # Unix 域套接字
+//!
+//! 本地进程间通信用的套接字，地址是 Micro-FS 根目录下的套接字文件：
+//! `bind` 创建套接字文件并在 [`SOCKET_TABLE`] 中按 inode 编号登记，
+//! `connect`/`sendto` 按路径找到 inode 编号，再找到绑定在上面的套接字。
+//!
+//! ## 流式套接字
+//!
+//! `connect` 为连接创建两个方向的 [`StreamBuffer`] 与服务端一侧的新套接字，
+//! 放入监听者的连接队列后立即返回，`accept` 从队列中取出服务端套接字。
+//! 读写语义与管道相同：对端关闭后读到 EOF，写入时发送 `SIGPIPE` 并返回 `-EPIPE`。
+//!
+//! ## 数据报套接字
+//!
+//! 每个数据报套接字有自己的接收队列，保留消息边界。发送时接收队列已满则等待；
+//! 绑定者关闭后，发往该地址的数据报返回 `-ECONNREFUSED`。
+//!
+//! ## 等待队列
+//!
+//! 每个套接字有一个等待队列；服务端套接字与发起连接的客户端共用同一个，
+//! 连接上的任何变化都唤醒它。数据报接收队列腾出空间时唤醒全局的
+//! [`DGRAM_SPACE`]。
+//!
+//! ## 限制
+//!
+//! 没有 `unlink`，套接字文件在绑定者关闭后仍留在磁盘上，可以被新的套接字重新绑定。
+
+use super::{MSG_DONTWAIT, MSG_NOSIGNAL, SockAddr, SockType, Socket};
+use crate::errno::Errno::{
+    EAGAIN, ECONNREFUSED, EINTR, EINVAL, EISCONN, EMSGSIZE, ENOTCONN, EOPNOTSUPP, EPIPE, EPROTOTYPE,
+};
+use crate::fs::{
+    File, FileStatus, OpenFlags, PollEvents, PollTable, bind_socket_node, find_socket_node,
+};
+use crate::mm::UserBuffer;
+use crate::process::{SignalFlags, current_send_signal, current_signal_pending};
+use crate::sync::{UPSafeCell, WaitQueue};
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use lazy_static::*;
+
+/// 流式套接字每个方向的缓冲区容量
+const STREAM_CAPACITY: usize = 64 * 1024;
+/// 数据报接收队列的最大长度
+const DGRAM_QUEUE_LEN: usize = 16;
+/// 单个数据报的最大长度
+const DGRAM_MAX_SIZE: usize = 64 * 1024;
+/// `listen` 积压连接数的上限
+const SOMAXCONN: usize = 128;
+
+lazy_static! {
+    /// 套接字文件的 inode 编号到绑定在其上的套接字
+    static ref SOCKET_TABLE: UPSafeCell<BTreeMap<u32, Weak<UnixSocket>>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+
+    /// 任一数据报接收队列腾出空间或关闭时唤醒，等待发送的进程在其上睡眠
+    static ref DGRAM_SPACE: WaitQueue = WaitQueue::new();
+}
+
+/// 按路径找到绑定的套接字
+///
+/// ## Returns
+/// 路径不存在时返回 `Err(-ENOENT)`，没有套接字绑定时返回 `Err(-ECONNREFUSED)`
+fn lookup(path: &str) -> Result<Arc<UnixSocket>, isize> {
+    let inode_id = find_socket_node(path)?;
+    SOCKET_TABLE
+        .exclusive_access()
+        .get(&inode_id)
+        .and_then(Weak::upgrade)
+        .ok_or(-ECONNREFUSED)
+}
+
+/// 已写入部分数据时返回写入的字节数，否则返回错误码
+fn partial(done: usize, errno: isize) -> isize {
+    if done > 0 { done as isize } else { errno }
+}
+
+struct StreamBufferInner {
+    data: VecDeque<u8>,
+    /// 接收方已关闭，继续写入返回 `-EPIPE`
+    reader_closed: bool,
+    /// 发送方已关闭，数据读完后返回 EOF
+    writer_closed: bool,
+}
+
+/// 流式连接一个方向上的字节流
+struct StreamBuffer {
+    inner: UPSafeCell<StreamBufferInner>,
+}
+
+impl StreamBuffer {
+    fn new() -> Arc<Self> {
+        Arc::new(Self {
+            inner: unsafe {
+                UPSafeCell::new(StreamBufferInner {
+                    data: VecDeque::new(),
+                    reader_closed: false,
+                    writer_closed: false,
+                })
+            },
+        })
+    }
+}
+
+/// 一个数据报及其发送方地址
+struct Datagram {
+    data: Vec<u8>,
+    from: String,
+}
+
+struct DgramQueueInner {
+    datagrams: VecDeque<Datagram>,
+    /// 所属套接字已关闭
+    closed: bool,
+}
+
+/// 数据报套接字的接收队列
+struct DgramQueue {
+    inner: UPSafeCell<DgramQueueInner>,
+    /// 所属套接字的等待队列，数据报到达时唤醒
+    waiters: Arc<WaitQueue>,
+}
+
+impl DgramQueue {
+    /// 把数据报放入队列，队列已满时等待
+    ///
+    /// ## Returns
+    /// 数据报的长度；队列所属的套接字已关闭时返回 `-ECONNREFUSED`
+    fn deliver(&self, datagram: Datagram, nonblocking: bool) -> isize {
+        let len = datagram.data.len();
+        loop {
+            let mut inner = self.inner.exclusive_access();
+            if inner.closed {
+                return -ECONNREFUSED;
+            }
+            if inner.datagrams.len() < DGRAM_QUEUE_LEN {
+                inner.datagrams.push_back(datagram);
+                drop(inner);
+                self.waiters.wake_all();
+                return len as isize;
+            }
+            drop(inner);
+            if nonblocking {
+                return -EAGAIN;
+            }
+            if current_signal_pending() {
+                return -EINTR;
+            }
+            DGRAM_SPACE.wait();
+        }
+    }
+}
+
+struct AcceptQueueInner {
+    /// 已建立、等待 `accept` 的连接（服务端一侧的套接字）
+    pending: VecDeque<Arc<UnixSocket>>,
+    backlog: usize,
+    /// 监听者已关闭
+    closed: bool,
+}
+
+/// 监听套接字的连接队列
+struct AcceptQueue {
+    inner: UPSafeCell<AcceptQueueInner>,
+    /// 监听者的等待队列：新连接到达、连接被取走时唤醒
+    waiters: Arc<WaitQueue>,
+}
+
+/// 套接字的连接状态
+enum State {
+    /// 未连接；数据报套接字绑定后即可接收
+    Unconnected,
+    /// 正在监听的流式套接字
+    Listening(Arc<AcceptQueue>),
+    /// 已连接的流式套接字
+    Connected {
+        rx: Arc<StreamBuffer>,
+        tx: Arc<StreamBuffer>,
+    },
+    /// 设置了默认目的地址的数据报套接字
+    DgramConnected(Arc<DgramQueue>),
+}
+
+struct UnixSocketInner {
+    /// 绑定的路径，未绑定时为空
+    local: String,
+    /// 对端地址，未知或匿名时为空
+    peer: String,
+    state: State,
+}
+
+/// Unix 域套接字
+pub struct UnixSocket {
+    type_: SockType,
+    status: FileStatus,
+    inner: UPSafeCell<UnixSocketInner>,
+    /// 数据报套接字的接收队列，流式套接字不使用
+    datagrams: Arc<DgramQueue>,
+    waiters: Arc<WaitQueue>,
+    me: Weak<UnixSocket>,
+}
+
+impl UnixSocket {
+    /// 创建未绑定、未连接的套接字
+    pub fn new(type_: SockType, flags: OpenFlags) -> Arc<Self> {
+        Self::with_state(
+            type_,
+            flags,
+            String::new(),
+            String::new(),
+            State::Unconnected,
+            Arc::new(WaitQueue::new()),
+        )
+    }
+
+    fn with_state(
+        type_: SockType,
+        flags: OpenFlags,
+        local: String,
+        peer: String,
+        state: State,
+        waiters: Arc<WaitQueue>,
+    ) -> Arc<Self> {
+        Arc::new_cyclic(|me| Self {
+            type_,
+            status: FileStatus::new(flags | OpenFlags::RDWR),
+            inner: unsafe { UPSafeCell::new(UnixSocketInner { local, peer, state }) },
+            datagrams: Arc::new(DgramQueue {
+                inner: unsafe {
+                    UPSafeCell::new(DgramQueueInner {
+                        datagrams: VecDeque::new(),
+                        closed: false,
+                    })
+                },
+                waiters: waiters.clone(),
+            }),
+            waiters,
+            me: me.clone(),
+        })
+    }
+
+    fn nonblocking(&self, flags: u32) -> bool {
+        self.status.nonblocking() || flags & MSG_DONTWAIT != 0
+    }
+
+    fn accept_queue(&self) -> Option<Arc<AcceptQueue>> {
+        match &self.inner.exclusive_access().state {
+            State::Listening(queue) => Some(queue.clone()),
+            _ => None,
+        }
+    }
+
+    fn connect_stream(&self, path: String, nonblocking: bool) -> isize {
+        match self.inner.exclusive_access().state {
+            State::Unconnected => {}
+            State::Connected { .. } => return -EISCONN,
+            State::Listening(_) | State::DgramConnected(_) => return -EINVAL,
+        }
+        let listener = match lookup(&path) {
+            Ok(listener) => listener,
+            Err(errno) => return errno,
+        };
+        if listener.type_ != SockType::Stream {
+            return -EPROTOTYPE;
+        }
+        let Some(queue) = listener.accept_queue() else {
+            return -ECONNREFUSED;
+        };
+        drop(listener);
+        loop {
+            let inner = queue.inner.exclusive_access();
+            if inner.closed {
+                return -ECONNREFUSED;
+            }
+            if inner.pending.len() < inner.backlog {
+                break;
+            }
+            drop(inner);
+            if nonblocking {
+                return -EAGAIN;
+            }
+            if current_signal_pending() {
+                return -EINTR;
+            }
+            queue.waiters.wait();
+        }
+        let rx = StreamBuffer::new();
+        let tx = StreamBuffer::new();
+        let mut inner = self.inner.exclusive_access();
+        let server = Self::with_state(
+            SockType::Stream,
+            OpenFlags::empty(),
+            path.clone(),
+            inner.local.clone(),
+            State::Connected {
+                rx: tx.clone(),
+                tx: rx.clone(),
+            },
+            self.waiters.clone(),
+        );
+        inner.state = State::Connected { rx, tx };
+        inner.peer = path;
+        drop(inner);
+        queue.inner.exclusive_access().pending.push_back(server);
+        queue.waiters.wake_all();
+        0
+    }
+
+    fn connect_dgram(&self, path: String) -> isize {
+        if matches!(self.inner.exclusive_access().state, State::Listening(_)) {
+            return -EINVAL;
+        }
+        let target = match lookup(&path) {
+            Ok(target) => target,
+            Err(errno) => return errno,
+        };
+        if target.type_ != SockType::Dgram {
+            return -EPROTOTYPE;
+        }
+        let mut inner = self.inner.exclusive_access();
+        inner.state = State::DgramConnected(target.datagrams.clone());
+        inner.peer = path;
+        0
+    }
+
+    fn send_stream(&self, buf: UserBuffer, flags: u32) -> isize {
+        let tx = match &self.inner.exclusive_access().state {
+            State::Connected { tx, .. } => tx.clone(),
+            _ => return -ENOTCONN,
+        };
+        let nonblocking = self.nonblocking(flags);
+        let mut sent = 0usize;
+        for src in buf.buffers.iter() {
+            let mut offset = 0;
+            while offset < src.len() {
+                let mut inner = tx.inner.exclusive_access();
+                if inner.reader_closed {
+                    drop(inner);
+                    if flags & MSG_NOSIGNAL == 0 {
+                        current_send_signal(SignalFlags::SIGPIPE);
+                    }
+                    return partial(sent, -EPIPE);
+                }
+                let room = STREAM_CAPACITY - inner.data.len();
+                if room > 0 {
+                    let n = room.min(src.len() - offset);
+                    inner.data.extend(src[offset..offset + n].iter());
+                    offset += n;
+                    sent += n;
+                    drop(inner);
+                    self.waiters.wake_all();
+                    continue;
+                }
+                drop(inner);
+                if nonblocking {
+                    return partial(sent, -EAGAIN);
+                }
+                if current_signal_pending() {
+                    return partial(sent, -EINTR);
+                }
+                self.waiters.wait();
+            }
+        }
+        sent as isize
+    }
+
+    fn recv_stream(&self, buf: UserBuffer, flags: u32) -> Result<(usize, SockAddr), isize> {
+        let (rx, peer) = {
+            let inner = self.inner.exclusive_access();
+            match &inner.state {
+                State::Connected { rx, .. } => (rx.clone(), inner.peer.clone()),
+                State::Listening(_) => return Err(-EINVAL),
+                _ => return Err(-ENOTCONN),
+            }
+        };
+        if buf.len() == 0 {
+            return Ok((0, SockAddr::Unix(peer)));
+        }
+        loop {
+            let mut inner = rx.inner.exclusive_access();
+            if !inner.data.is_empty() {
+                let mut received = 0usize;
+                for dst in buf.buffers {
+                    let n = dst.len().min(inner.data.len());
+                    for (byte, data) in dst.iter_mut().zip(inner.data.drain(..n)) {
+                        *byte = data;
+                    }
+                    received += n;
+                    if n < dst.len() {
+                        break;
+                    }
+                }
+                drop(inner);
+                self.waiters.wake_all();
+                return Ok((received, SockAddr::Unix(peer)));
+            }
+            if inner.writer_closed {
+                return Ok((0, SockAddr::Unix(peer)));
+            }
+            drop(inner);
+            if self.nonblocking(flags) {
+                return Err(-EAGAIN);
+            }
+            if current_signal_pending() {
+                return Err(-EINTR);
+            }
+            self.waiters.wait();
+        }
+    }
+
+    fn send_dgram(&self, buf: UserBuffer, flags: u32, addr: Option<SockAddr>) -> isize {
+        let queue = match addr {
+            Some(SockAddr::Unix(path)) => match lookup(&path) {
+                Ok(target) if target.type_ == SockType::Dgram => target.datagrams.clone(),
+                Ok(_) => return -EPROTOTYPE,
+                Err(errno) => return errno,
+            },
+            Some(_) => return -EINVAL,
+            None => match &self.inner.exclusive_access().state {
+                State::DgramConnected(queue) => queue.clone(),
+                _ => return -ENOTCONN,
+            },
+        };
+        if buf.len() > DGRAM_MAX_SIZE {
+            return -EMSGSIZE;
+        }
+        let mut data = Vec::with_capacity(buf.len());
+        for src in buf.buffers.iter() {
+            data.extend_from_slice(src);
+        }
+        let from = self.inner.exclusive_access().local.clone();
+        queue.deliver(Datagram { data, from }, self.nonblocking(flags))
+    }
+
+    fn recv_dgram(&self, buf: UserBuffer, flags: u32) -> Result<(usize, SockAddr), isize> {
+        loop {
+            let mut inner = self.datagrams.inner.exclusive_access();
+            if let Some(datagram) = inner.datagrams.pop_front() {
+                drop(inner);
+                DGRAM_SPACE.wake_all();
+                let mut received = 0usize;
+                for dst in buf.buffers {
+                    let n = dst.len().min(datagram.data.len() - received);
+                    dst[..n].copy_from_slice(&datagram.data[received..received + n]);
+                    received += n;
+                }
+                return Ok((received, SockAddr::Unix(datagram.from)));
+            }
+            drop(inner);
+            if self.nonblocking(flags) {
+                return Err(-EAGAIN);
+            }
+            if current_signal_pending() {
+                return Err(-EINTR);
+            }
+            self.waiters.wait();
+        }
+    }
+}
+
+impl Socket for UnixSocket {
+    /// 创建套接字文件并登记；已绑定的套接字不能再次绑定
+    fn bind(&self, addr: SockAddr) -> isize {
+        let SockAddr::Unix(path) = addr else {
+            return -EINVAL;
+        };
+        if !self.inner.exclusive_access().local.is_empty() {
+            return -EINVAL;
+        }
+        let in_use = |inode_id: u32| {
+            SOCKET_TABLE
+                .exclusive_access()
+                .get(&inode_id)
+                .is_some_and(|socket| socket.strong_count() > 0)
+        };
+        let inode_id = match bind_socket_node(&path, in_use) {
+            Ok(inode_id) => inode_id,
+            Err(errno) => return errno,
+        };
+        let mut table = SOCKET_TABLE.exclusive_access();
+        table.retain(|_, socket| socket.strong_count() > 0);
+        table.insert(inode_id, self.me.clone());
+        drop(table);
+        self.inner.exclusive_access().local = path;
+        0
+    }
+
+    fn listen(&self, backlog: usize) -> isize {
+        if self.type_ != SockType::Stream {
+            return -EOPNOTSUPP;
+        }
+        let backlog = backlog.clamp(1, SOMAXCONN);
+        let mut inner = self.inner.exclusive_access();
+        if inner.local.is_empty() {
+            return -EINVAL;
+        }
+        if let State::Listening(queue) = &inner.state {
+            queue.inner.exclusive_access().backlog = backlog;
+            return 0;
+        }
+        if !matches!(inner.state, State::Unconnected) {
+            return -EINVAL;
+        }
+        inner.state = State::Listening(Arc::new(AcceptQueue {
+            inner: unsafe {
+                UPSafeCell::new(AcceptQueueInner {
+                    pending: VecDeque::new(),
+                    backlog,
+                    closed: false,
+                })
+            },
+            waiters: self.waiters.clone(),
+        }));
+        0
+    }
+
+    fn accept(&self) -> Result<(Arc<dyn File + Send + Sync>, SockAddr), isize> {
+        let Some(queue) = self.accept_queue() else {
+            return Err(-EINVAL);
+        };
+        loop {
+            let server = queue.inner.exclusive_access().pending.pop_front();
+            if let Some(server) = server {
+                // 腾出的位置可以让等待的 `connect` 继续
+                self.waiters.wake_all();
+                let peer = server.inner.exclusive_access().peer.clone();
+                return Ok((server as Arc<dyn File + Send + Sync>, SockAddr::Unix(peer)));
+            }
+            if self.status.nonblocking() {
+                return Err(-EAGAIN);
+            }
+            if current_signal_pending() {
+                return Err(-EINTR);
+            }
+            self.waiters.wait();
+        }
+    }
+
+    fn connect(&self, addr: SockAddr) -> isize {
+        let SockAddr::Unix(path) = addr else {
+            return -EINVAL;
+        };
+        match self.type_ {
+            SockType::Stream => self.connect_stream(path, self.status.nonblocking()),
+            SockType::Dgram => self.connect_dgram(path),
+        }
+    }
+
+    fn sendto(&self, buf: UserBuffer, flags: u32, addr: Option<SockAddr>) -> isize {
+        match self.type_ {
+            SockType::Stream if addr.is_some() => match self.inner.exclusive_access().state {
+                State::Connected { .. } => -EISCONN,
+                _ => -EOPNOTSUPP,
+            },
+            SockType::Stream => self.send_stream(buf, flags),
+            SockType::Dgram => self.send_dgram(buf, flags, addr),
+        }
+    }
+
+    fn recvfrom(&self, buf: UserBuffer, flags: u32) -> Result<(usize, SockAddr), isize> {
+        match self.type_ {
+            SockType::Stream => self.recv_stream(buf, flags),
+            SockType::Dgram => self.recv_dgram(buf, flags),
+        }
+    }
+}
+
+impl File for UnixSocket {
+    fn read(&self, buf: UserBuffer) -> isize {
+        match self.recvfrom(buf, 0) {
+            Ok((len, _)) => len as isize,
+            Err(errno) => errno,
+        }
+    }
+
+    fn write(&self, buf: UserBuffer) -> isize {
+        self.sendto(buf, 0, None)
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn fcntl(&self, cmd: usize, arg: usize) -> isize {
+        self.status.fcntl(cmd, arg, true, true)
+    }
+
+    /// 监听者有连接等待 `accept` 时可读；已连接的流式套接字与管道相同，
+    /// 对端关闭后挂断；数据报套接字有数据报时可读，目的队列有空间时可写
+    fn poll(&self) -> PollEvents {
+        let inner = self.inner.exclusive_access();
+        let mut events = PollEvents::empty();
+        match &inner.state {
+            State::Listening(queue) => {
+                if !queue.inner.exclusive_access().pending.is_empty() {
+                    events |= PollEvents::POLLIN;
+                }
+            }
+            State::Connected { rx, tx } => {
+                let rx = rx.inner.exclusive_access();
+                if !rx.data.is_empty() || rx.writer_closed {
+                    events |= PollEvents::POLLIN;
+                }
+                if rx.writer_closed {
+                    events |= PollEvents::POLLHUP;
+                }
+                let tx = tx.inner.exclusive_access();
+                if tx.reader_closed {
+                    events |= PollEvents::POLLERR;
+                } else if tx.data.len() < STREAM_CAPACITY {
+                    events |= PollEvents::POLLOUT;
+                }
+            }
+            State::DgramConnected(queue) => {
+                let queue = queue.inner.exclusive_access();
+                if queue.closed {
+                    events |= PollEvents::POLLERR;
+                } else if queue.datagrams.len() < DGRAM_QUEUE_LEN {
+                    events |= PollEvents::POLLOUT;
+                }
+            }
+            State::Unconnected => {
+                if self.type_ == SockType::Dgram {
+                    events |= PollEvents::POLLOUT;
+                }
+            }
+        }
+        if self.type_ == SockType::Dgram
+            && !self.datagrams.inner.exclusive_access().datagrams.is_empty()
+        {
+            events |= PollEvents::POLLIN;
+        }
+        events
+    }
+
+    fn poll_wait<'a>(&'a self, table: &mut PollTable<'a>) {
+        table.add(&self.waiters);
+        if self.type_ == SockType::Dgram {
+            table.add(&DGRAM_SPACE);
+        }
+    }
+
+    fn as_socket(&self) -> Option<&dyn Socket> {
+        Some(self)
+    }
+}
+
+impl Drop for UnixSocket {
+    /// 关闭连接的两个方向、拒绝等待中的连接，并唤醒受影响的等待者
+    fn drop(&mut self) {
+        let state =
+            core::mem::replace(&mut self.inner.exclusive_access().state, State::Unconnected);
+        match state {
+            State::Connected { rx, tx } => {
+                rx.inner.exclusive_access().reader_closed = true;
+                tx.inner.exclusive_access().writer_closed = true;
+            }
+            State::Listening(queue) => {
+                let mut inner = queue.inner.exclusive_access();
+                inner.closed = true;
+                let pending = core::mem::take(&mut inner.pending);
+                drop(inner);
+                // 未被取走的连接随之关闭，客户端读到 EOF
+                drop(pending);
+            }
+            State::Unconnected | State::DgramConnected(_) => {}
+        }
+        let mut datagrams = self.datagrams.inner.exclusive_access();
+        datagrams.closed = true;
+        datagrams.datagrams.clear();
+        drop(datagrams);
+        DGRAM_SPACE.wake_all();
+        self.waiters.wake_all();
+    }
+}
@@ -0,0 +1,214 @@
+//! # 核心转储
+//!
+//! 进程因默认动作为“终止并转储”的信号（`SIGSEGV`、`SIGILL`、`SIGABRT` 等）
+//! 退出时，把它的寄存器与用户内存写入根目录下的 `core.<pid>`，格式为 ELF 核心文件：
+//!
+//! ```text
+//! ELF 文件头 | 程序头表 | PT_NOTE 内容 | 填充到页边界 | PT_LOAD 段 ...
+//! ```
+//!
+//! - `PT_NOTE` 中是一条 `NT_PRSTATUS`，布局与 Linux riscv64 的 `elf_prstatus` 一致，
+//!   其中 `pr_reg` 依次为 `pc`、`x1` ~ `x31`（取自保存的 Trap 上下文）
+//! - 每个带 `U` 权限的内存区域对应一个 `PT_LOAD` 段，内容按页写出，未映射的页写零
+//!
+//! 在主机上用 `micro-fs-fuse -x core.<pid>` 从磁盘镜像中取出后，可以交给
+//! `readelf`、`gdb` 等工具查看。
+
+use super::{SignalDefaultAction, SignalFlags, current_process};
+use crate::config::PAGE_SIZE;
+use crate::fs::{OpenFlags, open_file, write_kernel_bytes};
+use crate::mm::{ELF_MAGIC, MapPermission, PHENT_SIZE, PageTable, VPNRange};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+const ET_CORE: u64 = 4;
+const EM_RISCV: u64 = 243;
+const PT_LOAD: u64 = 1;
+const PT_NOTE: u64 = 4;
+const PF_X: u64 = 1;
+const PF_W: u64 = 2;
+const PF_R: u64 = 4;
+const NT_PRSTATUS: u64 = 1;
+
+/// ELF 文件头大小
+const EHDR_SIZE: usize = 64;
+/// `elf_prstatus` 的大小
+const PRSTATUS_SIZE: usize = 376;
+/// `elf_prstatus` 中 `pr_reg` 的偏移
+const PRSTATUS_REG_OFFSET: usize = 112;
+
+/// 生成转储所需的进程状态，在持有进程锁时一次取出
+struct CoreInfo {
+    pid: usize,
+    ppid: usize,
+    signum: usize,
+    pending: u64,
+    blocked: u64,
+    regs: [usize; 32],
+    token: usize,
+    areas: Vec<(VPNRange, MapPermission)>,
+}
+
+/// 为当前进程生成核心转储
+///
+/// ## Arguments
+///
+/// * `signum` - 导致进程终止的信号
+///
+/// ## Returns
+///
+/// 写出的文件名；信号的默认动作不是转储、文件无法创建或未能完整写入时返回 `None`
+pub fn current_core_dump(signum: usize) -> Option<String> {
+    let signal = SignalFlags::from_signum(signum)?;
+    if signal.default_action() != SignalDefaultAction::Core {
+        return None;
+    }
+    let info = {
+        let process = current_process()?;
+        let inner = process.inner_exclusive_access();
+        let cx = inner.trap_cx();
+        let mut regs = cx.x;
+        regs[0] = cx.sepc;
+        CoreInfo {
+            pid: process.getpid(),
+            ppid: inner
+                .parent
+                .as_ref()
+                .and_then(|parent| parent.upgrade())
+                .map_or(0, |parent| parent.getpid()),
+            signum,
+            pending: inner.signals.bits(),
+            blocked: inner.signal_mask.bits(),
+            regs,
+            token: inner.memory_set.token(),
+            areas: inner.memory_set.user_areas(),
+        }
+    };
+    // 写文件可能睡眠，此时不能持有进程锁
+    let name = format!("core.{}", info.pid);
+    let file = open_file(
+        &name,
+        OpenFlags::CREATE | OpenFlags::TRUNC | OpenFlags::WRONLY,
+    )?;
+    if !write_kernel_bytes(&*file, &mut core_header(&info)) {
+        return None;
+    }
+    let page_table = PageTable::from_token(info.token);
+    let mut zero_page = vec![0u8; PAGE_SIZE];
+    for (range, _) in info.areas.iter() {
+        for vpn in *range {
+            let written = match page_table.translate(vpn) {
+                Some(pte) if pte.is_valid() => write_kernel_bytes(&*file, pte.ppn().bytes_array()),
+                _ => write_kernel_bytes(&*file, &mut zero_page),
+            };
+            if !written {
+                return None;
+            }
+        }
+    }
+    Some(name)
+}
+
+/// 以小端序追加 `value` 的低 `size` 字节
+fn put(out: &mut Vec<u8>, value: u64, size: usize) {
+    out.extend_from_slice(&value.to_le_bytes()[..size]);
+}
+
+/// 追加一个 `Elf64_Phdr`
+fn put_phdr(out: &mut Vec<u8>, p_type: u64, flags: u64, offset: usize, vaddr: usize, size: usize) {
+    put(out, p_type, 4);
+    put(out, flags, 4);
+    put(out, offset as u64, 8);
+    put(out, vaddr as u64, 8);
+    put(out, 0, 8);
+    put(out, size as u64, 8);
+    put(out, size as u64, 8);
+    let align = if p_type == PT_LOAD { PAGE_SIZE } else { 4 };
+    put(out, align as u64, 8);
+}
+
+/// ELF 文件头、程序头表与 `PT_NOTE` 内容，填充到页边界
+fn core_header(info: &CoreInfo) -> Vec<u8> {
+    let phnum = info.areas.len() + 1;
+    let note_offset = EHDR_SIZE + phnum * PHENT_SIZE;
+    let note = prstatus_note(info);
+    let data_offset = (note_offset + note.len()).next_multiple_of(PAGE_SIZE);
+
+    let mut out = Vec::with_capacity(data_offset);
+    out.extend_from_slice(&ELF_MAGIC);
+    // ELFCLASS64、ELFDATA2LSB、EV_CURRENT、ELFOSABI_SYSV
+    out.extend_from_slice(&[2, 1, 1, 0]);
+    out.resize(16, 0);
+    put(&mut out, ET_CORE, 2);
+    put(&mut out, EM_RISCV, 2);
+    put(&mut out, 1, 4);
+    put(&mut out, 0, 8);
+    put(&mut out, EHDR_SIZE as u64, 8);
+    put(&mut out, 0, 8);
+    put(&mut out, 0, 4);
+    put(&mut out, EHDR_SIZE as u64, 2);
+    put(&mut out, PHENT_SIZE as u64, 2);
+    put(&mut out, phnum as u64, 2);
+    put(&mut out, 0, 6);
+
+    put_phdr(&mut out, PT_NOTE, 0, note_offset, 0, note.len());
+    let mut offset = data_offset;
+    for (range, perm) in info.areas.iter() {
+        let size = (range.end().0 - range.start().0) * PAGE_SIZE;
+        let mut flags = 0;
+        if perm.contains(MapPermission::R) {
+            flags |= PF_R;
+        }
+        if perm.contains(MapPermission::W) {
+            flags |= PF_W;
+        }
+        if perm.contains(MapPermission::X) {
+            flags |= PF_X;
+        }
+        put_phdr(
+            &mut out,
+            PT_LOAD,
+            flags,
+            offset,
+            range.start().0 * PAGE_SIZE,
+            size,
+        );
+        offset += size;
+    }
+    out.extend_from_slice(&note);
+    out.resize(data_offset, 0);
+    out
+}
+
+/// 名为 `CORE` 的 `NT_PRSTATUS` 记录
+fn prstatus_note(info: &CoreInfo) -> Vec<u8> {
+    let mut desc = Vec::with_capacity(PRSTATUS_SIZE);
+    // pr_info.si_signo、si_code、si_errno
+    put(&mut desc, info.signum as u64, 4);
+    put(&mut desc, 0, 8);
+    // pr_cursig 及填充
+    put(&mut desc, info.signum as u64, 2);
+    put(&mut desc, 0, 2);
+    put(&mut desc, info.pending, 8);
+    put(&mut desc, info.blocked, 8);
+    // pr_pid、pr_ppid
+    put(&mut desc, info.pid as u64, 4);
+    put(&mut desc, info.ppid as u64, 4);
+    // pr_pgrp、pr_sid，以及 pr_utime 等四个时间不统计
+    desc.resize(PRSTATUS_REG_OFFSET, 0);
+    for reg in info.regs {
+        put(&mut desc, reg as u64, 8);
+    }
+    // pr_fpvalid 及填充
+    desc.resize(PRSTATUS_SIZE, 0);
+
+    let mut note = Vec::with_capacity(20 + PRSTATUS_SIZE);
+    put(&mut note, 5, 4);
+    put(&mut note, PRSTATUS_SIZE as u64, 4);
+    put(&mut note, NT_PRSTATUS, 4);
+    note.extend_from_slice(b"CORE\0\0\0\0");
+    note.extend_from_slice(&desc);
+    note
+}
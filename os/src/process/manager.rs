@@ -550,6 +550,16 @@ pub fn pid2process(pid: usize) -> Option<Arc<ProcessControlBlock>> {
     map.get(&pid).map(Arc::clone)
 }
 
+/// 当前存活（尚未退出）的进程数
+///
+/// 用于在 fork 时执行 `RLIMIT_NPROC`。
+///
+/// ## 返回
+/// - `PID2PCB` 中登记的进程数量
+pub fn process_count() -> usize {
+    PID2PCB.exclusive_access().len()
+}
+
 /// 从全局 PID → PCB 映射中移除进程
 ///
 /// 典型调用点：
@@ -6,18 +6,21 @@
 //! ## 模块组织
 //!
 //! - [`context`]   - 进程上下文 `ProcessContext` 的保存与恢复
+//! - [`coredump`]  - 因转储类信号终止时写出 ELF 核心文件
 //! - [`manager`]   - 就绪队列管理与基本调度（FIFO）
 //! - [`pid`]       - 进程 ID 分配与回收、内核栈管理
 //! - [`processor`] - 当前处理器状态、当前进程获取、调度入口
 //! - [`switch`]    - 低层上下文切换实现（汇编封装）
 //! - [`process`]      - 进程控制块 `ProcessControlBlock` 及其内部结构
+//! - [`rlimit`]    - 进程资源限制表 `RLimits`（getrlimit/setrlimit）
 //!
 //! ## 公开接口（re-exports）
 //!
 //! - 类型：[`ProcessContext`]
 //! - 函数：[`add_process`], [`run_process`], [`schedule`], [`current_process`],
 //!   [`current_trap_cx`], [`current_user_token`], [`take_current_process`],
-//!   [`add_initproc`], [`suspend_current_and_run_next`], [`exit_current_and_run_next`]
+//!   [`add_initproc`], [`suspend_current_and_run_next`], [`exit_current_and_run_next`],
+//!   [`block_current_and_run_next`], [`wakeup_process`]
 //! - 常量：[`IDLE_PID`], [`INITPROC`]
 //!
 //! ## 调度模型
@@ -34,14 +37,21 @@
 //!   - `signal_actions`：用户自定义处理动作表
 //! - 处理流程要点：
 //!   1. 进入内核后在合适时机检查 `signals` 与 `signal_mask`
-//!   2. 对于致命信号，转换为退出码（如 SIGSEGV=-11 等）
+//!   2. 处理函数为 `SIG_DFL` 时执行默认动作（终止/忽略/停止/继续/转储），
+//!      终止类信号转换为退出码（如 SIGSEGV=-11 等）；`SIG_IGN` 直接丢弃
 //!   3. 对于可捕捉信号，按 `signal_actions` 进入用户处理程序，返回后 `sigreturn`
-//! - 相关对外接口：[`check_signals_error_of_current`], [`current_add_signal`]
+//!   4. 实时信号按发送顺序排队，每次投递一个并通过 `a1` 传递附带数据
+//! - 相关对外接口：[`check_signals_error_of_current`], [`current_add_signal`],
+//!   [`current_signal_pending`], [`signal_process`]
+//! - 向其他进程发送信号应使用 [`signal_process`]：它会唤醒阻塞在可中断等待中的目标，
+//!   目标在等待循环中发现待决信号后返回 `EINTR`
+//! - 被跟踪的进程（见 [`PtraceState`]）收到除 `SIGKILL` 以外的信号时进入跟踪停止，
+//!   由父进程决定是否投递
 //!
 //! ## 与系统调用的协作
 //!
 //! - 进程创建：[`sys_fork`] 深拷贝地址空间并返回父/子不同返回值
-//! - 进程替换：[`sys_exec`] 用新 ELF 重建地址空间（成功不返回）
+//! - 进程替换：[`sys_execve`] 用新 ELF 重建地址空间并传入 argv/envp/auxv（成功不返回）
 //! - 进程回收：[`sys_waitpid`] 回收子进程并写回退出码
 //! - 让出 CPU：[`sys_yield`] 通过 [`suspend_current_and_run_next`]
 //! - 退出：[`sys_exit`] 通过 [`exit_current_and_run_next`]
@@ -71,26 +81,37 @@ use crate::fs::{OpenFlags, open_file};
 use crate::{println, sbi::shutdown};
 use alloc::sync::Arc;
 use lazy_static::*;
-use process::{ProcessControlBlock, ProcessStatus};
+use process::ProcessStatus;
 
 mod context;
+mod coredump;
 mod manager;
 mod pid;
 #[allow(clippy::module_inception)]
 mod process;
 mod processor;
+mod ptrace;
+mod rlimit;
 mod signal;
 mod switch;
 
 pub use context::ProcessContext;
+pub use coredump::current_core_dump;
 pub use manager::{
-    add_process, add_process_with_priority, get_time_slice, pid2process, remove_from_pid2process,
+    add_process, add_process_with_priority, get_time_slice, pid2process, process_count,
+    remove_from_pid2process,
 };
+pub use process::{ExecError, ProcessControlBlock};
 pub use processor::{
     current_process, current_trap_cx, current_user_token, run_process, schedule,
     take_current_process,
 };
-pub use signal::{MAX_SIG, SignalAction, SignalActions, SignalFlags};
+pub use ptrace::PtraceState;
+pub use rlimit::{RLIM_INFINITY, RLIM_NLIMITS, RLIMIT_FSIZE, RLIMIT_NOFILE, RLIMIT_NPROC, RLimit};
+pub use signal::{
+    MAX_QUEUED_SIGNALS, MAX_SIG, SIG_DFL, SIG_IGN, SIGRTMIN, SignalAction, SignalActions,
+    SignalDefaultAction, SignalFlags, SignalInfo,
+};
 
 lazy_static! {
     /// 初始进程（initproc）
@@ -131,6 +152,45 @@ pub fn suspend_current_and_run_next() {
     schedule(process_cx_ptr);
 }
 
+/// 阻塞当前进程并切换到下一个进程
+///
+/// 当前进程被标记为 [`ProcessStatus::Blocked`] 且不会放回就绪队列，
+/// 调用者必须事先把它登记到某个等待队列中，否则它将永远无法被唤醒。
+pub fn block_current_and_run_next() {
+    let process = take_current_process().unwrap();
+    let mut process_inner = process.inner_exclusive_access();
+    let process_cx_ptr = &mut process_inner.process_cx as *mut ProcessContext;
+    process_inner.process_status = ProcessStatus::Blocked;
+    drop(process_inner);
+    drop(process);
+    schedule(process_cx_ptr);
+}
+
+/// 唤醒一个被阻塞的进程
+///
+/// 将进程状态改回就绪，并按其当前优先级放回 MLFQ 就绪队列。
+/// 同一进程可能登记在多个等待队列或定时器上，只有第一次唤醒生效。
+///
+/// ## Arguments
+///
+/// * `process` - 由等待队列取出的被阻塞进程
+///
+/// ## Returns
+///
+/// 进程原本处于阻塞态并被唤醒时返回 `true`
+pub fn wakeup_process(process: Arc<ProcessControlBlock>) -> bool {
+    let priority = {
+        let mut process_inner = process.inner_exclusive_access();
+        if process_inner.process_status != ProcessStatus::Blocked {
+            return false;
+        }
+        process_inner.process_status = ProcessStatus::Ready;
+        process_inner.priority
+    };
+    add_process_with_priority(process, priority);
+    true
+}
+
 /// 空闲进程的 PID
 ///
 /// 值为 0 的特殊 PID，用于标识系统中的空闲进程。当空闲进程退出时，
@@ -156,6 +216,7 @@ pub const IDLE_PID: usize = 0;
 /// ## 备注
 /// - 子进程在被重新托管后，退出回收将由 `initproc` 负责
 /// - 地址空间的底层页帧由 RAII 管理，进程生命周期结束时被回收
+/// - 打开的文件在退出时立即关闭
 pub fn exit_current_and_run_next(exit_code: i32) {
     let process = take_current_process().unwrap();
 
@@ -180,13 +241,28 @@ pub fn exit_current_and_run_next(exit_code: i32) {
     {
         let mut initproc_inner = INITPROC.inner_exclusive_access();
         for child in inner.children.iter() {
-            child.inner_exclusive_access().parent = Some(Arc::downgrade(&INITPROC));
+            let mut child_inner = child.inner_exclusive_access();
+            child_inner.parent = Some(Arc::downgrade(&INITPROC));
+            // 跟踪者总是父进程，父进程退出时子进程脱离跟踪
+            child_inner.ptrace_detach();
+            drop(child_inner);
             initproc_inner.children.push(child.clone());
         }
     }
     inner.children.clear();
     inner.memory_set.recycle_data_pages();
+    // 立即关闭打开的文件，让管道对端不必等到回收僵尸进程才看到 EOF 或 EPIPE
+    let fd_table = core::mem::take(&mut inner.fd_table);
+    inner.fd_cloexec.clear();
+    let trace = inner.trace.take();
+    // 同样立即关闭 IPC 端点，并让等待应答的调用者失败
+    let ipc_table = core::mem::take(&mut inner.ipc_table);
+    let ipc_reply = inner.ipc_reply.take();
     drop(inner);
+    drop(fd_table);
+    drop(trace);
+    drop(ipc_table);
+    drop(ipc_reply);
     drop(process);
     let mut _unused = ProcessContext::zero_init();
     schedule(&mut _unused as *mut _);
@@ -194,13 +270,13 @@ pub fn exit_current_and_run_next(exit_code: i32) {
 
 /// 检查当前进程的致命信号并返回标准退出码与原因
 ///
-/// - 当 `signals` 集合包含致命/错误类信号（如 SIGSEGV、SIGILL 等）时，
-///   返回对应的 `(exit_code, reason)`；否则返回 `None`。
+/// - 当信号阶段已判定进程被终止（`killed=true`）时，返回待决集合中
+///   终止类信号对应的 `(exit_code, reason)`；否则返回 `None`。
 /// - 该函数仅做快速判定，不会修改进程状态或触发调度。
 pub fn check_signals_error_of_current() -> Option<(i32, &'static str)> {
     let process = current_process().unwrap();
     let process_inner = process.inner_exclusive_access();
-    if !process_inner.signals.is_empty() {
+    if process_inner.killed {
         process_inner.signals.check_error()
     } else {
         None
@@ -209,33 +285,122 @@ pub fn check_signals_error_of_current() -> Option<(i32, &'static str)> {
 
 /// 向当前进程投递一个信号
 ///
+/// - 用于陷阱同步产生的信号（如 SIGSEGV、SIGILL）：若该信号被屏蔽或被忽略，
+///   则解除屏蔽并恢复默认动作，避免进程在出错指令上无限重复陷入。
 /// - 将 `signal` 置入当前进程的 `signals` 集合，后续由调度路径调用
 ///   [`handle_signals`] 进行处理。
 pub fn current_add_signal(signal: SignalFlags) {
     let process = current_process().unwrap();
     let mut process_inner = process.inner_exclusive_access();
-    process_inner.signals |= signal;
+    let signum = signal.lowest_signum().unwrap();
+    if process_inner.signal_mask.contains(signal)
+        || process_inner.signal_actions.table[signum].handler == SIG_IGN
+    {
+        process_inner.signal_mask.remove(signal);
+        process_inner.signal_actions.table[signum] = SignalAction::default();
+    }
+    process_inner.send_signal(signum, 0);
+}
+
+/// 向当前进程发送一个普通（异步）信号
+///
+/// - 与 [`current_add_signal`] 不同，不会强制解除屏蔽或恢复默认动作，
+///   用于资源限制等内核事件（如 `SIGXFSZ`）。
+pub fn current_send_signal(signal: SignalFlags) {
+    let process = current_process().unwrap();
+    let mut process_inner = process.inner_exclusive_access();
+    process_inner.send_signal(signal.lowest_signum().unwrap(), 0);
 }
 
-/// 处理内核级信号的默认动作
+/// 向指定进程发送信号，并唤醒阻塞中的目标
 ///
-/// - 支持内建处理：SIGSTOP（冻结）、SIGCONT（解冻）、其他视为 `killed=true`
+/// 信号未被屏蔽时（`SIGKILL` 与 `SIGCONT` 总是如此），把阻塞在等待队列上的目标
+/// 放回就绪队列；它在等待循环中由 [`current_signal_pending`] 发现信号并返回 `EINTR`。
+/// 被忽略的信号只会造成一次虚假唤醒，等待者会重新进入等待。
+///
+/// ## Arguments
+///
+/// * `process` - 目标进程
+/// * `signum` - 信号编号（1..=MAX_SIG）
+/// * `value` - 附带数据，仅实时信号会保留
+///
+/// ## Returns
+///
+/// 投递成功返回 `true`；编号非法或实时信号队列已满时返回 `false`
+pub fn signal_process(process: &Arc<ProcessControlBlock>, signum: usize, value: usize) -> bool {
+    let wake = {
+        let mut inner = process.inner_exclusive_access();
+        if !inner.send_signal(signum, value) {
+            return false;
+        }
+        let signal = SignalFlags::from_signum(signum).unwrap();
+        signal.intersects(SignalFlags::SIGKILL | SignalFlags::SIGCONT)
+            || !inner.signal_mask.contains(signal)
+    };
+    if wake {
+        wakeup_process(process.clone());
+    }
+    true
+}
+
+/// 当前进程是否有会打断阻塞等待的待决信号
+///
+/// - 用于可中断的睡眠（如读终端）：等待者被唤醒后据此决定是否提前返回，
+///   让陷阱返回路径处理信号。
+/// - 被屏蔽、被忽略、默认动作为忽略或停止的信号不计入。
+pub fn current_signal_pending() -> bool {
+    let process = current_process().unwrap();
+    let process_inner = process.inner_exclusive_access();
+    let pending = process_inner.signals - process_inner.signal_mask;
+    (0..(MAX_SIG + 1)).any(|sig| {
+        let signal = SignalFlags::from_signum(sig).unwrap();
+        if !pending.contains(signal) {
+            return false;
+        }
+        if signal == SignalFlags::SIGKILL {
+            return true;
+        }
+        match process_inner.signal_actions.table[sig].handler {
+            SIG_IGN => false,
+            SIG_DFL => matches!(
+                signal.default_action(),
+                SignalDefaultAction::Terminate | SignalDefaultAction::Core
+            ),
+            _ => true,
+        }
+    })
+}
+
+/// 查询当前进程某项资源的软限制
+///
+/// - 没有当前进程（如内核初始化阶段）时视为不限制，返回 [`RLIM_INFINITY`]。
+pub fn current_rlimit(resource: usize) -> usize {
+    current_process().map_or(RLIM_INFINITY, |process| {
+        process.inner_exclusive_access().rlimits.cur(resource)
+    })
+}
+
+/// 执行信号的默认动作
+///
+/// - 停止类：冻结进程（`frozen=true`）；继续类：解除冻结；忽略类：直接丢弃
+/// - 终止/转储类：保留待决位并置 `killed=true`，由陷阱返回前统一退出
 /// - 仅修改内核维护的进程状态，不切换地址空间
-fn call_kernel_signal_handler(signal: SignalFlags) {
+fn call_kernel_signal_handler(sig: usize, signal: SignalFlags) {
     let process = current_process().unwrap();
     let mut process_inner = process.inner_exclusive_access();
-    match signal {
-        SignalFlags::SIGSTOP => {
+    match signal.default_action() {
+        SignalDefaultAction::Stop => {
             process_inner.frozen = true;
-            process_inner.signals ^= SignalFlags::SIGSTOP;
+            process_inner.take_signal(sig);
         }
-        SignalFlags::SIGCONT => {
-            if process_inner.signals.contains(SignalFlags::SIGCONT) {
-                process_inner.signals ^= SignalFlags::SIGCONT;
-                process_inner.frozen = false;
-            }
+        SignalDefaultAction::Continue => {
+            process_inner.frozen = false;
+            process_inner.take_signal(sig);
+        }
+        SignalDefaultAction::Ignore => {
+            process_inner.take_signal(sig);
         }
-        _ => {
+        SignalDefaultAction::Terminate | SignalDefaultAction::Core => {
             process_inner.killed = true;
         }
     }
@@ -243,38 +408,38 @@ fn call_kernel_signal_handler(signal: SignalFlags) {
 
 /// 进入用户态信号处理程序
 ///
-/// - 备份 Trap 上下文，设置 `sepc=handler`，`a0=sig`
-/// - 标记 `handling_sig=sig`，并从 `signals` 中清除此信号位
-fn call_user_signal_handler(sig: usize, signal: SignalFlags) {
+/// - 备份 Trap 上下文，设置 `sepc=handler`，`a0=sig`，`a1=附带数据`
+/// - 标记 `handling_sig=sig`，并取出此信号（实时信号出队一项）
+fn call_user_signal_handler(sig: usize) {
     let process = current_process().unwrap();
     let mut process_inner = process.inner_exclusive_access();
 
     let handler = process_inner.signal_actions.table[sig].handler;
-    if handler != 0 {
-        process_inner.handling_sig = sig as isize;
-        process_inner.signals ^= signal;
+    process_inner.handling_sig = sig as isize;
+    let value = process_inner.take_signal(sig);
 
-        let trap_ctx = process_inner.trap_cx();
-        process_inner.trap_ctx_backup = Some(*trap_ctx);
+    let trap_ctx = process_inner.trap_cx();
+    process_inner.trap_ctx_backup = Some(*trap_ctx);
 
-        trap_ctx.sepc = handler;
+    trap_ctx.sepc = handler;
 
-        trap_ctx.x[10] = sig;
-    } else {
-        println!("[K] process/call_user_signal_handler: default action: ignore it or kill process");
-    }
+    trap_ctx.x[10] = sig;
+    trap_ctx.x[11] = value;
 }
 
 /// 扫描并处理一个可处理的待决信号
 ///
 /// - 遍历 `0..=MAX_SIG`，考虑 `signal_mask` 与当前处理中的掩码规则
-/// - 命中后调用 `call_kernel_signal_handler` 或 `call_user_signal_handler`
-/// - 只处理至多一个信号，返回后由上层循环决定是否继续
+/// - 被跟踪的进程收到除 `SIGKILL` 以外的信号时取出信号并进入跟踪停止，
+///   父进程用 `PTRACE_CONT` 指定的信号再次到达时才正常投递
+/// - `SIGKILL`/`SIGSTOP` 与处理函数为 `SIG_DFL` 的信号执行默认动作，
+///   `SIG_IGN` 直接丢弃，其余进入用户处理程序
+/// - 进入用户处理程序或判定进程终止后立即返回，由上层循环决定是否继续
 fn check_pending_signals() {
     for sig in 0..(MAX_SIG + 1) {
         let process = current_process().unwrap();
-        let process_inner = process.inner_exclusive_access();
-        let signal = SignalFlags::from_bits(1 << sig).unwrap();
+        let mut process_inner = process.inner_exclusive_access();
+        let signal = SignalFlags::from_signum(sig).unwrap();
         if process_inner.signals.contains(signal) && (!process_inner.signal_mask.contains(signal)) {
             let mut masked = true;
             let handling_sig = process_inner.handling_sig;
@@ -290,16 +455,41 @@ fn check_pending_signals() {
                 }
             }
             if !masked {
-                drop(process_inner);
-                drop(process);
+                if signal != SignalFlags::SIGKILL {
+                    if let Some(ptrace) = process_inner.ptrace.as_mut() {
+                        // 跟踪停止期间只处理 SIGKILL
+                        if ptrace.is_stopped() {
+                            continue;
+                        }
+                        if ptrace.inject_sig == Some(sig) {
+                            ptrace.inject_sig = None;
+                        } else {
+                            let token = process_inner.memory_set.token();
+                            process_inner.take_signal(sig);
+                            process_inner.ptrace.as_mut().unwrap().stop(token, sig);
+                            return;
+                        }
+                    }
+                }
+                let handler = process_inner.signal_actions.table[sig].handler;
                 if signal == SignalFlags::SIGKILL
                     || signal == SignalFlags::SIGSTOP
-                    || signal == SignalFlags::SIGCONT
-                    || signal == SignalFlags::SIGDEF
+                    || handler == SIG_DFL
                 {
-                    call_kernel_signal_handler(signal);
+                    drop(process_inner);
+                    drop(process);
+                    call_kernel_signal_handler(sig, signal);
+                    if signal.default_action() == SignalDefaultAction::Terminate
+                        || signal.default_action() == SignalDefaultAction::Core
+                    {
+                        return;
+                    }
+                } else if handler == SIG_IGN {
+                    process_inner.take_signal(sig);
                 } else {
-                    call_user_signal_handler(sig, signal);
+                    drop(process_inner);
+                    drop(process);
+                    call_user_signal_handler(sig);
                     return;
                 }
             }
@@ -309,17 +499,22 @@ fn check_pending_signals() {
 
 /// 处理当前进程的待决信号直至状态可继续执行
 ///
-/// - 循环处理待决信号；若被冻结（SIGSTOP）则持续让出 CPU，直至 SIGCONT 或被 kill
+/// - 循环处理待决信号；若被冻结（SIGSTOP）或处于跟踪停止则持续让出 CPU，
+///   直至 SIGCONT、父进程恢复执行或被 kill
 /// - 若 `killed=true` 则结束循环，交由上层采取后续动作（如退出）
 pub fn handle_signals() {
     loop {
         check_pending_signals();
-        let (frozen, killed) = {
+        let (stopped, killed) = {
             let process = current_process().unwrap();
             let process_inner = process.inner_exclusive_access();
-            (process_inner.frozen, process_inner.killed)
+            let traced_stop = process_inner
+                .ptrace
+                .as_ref()
+                .is_some_and(PtraceState::is_stopped);
+            (process_inner.frozen || traced_stop, process_inner.killed)
         };
-        if !frozen || killed {
+        if !stopped || killed {
             break;
         }
         suspend_current_and_run_next();
@@ -131,22 +131,69 @@
 //! println!("Process status: {:?}", inner.process_status);
 //! ```
 
-use super::{ProcessContext, SignalActions, SignalFlags};
+use super::rlimit::{RLIMIT_AS, RLIMIT_CPU, RLIMIT_NOFILE, RLIMIT_STACK, RLimits};
+use super::{
+    MAX_QUEUED_SIGNALS, ProcessContext, PtraceState, SIG_IGN, SIGRTMIN, SignalAction,
+    SignalActions, SignalDefaultAction, SignalFlags, SignalInfo,
+};
 use crate::fs::{File, Stderr, Stdin, Stdout};
+use crate::ipc::{EndpointHandle, ReplyCap};
 use crate::process::pid::pid_alloc;
 use crate::sync::UPSafeCell;
+use crate::timer::{TICKS_PER_SEC, time};
 use crate::{
-    config::TRAP_CONTEXT,
-    mm::{KERNEL_SPACE, MemorySet, PhysPageNum, VirtAddr, translated_refmut},
+    config::{PAGE_SIZE, TRAP_CONTEXT},
+    mm::{ElfError, KERNEL_SPACE, LoadError, MemorySet, PhysPageNum, VirtAddr, copy_to_user_bytes},
     process::pid::{KernelStack, PidHandle},
     trap::{TrapContext, trap_handler},
 };
+use alloc::collections::{BTreeSet, VecDeque};
 use alloc::string::String;
 use alloc::sync::{Arc, Weak};
 use alloc::vec;
 use alloc::vec::Vec;
 use core::cell::RefMut;
 
+/// 辅助向量（auxv）结束标记
+const AT_NULL: usize = 0;
+/// 辅助向量：程序头表地址
+const AT_PHDR: usize = 3;
+/// 辅助向量：单个程序头大小
+const AT_PHENT: usize = 4;
+/// 辅助向量：程序头数量
+const AT_PHNUM: usize = 5;
+/// 辅助向量：页大小
+const AT_PAGESZ: usize = 6;
+/// 辅助向量：程序入口地址
+const AT_ENTRY: usize = 9;
+/// 辅助向量：16 字节随机数的地址
+const AT_RANDOM: usize = 25;
+/// `exec` 写入的辅助向量项数（含 `AT_NULL`）
+const AUXV_LEN: usize = 7;
+
+/// `exec` 失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecError {
+    /// ELF 文件未通过校验
+    BadElf(ElfError),
+    /// 新地址空间超过 `RLIMIT_AS`
+    AddressSpaceLimit,
+    /// 参数与环境变量放不进用户栈
+    ArgumentListTooLong,
+    /// 建立新地址空间时物理页帧不足
+    OutOfMemory,
+}
+
+impl From<LoadError> for ExecError {
+    fn from(err: LoadError) -> Self {
+        match err {
+            LoadError::BadElf(err) => ExecError::BadElf(err),
+            LoadError::AddressSpaceLimit => ExecError::AddressSpaceLimit,
+            LoadError::OutOfMemory => ExecError::OutOfMemory,
+        }
+    }
+}
+
 /// 进程控制块 (Process Control Block)
 ///
 /// 操作系统中每个进程的核心数据结构，包含进程运行所需的全部信息。
@@ -434,7 +481,22 @@ pub struct ProcessControlBlockInner {
     /// ```
     pub fd_table: Vec<Option<Arc<dyn File + Send + Sync>>>,
 
+    /// 设置了 `FD_CLOEXEC` 的文件描述符，`exec` 时关闭
+    ///
+    /// [`alloc_fd`](Self::alloc_fd) 分配描述符时清除它的旧标志，
+    /// 因此关闭描述符时不必同步维护。
+    pub fd_cloexec: BTreeSet<usize>,
+
+    /// IPC 端点句柄表，与 `fd_table` 并列
+    ///
+    /// `fork` 时复制，`exec` 时保留，进程退出时关闭。
+    pub ipc_table: Vec<Option<EndpointHandle>>,
+    /// 最近一次 `ipc_recv` 取得的 `call` 的应答权
+    pub ipc_reply: Option<ReplyCap>,
+
     pub signals: SignalFlags,
+    /// 排队中的实时信号（按发送顺序），对应的待决位同时记录在 `signals` 中
+    pub rt_signal_queue: VecDeque<SignalInfo>,
     pub signal_mask: SignalFlags,
     pub handling_sig: isize,
     pub signal_actions: SignalActions,
@@ -460,6 +522,27 @@ pub struct ProcessControlBlockInner {
     /// 该进程在当前优先级队列中可使用的最大时间片长度，
     /// 当 time_slice_used >= time_slice_limit 时触发降级。
     pub time_slice_limit: usize,
+
+    /// 资源限制表
+    ///
+    /// fork 时由子进程继承，exec 后保持不变，通过 `prlimit64` 查询与修改。
+    pub rlimits: RLimits,
+
+    /// 累计占用的 CPU 时钟中断次数
+    ///
+    /// 每次时钟中断在本进程运行时加一，用于执行 `RLIMIT_CPU`。
+    pub cpu_ticks: usize,
+
+    /// 系统调用跟踪输出
+    ///
+    /// 为 `Some` 时系统调用分发器把每次调用的名称、参数与返回值写入该文件。
+    /// 由 `trace` 系统调用设置，`fork` 时不继承，`exec` 后保持。
+    pub trace: Option<Arc<dyn File + Send + Sync>>,
+
+    /// ptrace 跟踪状态，`None` 表示未被跟踪；跟踪者总是父进程
+    ///
+    /// `fork` 时不继承，`exec` 后保持并在新程序开始前以 `SIGTRAP` 停止。
+    pub ptrace: Option<PtraceState>,
 }
 
 /// 进程状态枚举
@@ -516,6 +599,12 @@ pub struct ProcessControlBlockInner {
 /// - **转入**: 从就绪队列被调度器选中
 /// - **转出**: 时间片用完、主动让出 CPU、进程退出
 ///
+/// ### Blocked (阻塞)
+/// - **含义**: 进程在等待某个事件（如磁盘 I/O 完成）
+/// - **特征**: 不在就绪队列中，由等待队列持有其引用
+/// - **转入**: 调用 `block_current_and_run_next`
+/// - **转出**: 事件发生后由 `wakeup_process` 重新放入就绪队列
+///
 /// ### Zombie (僵尸)
 /// - **含义**: 进程已执行完毕，等待父进程收集退出信息
 /// - **特征**: 保留进程控制块，但不再调度执行
@@ -573,6 +662,12 @@ pub enum ProcessStatus {
     /// 同一时刻只有一个进程可以处于运行状态。
     Running,
 
+    /// 阻塞状态
+    ///
+    /// 进程在等待外部事件（如设备中断），不参与调度。
+    /// 事件发生后被唤醒并重新进入就绪队列。
+    Blocked,
+
     /// 僵尸状态
     ///
     /// 进程已执行完毕并退出，但进程控制块仍然保留，等待父进程
@@ -681,7 +776,8 @@ impl ProcessControlBlockInner {
     ///
     /// ## Returns
     ///
-    /// 返回新分配的文件描述符编号（非负整数）
+    /// 返回新分配的文件描述符编号（非负整数）；若编号将达到 `RLIMIT_NOFILE`
+    /// 软限制则返回 `None`
     ///
     /// ## 分配策略
     ///
@@ -724,7 +820,9 @@ impl ProcessControlBlockInner {
     /// ```rust
     /// // 在 open 系统调用中使用
     /// let mut inner = process.inner_exclusive_access();
-    /// let new_fd = inner.alloc_fd();
+    /// let Some(new_fd) = inner.alloc_fd() else {
+    ///     return -1;
+    /// };
     /// inner.fd_table[new_fd] = Some(file_object);
     /// return new_fd as isize;
     ///
@@ -738,12 +836,128 @@ impl ProcessControlBlockInner {
     /// - **时间复杂度**: O(n)，其中 n 是文件描述符表的当前大小
     /// - **空间复杂度**: 最坏情况下需要扩展表大小
     /// - **内存效率**: 优先重用已关闭的描述符，减少内存浪费
-    pub fn alloc_fd(&mut self) -> usize {
-        if let Some(fd) = (0..self.fd_table.len()).find(|fd| self.fd_table[*fd].is_none()) {
-            fd
-        } else {
-            self.fd_table.push(None);
-            self.fd_table.len() - 1
+    pub fn alloc_fd(&mut self) -> Option<usize> {
+        self.alloc_fd_from(0)
+    }
+
+    /// 分配不小于 `min` 的最小空闲文件描述符
+    ///
+    /// 用于 `F_DUPFD`，规则与 [`alloc_fd`](Self::alloc_fd) 相同。
+    ///
+    /// ## Returns
+    ///
+    /// - `Some(fd)` - 分配到的文件描述符
+    /// - `None` - 不存在小于 `RLIMIT_NOFILE` 的可用描述符
+    pub fn alloc_fd_from(&mut self, min: usize) -> Option<usize> {
+        let limit = self.rlimits.cur(RLIMIT_NOFILE);
+        let fd = (min..limit).find(|&fd| !matches!(self.fd_table.get(fd), Some(Some(_))))?;
+        if fd >= self.fd_table.len() {
+            self.fd_table.resize(fd + 1, None);
+        }
+        self.fd_cloexec.remove(&fd);
+        Some(fd)
+    }
+
+    /// 记账一次时钟中断的 CPU 占用并执行 `RLIMIT_CPU`
+    ///
+    /// 累计时间每满一秒检查一次：达到软限制后投递 `SIGXCPU`，
+    /// 达到硬限制时投递 `SIGKILL`。
+    pub fn charge_cpu_tick(&mut self) {
+        self.cpu_ticks += 1;
+        if self.cpu_ticks % TICKS_PER_SEC != 0 {
+            return;
+        }
+        let seconds = self.cpu_ticks / TICKS_PER_SEC;
+        let limit = self.rlimits.table[RLIMIT_CPU];
+        if seconds >= limit.rlim_max {
+            self.send_signal(SignalFlags::SIGKILL.lowest_signum().unwrap(), 0);
+        } else if seconds >= limit.rlim_cur {
+            self.send_signal(SignalFlags::SIGXCPU.lowest_signum().unwrap(), 0);
+        }
+    }
+
+    /// 向本进程投递一个信号
+    ///
+    /// 标准信号已待决时直接合并；实时信号每次发送都追加到 `rt_signal_queue`。
+    /// 同时处理停止/继续类信号之间的相互抵消：`SIGCONT` 立即解除冻结并丢弃
+    /// 待决的停止类信号，停止类信号会丢弃待决的 `SIGCONT`。
+    ///
+    /// ## Arguments
+    /// * `signum` - 信号编号（1..=MAX_SIG）
+    /// * `value` - 附带数据，仅实时信号会保留并传给处理函数
+    ///
+    /// ## Returns
+    /// - `true`：投递成功（包括与已待决信号合并）
+    /// - `false`：编号非法或实时信号队列已满
+    pub fn send_signal(&mut self, signum: usize, value: usize) -> bool {
+        let Some(signal) = SignalFlags::from_signum(signum) else {
+            return false;
+        };
+        if signum >= SIGRTMIN {
+            if self.rt_signal_queue.len() >= MAX_QUEUED_SIGNALS {
+                return false;
+            }
+            self.rt_signal_queue.push_back(SignalInfo {
+                signo: signum,
+                value,
+            });
+        }
+        match signal.default_action() {
+            SignalDefaultAction::Continue => {
+                self.frozen = false;
+                self.signals.remove(
+                    SignalFlags::SIGSTOP
+                        | SignalFlags::SIGTSTP
+                        | SignalFlags::SIGTTIN
+                        | SignalFlags::SIGTTOU,
+                );
+            }
+            SignalDefaultAction::Stop => self.signals.remove(SignalFlags::SIGCONT),
+            _ => {}
+        }
+        self.signals.insert(signal);
+        true
+    }
+
+    /// 取出一个待决信号准备投递
+    ///
+    /// 标准信号直接清除待决位；实时信号弹出队列中最早的一项，
+    /// 队列中不再有同编号信号时才清除待决位。
+    ///
+    /// ## Arguments
+    /// * `signum` - 信号编号
+    ///
+    /// ## Returns
+    /// 该次投递携带的附带数据（标准信号为 0）
+    pub fn take_signal(&mut self, signum: usize) -> usize {
+        let signal = SignalFlags::from_signum(signum).unwrap();
+        if signum < SIGRTMIN {
+            self.signals.remove(signal);
+            return 0;
+        }
+        let mut value = 0;
+        if let Some(pos) = self
+            .rt_signal_queue
+            .iter()
+            .position(|info| info.signo == signum)
+        {
+            value = self.rt_signal_queue.remove(pos).unwrap().value;
+        }
+        if !self.rt_signal_queue.iter().any(|info| info.signo == signum) {
+            self.signals.remove(signal);
+        }
+        value
+    }
+
+    /// 解除 ptrace 跟踪，恢复单步执行的临时断点
+    ///
+    /// 僵尸进程的地址空间已回收，只丢弃跟踪状态。
+    pub fn ptrace_detach(&mut self) {
+        let Some(mut ptrace) = self.ptrace.take() else {
+            return;
+        };
+        if self.process_status != ProcessStatus::Zombie {
+            ptrace.clear_step_breakpoints(self.memory_set.token());
         }
     }
 }
@@ -861,7 +1075,10 @@ impl ProcessControlBlock {
     /// - 分配 `PidHandle` 与 `KernelStack`，设置进程上下文返回到 `trap_return`
     /// - 初始化标准文件描述符（0/1/2）与信号相关字段
     pub fn new(elf_data: &[u8]) -> Self {
-        let (memory_set, user_sp, entry_point) = MemorySet::from_elf(elf_data);
+        let rlimits = RLimits::default();
+        let (memory_set, user_sp, entry_point, _) =
+            MemorySet::from_elf(elf_data, rlimits.cur(RLIMIT_STACK), rlimits.cur(RLIMIT_AS))
+                .expect("invalid initproc elf");
         let trap_cx_ppn = memory_set
             .translate(VirtAddr::from(TRAP_CONTEXT).into())
             .unwrap()
@@ -883,11 +1100,15 @@ impl ProcessControlBlock {
                     children: Vec::new(),
                     exit_code: 0,
                     fd_table: vec![
-                        Some(Arc::new(Stdin)),
-                        Some(Arc::new(Stdout)),
-                        Some(Arc::new(Stderr)),
+                        Some(Arc::new(Stdin::default())),
+                        Some(Arc::new(Stdout::default())),
+                        Some(Arc::new(Stderr::default())),
                     ],
+                    fd_cloexec: BTreeSet::new(),
+                    ipc_table: Vec::new(),
+                    ipc_reply: None,
                     signals: SignalFlags::empty(),
+                    rt_signal_queue: VecDeque::new(),
                     signal_mask: SignalFlags::empty(),
                     handling_sig: -1,
                     signal_actions: SignalActions::default(),
@@ -900,6 +1121,10 @@ impl ProcessControlBlock {
                         use crate::config::MLFQ_BASE_TIME_SLICE;
                         MLFQ_BASE_TIME_SLICE
                     },
+                    rlimits: RLimits::default(),
+                    cpu_ticks: 0,
+                    trace: None,
+                    ptrace: None,
                 })
             },
         };
@@ -1251,7 +1476,11 @@ impl ProcessControlBlock {
                     children: Vec::new(),
                     exit_code: 0,
                     fd_table: new_fd_table,
+                    fd_cloexec: parent_inner.fd_cloexec.clone(),
+                    ipc_table: parent_inner.ipc_table.clone(),
+                    ipc_reply: None,
                     signals: SignalFlags::empty(),
+                    rt_signal_queue: VecDeque::new(),
                     signal_mask: parent_inner.signal_mask,
                     handling_sig: -1,
                     signal_actions: parent_inner.signal_actions.clone(),
@@ -1262,6 +1491,11 @@ impl ProcessControlBlock {
                     priority: parent_inner.priority,
                     time_slice_used: 0, // 重置时间片使用计数
                     time_slice_limit: parent_inner.time_slice_limit,
+                    // 子进程继承资源限制，CPU 时间重新计数
+                    rlimits: parent_inner.rlimits.clone(),
+                    cpu_ticks: 0,
+                    trace: None,
+                    ptrace: None,
                 })
             },
         });
@@ -1279,6 +1513,28 @@ impl ProcessControlBlock {
     /// ## Arguments
     ///
     /// * `elf_data` - 新程序的 ELF 文件二进制数据
+    /// * `args` - 新程序的命令行参数
+    /// * `envs` - 新程序的环境变量（`NAME=VALUE` 形式）
+    ///
+    /// ## 初始用户栈
+    ///
+    /// 按 Linux 约定布局，`sp` 指向 `argc` 且 16 字节对齐；同时通过
+    /// `a0`/`a1`/`a2` 传递 argc、argv、envp，便于不解析栈的运行时直接使用：
+    ///
+    /// ```text
+    /// 高地址  环境变量与参数字符串
+    ///         AT_RANDOM 的 16 字节随机数
+    ///         auxv: (AT_PHDR, ..) (AT_PHENT, ..) (AT_PHNUM, ..) (AT_PAGESZ, ..)
+    ///               (AT_ENTRY, ..) (AT_RANDOM, ..) (AT_NULL, 0)
+    ///         envp[0..n], NULL
+    ///         argv[0..argc], NULL
+    /// sp ->   argc
+    /// ```
+    ///
+    /// ## Returns
+    ///
+    /// 成功返回 `Ok(())`；ELF 未通过校验、新地址空间超过 `RLIMIT_AS` 或物理页帧
+    /// 不足时返回对应的 [`ExecError`]，原进程映像保持不变
     ///
     /// ## Exec 语义
     ///
@@ -1369,6 +1625,8 @@ impl ProcessControlBlock {
     /// └──────────────────┘    └─────────────────────┘
     /// ```
     ///
+    /// 文件描述符表保留，但设置了 `FD_CLOEXEC` 的描述符会被关闭。
+    ///
     /// ## 典型使用场景
     ///
     /// **Shell 命令执行**：
@@ -1424,6 +1682,7 @@ impl ProcessControlBlock {
     /// - **内存不足**: 无法为新程序分配足够的内存空间
     /// - **权限不足**: 没有执行目标文件的权限
     /// - **系统资源限制**: 超出系统资源限制
+    /// - **参数过长**: 参数、环境变量与辅助向量放不进用户栈，此时不会映射新地址空间
     ///
     /// ## 性能特性
     ///
@@ -1436,39 +1695,115 @@ impl ProcessControlBlock {
     /// - **fork() + exec()**: 经典的进程创建和程序加载模式
     /// - **wait()**: 父进程等待 exec 后的子进程完成
     /// - **exit()**: 进程执行完成后的正常退出
-    pub fn exec(&self, elf_data: &[u8], args: Vec<String>) {
-        let (memory_set, mut user_sp, entry_point) = MemorySet::from_elf(elf_data);
+    pub fn exec(
+        &self,
+        elf_data: &[u8],
+        args: Vec<String>,
+        envs: Vec<String>,
+    ) -> Result<(), ExecError> {
+        let (stack_size, as_limit) = {
+            let inner = self.inner_exclusive_access();
+            (
+                inner.rlimits.cur(RLIMIT_STACK),
+                inner.rlimits.cur(RLIMIT_AS),
+            )
+        };
+        // 参数、环境变量与辅助向量必须放得进新程序的用户栈
+        let word = core::mem::size_of::<usize>();
+        let strings_size: usize = envs.iter().chain(args.iter()).map(|s| s.len() + 1).sum();
+        let words = 1 + (args.len() + 1) + (envs.len() + 1) + AUXV_LEN * 2;
+        // 16 字节 AT_RANDOM 与最多 15 字节的对齐填充
+        if strings_size + 16 + words * word + 15 > MemorySet::user_stack_size(stack_size) {
+            return Err(ExecError::ArgumentListTooLong);
+        }
+        let (memory_set, mut user_sp, entry_point, aux) =
+            MemorySet::from_elf(elf_data, stack_size, as_limit)?;
         let trap_cx_ppn = memory_set
             .translate(VirtAddr::from(TRAP_CONTEXT).into())
             .unwrap()
             .ppn();
+        let token = memory_set.token();
+        // 上面已检查大小，写入只会在栈内进行；仍按可失败的方式访问用户内存
+        let too_big = |_: isize| ExecError::ArgumentListTooLong;
 
-        user_sp -= (args.len() + 1) * core::mem::size_of::<usize>();
-        let argv_base = user_sp;
-        let mut argv: Vec<_> = (0..=args.len())
-            .map(|arg| {
-                translated_refmut(
-                    memory_set.token(),
-                    (argv_base + arg * core::mem::size_of::<usize>()) as *mut usize,
-                )
-            })
-            .collect();
-        *argv[args.len()] = 0;
-        for i in 0..args.len() {
-            user_sp -= args[i].len() + 1;
-            *argv[i] = user_sp;
-            let mut p = user_sp;
-            for c in args[i].as_bytes() {
-                *translated_refmut(memory_set.token(), p as *mut u8) = *c;
-                p += 1;
-            }
-            *translated_refmut(memory_set.token(), p as *mut u8) = 0;
+        // 字符串区：环境变量与参数字符串依次压栈
+        let mut push_str = |s: &str| -> Result<usize, ExecError> {
+            user_sp -= s.len() + 1;
+            copy_to_user_bytes(token, user_sp as *mut u8, s.as_bytes()).map_err(too_big)?;
+            copy_to_user_bytes(token, (user_sp + s.len()) as *mut u8, &[0]).map_err(too_big)?;
+            Ok(user_sp)
+        };
+        let envp = envs
+            .iter()
+            .map(|env| push_str(env))
+            .collect::<Result<Vec<usize>, _>>()?;
+        let argv = args
+            .iter()
+            .map(|arg| push_str(arg))
+            .collect::<Result<Vec<usize>, _>>()?;
+
+        // AT_RANDOM 指向的 16 字节随机数
+        user_sp -= 16;
+        let random_ptr = user_sp;
+        let mut random = [0u8; 16];
+        let mut seed = time() ^ (self.getpid() << 32) ^ 0x9e37_79b9_7f4a_7c15;
+        for byte in random.iter_mut() {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            *byte = seed as u8;
+        }
+        copy_to_user_bytes(token, random_ptr as *mut u8, &random).map_err(too_big)?;
+
+        let auxv: [(usize, usize); AUXV_LEN] = [
+            (AT_PHDR, aux.phdr),
+            (AT_PHENT, aux.phent),
+            (AT_PHNUM, aux.phnum),
+            (AT_PAGESZ, PAGE_SIZE),
+            (AT_ENTRY, entry_point),
+            (AT_RANDOM, random_ptr),
+            (AT_NULL, 0),
+        ];
+
+        // 指针区：argc | argv[] | NULL | envp[] | NULL | auxv[]，栈指针 16 字节对齐
+        user_sp = (user_sp - words * word) & !0xf;
+        let mut block = Vec::with_capacity(words);
+        block.push(argv.len());
+        let argv_base = user_sp + word;
+        block.extend_from_slice(&argv);
+        block.push(0);
+        let envp_base = argv_base + (argv.len() + 1) * word;
+        block.extend_from_slice(&envp);
+        block.push(0);
+        for (key, value) in auxv {
+            block.push(key);
+            block.push(value);
         }
-        user_sp -= user_sp % core::mem::size_of::<usize>();
+        let bytes: Vec<u8> = block.iter().flat_map(|value| value.to_ne_bytes()).collect();
+        copy_to_user_bytes(token, user_sp as *mut u8, &bytes).map_err(too_big)?;
 
         let mut inner = self.inner_exclusive_access();
         inner.memory_set = memory_set;
         inner.trap_cx_ppn = trap_cx_ppn;
+        // 关闭设置了 FD_CLOEXEC 的描述符；文件在释放进程锁之后才析构
+        let cloexec = core::mem::take(&mut inner.fd_cloexec);
+        let closed: Vec<_> = cloexec
+            .into_iter()
+            .filter_map(|fd| inner.fd_table.get_mut(fd).and_then(Option::take))
+            .collect();
+        // 已捕捉的信号在新程序中没有处理函数，恢复为默认动作；被忽略的保持忽略
+        for action in inner.signal_actions.table.iter_mut() {
+            if action.handler != SIG_IGN {
+                *action = SignalAction::default();
+            }
+        }
+        inner.handling_sig = -1;
+        inner.trap_ctx_backup = None;
+        // 被跟踪的进程在新程序的第一条指令前停止，让调试器有机会设置断点
+        if let Some(ptrace) = inner.ptrace.as_mut() {
+            ptrace.forget_step_breakpoints();
+            inner.send_signal(SignalFlags::SIGTRAP.lowest_signum().unwrap(), 0);
+        }
         let mut trap_cx = TrapContext::app_init_context(
             entry_point,
             user_sp,
@@ -1478,6 +1813,10 @@ impl ProcessControlBlock {
         );
         trap_cx.x[10] = args.len();
         trap_cx.x[11] = argv_base;
+        trap_cx.x[12] = envp_base;
         *inner.trap_cx() = trap_cx;
+        drop(inner);
+        drop(closed);
+        Ok(())
     }
 }
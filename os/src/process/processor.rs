@@ -17,6 +17,7 @@
 //! - **时间片轮转**: 基于时钟中断的抢占式调度
 //! - **主动让出**: 进程可以主动调用 `yield` 让出 CPU
 //! - **阻塞调度**: I/O 等待时自动切换到其他进程
+//! - **空闲等待**: 就绪队列为空时执行 `wfi`，由设备中断唤醒被阻塞的进程
 //!
 //! ### 进程状态转换
 //! ```text
@@ -85,7 +86,7 @@ use crate::process::process::ProcessStatus;
 use crate::process::switch::__switch;
 use crate::process::{context::ProcessContext, process::ProcessControlBlock};
 use crate::sync::UPSafeCell;
-use crate::trap::TrapContext;
+use crate::trap::{TrapContext, wait_for_interrupt};
 use alloc::sync::Arc;
 use lazy_static::lazy_static;
 
@@ -906,6 +907,9 @@ pub fn run_process() {
             unsafe {
                 __switch(idle_process_cx_ptr, next_process_cx_ptr);
             }
+        } else {
+            drop(processor);
+            wait_for_interrupt();
         }
     }
 }
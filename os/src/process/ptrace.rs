@@ -0,0 +1,187 @@
+//! # 进程跟踪状态
+//!
+//! 被跟踪进程的跟踪者总是它的父进程（`PTRACE_TRACEME` 由子进程发起，
+//! `PTRACE_ATTACH` 只能附加到自己的子进程）。被跟踪进程收到除 `SIGKILL`
+//! 以外的信号时不立即投递，而是进入跟踪停止状态：父进程通过 `waitpid`
+//! 得知停止，读写它的内存与寄存器，再让它继续执行。
+//!
+//! ## 单步执行
+//!
+//! 没有硬件单步支持，用临时断点模拟：解码 `pc` 处的指令，在它执行后可能
+//! 到达的每个地址（顺序执行的下一条、分支或跳转目标）写入 `ebreak`。
+//! 进程下一次进入跟踪停止时恢复这些地址的原指令。
+
+use crate::mm::{copy_from_user_bytes, poke_user_bytes};
+use crate::trap::TrapContext;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// `ebreak` 指令
+const EBREAK: u32 = 0x0010_0073;
+/// 压缩指令 `c.ebreak`，用于替换 2 字节的指令
+const C_EBREAK: u16 = 0x9002;
+
+/// 被跟踪进程的状态
+#[derive(Debug, Default)]
+pub struct PtraceState {
+    /// 跟踪停止的原因（信号编号）；`None` 表示正在运行
+    pub stop_sig: Option<usize>,
+    /// 本次停止是否已由 `waitpid` 报告给父进程
+    pub stop_reported: bool,
+    /// 父进程恢复执行时要求投递的信号，投递时不再停止
+    pub inject_sig: Option<usize>,
+    /// 单步执行写入的临时断点：地址与被替换的原指令
+    step_breakpoints: Vec<(usize, Vec<u8>)>,
+}
+
+impl PtraceState {
+    /// 是否处于跟踪停止状态
+    pub fn is_stopped(&self) -> bool {
+        self.stop_sig.is_some()
+    }
+
+    /// 进入跟踪停止状态，并恢复单步执行的临时断点
+    ///
+    /// ## Arguments
+    ///
+    /// * `token` - 被跟踪进程的页表标识
+    /// * `sig` - 停止原因
+    pub fn stop(&mut self, token: usize, sig: usize) {
+        self.clear_step_breakpoints(token);
+        self.stop_sig = Some(sig);
+        self.stop_reported = false;
+    }
+
+    /// 为单步执行在 `cx.sepc` 处指令的所有后继地址写入临时断点
+    ///
+    /// ## Arguments
+    ///
+    /// * `token` - 被跟踪进程的页表标识
+    /// * `cx` - 被跟踪进程保存的 Trap 上下文
+    ///
+    /// ## Returns
+    ///
+    /// `pc` 处的指令不可读时返回 `-EFAULT`
+    pub fn set_step_breakpoints(&mut self, token: usize, cx: &TrapContext) -> Result<(), isize> {
+        for addr in next_pcs(token, cx)? {
+            // 目标不可读时不设断点，进程执行到那里会因缺页而停止
+            let Ok((_, len)) = read_inst(token, addr) else {
+                continue;
+            };
+            let mut orig = vec![0u8; len];
+            copy_from_user_bytes(token, addr as *const u8, &mut orig)?;
+            let ebreak = if len == 2 {
+                C_EBREAK.to_le_bytes().to_vec()
+            } else {
+                EBREAK.to_le_bytes().to_vec()
+            };
+            poke_user_bytes(token, addr as *mut u8, &ebreak)?;
+            self.step_breakpoints.push((addr, orig));
+        }
+        Ok(())
+    }
+
+    /// 恢复临时断点处的原指令
+    pub fn clear_step_breakpoints(&mut self, token: usize) {
+        for (addr, orig) in self.step_breakpoints.drain(..).rev() {
+            let _ = poke_user_bytes(token, addr as *mut u8, &orig);
+        }
+    }
+
+    /// `exec` 后旧的地址空间已不存在，直接丢弃临时断点
+    pub fn forget_step_breakpoints(&mut self) {
+        self.step_breakpoints.clear();
+    }
+}
+
+/// 读取 `addr` 处的指令，返回指令与它的长度（2 或 4 字节）
+fn read_inst(token: usize, addr: usize) -> Result<(u32, usize), isize> {
+    let mut low = [0u8; 2];
+    copy_from_user_bytes(token, addr as *const u8, &mut low)?;
+    let low = u16::from_le_bytes(low);
+    if low & 0b11 != 0b11 {
+        return Ok((low as u32, 2));
+    }
+    let mut inst = [0u8; 4];
+    copy_from_user_bytes(token, addr as *const u8, &mut inst)?;
+    Ok((u32::from_le_bytes(inst), 4))
+}
+
+/// 把 `value` 的低 `bits` 位作为有符号数扩展
+fn sign_extend(value: u32, bits: u32) -> isize {
+    let shift = 32 - bits;
+    (((value << shift) as i32) >> shift) as isize
+}
+
+/// `pc` 处的指令执行后可能到达的地址
+fn next_pcs(token: usize, cx: &TrapContext) -> Result<Vec<usize>, isize> {
+    let pc = cx.sepc;
+    let reg = |idx: u32| if idx == 0 { 0 } else { cx.x[idx as usize] };
+    let (inst, len) = read_inst(token, pc)?;
+    let next = pc.wrapping_add(len);
+    let offset = |imm: isize| pc.wrapping_add_signed(imm);
+    let targets = if len == 4 {
+        let rs1 = (inst >> 15) & 0x1f;
+        match inst & 0x7f {
+            // jal
+            0x6f => {
+                let imm = ((inst >> 31) & 1) << 20
+                    | ((inst >> 21) & 0x3ff) << 1
+                    | ((inst >> 20) & 1) << 11
+                    | ((inst >> 12) & 0xff) << 12;
+                vec![offset(sign_extend(imm, 21))]
+            }
+            // jalr
+            0x67 => {
+                let imm = sign_extend(inst >> 20, 12);
+                vec![reg(rs1).wrapping_add_signed(imm) & !1]
+            }
+            // beq/bne/blt/bge/bltu/bgeu
+            0x63 => {
+                let imm = ((inst >> 31) & 1) << 12
+                    | ((inst >> 25) & 0x3f) << 5
+                    | ((inst >> 8) & 0xf) << 1
+                    | ((inst >> 7) & 1) << 11;
+                vec![next, offset(sign_extend(imm, 13))]
+            }
+            _ => vec![next],
+        }
+    } else {
+        let funct3 = (inst >> 13) & 0b111;
+        let rs1 = (inst >> 7) & 0x1f;
+        let rs2 = (inst >> 2) & 0x1f;
+        match (inst & 0b11, funct3) {
+            // c.j
+            (0b01, 0b101) => {
+                let imm = ((inst >> 12) & 1) << 11
+                    | ((inst >> 11) & 1) << 4
+                    | ((inst >> 9) & 0b11) << 8
+                    | ((inst >> 8) & 1) << 10
+                    | ((inst >> 7) & 1) << 6
+                    | ((inst >> 6) & 1) << 7
+                    | ((inst >> 3) & 0b111) << 1
+                    | ((inst >> 2) & 1) << 5;
+                vec![offset(sign_extend(imm, 12))]
+            }
+            // c.beqz/c.bnez
+            (0b01, 0b110 | 0b111) => {
+                let imm = ((inst >> 12) & 1) << 8
+                    | ((inst >> 10) & 0b11) << 3
+                    | ((inst >> 5) & 0b11) << 6
+                    | ((inst >> 3) & 0b11) << 1
+                    | ((inst >> 2) & 1) << 5;
+                vec![next, offset(sign_extend(imm, 9))]
+            }
+            // c.jr/c.jalr
+            (0b10, 0b100) if rs2 == 0 && rs1 != 0 => vec![reg(rs1) & !1],
+            _ => vec![next],
+        }
+    };
+    let mut pcs = Vec::new();
+    for target in targets {
+        if !pcs.contains(&target) {
+            pcs.push(target);
+        }
+    }
+    Ok(pcs)
+}
@@ -0,0 +1,123 @@
+//! # 进程资源限制（rlimit）模块
+//!
+//! 为每个进程维护一张资源限制表，限制其可使用的文件描述符、进程数、CPU 时间、
+//! 文件大小、栈大小与地址空间大小。限制表在 `fork` 时由子进程继承，并可通过
+//! `prlimit64` 系统调用查询与修改。
+//!
+//! ## 组成
+//! - [`RLimit`]：单项限制（软限制 `rlim_cur` 与硬限制 `rlim_max`）
+//! - [`RLimits`]：全表（索引为 `RLIMIT_*` 资源编号）
+//!
+//! ## 内核执行点
+//! - `RLIMIT_NOFILE`：`alloc_fd` 分配的描述符编号不得达到软限制
+//! - `RLIMIT_NPROC`：`fork` 时系统中存活进程数不得达到软限制
+//! - `RLIMIT_CPU`：时钟中断累计 CPU 时间，超过软限制后每秒投递 `SIGXCPU`，
+//!   达到硬限制时投递 `SIGKILL`
+//! - `RLIMIT_FSIZE`：`OSInode::write` 不写出超过限制的部分；一个字节都写不进时
+//!   投递 `SIGXFSZ` 并返回 `EFBIG`
+//! - `RLIMIT_STACK`：`exec` 按软限制确定用户栈大小
+//! - `RLIMIT_AS`：`exec` 构建的用户地址空间不得超过软限制
+//!
+//! 资源编号与结构体布局与 Linux 保持一致，未列出的资源只记录不执行。
+
+use crate::config::{
+    DEFAULT_RLIMIT_NOFILE, DEFAULT_RLIMIT_NPROC, MAX_RLIMIT_NOFILE, USER_STACK_SIZE,
+    USER_STACK_SIZE_MAX,
+};
+use crate::errno::Errno::{EINVAL, EPERM};
+
+/// CPU 时间（秒）
+pub const RLIMIT_CPU: usize = 0;
+/// 可写文件的最大字节数
+pub const RLIMIT_FSIZE: usize = 1;
+/// 用户栈大小（字节）
+pub const RLIMIT_STACK: usize = 3;
+/// 可同时存活的进程数
+pub const RLIMIT_NPROC: usize = 6;
+/// 可打开的文件描述符数
+pub const RLIMIT_NOFILE: usize = 7;
+/// 用户地址空间大小（字节）
+pub const RLIMIT_AS: usize = 9;
+
+/// 资源种类数（与 Linux 的 `RLIM_NLIMITS` 一致）
+pub const RLIM_NLIMITS: usize = 16;
+
+/// 表示“无限制”的取值
+pub const RLIM_INFINITY: usize = usize::MAX;
+
+/// 单项资源限制
+///
+/// - `rlim_cur`：软限制，内核实际执行的值
+/// - `rlim_max`：硬限制，软限制的上界
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RLimit {
+    pub rlim_cur: usize,
+    pub rlim_max: usize,
+}
+
+impl RLimit {
+    /// 软硬限制均为 `limit`
+    const fn fixed(limit: usize) -> Self {
+        Self {
+            rlim_cur: limit,
+            rlim_max: limit,
+        }
+    }
+}
+
+/// 进程的资源限制表（索引为 `RLIMIT_*`）
+#[derive(Clone)]
+pub struct RLimits {
+    pub table: [RLimit; RLIM_NLIMITS],
+}
+
+impl Default for RLimits {
+    /// 初始进程使用的默认限制：描述符数、进程数、栈大小取配置值，其余不限制
+    fn default() -> Self {
+        let mut table = [RLimit::fixed(RLIM_INFINITY); RLIM_NLIMITS];
+        table[RLIMIT_NOFILE] = RLimit {
+            rlim_cur: DEFAULT_RLIMIT_NOFILE,
+            rlim_max: MAX_RLIMIT_NOFILE,
+        };
+        table[RLIMIT_NPROC] = RLimit::fixed(DEFAULT_RLIMIT_NPROC);
+        table[RLIMIT_STACK] = RLimit {
+            rlim_cur: USER_STACK_SIZE,
+            rlim_max: USER_STACK_SIZE_MAX,
+        };
+        Self { table }
+    }
+}
+
+impl RLimits {
+    /// 查询某项资源的当前软限制
+    ///
+    /// ## Arguments
+    /// * `resource` - 资源编号（`RLIMIT_*`）
+    pub fn cur(&self, resource: usize) -> usize {
+        self.table[resource].rlim_cur
+    }
+
+    /// 修改某项资源的限制
+    ///
+    /// 要求 `rlim_cur <= rlim_max`，且硬限制只能降低不能提高。
+    ///
+    /// ## Arguments
+    /// * `resource` - 资源编号（`RLIMIT_*`）
+    /// * `limit` - 新的限制
+    ///
+    /// ## Returns
+    /// - `Ok(())`：设置成功
+    /// - `Err(-EINVAL)`：资源编号非法或 `rlim_cur > rlim_max`
+    /// - `Err(-EPERM)`：试图提高硬限制
+    pub fn set(&mut self, resource: usize, limit: RLimit) -> Result<(), isize> {
+        if resource >= RLIM_NLIMITS || limit.rlim_cur > limit.rlim_max {
+            return Err(-EINVAL);
+        }
+        if limit.rlim_max > self.table[resource].rlim_max {
+            return Err(-EPERM);
+        }
+        self.table[resource] = limit;
+        Ok(())
+    }
+}
@@ -8,26 +8,46 @@
 //! - [`SignalFlags`]：信号位集合类型
 //! - [`SignalAction`]：单个信号的处理动作（用户态处理入口与掩码）
 //! - [`SignalActions`]：全表（索引 0..=MAX_SIG）
+//! - [`SignalDefaultAction`]：POSIX 默认动作（终止/忽略/停止/继续/转储）
+//! - [`SignalInfo`]：实时信号的排队项（信号编号 + 附带数据）
 //!
 //! ## 常见语义
-//! - 致命/错误类信号转为负退出码（见 [`SignalFlags::check_error`]）
-//! - 控制类信号（`SIGSTOP`/`SIGCONT`）由内核内建处理
-//! - 其余可捕捉信号可由用户程序通过 `sigaction` 自定义处理
+//! - 处理函数为 [`SIG_DFL`] 时执行默认动作（见 [`SignalFlags::default_action`]）
+//! - 处理函数为 [`SIG_IGN`] 时直接丢弃该信号（`SIGKILL`/`SIGSTOP` 除外）
+//! - 终止类信号转为负退出码 `-signum`（见 [`SignalFlags::check_error`]）
+//! - 标准信号（1..=31）在待决集合中只记一次；实时信号（[`SIGRTMIN`]..=[`MAX_SIG`]）
+//!   按发送顺序排队，每次发送都会单独投递一次并携带附带数据
 //!
 use bitflags::*;
 
 /// 支持的最大信号编号（含）
 ///
-/// 本实现支持 0..=MAX_SIG 共 32 个编号槽位，对应的位掩码使用 `1 << signum`。
-pub const MAX_SIG: usize = 31;
+/// 本实现支持 0..=MAX_SIG 共 64 个编号槽位，对应的位掩码使用 `1 << signum`。
+/// 同时也是最后一个实时信号编号（受位掩码宽度限制，比 Linux 的 SIGRTMAX=64 少一个）。
+pub const MAX_SIG: usize = 63;
+
+/// 第一个实时信号编号
+pub const SIGRTMIN: usize = 32;
+
+/// 处理函数取值：执行默认动作
+pub const SIG_DFL: usize = 0;
+
+/// 处理函数取值：忽略该信号
+pub const SIG_IGN: usize = 1;
+
+/// 单个进程最多排队的实时信号数量
+///
+/// 超出后 `kill`/`sigqueue` 返回失败，防止发送方无限占用内核内存。
+pub const MAX_QUEUED_SIGNALS: usize = 64;
 
 bitflags! {
     /// 信号位集合
     ///
     /// 每一位对应一个信号，结合 `insert/contains/remove` 操作可维护待决集合、
     /// 屏蔽集合等。数值与传统 Unix 信号编号保持一致（部分信号为兼容保留）。
+    /// 高 32 位整体对应实时信号 [`SIGRTMIN`]..=[`MAX_SIG`]。
     #[derive(Debug, Clone, Copy, PartialEq)]
-    pub struct SignalFlags: u32 {
+    pub struct SignalFlags: u64 {
         const SIGDEF = 1;
         const SIGHUP = 1 << 1;
         const SIGINT = 1 << 2;
@@ -60,38 +80,163 @@ bitflags! {
         const SIGIO = 1 << 29;
         const SIGPWR = 1 << 30;
         const SIGSYS = 1 << 31;
+        const SIGRT = 0xffff_ffff_0000_0000;
     }
 }
 
+/// 信号的 POSIX 默认动作
+///
+/// 当处理函数为 [`SIG_DFL`] 时，内核按此动作处理信号。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalDefaultAction {
+    /// 终止进程
+    Terminate,
+    /// 忽略信号
+    Ignore,
+    /// 停止（冻结）进程
+    Stop,
+    /// 继续执行被停止的进程
+    Continue,
+    /// 终止进程并生成核心转储
+    Core,
+}
+
+/// 排队中的实时信号
+///
+/// - `signo`：信号编号
+/// - `value`：发送方附带的数据，投递时通过 `a1` 传给处理函数
+#[derive(Debug, Clone, Copy)]
+pub struct SignalInfo {
+    pub signo: usize,
+    pub value: usize,
+}
+
+/// 标准信号（0..=31）的终止说明，索引为信号编号
+const SIGNAL_DESCRIPTIONS: [&str; 32] = [
+    "Default signal, SIGDEF=0",
+    "Hangup, SIGHUP=1",
+    "Killed, SIGINT=2",
+    "Quit, SIGQUIT=3",
+    "Illegal Instruction, SIGILL=4",
+    "Trace/Breakpoint Trap, SIGTRAP=5",
+    "Aborted, SIGABRT=6",
+    "Bus Error, SIGBUS=7",
+    "Erroneous Arithmetic Operation, SIGFPE=8",
+    "Killed, SIGKILL=9",
+    "User Defined Signal 1, SIGUSR1=10",
+    "Segmentation Fault, SIGSEGV=11",
+    "User Defined Signal 2, SIGUSR2=12",
+    "Broken Pipe, SIGPIPE=13",
+    "Alarm Clock, SIGALRM=14",
+    "Terminated, SIGTERM=15",
+    "Stack Fault, SIGSTKFLT=16",
+    "Child Status Changed, SIGCHLD=17",
+    "Continued, SIGCONT=18",
+    "Stopped, SIGSTOP=19",
+    "Stopped, SIGTSTP=20",
+    "Stopped (tty input), SIGTTIN=21",
+    "Stopped (tty output), SIGTTOU=22",
+    "Urgent I/O Condition, SIGURG=23",
+    "CPU Time Limit Exceeded, SIGXCPU=24",
+    "File Size Limit Exceeded, SIGXFSZ=25",
+    "Virtual Timer Expired, SIGVTALRM=26",
+    "Profiling Timer Expired, SIGPROF=27",
+    "Window Changed, SIGWINCH=28",
+    "I/O Possible, SIGIO=29",
+    "Power Failure, SIGPWR=30",
+    "Bad System Call, SIGSYS=31",
+];
+
 impl SignalFlags {
-    /// 将集合中的致命/错误类信号映射为标准退出码与原因
+    /// 由信号编号构造单个信号位
     ///
-    /// 若集合包含以下任意一个信号，则返回对应的 `(负退出码, 静态说明)`：
-    /// - `SIGINT`/`SIGILL`/`SIGABRT`/`SIGFPE`/`SIGKILL`/`SIGSEGV`
+    /// ## Arguments
+    /// * `signum` - 信号编号
     ///
-    /// 否则返回 `None`，表示不属于错误类（可能是可捕捉或控制类信号）。
-    pub fn check_error(&self) -> Option<(i32, &'static str)> {
-        if self.contains(Self::SIGINT) {
-            Some((-2, "Killed, SIGINT=2"))
-        } else if self.contains(Self::SIGILL) {
-            Some((-4, "Illegal Instruction, SIGILL=4"))
-        } else if self.contains(Self::SIGABRT) {
-            Some((-6, "Aborted, SIGABRT=6"))
-        } else if self.contains(Self::SIGFPE) {
-            Some((-8, "Erroneous Arithmetic Operation, SIGFPE=8"))
-        } else if self.contains(Self::SIGKILL) {
-            Some((-9, "Killed, SIGKILL=9"))
-        } else if self.contains(Self::SIGSEGV) {
-            Some((-11, "Segmentation Fault, SIGSEGV=11"))
+    /// ## Returns
+    /// 编号在 0..=MAX_SIG 内时返回 `Some(1 << signum)`，否则返回 `None`
+    pub fn from_signum(signum: usize) -> Option<Self> {
+        if signum > MAX_SIG {
+            None
         } else {
+            Self::from_bits(1 << signum)
+        }
+    }
+
+    /// 集合中编号最小的信号
+    pub fn lowest_signum(&self) -> Option<usize> {
+        if self.is_empty() {
             None
+        } else {
+            Some(self.bits().trailing_zeros() as usize)
+        }
+    }
+
+    /// 单个信号的 POSIX 默认动作
+    ///
+    /// 对集合调用时按其中编号最小的信号判定；`SIGDEF` 槽位与空集合均视为忽略，
+    /// 实时信号默认终止进程。
+    pub fn default_action(&self) -> SignalDefaultAction {
+        let Some(signum) = self.lowest_signum() else {
+            return SignalDefaultAction::Ignore;
+        };
+        if signum >= SIGRTMIN {
+            return SignalDefaultAction::Terminate;
+        }
+        let signal = Self::from_bits(1 << signum).unwrap();
+        if signal.intersects(Self::SIGDEF | Self::SIGCHLD | Self::SIGURG | Self::SIGWINCH) {
+            SignalDefaultAction::Ignore
+        } else if signal == Self::SIGCONT {
+            SignalDefaultAction::Continue
+        } else if signal.intersects(Self::SIGSTOP | Self::SIGTSTP | Self::SIGTTIN | Self::SIGTTOU) {
+            SignalDefaultAction::Stop
+        } else if signal.intersects(
+            Self::SIGQUIT
+                | Self::SIGILL
+                | Self::SIGTRAP
+                | Self::SIGABRT
+                | Self::SIGBUS
+                | Self::SIGFPE
+                | Self::SIGSEGV
+                | Self::SIGXCPU
+                | Self::SIGXFSZ
+                | Self::SIGSYS,
+        ) {
+            SignalDefaultAction::Core
+        } else {
+            SignalDefaultAction::Terminate
+        }
+    }
+
+    /// 将集合中的终止类信号映射为标准退出码与原因
+    ///
+    /// 找到集合中编号最小、默认动作为终止或核心转储的信号，返回
+    /// `(-signum, 静态说明)`；例如 `SIGSEGV` 对应 `(-11, "Segmentation Fault, SIGSEGV=11")`。
+    ///
+    /// 若不存在此类信号则返回 `None`（可能是可忽略或控制类信号）。
+    pub fn check_error(&self) -> Option<(i32, &'static str)> {
+        let mut rest = *self;
+        while let Some(signum) = rest.lowest_signum() {
+            let signal = Self::from_bits(1 << signum).unwrap();
+            match signal.default_action() {
+                SignalDefaultAction::Terminate | SignalDefaultAction::Core => {
+                    let msg = if signum >= SIGRTMIN {
+                        "Real-time signal"
+                    } else {
+                        SIGNAL_DESCRIPTIONS[signum]
+                    };
+                    return Some((-(signum as i32), msg));
+                }
+                _ => rest.remove(signal),
+            }
         }
+        None
     }
 }
 
 /// 用户态信号处理动作
 ///
-/// - `handler`：用户态处理函数入口（0 表示采用默认动作）
+/// - `handler`：用户态处理函数入口（[`SIG_DFL`] 表示默认动作，[`SIG_IGN`] 表示忽略）
 /// - `mask`：进入处理程序期间额外屏蔽的信号集合
 #[repr(C, align(16))]
 #[derive(Debug, Clone, Copy, PartialEq)]
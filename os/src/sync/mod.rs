@@ -6,7 +6,14 @@
 //! ## 主要组件
 //!
 //! - [`UPSafeCell`] - 单处理器安全的共享可变数据结构
+//! - [`WaitQueue`] - 阻塞进程的等待队列，用于等待设备中断等事件；
+//!   [`WakeWatcher`] 在队列被唤醒时得到通知
+//! - [`SleepLock`] - 持有期间允许睡眠的互斥锁
 
+mod sleep_lock;
 mod up;
+mod wait_queue;
 
+pub use sleep_lock::{SleepLock, SleepLockGuard};
 pub use up::UPSafeCell;
+pub use wait_queue::{WaitQueue, WakeWatcher};
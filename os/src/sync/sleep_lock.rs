@@ -0,0 +1,72 @@
+//! # 睡眠锁
+//!
+//! 持有期间允许进程阻塞（例如等待磁盘 I/O）的互斥锁。竞争者不会自旋，
+//! 而是在 [`WaitQueue`] 上睡眠，直到持有者释放锁。
+//!
+//! 单处理器下自旋锁在持有者睡眠时会让竞争者永远自旋，因此凡是可能在
+//! 持锁期间睡眠的代码路径（如文件系统）都必须使用睡眠锁。
+//!
+//! 没有当前进程时（内核启动阶段）锁不会发生竞争，可以直接获取。
+
+use super::{UPSafeCell, WaitQueue};
+use core::cell::UnsafeCell;
+use core::ops::{Deref, DerefMut};
+
+/// 可睡眠的互斥锁
+pub struct SleepLock<T> {
+    locked: UPSafeCell<bool>,
+    waiters: WaitQueue,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T> Sync for SleepLock<T> {}
+
+/// [`SleepLock`] 的守卫，离开作用域时释放锁并唤醒一个等待者
+pub struct SleepLockGuard<'a, T> {
+    lock: &'a SleepLock<T>,
+}
+
+impl<T> SleepLock<T> {
+    /// 创建未上锁的睡眠锁
+    pub fn new(data: T) -> Self {
+        Self {
+            locked: unsafe { UPSafeCell::new(false) },
+            waiters: WaitQueue::new(),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// 获取锁，锁被占用时睡眠等待
+    pub fn lock(&self) -> SleepLockGuard<'_, T> {
+        loop {
+            let mut locked = self.locked.exclusive_access();
+            if !*locked {
+                *locked = true;
+                return SleepLockGuard { lock: self };
+            }
+            drop(locked);
+            self.waiters.wait();
+        }
+    }
+}
+
+impl<T> Deref for SleepLockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> DerefMut for SleepLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for SleepLockGuard<'_, T> {
+    fn drop(&mut self) {
+        *self.lock.locked.exclusive_access() = false;
+        self.lock.waiters.wake_one();
+    }
+}
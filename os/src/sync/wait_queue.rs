@@ -0,0 +1,124 @@
+//! # 等待队列
+//!
+//! 保存因等待某个事件而被阻塞的进程。等待者调用 [`WaitQueue::wait`]
+//! 把自己登记到队列中并让出 CPU，事件发生时由中断处理程序或其他进程
+//! 调用 [`WaitQueue::wake_one`] / [`WaitQueue::wake_all`] 唤醒。
+//!
+//! ## 竞争条件
+//!
+//! 内核态运行时 `sstatus.SIE` 为 0，中断只会在返回用户态或调度器空闲时
+//! 被处理。因此“发起请求 → 登记等待 → 阻塞”这一序列不会被完成中断打断，
+//! 不存在唤醒丢失的问题。
+//!
+//! ## 监听者
+//!
+//! 除了阻塞的进程，队列上还可以挂接实现 [`WakeWatcher`] 的监听者（如 epoll
+//! 的兴趣项）。监听者在每次唤醒时得到通知，且不会因唤醒而离开队列；
+//! 队列只持有它的弱引用，监听者被释放后自动失效。
+
+use super::UPSafeCell;
+use crate::process::{
+    ProcessControlBlock, block_current_and_run_next, current_process, wakeup_process,
+};
+use alloc::collections::VecDeque;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+
+/// 等待队列的监听者，队列每次被唤醒时得到通知
+pub trait WakeWatcher: Send + Sync {
+    /// 队列被唤醒；可能在中断上下文中调用，不能阻塞
+    fn notify(self: Arc<Self>);
+}
+
+/// 被阻塞进程的 FIFO 队列
+pub struct WaitQueue {
+    queue: UPSafeCell<VecDeque<Arc<ProcessControlBlock>>>,
+    watchers: UPSafeCell<Vec<Weak<dyn WakeWatcher>>>,
+}
+
+impl WaitQueue {
+    /// 创建空的等待队列
+    pub fn new() -> Self {
+        Self {
+            queue: unsafe { UPSafeCell::new(VecDeque::new()) },
+            watchers: unsafe { UPSafeCell::new(Vec::new()) },
+        }
+    }
+
+    /// 阻塞当前进程直到被唤醒
+    ///
+    /// 返回时进程已被重新调度，调用者需要自行检查等待的条件是否满足。
+    /// 被信号唤醒时进程仍留在队列中，返回前撤销登记，避免之后的唤醒落到它身上。
+    pub fn wait(&self) {
+        let process = current_process().unwrap();
+        self.register(process.clone());
+        block_current_and_run_next();
+        self.remove(&process);
+    }
+
+    /// 把进程登记到队列中但不阻塞
+    ///
+    /// 用于同时等待多个队列的场景（如 `poll`）：调用者先在每个队列上登记，
+    /// 再自行阻塞，被唤醒后用 [`remove`](Self::remove) 撤销其余队列中的登记。
+    pub fn register(&self, process: Arc<ProcessControlBlock>) {
+        self.queue.exclusive_access().push_back(process);
+    }
+
+    /// 撤销进程在队列中的登记
+    pub fn remove(&self, process: &Arc<ProcessControlBlock>) {
+        self.queue
+            .exclusive_access()
+            .retain(|waiter| !Arc::ptr_eq(waiter, process));
+    }
+
+    /// 挂接监听者，直到监听者被释放为止
+    pub fn watch(&self, watcher: Weak<dyn WakeWatcher>) {
+        let mut watchers = self.watchers.exclusive_access();
+        watchers.retain(|watcher| watcher.strong_count() > 0);
+        watchers.push(watcher);
+    }
+
+    /// 通知全部仍然存活的监听者
+    ///
+    /// 先取出监听者再通知，监听者在通知中可以安全地访问本队列。
+    fn notify_watchers(&self) {
+        let watchers: Vec<_> = {
+            let mut watchers = self.watchers.exclusive_access();
+            watchers.retain(|watcher| watcher.strong_count() > 0);
+            watchers.iter().filter_map(Weak::upgrade).collect()
+        };
+        for watcher in watchers {
+            watcher.notify();
+        }
+    }
+
+    /// 唤醒最早进入队列的进程，并通知监听者
+    ///
+    /// ## Returns
+    ///
+    /// 唤醒了一个进程时返回 `true`；已被其他队列唤醒的进程被跳过
+    pub fn wake_one(&self) -> bool {
+        self.notify_watchers();
+        self.wake_one_process()
+    }
+
+    /// 唤醒队列中的全部进程，并通知监听者
+    pub fn wake_all(&self) {
+        self.notify_watchers();
+        while self.wake_one_process() {}
+    }
+
+    fn wake_one_process(&self) -> bool {
+        loop {
+            let process = self.queue.exclusive_access().pop_front();
+            match process {
+                Some(process) => {
+                    if wakeup_process(process) {
+                        return true;
+                    }
+                }
+                None => return false,
+            }
+        }
+    }
+}
@@ -10,7 +10,15 @@
 //! - [`sys_open`]    - 打开文件并返回文件描述符
 //! - [`sys_close`]   - 关闭文件描述符
 //! - [`sys_dup`]     - 复制文件描述符
+//! - [`sys_dup3`]    - 复制到指定的文件描述符
 //! - [`sys_pipe`]    - 创建管道
+//! - [`sys_ioctl`]   - 设备控制
+//! - [`sys_fcntl`]   - 文件描述符控制
+//! - [`sys_ppoll`]   - 等待多个文件描述符就绪
+//! - [`sys_pselect6`] - 以描述符集合的形式等待多个文件描述符就绪
+//! - [`sys_epoll_create1`] - 创建 epoll 实例
+//! - [`sys_epoll_ctl`] - 修改 epoll 兴趣集合
+//! - [`sys_epoll_pwait`] - 等待 epoll 兴趣集合中的文件就绪
 //!
 //! ## 文件描述符管理
 //!
@@ -22,13 +30,26 @@
 //!
 //! ## 地址空间转换
 //!
-//! 所有系统调用都通过 [`translated_byte_buffer`] 和 [`translated_str`]
-//! 安全地访问用户空间数据，确保地址空间隔离。
+//! 所有系统调用都通过 [`crate::mm`] 的用户内存访问函数（[`user_buffer`]、
+//! [`copy_str_from_user`] 等）读写用户空间数据，无效的指针返回 `-EFAULT`。
 
-use crate::fs::{OpenFlags, make_pipe, open_file};
-use crate::mm::{UserBuffer, translated_byte_buffer, translated_refmut, translated_str};
-use crate::process::{current_process, current_user_token};
+use crate::errno::Errno::{EBADF, EFAULT, EINTR, EINVAL, EMFILE, ENOENT, ENOMEM};
+use crate::fs::{
+    Epoll, EpollEvent, F_DUPFD, F_DUPFD_CLOEXEC, F_GETFD, F_SETFD, FD_CLOEXEC, FD_SETSIZE, FdSet,
+    File, OpenFlags, PollEvents, PollFd, PollTable, make_node, make_pipe, open_device, open_path,
+};
+use crate::mm::{
+    copy_from_user, copy_from_user_bytes, copy_str_from_user, copy_to_user, copy_to_user_bytes,
+    user_buffer, user_buffer_mut,
+};
+use crate::process::{
+    RLIMIT_NOFILE, SignalFlags, current_process, current_rlimit, current_signal_pending,
+    current_user_token,
+};
+use crate::timer::{TimeSpec, time_ms};
 use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::mem::size_of;
 
 /// 系统调用：向文件描述符写入数据
 ///
@@ -44,7 +65,8 @@ use alloc::sync::Arc;
 /// ## Returns
 ///
 /// - 成功时返回实际写入的字节数
-/// - 失败时返回 -1
+/// - `fd` 未打开或不可写时返回 `-EBADF`
+/// - 缓冲区不可读时返回 `-EFAULT`
 ///
 /// ## 错误情况
 ///
@@ -54,24 +76,27 @@ use alloc::sync::Arc;
 ///
 /// ## 安全考虑
 ///
-/// 通过 [`translated_byte_buffer`] 安全地访问用户空间缓冲区，
+/// 通过 [`user_buffer`] 检查并访问用户空间缓冲区，
 /// 确保地址空间隔离和内存安全。
 pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
     let token = current_user_token();
     let process = current_process().unwrap();
     let inner = process.inner_exclusive_access();
     if fd >= inner.fd_table.len() {
-        return -1;
+        return -EBADF;
     }
     if let Some(file) = &inner.fd_table[fd] {
         if !file.writable() {
-            return -1;
+            return -EBADF;
         }
         let file = file.clone();
         drop(inner);
-        file.write(UserBuffer::new(translated_byte_buffer(token, buf, len))) as isize
+        match user_buffer(token, buf, len) {
+            Ok(buf) => file.write(buf),
+            Err(errno) => errno,
+        }
     } else {
-        -1
+        -EBADF
     }
 }
 
@@ -90,7 +115,8 @@ pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
 ///
 /// - 成功时返回实际读取的字节数
 /// - 到达文件末尾时返回 0
-/// - 失败时返回 -1
+/// - `fd` 未打开或不可读时返回 `-EBADF`
+/// - 缓冲区不可写时返回 `-EFAULT`
 ///
 /// ## 错误情况
 ///
@@ -100,24 +126,27 @@ pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
 ///
 /// ## 安全考虑
 ///
-/// 通过 [`translated_byte_buffer`] 安全地访问用户空间缓冲区，
+/// 通过 [`user_buffer_mut`] 检查并访问用户空间缓冲区，
 /// 确保地址空间隔离和内存安全。
-pub fn sys_read(fd: usize, buf: *const u8, len: usize) -> isize {
+pub fn sys_read(fd: usize, buf: *mut u8, len: usize) -> isize {
     let token = current_user_token();
     let process = current_process().unwrap();
     let inner = process.inner_exclusive_access();
     if fd >= inner.fd_table.len() {
-        return -1;
+        return -EBADF;
     }
     if let Some(file) = &inner.fd_table[fd] {
         if !file.readable() {
-            return -1;
+            return -EBADF;
         }
         let file = file.clone();
         drop(inner);
-        file.read(UserBuffer::new(translated_byte_buffer(token, buf, len))) as isize
+        match user_buffer_mut(token, buf, len) {
+            Ok(buf) => file.read(buf),
+            Err(errno) => errno,
+        }
     } else {
-        -1
+        -EBADF
     }
 }
 
@@ -138,32 +167,81 @@ pub fn sys_read(fd: usize, buf: *const u8, len: usize) -> isize {
 /// - `O_RDWR` (2) - 读写模式
 /// - `O_CREAT` (64) - 如果文件不存在则创建
 /// - `O_TRUNC` (512) - 如果文件存在则截断
+/// - `O_NONBLOCK` / `O_APPEND` - 文件状态标志，之后可由 [`sys_fcntl`] 修改
+/// - `O_CLOEXEC` - 为新文件描述符设置 `FD_CLOEXEC`
+///
+/// 以 `/dev/` 开头的路径由 [`open_device`] 打开设备文件。
+/// 命名管道由 [`open_path`] 打开，可能阻塞到对端出现。
 ///
 /// ## Returns
 ///
 /// - 成功时返回新分配的文件描述符（非负整数）
-/// - 失败时返回 -1
+/// - 路径不可读时返回 `-EFAULT`，过长时返回 `-ENAMETOOLONG`
+/// - `flags` 含有未知的位时返回 `-EINVAL`
+/// - 文件不存在时返回 `-ENOENT`
+/// - 文件描述符数已达到 `RLIMIT_NOFILE` 时返回 `-EMFILE`
+/// - 打开命名管道失败时返回 `-ENXIO`、`-EINTR` 等错误码
 ///
 /// ## 错误情况
 ///
 /// - 文件路径无效或不存在
 /// - 权限不足
-/// - 文件描述符表已满
+/// - 文件描述符数已达到 `RLIMIT_NOFILE`
 ///
 /// ## 安全考虑
 ///
-/// 通过 [`translated_str`] 安全地读取用户空间的文件路径字符串。
+/// 通过 [`copy_str_from_user`] 检查并读取用户空间的文件路径字符串。
 pub fn sys_open(path: *const u8, flags: u32) -> isize {
     let process = current_process().unwrap();
     let token = current_user_token();
-    let path = translated_str(token, path);
-    if let Some(inode) = open_file(path.as_str(), OpenFlags::from_bits(flags).unwrap()) {
-        let mut inner = process.inner_exclusive_access();
-        let fd = inner.alloc_fd();
-        inner.fd_table[fd] = Some(inode);
-        fd as isize
+    let path = match copy_str_from_user(token, path) {
+        Ok(path) => path,
+        Err(errno) => return errno,
+    };
+    let Some(flags) = OpenFlags::from_bits(flags) else {
+        return -EINVAL;
+    };
+    let file = if path.starts_with("/dev/") {
+        open_device(path.as_str(), flags).ok_or(-ENOENT)
     } else {
-        -1
+        open_path(path.as_str(), flags)
+    };
+    match file {
+        Ok(inode) => {
+            let mut inner = process.inner_exclusive_access();
+            let Some(fd) = inner.alloc_fd() else {
+                return -EMFILE;
+            };
+            inner.fd_table[fd] = Some(inode);
+            if flags.contains(OpenFlags::CLOEXEC) {
+                inner.fd_cloexec.insert(fd);
+            }
+            fd as isize
+        }
+        Err(errno) => errno,
+    }
+}
+
+/// 系统调用：创建文件节点
+///
+/// 实现 `mknodat(2)`，用于创建命名管道（`mkfifo`）或空的普通文件。
+/// 文件系统只有根目录，`dirfd` 被忽略。
+///
+/// ## Arguments
+///
+/// * `dirfd` - 相对路径的起点目录，忽略
+/// * `path` - 指向用户空间以 `\0` 结尾的文件路径字符串
+/// * `mode` - 文件类型（`S_IFIFO`、`S_IFREG`）与权限位
+/// * `dev` - 设备号，不支持设备文件，忽略
+///
+/// ## Returns
+///
+/// 成功返回 0；路径不可读时返回 `-EFAULT`，其余错误码见 [`make_node`]
+pub fn sys_mknodat(_dirfd: isize, path: *const u8, mode: u32, _dev: usize) -> isize {
+    let token = current_user_token();
+    match copy_str_from_user(token, path) {
+        Ok(path) => make_node(path.as_str(), mode),
+        Err(errno) => errno,
     }
 }
 
@@ -179,12 +257,7 @@ pub fn sys_open(path: *const u8, flags: u32) -> isize {
 /// ## Returns
 ///
 /// - 成功时返回 0
-/// - 失败时返回 -1
-///
-/// ## 错误情况
-///
-/// - 文件描述符无效或超出范围
-/// - 文件描述符已经关闭
+/// - 文件描述符无效、超出范围或已经关闭时返回 `-EBADF`
 ///
 /// ## 资源管理
 ///
@@ -196,10 +269,10 @@ pub fn sys_close(fd: usize) -> isize {
     let process = current_process().unwrap();
     let mut inner = process.inner_exclusive_access();
     if fd >= inner.fd_table.len() {
-        return -1;
+        return -EBADF;
     }
     if inner.fd_table[fd].is_none() {
-        return -1;
+        return -EBADF;
     }
     inner.fd_table[fd].take();
     0
@@ -217,7 +290,8 @@ pub fn sys_close(fd: usize) -> isize {
 /// ## Returns
 ///
 /// - 成功：返回新的文件描述符编号
-/// - 失败：返回 -1（如 `fd` 无效或未打开）
+/// - `fd` 无效或未打开：返回 `-EBADF`
+/// - 文件描述符数已达到 `RLIMIT_NOFILE`：返回 `-EMFILE`
 ///
 /// ## 共享语义
 ///
@@ -227,45 +301,665 @@ pub fn sys_dup(fd: usize) -> isize {
     let process = current_process().unwrap();
     let mut inner = process.inner_exclusive_access();
     if fd >= inner.fd_table.len() {
-        return -1;
+        return -EBADF;
     }
     if inner.fd_table[fd].is_none() {
-        return -1;
+        return -EBADF;
     }
-    let new_fd = inner.alloc_fd();
+    let Some(new_fd) = inner.alloc_fd() else {
+        return -EMFILE;
+    };
     inner.fd_table[new_fd] = Some(Arc::clone(inner.fd_table[fd].as_ref().unwrap()));
     new_fd as isize
 }
 
+/// 系统调用：复制到指定的文件描述符（dup3）
+///
+/// 实现 `dup3(2)`：让 `new_fd` 引用 `old_fd` 的文件对象。若 `new_fd` 已打开，
+/// 先将其关闭；关闭与复制在一次持锁期间完成，不会有其他描述符分配插入其间。
+/// `dup2(2)` 由用户库在其上实现。
+///
+/// ## Arguments
+///
+/// * `old_fd` - 已打开的文件描述符
+/// * `new_fd` - 目标文件描述符
+/// * `flags` - 只接受 `O_CLOEXEC`，为 `new_fd` 设置 `FD_CLOEXEC`；否则清除
+///
+/// ## Returns
+///
+/// - 成功：返回 `new_fd`
+/// - `old_fd` 未打开或 `new_fd` 不小于 `RLIMIT_NOFILE`：返回 `-EBADF`
+/// - `old_fd == new_fd` 或 `flags` 含其他标志：返回 `-EINVAL`
+pub fn sys_dup3(old_fd: usize, new_fd: usize, flags: u32) -> isize {
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    let Some(Some(file)) = inner.fd_table.get(old_fd) else {
+        return -EBADF;
+    };
+    let file = file.clone();
+    if old_fd == new_fd || flags & !OpenFlags::CLOEXEC.bits() != 0 {
+        return -EINVAL;
+    }
+    if new_fd >= inner.rlimits.cur(RLIMIT_NOFILE) {
+        return -EBADF;
+    }
+    if new_fd >= inner.fd_table.len() {
+        inner.fd_table.resize(new_fd + 1, None);
+    }
+    let replaced = inner.fd_table[new_fd].replace(file);
+    if flags & OpenFlags::CLOEXEC.bits() != 0 {
+        inner.fd_cloexec.insert(new_fd);
+    } else {
+        inner.fd_cloexec.remove(&new_fd);
+    }
+    drop(inner);
+    // 被替换的文件在释放进程锁之后析构
+    drop(replaced);
+    new_fd as isize
+}
+
 /// 系统调用：创建管道
 ///
-/// 创建一对相互连接的文件描述符：`pipe[0]` 为读端、`pipe[1]` 为写端。
-/// 进程或父子进程间可通过该字节流进行单向通信。实现遵循 POSIX 语义：
+/// 实现 `pipe2(2)` 系统调用，创建一对相互连接的文件描述符：`pipe[0]` 为读端、
+/// `pipe[1]` 为写端。进程或父子进程间可通过该字节流进行单向通信。实现遵循 POSIX 语义：
 /// - 读端在缓冲区空且写端全部关闭时返回 0（EOF）
-/// - 写端在缓冲区满时阻塞（当前实现通过让出 CPU 实现）
+/// - 写端在缓冲区满时睡眠，读端全部关闭后返回 `-EPIPE` 并收到 `SIGPIPE`
 ///
 /// ## Arguments
 ///
 /// * `pipe` - 指向用户空间 usize[2] 的指针，用于写回读/写端 fd
+/// * `flags` - `O_NONBLOCK` 作用于两端，`O_CLOEXEC` 为两个描述符设置 `FD_CLOEXEC`
 ///
 /// ## Returns
 ///
-/// - 成功返回 0，失败返回 -1
+/// - 成功返回 0
+/// - `flags` 含有其他位时返回 `-EINVAL`
+/// - `pipe` 不可写时返回 `-EFAULT`，已分配的描述符被关闭
+/// - 文件描述符数已达到 `RLIMIT_NOFILE` 时返回 `-EMFILE`
+/// - 缓冲区内存耗尽时返回 `-ENOMEM`
 ///
 /// ## 安全考虑
 ///
-/// - 使用 `translated_refmut` 将两个 fd 写回到用户空间
+/// - 使用 [`copy_to_user`] 将两个 fd 写回到用户空间
 /// - fd 的实际分配来源于当前进程的 fd 表
-pub fn sys_pipe(pipe: *mut usize) -> isize {
+pub fn sys_pipe(pipe: *mut usize, flags: u32) -> isize {
+    let Some(flags) = OpenFlags::from_bits(flags) else {
+        return -EINVAL;
+    };
+    if !(flags - (OpenFlags::NONBLOCK | OpenFlags::CLOEXEC)).is_empty() {
+        return -EINVAL;
+    }
     let process = current_process().unwrap();
     let token = current_user_token();
+    let Some((pipe_read, pipe_write)) = make_pipe(flags) else {
+        return -ENOMEM;
+    };
     let mut inner = process.inner_exclusive_access();
-    let (pipe_read, pipe_write) = make_pipe();
-    let read_fd = inner.alloc_fd();
+    let Some(read_fd) = inner.alloc_fd() else {
+        return -EMFILE;
+    };
     inner.fd_table[read_fd] = Some(pipe_read);
-    let write_fd = inner.alloc_fd();
+    let Some(write_fd) = inner.alloc_fd() else {
+        inner.fd_table[read_fd] = None;
+        return -EMFILE;
+    };
     inner.fd_table[write_fd] = Some(pipe_write);
-    *translated_refmut(token, pipe) = read_fd;
-    *translated_refmut(token, unsafe { pipe.add(1) }) = write_fd;
+    if flags.contains(OpenFlags::CLOEXEC) {
+        inner.fd_cloexec.insert(read_fd);
+        inner.fd_cloexec.insert(write_fd);
+    }
+    if let Err(errno) = copy_to_user(token, pipe as *mut [usize; 2], &[read_fd, write_fd]) {
+        let ends = (
+            inner.fd_table[read_fd].take(),
+            inner.fd_table[write_fd].take(),
+        );
+        drop(inner);
+        drop(ends);
+        return errno;
+    }
     0
 }
+
+/// 系统调用：设备控制
+///
+/// 实现 `ioctl(2)` 系统调用，把命令转交给文件描述符对应的文件对象。
+/// 控制台终端与伪终端支持，用于读写终端属性、前台进程与窗口大小。
+///
+/// ## Arguments
+///
+/// * `fd` - 文件描述符
+/// * `cmd` - 控制命令（如 `TCGETS`、`TCSETS`、`TIOCSPGRP`）
+/// * `arg` - 命令参数，通常是指向用户空间的指针
+///
+/// ## Returns
+///
+/// - 文件对象返回的结果；不支持设备控制时为 `-ENOTTY`
+/// - 文件描述符无效时返回 `-EBADF`
+pub fn sys_ioctl(fd: usize, cmd: usize, arg: usize) -> isize {
+    let process = current_process().unwrap();
+    let inner = process.inner_exclusive_access();
+    if fd >= inner.fd_table.len() {
+        return -EBADF;
+    }
+    if let Some(file) = &inner.fd_table[fd] {
+        let file = file.clone();
+        drop(inner);
+        file.ioctl(cmd, arg)
+    } else {
+        -EBADF
+    }
+}
+
+/// 系统调用：文件描述符控制
+///
+/// 实现 `fcntl(2)` 系统调用。描述符级的命令在进程的文件描述符表上完成，
+/// 其余命令（`F_GETFL`、`F_SETFL`）交给文件对象的 [`File::fcntl`]。
+///
+/// ## Arguments
+///
+/// * `fd` - 文件描述符
+/// * `cmd` - 控制命令
+/// * `arg` - 命令参数
+///
+/// ## 支持的命令
+///
+/// - `F_DUPFD` / `F_DUPFD_CLOEXEC` - 复制到不小于 `arg` 的最小空闲描述符，
+///   后者同时为新描述符设置 `FD_CLOEXEC`
+/// - `F_GETFD` / `F_SETFD` - 读写描述符标志 `FD_CLOEXEC`
+/// - `F_GETFL` / `F_SETFL` - 读取访问模式与状态标志，修改 `O_NONBLOCK`、`O_APPEND`
+///
+/// ## Returns
+///
+/// - `F_DUPFD` 返回新描述符，`F_GETFD`/`F_GETFL` 返回标志，其余成功时返回 0
+/// - 文件描述符无效时返回 `-EBADF`
+/// - `F_DUPFD` 找不到小于 `RLIMIT_NOFILE` 的空闲描述符时返回 `-EMFILE`
+/// - 不支持的命令返回 `-EINVAL`
+pub fn sys_fcntl(fd: usize, cmd: usize, arg: usize) -> isize {
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    let Some(Some(file)) = inner.fd_table.get(fd) else {
+        return -EBADF;
+    };
+    let file = file.clone();
+    match cmd {
+        F_DUPFD | F_DUPFD_CLOEXEC => {
+            let Some(new_fd) = inner.alloc_fd_from(arg) else {
+                return -EMFILE;
+            };
+            inner.fd_table[new_fd] = Some(file);
+            if cmd == F_DUPFD_CLOEXEC {
+                inner.fd_cloexec.insert(new_fd);
+            }
+            new_fd as isize
+        }
+        F_GETFD => {
+            if inner.fd_cloexec.contains(&fd) {
+                FD_CLOEXEC as isize
+            } else {
+                0
+            }
+        }
+        F_SETFD => {
+            if arg & FD_CLOEXEC != 0 {
+                inner.fd_cloexec.insert(fd);
+            } else {
+                inner.fd_cloexec.remove(&fd);
+            }
+            0
+        }
+        _ => {
+            drop(inner);
+            file.fcntl(cmd, arg)
+        }
+    }
+}
+
+/// 等待的文件及其关心的事件
+type PollEntry = Option<(Arc<dyn File + Send + Sync>, PollEvents)>;
+
+/// 等待一组文件中的任一个就绪
+///
+/// `ppoll` 与 `pselect6` 的共同实现：查询每个文件的就绪事件，都没有就绪时
+/// 在各文件登记的等待队列上睡眠，被唤醒后重新查询。
+///
+/// ## Arguments
+///
+/// * `entries` - 文件及其关心的事件，`None` 项被跳过
+/// * `timeout_ms` - 超时毫秒数，`None` 表示一直等待，0 表示只查询不等待
+///
+/// ## Returns
+///
+/// - `Ok` - 与 `entries` 一一对应的就绪事件（已按关心的事件过滤）；超时则全部为空
+/// - `Err(-EINTR)` - 等待期间收到需要处理的信号
+fn poll_files(entries: &[PollEntry], timeout_ms: Option<usize>) -> Result<Vec<PollEvents>, isize> {
+    let deadline = timeout_ms.map(|timeout_ms| time_ms() + timeout_ms);
+    loop {
+        let revents: Vec<PollEvents> = entries
+            .iter()
+            .map(|entry| match entry {
+                Some((file, events)) => file.poll() & *events,
+                None => PollEvents::empty(),
+            })
+            .collect();
+        if revents.iter().any(|events| !events.is_empty()) {
+            return Ok(revents);
+        }
+        if deadline.is_some_and(|deadline| time_ms() >= deadline) {
+            return Ok(revents);
+        }
+        if current_signal_pending() {
+            return Err(-EINTR);
+        }
+        let mut table = PollTable::new();
+        for (file, _) in entries.iter().flatten() {
+            file.poll_wait(&mut table);
+        }
+        table.wait(deadline);
+    }
+}
+
+/// 读取用户空间的超时参数，空指针表示一直等待
+fn read_timeout(timeout: *const TimeSpec) -> Result<Option<usize>, isize> {
+    if timeout.is_null() {
+        return Ok(None);
+    }
+    copy_from_user(current_user_token(), timeout).map(|timeout| Some(timeout.as_ms()))
+}
+
+/// 在等待期间临时替换信号掩码
+///
+/// ## Returns
+///
+/// 原来的信号掩码，`sigmask` 为空指针时返回 `None`；`sigmask` 不可读时返回 `-EFAULT`
+fn replace_sigmask(sigmask: *const u64) -> Result<Option<SignalFlags>, isize> {
+    if sigmask.is_null() {
+        return Ok(None);
+    }
+    let mask = SignalFlags::from_bits_truncate(copy_from_user(current_user_token(), sigmask)?);
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    let old_mask = inner.signal_mask;
+    inner.signal_mask = mask - (SignalFlags::SIGKILL | SignalFlags::SIGSTOP);
+    Ok(Some(old_mask))
+}
+
+/// 恢复 [`replace_sigmask`] 替换前的信号掩码
+fn restore_sigmask(old_mask: Option<SignalFlags>) {
+    if let Some(old_mask) = old_mask {
+        current_process()
+            .unwrap()
+            .inner_exclusive_access()
+            .signal_mask = old_mask;
+    }
+}
+
+/// 取出文件描述符对应的文件
+pub(super) fn get_file(fd: usize) -> Option<Arc<dyn File + Send + Sync>> {
+    let process = current_process().unwrap();
+    let inner = process.inner_exclusive_access();
+    inner.fd_table.get(fd).cloned().flatten()
+}
+
+/// 系统调用：等待多个文件描述符就绪
+///
+/// 实现 `ppoll(2)` 系统调用。管道、终端、伪终端报告真实的就绪状态，
+/// 普通文件总是可读写。
+///
+/// ## Arguments
+///
+/// * `fds` - 用户空间 `struct pollfd` 数组，返回时填写每项的 `revents`
+/// * `nfds` - 数组长度
+/// * `timeout` - 超时时间，空指针表示一直等待；不回写剩余时间
+/// * `sigmask` - 等待期间使用的信号掩码，空指针表示不改变
+///
+/// ## Returns
+///
+/// - 就绪的项数，超时返回 0
+/// - `nfds` 超过 `RLIMIT_NOFILE` 时返回 `-EINVAL`
+/// - `fds`、`timeout` 或 `sigmask` 不可访问时返回 `-EFAULT`
+/// - 等待期间被信号打断时返回 `-EINTR`
+///
+/// ## 说明
+///
+/// `fd` 为负数的项被忽略，`revents` 置 0；无效的描述符报告 `POLLNVAL`。
+/// `POLLERR`、`POLLHUP` 无论是否请求都会报告。
+pub fn sys_ppoll(
+    fds: *mut PollFd,
+    nfds: usize,
+    timeout: *const TimeSpec,
+    sigmask: *const u64,
+) -> isize {
+    if nfds > current_rlimit(RLIMIT_NOFILE) {
+        return -EINVAL;
+    }
+    let token = current_user_token();
+    let mut pollfds = Vec::with_capacity(nfds);
+    for i in 0..nfds {
+        match copy_from_user(token, fds.wrapping_add(i)) {
+            Ok(pollfd) => pollfds.push(pollfd),
+            Err(errno) => return errno,
+        }
+    }
+    // revents 在等待之后才写回，先确认整个数组可写
+    if user_buffer_mut(token, fds as *mut u8, nfds * size_of::<PollFd>()).is_err() {
+        return -EFAULT;
+    }
+    let mut entries = Vec::with_capacity(nfds);
+    let mut invalid = Vec::with_capacity(nfds);
+    for pollfd in pollfds.iter() {
+        let file = if pollfd.fd < 0 {
+            None
+        } else {
+            get_file(pollfd.fd as usize)
+        };
+        invalid.push(pollfd.fd >= 0 && file.is_none());
+        let events = PollEvents::from_bits_truncate(pollfd.events as u16) | PollEvents::always();
+        entries.push(file.map(|file| (file, events)));
+    }
+    // 无效描述符本身就是就绪事件，不再等待
+    let timeout_ms = if invalid.contains(&true) {
+        Some(0)
+    } else {
+        match read_timeout(timeout) {
+            Ok(timeout_ms) => timeout_ms,
+            Err(errno) => return errno,
+        }
+    };
+
+    let old_mask = match replace_sigmask(sigmask) {
+        Ok(old_mask) => old_mask,
+        Err(errno) => return errno,
+    };
+    let result = poll_files(&entries, timeout_ms);
+    restore_sigmask(old_mask);
+    let revents = match result {
+        Ok(revents) => revents,
+        Err(errno) => return errno,
+    };
+
+    let mut ready = 0;
+    for (i, mut events) in revents.into_iter().enumerate() {
+        if invalid[i] {
+            events = PollEvents::POLLNVAL;
+        }
+        if !events.is_empty() {
+            ready += 1;
+        }
+        pollfds[i].revents = events.bits() as i16;
+    }
+    let bytes = unsafe {
+        core::slice::from_raw_parts(pollfds.as_ptr() as *const u8, nfds * size_of::<PollFd>())
+    };
+    match copy_to_user_bytes(token, fds as *mut u8, bytes) {
+        Ok(()) => ready,
+        Err(errno) => errno,
+    }
+}
+
+/// 从用户空间读取描述符集合中前 `nfds` 位，空指针返回 `None`
+fn read_fd_set(set: *const FdSet, nfds: usize) -> Result<Option<FdSet>, isize> {
+    if set.is_null() {
+        return Ok(None);
+    }
+    let mut fd_set = FdSet::empty();
+    let bytes = &mut fd_set.as_bytes_mut()[..nfds.div_ceil(8)];
+    copy_from_user_bytes(current_user_token(), set as *const u8, bytes)?;
+    Ok(Some(fd_set))
+}
+
+/// 把描述符集合的前 `nfds` 位写回用户空间
+fn write_fd_set(set: *mut FdSet, nfds: usize, fd_set: &FdSet) -> Result<(), isize> {
+    if set.is_null() {
+        return Ok(());
+    }
+    let bytes = &fd_set.as_bytes()[..nfds.div_ceil(8)];
+    copy_to_user_bytes(current_user_token(), set as *mut u8, bytes)
+}
+
+/// 系统调用：以描述符集合的形式等待多个文件描述符就绪
+///
+/// 实现 `pselect6(2)` 系统调用，在 [`sys_ppoll`] 的基础上按 `select` 的语义
+/// 换算事件：挂断与错误视为可读，错误视为可写，`POLLPRI` 对应异常集合。
+///
+/// ## Arguments
+///
+/// * `nfds` - 最大的描述符加 1，不超过 [`FD_SETSIZE`]
+/// * `readfds` / `writefds` / `exceptfds` - 关心可读、可写、异常的描述符集合，
+///   返回时只保留就绪的描述符；可以为空指针
+/// * `timeout` - 超时时间，空指针表示一直等待；不回写剩余时间
+/// * `sigmask` - 指向等待期间使用的信号掩码的指针，空指针表示不改变
+///
+/// ## Returns
+///
+/// - 三个集合中就绪描述符的总数，超时返回 0
+/// - 集合中包含无效描述符时返回 `-EBADF`
+/// - `nfds` 超过 [`FD_SETSIZE`] 时返回 `-EINVAL`
+/// - 集合、`timeout` 或 `sigmask` 不可访问时返回 `-EFAULT`
+/// - 等待期间被信号打断时返回 `-EINTR`，此时集合不被修改
+///
+/// ## 说明
+///
+/// Linux 的 `pselect6` 第六个参数指向 `{sigset_t *, size_t}`，本内核直接传递掩码指针。
+pub fn sys_pselect6(
+    nfds: usize,
+    readfds: *mut FdSet,
+    writefds: *mut FdSet,
+    exceptfds: *mut FdSet,
+    timeout: *const TimeSpec,
+    sigmask: *const u64,
+) -> isize {
+    if nfds > FD_SETSIZE {
+        return -EINVAL;
+    }
+    let sets = (
+        read_fd_set(readfds, nfds),
+        read_fd_set(writefds, nfds),
+        read_fd_set(exceptfds, nfds),
+        read_timeout(timeout),
+    );
+    let (Ok(read_set), Ok(write_set), Ok(except_set), Ok(timeout_ms)) = sets else {
+        return -EFAULT;
+    };
+    let in_set = |set: &Option<FdSet>, fd| set.as_ref().is_some_and(|set| set.contains(fd));
+    let read_events = PollEvents::POLLIN | PollEvents::POLLHUP | PollEvents::POLLERR;
+    let write_events = PollEvents::POLLOUT | PollEvents::POLLERR;
+    let except_events = PollEvents::POLLPRI;
+
+    let mut entries = Vec::with_capacity(nfds);
+    for fd in 0..nfds {
+        let mut events = PollEvents::empty();
+        if in_set(&read_set, fd) {
+            events |= read_events;
+        }
+        if in_set(&write_set, fd) {
+            events |= write_events;
+        }
+        if in_set(&except_set, fd) {
+            events |= except_events;
+        }
+        if events.is_empty() {
+            entries.push(None);
+            continue;
+        }
+        let Some(file) = get_file(fd) else {
+            return -EBADF;
+        };
+        entries.push(Some((file, events)));
+    }
+
+    let old_mask = match replace_sigmask(sigmask) {
+        Ok(old_mask) => old_mask,
+        Err(errno) => return errno,
+    };
+    let result = poll_files(&entries, timeout_ms);
+    restore_sigmask(old_mask);
+    let revents = match result {
+        Ok(revents) => revents,
+        Err(errno) => return errno,
+    };
+
+    let mut ready = 0;
+    let mut ready_sets = [FdSet::empty(), FdSet::empty(), FdSet::empty()];
+    for (fd, events) in revents.into_iter().enumerate() {
+        let checks = [
+            (&read_set, read_events),
+            (&write_set, write_events),
+            (&except_set, except_events),
+        ];
+        for (ready_set, (set, mask)) in ready_sets.iter_mut().zip(checks) {
+            if in_set(set, fd) && events.intersects(mask) {
+                ready_set.insert(fd);
+                ready += 1;
+            }
+        }
+    }
+    let written = [
+        write_fd_set(readfds, nfds, &ready_sets[0]),
+        write_fd_set(writefds, nfds, &ready_sets[1]),
+        write_fd_set(exceptfds, nfds, &ready_sets[2]),
+    ];
+    if written.iter().any(Result::is_err) {
+        return -EFAULT;
+    }
+    ready
+}
+
+/// 系统调用：创建 epoll 实例
+///
+/// 实现 `epoll_create1(2)` 系统调用。实例本身是一个文件，随文件描述符在
+/// `fork` 时被子进程继承，与父进程共享同一个兴趣集合。
+///
+/// ## Arguments
+///
+/// * `flags` - 0 或 `EPOLL_CLOEXEC`（与 `O_CLOEXEC` 取值相同）
+///
+/// ## Returns
+///
+/// - 新的文件描述符
+/// - `flags` 含有其他位时返回 `-EINVAL`
+/// - 文件描述符数已达到 `RLIMIT_NOFILE` 时返回 `-EMFILE`
+pub fn sys_epoll_create1(flags: u32) -> isize {
+    let Some(flags) = OpenFlags::from_bits(flags) else {
+        return -EINVAL;
+    };
+    if !(flags - OpenFlags::CLOEXEC).is_empty() {
+        return -EINVAL;
+    }
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    let Some(fd) = inner.alloc_fd() else {
+        return -EMFILE;
+    };
+    inner.fd_table[fd] = Some(Epoll::new());
+    if flags.contains(OpenFlags::CLOEXEC) {
+        inner.fd_cloexec.insert(fd);
+    }
+    fd as isize
+}
+
+/// 系统调用：修改 epoll 兴趣集合
+///
+/// 实现 `epoll_ctl(2)` 系统调用。
+///
+/// ## Arguments
+///
+/// * `epfd` - epoll 实例的文件描述符
+/// * `op` - `EPOLL_CTL_ADD`（1）、`EPOLL_CTL_DEL`（2）或 `EPOLL_CTL_MOD`（3）
+/// * `fd` - 被监视的文件描述符
+/// * `event` - 关心的事件与用户数据，`EPOLL_CTL_DEL` 时可以为空指针
+///
+/// ## Returns
+///
+/// - 成功返回 0
+/// - `epfd` 或 `fd` 无效时返回 `-EBADF`
+/// - `epfd` 不是 epoll 实例、`fd` 与 `epfd` 相同或 `op` 无效时返回 `-EINVAL`
+/// - `event` 不可读时返回 `-EFAULT`
+/// - 添加已存在的描述符返回 `-EEXIST`，修改或删除不存在的描述符返回 `-ENOENT`
+///
+/// ## 说明
+///
+/// 兴趣项按文件描述符号与文件共同标识：描述符关闭且文件被释放后，
+/// 兴趣项自动失效，同一描述符号可以重新添加。普通文件总是就绪。
+pub fn sys_epoll_ctl(epfd: usize, op: usize, fd: usize, event: *const EpollEvent) -> isize {
+    let (Some(epoll_file), Some(file)) = (get_file(epfd), get_file(fd)) else {
+        return -EBADF;
+    };
+    let Some(epoll) = epoll_file.as_epoll() else {
+        return -EINVAL;
+    };
+    if epfd == fd {
+        return -EINVAL;
+    }
+    let event = if event.is_null() {
+        EpollEvent::default()
+    } else {
+        match copy_from_user(current_user_token(), event) {
+            Ok(event) => event,
+            Err(errno) => return errno,
+        }
+    };
+    epoll.ctl(op, fd, &file, event)
+}
+
+/// 系统调用：等待 epoll 兴趣集合中的文件就绪
+///
+/// 实现 `epoll_pwait(2)` 系统调用。只检查就绪列表中的兴趣项，
+/// 不逐个扫描整个兴趣集合。
+///
+/// ## Arguments
+///
+/// * `epfd` - epoll 实例的文件描述符
+/// * `events` - 用户空间 `struct epoll_event` 数组，用于返回就绪事件
+/// * `maxevents` - 数组长度，必须大于 0
+/// * `timeout` - 超时毫秒数，负数表示一直等待，0 表示只查询不等待
+/// * `sigmask` - 等待期间使用的信号掩码，空指针表示不改变
+///
+/// ## Returns
+///
+/// - 写入 `events` 的事件数，超时返回 0
+/// - `epfd` 无效时返回 `-EBADF`
+/// - `epfd` 不是 epoll 实例或 `maxevents` 不大于 0 时返回 `-EINVAL`
+/// - `events` 或 `sigmask` 不可访问时返回 `-EFAULT`，此时不等待
+/// - 等待期间被信号打断时返回 `-EINTR`
+pub fn sys_epoll_pwait(
+    epfd: usize,
+    events: *mut EpollEvent,
+    maxevents: isize,
+    timeout: isize,
+    sigmask: *const u64,
+) -> isize {
+    let Some(epoll_file) = get_file(epfd) else {
+        return -EBADF;
+    };
+    let Some(epoll) = epoll_file.as_epoll() else {
+        return -EINVAL;
+    };
+    if maxevents <= 0 {
+        return -EINVAL;
+    }
+    let timeout_ms = (timeout >= 0).then_some(timeout as usize);
+    // 取出的事件不能退回就绪列表，等待前先确认整个数组可写
+    let token = current_user_token();
+    let len = (maxevents as usize).saturating_mul(size_of::<EpollEvent>());
+    if user_buffer_mut(token, events as *mut u8, len).is_err() {
+        return -EFAULT;
+    }
+
+    let old_mask = match replace_sigmask(sigmask) {
+        Ok(old_mask) => old_mask,
+        Err(errno) => return errno,
+    };
+    let result = epoll.wait(maxevents as usize, timeout_ms);
+    restore_sigmask(old_mask);
+    let ready = match result {
+        Ok(ready) => ready,
+        Err(errno) => return errno,
+    };
+
+    for (i, event) in ready.iter().enumerate() {
+        if let Err(errno) = copy_to_user(token, events.wrapping_add(i), event) {
+            return errno;
+        }
+    }
+    ready.len() as isize
+}
@@ -0,0 +1,352 @@
+//! # IPC 端点相关系统调用
+//!
+//! 端点与消息的语义由 [`crate::ipc`] 实现，这里负责句柄表的管理、
+//! 在寄存器与 [`IpcMessage`] 之间搬运消息字，以及在用户缓冲区与消息之间交换页帧。
+//!
+//! 这些系统调用不是 Linux 的一部分，编号取自 Linux 未使用的 500 起的区间。
+//!
+//! ## 支持的系统调用
+//!
+//! - [`sys_ipc_create`] - 创建端点
+//! - [`sys_ipc_close`]  - 关闭端点句柄
+//! - [`sys_ipc_send`]   - 发送消息
+//! - [`sys_ipc_recv`]   - 接收消息
+//! - [`sys_ipc_call`]   - 发送消息并等待应答
+//! - [`sys_ipc_reply`]  - 应答最近一次接收的 `call`
+//!
+//! ## 页转移
+//!
+//! 发送方缓冲区所在的页被整页移交给接收方，发送方的这些页随即被替换为清零的页，
+//! 因此 `len` 之后直到页尾的内容也会对接收方可见。缓冲区必须按页对齐，
+//! 并且位于同一个可写的普通（非共享）映射区域中。
+
+use crate::config::PAGE_SIZE;
+use crate::errno::Errno::{EBADF, EFAULT, EINVAL, EMFILE, EMSGSIZE, ENOMEM};
+use crate::ipc::{Endpoint, EndpointHandle, IPC_MAX_PAGES, IPC_MSG_WORDS, IpcBuffer, IpcMessage};
+use crate::mm::{FrameTracker, VirtAddr, copy_from_user, copy_to_user, frame_alloc};
+use crate::process::{current_process, current_trap_cx, current_user_token};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// 每个进程最多持有的端点句柄数
+const IPC_MAX_HANDLES: usize = 64;
+/// `ipc_recv` 标志：没有消息时立即返回 `EAGAIN`
+const IPC_NONBLOCK: usize = 1;
+
+/// 取出句柄指向的端点
+fn get_endpoint(handle: usize) -> Result<Arc<Endpoint>, isize> {
+    let process = current_process().unwrap();
+    let inner = process.inner_exclusive_access();
+    match inner.ipc_table.get(handle) {
+        Some(Some(handle)) => Ok(handle.endpoint()),
+        _ => Err(-EBADF),
+    }
+}
+
+/// 读取用户空间的缓冲区描述，空指针表示没有缓冲区
+///
+/// `writable` 为真时同时确认描述可写，使阻塞返回后能写回 `len`。
+fn read_buffer(buf: *const IpcBuffer, writable: bool) -> Result<IpcBuffer, isize> {
+    if buf.is_null() {
+        return Ok(IpcBuffer::default());
+    }
+    let token = current_user_token();
+    let buffer = copy_from_user(token, buf)?;
+    if writable {
+        copy_to_user(token, buf as *mut IpcBuffer, &buffer)?;
+    }
+    Ok(buffer)
+}
+
+/// 校验 `[addr, addr + len)` 可以按页交换，返回页数
+fn check_pages(addr: usize, len: usize) -> Result<usize, isize> {
+    if len == 0 {
+        return Ok(0);
+    }
+    if addr % PAGE_SIZE != 0 {
+        return Err(-EINVAL);
+    }
+    let pages = len.div_ceil(PAGE_SIZE);
+    if pages > IPC_MAX_PAGES {
+        return Err(-EMSGSIZE);
+    }
+    let process = current_process().unwrap();
+    let inner = process.inner_exclusive_access();
+    if !inner.memory_set.framed_pages(VirtAddr::from(addr), pages) {
+        return Err(-EFAULT);
+    }
+    Ok(pages)
+}
+
+/// 用清零的新页帧换出发送缓冲区，取得数据所在的页帧
+fn take_pages(addr: usize, len: usize) -> Result<Vec<FrameTracker>, isize> {
+    let pages = check_pages(addr, len)?;
+    let mut frames = Vec::with_capacity(pages);
+    for _ in 0..pages {
+        frames.push(frame_alloc().ok_or(-ENOMEM)?);
+    }
+    if frames.is_empty() {
+        return Ok(frames);
+    }
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    Ok(inner
+        .memory_set
+        .exchange_frames(VirtAddr::from(addr), frames))
+}
+
+/// 把页帧换入 `addr` 处的缓冲区，原有的页帧被释放
+///
+/// 缓冲区在阻塞期间不会变化，但仍重新校验，校验失败时丢弃数据。
+fn put_pages(addr: usize, frames: Vec<FrameTracker>) {
+    if frames.is_empty() || check_pages(addr, frames.len() * PAGE_SIZE).is_err() {
+        return;
+    }
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    inner
+        .memory_set
+        .exchange_frames(VirtAddr::from(addr), frames);
+}
+
+/// 把收到的消息交给当前进程：页帧换入缓冲区，字数写入 `a1`~`a4`
+///
+/// ## Returns
+///
+/// 写回 `len` 失败时返回 `-EFAULT`
+fn deliver(buf: *mut IpcBuffer, buffer: IpcBuffer, message: IpcMessage) -> Result<(), isize> {
+    put_pages(buffer.addr, message.frames);
+    let cx = current_trap_cx();
+    cx.x[11..11 + IPC_MSG_WORDS].copy_from_slice(&message.words);
+    if buf.is_null() {
+        return Ok(());
+    }
+    let buffer = IpcBuffer {
+        len: message.len,
+        ..buffer
+    };
+    copy_to_user(current_user_token(), buf, &buffer)
+}
+
+/// 系统调用：创建端点
+///
+/// ## Returns
+///
+/// 新端点的句柄；句柄已达上限时返回 `-EMFILE`
+pub fn sys_ipc_create() -> isize {
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    let handle = match inner.ipc_table.iter().position(Option::is_none) {
+        Some(handle) => handle,
+        None if inner.ipc_table.len() < IPC_MAX_HANDLES => {
+            inner.ipc_table.push(None);
+            inner.ipc_table.len() - 1
+        }
+        None => return -EMFILE,
+    };
+    inner.ipc_table[handle] = Some(EndpointHandle::new());
+    handle as isize
+}
+
+/// 系统调用：关闭端点句柄
+///
+/// ## Returns
+///
+/// - 0：成功
+/// - `-EBADF`：句柄无效
+pub fn sys_ipc_close(handle: usize) -> isize {
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    let Some(endpoint) = inner.ipc_table.get_mut(handle).and_then(Option::take) else {
+        return -EBADF;
+    };
+    // 丢弃句柄可能唤醒其他进程，不在持有进程控制块时进行
+    drop(inner);
+    drop(endpoint);
+    0
+}
+
+/// 系统调用：发送消息并阻塞到它被接收
+///
+/// ## Arguments
+///
+/// * `handle` - 端点句柄
+/// * `words` - 消息字
+/// * `buf` - 按页转移的数据（`addr`/`len`），可以为空
+///
+/// ## Returns
+///
+/// - 0：消息已被接收
+/// - `-EBADF`：句柄无效
+/// - `-EINVAL` / `-EFAULT` / `-EMSGSIZE`：缓冲区未对齐、不可交换或超过 [`IPC_MAX_PAGES`] 页
+/// - `-EPIPE`：端点没有其他句柄
+/// - `-EMSGSIZE`：接收方缓冲区不足
+/// - `-EINTR`：被信号中断，消息被撤回
+///
+/// 失败时缓冲区的内容被恢复。
+pub fn sys_ipc_send(handle: usize, words: [usize; IPC_MSG_WORDS], buf: *const IpcBuffer) -> isize {
+    let endpoint = match get_endpoint(handle) {
+        Ok(endpoint) => endpoint,
+        Err(errno) => return errno,
+    };
+    let buffer = match read_buffer(buf, false) {
+        Ok(buffer) => buffer,
+        Err(errno) => return errno,
+    };
+    let frames = match take_pages(buffer.addr, buffer.len) {
+        Ok(frames) => frames,
+        Err(errno) => return errno,
+    };
+    let message = IpcMessage {
+        words,
+        frames,
+        len: buffer.len,
+    };
+    let pid = current_process().unwrap().getpid();
+    match endpoint.send(pid, message, None) {
+        Ok(_) => 0,
+        Err((errno, message)) => {
+            if let Some(message) = message {
+                put_pages(buffer.addr, message.frames);
+            }
+            errno
+        }
+    }
+}
+
+/// 系统调用：接收消息
+///
+/// 消息来自 `ipc_call` 时，当前进程取得应答权，直到 `ipc_reply` 或下一次
+/// `ipc_recv`；此前未应答的调用者以 `EPIPE` 失败。
+///
+/// ## Arguments
+///
+/// * `handle` - 端点句柄
+/// * `buf` - 接收数据的缓冲区（`addr`/`cap`），收到的字节数写回 `len`；可以为空
+/// * `flags` - `IPC_NONBLOCK`
+///
+/// ## Returns
+///
+/// 发送方 PID，消息字写入 `a1`~`a4`；失败时返回 `-EBADF`、`-EINVAL`、`-EFAULT`、
+/// `-EMSGSIZE`（消息超出缓冲区，退回发送方）、`-EPIPE`（端点没有其他句柄且没有消息）、
+/// `-EAGAIN` 或 `-EINTR`
+pub fn sys_ipc_recv(handle: usize, buf: *mut IpcBuffer, flags: usize) -> isize {
+    let endpoint = match get_endpoint(handle) {
+        Ok(endpoint) => endpoint,
+        Err(errno) => return errno,
+    };
+    let buffer = match read_buffer(buf, true) {
+        Ok(buffer) => buffer,
+        Err(errno) => return errno,
+    };
+    let cap = buffer.cap.min(IPC_MAX_PAGES * PAGE_SIZE);
+    if let Err(errno) = check_pages(buffer.addr, cap) {
+        return errno;
+    }
+    let process = current_process().unwrap();
+    let stale = process.inner_exclusive_access().ipc_reply.take();
+    drop(stale);
+    match endpoint.recv(cap, flags & IPC_NONBLOCK != 0) {
+        Ok((sender, message, reply)) => {
+            process.inner_exclusive_access().ipc_reply = reply;
+            match deliver(buf, buffer, message) {
+                Ok(()) => sender as isize,
+                Err(errno) => errno,
+            }
+        }
+        Err(errno) => errno,
+    }
+}
+
+/// 系统调用：发送消息并阻塞到接收方应答
+///
+/// ## Arguments
+///
+/// * `handle` - 端点句柄
+/// * `words` - 消息字
+/// * `buf` - 发送的数据（`addr`/`len`）与接收应答的容量（`cap`），应答数据换入同一缓冲区，
+///   字节数写回 `len`；可以为空
+///
+/// ## Returns
+///
+/// - 0：收到应答，应答字写入 `a1`~`a4`
+/// - 其余错误同 [`sys_ipc_send`]；接收方未应答就放弃应答权时为 `-EPIPE`，
+///   应答超出 `cap` 时为 `-EMSGSIZE`
+pub fn sys_ipc_call(handle: usize, words: [usize; IPC_MSG_WORDS], buf: *mut IpcBuffer) -> isize {
+    let endpoint = match get_endpoint(handle) {
+        Ok(endpoint) => endpoint,
+        Err(errno) => return errno,
+    };
+    let buffer = match read_buffer(buf, true) {
+        Ok(buffer) => buffer,
+        Err(errno) => return errno,
+    };
+    let cap = buffer.cap.min(IPC_MAX_PAGES * PAGE_SIZE);
+    if let Err(errno) = check_pages(buffer.addr, cap) {
+        return errno;
+    }
+    let frames = match take_pages(buffer.addr, buffer.len) {
+        Ok(frames) => frames,
+        Err(errno) => return errno,
+    };
+    let message = IpcMessage {
+        words,
+        frames,
+        len: buffer.len,
+    };
+    let pid = current_process().unwrap().getpid();
+    match endpoint.send(pid, message, Some(cap)) {
+        Ok(reply) => match deliver(buf, buffer, reply.unwrap()) {
+            Ok(()) => 0,
+            Err(errno) => errno,
+        },
+        Err((errno, message)) => {
+            if let Some(message) = message {
+                put_pages(buffer.addr, message.frames);
+            }
+            errno
+        }
+    }
+}
+
+/// 系统调用：应答最近一次 `ipc_recv` 取得的 `call`
+///
+/// ## Arguments
+///
+/// * `words` - 应答字
+/// * `buf` - 按页转移的应答数据（`addr`/`len`），可以为空
+///
+/// ## Returns
+///
+/// - 0：成功
+/// - `-EINVAL`：没有待应答的 `call`，或缓冲区未对齐
+/// - `-EFAULT` / `-EMSGSIZE`：缓冲区不可交换或超出调用者的容量
+/// - `-EPIPE`：调用者已放弃等待
+///
+/// 无论成败应答权都被用掉；失败时缓冲区的内容被恢复。
+pub fn sys_ipc_reply(words: [usize; IPC_MSG_WORDS], buf: *const IpcBuffer) -> isize {
+    let process = current_process().unwrap();
+    let Some(reply) = process.inner_exclusive_access().ipc_reply.take() else {
+        return -EINVAL;
+    };
+    let buffer = match read_buffer(buf, false) {
+        Ok(buffer) => buffer,
+        Err(errno) => return errno,
+    };
+    let frames = match take_pages(buffer.addr, buffer.len) {
+        Ok(frames) => frames,
+        Err(errno) => return errno,
+    };
+    let message = IpcMessage {
+        words,
+        frames,
+        len: buffer.len,
+    };
+    match reply.reply(message) {
+        Ok(()) => 0,
+        Err((errno, message)) => {
+            put_pages(buffer.addr, message.frames);
+            errno
+        }
+    }
+}
@@ -9,21 +9,51 @@
 //!   - [`sys_open`]     - 打开文件
 //!   - [`sys_close`]    - 关闭文件
 //!   - [`sys_dup`]    - 复制文件描述符
+//!   - [`sys_dup3`]   - 复制到指定的文件描述符
 //!   - [`sys_pipe`]    - 创建管道
+//!   - [`sys_mknodat`] - 创建命名管道或普通文件
+//!   - [`sys_fcntl`]   - 文件描述符控制（复制、close-on-exec、状态标志）
+//!   - [`sys_ioctl`]   - 设备控制（终端属性）
+//!   - [`sys_ppoll`]   - 等待多个文件描述符就绪
+//!   - [`sys_pselect6`] - 以描述符集合的形式等待多个文件描述符就绪
+//!   - [`sys_epoll_create1`] / [`sys_epoll_ctl`] / [`sys_epoll_pwait`] - epoll
 //!   - [`sys_read`]  - 从文件描述符读取数据
 //!   - [`sys_write`] - 向文件描述符写入数据
+//! - **套接字**:
+//!   - [`sys_socket`]   - 创建套接字
+//!   - [`sys_bind`] / [`sys_listen`] / [`sys_accept`] / [`sys_connect`] - 地址与连接
+//!   - [`sys_sendto`] / [`sys_recvfrom`] - 发送、接收数据
+//! - **共享内存**:
+//!   - [`sys_shmget`] - 按键查找或创建共享内存段
+//!   - [`sys_shmat`] / [`sys_shmdt`] - 附加、解除附加
+//!   - [`sys_shmctl`] - 查询状态、修改权限、标记删除
+//! - **调试**:
+//!   - [`sys_trace`] - 设置系统调用跟踪
+//!   - [`sys_ptrace`] - 跟踪子进程：断点、单步、读写内存与寄存器
+//! - **IPC 端点**:
+//!   - [`sys_ipc_create`] / [`sys_ipc_close`] - 创建端点、关闭句柄
+//!   - [`sys_ipc_send`] / [`sys_ipc_recv`] - 发送、接收消息
+//!   - [`sys_ipc_call`] / [`sys_ipc_reply`] - 请求与应答
 //! - **进程管理**:
 //!   - [`sys_exit`]     - 进程退出
 //!   - [`sys_yield`]    - 让出 CPU
 //!   - [`sys_time`] - 获取系统时间
 //!   - [`sys_pid`]   - 获取当前进程 PID
 //!   - [`sys_fork`]     - 创建子进程（复制地址空间）
-//!   - [`sys_exec`]     - 替换为新程序镜像
+//!   - [`sys_execve`]   - 替换为新程序镜像（argv/envp/auxv）
 //!   - [`sys_waitpid`]  - 等待子进程结束并获取退出码
 //!   - [`sys_kill`]     - 发送信号给进程
+//!   - [`sys_sigqueue`] - 发送带附带数据的信号（实时信号排队）
 //!   - [`sys_sigaction`] - 设置信号处理
 //!   - [`sys_sigprocmask`] - 设置信号掩码
 //!   - [`sys_sigreturn`] - 从信号处理返回
+//!   - [`sys_prlimit64`] - 查询/设置资源限制
+//!
+//! ## 系统调用表
+//!
+//! 所有系统调用登记在按编号排序的 `SYSCALL_TABLE` 中，每一项记录编号、名称、
+//! 参数个数与处理函数；[`syscall`] 二分查找后以 `a0` ~ `a5` 六个参数调用处理函数，
+//! 可通过 [`syscall_entry`] 查询某个编号的名称与参数个数。
 //!
 //! ## 系统调用编号
 //!
@@ -33,94 +63,424 @@
 //! - `SYSCALL_READ` (63)         - 读操作
 //! - `SYSCALL_WRITE` (64)        - 写操作
 //! - `SYSCALL_EXIT` (93)         - 进程退出
+//! - `SYSCALL_PTRACE` (117)      - 进程跟踪
 //! - `SYSCALL_YIELD` (124)       - 让出 CPU
 //! - `SYSCALL_TIME` (169)        - 获取系统时间
 //! - `SYSCALL_PID` (172)         - 获取进程 PID
 //! - `SYSCALL_FORK` (220)        - 创建子进程
-//! - `SYSCALL_EXEC` (221)        - 执行新程序
+//! - `SYSCALL_EXECVE` (221)      - 执行新程序
 //! - `SYSCALL_WAITPID` (260)     - 等待子进程
-//! - `SYSCALL_DUP` (24)          - 复制文件描述符
+//! - `SYSCALL_PRLIMIT64` (261)   - 查询/设置资源限制
+//! - `SYSCALL_EPOLL_CREATE1` (20) - 创建 epoll 实例
+//! - `SYSCALL_EPOLL_CTL` (21)    - 修改 epoll 兴趣集合
+//! - `SYSCALL_EPOLL_PWAIT` (22)  - 等待 epoll 事件
+//! - `SYSCALL_DUP` (23)          - 复制文件描述符
+//! - `SYSCALL_DUP3` (24)         - 复制到指定的文件描述符
+//! - `SYSCALL_FCNTL` (25)        - 文件描述符控制
+//! - `SYSCALL_IOCTL` (29)        - 设备控制
+//! - `SYSCALL_MKNODAT` (33)      - 创建命名管道或普通文件
 //! - `SYSCALL_PIPE` (59)         - 创建管道
+//! - `SYSCALL_PSELECT6` (72)     - 等待描述符集合就绪
+//! - `SYSCALL_PPOLL` (73)        - 等待多个文件描述符就绪
 //! - `SYSCALL_KILL` (129)        - 发送信号给进程
 //! - `SYSCALL_SIGACTION` (134)   - 设置信号处理
 //! - `SYSCALL_SIGPROCMASK` (135) - 设置信号掩码
+//! - `SYSCALL_SIGQUEUE` (138)    - 发送带附带数据的信号
 //! - `SYSCALL_SIGRETURN` (139)   - 从信号处理返回
+//! - `SYSCALL_SOCKET` (198)      - 创建套接字
+//! - `SYSCALL_BIND` (200)        - 绑定本地地址
+//! - `SYSCALL_LISTEN` (201)      - 开始接受连接
+//! - `SYSCALL_ACCEPT` (202)      - 取出一个已建立的连接
+//! - `SYSCALL_CONNECT` (203)     - 连接到指定地址
+//! - `SYSCALL_SENDTO` (206)      - 发送数据
+//! - `SYSCALL_RECVFROM` (207)    - 接收数据
+//! - `SYSCALL_SHMGET` (194)      - 查找或创建共享内存段
+//! - `SYSCALL_SHMCTL` (195)      - 共享内存段控制
+//! - `SYSCALL_SHMAT` (196)       - 附加共享内存段
+//! - `SYSCALL_SHMDT` (197)       - 解除附加共享内存段
+//!
+//! IPC 端点不是 Linux 的系统调用，使用 Linux 未分配的编号：
+//! - `SYSCALL_IPC_CREATE` (500)  - 创建端点
+//! - `SYSCALL_IPC_CLOSE` (501)   - 关闭端点句柄
+//! - `SYSCALL_IPC_SEND` (502)    - 发送消息
+//! - `SYSCALL_IPC_RECV` (503)    - 接收消息
+//! - `SYSCALL_IPC_CALL` (504)    - 发送消息并等待应答
+//! - `SYSCALL_IPC_REPLY` (505)   - 应答
+//!
+//! 系统调用跟踪同样使用未分配的编号：
+//! - `SYSCALL_TRACE` (506)       - 设置系统调用跟踪
 
-use crate::process::SignalAction;
+use crate::errno::Errno::ENOSYS;
+use crate::fs::{EpollEvent, FdSet, PollFd};
+use crate::ipc::IpcBuffer;
+use crate::mm::ShmIdDs;
+use crate::process::{RLimit, SignalAction};
+use crate::timer::TimeSpec;
 
 mod fs;
+mod ipc;
+mod net;
 mod process;
+mod ptrace;
+mod shm;
+mod trace;
 
 pub use fs::*;
+pub use ipc::*;
+pub use net::*;
 pub use process::*;
+pub use ptrace::*;
+pub use shm::*;
+pub use trace::*;
 
-const SYSCALL_DUP: usize = 24;
+use trace::SyscallArg::{Buf, Hex, Int, Str, Uint};
+
+const SYSCALL_EPOLL_CREATE1: usize = 20;
+const SYSCALL_EPOLL_CTL: usize = 21;
+const SYSCALL_EPOLL_PWAIT: usize = 22;
+const SYSCALL_DUP: usize = 23;
+const SYSCALL_DUP3: usize = 24;
+const SYSCALL_FCNTL: usize = 25;
+const SYSCALL_IOCTL: usize = 29;
+const SYSCALL_MKNODAT: usize = 33;
 const SYSCALL_OPEN: usize = 56;
 const SYSCALL_CLOSE: usize = 57;
 const SYSCALL_PIPE: usize = 59;
 const SYSCALL_READ: usize = 63;
 const SYSCALL_WRITE: usize = 64;
+const SYSCALL_PSELECT6: usize = 72;
+const SYSCALL_PPOLL: usize = 73;
 const SYSCALL_EXIT: usize = 93;
+const SYSCALL_PTRACE: usize = 117;
 const SYSCALL_YIELD: usize = 124;
 const SYSCALL_KILL: usize = 129;
 const SYSCALL_SIGACTION: usize = 134;
 const SYSCALL_SIGPROCMASK: usize = 135;
+const SYSCALL_SIGQUEUE: usize = 138;
 const SYSCALL_SIGRETURN: usize = 139;
 const SYSCALL_TIME: usize = 169;
 const SYSCALL_PID: usize = 172;
+const SYSCALL_SHMGET: usize = 194;
+const SYSCALL_SHMCTL: usize = 195;
+const SYSCALL_SHMAT: usize = 196;
+const SYSCALL_SHMDT: usize = 197;
+const SYSCALL_SOCKET: usize = 198;
+const SYSCALL_BIND: usize = 200;
+const SYSCALL_LISTEN: usize = 201;
+const SYSCALL_ACCEPT: usize = 202;
+const SYSCALL_CONNECT: usize = 203;
+const SYSCALL_SENDTO: usize = 206;
+const SYSCALL_RECVFROM: usize = 207;
 const SYSCALL_FORK: usize = 220;
-const SYSCALL_EXEC: usize = 221;
+const SYSCALL_EXECVE: usize = 221;
 const SYSCALL_WAITPID: usize = 260;
+const SYSCALL_PRLIMIT64: usize = 261;
+const SYSCALL_IPC_CREATE: usize = 500;
+const SYSCALL_IPC_CLOSE: usize = 501;
+const SYSCALL_IPC_SEND: usize = 502;
+const SYSCALL_IPC_RECV: usize = 503;
+const SYSCALL_IPC_CALL: usize = 504;
+const SYSCALL_IPC_REPLY: usize = 505;
+const SYSCALL_TRACE: usize = 506;
+
+/// 系统调用处理函数：接收 `a0` ~ `a5` 六个原始参数，自行转换为所需类型
+type SyscallHandler = fn([usize; 6]) -> isize;
+
+/// 系统调用表项
+///
+/// 记录系统调用号、名称、参数个数及显示方式与处理函数，供分发器和系统调用跟踪使用。
+pub struct SyscallEntry {
+    /// 系统调用号
+    pub id: usize,
+    /// 系统调用名称（不含 `sys_` 前缀）
+    pub name: &'static str,
+    /// 实际使用的参数个数（`a0` 起依次计数）
+    pub argc: usize,
+    /// 各参数在跟踪输出中的显示方式，长度为 `argc`
+    pub args: &'static [SyscallArg],
+    handler: SyscallHandler,
+}
+
+impl SyscallEntry {
+    const fn new(
+        id: usize,
+        name: &'static str,
+        args: &'static [SyscallArg],
+        handler: SyscallHandler,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            argc: args.len(),
+            args,
+            handler,
+        }
+    }
+}
+
+/// 系统调用表，按系统调用号升序排列以便二分查找
+const SYSCALL_TABLE: &[SyscallEntry] = &[
+    SyscallEntry::new(SYSCALL_EPOLL_CREATE1, "epoll_create1", &[Hex], |a| {
+        sys_epoll_create1(a[0] as u32)
+    }),
+    SyscallEntry::new(SYSCALL_EPOLL_CTL, "epoll_ctl", &[Int, Int, Int, Hex], |a| {
+        sys_epoll_ctl(a[0], a[1], a[2], a[3] as *const EpollEvent)
+    }),
+    SyscallEntry::new(
+        SYSCALL_EPOLL_PWAIT,
+        "epoll_pwait",
+        &[Int, Hex, Int, Int, Hex],
+        |a| {
+            sys_epoll_pwait(
+                a[0],
+                a[1] as *mut EpollEvent,
+                a[2] as i32 as isize,
+                a[3] as i32 as isize,
+                a[4] as *const u64,
+            )
+        },
+    ),
+    SyscallEntry::new(SYSCALL_DUP, "dup", &[Int], |a| sys_dup(a[0])),
+    SyscallEntry::new(SYSCALL_DUP3, "dup3", &[Int, Int, Hex], |a| {
+        sys_dup3(a[0], a[1], a[2] as u32)
+    }),
+    SyscallEntry::new(SYSCALL_FCNTL, "fcntl", &[Int, Int, Hex], |a| {
+        sys_fcntl(a[0], a[1], a[2])
+    }),
+    SyscallEntry::new(SYSCALL_IOCTL, "ioctl", &[Int, Hex, Hex], |a| {
+        sys_ioctl(a[0], a[1], a[2])
+    }),
+    SyscallEntry::new(SYSCALL_MKNODAT, "mknodat", &[Int, Str, Hex, Hex], |a| {
+        sys_mknodat(a[0] as isize, a[1] as *const u8, a[2] as u32, a[3])
+    }),
+    SyscallEntry::new(SYSCALL_OPEN, "open", &[Str, Hex], |a| {
+        sys_open(a[0] as *const u8, a[1] as u32)
+    }),
+    SyscallEntry::new(SYSCALL_CLOSE, "close", &[Int], |a| sys_close(a[0])),
+    SyscallEntry::new(SYSCALL_PIPE, "pipe", &[Hex, Hex], |a| {
+        sys_pipe(a[0] as *mut usize, a[1] as u32)
+    }),
+    SyscallEntry::new(SYSCALL_READ, "read", &[Int, Hex, Uint], |a| {
+        sys_read(a[0], a[1] as *mut u8, a[2])
+    }),
+    SyscallEntry::new(SYSCALL_WRITE, "write", &[Int, Buf, Uint], |a| {
+        sys_write(a[0], a[1] as *const u8, a[2])
+    }),
+    SyscallEntry::new(
+        SYSCALL_PSELECT6,
+        "pselect6",
+        &[Int, Hex, Hex, Hex, Hex, Hex],
+        |a| {
+            sys_pselect6(
+                a[0],
+                a[1] as *mut FdSet,
+                a[2] as *mut FdSet,
+                a[3] as *mut FdSet,
+                a[4] as *const TimeSpec,
+                a[5] as *const u64,
+            )
+        },
+    ),
+    SyscallEntry::new(SYSCALL_PPOLL, "ppoll", &[Hex, Uint, Hex, Hex], |a| {
+        sys_ppoll(
+            a[0] as *mut PollFd,
+            a[1],
+            a[2] as *const TimeSpec,
+            a[3] as *const u64,
+        )
+    }),
+    SyscallEntry::new(SYSCALL_EXIT, "exit", &[Int], |a| sys_exit(a[0] as i32)),
+    SyscallEntry::new(SYSCALL_PTRACE, "ptrace", &[Int, Int, Hex, Hex], |a| {
+        sys_ptrace(a[0], a[1], a[2], a[3])
+    }),
+    SyscallEntry::new(SYSCALL_YIELD, "yield", &[], |_| sys_yield()),
+    SyscallEntry::new(SYSCALL_KILL, "kill", &[Int, Int], |a| {
+        sys_kill(a[0], a[1] as i32)
+    }),
+    SyscallEntry::new(SYSCALL_SIGACTION, "sigaction", &[Int, Hex, Hex], |a| {
+        sys_sigaction(
+            a[0] as i32,
+            a[1] as *const SignalAction,
+            a[2] as *mut SignalAction,
+        )
+    }),
+    SyscallEntry::new(SYSCALL_SIGPROCMASK, "sigprocmask", &[Hex, Hex], |a| {
+        sys_sigprocmask(a[0] as u64, a[1] as *mut u64)
+    }),
+    SyscallEntry::new(SYSCALL_SIGQUEUE, "sigqueue", &[Int, Int, Hex], |a| {
+        sys_sigqueue(a[0], a[1] as i32, a[2])
+    }),
+    SyscallEntry::new(SYSCALL_SIGRETURN, "sigreturn", &[], |_| sys_sigreturn()),
+    SyscallEntry::new(SYSCALL_TIME, "time", &[], |_| sys_time()),
+    SyscallEntry::new(SYSCALL_PID, "pid", &[], |_| sys_pid()),
+    SyscallEntry::new(SYSCALL_SHMGET, "shmget", &[Int, Uint, Hex], |a| {
+        sys_shmget(a[0] as i32, a[1], a[2])
+    }),
+    SyscallEntry::new(SYSCALL_SHMCTL, "shmctl", &[Int, Int, Hex], |a| {
+        sys_shmctl(a[0], a[1], a[2] as *mut ShmIdDs)
+    }),
+    SyscallEntry::new(SYSCALL_SHMAT, "shmat", &[Int, Hex, Hex], |a| {
+        sys_shmat(a[0], a[1], a[2])
+    }),
+    SyscallEntry::new(SYSCALL_SHMDT, "shmdt", &[Hex], |a| sys_shmdt(a[0])),
+    SyscallEntry::new(SYSCALL_SOCKET, "socket", &[Int, Int, Int], |a| {
+        sys_socket(a[0], a[1], a[2])
+    }),
+    SyscallEntry::new(SYSCALL_BIND, "bind", &[Int, Hex, Uint], |a| {
+        sys_bind(a[0], a[1] as *const u8, a[2])
+    }),
+    SyscallEntry::new(SYSCALL_LISTEN, "listen", &[Int, Int], |a| {
+        sys_listen(a[0], a[1])
+    }),
+    SyscallEntry::new(SYSCALL_ACCEPT, "accept", &[Int, Hex, Hex], |a| {
+        sys_accept(a[0], a[1] as *mut u8, a[2] as *mut u32)
+    }),
+    SyscallEntry::new(SYSCALL_CONNECT, "connect", &[Int, Hex, Uint], |a| {
+        sys_connect(a[0], a[1] as *const u8, a[2])
+    }),
+    SyscallEntry::new(
+        SYSCALL_SENDTO,
+        "sendto",
+        &[Int, Buf, Uint, Hex, Hex, Uint],
+        |a| {
+            sys_sendto(
+                a[0],
+                a[1] as *const u8,
+                a[2],
+                a[3] as u32,
+                a[4] as *const u8,
+                a[5],
+            )
+        },
+    ),
+    SyscallEntry::new(
+        SYSCALL_RECVFROM,
+        "recvfrom",
+        &[Int, Hex, Uint, Hex, Hex, Hex],
+        |a| {
+            sys_recvfrom(
+                a[0],
+                a[1] as *mut u8,
+                a[2],
+                a[3] as u32,
+                a[4] as *mut u8,
+                a[5] as *mut u32,
+            )
+        },
+    ),
+    SyscallEntry::new(SYSCALL_FORK, "fork", &[], |_| sys_fork()),
+    SyscallEntry::new(SYSCALL_EXECVE, "execve", &[Str, Hex, Hex], |a| {
+        sys_execve(
+            a[0] as *const u8,
+            a[1] as *const usize,
+            a[2] as *const usize,
+        )
+    }),
+    SyscallEntry::new(SYSCALL_WAITPID, "waitpid", &[Int, Hex], |a| {
+        sys_waitpid(a[0] as isize, a[1] as *mut i32)
+    }),
+    SyscallEntry::new(SYSCALL_PRLIMIT64, "prlimit64", &[Int, Int, Hex, Hex], |a| {
+        sys_prlimit64(a[0], a[1], a[2] as *const RLimit, a[3] as *mut RLimit)
+    }),
+    SyscallEntry::new(SYSCALL_IPC_CREATE, "ipc_create", &[], |_| sys_ipc_create()),
+    SyscallEntry::new(SYSCALL_IPC_CLOSE, "ipc_close", &[Int], |a| {
+        sys_ipc_close(a[0])
+    }),
+    SyscallEntry::new(
+        SYSCALL_IPC_SEND,
+        "ipc_send",
+        &[Int, Hex, Hex, Hex, Hex, Hex],
+        |a| sys_ipc_send(a[0], [a[1], a[2], a[3], a[4]], a[5] as *const IpcBuffer),
+    ),
+    SyscallEntry::new(SYSCALL_IPC_RECV, "ipc_recv", &[Int, Hex, Hex], |a| {
+        sys_ipc_recv(a[0], a[1] as *mut IpcBuffer, a[2])
+    }),
+    SyscallEntry::new(
+        SYSCALL_IPC_CALL,
+        "ipc_call",
+        &[Int, Hex, Hex, Hex, Hex, Hex],
+        |a| sys_ipc_call(a[0], [a[1], a[2], a[3], a[4]], a[5] as *mut IpcBuffer),
+    ),
+    SyscallEntry::new(
+        SYSCALL_IPC_REPLY,
+        "ipc_reply",
+        &[Hex, Hex, Hex, Hex, Hex],
+        |a| sys_ipc_reply([a[0], a[1], a[2], a[3]], a[4] as *const IpcBuffer),
+    ),
+    SyscallEntry::new(SYSCALL_TRACE, "trace", &[Int, Int], |a| {
+        sys_trace(a[0], a[1])
+    }),
+];
+
+/// 检查系统调用表是否按编号严格升序排列
+const fn table_sorted(table: &[SyscallEntry]) -> bool {
+    let mut i = 1;
+    while i < table.len() {
+        if table[i - 1].id >= table[i].id {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// 按系统调用号查找表项
+///
+/// ## Arguments
+///
+/// * `syscall_id` - 系统调用编号
+///
+/// ## Returns
+///
+/// 找到时返回对应表项，未实现的编号返回 `None`
+pub fn syscall_entry(syscall_id: usize) -> Option<&'static SyscallEntry> {
+    const {
+        assert!(
+            table_sorted(SYSCALL_TABLE),
+            "SYSCALL_TABLE must be sorted by id"
+        )
+    };
+    SYSCALL_TABLE
+        .binary_search_by_key(&syscall_id, |entry| entry.id)
+        .ok()
+        .map(|idx| &SYSCALL_TABLE[idx])
+}
 
 /// 系统调用分发器
 ///
-/// 这是系统调用处理的主入口点，负责根据系统调用号分发到具体的处理函数。
+/// 这是系统调用处理的主入口点，在 `SYSCALL_TABLE` 中查找系统调用号并调用对应的处理函数。
 /// 该函数由陷阱处理器调用，将用户态的系统调用请求转换为内核函数调用。
 ///
 /// ## Arguments
 ///
 /// * `syscall_id` - 系统调用编号，标识要执行的系统调用类型
-/// * `args` - 系统调用参数数组，最多支持 3 个参数
+/// * `args` - 系统调用参数数组，即 `a0` ~ `a5` 六个寄存器
 ///
 /// ## Returns
 ///
 /// 返回系统调用的执行结果：
 /// - 成功时返回非负值（具体含义取决于系统调用类型）
-/// - 失败时返回负值（错误码）
-///
-/// ## Panics
-///
-/// 当遇到不支持的系统调用编号时会触发 panic
+/// - 失败时返回负的错误码（见 [`crate::errno::Errno`]）
+/// - 不支持的系统调用编号返回 `-ENOSYS`
 ///
 /// ## 调用约定
 ///
 /// 遵循 RISC-V 系统调用约定：
 /// - `a7` 寄存器存放系统调用号 (`syscall_id`)
-/// - `a0`, `a1`, `a2` 寄存器存放参数 (`args[0]`, `args[1]`, `args[2]`)
-/// - `a0` 寄存器存放返回值
-pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
-    match syscall_id {
-        SYSCALL_READ => sys_read(args[0], args[1] as *const u8, args[2]),
-        SYSCALL_WRITE => sys_write(args[0], args[1] as *const u8, args[2]),
-        SYSCALL_EXIT => sys_exit(args[0] as i32),
-        SYSCALL_YIELD => sys_yield(),
-        SYSCALL_TIME => sys_time(),
-        SYSCALL_PID => sys_pid(),
-        SYSCALL_FORK => sys_fork(),
-        SYSCALL_EXEC => sys_exec(args[0] as *const u8, args[1] as *const usize),
-        SYSCALL_WAITPID => sys_waitpid(args[0] as isize, args[1] as *mut i32),
-        SYSCALL_OPEN => sys_open(args[0] as *const u8, args[1] as u32),
-        SYSCALL_CLOSE => sys_close(args[0]),
-        SYSCALL_DUP => sys_dup(args[0]),
-        SYSCALL_PIPE => sys_pipe(args[0] as *mut usize),
-        SYSCALL_KILL => sys_kill(args[0], args[1] as i32),
-        SYSCALL_SIGACTION => sys_sigaction(
-            args[0] as i32,
-            args[1] as *const SignalAction,
-            args[2] as *mut SignalAction,
-        ),
-        SYSCALL_SIGPROCMASK => sys_sigprocmask(args[0] as u32),
-        SYSCALL_SIGRETURN => sys_sigreturn(),
-        _ => panic!("Unsupported syscall_id: {}", syscall_id),
+/// - `a0` ~ `a5` 寄存器存放参数 (`args[0]` ~ `args[5]`)，未使用的参数被忽略
+/// - `a0` 寄存器存放返回值；IPC 系统调用另在 `a1` ~ `a4` 返回消息字
+pub fn syscall(syscall_id: usize, args: [usize; 6]) -> isize {
+    match syscall_entry(syscall_id) {
+        Some(entry) => {
+            ::log::trace!("syscall {}({:x?})", entry.name, &args[..entry.argc]);
+            match current_trace() {
+                Some(file) => traced_syscall(entry, args, file),
+                None => (entry.handler)(args),
+            }
+        }
+        None => {
+            ::log::warn!("Unsupported syscall_id: {}", syscall_id);
+            -ENOSYS
+        }
     }
 }
@@ -0,0 +1,269 @@
+//! # 套接字相关系统调用
+//!
+//! 套接字以文件描述符的形式存在，这里只负责查找描述符、在用户空间的
+//! `struct sockaddr` 与 [`SockAddr`] 之间转换，具体操作交给 [`Socket`](crate::net::Socket)。
+//!
+//! ## 支持的系统调用
+//!
+//! - [`sys_socket`]   - 创建套接字
+//! - [`sys_bind`]     - 绑定本地地址
+//! - [`sys_listen`]   - 开始接受连接
+//! - [`sys_accept`]   - 取出一个已建立的连接
+//! - [`sys_connect`]  - 连接到指定地址
+//! - [`sys_sendto`]   - 发送数据（可指定数据报的目的地址）
+//! - [`sys_recvfrom`] - 接收数据并取得发送方地址
+
+use super::fs::get_file;
+use crate::errno::Errno::{EBADF, EINVAL, EMFILE, ENOTSOCK, EPROTONOSUPPORT};
+use crate::fs::{File, OpenFlags};
+use crate::mm::{
+    copy_from_user, copy_from_user_bytes, copy_to_user, copy_to_user_bytes, user_buffer,
+    user_buffer_mut,
+};
+use crate::net::{SOCK_DGRAM, SOCK_STREAM, SockAddr, SockType, socket};
+use crate::process::{current_process, current_user_token};
+use alloc::sync::Arc;
+use alloc::vec;
+
+/// `type` 参数中表示套接字类型的位
+const SOCK_TYPE_MASK: usize = 0xf;
+
+/// `struct sockaddr` 的最大长度（`sizeof(struct sockaddr_storage)`）
+const SOCKADDR_MAX: usize = 128;
+
+/// 取出文件描述符对应的套接字文件
+fn get_socket_file(fd: usize) -> Result<Arc<dyn File + Send + Sync>, isize> {
+    let file = get_file(fd).ok_or(-EBADF)?;
+    if file.as_socket().is_none() {
+        return Err(-ENOTSOCK);
+    }
+    Ok(file)
+}
+
+/// 在当前进程中为 `file` 分配文件描述符
+fn install_fd(file: Arc<dyn File + Send + Sync>, cloexec: bool) -> isize {
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    let Some(fd) = inner.alloc_fd() else {
+        return -EMFILE;
+    };
+    inner.fd_table[fd] = Some(file);
+    if cloexec {
+        inner.fd_cloexec.insert(fd);
+    }
+    fd as isize
+}
+
+/// 读取用户空间的 `struct sockaddr`
+fn read_sockaddr(addr: *const u8, addrlen: usize) -> Result<SockAddr, isize> {
+    if addrlen > SOCKADDR_MAX {
+        return Err(-EINVAL);
+    }
+    let mut bytes = vec![0; addrlen];
+    copy_from_user_bytes(current_user_token(), addr, &mut bytes)?;
+    SockAddr::from_bytes(&bytes)
+}
+
+/// 把地址写回用户空间
+///
+/// 与 Linux 相同：最多写入 `*addrlen` 字节，`*addrlen` 更新为地址的完整长度；
+/// 两个指针任一为空时不写回；指针不可访问时返回 `-EFAULT`。
+fn write_sockaddr(sockaddr: &SockAddr, addr: *mut u8, addrlen: *mut u32) -> Result<(), isize> {
+    if addr.is_null() || addrlen.is_null() {
+        return Ok(());
+    }
+    let token = current_user_token();
+    let bytes = sockaddr.to_bytes();
+    let len = (copy_from_user(token, addrlen)? as usize).min(bytes.len());
+    copy_to_user_bytes(token, addr, &bytes[..len])?;
+    copy_to_user(token, addrlen, &(bytes.len() as u32))
+}
+
+/// 系统调用：创建套接字
+///
+/// 实现 `socket(2)`。
+///
+/// ## Arguments
+///
+/// * `domain` - 地址族，`AF_UNIX` 或 `AF_INET`
+/// * `type_` - `SOCK_STREAM` 或 `SOCK_DGRAM`，可以或上 `SOCK_NONBLOCK`、`SOCK_CLOEXEC`
+/// * `protocol` - 协议，必须为 0
+///
+/// ## Returns
+///
+/// 成功返回新的文件描述符；失败返回 `-EAFNOSUPPORT`、`-EINVAL`、
+/// `-EPROTONOSUPPORT` 或 `-EMFILE`
+pub fn sys_socket(domain: usize, type_: usize, protocol: usize) -> isize {
+    let flag_bits = (OpenFlags::NONBLOCK | OpenFlags::CLOEXEC).bits() as usize;
+    if type_ & !(SOCK_TYPE_MASK | flag_bits) != 0 {
+        return -EINVAL;
+    }
+    let sock_type = match type_ & SOCK_TYPE_MASK {
+        SOCK_STREAM => SockType::Stream,
+        SOCK_DGRAM => SockType::Dgram,
+        _ => return -EINVAL,
+    };
+    if protocol != 0 {
+        return -EPROTONOSUPPORT;
+    }
+    let flags = OpenFlags::from_bits_truncate((type_ & flag_bits) as u32);
+    match socket(domain, sock_type, flags - OpenFlags::CLOEXEC) {
+        Ok(file) => install_fd(file, flags.contains(OpenFlags::CLOEXEC)),
+        Err(errno) => errno,
+    }
+}
+
+/// 系统调用：绑定本地地址
+///
+/// 实现 `bind(2)`。Unix 域套接字在 Micro-FS 中创建同名的套接字文件；
+/// IPv4 套接字的端口为 0 时分配临时端口。
+///
+/// ## Returns
+///
+/// 成功返回 0；地址已被使用返回 `-EADDRINUSE`，不是本机地址返回 `-EADDRNOTAVAIL`
+pub fn sys_bind(fd: usize, addr: *const u8, addrlen: usize) -> isize {
+    let file = match get_socket_file(fd) {
+        Ok(file) => file,
+        Err(errno) => return errno,
+    };
+    match read_sockaddr(addr, addrlen) {
+        Ok(addr) => file.as_socket().unwrap().bind(addr),
+        Err(errno) => errno,
+    }
+}
+
+/// 系统调用：开始接受连接
+///
+/// 实现 `listen(2)`，套接字必须已经绑定。
+pub fn sys_listen(fd: usize, backlog: usize) -> isize {
+    match get_socket_file(fd) {
+        Ok(file) => file.as_socket().unwrap().listen(backlog),
+        Err(errno) => errno,
+    }
+}
+
+/// 系统调用：取出一个已建立的连接
+///
+/// 实现 `accept(2)`。新套接字处于阻塞模式，不设置 `FD_CLOEXEC`。
+///
+/// ## Arguments
+///
+/// * `fd` - 监听套接字
+/// * `addr` / `addrlen` - 返回对端地址，可以为空
+///
+/// ## Returns
+///
+/// 成功返回新连接的文件描述符
+pub fn sys_accept(fd: usize, addr: *mut u8, addrlen: *mut u32) -> isize {
+    let file = match get_socket_file(fd) {
+        Ok(file) => file,
+        Err(errno) => return errno,
+    };
+    match file.as_socket().unwrap().accept() {
+        // 与 Linux 相同：地址写回失败时连接被丢弃
+        Ok((conn, peer)) => match write_sockaddr(&peer, addr, addrlen) {
+            Ok(()) => install_fd(conn, false),
+            Err(errno) => errno,
+        },
+        Err(errno) => errno,
+    }
+}
+
+/// 系统调用：连接到指定地址
+///
+/// 实现 `connect(2)`。流式套接字建立连接；数据报套接字设置默认的目的地址。
+///
+/// ## Returns
+///
+/// 成功返回 0；地址不存在返回 `-ENOENT`，没有套接字在该地址上监听返回
+/// `-ECONNREFUSED`；非阻塞的 TCP 套接字返回 `-EINPROGRESS`，连接完成后可写
+pub fn sys_connect(fd: usize, addr: *const u8, addrlen: usize) -> isize {
+    let file = match get_socket_file(fd) {
+        Ok(file) => file,
+        Err(errno) => return errno,
+    };
+    match read_sockaddr(addr, addrlen) {
+        Ok(addr) => file.as_socket().unwrap().connect(addr),
+        Err(errno) => errno,
+    }
+}
+
+/// 系统调用：发送数据
+///
+/// 实现 `sendto(2)`，`send(2)` 即目的地址为空的 `sendto`。
+///
+/// ## Arguments
+///
+/// * `fd` - 套接字
+/// * `buf` / `len` - 要发送的数据
+/// * `flags` - `MSG_DONTWAIT`、`MSG_NOSIGNAL`
+/// * `addr` / `addrlen` - 数据报的目的地址，为空时发往已连接的对端
+///
+/// ## Returns
+///
+/// 发送的字节数
+pub fn sys_sendto(
+    fd: usize,
+    buf: *const u8,
+    len: usize,
+    flags: u32,
+    addr: *const u8,
+    addrlen: usize,
+) -> isize {
+    let file = match get_socket_file(fd) {
+        Ok(file) => file,
+        Err(errno) => return errno,
+    };
+    let addr = if addr.is_null() {
+        None
+    } else {
+        match read_sockaddr(addr, addrlen) {
+            Ok(addr) => Some(addr),
+            Err(errno) => return errno,
+        }
+    };
+    let buf = match user_buffer(current_user_token(), buf, len) {
+        Ok(buf) => buf,
+        Err(errno) => return errno,
+    };
+    file.as_socket().unwrap().sendto(buf, flags, addr)
+}
+
+/// 系统调用：接收数据
+///
+/// 实现 `recvfrom(2)`，`recv(2)` 即地址为空的 `recvfrom`。
+///
+/// ## Arguments
+///
+/// * `fd` - 套接字
+/// * `buf` / `len` - 接收缓冲区
+/// * `flags` - `MSG_DONTWAIT`
+/// * `addr` / `addrlen` - 返回发送方地址，可以为空
+///
+/// ## Returns
+///
+/// 接收的字节数；流式套接字的对端关闭后返回 0
+pub fn sys_recvfrom(
+    fd: usize,
+    buf: *mut u8,
+    len: usize,
+    flags: u32,
+    addr: *mut u8,
+    addrlen: *mut u32,
+) -> isize {
+    let file = match get_socket_file(fd) {
+        Ok(file) => file,
+        Err(errno) => return errno,
+    };
+    let buf = match user_buffer_mut(current_user_token(), buf, len) {
+        Ok(buf) => buf,
+        Err(errno) => return errno,
+    };
+    match file.as_socket().unwrap().recvfrom(buf, flags) {
+        Ok((len, from)) => match write_sockaddr(&from, addr, addrlen) {
+            Ok(()) => len as isize,
+            Err(errno) => errno,
+        },
+        Err(errno) => errno,
+    }
+}
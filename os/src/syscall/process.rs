@@ -10,12 +10,14 @@
 //! - [`sys_time`] - 获取系统时间
 //! - [`sys_pid`] - 获取进程 PID
 //! - [`sys_fork`] - 创建子进程
-//! - [`sys_exec`] - 执行新程序
+//! - [`sys_execve`] - 执行新程序（带环境变量）
 //! - [`sys_waitpid`] - 等待子进程结束
 //! - [`sys_kill`] - 发送信号
+//! - [`sys_sigqueue`] - 发送带附带数据的信号
 //! - [`sys_sigaction`] - 设置信号处理
 //! - [`sys_sigprocmask`] - 设置信号掩码
 //! - [`sys_sigreturn`] - 从信号处理返回
+//! - [`sys_prlimit64`] - 查询/设置资源限制
 //!
 //! ## 进程状态管理
 //!
@@ -33,14 +35,17 @@
 //! - 进程等待和回收（waitpid）
 //! - 进程退出和清理（exit）
 
+use crate::errno::Errno::{E2BIG, EAGAIN, ECHILD, EFAULT, EINVAL, ENOENT, ENOEXEC, ENOMEM, ESRCH};
 use crate::fs::{OpenFlags, open_file};
-use crate::mm::{translated_ref, translated_refmut, translated_str};
+use crate::mm::{ELF_MAGIC, copy_from_user, copy_str_from_user, copy_to_user};
 use crate::println;
 use crate::process::{
-    MAX_SIG, SignalAction, SignalFlags, add_process, current_process, current_user_token,
-    exit_current_and_run_next, pid2process, suspend_current_and_run_next,
+    ExecError, MAX_SIG, RLIM_NLIMITS, RLIMIT_NPROC, RLimit, SignalAction, SignalFlags, add_process,
+    current_process, current_user_token, exit_current_and_run_next, pid2process, process_count,
+    signal_process, suspend_current_and_run_next,
 };
 use crate::timer::time_ms;
+use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 
@@ -162,6 +167,7 @@ pub fn sys_pid() -> isize {
 ///
 /// - 父进程中返回新建子进程的 PID（正数）
 /// - 子进程中返回 0
+/// - 进程数已达到 `RLIMIT_NPROC` 时返回 `-EAGAIN`
 ///
 /// ## 行为说明
 ///
@@ -175,6 +181,13 @@ pub fn sys_pid() -> isize {
 /// - Trampoline 等只读共享页面除外
 pub fn sys_fork() -> isize {
     let current_process = current_process().unwrap();
+    let nproc_limit = current_process
+        .inner_exclusive_access()
+        .rlimits
+        .cur(RLIMIT_NPROC);
+    if process_count() >= nproc_limit {
+        return -EAGAIN;
+    }
     let new_process = current_process.fork();
     let new_pid = new_process.pid.0;
     let trap_cx = new_process.inner_exclusive_access().trap_cx();
@@ -183,26 +196,100 @@ pub fn sys_fork() -> isize {
     new_pid as isize
 }
 
-/// 系统调用：执行新程序（exec）
+/// 从用户空间读取以空指针结尾的字符串指针数组
+///
+/// ## Arguments
+///
+/// * `token` - 用户地址空间的页表标识
+/// * `ptr` - 指针数组首地址；为空时视为空数组
+///
+/// ## Returns
+///
+/// 依次读出的字符串；任一指针无效时返回 `-EFAULT`，字符串过长时返回 `-ENAMETOOLONG`
+fn copy_str_array_from_user(token: usize, mut ptr: *const usize) -> Result<Vec<String>, isize> {
+    let mut strings = Vec::new();
+    if ptr.is_null() {
+        return Ok(strings);
+    }
+    loop {
+        let str_ptr = copy_from_user(token, ptr)?;
+        if str_ptr == 0 {
+            break;
+        }
+        strings.push(copy_str_from_user(token, str_ptr as *const u8)?);
+        ptr = ptr.wrapping_add(1);
+    }
+    Ok(strings)
+}
+
+/// 脚本解释器嵌套的最大层数（与 Linux 的 `BINPRM_MAX_RECURSION` 一致）
+const MAX_SCRIPT_DEPTH: usize = 4;
+
+/// `#!` 行的最大长度（与 Linux 的 `BINPRM_BUF_SIZE` 一致）
+const SHEBANG_MAX_LEN: usize = 256;
+
+/// 解析脚本首行的 `#!interpreter [arg]`
+///
+/// 与 Linux 一致：解释器路径之后的剩余部分（去除首尾空白）整体作为一个参数。
+///
+/// ## Arguments
+///
+/// * `data` - 脚本文件内容
+///
+/// ## Returns
+///
+/// - `Some((interpreter, arg))`：解析成功
+/// - `None`：不是以 `#!` 开头，或首行中没有解释器路径
+fn parse_shebang(data: &[u8]) -> Option<(String, Option<String>)> {
+    let line = data.strip_prefix(b"#!")?;
+    let line = &line[..line.len().min(SHEBANG_MAX_LEN - 2)];
+    let line = match line.iter().position(|&c| c == b'\n') {
+        Some(end) => &line[..end],
+        None => line,
+    };
+    let line = core::str::from_utf8(line).ok()?.trim();
+    let (interpreter, arg) = match line.split_once([' ', '\t']) {
+        Some((interpreter, arg)) => (interpreter, Some(arg.trim())),
+        None => (line, None),
+    };
+    if interpreter.is_empty() {
+        return None;
+    }
+    Some((
+        String::from(interpreter),
+        arg.filter(|arg| !arg.is_empty()).map(String::from),
+    ))
+}
+
+/// 系统调用：执行新程序（execve）
 ///
-/// 实现 `execve(2)` 的简化版本，用指定的程序镜像替换当前进程的
-/// 地址空间。若加载成功，不返回；若失败，返回 -1。
+/// 实现 `execve(2)`，用指定的程序镜像替换当前进程的地址空间，
+/// 并按 Linux 布局在新用户栈上放置 argc、argv、envp 与辅助向量。
+/// 若加载成功，不返回；若失败，返回负的错误码。
 ///
 /// ## Arguments
 ///
 /// * `path` - 指向用户空间以 `\0` 结尾的程序名字符串
+/// * `args` - 以空指针结尾的参数字符串指针数组
+/// * `envs` - 以空指针结尾的环境变量字符串指针数组（可为空指针）
 ///
 /// ## Returns
 ///
-/// - 成功时返回 0（实际执行不会返回到此处，进程上下文被替换）
-/// - 失败时返回 -1（未找到指定程序）
+/// - 成功时返回 argc（写入新程序的 `a0`，进程上下文已被替换）
+/// - 文件既不是 ELF 也不是 `#!` 脚本，或 ELF 未通过校验时返回 `-ENOEXEC`
+/// - 新地址空间超过 `RLIMIT_AS`，或物理页帧不足以建立新地址空间时返回 `-ENOMEM`
+/// - 参数、环境变量与辅助向量放不进 `RLIMIT_STACK` 大小的用户栈时返回 `-E2BIG`
+/// - 路径、参数或环境变量的指针无效时返回 `-EFAULT`，字符串过长时返回 `-ENAMETOOLONG`
+/// - 未找到指定程序时返回 `-ENOENT`
 ///
 /// ## 行为说明
 ///
-/// 1. 从用户态读取程序名字符串
+/// 1. 从用户态读取程序名、参数与环境变量
 /// 2. 打开文件
 /// 3. 读取文件内容
-/// 4. 调用进程的 `exec` 方法重建地址空间并跳转到新入口
+/// 4. 若文件以 `#!` 开头，改为执行首行指定的解释器，
+///    参数变为 `interpreter [arg] path argv[1..]`，并重复 2~4（最多嵌套 4 层）
+/// 5. 调用进程的 `exec` 方法校验 ELF、重建地址空间并跳转到新入口
 ///
 /// ## 进程替换特性
 ///
@@ -213,30 +300,57 @@ pub fn sys_fork() -> isize {
 ///
 /// ## 安全考虑
 ///
-/// 通过 [`translated_str`] 安全地读取用户空间的程序路径字符串。
-/// 参数向量以空指针结尾逐项读取；成功加载后不会返回到调用点（地址空间被替换）。
-pub fn sys_exec(path: *const u8, mut args: *const usize) -> isize {
+/// 通过 [`copy_str_from_user`] 读取用户空间的程序路径字符串，指针无效时不会导致内核 panic。
+/// 参数与环境变量向量以空指针结尾逐项读取；成功加载后不会返回到调用点（地址空间被替换）。
+pub fn sys_execve(path: *const u8, args: *const usize, envs: *const usize) -> isize {
     let token = current_user_token();
-    let path = translated_str(token, path);
-    let mut args_vec = Vec::new();
-    loop {
-        let arg_str_ptr = *translated_ref(token, args);
-        if arg_str_ptr == 0 {
-            break;
+    let mut path = match copy_str_from_user(token, path) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+    let mut args_vec = match copy_str_array_from_user(token, args) {
+        Ok(args) => args,
+        Err(err) => return err,
+    };
+    let envs_vec = match copy_str_array_from_user(token, envs) {
+        Ok(envs) => envs,
+        Err(err) => return err,
+    };
+    let mut depth = 0;
+    let all_data = loop {
+        let Some(data) = open_file(path.as_str(), OpenFlags::RDONLY) else {
+            return -ENOENT;
+        };
+        let all_data = data.read_all();
+        if all_data.starts_with(&ELF_MAGIC) {
+            break all_data;
         }
-        args_vec.push(translated_str(token, arg_str_ptr as *const u8));
-        unsafe {
-            args = args.add(1);
+        if depth == MAX_SCRIPT_DEPTH {
+            return -ENOEXEC;
         }
-    }
-    if let Some(data) = open_file(path.as_str(), OpenFlags::RDONLY) {
-        let all_data = data.read_all();
-        let process = current_process().unwrap();
-        let argc = args_vec.len();
-        process.exec(all_data.as_slice(), args_vec);
-        argc as isize
-    } else {
-        -1
+        let Some((interpreter, arg)) = parse_shebang(&all_data) else {
+            return -ENOEXEC;
+        };
+        // 解释器取代 argv[0]，脚本路径作为解释器的参数
+        let mut new_args = Vec::with_capacity(args_vec.len() + 2);
+        new_args.push(interpreter.clone());
+        new_args.extend(arg);
+        new_args.push(path);
+        new_args.extend(args_vec.into_iter().skip(1));
+        args_vec = new_args;
+        path = interpreter;
+        depth += 1;
+    };
+    let process = current_process().unwrap();
+    let argc = args_vec.len();
+    match process.exec(all_data.as_slice(), args_vec, envs_vec) {
+        Ok(()) => argc as isize,
+        Err(ExecError::BadElf(err)) => {
+            ::log::debug!("exec {}: invalid elf ({:?})", path, err);
+            -ENOEXEC
+        }
+        Err(ExecError::AddressSpaceLimit | ExecError::OutOfMemory) => -ENOMEM,
+        Err(ExecError::ArgumentListTooLong) => -E2BIG,
     }
 }
 
@@ -253,19 +367,23 @@ pub fn sys_exec(path: *const u8, mut args: *const usize) -> isize {
 /// ## Returns
 ///
 /// - 成功时返回已回收子进程的 PID
-/// - 若没有匹配的子进程返回 -1
-/// - 若暂时没有已退出的符合条件的子进程返回 -2（可由上层重试/阻塞）
+/// - 被跟踪的子进程进入跟踪停止时返回它的 PID 但不回收，状态为
+///   `(信号编号 << 8) | 0x7f`；每次停止只报告一次
+/// - 若没有匹配的子进程返回 `-ECHILD`
+/// - 若暂时没有已退出的符合条件的子进程返回 `-EAGAIN`（可由上层重试/阻塞）
+/// - `exit_code_ptr` 非空且不可写时返回 `-EFAULT`，子进程不会被回收
 ///
 /// ## 行为说明
 ///
 /// 1. 校验待等待的子进程是否存在
 /// 2. 查找符合条件且已处于 Zombie 状态的子进程
 /// 3. 回收其 Process 对象，获取退出码并写回用户缓冲区
+/// 4. 没有可回收的子进程时，查找尚未报告的跟踪停止
 ///
 /// ## 等待策略
 ///
 /// - **阻塞等待**：如果指定 PID 的子进程尚未退出，父进程会等待
-/// - **非阻塞检查**：如果暂时没有符合条件的子进程，返回 -2
+/// - **非阻塞检查**：如果暂时没有符合条件的子进程，返回 `-EAGAIN`
 /// - **任意子进程**：传入 `pid = -1` 等待任意子进程
 ///
 /// ## 僵尸进程处理
@@ -278,8 +396,7 @@ pub fn sys_exec(path: *const u8, mut args: *const usize) -> isize {
 ///
 /// ## Safety
 ///
-/// 通过 `translated_refmut()` 将退出码写入用户空间，调用前已验证指针
-/// 在当前地址空间内有效（失败会 panic；未来可改为错误返回）。
+/// 通过 [`copy_to_user`] 将退出码写入用户空间；`exit_code_ptr` 为空时不写回。
 pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
     let process = current_process().unwrap();
     let mut inner = process.inner_exclusive_access();
@@ -288,54 +405,80 @@ pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
         .iter()
         .any(|p| pid == -1 || pid as usize == p.getpid())
     {
-        return -1;
+        return -ECHILD;
     }
     let pair = inner.children.iter().enumerate().find(|(_, p)| {
         p.inner_exclusive_access().is_zombie() && (pid == -1 || pid as usize == p.getpid())
     });
     if let Some((idx, _)) = pair {
+        let exit_code = inner.children[idx].inner_exclusive_access().exit_code;
+        if !exit_code_ptr.is_null()
+            && copy_to_user(inner.memory_set.token(), exit_code_ptr, &exit_code).is_err()
+        {
+            return -EFAULT;
+        }
         let child = inner.children.remove(idx);
         assert_eq!(Arc::strong_count(&child), 1);
-        let found_pid = child.getpid();
-        let exit_code = child.inner_exclusive_access().exit_code;
-        *translated_refmut(inner.memory_set.token(), exit_code_ptr) = exit_code;
-        found_pid as isize
+        child.getpid() as isize
+    } else if let Some(child) = inner.children.iter().find(|p| {
+        (pid == -1 || pid as usize == p.getpid())
+            && p.inner_exclusive_access()
+                .ptrace
+                .as_ref()
+                .is_some_and(|ptrace| ptrace.is_stopped() && !ptrace.stop_reported)
+    }) {
+        let mut child_inner = child.inner_exclusive_access();
+        let ptrace = child_inner.ptrace.as_mut().unwrap();
+        let status = ((ptrace.stop_sig.unwrap() << 8) | 0x7f) as i32;
+        if !exit_code_ptr.is_null()
+            && copy_to_user(inner.memory_set.token(), exit_code_ptr, &status).is_err()
+        {
+            return -EFAULT;
+        }
+        ptrace.stop_reported = true;
+        child.getpid() as isize
     } else {
-        -2
+        -EAGAIN
     }
 }
 
 /// 系统调用：设置信号屏蔽字（sigprocmask）
 ///
-/// 将当前进程的信号屏蔽集合设为 `mask`，返回旧的屏蔽集合。
-/// 仅接受由 `SignalFlags` 可表示的位集合。
+/// 将当前进程的信号屏蔽集合设为 `mask`，旧的屏蔽集合写回 `old_mask`。
+/// 仅接受由 `SignalFlags` 可表示的位集合；`SIGKILL` 与 `SIGSTOP` 不可屏蔽，
+/// 对应位会被静默忽略。
+///
+/// 实时信号占用到第 63 位，旧屏蔽集合无法作为返回值与错误码区分，
+/// 因此与 `rt_sigprocmask` 的 `oldset` 一样通过用户指针返回。
 ///
 /// ## Arguments
 ///
 /// * `mask` - 新的信号屏蔽位集合（按位编码）
+/// * `old_mask` - 旧屏蔽集合写回的用户指针（可为空）
 ///
 /// ## Returns
 ///
-/// - 成功：返回旧的屏蔽集合（按位编码）
-/// - 失败：返回 -1（如位集合非法或当前进程不存在）
-pub fn sys_sigprocmask(mask: u32) -> isize {
-    if let Some(process) = current_process() {
-        let mut inner = process.inner_exclusive_access();
-        let old_mask = inner.signal_mask;
-        if let Some(flag) = SignalFlags::from_bits(mask) {
-            inner.signal_mask = flag;
-            old_mask.bits() as isize
-        } else {
-            -1
-        }
-    } else {
-        -1
+/// - 0：成功
+/// - `-EINVAL`：位集合非法
+/// - `-EFAULT`：`old_mask` 指针无效，此时屏蔽集合不变
+pub fn sys_sigprocmask(mask: u64, old_mask: *mut u64) -> isize {
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    let Some(flag) = SignalFlags::from_bits(mask) else {
+        return -EINVAL;
+    };
+    let token = inner.memory_set.token();
+    if !old_mask.is_null() && copy_to_user(token, old_mask, &inner.signal_mask.bits()).is_err() {
+        return -EFAULT;
     }
+    inner.signal_mask = flag - (SignalFlags::SIGKILL | SignalFlags::SIGSTOP);
+    0
 }
 
 /// 系统调用：向目标进程发送信号（kill）
 ///
-/// 按 `signum` 将对应位写入目标进程的 `signals` 集合，若信号已存在则返回错误。
+/// 按 `signum` 将对应位写入目标进程的 `signals` 集合。标准信号已待决时与之合并，
+/// 实时信号每次发送都会排队；`signum` 为 0 时只检查目标进程是否存在。
 ///
 /// ## Arguments
 ///
@@ -344,52 +487,64 @@ pub fn sys_sigprocmask(mask: u32) -> isize {
 ///
 /// ## Returns
 ///
-/// - 0：发送成功
-/// - -1：目标不存在 / `signum` 非法 / 信号已存在
+/// - 0：发送成功（或目标存在）
+/// - 错误码同 [`sys_sigqueue`]
 pub fn sys_kill(pid: usize, signum: i32) -> isize {
+    sys_sigqueue(pid, signum, 0)
+}
+
+/// 系统调用：发送带附带数据的信号（sigqueue）
+///
+/// 与 [`sys_kill`] 相同，但额外携带 `value`。对实时信号（编号 >= 32），
+/// 每次发送单独排队，投递时 `value` 通过 `a1` 传给用户处理函数；
+/// 标准信号不排队，`value` 被丢弃。
+///
+/// ## Arguments
+///
+/// * `pid` - 目标进程 PID
+/// * `signum` - 信号编号（0..=MAX_SIG）
+/// * `value` - 附带数据
+///
+/// ## Returns
+///
+/// - 0：发送成功（或 `signum` 为 0 且目标存在）
+/// - `-EINVAL`：`signum` 非法
+/// - `-ESRCH`：目标不存在
+/// - `-EAGAIN`：实时信号队列已满
+pub fn sys_sigqueue(pid: usize, signum: i32, value: usize) -> isize {
+    if signum < 0 || signum as usize > MAX_SIG {
+        return -EINVAL;
+    }
     if let Some(process) = pid2process(pid) {
-        if let Some(flag) = SignalFlags::from_bits(1 << signum) {
-            // insert the signal if legal
-            let mut process_ref = process.inner_exclusive_access();
-            if process_ref.signals.contains(flag) {
-                return -1;
-            }
-            process_ref.signals.insert(flag);
+        if signum == 0 {
+            return 0;
+        }
+        if signal_process(&process, signum as usize, value) {
             0
         } else {
-            -1
+            -EAGAIN
         }
     } else {
-        -1
-    }
-}
-
-fn check_sigaction_error(signal: SignalFlags, action: usize, old_action: usize) -> bool {
-    if action == 0
-        || old_action == 0
-        || signal == SignalFlags::SIGKILL
-        || signal == SignalFlags::SIGSTOP
-    {
-        return true;
-    } else {
-        false
+        -ESRCH
     }
 }
 
 /// 系统调用：设置信号处理动作（sigaction）
 ///
 /// 为信号安装/查询处理动作。禁止对 `SIGKILL` 与 `SIGSTOP` 自定义处理。
+/// `action` 为空时只查询，`old_action` 为空时不写回旧动作。
 ///
 /// ## Arguments
 ///
 /// * `signum` - 信号编号（0..=MAX_SIG）
-/// * `action` - 新动作的用户指针（只读）
-/// * `old_action` - 旧动作写回的用户指针（可写）
+/// * `action` - 新动作的用户指针（只读，可为空）
+/// * `old_action` - 旧动作写回的用户指针（可写，可为空）
 ///
 /// ## Returns
 ///
-/// - 0：设置成功，并将旧动作写回
-/// - -1：参数非法或越界
+/// - 0：成功
+/// - `-EINVAL`：`signum` 非法，或试图修改 `SIGKILL`/`SIGSTOP` 的动作
+/// - `-EFAULT`：`action` 不可读或 `old_action` 不可写，此时动作不变
 pub fn sys_sigaction(
     signum: i32,
     action: *const SignalAction,
@@ -398,20 +553,31 @@ pub fn sys_sigaction(
     let token = current_user_token();
     let process = current_process().unwrap();
     let mut inner = process.inner_exclusive_access();
-    if signum as usize > MAX_SIG {
-        return -1;
+    if signum < 0 {
+        return -EINVAL;
     }
-    if let Some(flag) = SignalFlags::from_bits(1 << signum) {
-        if check_sigaction_error(flag, action as usize, old_action as usize) {
-            return -1;
-        }
-        let prev_action = inner.signal_actions.table[signum as usize];
-        *translated_refmut(token, old_action) = prev_action;
-        inner.signal_actions.table[signum as usize] = *translated_ref(token, action);
-        0
+    let Some(flag) = SignalFlags::from_signum(signum as usize) else {
+        return -EINVAL;
+    };
+    if !action.is_null() && (flag == SignalFlags::SIGKILL || flag == SignalFlags::SIGSTOP) {
+        return -EINVAL;
+    }
+    let prev_action = inner.signal_actions.table[signum as usize];
+    let new_action = if action.is_null() {
+        None
     } else {
-        -1
+        let Ok(new_action) = copy_from_user(token, action) else {
+            return -EFAULT;
+        };
+        Some(new_action)
+    };
+    if !old_action.is_null() && copy_to_user(token, old_action, &prev_action).is_err() {
+        return -EFAULT;
     }
+    if let Some(new_action) = new_action {
+        inner.signal_actions.table[signum as usize] = new_action;
+    }
+    0
 }
 
 /// 系统调用：从用户信号处理程序返回（sigreturn）
@@ -422,15 +588,75 @@ pub fn sys_sigaction(
 /// ## Returns
 ///
 /// - `a0`：原用户态上下文中的 a0 值
-/// - -1：当前进程不存在
+/// - `-EINVAL`：不在信号处理程序中（未进入过处理程序，或 `exec` 已丢弃保存的上下文）
 pub fn sys_sigreturn() -> isize {
-    if let Some(process) = current_process() {
-        let mut inner = process.inner_exclusive_access();
-        inner.handling_sig = -1;
-        let trap_ctx = inner.trap_cx();
-        *trap_ctx = inner.trap_ctx_backup.unwrap();
-        trap_ctx.x[10] as isize
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    let Some(backup) = inner.trap_ctx_backup.take() else {
+        return -EINVAL;
+    };
+    inner.handling_sig = -1;
+    let trap_ctx = inner.trap_cx();
+    *trap_ctx = backup;
+    trap_ctx.x[10] as isize
+}
+
+/// 系统调用：查询并设置资源限制（prlimit64）
+///
+/// 先将目标进程 `resource` 的旧限制写回 `old_limit`（非空时），
+/// 再以 `new_limit`（非空时）替换。新限制需满足 `rlim_cur <= rlim_max`，
+/// 且硬限制只能降低。
+///
+/// ## Arguments
+///
+/// * `pid` - 目标进程 PID，0 表示当前进程
+/// * `resource` - 资源编号（`RLIMIT_*`）
+/// * `new_limit` - 新限制的用户指针（可为空）
+/// * `old_limit` - 旧限制写回的用户指针（可为空）
+///
+/// ## Returns
+///
+/// - 0：成功
+/// - `-EINVAL`：资源编号非法或新限制 `rlim_cur > rlim_max`
+/// - `-EPERM`：试图提高硬限制
+/// - `-ESRCH`：目标进程不存在
+/// - `-EFAULT`：`new_limit` 不可读或 `old_limit` 不可写，此时限制不变
+pub fn sys_prlimit64(
+    pid: usize,
+    resource: usize,
+    new_limit: *const RLimit,
+    old_limit: *mut RLimit,
+) -> isize {
+    if resource >= RLIM_NLIMITS {
+        return -EINVAL;
+    }
+    let token = current_user_token();
+    let process = if pid == 0 {
+        current_process()
+    } else {
+        pid2process(pid)
+    };
+    let Some(process) = process else {
+        return -ESRCH;
+    };
+    let mut inner = process.inner_exclusive_access();
+    let prev_limit = inner.rlimits.table[resource];
+    let limit = if new_limit.is_null() {
+        None
     } else {
-        -1
+        match copy_from_user(token, new_limit) {
+            Ok(limit) => Some(limit),
+            Err(err) => return err,
+        }
+    };
+    if !old_limit.is_null() && copy_to_user(token, old_limit, &prev_limit).is_err() {
+        return -EFAULT;
+    }
+    match limit {
+        Some(limit) => match inner.rlimits.set(resource, limit) {
+            Ok(()) => 0,
+            Err(errno) => errno,
+        },
+        None => 0,
     }
 }
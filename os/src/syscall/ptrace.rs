@@ -0,0 +1,206 @@
+//! # 进程跟踪
+//!
+//! [`sys_ptrace`] 实现 `ptrace(2)` 的一个子集，供调试器控制子进程：
+//!
+//! | 请求 | 编号 | 作用 |
+//! |------|------|------|
+//! | `PTRACE_TRACEME` | 0 | 让父进程跟踪自己 |
+//! | `PTRACE_PEEKTEXT` / `PTRACE_PEEKDATA` | 1 / 2 | 读取被跟踪进程的一个字，写到 `data` 指向处 |
+//! | `PTRACE_POKETEXT` / `PTRACE_POKEDATA` | 4 / 5 | 把 `data` 写入被跟踪进程的 `addr` 处 |
+//! | `PTRACE_CONT` | 7 | 继续执行，`data` 非 0 时投递该信号 |
+//! | `PTRACE_KILL` | 8 | 终止被跟踪进程 |
+//! | `PTRACE_SINGLESTEP` | 9 | 执行一条指令后再次停止 |
+//! | `PTRACE_GETREGS` / `PTRACE_SETREGS` | 12 / 13 | 读写寄存器 |
+//! | `PTRACE_ATTACH` | 16 | 跟踪自己的子进程并发送 `SIGSTOP` |
+//! | `PTRACE_DETACH` | 17 | 解除跟踪并继续执行 |
+//!
+//! 寄存器以 `[usize; 32]` 交换：下标 0 为 `pc`（`sepc`），下标 1 ~ 31 为
+//! `x1` ~ `x31`。`POKETEXT` 可以写入只读的代码段，用于设置断点。
+
+use crate::errno::Errno::{EFAULT, EIO, EPERM, ESRCH};
+use crate::mm::{copy_from_user, copy_to_user, poke_user_bytes};
+use crate::process::{
+    MAX_SIG, ProcessControlBlock, PtraceState, SignalFlags, current_process, pid2process,
+};
+use alloc::sync::Arc;
+
+const PTRACE_TRACEME: usize = 0;
+const PTRACE_PEEKTEXT: usize = 1;
+const PTRACE_PEEKDATA: usize = 2;
+const PTRACE_POKETEXT: usize = 4;
+const PTRACE_POKEDATA: usize = 5;
+const PTRACE_CONT: usize = 7;
+const PTRACE_KILL: usize = 8;
+const PTRACE_SINGLESTEP: usize = 9;
+const PTRACE_GETREGS: usize = 12;
+const PTRACE_SETREGS: usize = 13;
+const PTRACE_ATTACH: usize = 16;
+const PTRACE_DETACH: usize = 17;
+
+/// `PTRACE_GETREGS` / `PTRACE_SETREGS` 交换的寄存器个数
+const PTRACE_NREGS: usize = 32;
+
+/// 系统调用：进程跟踪（ptrace）
+///
+/// 除 `PTRACE_TRACEME` 与 `PTRACE_ATTACH` 外，目标必须是调用者正在跟踪、
+/// 且处于跟踪停止状态（已由 `waitpid` 得知）的子进程。
+///
+/// ## Arguments
+///
+/// * `request` - 请求编号，见模块文档
+/// * `pid` - 被跟踪进程的 PID（`PTRACE_TRACEME` 忽略）
+/// * `addr` - 被跟踪进程中的地址（`PEEK*` / `POKE*` 使用）
+/// * `data` - 请求相关的数据：写入的值、信号编号或调用者中的缓冲区地址
+///
+/// ## Returns
+///
+/// - 0：成功
+/// - `-EPERM`：已被跟踪、没有父进程，或 `ATTACH` 的目标不是调用者的子进程
+/// - `-ESRCH`：目标不存在、未被调用者跟踪或未处于跟踪停止状态
+/// - `-EIO`：未知请求、信号编号非法，或被跟踪进程中的地址不可访问
+/// - `-EFAULT`：调用者的 `data` 缓冲区不可访问
+pub fn sys_ptrace(request: usize, pid: usize, addr: usize, data: usize) -> isize {
+    match request {
+        PTRACE_TRACEME => ptrace_traceme(),
+        PTRACE_ATTACH => ptrace_attach(pid),
+        _ => match ptrace_stopped_child(pid) {
+            Ok(tracee) => ptrace_request(&tracee, request, addr, data),
+            Err(errno) => errno,
+        },
+    }
+}
+
+/// `PTRACE_TRACEME`：由父进程跟踪当前进程
+fn ptrace_traceme() -> isize {
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    let has_parent = inner
+        .parent
+        .as_ref()
+        .is_some_and(|parent| parent.upgrade().is_some());
+    if !has_parent || inner.ptrace.is_some() {
+        return -EPERM;
+    }
+    inner.ptrace = Some(PtraceState::default());
+    0
+}
+
+/// `PTRACE_ATTACH`：跟踪调用者的子进程，并让它以 `SIGSTOP` 停止
+fn ptrace_attach(pid: usize) -> isize {
+    let process = current_process().unwrap();
+    let Some(target) = pid2process(pid) else {
+        return -ESRCH;
+    };
+    if !process
+        .inner_exclusive_access()
+        .children
+        .iter()
+        .any(|child| Arc::ptr_eq(child, &target))
+    {
+        return -EPERM;
+    }
+    let mut target_inner = target.inner_exclusive_access();
+    if target_inner.ptrace.is_some() || target_inner.is_zombie() {
+        return -EPERM;
+    }
+    target_inner.ptrace = Some(PtraceState::default());
+    target_inner.send_signal(SignalFlags::SIGSTOP.lowest_signum().unwrap(), 0);
+    0
+}
+
+/// 查找调用者正在跟踪且处于跟踪停止状态的子进程
+fn ptrace_stopped_child(pid: usize) -> Result<Arc<ProcessControlBlock>, isize> {
+    let process = current_process().unwrap();
+    let tracee = pid2process(pid).ok_or(-ESRCH)?;
+    let is_child = process
+        .inner_exclusive_access()
+        .children
+        .iter()
+        .any(|child| Arc::ptr_eq(child, &tracee));
+    let stopped = tracee
+        .inner_exclusive_access()
+        .ptrace
+        .as_ref()
+        .is_some_and(PtraceState::is_stopped);
+    if is_child && stopped {
+        Ok(tracee)
+    } else {
+        Err(-ESRCH)
+    }
+}
+
+/// 对处于跟踪停止状态的子进程执行请求
+fn ptrace_request(tracee: &ProcessControlBlock, request: usize, addr: usize, data: usize) -> isize {
+    let token = current_process()
+        .unwrap()
+        .inner_exclusive_access()
+        .memory_set
+        .token();
+    let mut inner = tracee.inner_exclusive_access();
+    let tracee_token = inner.memory_set.token();
+    match request {
+        PTRACE_PEEKTEXT | PTRACE_PEEKDATA => {
+            let Ok(value) = copy_from_user(tracee_token, addr as *const usize) else {
+                return -EIO;
+            };
+            if copy_to_user(token, data as *mut usize, &value).is_err() {
+                return -EFAULT;
+            }
+            0
+        }
+        PTRACE_POKETEXT | PTRACE_POKEDATA => {
+            match poke_user_bytes(tracee_token, addr as *mut u8, &data.to_le_bytes()) {
+                Ok(()) => 0,
+                Err(_) => -EIO,
+            }
+        }
+        PTRACE_GETREGS => {
+            let cx = inner.trap_cx();
+            let mut regs = [0usize; PTRACE_NREGS];
+            regs[0] = cx.sepc;
+            regs[1..].copy_from_slice(&cx.x[1..]);
+            match copy_to_user(token, data as *mut [usize; PTRACE_NREGS], &regs) {
+                Ok(()) => 0,
+                Err(_) => -EFAULT,
+            }
+        }
+        PTRACE_SETREGS => {
+            let Ok(regs) = copy_from_user(token, data as *const [usize; PTRACE_NREGS]) else {
+                return -EFAULT;
+            };
+            let cx = inner.trap_cx();
+            cx.sepc = regs[0];
+            cx.x[1..].copy_from_slice(&regs[1..]);
+            0
+        }
+        PTRACE_KILL => {
+            inner.send_signal(SignalFlags::SIGKILL.lowest_signum().unwrap(), 0);
+            inner.ptrace.as_mut().unwrap().stop_sig = None;
+            0
+        }
+        PTRACE_CONT | PTRACE_SINGLESTEP | PTRACE_DETACH => {
+            if data > MAX_SIG {
+                return -EIO;
+            }
+            let cx = inner.trap_cx();
+            let ptrace = inner.ptrace.as_mut().unwrap();
+            if request == PTRACE_SINGLESTEP
+                && ptrace.set_step_breakpoints(tracee_token, cx).is_err()
+            {
+                return -EIO;
+            }
+            ptrace.stop_sig = None;
+            if request == PTRACE_DETACH {
+                inner.ptrace_detach();
+            } else if data != 0 {
+                // 带着这个信号继续执行时不再进入跟踪停止
+                ptrace.inject_sig = Some(data);
+            }
+            if data != 0 {
+                inner.send_signal(data, 0);
+            }
+            0
+        }
+        _ => -EIO,
+    }
+}
@@ -0,0 +1,156 @@
+//! # 共享内存相关系统调用
+//!
+//! 段的创建与生命周期由 [`crate::mm`] 中的共享内存段管理，这里只负责解析
+//! 参数、在当前进程的地址空间中附加/解除附加段，以及与用户空间交换 `shmid_ds`。
+//!
+//! ## 支持的系统调用
+//!
+//! - [`sys_shmget`] - 按键查找或创建段
+//! - [`sys_shmat`]  - 把段附加到当前地址空间
+//! - [`sys_shmdt`]  - 解除附加
+//! - [`sys_shmctl`] - 查询状态、修改权限、标记删除
+
+use crate::config::PAGE_SIZE;
+use crate::errno::Errno::{EFAULT, EINVAL};
+use crate::mm::{
+    MapPermission, ShmIdDs, VirtAddr, copy_from_user, copy_to_user, shm_find, shm_get,
+};
+use crate::process::{current_process, current_user_token};
+
+/// 键不存在时创建段
+const IPC_CREAT: usize = 0o1000;
+/// 与 `IPC_CREAT` 一起使用时，键已存在则失败
+const IPC_EXCL: usize = 0o2000;
+/// 标记删除
+const IPC_RMID: usize = 0;
+/// 修改权限
+const IPC_SET: usize = 1;
+/// 查询状态
+const IPC_STAT: usize = 2;
+/// 64 位 `shmid_ds` 格式标志，这里总是使用该格式
+const IPC_64: usize = 0x100;
+
+/// 只读附加
+const SHM_RDONLY: usize = 0o10000;
+/// 附加地址向下对齐到 `SHMLBA`
+const SHM_RND: usize = 0o20000;
+/// 附加地址的对齐要求
+const SHMLBA: usize = PAGE_SIZE;
+
+/// 系统调用：按键查找或创建共享内存段（shmget）
+///
+/// ## Arguments
+///
+/// * `key` - 段的键，`IPC_PRIVATE`（0）总是创建新段
+/// * `size` - 段的字节数
+/// * `shmflg` - `IPC_CREAT`/`IPC_EXCL` 与新段的权限位
+///
+/// ## Returns
+///
+/// 段标识符；失败时返回 `-ENOENT`、`-EEXIST`、`-EINVAL`、`-ENOSPC` 或 `-ENOMEM`
+pub fn sys_shmget(key: i32, size: usize, shmflg: usize) -> isize {
+    let pid = current_process().unwrap().getpid();
+    match shm_get(
+        key,
+        size,
+        (shmflg & 0o777) as u32,
+        shmflg & IPC_CREAT != 0,
+        shmflg & IPC_EXCL != 0,
+        pid,
+    ) {
+        Ok(id) => id as isize,
+        Err(errno) => errno,
+    }
+}
+
+/// 系统调用：把共享内存段附加到当前地址空间（shmat）
+///
+/// ## Arguments
+///
+/// * `shmid` - 段标识符
+/// * `shmaddr` - 附加地址，0 表示由内核选择
+/// * `shmflg` - `SHM_RDONLY`/`SHM_RND`
+///
+/// ## Returns
+///
+/// 附加的起始地址；段不存在、地址未对齐或与已有区域重叠时返回 `-EINVAL`
+pub fn sys_shmat(shmid: usize, shmaddr: usize, shmflg: usize) -> isize {
+    let Some(segment) = shm_find(shmid) else {
+        return -EINVAL;
+    };
+    let start_va = match shmaddr {
+        0 => None,
+        addr if shmflg & SHM_RND != 0 => Some(VirtAddr::from(addr & !(SHMLBA - 1))),
+        addr if addr % SHMLBA == 0 => Some(VirtAddr::from(addr)),
+        _ => return -EINVAL,
+    };
+    let mut perm = MapPermission::R | MapPermission::U;
+    if shmflg & SHM_RDONLY == 0 {
+        perm |= MapPermission::W;
+    }
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    match inner.memory_set.attach_shm(start_va, segment.clone(), perm) {
+        Some(va) => {
+            segment.touch(process.getpid(), true);
+            usize::from(va) as isize
+        }
+        None => -EINVAL,
+    }
+}
+
+/// 系统调用：解除附加在 `shmaddr` 处的共享内存段（shmdt）
+///
+/// ## Returns
+///
+/// - 0：成功
+/// - `-EINVAL`：该地址处没有附加共享内存段
+pub fn sys_shmdt(shmaddr: usize) -> isize {
+    if shmaddr % PAGE_SIZE != 0 {
+        return -EINVAL;
+    }
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    match inner.memory_set.detach_shm(VirtAddr::from(shmaddr)) {
+        Some(segment) => {
+            segment.touch(process.getpid(), false);
+            0
+        }
+        None => -EINVAL,
+    }
+}
+
+/// 系统调用：共享内存段控制（shmctl）
+///
+/// ## Arguments
+///
+/// * `shmid` - 段标识符
+/// * `cmd` - `IPC_STAT`、`IPC_SET` 或 `IPC_RMID`
+/// * `buf` - `IPC_STAT` 写回、`IPC_SET` 读取的 `shmid_ds`
+///
+/// ## Returns
+///
+/// - 0：成功
+/// - `-EINVAL`：段不存在或命令无效
+/// - `-EFAULT`：`IPC_STAT`/`IPC_SET` 的 `buf` 为空或不可访问
+pub fn sys_shmctl(shmid: usize, cmd: usize, buf: *mut ShmIdDs) -> isize {
+    let Some(segment) = shm_find(shmid) else {
+        return -EINVAL;
+    };
+    let token = current_user_token();
+    let cmd = cmd & !IPC_64;
+    match cmd {
+        IPC_STAT => {
+            if copy_to_user(token, buf, &segment.stat()).is_err() {
+                return -EFAULT;
+            }
+        }
+        IPC_SET => match copy_from_user(token, buf as *const ShmIdDs) {
+            Ok(ds) => segment.set(&ds),
+            Err(err) => return err,
+        },
+        IPC_RMID => segment.remove(),
+        _ => return -EINVAL,
+    }
+    0
+}
@@ -0,0 +1,211 @@
+//! # 系统调用跟踪
+//!
+//! 进程通过 [`sys_trace`] 设置跟踪输出后，分发器在它的每次系统调用返回时
+//! 向该文件写入一行记录，格式与 strace 类似：
+//!
+//! ```text
+//! [3] open("hello.txt", 0x0) = 4
+//! [3] write(1, "hello\n", 6) = 6
+//! [3] close(9) = -9 (EBADF)
+//! [3] exit(0) = ?
+//! ```
+//!
+//! 参数按系统调用表中登记的 [`SyscallArg`] 解码；字符串与写入的缓冲区在
+//! 调用前读取（`execve` 成功后原地址空间已不存在），过长时截断。
+
+use super::{SYSCALL_EXIT, SyscallEntry};
+use crate::errno::Errno;
+use crate::errno::Errno::{EBADF, EPERM, ESRCH};
+use crate::fs::{File, write_kernel_bytes};
+use crate::mm::{copy_from_user_bytes, copy_str_from_user};
+use crate::process::{current_process, current_user_token, pid2process};
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use core::fmt::Write;
+
+/// 关闭跟踪时传入的 `fd`（即 -1）
+const TRACE_OFF: usize = usize::MAX;
+/// 字符串与缓冲区参数最多显示的字节数
+const TRACE_STR_MAX: usize = 32;
+
+/// 系统调用参数的显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyscallArg {
+    /// 有符号十进制：文件描述符、PID、信号编号等
+    Int,
+    /// 无符号十进制：长度、个数
+    Uint,
+    /// 十六进制：指针、标志位
+    Hex,
+    /// 以 `\0` 结尾的用户字符串
+    Str,
+    /// 用户缓冲区，长度由下一个参数给出
+    Buf,
+}
+
+/// 系统调用：设置系统调用跟踪（trace）
+///
+/// 目标进程此后的每次系统调用都向调用者的 `fd` 所指文件写入一行记录。
+/// 跟踪持有文件本身的引用，之后关闭 `fd` 不影响输出；`fork` 出的子进程不被跟踪，
+/// `exec` 后跟踪保持。
+///
+/// 记录在目标进程的上下文中通过 [`File::write_nowait`] 写出，不会让目标进程阻塞
+/// 或收到信号（如读端已关闭的管道引发的 `SIGPIPE`）。一行记录没能完整写入时
+/// （跟踪者不再读取、读端已关闭、文件不支持内核写入等），目标进程的跟踪随即关闭。
+///
+/// ## Arguments
+///
+/// * `pid` - 目标进程 PID，0 表示当前进程；只能是调用者自己或它的子进程
+/// * `fd` - 调用者中用于输出的可写文件描述符，-1 表示关闭跟踪
+///
+/// ## Returns
+///
+/// - 0：成功
+/// - `-EBADF`：`fd` 未打开或不可写
+/// - `-ESRCH`：目标进程不存在
+/// - `-EPERM`：目标不是调用者或它的子进程
+pub fn sys_trace(pid: usize, fd: usize) -> isize {
+    let process = current_process().unwrap();
+    let inner = process.inner_exclusive_access();
+    let file = if fd == TRACE_OFF {
+        None
+    } else {
+        match inner.fd_table.get(fd) {
+            Some(Some(file)) if file.writable() => Some(file.clone()),
+            _ => return -EBADF,
+        }
+    };
+    let target = if pid == 0 || pid == process.getpid() {
+        process.clone()
+    } else {
+        let Some(target) = pid2process(pid) else {
+            return -ESRCH;
+        };
+        if !inner
+            .children
+            .iter()
+            .any(|child| Arc::ptr_eq(child, &target))
+        {
+            return -EPERM;
+        }
+        target
+    };
+    drop(inner);
+    target.inner_exclusive_access().trace = file;
+    0
+}
+
+/// 当前进程的跟踪输出
+pub(super) fn current_trace() -> Option<Arc<dyn File + Send + Sync>> {
+    current_process()?.inner_exclusive_access().trace.clone()
+}
+
+/// 执行一次被跟踪的系统调用，并把记录写入 `file`
+///
+/// ## Arguments
+///
+/// * `entry` - 系统调用表项
+/// * `args` - `a0` ~ `a5` 六个参数
+/// * `file` - 跟踪输出
+///
+/// ## Returns
+///
+/// 系统调用的返回值
+pub(super) fn traced_syscall(
+    entry: &SyscallEntry,
+    args: [usize; 6],
+    file: Arc<dyn File + Send + Sync>,
+) -> isize {
+    let call = format_call(entry, &args);
+    if entry.id == SYSCALL_EXIT {
+        // exit 不返回，局部变量不会被析构：先写出记录并释放对文件的引用
+        emit(&file, format!("{} = ?\n", call));
+        drop(file);
+        drop(call);
+        return (entry.handler)(args);
+    }
+    let ret = (entry.handler)(args);
+    emit(&file, format!("{} = {}\n", call, format_ret(ret)));
+    ret
+}
+
+/// 格式化调用部分，如 `[3] write(1, "hi\n", 3)`
+fn format_call(entry: &SyscallEntry, args: &[usize; 6]) -> String {
+    let token = current_user_token();
+    let pid = current_process().unwrap().getpid();
+    let mut out = format!("[{}] {}(", pid, entry.name);
+    for (idx, kind) in entry.args.iter().enumerate() {
+        if idx > 0 {
+            out.push_str(", ");
+        }
+        let arg = args[idx];
+        match kind {
+            SyscallArg::Int => write!(out, "{}", arg as isize).unwrap(),
+            SyscallArg::Uint => write!(out, "{}", arg).unwrap(),
+            SyscallArg::Hex => write!(out, "{:#x}", arg).unwrap(),
+            SyscallArg::Str => match copy_str_from_user(token, arg as *const u8) {
+                Ok(s) => push_quoted(&mut out, s.as_bytes()),
+                Err(_) => write!(out, "{:#x}", arg).unwrap(),
+            },
+            SyscallArg::Buf => {
+                let len = args.get(idx + 1).copied().unwrap_or(0);
+                let mut buf = vec![0u8; len.min(TRACE_STR_MAX)];
+                match copy_from_user_bytes(token, arg as *const u8, &mut buf) {
+                    Ok(()) => push_quoted(&mut out, &buf),
+                    Err(_) => write!(out, "{:#x}", arg).unwrap(),
+                }
+                if len > TRACE_STR_MAX {
+                    out.push_str("...");
+                }
+            }
+        }
+    }
+    out.push(')');
+    out
+}
+
+/// 格式化返回值：错误码附带名称，如 `-2 (ENOENT)`
+fn format_ret(ret: isize) -> String {
+    match Errno::from_code(-ret) {
+        Some(errno) if ret < 0 => format!("{} ({:?})", ret, errno),
+        _ => format!("{}", ret),
+    }
+}
+
+/// 把字节串加引号写入 `out`，不可打印字符转义，超过 [`TRACE_STR_MAX`] 时截断
+fn push_quoted(out: &mut String, bytes: &[u8]) {
+    out.push('"');
+    for &byte in bytes.iter().take(TRACE_STR_MAX) {
+        match byte {
+            b'\n' => out.push_str("\\n"),
+            b'\t' => out.push_str("\\t"),
+            b'\r' => out.push_str("\\r"),
+            b'"' => out.push_str("\\\""),
+            b'\\' => out.push_str("\\\\"),
+            0x20..=0x7e => out.push(byte as char),
+            _ => write!(out, "\\x{:02x}", byte).unwrap(),
+        }
+    }
+    out.push('"');
+    if bytes.len() > TRACE_STR_MAX {
+        out.push_str("...");
+    }
+}
+
+/// 把一行记录写入跟踪输出，写入失败或未写完时关闭当前进程的跟踪
+fn emit(file: &Arc<dyn File + Send + Sync>, line: String) {
+    if write_kernel_bytes(&**file, &mut line.into_bytes()) {
+        return;
+    }
+    let process = current_process().unwrap();
+    let mut inner = process.inner_exclusive_access();
+    if inner
+        .trace
+        .as_ref()
+        .is_some_and(|trace| Arc::ptr_eq(trace, file))
+    {
+        inner.trace = None;
+    }
+}
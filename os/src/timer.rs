@@ -21,18 +21,29 @@
 //! - 系统调用 `time()` 的实现
 //! - 性能测量和基准测试
 //! - 超时和延迟功能
+//!
+//! ## 定时唤醒
+//!
+//! 阻塞等待可以附带截止时间：[`add_timer`] 登记进程与到期时刻，时钟中断
+//! （以及调度器空闲时的 `wfi`）调用 [`check_timer`] 唤醒所有到期的进程。
+//! 精度为一个时间片（10ms）。
 
 use crate::config::CLOCK_FREQ;
+use crate::process::{ProcessControlBlock, wakeup_process};
 use crate::sbi::timer;
+use crate::sync::UPSafeCell;
+use alloc::collections::BinaryHeap;
+use alloc::sync::Arc;
+use core::cmp::Ordering;
+use lazy_static::*;
 
 /// 每秒的时钟中断次数 (100Hz)
 ///
 /// 定义抢占式调度的时间片频率。100Hz 意味着每 10 毫秒触发一次
 /// 时钟中断，提供良好的响应性和合理的调度开销。
-const TICKS_PER_SEC: usize = 100;
+pub const TICKS_PER_SEC: usize = 100;
 
 /// 每秒的毫秒数常量
-#[allow(unused)]
 const MSEC_PER_SEC: usize = 1000;
 
 /// 获取当前系统时间（时钟周期数）
@@ -74,7 +85,7 @@ pub fn time() -> usize {
 /// let elapsed_ms = end_ms - start_ms;
 /// ```
 pub fn time_ms() -> usize {
-    time() / (CLOCK_FREQ / 1000)
+    time() / (CLOCK_FREQ / MSEC_PER_SEC)
 }
 
 /// 设置下一次时钟中断触发时间
@@ -107,3 +118,87 @@ pub fn time_ms() -> usize {
 pub fn next_trigger() {
     timer(time() + CLOCK_FREQ / TICKS_PER_SEC);
 }
+
+/// 时间间隔，内存布局与 Linux 的 `struct timespec` 一致
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeSpec {
+    /// 秒
+    pub tv_sec: usize,
+    /// 纳秒，小于 10^9
+    pub tv_nsec: usize,
+}
+
+impl TimeSpec {
+    /// 换算为毫秒，不足一毫秒的部分向上取整，保证等待不短于要求的时间
+    pub fn as_ms(&self) -> usize {
+        self.tv_sec * MSEC_PER_SEC + self.tv_nsec.div_ceil(1_000_000)
+    }
+}
+
+/// 等待到期的定时器：到期时唤醒 `process`
+struct TimerCondVar {
+    expire_ms: usize,
+    process: Arc<ProcessControlBlock>,
+}
+
+impl PartialEq for TimerCondVar {
+    fn eq(&self, other: &Self) -> bool {
+        self.expire_ms == other.expire_ms
+    }
+}
+
+impl Eq for TimerCondVar {}
+
+impl PartialOrd for TimerCondVar {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TimerCondVar {
+    /// 反转比较顺序，使 [`BinaryHeap`] 成为以到期时间为键的小根堆
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.expire_ms.cmp(&self.expire_ms)
+    }
+}
+
+lazy_static! {
+    /// 按到期时间排序的定时器
+    static ref TIMERS: UPSafeCell<BinaryHeap<TimerCondVar>> =
+        unsafe { UPSafeCell::new(BinaryHeap::new()) };
+}
+
+/// 登记定时器，在 `expire_ms` 之后唤醒进程
+///
+/// ## Arguments
+///
+/// * `expire_ms` - 到期时刻，以 [`time_ms`] 为基准
+/// * `process` - 到期时唤醒的进程；唤醒时若它已不在阻塞态则忽略
+pub fn add_timer(expire_ms: usize, process: Arc<ProcessControlBlock>) {
+    TIMERS
+        .exclusive_access()
+        .push(TimerCondVar { expire_ms, process });
+}
+
+/// 撤销进程登记的全部定时器
+///
+/// 等待因其他事件提前结束时调用，避免过期的定时器日后误唤醒进程。
+pub fn remove_timer(process: &Arc<ProcessControlBlock>) {
+    TIMERS
+        .exclusive_access()
+        .retain(|timer| !Arc::ptr_eq(&timer.process, process));
+}
+
+/// 唤醒所有已到期定时器对应的进程
+pub fn check_timer() {
+    let current_ms = time_ms();
+    let mut timers = TIMERS.exclusive_access();
+    while let Some(timer) = timers.peek() {
+        if timer.expire_ms > current_ms {
+            break;
+        }
+        let timer = timers.pop().unwrap();
+        wakeup_process(timer.process);
+    }
+}
@@ -8,10 +8,12 @@
 //! ## 处理的陷阱类型
 //!
 //! - **系统调用** (`UserEnvCall`): 用户程序请求内核服务
-//! - **时钟中断** (`SupervisorTimer`): 实现抢占式多进程调度
+//! - **时钟中断** (`SupervisorTimer`): 实现抢占式多进程调度，并驱动协议栈的超时重传
+//! - **外部中断** (`SupervisorExternal`): 经 PLIC 分发给已注册的设备驱动
 //! - **数据访问异常** (`StoreFault`, `StorePageFault`, `LoadFault`, `LoadPageFault`): 数据内存访问违规
 //! - **指令访问异常** (`InstructionFault`, `InstructionPageFault`): 指令内存访问违规
 //! - **非法指令** (`IllegalInstruction`): 执行无效指令
+//! - **断点** (`Breakpoint`): 执行 `ebreak`，投递 `SIGTRAP` 供调试器使用
 //!
 //! ## 执行流程（更新）
 //!
@@ -20,7 +22,8 @@
 //! 3. **上下文保存**: `__alltraps` 保存所有寄存器到陷阱上下文
 //! 4. **处理分发**: `trap_handler` 根据陷阱类型执行相应处理（见下）
 //! 5. **信号阶段**: 调用 `handle_signals()` 检查/进入用户信号处理；
-//!    对致命信号，`check_signals_error_of_current()` 会返回标准退出码并退出进程
+//!    对致命信号，`check_signals_error_of_current()` 会返回标准退出码并退出进程，
+//!    默认动作为转储的信号先写出核心文件（见 [`current_core_dump`]）
 //! 6. **上下文恢复**: `trap_return()` → `__restore` 恢复寄存器并返回用户态
 //!
 //! ## 寄存器使用
@@ -32,20 +35,22 @@
 //! - `sepc`: 异常程序计数器，指向触发陷阱的指令地址
 
 use crate::config::{TRAMPOLINE, TRAP_CONTEXT};
+use crate::drivers::handle_external_interrupt;
+use crate::net;
 use crate::process::{
-    SignalFlags, check_signals_error_of_current, current_add_signal, current_process,
-    current_trap_cx, current_user_token, exit_current_and_run_next, handle_signals,
-    take_current_process,
+    SignalFlags, check_signals_error_of_current, current_add_signal, current_core_dump,
+    current_process, current_trap_cx, current_user_token, exit_current_and_run_next,
+    handle_signals, take_current_process,
 };
 use crate::process::{add_process_with_priority, get_time_slice};
 use crate::syscall::syscall;
-use crate::timer::next_trigger;
+use crate::timer::{check_timer, next_trigger};
 use crate::{println, process::suspend_current_and_run_next};
 use core::arch::{asm, global_asm};
 use riscv::register::{
     mtvec::TrapMode,
     scause::{self, Exception, Interrupt, Trap},
-    sie, stval, stvec,
+    sie, sip, stval, stvec,
 };
 
 pub use context::TrapContext;
@@ -99,6 +104,26 @@ pub fn enable_timer_interrupt() {
     }
 }
 
+/// 调度器空闲时等待中断
+///
+/// 内核态运行时 `sstatus.SIE` 保持关闭，因此 `wfi` 只会在中断挂起时唤醒 CPU
+/// 而不会真正陷入。唤醒后检查 `sip` 并直接处理挂起的外部中断与时钟中断，
+/// 使没有可运行进程时阻塞在 I/O 上的进程仍能被设备中断唤醒。
+pub fn wait_for_interrupt() {
+    unsafe {
+        asm!("wfi");
+    }
+    let pending = sip::read();
+    if pending.sext() {
+        handle_external_interrupt();
+    }
+    if pending.stimer() {
+        next_trigger();
+        check_timer();
+        net::poll();
+    }
+}
+
 /// 设置内核态陷阱入口
 ///
 /// 配置 `stvec` 寄存器指向内核陷阱处理函数 `trap_from_kernel`。
@@ -152,7 +177,8 @@ pub fn trap_from_kernel() -> ! {
 /// 1. **设置内核陷阱入口**: 防止处理过程中的嵌套陷阱（`stvec` 指向内核）
 /// 2. **获取陷阱信息**: 读取 `scause` 和 `stval` 寄存器
 /// 3. **分发处理**: 根据陷阱类型执行：系统调用/异常转信号/时钟中断让出
-/// 4. **信号处理**: `handle_signals()` 进入/完成用户处理；对致命信号退出
+/// 4. **信号处理**: `handle_signals()` 进入/完成用户处理；对致命信号退出，
+///    转储类信号先写出核心文件
 /// 5. **返回用户态**: `trap_return()` 恢复用户执行
 ///
 /// ## 系统调用处理细节
@@ -169,6 +195,7 @@ pub fn trap_from_kernel() -> ! {
 /// - **数据访问异常** (`StoreFault`, `StorePageFault`, `LoadFault`, `LoadPageFault`): 处理数据内存访问违规
 /// - **指令访问异常** (`InstructionFault`, `InstructionPageFault`): 处理指令内存访问违规
 /// - **非法指令** (`IllegalInstruction`): 处理无效指令执行
+/// - **断点** (`Breakpoint`): 投递 `SIGTRAP`
 /// - **时钟中断** (`SupervisorTimer`): 处理抢占式调度
 ///
 /// ## 错误/信号处理
@@ -189,7 +216,10 @@ pub fn trap_handler() -> ! {
         Trap::Exception(Exception::UserEnvCall) => {
             let mut cx = current_trap_cx();
             cx.sepc += 4;
-            let result = syscall(cx.x[17], [cx.x[10], cx.x[11], cx.x[12]]);
+            let result = syscall(
+                cx.x[17],
+                [cx.x[10], cx.x[11], cx.x[12], cx.x[13], cx.x[14], cx.x[15]],
+            );
             cx = current_trap_cx();
             cx.x[10] = result as usize;
         }
@@ -214,13 +244,23 @@ pub fn trap_handler() -> ! {
             // );
             current_add_signal(SignalFlags::SIGILL);
         }
+        Trap::Exception(Exception::Breakpoint) => {
+            // 被跟踪的进程因此进入跟踪停止，否则按默认动作终止
+            current_add_signal(SignalFlags::SIGTRAP);
+        }
+        Trap::Interrupt(Interrupt::SupervisorExternal) => {
+            handle_external_interrupt();
+        }
         Trap::Interrupt(Interrupt::SupervisorTimer) => {
             next_trigger();
+            check_timer();
+            net::poll();
 
             // MLFQ 时间片降级逻辑
             if let Some(process) = current_process() {
                 let mut inner = process.inner_exclusive_access();
                 inner.time_slice_used += 1;
+                inner.charge_cpu_tick();
 
                 // 检查是否用完时间片
                 if inner.time_slice_used >= inner.time_slice_limit {
@@ -261,7 +301,10 @@ pub fn trap_handler() -> ! {
     handle_signals();
 
     if let Some((errno, msg)) = check_signals_error_of_current() {
-        println!("[kernel] {}", msg);
+        match current_core_dump(-errno as usize) {
+            Some(core) => println!("[kernel] {} (core dumped to {})", msg, core),
+            None => println!("[kernel] {}", msg),
+        }
         exit_current_and_run_next(errno);
     }
 
@@ -0,0 +1,153 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::format;
+use alloc::vec;
+use user_lib::*;
+
+const ET_CORE: u16 = 4;
+const EM_RISCV: u16 = 243;
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const NT_PRSTATUS: u32 = 1;
+const PAGE_SIZE: usize = 4096;
+
+// 子进程崩溃前写入，父进程在核心文件中找回
+static mut MARKER: [u8; 16] = [0; 16];
+const PATTERN: &[u8; 16] = b"core dump marker";
+
+fn u16_at(buf: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(buf[offset..offset + 2].try_into().unwrap())
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(buf[offset..offset + 4].try_into().unwrap())
+}
+
+fn u64_at(buf: &[u8], offset: usize) -> usize {
+    u64::from_le_bytes(buf[offset..offset + 8].try_into().unwrap()) as usize
+}
+
+// 检查 core.<pid> 的文件头与 NT_PRSTATUS，返回 MARKER 在文件中的偏移与文件总长度
+fn check_header(header: &[u8], pid: usize, sig: i32) -> (usize, usize) {
+    assert_eq!(&header[..4], b"\x7fELF");
+    assert_eq!(u16_at(header, 16), ET_CORE);
+    assert_eq!(u16_at(header, 18), EM_RISCV);
+    let phoff = u64_at(header, 32);
+    let phnum = u16_at(header, 56) as usize;
+    let marker = &raw const MARKER as usize;
+    let mut marker_offset = None;
+    let mut file_end = 0;
+    let mut found_note = false;
+    for idx in 0..phnum {
+        let ph = &header[phoff + idx * 56..];
+        let offset = u64_at(ph, 8);
+        let vaddr = u64_at(ph, 16);
+        let size = u64_at(ph, 32);
+        match u32_at(ph, 0) {
+            PT_NOTE => {
+                let note = &header[offset..offset + size];
+                assert_eq!(u32_at(note, 0), 5);
+                assert_eq!(u32_at(note, 8), NT_PRSTATUS);
+                assert_eq!(&note[12..17], b"CORE\0");
+                let prstatus = &note[20..];
+                assert_eq!(u16_at(prstatus, 12) as i32, sig);
+                assert_eq!(u32_at(prstatus, 32) as usize, pid);
+                assert_eq!(u32_at(prstatus, 36) as isize, user_lib::pid());
+                // pc 与 sp 都在用户地址空间内
+                assert_ne!(u64_at(prstatus, 112), 0);
+                assert_ne!(u64_at(prstatus, 112 + 2 * 8), 0);
+                found_note = true;
+            }
+            PT_LOAD => {
+                assert_eq!(offset % PAGE_SIZE, 0);
+                if vaddr <= marker && marker + PATTERN.len() <= vaddr + size {
+                    marker_offset = Some(offset + marker - vaddr);
+                }
+                file_end = file_end.max(offset + size);
+            }
+            _ => panic!("unexpected program header"),
+        }
+    }
+    assert!(found_note);
+    (
+        marker_offset.expect("MARKER is not in any PT_LOAD"),
+        file_end,
+    )
+}
+
+// 用户堆放不下整个核心文件，只能顺序读取
+fn check_core(pid: usize, sig: i32) {
+    let fd = open(format!("core.{}\0", pid).as_str(), OpenFlags::RDONLY);
+    assert!(fd >= 0, "core.{} not found", pid);
+    let fd = fd as usize;
+    let mut header = vec![0u8; PAGE_SIZE];
+    assert_eq!(read(fd, &mut header), PAGE_SIZE as isize);
+    let (marker_offset, file_end) = check_header(&header, pid, sig);
+
+    let mut found = [0u8; 16];
+    let mut buf = [0u8; 512];
+    let mut pos = PAGE_SIZE;
+    loop {
+        let len = read(fd, &mut buf);
+        assert!(len >= 0);
+        if len == 0 {
+            break;
+        }
+        let len = len as usize;
+        for (idx, byte) in buf[..len].iter().enumerate() {
+            let at = pos + idx;
+            if (marker_offset..marker_offset + found.len()).contains(&at) {
+                found[at - marker_offset] = *byte;
+            }
+        }
+        pos += len;
+    }
+    close(fd);
+    assert_eq!(pos, file_end);
+    assert_eq!(&found, PATTERN);
+}
+
+// 访问空指针被 SIGSEGV 终止，核心文件中保留崩溃时的内存
+fn coredump_test_segv() {
+    let child = fork();
+    if child == 0 {
+        unsafe {
+            (&raw mut MARKER).write_volatile(*PATTERN);
+            core::ptr::null_mut::<u8>().write_volatile(0);
+        }
+        exit(0);
+    }
+    let mut status = 0;
+    assert_eq!(waitpid(child as usize, &mut status), child);
+    assert_eq!(status, -SIGSEGV);
+    check_core(child as usize, SIGSEGV);
+}
+
+// kill 发来的 SIGABRT 同样转储
+fn coredump_test_abort() {
+    let child = fork();
+    if child == 0 {
+        unsafe { (&raw mut MARKER).write_volatile(*PATTERN) };
+        kill(user_lib::pid() as usize, SIGABRT);
+        exit(0);
+    }
+    let mut status = 0;
+    assert_eq!(waitpid(child as usize, &mut status), child);
+    assert_eq!(status, -SIGABRT);
+    check_core(child as usize, SIGABRT);
+    // 父进程自己的 MARKER 没有被写过
+    assert_eq!(unsafe { (&raw const MARKER).read_volatile() }, [0; 16]);
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    coredump_test_segv();
+    coredump_test_abort();
+    println!("coredump_test passed!");
+    0
+}
@@ -0,0 +1,320 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use user_lib::*;
+
+const EBREAK: usize = 0x0010_0073;
+const C_EBREAK: usize = 0x9002;
+
+const REG_NAMES: [&str; PTRACE_NREGS] = [
+    "pc", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4", "a5",
+    "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4", "t5",
+    "t6",
+];
+
+const HELP: &str = "commands:
+  b <addr>       set a breakpoint
+  d <addr>       delete a breakpoint
+  c              continue
+  s              single step
+  regs           show registers
+  x <addr> [n]   show n words of memory
+  q              kill the program and quit
+";
+
+enum Event {
+    Stopped(i32),
+    Exited(i32),
+}
+
+// 断点处被 ebreak 替换掉的原指令
+struct Breakpoint {
+    addr: usize,
+    orig: usize,
+    len: usize,
+}
+
+struct Debugger {
+    pid: usize,
+    breakpoints: Vec<Breakpoint>,
+    // 上次停止的信号，继续执行时转交给程序（SIGTRAP/SIGSTOP 除外）
+    stop_sig: i32,
+}
+
+fn inst_mask(len: usize) -> usize {
+    if len == 2 { 0xffff } else { 0xffff_ffff }
+}
+
+fn parse_hex(arg: &str) -> Option<usize> {
+    let arg = arg.strip_prefix("0x").unwrap_or(arg);
+    usize::from_str_radix(arg, 16).ok()
+}
+
+impl Debugger {
+    fn wait(&mut self) -> Event {
+        let mut status = 0;
+        waitpid(self.pid, &mut status);
+        if wifstopped(status) {
+            self.stop_sig = wstopsig(status);
+            Event::Stopped(self.stop_sig)
+        } else {
+            Event::Exited(status)
+        }
+    }
+
+    fn peek(&self, addr: usize) -> Result<usize, isize> {
+        let mut word = 0usize;
+        match ptrace(
+            PTRACE_PEEKTEXT,
+            self.pid,
+            addr,
+            &mut word as *mut _ as usize,
+        ) {
+            0 => Ok(word),
+            err => Err(err),
+        }
+    }
+
+    fn poke(&self, addr: usize, word: usize) -> Result<(), isize> {
+        match ptrace(PTRACE_POKETEXT, self.pid, addr, word) {
+            0 => Ok(()),
+            err => Err(err),
+        }
+    }
+
+    fn regs(&self) -> [usize; PTRACE_NREGS] {
+        let mut regs = [0usize; PTRACE_NREGS];
+        ptrace(PTRACE_GETREGS, self.pid, 0, regs.as_mut_ptr() as usize);
+        regs
+    }
+
+    fn pc(&self) -> usize {
+        self.regs()[0]
+    }
+
+    // 只改写指令所在的低位字节，相邻的断点不受影响
+    fn insert(&mut self, addr: usize) -> Result<(), isize> {
+        if self.breakpoints.iter().any(|bp| bp.addr == addr) {
+            return Ok(());
+        }
+        let word = self.peek(addr)?;
+        let len = if word & 0b11 == 0b11 { 4 } else { 2 };
+        let ebreak = if len == 2 { C_EBREAK } else { EBREAK };
+        let mask = inst_mask(len);
+        self.poke(addr, (word & !mask) | ebreak)?;
+        self.breakpoints.push(Breakpoint {
+            addr,
+            orig: word & mask,
+            len,
+        });
+        Ok(())
+    }
+
+    fn restore(&self, bp: &Breakpoint) -> Result<(), isize> {
+        let word = self.peek(bp.addr)?;
+        self.poke(bp.addr, (word & !inst_mask(bp.len)) | bp.orig)
+    }
+
+    fn remove(&mut self, addr: usize) -> Result<bool, isize> {
+        let Some(idx) = self.breakpoints.iter().position(|bp| bp.addr == addr) else {
+            return Ok(false);
+        };
+        let bp = self.breakpoints.remove(idx);
+        self.restore(&bp)?;
+        Ok(true)
+    }
+
+    fn pending_sig(&self) -> usize {
+        match self.stop_sig {
+            SIGTRAP | SIGSTOP => 0,
+            sig => sig as usize,
+        }
+    }
+
+    // 单步执行一条指令；停在断点上时先恢复原指令，执行后再写回 ebreak
+    fn step(&mut self) -> Event {
+        let pc = self.pc();
+        let sig = self.pending_sig();
+        let idx = self.breakpoints.iter().position(|bp| bp.addr == pc);
+        if let Some(idx) = idx {
+            let _ = self.restore(&self.breakpoints[idx]);
+        }
+        ptrace(PTRACE_SINGLESTEP, self.pid, 0, sig);
+        let event = self.wait();
+        if let (Some(idx), Event::Stopped(_)) = (idx, &event) {
+            let ebreak = if self.breakpoints[idx].len == 2 {
+                C_EBREAK
+            } else {
+                EBREAK
+            };
+            let mask = inst_mask(self.breakpoints[idx].len);
+            if let Ok(word) = self.peek(pc) {
+                let _ = self.poke(pc, (word & !mask) | ebreak);
+            }
+        }
+        event
+    }
+
+    fn cont(&mut self) -> Event {
+        let pc = self.pc();
+        if self.breakpoints.iter().any(|bp| bp.addr == pc) {
+            match self.step() {
+                Event::Stopped(SIGTRAP) => {}
+                event => return event,
+            }
+        }
+        ptrace(PTRACE_CONT, self.pid, 0, self.pending_sig());
+        self.wait()
+    }
+
+    fn show_regs(&self) {
+        let regs = self.regs();
+        for (idx, (name, value)) in REG_NAMES.iter().zip(regs.iter()).enumerate() {
+            print!("{:>4} {:#018x}", name, value);
+            print!("{}", if idx % 4 == 3 { "\n" } else { "  " });
+        }
+    }
+
+    fn show_memory(&self, addr: usize, count: usize) {
+        for idx in 0..count {
+            let addr = addr + idx * core::mem::size_of::<usize>();
+            match self.peek(addr) {
+                Ok(word) => println!("{:#x}: {:#018x}", addr, word),
+                Err(err) => {
+                    println!("{:#x}: {}", addr, strerror(-err));
+                    break;
+                }
+            }
+        }
+    }
+
+    // 处理一条命令；返回 false 表示调试结束
+    fn command(&mut self, line: &str) -> bool {
+        let mut words = line.split_whitespace();
+        let Some(cmd) = words.next() else {
+            return true;
+        };
+        let addr = words.next().and_then(parse_hex);
+        let event = match (cmd, addr) {
+            ("b", Some(addr)) => {
+                match self.insert(addr) {
+                    Ok(()) => println!("breakpoint at {:#x}", addr),
+                    Err(err) => println!("cannot set breakpoint: {}", strerror(-err)),
+                }
+                return true;
+            }
+            ("d", Some(addr)) => {
+                match self.remove(addr) {
+                    Ok(true) => println!("deleted breakpoint at {:#x}", addr),
+                    Ok(false) => println!("no breakpoint at {:#x}", addr),
+                    Err(err) => println!("cannot delete breakpoint: {}", strerror(-err)),
+                }
+                return true;
+            }
+            ("x", Some(addr)) => {
+                let count = words.next().and_then(|n| n.parse().ok()).unwrap_or(1);
+                self.show_memory(addr, count);
+                return true;
+            }
+            ("regs", _) => {
+                self.show_regs();
+                return true;
+            }
+            ("c", _) => self.cont(),
+            ("s", _) => self.step(),
+            ("q", _) => {
+                ptrace(PTRACE_KILL, self.pid, 0, 0);
+                self.wait();
+                return false;
+            }
+            _ => {
+                print!("{}", HELP);
+                return true;
+            }
+        };
+        match event {
+            Event::Stopped(SIGTRAP) => {
+                let pc = self.pc();
+                if self.breakpoints.iter().any(|bp| bp.addr == pc) {
+                    println!("breakpoint hit at {:#x}", pc);
+                } else {
+                    println!("stopped at {:#x}", pc);
+                }
+                true
+            }
+            Event::Stopped(sig) => {
+                println!("stopped by signal {} at {:#x}", sig, self.pc());
+                true
+            }
+            Event::Exited(code) => {
+                println!("process {} exited with {}", self.pid, code);
+                false
+            }
+        }
+    }
+}
+
+// 从标准输入读取一行（不含换行符）；输入结束时返回 None
+fn read_line(pending: &mut String) -> Option<String> {
+    let mut buf = [0u8; 128];
+    loop {
+        if let Some(end) = pending.find('\n') {
+            let line = String::from(&pending[..end]);
+            pending.drain(..=end);
+            return Some(line);
+        }
+        let len = read(0, &mut buf);
+        if len <= 0 {
+            return None;
+        }
+        pending.push_str(core::str::from_utf8(&buf[..len as usize]).unwrap_or(""));
+    }
+}
+
+// 用法：dbg <program> [args...]，程序在第一条指令前停止
+#[unsafe(no_mangle)]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    if argc < 2 {
+        write_stderr(b"usage: dbg <program> [args...]\n");
+        return 1;
+    }
+    let args: Vec<String> = argv[1..].iter().map(|arg| format!("{}\0", arg)).collect();
+    let pid = fork();
+    if pid == 0 {
+        ptrace(PTRACE_TRACEME, 0, 0, 0);
+        let mut args_addr: Vec<*const u8> = args.iter().map(|arg| arg.as_ptr()).collect();
+        args_addr.push(core::ptr::null::<u8>());
+        let ret = exec(args[0].as_str(), &args_addr);
+        write_stderr(format!("dbg: {}: {}\n", argv[1], strerror(-ret)).as_bytes());
+        exit(127);
+    }
+    let mut dbg = Debugger {
+        pid: pid as usize,
+        breakpoints: Vec::new(),
+        stop_sig: 0,
+    };
+    match dbg.wait() {
+        Event::Stopped(_) => println!("process {} stopped at {:#x}", pid, dbg.pc()),
+        Event::Exited(code) => return code,
+    }
+    let mut pending = String::new();
+    loop {
+        print!("(dbg) ");
+        let Some(line) = read_line(&mut pending) else {
+            ptrace(PTRACE_KILL, dbg.pid, 0, 0);
+            dbg.wait();
+            break;
+        };
+        if !dbg.command(&line) {
+            break;
+        }
+    }
+    0
+}
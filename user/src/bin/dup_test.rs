@@ -0,0 +1,123 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::*;
+
+const FILE_A: &str = "dup_test_a\0";
+const FILE_B: &str = "dup_test_b\0";
+
+fn write_file(path: &str, content: &[u8]) {
+    let fd = open(
+        path,
+        OpenFlags::CREATE | OpenFlags::WRONLY | OpenFlags::TRUNC,
+    );
+    assert!(fd > 0);
+    assert_eq!(write(fd as usize, content), content.len() as isize);
+    close(fd as usize);
+}
+
+fn read_file(path: &str, buf: &mut [u8]) -> usize {
+    let fd = open(path, OpenFlags::RDONLY);
+    assert!(fd > 0);
+    let len = read(fd as usize, buf);
+    close(fd as usize);
+    len as usize
+}
+
+fn dup_test_dup3() {
+    write_file(FILE_A, b"aaa");
+    write_file(FILE_B, b"bbb");
+    let a = open(FILE_A, OpenFlags::RDONLY) as usize;
+    let b = open(FILE_B, OpenFlags::RDONLY | OpenFlags::CLOEXEC) as usize;
+    assert_eq!(fcntl(a, F_GETFD, 0), 0);
+    assert_eq!(fcntl(b, F_GETFD, 0), FD_CLOEXEC as isize);
+
+    // 替换已打开的描述符，新描述符不继承 FD_CLOEXEC
+    assert_eq!(dup2(a, b), b as isize);
+    assert_eq!(fcntl(b, F_GETFD, 0), 0);
+    let mut buf = [0u8; 8];
+    assert_eq!(read(b, &mut buf), 3);
+    assert_eq!(&buf[..3], b"aaa");
+
+    assert_eq!(dup3(a, 30, OpenFlags::CLOEXEC), 30);
+    assert_eq!(fcntl(30, F_GETFD, 0), FD_CLOEXEC as isize);
+    assert_eq!(dup3(a, 30, OpenFlags::empty()), 30);
+    assert_eq!(fcntl(30, F_GETFD, 0), 0);
+
+    assert_eq!(dup3(a, a, OpenFlags::empty()), -EINVAL);
+    assert_eq!(dup3(a, 31, OpenFlags::APPEND), -EINVAL);
+    assert_eq!(dup2(a, a), a as isize);
+    close(30);
+    assert_eq!(dup2(30, 31), -EBADF);
+    assert_eq!(dup2(30, 30), -EBADF);
+    close(a);
+    close(b);
+}
+
+fn run_shell(commands: &[u8]) {
+    let (mut master, mut slave) = (0, 0);
+    assert_eq!(openpty(&mut master, &mut slave), 0);
+    let pid = fork();
+    if pid == 0 {
+        for fd in 0..3 {
+            dup2(slave, fd);
+        }
+        close(master);
+        close(slave);
+        exec("user_shell\0", &[core::ptr::null::<u8>()]);
+        exit(-1);
+    }
+    close(slave);
+    assert_eq!(write(master, commands), commands.len() as isize);
+    assert_eq!(write(master, b"exit 0\n"), 7);
+    let mut buf = [0u8; 128];
+    while read(master, &mut buf) > 0 {}
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    close(master);
+}
+
+// 通过 user_shell 检查 `>`、`2>`、`2>&1` 与 `<` 的效果
+fn dup_test_shell_redirect() {
+    run_shell(
+        b"dup_test -print > dup_test_out 2> dup_test_err\n\
+          dup_test -print > dup_test_both 2>&1\n\
+          dup_test -cat < dup_test_out > dup_test_copy\n",
+    );
+    let mut buf = [0u8; 64];
+    let len = read_file("dup_test_out\0", &mut buf);
+    assert_eq!(&buf[..len], b"stdout\n");
+    let len = read_file("dup_test_err\0", &mut buf);
+    assert_eq!(&buf[..len], b"stderr\n");
+    let len = read_file("dup_test_both\0", &mut buf);
+    assert_eq!(&buf[..len], b"stdout\nstderr\n");
+    let len = read_file("dup_test_copy\0", &mut buf);
+    assert_eq!(&buf[..len], b"stdout\n");
+}
+
+#[unsafe(no_mangle)]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    if argc > 1 && argv[1] == "-print" {
+        write(1, b"stdout\n");
+        write(2, b"stderr\n");
+        return 0;
+    }
+    if argc > 1 && argv[1] == "-cat" {
+        let mut buf = [0u8; 64];
+        loop {
+            let len = read(0, &mut buf);
+            if len <= 0 {
+                return 0;
+            }
+            write(1, &buf[..len as usize]);
+        }
+    }
+    dup_test_dup3();
+    dup_test_shell_redirect();
+    println!("dup_test passed!");
+    0
+}
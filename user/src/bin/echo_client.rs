@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::*;
+
+fn parse_ipv4(s: &str) -> Option<[u8; 4]> {
+    let mut addr = [0u8; 4];
+    let mut parts = s.split('.');
+    for byte in addr.iter_mut() {
+        *byte = parts.next()?.parse().ok()?;
+    }
+    match parts.next() {
+        Some(_) => None,
+        None => Some(addr),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    if argc != 4 {
+        println!("usage: echo_client <ip> <port> <message>");
+        return -1;
+    }
+    let (Some(ip), Ok(port)) = (parse_ipv4(argv[1]), argv[2].parse::<u16>()) else {
+        println!("usage: echo_client <ip> <port> <message>");
+        return -1;
+    };
+    let fd = socket(AF_INET, SOCK_STREAM, 0);
+    assert!(fd >= 0);
+    let fd = fd as usize;
+    let ret = connect(fd, &SockAddrIn::new(ip, port));
+    if ret < 0 {
+        println!("echo_client: connect failed: {}", ret);
+        return -1;
+    }
+    let message = argv[3].as_bytes();
+    assert_eq!(write(fd, message), message.len() as isize);
+    let mut buf = [0u8; 1024];
+    let mut received = 0;
+    while received < message.len() {
+        let n = read(fd, &mut buf[received..]);
+        if n <= 0 {
+            break;
+        }
+        received += n as usize;
+    }
+    println!(
+        "{}",
+        core::str::from_utf8(&buf[..received]).unwrap_or("<invalid utf-8>")
+    );
+    close(fd);
+    0
+}
@@ -0,0 +1,78 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::*;
+
+const DEFAULT_PORT: u16 = 8000;
+
+fn serve(conn: usize) {
+    let mut buf = [0u8; 1024];
+    loop {
+        let n = read(conn, &mut buf);
+        if n <= 0 {
+            break;
+        }
+        if write(conn, &buf[..n as usize]) < 0 {
+            break;
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    let port = match argc {
+        1 => DEFAULT_PORT,
+        2 => match argv[1].parse() {
+            Ok(port) => port,
+            Err(_) => {
+                println!("usage: echo_server [port]");
+                return -1;
+            }
+        },
+        _ => {
+            println!("usage: echo_server [port]");
+            return -1;
+        }
+    };
+    let listener = socket(AF_INET, SOCK_STREAM, 0);
+    assert!(listener >= 0);
+    let listener = listener as usize;
+    if bind(listener, &SockAddrIn::new([0; 4], port)) < 0 || listen(listener, 8) < 0 {
+        println!("echo_server: cannot listen on port {}", port);
+        return -1;
+    }
+    println!("echo_server: listening on port {}", port);
+    loop {
+        let mut peer = SockAddrIn::default();
+        let mut peer_len = core::mem::size_of::<SockAddrIn>() as u32;
+        let conn = accept(listener, Some((&mut peer, &mut peer_len)));
+        if conn < 0 {
+            continue;
+        }
+        let conn = conn as usize;
+        let [a, b, c, d] = peer.addr();
+        println!(
+            "echo_server: connection from {}.{}.{}.{}:{}",
+            a,
+            b,
+            c,
+            d,
+            peer.port()
+        );
+        // 两次 fork：服务连接的孙进程由 initproc 回收
+        let pid = fork();
+        if pid == 0 {
+            close(listener);
+            if fork() == 0 {
+                serve(conn);
+            }
+            exit(0);
+        }
+        close(conn);
+        let mut exit_code = 0;
+        waitpid(pid as usize, &mut exit_code);
+    }
+}
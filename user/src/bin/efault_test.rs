@@ -0,0 +1,373 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+#[macro_use]
+extern crate user_lib;
+
+use alloc::string::String;
+use user_lib::*;
+
+const SYSCALL_EPOLL_CTL: usize = 21;
+const SYSCALL_EPOLL_PWAIT: usize = 22;
+const SYSCALL_IOCTL: usize = 29;
+const SYSCALL_MKNODAT: usize = 33;
+const SYSCALL_OPEN: usize = 56;
+const SYSCALL_PIPE: usize = 59;
+const SYSCALL_READ: usize = 63;
+const SYSCALL_WRITE: usize = 64;
+const SYSCALL_PSELECT6: usize = 72;
+const SYSCALL_PPOLL: usize = 73;
+const SYSCALL_SIGACTION: usize = 134;
+const SYSCALL_SHMCTL: usize = 195;
+const SYSCALL_BIND: usize = 200;
+const SYSCALL_ACCEPT: usize = 202;
+const SYSCALL_CONNECT: usize = 203;
+const SYSCALL_SENDTO: usize = 206;
+const SYSCALL_RECVFROM: usize = 207;
+const SYSCALL_EXECVE: usize = 221;
+const SYSCALL_WAITPID: usize = 260;
+const SYSCALL_PRLIMIT64: usize = 261;
+const SYSCALL_IPC_SEND: usize = 502;
+const SYSCALL_IPC_RECV: usize = 503;
+const SYSCALL_IPC_CALL: usize = 504;
+
+// 未映射的用户地址、内核镜像所在地址、跳板页（无 U 位，且超出用户地址空间）
+const BAD_POINTERS: [usize; 3] = [0xdead_b000, 0x8020_0000, usize::MAX - 0xfff];
+
+// 只读的代码段，只能作为写入目标的坏指针
+fn text() -> usize {
+    main as fn() -> i32 as usize
+}
+
+fn new_pipe() -> [usize; 2] {
+    let mut fds = [0usize; 2];
+    assert_eq!(pipe(&mut fds), 0);
+    fds
+}
+
+fn efault_test_fs() {
+    let [rd, wr] = new_pipe();
+    assert_eq!(write(wr, b"data"), 4);
+    for bad in BAD_POINTERS.into_iter().chain([0, text()]) {
+        assert_eq!(syscall6(SYSCALL_READ, [rd, bad, 4, 0, 0, 0]), -EFAULT);
+        assert_eq!(syscall6(SYSCALL_PIPE, [bad, 0, 0, 0, 0, 0]), -EFAULT);
+    }
+    // 失败的 read 不消耗数据
+    let mut buf = [0u8; 4];
+    assert_eq!(read(rd, &mut buf), 4);
+    assert_eq!(&buf, b"data");
+
+    for bad in BAD_POINTERS.into_iter().chain([0]) {
+        assert_eq!(syscall6(SYSCALL_WRITE, [wr, bad, 4, 0, 0, 0]), -EFAULT);
+        assert_eq!(syscall6(SYSCALL_OPEN, [bad, 0, 0, 0, 0, 0]), -EFAULT);
+        assert_eq!(
+            syscall6(SYSCALL_MKNODAT, [0, bad, 0o10644, 0, 0, 0]),
+            -EFAULT
+        );
+    }
+    // 从可读的代码段开始、延伸到未映射区域的缓冲区
+    assert_eq!(
+        syscall6(SYSCALL_WRITE, [wr, text(), 1 << 30, 0, 0, 0]),
+        -EFAULT
+    );
+    // 地址回绕
+    assert_eq!(
+        syscall6(
+            SYSCALL_WRITE,
+            [wr, buf.as_ptr() as usize, usize::MAX, 0, 0, 0]
+        ),
+        -EFAULT
+    );
+
+    let mut long = String::new();
+    for _ in 0..5000 {
+        long.push('a');
+    }
+    long.push('\0');
+    assert_eq!(open(&long, OpenFlags::RDONLY), -ENAMETOOLONG);
+    close(rd);
+    close(wr);
+}
+
+fn efault_test_poll() {
+    let [rd, wr] = new_pipe();
+    let epfd = epoll_create1(OpenFlags::empty()) as usize;
+    let mut fds = [PollFd::new(rd, POLLIN)];
+    let fds_ptr = fds.as_mut_ptr() as usize;
+    let mut set = FdSet::default();
+    set.insert(rd);
+    let set_ptr = &mut set as *mut FdSet as usize;
+    let zero = TimeSpec::default();
+    let zero_ptr = &zero as *const TimeSpec as usize;
+    // 结果写回的数组不能是只读的
+    for bad in BAD_POINTERS.into_iter().chain([text()]) {
+        assert_eq!(
+            syscall6(SYSCALL_PPOLL, [bad, 1, zero_ptr, 0, 0, 0]),
+            -EFAULT
+        );
+        assert_eq!(
+            syscall6(SYSCALL_EPOLL_PWAIT, [epfd, bad, 4, 0, 0, 8]),
+            -EFAULT
+        );
+    }
+    for bad in BAD_POINTERS {
+        assert_eq!(syscall6(SYSCALL_PPOLL, [fds_ptr, 1, bad, 0, 0, 0]), -EFAULT);
+        assert_eq!(
+            syscall6(SYSCALL_PPOLL, [fds_ptr, 1, zero_ptr, bad, 0, 0]),
+            -EFAULT
+        );
+        assert_eq!(
+            syscall6(SYSCALL_PSELECT6, [rd + 1, bad, 0, 0, zero_ptr, 0]),
+            -EFAULT
+        );
+        assert_eq!(
+            syscall6(SYSCALL_PSELECT6, [rd + 1, set_ptr, 0, 0, bad, 0]),
+            -EFAULT
+        );
+        assert_eq!(
+            syscall6(SYSCALL_EPOLL_CTL, [epfd, EPOLL_CTL_ADD, rd, bad, 0, 0]),
+            -EFAULT
+        );
+    }
+
+    // 就绪事件在写回失败时不丢失
+    let event = EpollEvent::new(EPOLLIN, 7);
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_ADD, rd, Some(&event)), 0);
+    assert_eq!(write(wr, b"x"), 1);
+    assert_eq!(
+        syscall6(SYSCALL_EPOLL_PWAIT, [epfd, text(), 1, 0, 0, 8]),
+        -EFAULT
+    );
+    let mut events = [EpollEvent::default(); 1];
+    assert_eq!(epoll_wait(epfd, &mut events, 0), 1);
+    assert_eq!(events[0].data, 7);
+    close(epfd);
+    close(rd);
+    close(wr);
+}
+
+fn efault_test_process() {
+    let child = fork();
+    if child == 0 {
+        exit(7);
+    }
+    for bad in BAD_POINTERS.into_iter().chain([text()]) {
+        // 子进程退出前返回 -EAGAIN
+        let ret = loop {
+            let ret = syscall6(SYSCALL_WAITPID, [child as usize, bad, 0, 0, 0, 0]);
+            if ret != -EAGAIN {
+                break ret;
+            }
+            yield_();
+        };
+        assert_eq!(ret, -EFAULT);
+    }
+    // 写回失败时子进程没有被回收
+    let mut exit_code = 0;
+    assert_eq!(waitpid(child as usize, &mut exit_code), child);
+    assert_eq!(exit_code, 7);
+
+    let path = "efault_test\0";
+    let args = [path.as_ptr() as usize, 0];
+    for bad in BAD_POINTERS {
+        assert_eq!(syscall6(SYSCALL_EXECVE, [bad, 0, 0, 0, 0, 0]), -EFAULT);
+        let bad_args = [bad, 0];
+        let bad_args = bad_args.as_ptr() as usize;
+        assert_eq!(
+            syscall6(
+                SYSCALL_EXECVE,
+                [path.as_ptr() as usize, bad_args, 0, 0, 0, 0]
+            ),
+            -EFAULT
+        );
+        assert_eq!(
+            syscall6(SYSCALL_EXECVE, [path.as_ptr() as usize, bad, 0, 0, 0, 0]),
+            -EFAULT
+        );
+        assert_eq!(
+            syscall6(
+                SYSCALL_EXECVE,
+                [path.as_ptr() as usize, args.as_ptr() as usize, bad, 0, 0, 0]
+            ),
+            -EFAULT
+        );
+    }
+
+    let action = SignalAction {
+        handler: SIG_IGN,
+        mask: SignalFlags::empty(),
+    };
+    let action_ptr = &action as *const SignalAction as usize;
+    let mut old = SignalAction::default();
+    let old_ptr = &mut old as *mut SignalAction as usize;
+    for bad in BAD_POINTERS {
+        assert_eq!(
+            syscall6(SYSCALL_SIGACTION, [SIGUSR1 as usize, bad, old_ptr, 0, 0, 0]),
+            -EFAULT
+        );
+        assert_eq!(
+            syscall6(SYSCALL_PRLIMIT64, [0, RLIMIT_NOFILE, bad, 0, 0, 0]),
+            -EFAULT
+        );
+    }
+    for bad in BAD_POINTERS.into_iter().chain([text()]) {
+        assert_eq!(
+            syscall6(
+                SYSCALL_SIGACTION,
+                [SIGUSR1 as usize, action_ptr, bad, 0, 0, 0]
+            ),
+            -EFAULT
+        );
+        assert_eq!(
+            syscall6(SYSCALL_PRLIMIT64, [0, RLIMIT_NOFILE, 0, bad, 0, 0]),
+            -EFAULT
+        );
+    }
+    // 失败的调用没有安装处理函数
+    assert_eq!(sigaction(SIGUSR1, Some(&action), Some(&mut old)), 0);
+    assert_eq!(old.handler, SIG_DFL);
+    assert_eq!(sigaction(SIGUSR1, Some(&old), Some(&mut old)), 0);
+}
+
+fn efault_test_tty_shm() {
+    let mut master = 0;
+    let mut slave = 0;
+    assert_eq!(openpty(&mut master, &mut slave), 0);
+    for bad in BAD_POINTERS.into_iter().chain([text()]) {
+        assert_eq!(
+            syscall6(SYSCALL_IOCTL, [slave, TCGETS, bad, 0, 0, 0]),
+            -EFAULT
+        );
+        assert_eq!(
+            syscall6(SYSCALL_IOCTL, [slave, TIOCGWINSZ, bad, 0, 0, 0]),
+            -EFAULT
+        );
+        assert_eq!(
+            syscall6(SYSCALL_IOCTL, [master, TIOCGPTN, bad, 0, 0, 0]),
+            -EFAULT
+        );
+    }
+    for bad in BAD_POINTERS {
+        assert_eq!(
+            syscall6(SYSCALL_IOCTL, [slave, TCSETS, bad, 0, 0, 0]),
+            -EFAULT
+        );
+    }
+    close(master);
+    close(slave);
+
+    let shmid = shmget(IPC_PRIVATE, 4096, IPC_CREAT | 0o600);
+    assert!(shmid >= 0);
+    let shmid = shmid as usize;
+    for bad in BAD_POINTERS.into_iter().chain([0, text()]) {
+        assert_eq!(
+            syscall6(SYSCALL_SHMCTL, [shmid, IPC_STAT, bad, 0, 0, 0]),
+            -EFAULT
+        );
+    }
+    for bad in BAD_POINTERS.into_iter().chain([0]) {
+        assert_eq!(
+            syscall6(SYSCALL_SHMCTL, [shmid, IPC_SET, bad, 0, 0, 0]),
+            -EFAULT
+        );
+    }
+    assert_eq!(shmctl(shmid, IPC_RMID, None), 0);
+}
+
+fn efault_test_socket() {
+    let dgram = socket(AF_UNIX, SOCK_DGRAM, 0) as usize;
+    let addr = SockAddrUn::new("efault_test_sock");
+    let addr_len = core::mem::size_of::<SockAddrUn>();
+    for bad in BAD_POINTERS {
+        assert_eq!(
+            syscall6(SYSCALL_BIND, [dgram, bad, addr_len, 0, 0, 0]),
+            -EFAULT
+        );
+        assert_eq!(
+            syscall6(SYSCALL_CONNECT, [dgram, bad, addr_len, 0, 0, 0]),
+            -EFAULT
+        );
+        assert_eq!(syscall6(SYSCALL_SENDTO, [dgram, bad, 4, 0, 0, 0]), -EFAULT);
+        assert_eq!(
+            syscall6(SYSCALL_SENDTO, [dgram, text(), 4, 0, bad, addr_len]),
+            -EFAULT
+        );
+    }
+    for bad in BAD_POINTERS.into_iter().chain([text()]) {
+        assert_eq!(
+            syscall6(
+                SYSCALL_RECVFROM,
+                [dgram, bad, 4, MSG_DONTWAIT as usize, 0, 0]
+            ),
+            -EFAULT
+        );
+    }
+    // 过长的地址
+    let addr_ptr = &addr as *const SockAddrUn as usize;
+    assert_eq!(
+        syscall6(SYSCALL_BIND, [dgram, addr_ptr, 4096, 0, 0, 0]),
+        -EINVAL
+    );
+    close(dgram);
+
+    // accept 写回对端地址失败时连接被丢弃
+    let listener = socket(AF_UNIX, SOCK_STREAM, 0) as usize;
+    assert_eq!(bind(listener, &addr), 0);
+    assert_eq!(listen(listener, 4), 0);
+    let child = fork();
+    if child == 0 {
+        let client = socket(AF_UNIX, SOCK_STREAM, 0) as usize;
+        connect(client, &addr);
+        exit(0);
+    }
+    let mut addr_len = addr_len as u32;
+    let addr_len_ptr = &mut addr_len as *mut u32 as usize;
+    assert_eq!(
+        syscall6(
+            SYSCALL_ACCEPT,
+            [listener, 0xdead_b000, addr_len_ptr, 0, 0, 0]
+        ),
+        -EFAULT
+    );
+    let mut exit_code = -1;
+    assert_eq!(waitpid(child as usize, &mut exit_code), child);
+    close(listener);
+}
+
+fn efault_test_ipc() {
+    let h = ipc_create() as usize;
+    let child = fork();
+    if child == 0 {
+        // 保持端点另一端打开，直到父进程关闭句柄
+        let mut words = [0; IPC_MSG_WORDS];
+        ipc_recv(h, &mut words, None, 0);
+        exit(0);
+    }
+    for bad in BAD_POINTERS {
+        assert_eq!(syscall6(SYSCALL_IPC_SEND, [h, 0, 0, 0, 0, bad]), -EFAULT);
+        assert_eq!(syscall6(SYSCALL_IPC_CALL, [h, 0, 0, 0, 0, bad]), -EFAULT);
+    }
+    for bad in BAD_POINTERS.into_iter().chain([text()]) {
+        assert_eq!(
+            syscall6(SYSCALL_IPC_RECV, [h, bad, IPC_NONBLOCK, 0, 0, 0]),
+            -EFAULT
+        );
+    }
+    assert_eq!(ipc_close(h), 0);
+    let mut exit_code = -1;
+    assert_eq!(waitpid(child as usize, &mut exit_code), child);
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    efault_test_fs();
+    efault_test_poll();
+    efault_test_process();
+    efault_test_tty_shm();
+    efault_test_socket();
+    efault_test_ipc();
+    println!("efault_test passed!");
+    0
+}
@@ -0,0 +1,177 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::*;
+
+const BROKEN: &str = "elf_broken\0";
+
+fn read_file(path: &str) -> Vec<u8> {
+    let fd = open(path, OpenFlags::RDONLY);
+    assert!(fd > 0);
+    let mut data = Vec::new();
+    let mut buf = [0u8; 512];
+    loop {
+        let len = read(fd as usize, &mut buf);
+        if len <= 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..len as usize]);
+    }
+    close(fd as usize);
+    data
+}
+
+fn write_file(path: &str, data: &[u8]) {
+    let fd = open(
+        path,
+        OpenFlags::CREATE | OpenFlags::WRONLY | OpenFlags::TRUNC,
+    );
+    assert!(fd > 0);
+    write(fd as usize, data);
+    close(fd as usize);
+}
+
+fn u64_at(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn load_phdrs(data: &[u8]) -> Vec<usize> {
+    let ph_offset = u64_at(data, 0x20) as usize;
+    let ph_count = u16::from_le_bytes([data[0x38], data[0x39]]) as usize;
+    (0..ph_count)
+        .map(|i| ph_offset + i * 56)
+        .filter(|&ph| u32::from_le_bytes(data[ph..ph + 4].try_into().unwrap()) == 1)
+        .collect()
+}
+
+fn set_u64(data: &mut [u8], offset: usize, value: u64) {
+    data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+/// 执行静态 PIE 程序，检查它的输出与退出码
+fn run_pie() -> bool {
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    let pid = fork();
+    if pid == 0 {
+        close(pipe_fd[0]);
+        dup2(pipe_fd[1], 1);
+        close(pipe_fd[1]);
+        let args: [*const u8; 2] = ["pie_hello\0".as_ptr(), core::ptr::null::<u8>()];
+        exec("pie_hello\0", &args);
+        exit(-1);
+    }
+    close(pipe_fd[1]);
+    let mut output = Vec::new();
+    let mut buf = [0u8; 64];
+    loop {
+        let len = read(pipe_fd[0], &mut buf);
+        if len <= 0 {
+            break;
+        }
+        output.extend_from_slice(&buf[..len as usize]);
+    }
+    close(pipe_fd[0]);
+    let mut exit_code = 0;
+    waitpid(pid as usize, &mut exit_code);
+    if output != b"hello from static-pie\n" || exit_code != 42 {
+        println!(
+            "pie_hello: output {:?}, exit code {}",
+            core::str::from_utf8(&output),
+            exit_code
+        );
+        return false;
+    }
+    true
+}
+
+/// 写入损坏的 ELF 并确认 exec 返回 ENOEXEC 而不是让内核崩溃
+fn expect_enoexec(name: &str, data: &[u8]) -> bool {
+    write_file(BROKEN, data);
+    let args: [*const u8; 2] = [BROKEN.as_ptr(), core::ptr::null::<u8>()];
+    let ret = exec(BROKEN, &args);
+    if ret != -ENOEXEC {
+        println!("{}: exec returned {}, expected ENOEXEC", name, ret);
+        return false;
+    }
+    true
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    let elf = read_file("hello_world\0");
+    assert!(elf.starts_with(&[0x7f, b'E', b'L', b'F']));
+    let ph_offset = u64_at(&elf, 0x20) as usize;
+    let loads = load_phdrs(&elf);
+    let load = loads[0];
+    let mut ok = true;
+
+    // 截断到文件头之内
+    ok &= expect_enoexec("truncated header", &elf[..32]);
+
+    // 截断到程序头表之内
+    ok &= expect_enoexec("truncated phdrs", &elf[..ph_offset + 8]);
+
+    // 机器类型改为 x86-64
+    let mut data = elf.clone();
+    data[18] = 62;
+    data[19] = 0;
+    ok &= expect_enoexec("wrong machine", &data);
+
+    // 32 位文件
+    let mut data = elf.clone();
+    data[4] = 1;
+    ok &= expect_enoexec("elf32", &data);
+
+    // LOAD 段的文件偏移指向文件之外
+    let mut data = elf.clone();
+    set_u64(&mut data, load + 8, 0x7fff_ffff_0000);
+    ok &= expect_enoexec("segment out of file", &data);
+
+    // LOAD 段映射到内核地址
+    let mut data = elf.clone();
+    set_u64(&mut data, load + 16, 0xffff_ffff_0000_0000);
+    ok &= expect_enoexec("segment in kernel space", &data);
+
+    // 第二个 LOAD 段与第一个映射到同一页
+    let mut data = elf.clone();
+    let first_page = u64_at(&data, load + 16) & !0xfff;
+    let second_offset = u64_at(&data, loads[1] + 16) & 0xfff;
+    set_u64(&mut data, loads[1] + 16, first_page + second_offset);
+    ok &= expect_enoexec("overlapping segments", &data);
+
+    // 入口地址指向程序段之外
+    let mut data = elf.clone();
+    set_u64(&mut data, 0x18, 0x10);
+    ok &= expect_enoexec("bad entry", &data);
+
+    // 未损坏的副本仍可正常执行
+    write_file(BROKEN, &elf);
+    let pid = fork();
+    if pid == 0 {
+        let args: [*const u8; 2] = [BROKEN.as_ptr(), core::ptr::null::<u8>()];
+        exec(BROKEN, &args);
+        exit(-1);
+    }
+    let mut exit_code = 0;
+    waitpid(pid as usize, &mut exit_code);
+    if exit_code != 0 {
+        println!("valid copy failed to run: {}", exit_code);
+        ok = false;
+    }
+
+    // ET_DYN 文件按 R_RISCV_RELATIVE 重定位后运行
+    ok &= run_pie();
+
+    if ok {
+        println!("elf_test passed!");
+        0
+    } else {
+        -1
+    }
+}
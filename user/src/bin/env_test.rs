@@ -0,0 +1,103 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::*;
+
+fn check_child() -> i32 {
+    if getenv("ENV_TEST").as_deref() != Some("hello world") {
+        println!("environment not passed through exec!");
+        return -1;
+    }
+    if getenv("ENV_TEST_UNSET").is_some() {
+        println!("unset variable leaked into exec!");
+        return -1;
+    }
+    if getauxval(AT_PAGESZ) != 4096 {
+        println!("AT_PAGESZ = {}", getauxval(AT_PAGESZ));
+        return -1;
+    }
+    if getauxval(AT_ENTRY) == 0 || getauxval(AT_PHDR) == 0 || getauxval(AT_PHNUM) == 0 {
+        println!("missing program header auxv entries!");
+        return -1;
+    }
+    let random = getauxval(AT_RANDOM);
+    if random == 0 {
+        println!("AT_RANDOM missing!");
+        return -1;
+    }
+    let bytes = unsafe { core::slice::from_raw_parts(random as *const u8, 16) };
+    if bytes.iter().all(|b| *b == 0) {
+        println!("AT_RANDOM is all zero!");
+        return -1;
+    }
+    0
+}
+
+// 参数放不进一页大小的用户栈时 exec 返回 E2BIG，进程继续运行
+fn exec_too_big() -> i32 {
+    let limit = RLimit {
+        rlim_cur: 4096,
+        rlim_max: 4096,
+    };
+    assert_eq!(setrlimit(RLIMIT_STACK, &limit), 0);
+    let mut arg = "a".repeat(1000);
+    arg.push('\0');
+    let mut args: Vec<*const u8> = Vec::from(["env_test\0".as_ptr()]);
+    args.extend((0..8).map(|_| arg.as_ptr()));
+    args.push(core::ptr::null::<u8>());
+    let ret = exec("env_test\0", &args);
+    if ret != -E2BIG {
+        println!("exec with oversized argv returned {}", ret);
+        return -1;
+    }
+    0
+}
+
+#[unsafe(no_mangle)]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    if argc > 1 && argv[1] == "child" {
+        return check_child();
+    }
+
+    assert_eq!(setenv("ENV_TEST", "hello", false), 0);
+    assert_eq!(setenv("ENV_TEST", "world", false), 0);
+    assert_eq!(getenv("ENV_TEST").as_deref(), Some("hello"));
+    assert_eq!(setenv("ENV_TEST", "hello world", true), 0);
+    assert!(setenv("BAD=NAME", "x", true) < 0);
+    assert_eq!(setenv("ENV_TEST_UNSET", "x", true), 0);
+    assert_eq!(unsetenv("ENV_TEST_UNSET"), 0);
+
+    let pid = fork();
+    if pid == 0 {
+        let args: [*const u8; 3] = [
+            "env_test\0".as_ptr(),
+            "child\0".as_ptr(),
+            core::ptr::null::<u8>(),
+        ];
+        exec("env_test\0", &args);
+        panic!("unreachable!");
+    }
+    let mut exit_code = 0;
+    waitpid(pid as usize, &mut exit_code);
+    if exit_code != 0 {
+        println!("env_test failed!");
+        return -1;
+    }
+
+    let pid = fork();
+    if pid == 0 {
+        exit(exec_too_big());
+    }
+    waitpid(pid as usize, &mut exit_code);
+    if exit_code != 0 {
+        println!("env_test failed!");
+        return -1;
+    }
+    println!("env_test passed!");
+    0
+}
@@ -0,0 +1,201 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::*;
+
+fn new_pipe() -> (usize, usize) {
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    (pipe_fd[0], pipe_fd[1])
+}
+
+fn wait_one(epfd: usize, timeout_ms: isize) -> Option<EpollEvent> {
+    let mut events = [EpollEvent::default(); 4];
+    let ready = epoll_wait(epfd, &mut events, timeout_ms);
+    assert!(ready >= 0);
+    assert!(ready <= 1);
+    (ready == 1).then_some(events[0])
+}
+
+// 水平触发：数据未读完时每次等待都报告
+fn epoll_test_level() {
+    let epfd = epoll_create1(OpenFlags::CLOEXEC) as usize;
+    assert_eq!(fcntl(epfd, F_GETFD, 0), FD_CLOEXEC as isize);
+    let (read_end, write_end) = new_pipe();
+    let event = EpollEvent::new(EPOLLIN, 7);
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_ADD, read_end, Some(&event)), 0);
+    assert!(wait_one(epfd, 0).is_none());
+
+    write(write_end, b"hi");
+    for _ in 0..2 {
+        let event = wait_one(epfd, 0).unwrap();
+        assert_eq!(event.events, EPOLLIN);
+        assert_eq!(event.data, 7);
+    }
+    let mut buf = [0u8; 4];
+    assert_eq!(read(read_end, &mut buf), 2);
+    assert!(wait_one(epfd, 0).is_none());
+
+    // 写端全部关闭后总是报告挂断
+    close(write_end);
+    assert_eq!(wait_one(epfd, -1).unwrap().events, EPOLLHUP);
+    close(read_end);
+    close(epfd);
+}
+
+// 边沿触发：每次写入只报告一次；一次性：报告后停用直到重新设置
+fn epoll_test_edge_and_oneshot() {
+    let epfd = epoll_create1(OpenFlags::empty()) as usize;
+    let (read_end, write_end) = new_pipe();
+    let event = EpollEvent::new(EPOLLIN | EPOLLET, 1);
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_ADD, read_end, Some(&event)), 0);
+    write(write_end, b"a");
+    assert_eq!(wait_one(epfd, 0).unwrap().data, 1);
+    assert!(wait_one(epfd, 0).is_none());
+    write(write_end, b"b");
+    assert_eq!(wait_one(epfd, 0).unwrap().data, 1);
+    assert!(wait_one(epfd, 0).is_none());
+
+    let event = EpollEvent::new(EPOLLIN | EPOLLONESHOT, 2);
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_MOD, read_end, Some(&event)), 0);
+    assert_eq!(wait_one(epfd, 0).unwrap().data, 2);
+    assert!(wait_one(epfd, 0).is_none());
+    write(write_end, b"c");
+    assert!(wait_one(epfd, 0).is_none());
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_MOD, read_end, Some(&event)), 0);
+    assert_eq!(wait_one(epfd, 0).unwrap().data, 2);
+
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_DEL, read_end, None), 0);
+    assert!(wait_one(epfd, 0).is_none());
+    close(read_end);
+    close(write_end);
+    close(epfd);
+}
+
+// 子进程通过继承的 epoll 描述符添加兴趣项并写入，父进程的等待被唤醒
+fn epoll_test_fork() {
+    let epfd = epoll_create1(OpenFlags::empty()) as usize;
+    let (first_read, first_write) = new_pipe();
+    let (second_read, second_write) = new_pipe();
+    let event = EpollEvent::new(EPOLLIN, 1);
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_ADD, first_read, Some(&event)), 0);
+    let pid = fork();
+    if pid == 0 {
+        let event = EpollEvent::new(EPOLLIN, 2);
+        assert_eq!(epoll_ctl(epfd, EPOLL_CTL_ADD, second_read, Some(&event)), 0);
+        sleep(20);
+        write(second_write, b"x");
+        exit(0);
+    }
+    let event = wait_one(epfd, -1).unwrap();
+    assert_eq!(event.events, EPOLLIN);
+    assert_eq!(event.data, 2);
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    for fd in [first_read, first_write, second_read, second_write, epfd] {
+        close(fd);
+    }
+}
+
+// 超时返回 0；多个就绪项按 maxevents 分批返回
+fn epoll_test_timeout_and_batches() {
+    let epfd = epoll_create1(OpenFlags::empty()) as usize;
+    let (read_end, write_end) = new_pipe();
+    let event = EpollEvent::new(EPOLLIN, 0);
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_ADD, read_end, Some(&event)), 0);
+    let start = time();
+    assert!(wait_one(epfd, 30).is_none());
+    assert!(time() - start >= 30);
+
+    let event = EpollEvent::new(EPOLLOUT, 1);
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_ADD, write_end, Some(&event)), 0);
+    write(write_end, b"x");
+    let mut events = [EpollEvent::default(); 1];
+    assert_eq!(epoll_wait(epfd, &mut events, 0), 1);
+    let first = events[0].data;
+    assert_eq!(epoll_wait(epfd, &mut events, 0), 1);
+    assert_ne!(events[0].data, first);
+    let mut events = [EpollEvent::default(); 4];
+    assert_eq!(epoll_wait(epfd, &mut events, 0), 2);
+    close(read_end);
+    close(write_end);
+    close(epfd);
+}
+
+fn epoll_test_errors() {
+    assert_eq!(epoll_create1(OpenFlags::APPEND), -EINVAL);
+    let epfd = epoll_create1(OpenFlags::empty()) as usize;
+    let (read_end, write_end) = new_pipe();
+    let event = EpollEvent::new(EPOLLIN, 0);
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_ADD, read_end, Some(&event)), 0);
+    assert_eq!(
+        epoll_ctl(epfd, EPOLL_CTL_ADD, read_end, Some(&event)),
+        -EEXIST
+    );
+    assert_eq!(
+        epoll_ctl(epfd, EPOLL_CTL_MOD, write_end, Some(&event)),
+        -ENOENT
+    );
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_DEL, write_end, None), -ENOENT);
+    assert_eq!(epoll_ctl(epfd, 9, write_end, Some(&event)), -EINVAL);
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_ADD, epfd, Some(&event)), -EINVAL);
+    assert_eq!(
+        epoll_ctl(read_end, EPOLL_CTL_ADD, write_end, Some(&event)),
+        -EINVAL
+    );
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_ADD, 100, Some(&event)), -EBADF);
+    let mut events = [EpollEvent::default(); 1];
+    assert_eq!(epoll_wait(epfd, &mut events[..0], 0), -EINVAL);
+    assert_eq!(epoll_wait(read_end, &mut events, 0), -EINVAL);
+    assert_eq!(epoll_wait(100, &mut events, 0), -EBADF);
+
+    // 文件被释放后兴趣项失效，复用的描述符号可以重新添加
+    close(read_end);
+    close(write_end);
+    let (new_read, new_write) = new_pipe();
+    assert_eq!(new_read, read_end);
+    assert_eq!(epoll_ctl(epfd, EPOLL_CTL_ADD, new_read, Some(&event)), 0);
+    assert!(wait_one(epfd, 0).is_none());
+    close(new_read);
+    close(new_write);
+    close(epfd);
+}
+
+fn on_usr1() {
+    sigreturn();
+}
+
+// 待决信号被临时掩码解除屏蔽时，等待被打断
+fn epoll_test_sigmask() {
+    let action = SignalAction {
+        handler: on_usr1 as usize,
+        mask: SignalFlags::empty(),
+    };
+    assert_eq!(sigaction(SIGUSR1, Some(&action), None), 0);
+    sigprocmask(SignalFlags::SIGUSR1.bits(), None);
+    kill(pid() as usize, SIGUSR1);
+
+    let epfd = epoll_create1(OpenFlags::empty()) as usize;
+    let mut events = [EpollEvent::default(); 1];
+    let blocked = SignalFlags::SIGUSR1.bits();
+    assert_eq!(epoll_pwait(epfd, &mut events, 10, Some(&blocked)), 0);
+    assert_eq!(epoll_pwait(epfd, &mut events, 10, Some(&0)), -EINTR);
+    sigprocmask(0, None);
+    close(epfd);
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    epoll_test_level();
+    epoll_test_edge_and_oneshot();
+    epoll_test_fork();
+    epoll_test_timeout_and_batches();
+    epoll_test_errors();
+    epoll_test_sigmask();
+    println!("epoll_test passed!");
+    0
+}
@@ -0,0 +1,74 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::*;
+
+// 内核没有实现的系统调用号
+const SYSCALL_UNKNOWN: usize = 9999;
+
+// 未实现的系统调用返回 -ENOSYS，进程继续运行
+fn errno_test_enosys() {
+    assert_eq!(syscall6(SYSCALL_UNKNOWN, [0; 6]), -ENOSYS);
+    assert_eq!(syscall6(usize::MAX, [0; 6]), -ENOSYS);
+}
+
+fn errno_test_result() {
+    assert_eq!(check(-EBADF), Err(Errno(EBADF)));
+    assert_eq!(check(3), Ok(3));
+    assert_eq!(check(close(100)), Err(Errno(EBADF)));
+    assert_eq!(strerror(ENOENT), "No such file or directory");
+    assert_eq!(Errno(ENOSYS).strerror(), "Function not implemented");
+    assert_eq!(strerror(4095), "Unknown error");
+}
+
+fn errno_test_process() {
+    assert_eq!(wait(&mut 0), -ECHILD);
+    assert_eq!(
+        exec("no_such_program\0", &[core::ptr::null::<u8>()]),
+        -ENOENT
+    );
+    assert_eq!(kill(1_000_000, SIGUSR1), -ESRCH);
+    assert_eq!(kill(pid() as usize, 1000), -EINVAL);
+}
+
+fn errno_test_signal() {
+    let action = SignalAction::default();
+    let mut old = SignalAction::default();
+    assert_eq!(sigaction(SIGKILL, Some(&action), None), -EINVAL);
+    assert_eq!(sigaction(SIGRTMAX + 1, None, Some(&mut old)), -EINVAL);
+    // 只查询 SIGKILL 的动作是允许的
+    assert_eq!(sigaction(SIGKILL, None, Some(&mut old)), 0);
+    assert_eq!(sigaction(SIGUSR1, None, None), 0);
+}
+
+fn errno_test_rlimit() {
+    let mut limit = RLimit::default();
+    assert_eq!(getrlimit(RLIMIT_NOFILE, &mut limit), 0);
+    let bad = RLimit {
+        rlim_cur: limit.rlim_max,
+        rlim_max: limit.rlim_cur.saturating_sub(1),
+    };
+    assert_eq!(setrlimit(RLIMIT_NOFILE, &bad), -EINVAL);
+    if limit.rlim_max != RLIM_INFINITY {
+        let raise = RLimit {
+            rlim_cur: limit.rlim_cur,
+            rlim_max: limit.rlim_max + 1,
+        };
+        assert_eq!(setrlimit(RLIMIT_NOFILE, &raise), -EPERM);
+    }
+    assert_eq!(getrlimit(1000, &mut limit), -EINVAL);
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    errno_test_enosys();
+    errno_test_result();
+    errno_test_process();
+    errno_test_signal();
+    errno_test_rlimit();
+    println!("errno_test passed!");
+    0
+}
@@ -0,0 +1,159 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::*;
+
+const FILE: &str = "fcntl_test_file\0";
+const CLOEXEC_FD: usize = 20;
+const KEEP_FD: usize = 21;
+
+// exec 之后运行：带 FD_CLOEXEC 的描述符已关闭，其余保持打开
+fn after_exec() -> i32 {
+    if fcntl(CLOEXEC_FD, F_GETFD, 0) != -EBADF {
+        return 1;
+    }
+    if fcntl(KEEP_FD, F_GETFD, 0) != 0 {
+        return 2;
+    }
+    0
+}
+
+fn read_file() -> ([u8; 16], usize) {
+    let fd = open(FILE, OpenFlags::RDONLY);
+    assert!(fd > 0);
+    let mut buf = [0u8; 16];
+    let len = read(fd as usize, &mut buf);
+    close(fd as usize);
+    (buf, len as usize)
+}
+
+fn fcntl_test_append() {
+    let fd = open(FILE, OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(fd > 0);
+    let fd = fd as usize;
+    assert_eq!(write(fd, b"abc"), 3);
+    assert_eq!(fcntl(fd, F_GETFL, 0), OpenFlags::WRONLY.bits() as isize);
+    close(fd);
+
+    // 打开时指定 O_APPEND：偏移量从 0 开始，写入仍然追加到末尾
+    let fd = open(FILE, OpenFlags::WRONLY | OpenFlags::APPEND) as usize;
+    assert_eq!(
+        fcntl(fd, F_GETFL, 0),
+        (OpenFlags::WRONLY | OpenFlags::APPEND).bits() as isize
+    );
+    assert_eq!(write(fd, b"de"), 2);
+    close(fd);
+    let (buf, len) = read_file();
+    assert_eq!(&buf[..len], b"abcde");
+
+    // 通过 F_SETFL 关闭追加模式后从当前偏移量写入
+    let fd = open(FILE, OpenFlags::RDWR | OpenFlags::APPEND) as usize;
+    assert_eq!(write(fd, b"f"), 1);
+    assert_eq!(fcntl(fd, F_SETFL, 0), 0);
+    assert_eq!(fcntl(fd, F_GETFL, 0), OpenFlags::RDWR.bits() as isize);
+    assert_eq!(write(fd, b"X"), 1);
+    close(fd);
+    let (buf, len) = read_file();
+    assert_eq!(&buf[..len], b"abcdefX");
+}
+
+fn fcntl_test_dupfd() {
+    let fd = open(FILE, OpenFlags::RDONLY) as usize;
+    let new_fd = fcntl(fd, F_DUPFD, 10);
+    assert!(new_fd >= 10);
+    assert_eq!(fcntl(new_fd as usize, F_GETFD, 0), 0);
+    let cloexec_fd = fcntl(fd, F_DUPFD_CLOEXEC, new_fd as usize);
+    assert!(cloexec_fd > new_fd);
+    assert_eq!(fcntl(cloexec_fd as usize, F_GETFD, 0), FD_CLOEXEC as isize);
+    assert_eq!(fcntl(cloexec_fd as usize, F_SETFD, 0), 0);
+    assert_eq!(fcntl(cloexec_fd as usize, F_GETFD, 0), 0);
+    close(cloexec_fd as usize);
+    close(new_fd as usize);
+    close(fd);
+    assert_eq!(fcntl(fd, F_GETFL, 0), -EBADF);
+    assert_eq!(fcntl(0, 12345, 0), -EINVAL);
+}
+
+fn fcntl_test_cloexec() {
+    let fd = open(FILE, OpenFlags::RDONLY) as usize;
+    assert_eq!(fcntl(fd, F_DUPFD_CLOEXEC, CLOEXEC_FD), CLOEXEC_FD as isize);
+    assert_eq!(fcntl(fd, F_DUPFD, KEEP_FD), KEEP_FD as isize);
+    close(fd);
+    let pid = fork();
+    if pid == 0 {
+        let args: [*const u8; 3] = [
+            "fcntl_test\0".as_ptr(),
+            "-exec\0".as_ptr(),
+            core::ptr::null::<u8>(),
+        ];
+        exec("fcntl_test\0", &args);
+        exit(-1);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    close(CLOEXEC_FD);
+    close(KEEP_FD);
+}
+
+// O_NONBLOCK 下终端没有可读数据时返回 EAGAIN
+fn fcntl_test_nonblock() {
+    let (mut master, mut slave) = (0, 0);
+    assert_eq!(openpty(&mut master, &mut slave), 0);
+    let mut buf = [0u8; 8];
+    for fd in [master, slave] {
+        let flags = fcntl(fd, F_GETFL, 0);
+        assert_eq!(flags & OpenFlags::NONBLOCK.bits() as isize, 0);
+        assert_eq!(
+            fcntl(
+                fd,
+                F_SETFL,
+                flags as usize | OpenFlags::NONBLOCK.bits() as usize
+            ),
+            0
+        );
+        assert_eq!(read(fd, &mut buf), -EAGAIN);
+    }
+    assert_eq!(write(master, b"x\n"), 2);
+    assert_eq!(read(slave, &mut buf), 2);
+    assert_eq!(&buf[..2], b"x\n");
+    close(master);
+    close(slave);
+}
+
+fn fcntl_test_winsize() {
+    let (mut master, mut slave) = (0, 0);
+    assert_eq!(openpty(&mut master, &mut slave), 0);
+    let mut winsize = Winsize::default();
+    assert_eq!(tcgetwinsize(slave, &mut winsize), 0);
+    assert_eq!((winsize.ws_row, winsize.ws_col), (24, 80));
+    let new_size = Winsize {
+        ws_row: 40,
+        ws_col: 120,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    assert_eq!(tcsetwinsize(master, &new_size), 0);
+    assert_eq!(tcgetwinsize(slave, &mut winsize), 0);
+    assert_eq!(winsize, new_size);
+    assert_eq!(tcgetwinsize(0, &mut winsize), 0);
+    close(master);
+    close(slave);
+}
+
+#[unsafe(no_mangle)]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    if argc > 1 && argv[1] == "-exec" {
+        return after_exec();
+    }
+    fcntl_test_append();
+    fcntl_test_dupfd();
+    fcntl_test_cloexec();
+    fcntl_test_nonblock();
+    fcntl_test_winsize();
+    println!("fcntl_test passed!");
+    0
+}
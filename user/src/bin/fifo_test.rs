@@ -0,0 +1,130 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::*;
+
+// 文件系统在多次运行之间保留，命名管道可能已经存在
+fn make_fifo(path: &str) {
+    let ret = mkfifo(path);
+    assert!(ret == 0 || ret == -EEXIST);
+}
+
+// 只写打开阻塞到读端出现；写端关闭后读端读到 EOF
+fn fifo_test_blocking_open() {
+    make_fifo("fifo_test_a\0");
+    let pid = fork();
+    if pid == 0 {
+        let fd = open("fifo_test_a\0", OpenFlags::WRONLY);
+        assert!(fd >= 0);
+        assert_eq!(write(fd as usize, b"hello fifo"), 10);
+        exit(0);
+    }
+    sleep(20);
+    let fd = open("fifo_test_a\0", OpenFlags::RDONLY);
+    assert!(fd >= 0);
+    let mut buf = [0u8; 32];
+    let mut len = 0;
+    loop {
+        let n = read(fd as usize, &mut buf[len..]);
+        assert!(n >= 0);
+        if n == 0 {
+            break;
+        }
+        len += n as usize;
+    }
+    assert_eq!(&buf[..len], b"hello fifo");
+    close(fd as usize);
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+}
+
+// 只读打开阻塞到写端出现
+fn fifo_test_blocking_reader() {
+    make_fifo("fifo_test_b\0");
+    let pid = fork();
+    if pid == 0 {
+        let fd = open("fifo_test_b\0", OpenFlags::RDONLY);
+        assert!(fd >= 0);
+        let mut buf = [0u8; 8];
+        assert_eq!(read(fd as usize, &mut buf), 4);
+        assert_eq!(&buf[..4], b"ping");
+        exit(0);
+    }
+    sleep(20);
+    let fd = open("fifo_test_b\0", OpenFlags::WRONLY);
+    assert!(fd >= 0);
+    assert_eq!(write(fd as usize, b"ping"), 4);
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    close(fd as usize);
+}
+
+// 非阻塞打开：没有读端时只写打开返回 ENXIO，只读打开立即返回
+fn fifo_test_nonblock() {
+    make_fifo("fifo_test_c\0");
+    assert_eq!(
+        open("fifo_test_c\0", OpenFlags::WRONLY | OpenFlags::NONBLOCK),
+        -ENXIO
+    );
+    let read_fd = open("fifo_test_c\0", OpenFlags::RDONLY | OpenFlags::NONBLOCK);
+    assert!(read_fd >= 0);
+    let mut buf = [0u8; 8];
+    assert_eq!(read(read_fd as usize, &mut buf), 0);
+    let write_fd = open("fifo_test_c\0", OpenFlags::WRONLY | OpenFlags::NONBLOCK);
+    assert!(write_fd >= 0);
+    assert_eq!(read(read_fd as usize, &mut buf), -EAGAIN);
+    assert_eq!(write(write_fd as usize, b"abc"), 3);
+    assert_eq!(read(read_fd as usize, &mut buf), 3);
+    assert_eq!(&buf[..3], b"abc");
+    close(write_fd as usize);
+    close(read_fd as usize);
+}
+
+// 读写打开不阻塞；所有端点关闭后未读的数据被丢弃
+fn fifo_test_rdwr() {
+    make_fifo("fifo_test_d\0");
+    let fd = open("fifo_test_d\0", OpenFlags::RDWR | OpenFlags::CREATE);
+    assert!(fd >= 0);
+    assert_eq!(fcntl(fd as usize, F_GETPIPE_SZ, 0), 65536);
+    assert_eq!(write(fd as usize, b"loop"), 4);
+    let mut buf = [0u8; 8];
+    assert_eq!(read(fd as usize, &mut buf), 4);
+    assert_eq!(&buf[..4], b"loop");
+    assert_eq!(write(fd as usize, b"lost"), 4);
+    close(fd as usize);
+
+    let fd = open("fifo_test_d\0", OpenFlags::RDWR | OpenFlags::NONBLOCK);
+    assert!(fd >= 0);
+    assert_eq!(read(fd as usize, &mut buf), -EAGAIN);
+    close(fd as usize);
+}
+
+// mknod 的错误：同名文件已存在、不支持的文件类型
+fn fifo_test_mknod() {
+    make_fifo("fifo_test_a\0");
+    assert_eq!(mkfifo("fifo_test_a\0"), -EEXIST);
+    assert_eq!(mknod("fifo_test_chr\0", 0o020644), -EPERM);
+    assert_eq!(mknod("fifo_test_dir\0", 0o040755), -EINVAL);
+    let ret = mknod("fifo_test_reg\0", S_IFREG | 0o644);
+    assert!(ret == 0 || ret == -EEXIST);
+    let fd = open("fifo_test_reg\0", OpenFlags::RDWR);
+    assert!(fd >= 0);
+    assert_eq!(fcntl(fd as usize, F_GETPIPE_SZ, 0), -EINVAL);
+    close(fd as usize);
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    fifo_test_blocking_open();
+    fifo_test_blocking_reader();
+    fifo_test_nonblock();
+    fifo_test_rdwr();
+    fifo_test_mknod();
+    println!("fifo_test passed!");
+    0
+}
@@ -0,0 +1,194 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::*;
+
+const LOOPBACK: [u8; 4] = [127, 0, 0, 1];
+
+fn new_socket(type_: usize) -> usize {
+    let fd = socket(AF_INET, type_, 0);
+    assert!(fd >= 0);
+    fd as usize
+}
+
+fn pattern(i: usize) -> u8 {
+    (i * 7 % 251) as u8
+}
+
+// TCP：经回环接口连接、accept 得到对端地址、双向收发、对端关闭后 EOF
+fn inet_test_tcp() {
+    let listener = new_socket(SOCK_STREAM);
+    let addr = SockAddrIn::new(LOOPBACK, 7100);
+    assert_eq!(bind(listener, &addr), 0);
+    assert_eq!(listen(listener, 4), 0);
+
+    let pid = fork();
+    if pid == 0 {
+        close(listener);
+        let client = new_socket(SOCK_STREAM);
+        assert_eq!(connect(client, &addr), 0);
+        assert_eq!(connect(client, &addr), -EISCONN);
+        assert_eq!(write(client, b"hello"), 5);
+        let mut buf = [0u8; 16];
+        assert_eq!(read(client, &mut buf), 5);
+        assert_eq!(&buf[..5], b"world");
+        exit(0);
+    }
+
+    let mut peer = SockAddrIn::default();
+    let mut peer_len = core::mem::size_of::<SockAddrIn>() as u32;
+    let conn = accept(listener, Some((&mut peer, &mut peer_len)));
+    assert!(conn >= 0);
+    let conn = conn as usize;
+    assert_eq!(peer_len as usize, core::mem::size_of::<SockAddrIn>());
+    assert_eq!(peer.addr(), LOOPBACK);
+    assert_ne!(peer.port(), 0);
+    let mut buf = [0u8; 16];
+    assert_eq!(recv(conn, &mut buf, 0), 5);
+    assert_eq!(&buf[..5], b"hello");
+    assert_eq!(send(conn, b"world", 0), 5);
+
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(read(conn, &mut buf), 0);
+    close(conn);
+    close(listener);
+}
+
+// 大块数据需要多个报文段与窗口更新才能传完，内容保持不变
+fn inet_test_bulk() {
+    const TOTAL: usize = 256 * 1024;
+    let listener = new_socket(SOCK_STREAM);
+    let addr = SockAddrIn::new(LOOPBACK, 7101);
+    assert_eq!(bind(listener, &addr), 0);
+    assert_eq!(listen(listener, 1), 0);
+
+    let pid = fork();
+    if pid == 0 {
+        close(listener);
+        let client = new_socket(SOCK_STREAM);
+        assert_eq!(connect(client, &addr), 0);
+        let data: Vec<u8> = (0..TOTAL).map(pattern).collect();
+        let mut sent = 0;
+        while sent < TOTAL {
+            let n = write(client, &data[sent..]);
+            assert!(n > 0);
+            sent += n as usize;
+        }
+        close(client);
+        exit(0);
+    }
+
+    let conn = accept::<SockAddrIn>(listener, None);
+    assert!(conn >= 0);
+    let conn = conn as usize;
+    let mut buf = [0u8; 4096];
+    let mut received = 0;
+    loop {
+        let n = read(conn, &mut buf);
+        assert!(n >= 0);
+        if n == 0 {
+            break;
+        }
+        for (i, &b) in buf[..n as usize].iter().enumerate() {
+            assert_eq!(b, pattern(received + i));
+        }
+        received += n as usize;
+    }
+    assert_eq!(received, TOTAL);
+
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    close(conn);
+    close(listener);
+}
+
+// UDP：保留消息边界、返回发送方地址、connect 之后直接 read/write
+fn inet_test_udp() {
+    let a = new_socket(SOCK_DGRAM);
+    let b = new_socket(SOCK_DGRAM | SOCK_NONBLOCK);
+    let addr_a = SockAddrIn::new(LOOPBACK, 7200);
+    assert_eq!(bind(a, &addr_a), 0);
+    assert_eq!(send(b, b"no peer", 0), -EDESTADDRREQ);
+    assert_eq!(sendto(b, b"one", 0, &addr_a), 3);
+    assert_eq!(sendto(b, b"three", 0, &addr_a), 5);
+
+    let mut from = SockAddrIn::default();
+    let mut from_len = core::mem::size_of::<SockAddrIn>() as u32;
+    let mut buf = [0u8; 16];
+    assert_eq!(
+        recvfrom(a, &mut buf, 0, Some((&mut from, &mut from_len))),
+        3
+    );
+    assert_eq!(&buf[..3], b"one");
+    assert_eq!(from.addr(), LOOPBACK);
+    assert_eq!(recv(a, &mut buf[..2], 0), 2);
+    assert_eq!(&buf[..2], b"th");
+    assert_eq!(recv(a, &mut buf, MSG_DONTWAIT), -EAGAIN);
+
+    assert_eq!(sendto(a, b"reply", 0, &from), 5);
+    assert_eq!(read(b, &mut buf), 5);
+    assert_eq!(&buf[..5], b"reply");
+    assert_eq!(read(b, &mut buf), -EAGAIN);
+
+    assert_eq!(connect(b, &addr_a), 0);
+    assert_eq!(write(b, b"again"), 5);
+    assert_eq!(read(a, &mut buf), 5);
+    assert_eq!(&buf[..5], b"again");
+    close(a);
+    close(b);
+}
+
+// 错误：连接被拒绝、端口占用、非本机地址、监听者关闭时重置未 accept 的连接
+fn inet_test_errors() {
+    let client = new_socket(SOCK_STREAM);
+    assert_eq!(
+        connect(client, &SockAddrIn::new(LOOPBACK, 7300)),
+        -ECONNREFUSED
+    );
+    close(client);
+
+    let listener = new_socket(SOCK_STREAM);
+    let other = new_socket(SOCK_STREAM);
+    let addr = SockAddrIn::new(LOOPBACK, 7301);
+    assert_eq!(bind(listener, &SockAddrIn::new([0; 4], 7301)), 0);
+    assert_eq!(bind(other, &addr), -EADDRINUSE);
+    assert_eq!(
+        bind(other, &SockAddrIn::new([8, 8, 8, 8], 7302)),
+        -EADDRNOTAVAIL
+    );
+    assert_eq!(bind(other, &SockAddrUn::new("inet")), -97);
+    assert_eq!(listen(listener, 1), 0);
+
+    let client = new_socket(SOCK_STREAM);
+    assert_eq!(connect(client, &addr), 0);
+    assert_eq!(write(client, b"pending"), 7);
+    close(listener);
+    let mut buf = [0u8; 8];
+    assert_eq!(read(client, &mut buf), -ECONNRESET);
+    assert_eq!(read(client, &mut buf), 0);
+    close(client);
+
+    let dgram = new_socket(SOCK_DGRAM);
+    assert_eq!(listen(dgram, 1), -EOPNOTSUPP);
+    close(dgram);
+    close(other);
+    assert_eq!(socket(AF_INET, 3, 0), -EINVAL);
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    inet_test_tcp();
+    inet_test_bulk();
+    inet_test_udp();
+    inet_test_errors();
+    println!("inet_socket_test passed!");
+    0
+}
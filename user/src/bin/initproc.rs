@@ -3,7 +3,7 @@
 
 extern crate user_lib;
 
-use user_lib::{exec, fork, wait, yield_};
+use user_lib::{ECHILD, exec, fork, wait, yield_};
 
 #[unsafe(no_mangle)]
 fn main() -> i32 {
@@ -13,7 +13,7 @@ fn main() -> i32 {
         loop {
             let mut exit_code: i32 = 0;
             let pid = wait(&mut exit_code);
-            if pid == -1 {
+            if pid == -ECHILD {
                 yield_();
                 continue;
             }
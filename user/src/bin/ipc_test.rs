@@ -0,0 +1,226 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+#[macro_use]
+extern crate user_lib;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use components::log::{IpcTransport, LogClient, LogLevel, LogTransport};
+use user_lib::*;
+
+fn new_pages(n: usize) -> Vec<IpcPage> {
+    (0..n).map(|_| IpcPage::default()).collect()
+}
+
+fn fill(pages: &mut [IpcPage], seed: u8) {
+    for (i, page) in pages.iter_mut().enumerate() {
+        for (j, byte) in page.0.iter_mut().enumerate() {
+            *byte = seed.wrapping_add((i * 7 + j) as u8);
+        }
+    }
+}
+
+fn check(pages: &[IpcPage], seed: u8, len: usize) -> bool {
+    pages
+        .iter()
+        .flat_map(|page| page.0.iter())
+        .take(len)
+        .enumerate()
+        .all(|(k, &byte)| {
+            byte == seed.wrapping_add((k / IPC_PAGE_SIZE * 7 + k % IPC_PAGE_SIZE) as u8)
+        })
+}
+
+fn wait_child(pid: isize) -> i32 {
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    exit_code
+}
+
+// 消息字的 send/recv 与 call/reply
+fn ipc_test_words() {
+    let h = ipc_create();
+    assert!(h >= 0);
+    let h = h as usize;
+    let parent = pid();
+
+    let child = fork();
+    if child == 0 {
+        let mut words = [0; IPC_MSG_WORDS];
+        assert_eq!(ipc_recv(h, &mut words, None, 0), parent);
+        assert_eq!(words, [1, 2, 3, 4]);
+        assert_eq!(ipc_recv(h, &mut words, None, 0), parent);
+        assert_eq!(words, [10, 20, 30, 40]);
+        let sum = words.iter().sum();
+        assert_eq!(ipc_reply([sum, 0, 0, 0], None), 0);
+        // 应答权只能使用一次
+        assert_eq!(ipc_reply([0; IPC_MSG_WORDS], None), -EINVAL);
+        exit(0);
+    }
+    assert_eq!(ipc_send(h, [1, 2, 3, 4], None), 0);
+    let mut words = [10, 20, 30, 40];
+    assert_eq!(ipc_call(h, &mut words, None), 0);
+    assert_eq!(words[0], 100);
+    assert_eq!(wait_child(child), 0);
+    assert_eq!(ipc_close(h), 0);
+    assert_eq!(ipc_close(h), -EBADF);
+}
+
+// 按页转移数据：发送方的页被换成零页，过大的消息退回发送方
+fn ipc_test_pages() {
+    let h = ipc_create() as usize;
+    let child = fork();
+    if child == 0 {
+        let mut words = [0; IPC_MSG_WORDS];
+        let mut pages = new_pages(2);
+        let mut buf = IpcBuffer::new(&mut pages, 0);
+        assert!(ipc_recv(h, &mut words, Some(&mut buf), 0) > 0);
+        assert_eq!(buf.len, 5000);
+        assert!(check(&pages, 3, 5000));
+
+        // 只能容纳一页，两页的消息以 EMSGSIZE 失败
+        let mut small = new_pages(1);
+        let mut buf = IpcBuffer::new(&mut small, 0);
+        assert_eq!(ipc_recv(h, &mut words, Some(&mut buf), 0), -EMSGSIZE);
+
+        let mut buf = IpcBuffer::new(&mut small, 0);
+        assert!(ipc_recv(h, &mut words, Some(&mut buf), 0) > 0);
+        assert_eq!(&small[0].0[..buf.len], b"ping");
+        let mut reply = new_pages(1);
+        reply[0].0[..4].copy_from_slice(b"pong");
+        let buf = IpcBuffer::new(&mut reply, 4);
+        assert_eq!(ipc_reply([7, 0, 0, 0], Some(&buf)), 0);
+        exit(0);
+    }
+    let mut words = [0; IPC_MSG_WORDS];
+    let mut pages = new_pages(2);
+    fill(&mut pages, 3);
+    let buf = IpcBuffer::new(&mut pages, 5000);
+    assert_eq!(ipc_send(h, words, Some(&buf)), 0);
+    assert!(
+        pages
+            .iter()
+            .all(|page| page.0.iter().all(|&byte| byte == 0))
+    );
+
+    fill(&mut pages, 5);
+    let buf = IpcBuffer::new(&mut pages, 5000);
+    assert_eq!(ipc_send(h, words, Some(&buf)), -EMSGSIZE);
+    assert!(check(&pages, 5, 2 * IPC_PAGE_SIZE));
+
+    let mut page = new_pages(1);
+    page[0].0[..4].copy_from_slice(b"ping");
+    let mut buf = IpcBuffer::new(&mut page, 4);
+    assert_eq!(ipc_call(h, &mut words, Some(&mut buf)), 0);
+    assert_eq!(words[0], 7);
+    assert_eq!(&page[0].0[..buf.len], b"pong");
+    assert_eq!(wait_child(child), 0);
+
+    // 缓冲区必须按页对齐，且不超过 IPC_MAX_PAGES 页
+    let mut buf = IpcBuffer::new(&mut page, 4);
+    buf.addr += 1;
+    assert_eq!(ipc_send(h, words, Some(&buf)), -EINVAL);
+    let mut big = new_pages(IPC_MAX_PAGES + 1);
+    let buf = IpcBuffer::new(&mut big, (IPC_MAX_PAGES + 1) * IPC_PAGE_SIZE);
+    assert_eq!(ipc_send(h, words, Some(&buf)), -EMSGSIZE);
+    assert_eq!(ipc_close(h), 0);
+}
+
+// 对端关闭：没有其他句柄时收发以 EPIPE 失败，未应答的 call 以 EPIPE 失败
+fn ipc_test_epipe() {
+    let h = ipc_create() as usize;
+    let mut words = [0; IPC_MSG_WORDS];
+    assert_eq!(ipc_send(h, words, None), -EPIPE);
+    assert_eq!(ipc_recv(h, &mut words, None, IPC_NONBLOCK), -EPIPE);
+
+    let child = fork();
+    if child == 0 {
+        // 父进程关闭句柄后阻塞的接收被唤醒
+        exit(if ipc_recv(h, &mut words, None, 0) == -EPIPE {
+            0
+        } else {
+            1
+        });
+    }
+    assert_eq!(ipc_recv(h, &mut words, None, IPC_NONBLOCK), -EAGAIN);
+    assert_eq!(ipc_close(h), 0);
+    assert_eq!(wait_child(child), 0);
+
+    let h = ipc_create() as usize;
+    let child = fork();
+    if child == 0 {
+        assert!(ipc_recv(h, &mut words, None, 0) > 0);
+        // 不应答就退出
+        exit(0);
+    }
+    assert_eq!(ipc_call(h, &mut words, None), -EPIPE);
+    assert_eq!(wait_child(child), 0);
+    assert_eq!(ipc_close(h), 0);
+}
+
+fn ipc_send_wrapper(handle: usize, words: &[usize; 4], data: &mut [u8]) -> isize {
+    if data.is_empty() {
+        return ipc_send(handle, *words, None);
+    }
+    let buf = IpcBuffer {
+        addr: data.as_mut_ptr() as usize,
+        len: data.len(),
+        cap: data.len(),
+    };
+    ipc_send(handle, *words, Some(&buf))
+}
+
+fn ipc_recv_wrapper(
+    handle: usize,
+    words: &mut [usize; 4],
+    buf: &mut [u8],
+    nonblock: bool,
+) -> isize {
+    let mut buf = IpcBuffer {
+        addr: buf.as_mut_ptr() as usize,
+        len: 0,
+        cap: buf.len(),
+    };
+    let flags = if nonblock { IPC_NONBLOCK } else { 0 };
+    ipc_recv(handle, words, Some(&mut buf), flags)
+}
+
+// 日志消息经 IpcTransport 传给服务进程：短消息放在消息字中，长消息按页转移
+fn ipc_test_log() {
+    let long = "x".repeat(300);
+    let h = ipc_create() as usize;
+    let child = fork();
+    if child == 0 {
+        let server = IpcTransport::new_server(h, ipc_recv_wrapper);
+        let mut count = 0;
+        while server.wait_readable().unwrap() {
+            let message = server.receive().unwrap();
+            match count {
+                0 => assert_eq!(message.message, "hi"),
+                1 => assert_eq!(message.message, long),
+                _ => assert_eq!(message.level, LogLevel::Warn),
+            }
+            count += 1;
+        }
+        exit(count);
+    }
+    let client = LogClient::new(Box::new(IpcTransport::new_client(h, ipc_send_wrapper)));
+    assert!(client.info("ipc", "hi").is_ok());
+    assert!(client.info("ipc", &long).is_ok());
+    assert!(client.warn("ipc", "done").is_ok());
+    assert_eq!(ipc_close(h), 0);
+    assert_eq!(wait_child(child), 3);
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    ipc_test_words();
+    ipc_test_pages();
+    ipc_test_epipe();
+    ipc_test_log();
+    println!("ipc_test passed!");
+    0
+}
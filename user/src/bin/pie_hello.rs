@@ -0,0 +1,22 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+// 内核加载 ET_DYN 文件的基址（os/src/config.rs 的 ELF_DYN_BASE）
+const ELF_DYN_BASE: usize = 0x1000_0000;
+
+// 数据段中的指针在链接时无法确定，依赖内核按 R_RISCV_RELATIVE 重定位
+static WORDS: [&str; 3] = ["hello", "from", "static-pie"];
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    let pc = main as usize;
+    if pc < ELF_DYN_BASE {
+        println!("pie_hello: loaded at {:#x}, not as a PIE", pc);
+        return -1;
+    }
+    println!("{} {} {}", WORDS[0], WORDS[1], WORDS[2]);
+    42
+}
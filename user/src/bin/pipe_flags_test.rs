@@ -0,0 +1,185 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+extern crate alloc;
+
+use alloc::vec;
+use user_lib::*;
+
+fn new_pipe(flags: OpenFlags) -> (usize, usize) {
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe2(&mut pipe_fd, flags), 0);
+    (pipe_fd[0], pipe_fd[1])
+}
+
+// 非阻塞模式下空管道读、满管道写返回 EAGAIN；满时的长写入只写入能容纳的部分
+fn pipe_test_nonblock() {
+    let (read_end, write_end) = new_pipe(OpenFlags::NONBLOCK | OpenFlags::CLOEXEC);
+    assert_eq!(fcntl(read_end, F_GETFD, 0), FD_CLOEXEC as isize);
+    assert_eq!(
+        fcntl(write_end, F_GETFL, 0),
+        (OpenFlags::WRONLY | OpenFlags::NONBLOCK).bits() as isize
+    );
+    let mut buf = [0u8; 512];
+    assert_eq!(read(read_end, &mut buf), -EAGAIN);
+
+    assert_eq!(fcntl(write_end, F_SETPIPE_SZ, 4096), 4096);
+    let data = [b'x'; 3000];
+    assert_eq!(write(write_end, &data), 3000);
+    // 不超过 PIPE_BUF 的写入是原子的：空间不足时整体失败
+    assert_eq!(write(write_end, &data[..2000]), -EAGAIN);
+    assert_eq!(write(write_end, &vec![b'y'; 5000]), 1096);
+    assert_eq!(write(write_end, b"z"), -EAGAIN);
+
+    // 读取返回已有的数据，不等待填满缓冲区
+    let mut total = 0;
+    loop {
+        let len = read(read_end, &mut buf);
+        if len == -EAGAIN {
+            break;
+        }
+        assert!(len > 0);
+        total += len as usize;
+    }
+    assert_eq!(total, 4096);
+
+    // 清除 O_NONBLOCK 后读取重新阻塞，直到写端关闭返回 EOF
+    assert_eq!(fcntl(read_end, F_SETFL, 0), 0);
+    close(write_end);
+    assert_eq!(read(read_end, &mut buf), 0);
+    close(read_end);
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe2(&mut pipe_fd, OpenFlags::APPEND), -EINVAL);
+}
+
+// 数据超过新容量时不能缩小，调整后已缓存的数据保持不变
+fn pipe_test_resize() {
+    let (read_end, write_end) = new_pipe(OpenFlags::empty());
+    assert_eq!(fcntl(write_end, F_SETPIPE_SZ, 100), 4096);
+    assert_eq!(write(write_end, &[b'a'; 3000]), 3000);
+    assert_eq!(write(write_end, b"end"), 3);
+    assert_eq!(fcntl(read_end, F_SETPIPE_SZ, 8192), 8192);
+    assert_eq!(fcntl(read_end, F_SETPIPE_SZ, 10 << 20), -EPERM);
+    let mut buf = [0u8; 4096];
+    assert_eq!(read(read_end, &mut buf), 3003);
+    assert_eq!(&buf[3000..3003], b"end");
+    assert_eq!(write(write_end, &vec![b'b'; 6000]), 6000);
+    assert_eq!(fcntl(write_end, F_SETPIPE_SZ, 4096), -EBUSY);
+    close(read_end);
+    close(write_end);
+}
+
+// 读端全部关闭后写入返回 EPIPE；默认动作下 SIGPIPE 终止写者
+fn pipe_test_epipe() {
+    let (read_end, write_end) = new_pipe(OpenFlags::empty());
+    close(read_end);
+    let pid = fork();
+    if pid == 0 {
+        write(write_end, b"lost");
+        exit(0);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -SIGPIPE);
+
+    let ignore = SignalAction {
+        handler: SIG_IGN,
+        mask: SignalFlags::empty(),
+    };
+    let mut old = SignalAction::default();
+    assert_eq!(sigaction(SIGPIPE, Some(&ignore), Some(&mut old)), 0);
+    assert_eq!(write(write_end, b"lost"), -EPIPE);
+    assert_eq!(write(write_end, b""), 0);
+    assert_eq!(sigaction(SIGPIPE, Some(&old), None), 0);
+    close(write_end);
+}
+
+// 阻塞的写者在读端关闭时被唤醒并收到 EPIPE
+fn pipe_test_blocked_writer() {
+    let (read_end, write_end) = new_pipe(OpenFlags::empty());
+    let pid = fork();
+    if pid == 0 {
+        close(write_end);
+        sleep(20);
+        exit(0);
+    }
+    let ignore = SignalAction {
+        handler: SIG_IGN,
+        mask: SignalFlags::empty(),
+    };
+    let mut old = SignalAction::default();
+    assert_eq!(sigaction(SIGPIPE, Some(&ignore), Some(&mut old)), 0);
+    close(read_end);
+    assert_eq!(fcntl(write_end, F_SETPIPE_SZ, 4096), 4096);
+    let data = vec![b'x'; 8192];
+    // 子进程退出前写入 4096 字节后阻塞，读端随子进程退出全部关闭
+    assert_eq!(write(write_end, &data), 4096);
+    assert_eq!(write(write_end, &data), -EPIPE);
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(sigaction(SIGPIPE, Some(&old), None), 0);
+    close(write_end);
+}
+
+fn on_sigusr1() {
+    sigreturn();
+}
+
+// 阻塞在空管道上的读者可以被 SIGKILL 终止
+fn pipe_test_kill_blocked_reader() {
+    let (read_end, write_end) = new_pipe(OpenFlags::empty());
+    let pid = fork();
+    if pid == 0 {
+        let mut buf = [0u8; 16];
+        read(read_end, &mut buf);
+        exit(0);
+    }
+    sleep(20);
+    assert_eq!(kill(pid as usize, SIGKILL), 0);
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -SIGKILL);
+    close(read_end);
+    close(write_end);
+}
+
+// 捕捉的信号打断阻塞的读，read 返回 EINTR
+fn pipe_test_interrupted_reader() {
+    let (read_end, write_end) = new_pipe(OpenFlags::empty());
+    let pid = fork();
+    if pid == 0 {
+        let action = SignalAction {
+            handler: on_sigusr1 as usize,
+            mask: SignalFlags::empty(),
+        };
+        assert_eq!(sigaction(SIGUSR1, Some(&action), None), 0);
+        let mut buf = [0u8; 16];
+        exit(if read(read_end, &mut buf) == -EINTR {
+            0
+        } else {
+            1
+        });
+    }
+    sleep(20);
+    assert_eq!(kill(pid as usize, SIGUSR1), 0);
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    close(read_end);
+    close(write_end);
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    pipe_test_nonblock();
+    pipe_test_resize();
+    pipe_test_epipe();
+    pipe_test_blocked_writer();
+    pipe_test_kill_blocked_reader();
+    pipe_test_interrupted_reader();
+    println!("pipe_flags_test passed!");
+    0
+}
@@ -6,66 +6,82 @@ extern crate user_lib;
 
 extern crate alloc;
 
-use alloc::format;
-use user_lib::{close, fork, pipe, read, time, wait, write};
+use alloc::vec;
+use user_lib::{
+    F_GETPIPE_SZ, F_SETPIPE_SZ, close, exit, fcntl, fork, pipe, read, time, waitpid, write,
+};
 
-const LENGTH: usize = 3000;
-#[unsafe(no_mangle)]
-pub fn main() -> i32 {
-    // create pipes
-    // parent write to child
-    let mut down_pipe_fd = [0usize; 2];
-    // child write to parent
-    let mut up_pipe_fd = [0usize; 2];
-    pipe(&mut down_pipe_fd);
-    pipe(&mut up_pipe_fd);
-    let mut random_str = [0u8; LENGTH];
-    if fork() == 0 {
-        // close write end of down pipe
-        close(down_pipe_fd[1]);
-        // close read end of up pipe
-        close(up_pipe_fd[0]);
-        assert_eq!(read(down_pipe_fd[0], &mut random_str) as usize, LENGTH);
-        close(down_pipe_fd[0]);
-        let sum: usize = random_str.iter().map(|v| *v as usize).sum::<usize>();
-        println!("sum = {}(child)", sum);
-        let sum_str = format!("{}", sum);
-        write(up_pipe_fd[1], sum_str.as_bytes());
-        close(up_pipe_fd[1]);
-        println!("Child process exited!");
-        0
-    } else {
-        // close read end of down pipe
-        close(down_pipe_fd[0]);
-        // close write end of up pipe
-        close(up_pipe_fd[1]);
-        // generate a long random string
-        for ch in random_str.iter_mut() {
-            *ch = time() as u8;
+const TOTAL: usize = 1 << 20;
+const CHUNK: usize = 8192;
+const DEFAULT_PIPE_SIZE: usize = 65536;
+
+fn pattern(offset: usize) -> u8 {
+    (offset * 7 + offset / 251) as u8
+}
+
+// 父进程写入 TOTAL 字节，子进程边读边校验；返回耗时（毫秒）
+fn transfer(pipe_size: Option<usize>) -> usize {
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    let (read_end, write_end) = (pipe_fd[0], pipe_fd[1]);
+    assert_eq!(fcntl(read_end, F_GETPIPE_SZ, 0), DEFAULT_PIPE_SIZE as isize);
+    if let Some(size) = pipe_size {
+        assert_eq!(fcntl(write_end, F_SETPIPE_SZ, size), size as isize);
+        assert_eq!(fcntl(read_end, F_GETPIPE_SZ, 0), size as isize);
+    }
+    let pid = fork();
+    if pid == 0 {
+        close(write_end);
+        let mut buf = vec![0u8; CHUNK];
+        let mut received = 0;
+        loop {
+            let len = read(read_end, &mut buf);
+            assert!(len >= 0);
+            if len == 0 {
+                break;
+            }
+            for (i, byte) in buf[..len as usize].iter().enumerate() {
+                assert_eq!(*byte, pattern(received + i));
+            }
+            received += len as usize;
         }
-        // send it
-        assert_eq!(
-            write(down_pipe_fd[1], &random_str) as usize,
-            random_str.len()
-        );
-        // close write end of down pipe
-        close(down_pipe_fd[1]);
-        // calculate sum(parent)
-        let sum: usize = random_str.iter().map(|v| *v as usize).sum::<usize>();
-        println!("sum = {}(parent)", sum);
-        // recv sum(child)
-        let mut child_result = [0u8; 32];
-        let result_len = read(up_pipe_fd[0], &mut child_result) as usize;
-        close(up_pipe_fd[0]);
-        // check
-        assert_eq!(
-            sum,
-            str::parse::<usize>(core::str::from_utf8(&child_result[..result_len]).unwrap())
-                .unwrap()
-        );
-        let mut _unused: i32 = 0;
-        wait(&mut _unused);
-        println!("pipe_large_test passed!");
-        0
+        assert_eq!(received, TOTAL);
+        exit(0);
     }
+    close(read_end);
+    let start = time();
+    let mut chunk = vec![0u8; CHUNK];
+    let mut sent = 0;
+    while sent < TOTAL {
+        for (i, byte) in chunk.iter_mut().enumerate() {
+            *byte = pattern(sent + i);
+        }
+        assert_eq!(write(write_end, &chunk), CHUNK as isize);
+        sent += CHUNK;
+    }
+    close(write_end);
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    (time() - start) as usize
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    let small = transfer(Some(4096));
+    let large = transfer(None);
+    println!(
+        "{} KiB through a 4 KiB pipe: {} ms, through a {} KiB pipe: {} ms",
+        TOTAL / 1024,
+        small,
+        DEFAULT_PIPE_SIZE / 1024,
+        large
+    );
+    println!(
+        "throughput: {} KiB/s vs {} KiB/s",
+        TOTAL / 1024 * 1000 / small.max(1),
+        TOTAL / 1024 * 1000 / large.max(1)
+    );
+    println!("pipe_large_test passed!");
+    0
 }
@@ -0,0 +1,183 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::*;
+
+const FILE: &str = "poll_test_file\0";
+
+fn poll_one(fd: usize, events: i16, timeout_ms: isize) -> i16 {
+    let mut fds = [PollFd::new(fd, events)];
+    let ready = poll(&mut fds, timeout_ms);
+    assert!(ready >= 0);
+    fds[0].revents
+}
+
+// 读端有数据时可读，写端关闭后挂断
+fn poll_test_pipe() {
+    let mut pipe_fd = [0usize; 2];
+    pipe(&mut pipe_fd);
+    let (read_end, write_end) = (pipe_fd[0], pipe_fd[1]);
+    assert_eq!(poll_one(read_end, POLLIN, 0), 0);
+    assert_eq!(poll_one(write_end, POLLOUT, 0), POLLOUT);
+
+    assert_eq!(write(write_end, b"hi"), 2);
+    assert_eq!(poll_one(read_end, POLLIN, 0), POLLIN);
+    let mut buf = [0u8; 4];
+    assert_eq!(read(read_end, &mut buf), 2);
+    assert_eq!(poll_one(read_end, POLLIN, 0), 0);
+
+    close(write_end);
+    assert_eq!(poll_one(read_end, POLLIN, -1), POLLHUP);
+    close(read_end);
+}
+
+// 等待到超时，期间不占用 CPU 轮询
+fn poll_test_timeout() {
+    let mut pipe_fd = [0usize; 2];
+    pipe(&mut pipe_fd);
+    let start = time();
+    let mut fds = [PollFd::new(pipe_fd[0], POLLIN)];
+    assert_eq!(poll(&mut fds, 50), 0);
+    assert!(time() - start >= 50);
+    close(pipe_fd[0]);
+    close(pipe_fd[1]);
+}
+
+// 子进程稍后向第二个管道写入，父进程同时等待两个管道
+fn poll_test_wakeup() {
+    let (mut first, mut second) = ([0usize; 2], [0usize; 2]);
+    pipe(&mut first);
+    pipe(&mut second);
+    let pid = fork();
+    if pid == 0 {
+        sleep(20);
+        write(second[1], b"x");
+        exit(0);
+    }
+    let mut fds = [
+        PollFd::new(first[0], POLLIN),
+        PollFd::new(second[0], POLLIN),
+    ];
+    assert_eq!(poll(&mut fds, -1), 1);
+    assert_eq!(fds[0].revents, 0);
+    assert_eq!(fds[1].revents, POLLIN);
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    for fd in first.iter().chain(second.iter()) {
+        close(*fd);
+    }
+}
+
+// 普通文件总是就绪；无效描述符报告 POLLNVAL，负数描述符被忽略
+fn poll_test_file_and_invalid() {
+    let fd = open(FILE, OpenFlags::CREATE | OpenFlags::RDWR) as usize;
+    assert_eq!(poll_one(fd, POLLIN | POLLOUT, -1), POLLIN | POLLOUT);
+    close(fd);
+
+    let mut fds = [PollFd::new(fd, POLLIN), PollFd::new(0, POLLIN)];
+    fds[1].fd = -1;
+    assert_eq!(poll(&mut fds, -1), 1);
+    assert_eq!(fds[0].revents, POLLNVAL);
+    assert_eq!(fds[1].revents, 0);
+}
+
+// 规范模式下从端在整行到达后才可读，回显使主端可读
+fn poll_test_pty() {
+    let (mut master, mut slave) = (0, 0);
+    assert_eq!(openpty(&mut master, &mut slave), 0);
+    assert_eq!(poll_one(master, POLLIN | POLLOUT, 0), POLLOUT);
+    assert_eq!(poll_one(slave, POLLIN, 0), 0);
+
+    assert_eq!(write(master, b"ab"), 2);
+    assert_eq!(poll_one(slave, POLLIN, 0), 0);
+    assert_eq!(poll_one(master, POLLIN, 0), POLLIN);
+    assert_eq!(write(master, b"\n"), 1);
+    assert_eq!(poll_one(slave, POLLIN, 0), POLLIN);
+    let mut buf = [0u8; 16];
+    assert_eq!(read(slave, &mut buf), 3);
+
+    // 从端全部关闭后主端挂断
+    close(slave);
+    while poll_one(master, POLLIN, 0) & POLLIN != 0 && read(master, &mut buf) > 0 {}
+    assert_eq!(poll_one(master, POLLIN, 0) & POLLHUP, POLLHUP);
+    close(master);
+}
+
+fn poll_test_select() {
+    let mut pipe_fd = [0usize; 2];
+    pipe(&mut pipe_fd);
+    let (read_end, write_end) = (pipe_fd[0], pipe_fd[1]);
+    let nfds = read_end.max(write_end) + 1;
+
+    let mut readfds = FdSet::default();
+    readfds.insert(read_end);
+    let mut writefds = FdSet::default();
+    writefds.insert(write_end);
+    let timeout = TimeSpec::from_ms(0);
+    assert_eq!(
+        select(
+            nfds,
+            Some(&mut readfds),
+            Some(&mut writefds),
+            None,
+            Some(&timeout)
+        ),
+        1
+    );
+    assert!(!readfds.contains(read_end));
+    assert!(writefds.contains(write_end));
+
+    write(write_end, b"x");
+    let mut readfds = FdSet::default();
+    readfds.insert(read_end);
+    assert_eq!(select(nfds, Some(&mut readfds), None, None, None), 1);
+    assert!(readfds.contains(read_end));
+
+    close(write_end);
+    close(read_end);
+    let mut readfds = FdSet::default();
+    readfds.insert(read_end);
+    assert_eq!(select(nfds, Some(&mut readfds), None, None, None), -EBADF);
+}
+
+fn on_usr1() {
+    sigreturn();
+}
+
+// 待决信号被 ppoll 的临时掩码解除屏蔽时，等待被打断
+fn poll_test_sigmask() {
+    let action = SignalAction {
+        handler: on_usr1 as usize,
+        mask: SignalFlags::empty(),
+    };
+    assert_eq!(sigaction(SIGUSR1, Some(&action), None), 0);
+    sigprocmask(SignalFlags::SIGUSR1.bits(), None);
+    kill(pid() as usize, SIGUSR1);
+
+    let mut pipe_fd = [0usize; 2];
+    pipe(&mut pipe_fd);
+    let mut fds = [PollFd::new(pipe_fd[0], POLLIN)];
+    let timeout = TimeSpec::from_ms(10);
+    let blocked = SignalFlags::SIGUSR1.bits();
+    assert_eq!(ppoll(&mut fds, Some(&timeout), Some(&blocked)), 0);
+    assert_eq!(ppoll(&mut fds, Some(&timeout), Some(&0)), -EINTR);
+    sigprocmask(0, None);
+    close(pipe_fd[0]);
+    close(pipe_fd[1]);
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    poll_test_pipe();
+    poll_test_timeout();
+    poll_test_wakeup();
+    poll_test_file_and_invalid();
+    poll_test_pty();
+    poll_test_select();
+    poll_test_sigmask();
+    println!("poll_test passed!");
+    0
+}
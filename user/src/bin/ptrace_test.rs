@@ -0,0 +1,198 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::arch::asm;
+use core::hint::black_box;
+use user_lib::*;
+
+const EBREAK: usize = 0x0010_0073;
+const C_EBREAK: usize = 0x9002;
+
+// 父进程通过 PTRACE_POKEDATA 修改，子进程读取
+static mut POKED: usize = 0;
+
+#[inline(never)]
+fn bp_target(x: usize) -> usize {
+    x * 3 + 1
+}
+
+fn wait_stopped(child: usize, sig: i32) {
+    let mut status = 0;
+    assert_eq!(waitpid(child, &mut status), child as isize);
+    assert!(wifstopped(status), "status {:#x}", status);
+    assert_eq!(wstopsig(status), sig);
+}
+
+fn wait_exited(child: usize) -> i32 {
+    let mut status = 0;
+    assert_eq!(waitpid(child, &mut status), child as isize);
+    assert!(!wifstopped(status));
+    status
+}
+
+fn peek(child: usize, addr: usize) -> usize {
+    let mut word = 0usize;
+    assert_eq!(
+        ptrace(PTRACE_PEEKTEXT, child, addr, &mut word as *mut _ as usize),
+        0
+    );
+    word
+}
+
+fn get_regs(child: usize) -> [usize; PTRACE_NREGS] {
+    let mut regs = [0usize; PTRACE_NREGS];
+    assert_eq!(
+        ptrace(PTRACE_GETREGS, child, 0, regs.as_mut_ptr() as usize),
+        0
+    );
+    regs
+}
+
+fn inst_len(word: usize) -> usize {
+    if word & 0b11 == 0b11 { 4 } else { 2 }
+}
+
+// 未被跟踪的进程执行 ebreak 时被 SIGTRAP 终止
+fn ptrace_test_untraced() {
+    let child = fork();
+    if child == 0 {
+        unsafe { asm!("ebreak") };
+        exit(0);
+    }
+    assert_eq!(wait_exited(child as usize), -SIGTRAP);
+}
+
+// 停在程序中的 ebreak 上，修改寄存器与内存后继续
+fn ptrace_test_traceme() {
+    let child = fork();
+    if child == 0 {
+        assert_eq!(ptrace(PTRACE_TRACEME, 0, 0, 0), 0);
+        assert_eq!(ptrace(PTRACE_TRACEME, 0, 0, 0), -EPERM);
+        let mut a0 = 0x1234usize;
+        unsafe { asm!("ebreak", inout("a0") a0) };
+        exit((a0 + unsafe { (&raw const POKED).read_volatile() }) as i32);
+    }
+    let child = child as usize;
+    wait_stopped(child, SIGTRAP);
+    let mut regs = get_regs(child);
+    assert_eq!(regs[10], 0x1234);
+    let word = peek(child, regs[0]);
+    if inst_len(word) == 2 {
+        assert_eq!(word & 0xffff, C_EBREAK);
+    } else {
+        assert_eq!(word & 0xffff_ffff, EBREAK);
+    }
+    // 跳过 ebreak，否则继续执行时会再次停止
+    regs[0] += inst_len(word);
+    regs[10] = 40;
+    assert_eq!(ptrace(PTRACE_SETREGS, child, 0, regs.as_ptr() as usize), 0);
+    assert_eq!(
+        ptrace(PTRACE_POKEDATA, child, &raw const POKED as usize, 2),
+        0
+    );
+    assert_eq!(peek(child, &raw const POKED as usize), 2);
+    // 父进程自己的副本不受影响
+    assert_eq!(unsafe { (&raw const POKED).read_volatile() }, 0);
+    assert_eq!(ptrace(PTRACE_CONT, child, 0, 0), 0);
+    assert_eq!(wait_exited(child), 42);
+}
+
+// 在函数入口设置断点，命中后恢复原指令并单步执行
+fn ptrace_test_breakpoint() {
+    let child = fork();
+    if child == 0 {
+        assert_eq!(ptrace(PTRACE_TRACEME, 0, 0, 0), 0);
+        kill(pid() as usize, SIGSTOP);
+        exit(bp_target(black_box(13)) as i32);
+    }
+    let child = child as usize;
+    wait_stopped(child, SIGSTOP);
+
+    let mut word = 0usize;
+    assert_eq!(
+        ptrace(PTRACE_PEEKDATA, child, 0, &mut word as *mut _ as usize),
+        -EIO
+    );
+    assert_eq!(
+        ptrace(PTRACE_PEEKDATA, child, bp_target as usize, 0),
+        -EFAULT
+    );
+    assert_eq!(ptrace(PTRACE_CONT, child, 0, 1000), -EIO);
+    assert_eq!(ptrace(1000, child, 0, 0), -EIO);
+
+    let addr = bp_target as usize;
+    let orig = peek(child, addr);
+    let patched = if inst_len(orig) == 2 {
+        (orig & !0xffff) | C_EBREAK
+    } else {
+        (orig & !0xffff_ffff) | EBREAK
+    };
+    assert_eq!(ptrace(PTRACE_POKETEXT, child, addr, patched), 0);
+    // SIGSTOP 已被跟踪者截获，继续执行时不再投递
+    assert_eq!(ptrace(PTRACE_CONT, child, 0, 0), 0);
+    wait_stopped(child, SIGTRAP);
+    assert_eq!(get_regs(child)[0], addr);
+
+    assert_eq!(ptrace(PTRACE_POKETEXT, child, addr, orig), 0);
+    assert_eq!(ptrace(PTRACE_SINGLESTEP, child, 0, 0), 0);
+    wait_stopped(child, SIGTRAP);
+    assert_ne!(get_regs(child)[0], addr);
+    // 单步的临时断点已被移除
+    assert_eq!(peek(child, addr), orig);
+    assert_eq!(ptrace(PTRACE_CONT, child, 0, 0), 0);
+    assert_eq!(wait_exited(child), 40);
+}
+
+// exec 后在新程序的第一条指令前停止
+fn ptrace_test_exec() {
+    let child = fork();
+    if child == 0 {
+        assert_eq!(ptrace(PTRACE_TRACEME, 0, 0, 0), 0);
+        exec("hello_world\0", &[core::ptr::null::<u8>()]);
+        exit(-1);
+    }
+    let child = child as usize;
+    wait_stopped(child, SIGTRAP);
+    assert_eq!(ptrace(PTRACE_DETACH, child, 0, 0), 0);
+    assert_eq!(wait_exited(child), 0);
+}
+
+// 附加到运行中的子进程，再终止它
+fn ptrace_test_attach() {
+    let mut word = 0usize;
+    // initproc 不是本进程的子进程
+    assert_eq!(ptrace(PTRACE_ATTACH, 1, 0, 0), -EPERM);
+    assert_eq!(ptrace(PTRACE_ATTACH, 1_000_000, 0, 0), -ESRCH);
+    assert_eq!(
+        ptrace(PTRACE_PEEKDATA, 1, 0, &mut word as *mut _ as usize),
+        -ESRCH
+    );
+    let child = fork();
+    if child == 0 {
+        loop {
+            yield_();
+        }
+    }
+    let child = child as usize;
+    // 未被跟踪的子进程
+    assert_eq!(ptrace(PTRACE_CONT, child, 0, 0), -ESRCH);
+    assert_eq!(ptrace(PTRACE_ATTACH, child, 0, 0), 0);
+    assert_eq!(ptrace(PTRACE_ATTACH, child, 0, 0), -EPERM);
+    wait_stopped(child, SIGSTOP);
+    assert_eq!(ptrace(PTRACE_KILL, child, 0, 0), 0);
+    assert_eq!(wait_exited(child), -SIGKILL);
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    ptrace_test_untraced();
+    ptrace_test_traceme();
+    ptrace_test_breakpoint();
+    ptrace_test_exec();
+    ptrace_test_attach();
+    println!("ptrace_test passed!");
+    0
+}
@@ -0,0 +1,157 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::*;
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+// 从主端读取，直到输出中出现 needle 或从端全部关闭
+fn read_until(master: usize, output: &mut Vec<u8>, needle: &[u8]) -> bool {
+    let mut buf = [0u8; 128];
+    while !contains(output, needle) {
+        let len = read(master, &mut buf);
+        if len <= 0 {
+            return false;
+        }
+        output.extend_from_slice(&buf[..len as usize]);
+    }
+    true
+}
+
+// 主端写入的数据经过从端的行规程：回显到主端，整行到达后从端可读
+fn pty_test_line_discipline() {
+    let (mut master, mut slave) = (0, 0);
+    assert_eq!(openpty(&mut master, &mut slave), 0);
+    assert!(isatty(master));
+    assert!(isatty(slave));
+
+    assert_eq!(write(master, b"abc\x7fd\n"), 6);
+    let mut buf = [0u8; 16];
+    assert_eq!(read(slave, &mut buf), 4);
+    assert_eq!(&buf[..4], b"abd\n");
+    let mut echo = Vec::new();
+    assert!(read_until(master, &mut echo, b"d\r\n"));
+
+    assert_eq!(write(slave, b"out\n"), 4);
+    let mut out = Vec::new();
+    assert!(read_until(master, &mut out, b"out\r\n"));
+
+    // 主端关闭后从端挂断，读取立即返回 0
+    close(master);
+    assert_eq!(read(slave, &mut buf), 0);
+    close(slave);
+}
+
+// 读者跟不上时，放不进 MAX_INPUT 的整行被丢弃
+fn pty_test_input_limit() {
+    let (mut master, mut slave) = (0, 0);
+    assert_eq!(openpty(&mut master, &mut slave), 0);
+    for _ in 0..3000 {
+        assert_eq!(write(master, b"ab\n"), 3);
+    }
+    // 4096 字节最多容纳 1365 个完整的 "ab\n"
+    let mut buf = [0u8; 16];
+    for _ in 0..1365 {
+        assert_eq!(read(slave, &mut buf), 3);
+        assert_eq!(&buf[..3], b"ab\n");
+    }
+    assert_eq!(write(master, b"end\n"), 4);
+    assert_eq!(read(slave, &mut buf), 4);
+    assert_eq!(&buf[..4], b"end\n");
+    close(slave);
+    close(master);
+}
+
+// 主端不读取时从端输出最多缓冲 4096 字节，写满后非阻塞写者得到 EAGAIN、阻塞写者睡眠
+fn pty_test_output_limit() {
+    let (mut master, mut slave) = (0, 0);
+    assert_eq!(openpty(&mut master, &mut slave), 0);
+    assert_eq!(
+        fcntl(slave, F_SETFL, OpenFlags::NONBLOCK.bits() as usize),
+        0
+    );
+    let chunk = [b'x'; 1000];
+    let mut total = 0;
+    loop {
+        let len = write(slave, &chunk);
+        if len == -EAGAIN {
+            break;
+        }
+        assert!(len > 0);
+        total += len as usize;
+    }
+    assert_eq!(total, 4096);
+
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(fcntl(slave, F_SETFL, 0), 0);
+        exit(write(slave, &chunk[..100]) as i32);
+    }
+    let mut buf = [0u8; 512];
+    while total > 0 {
+        let len = read(master, &mut buf);
+        assert!(len > 0);
+        total -= len as usize;
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 100);
+    assert_eq!(read(master, &mut buf), 100);
+    close(slave);
+    close(master);
+}
+
+// 在从端上运行 user_shell，通过主端输入命令并检查输出
+fn pty_test_shell() {
+    let (mut master, mut slave) = (0, 0);
+    assert_eq!(openpty(&mut master, &mut slave), 0);
+    let pid = fork();
+    if pid == 0 {
+        close(master);
+        for fd in 0..3 {
+            close(fd);
+            assert_eq!(dup(slave), fd as isize);
+        }
+        close(slave);
+        exec("user_shell\0", &[core::ptr::null::<u8>()]);
+        exit(-1);
+    }
+    close(slave);
+
+    let mut output = Vec::new();
+    assert_eq!(write(master, b"hello_world\n"), 12);
+    assert!(read_until(
+        master,
+        &mut output,
+        b"Hello world from user mode program!"
+    ));
+    assert_eq!(write(master, b"exit 7\n"), 7);
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 7);
+
+    // 从端全部关闭后，读完剩余输出主端读取返回 0
+    let mut buf = [0u8; 128];
+    while read(master, &mut buf) > 0 {}
+    close(master);
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    pty_test_line_discipline();
+    pty_test_input_limit();
+    pty_test_output_limit();
+    pty_test_shell();
+    println!("pty_test passed!");
+    0
+}
@@ -0,0 +1,195 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::*;
+
+fn set_cur(resource: usize, cur: usize) {
+    let mut limit = RLimit::default();
+    assert_eq!(getrlimit(resource, &mut limit), 0);
+    limit.rlim_cur = cur;
+    assert_eq!(setrlimit(resource, &limit), 0);
+}
+
+fn wait_child(child: isize) -> i32 {
+    let mut exit_code = 0;
+    assert_eq!(waitpid(child as usize, &mut exit_code), child);
+    exit_code
+}
+
+fn rlimit_test_nofile() {
+    // fd 0..=2 已被标准输入输出占用，限制为 5 时只能再分配两个
+    set_cur(RLIMIT_NOFILE, 5);
+    assert!(dup(0) >= 0);
+    assert!(dup(0) >= 0);
+    if dup(0) >= 0 {
+        println!("dup beyond RLIMIT_NOFILE succeeded!");
+        exit(-1);
+    }
+}
+
+fn rlimit_test_invalid() {
+    let mut limit = RLimit::default();
+    assert_eq!(getrlimit(RLIMIT_NOFILE, &mut limit), 0);
+    let bad = RLimit {
+        rlim_cur: limit.rlim_max + 1,
+        rlim_max: limit.rlim_max,
+    };
+    if setrlimit(RLIMIT_NOFILE, &bad) >= 0 {
+        println!("soft limit above hard limit accepted!");
+        exit(-1);
+    }
+    let raise = RLimit {
+        rlim_cur: limit.rlim_cur,
+        rlim_max: limit.rlim_max + 1,
+    };
+    if setrlimit(RLIMIT_NOFILE, &raise) >= 0 {
+        println!("hard limit raised!");
+        exit(-1);
+    }
+}
+
+fn rlimit_test_inherit() {
+    set_cur(RLIMIT_NOFILE, 8);
+    let child = fork();
+    if child == 0 {
+        let mut limit = RLimit::default();
+        getrlimit(RLIMIT_NOFILE, &mut limit);
+        exit(if limit.rlim_cur == 8 { 0 } else { -1 });
+    }
+    if wait_child(child) != 0 {
+        println!("rlimit not inherited across fork!");
+        exit(-1);
+    }
+}
+
+fn rlimit_test_nproc() {
+    let limit = RLimit {
+        rlim_cur: 1,
+        rlim_max: 1,
+    };
+    assert_eq!(setrlimit(RLIMIT_NPROC, &limit), 0);
+    let child = fork();
+    if child == 0 {
+        exit(-1);
+    }
+    if child >= 0 {
+        println!("fork beyond RLIMIT_NPROC succeeded!");
+        exit(-1);
+    }
+}
+
+fn rlimit_test_fsize() {
+    let path = "rlimit_fsize\0";
+    let child = fork();
+    if child == 0 {
+        set_cur(RLIMIT_FSIZE, 10);
+        let fd = open(
+            path,
+            OpenFlags::CREATE | OpenFlags::WRONLY | OpenFlags::TRUNC,
+        );
+        assert!(fd > 0);
+        // 部分写入只返回较短的字节数，不投递信号
+        assert_eq!(write(fd as usize, &[b'x'; 20]), 10);
+        // 忽略 SIGXFSZ 时一个字节都写不进去的写入返回 EFBIG
+        let ignore = SignalAction {
+            handler: SIG_IGN,
+            mask: SignalFlags::empty(),
+        };
+        let mut old = SignalAction::default();
+        assert_eq!(sigaction(SIGXFSZ, Some(&ignore), Some(&mut old)), 0);
+        assert_eq!(write(fd as usize, b"x"), -EFBIG);
+        assert_eq!(sigaction(SIGXFSZ, Some(&old), None), 0);
+        write(fd as usize, b"x");
+        exit(0);
+    }
+    if wait_child(child) != -SIGXFSZ {
+        println!("SIGXFSZ not delivered!");
+        exit(-1);
+    }
+    let fd = open(path, OpenFlags::RDONLY);
+    assert!(fd > 0);
+    let mut buf = [0u8; 32];
+    let len = read(fd as usize, &mut buf);
+    close(fd as usize);
+    if len != 10 {
+        println!("file grew past RLIMIT_FSIZE: {} bytes", len);
+        exit(-1);
+    }
+}
+
+fn rlimit_test_cpu() {
+    let child = fork();
+    if child == 0 {
+        let limit = RLimit {
+            rlim_cur: 1,
+            rlim_max: 10,
+        };
+        assert_eq!(setrlimit(RLIMIT_CPU, &limit), 0);
+        loop {}
+    }
+    if wait_child(child) != -SIGXCPU {
+        println!("SIGXCPU not delivered!");
+        exit(-1);
+    }
+}
+
+fn rlimit_test_as() {
+    let child = fork();
+    if child == 0 {
+        set_cur(RLIMIT_AS, 4096);
+        let args: [*const u8; 2] = ["hello_world\0".as_ptr(), core::ptr::null::<u8>()];
+        exec("hello_world\0", &args);
+        // exec 失败时返回到这里，进程映像保持不变
+        exit(7);
+    }
+    if wait_child(child) != 7 {
+        println!("exec beyond RLIMIT_AS succeeded!");
+        exit(-1);
+    }
+}
+
+fn run(f: fn()) -> bool {
+    let pid = fork();
+    if pid == 0 {
+        f();
+        exit(0);
+    } else {
+        let exit_code = wait_child(pid);
+        if exit_code != 0 {
+            println!("FAILED!");
+        } else {
+            println!("OK!");
+        }
+        exit_code == 0
+    }
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    let tests: [(fn(), &str); 7] = [
+        (rlimit_test_nofile, "rlimit_test_nofile"),
+        (rlimit_test_invalid, "rlimit_test_invalid"),
+        (rlimit_test_inherit, "rlimit_test_inherit"),
+        (rlimit_test_nproc, "rlimit_test_nproc"),
+        (rlimit_test_fsize, "rlimit_test_fsize"),
+        (rlimit_test_cpu, "rlimit_test_cpu"),
+        (rlimit_test_as, "rlimit_test_as"),
+    ];
+    let mut fail_num = 0;
+    for test in tests {
+        println!("Testing {}", test.1);
+        if !run(test.0) {
+            fail_num += 1;
+        }
+    }
+    if fail_num == 0 {
+        println!("ALL TESTS PASSED");
+        0
+    } else {
+        println!("SOME TESTS FAILED");
+        -1
+    }
+}
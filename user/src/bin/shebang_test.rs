@@ -0,0 +1,89 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::*;
+
+const SCRIPT: &str = "shebang_script\0";
+const NESTED: &str = "shebang_nested\0";
+
+fn write_file(path: &str, content: &str) {
+    let fd = open(
+        path,
+        OpenFlags::CREATE | OpenFlags::WRONLY | OpenFlags::TRUNC,
+    );
+    assert!(fd > 0);
+    write(fd as usize, content.as_bytes());
+    close(fd as usize);
+}
+
+// 作为解释器运行：argv = [shebang_test, -interp, shebang_script, ...]
+fn interpreter(argc: usize, argv: &[&str]) -> i32 {
+    if argc != 5 || argv[2] != "shebang_script" {
+        return -1;
+    }
+    match (argv[3], argv[4]) {
+        ("a", "b") => 1,
+        ("shebang_nested", "a") => 2,
+        _ => -1,
+    }
+}
+
+fn run_script(path: &str, args: &[*const u8]) -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        exec(path, args);
+        exit(-100);
+    }
+    let mut exit_code = 0;
+    waitpid(pid as usize, &mut exit_code);
+    exit_code
+}
+
+fn expect_enoexec(path: &str, content: &str) {
+    write_file(path, content);
+    let args: [*const u8; 2] = [path.as_ptr(), core::ptr::null::<u8>()];
+    let ret = exec(path, &args);
+    if ret != -ENOEXEC {
+        println!("exec {} returned {}, expected ENOEXEC", path, ret);
+        exit(-1);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    if argc > 1 && argv[1] == "-interp" {
+        return interpreter(argc, argv);
+    }
+
+    write_file(SCRIPT, "#!shebang_test   -interp  \necho unused\n");
+    write_file(NESTED, "#!shebang_script\n");
+
+    let args: [*const u8; 4] = [
+        SCRIPT.as_ptr(),
+        "a\0".as_ptr(),
+        "b\0".as_ptr(),
+        core::ptr::null::<u8>(),
+    ];
+    let code = run_script(SCRIPT, &args);
+    if code != 1 {
+        println!("script exec failed: exit code {}", code);
+        return -1;
+    }
+
+    let args: [*const u8; 3] = [NESTED.as_ptr(), "a\0".as_ptr(), core::ptr::null::<u8>()];
+    let code = run_script(NESTED, &args);
+    if code != 2 {
+        println!("nested script exec failed: exit code {}", code);
+        return -1;
+    }
+
+    expect_enoexec("shebang_garbage\0", "hello world\n");
+    expect_enoexec("shebang_empty\0", "#!\n");
+    expect_enoexec("shebang_loop\0", "#!shebang_loop\n");
+
+    println!("shebang_test passed!");
+    0
+}
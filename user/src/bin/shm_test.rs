@@ -0,0 +1,151 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::*;
+
+const PAGE_SIZE: usize = 4096;
+
+fn stat(shmid: usize) -> ShmIdDs {
+    let mut ds = ShmIdDs::default();
+    assert_eq!(shmctl(shmid, IPC_STAT, Some(&mut ds)), 0);
+    ds
+}
+
+fn attach(shmid: usize, shmflg: usize, pages: usize) -> &'static mut [u32] {
+    let addr = shmat(shmid, 0, shmflg);
+    assert!(addr > 0);
+    unsafe { core::slice::from_raw_parts_mut(addr as *mut u32, pages * PAGE_SIZE / 4) }
+}
+
+// 父子进程通过 fork 继承的附加共享写入，附加计数随 fork 与退出变化
+fn shm_test_fork() {
+    let shmid = shmget(IPC_PRIVATE, 2 * PAGE_SIZE, 0o600);
+    assert!(shmid >= 0);
+    let shmid = shmid as usize;
+    let ds = stat(shmid);
+    assert_eq!(ds.shm_segsz, 2 * PAGE_SIZE);
+    assert_eq!(ds.shm_nattch, 0);
+    assert_eq!(ds.shm_perm.mode, 0o600);
+
+    let mem = attach(shmid, 0, 2);
+    assert!(mem.iter().all(|&x| x == 0));
+    mem[0] = 1;
+    assert_eq!(stat(shmid).shm_nattch, 1);
+
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(stat(shmid).shm_nattch, 2);
+        assert_eq!(mem[0], 1);
+        // 写到第二页，确认整个段都是共享的
+        for (i, x) in mem.iter_mut().enumerate() {
+            *x = i as u32;
+        }
+        exit(0);
+    }
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert!(mem.iter().enumerate().all(|(i, &x)| x == i as u32));
+    assert_eq!(stat(shmid).shm_nattch, 1);
+
+    assert_eq!(shmdt(mem.as_ptr() as usize), 0);
+    assert_eq!(shmdt(mem.as_ptr() as usize), -EINVAL);
+    assert_eq!(stat(shmid).shm_nattch, 0);
+    assert_eq!(shmctl(shmid, IPC_RMID, None), 0);
+    assert_eq!(shmat(shmid, 0, 0), -EINVAL);
+}
+
+// 按键查找同一个段，两次附加映射到同一组页帧
+fn shm_test_key() {
+    const KEY: i32 = 0x5348;
+    assert_eq!(shmget(KEY, PAGE_SIZE, 0o600), -ENOENT);
+    let shmid = shmget(KEY, PAGE_SIZE, IPC_CREAT | 0o600);
+    assert!(shmid >= 0);
+    assert_eq!(shmget(KEY, PAGE_SIZE, 0), shmid);
+    assert_eq!(
+        shmget(KEY, PAGE_SIZE, IPC_CREAT | IPC_EXCL | 0o600),
+        -EEXIST
+    );
+    assert_eq!(shmget(KEY, 2 * PAGE_SIZE, 0), -EINVAL);
+    let shmid = shmid as usize;
+
+    let pid = fork();
+    if pid == 0 {
+        let shmid = shmget(KEY, 0, 0);
+        assert!(shmid >= 0);
+        let mem = attach(shmid as usize, 0, 1);
+        mem[0] = 0xdead;
+        exit(0);
+    }
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+
+    let a = attach(shmid, 0, 1);
+    let b = attach(shmid, SHM_RDONLY, 1);
+    assert_ne!(a.as_ptr(), b.as_ptr());
+    assert_eq!(a[0], 0xdead);
+    a[1] = 42;
+    assert_eq!(b[1], 42);
+    assert_eq!(stat(shmid).shm_nattch, 2);
+
+    // 标记删除后键不再可见，但已有附加仍然有效，段在最后一次解除附加后消失
+    assert_eq!(shmctl(shmid, IPC_RMID, None), 0);
+    assert_eq!(shmget(KEY, PAGE_SIZE, 0), -ENOENT);
+    assert_eq!(b[0], 0xdead);
+    assert_eq!(shmdt(a.as_ptr() as usize), 0);
+    assert_eq!(stat(shmid).shm_nattch, 1);
+    assert_eq!(shmdt(b.as_ptr() as usize), 0);
+    assert_eq!(shmctl(shmid, IPC_STAT, None), -EINVAL);
+}
+
+// 只读附加的段不可写：写入触发 SIGSEGV
+fn shm_test_rdonly() {
+    let shmid = shmget(IPC_PRIVATE, PAGE_SIZE, 0o600);
+    assert!(shmid >= 0);
+    let shmid = shmid as usize;
+    let pid = fork();
+    if pid == 0 {
+        let mem = attach(shmid, SHM_RDONLY, 1);
+        unsafe { core::ptr::write_volatile(mem.as_mut_ptr(), 1) };
+        exit(0);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -11);
+    // 子进程退出时解除了附加
+    assert_eq!(stat(shmid).shm_nattch, 0);
+    assert_eq!(shmctl(shmid, IPC_RMID, None), 0);
+}
+
+// 指定附加地址：必须按页对齐（SHM_RND 时向下对齐），不能与已有区域重叠
+fn shm_test_addr() {
+    let shmid = shmget(IPC_PRIVATE, PAGE_SIZE, 0o600);
+    assert!(shmid >= 0);
+    let shmid = shmid as usize;
+    let addr = 0x30_0000_0000usize;
+    assert_eq!(shmat(shmid, addr + 1, 0), -EINVAL);
+    assert_eq!(shmat(shmid, addr + 1, SHM_RND), addr as isize);
+    assert_eq!(shmat(shmid, addr, 0), -EINVAL);
+    assert_eq!(shmdt(addr), 0);
+    assert_eq!(shmat(shmid, addr, 0), addr as isize);
+    assert_eq!(shmdt(addr), 0);
+    assert_eq!(shmctl(shmid, IPC_RMID, None), 0);
+
+    assert_eq!(shmget(IPC_PRIVATE, 0, 0o600), -EINVAL);
+    assert_eq!(shmat(shmid, 0, 0), -EINVAL);
+    assert_eq!(shmctl(shmid, 7, None), -EINVAL);
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    shm_test_fork();
+    shm_test_key();
+    shm_test_rdonly();
+    shm_test_addr();
+    println!("shm_test passed!");
+    0
+}
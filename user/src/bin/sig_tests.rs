@@ -3,22 +3,48 @@
 
 extern crate user_lib;
 
+use core::sync::atomic::{AtomicUsize, Ordering};
 use user_lib::*;
 
+static RT_RECEIVED: AtomicUsize = AtomicUsize::new(0);
+static RT_VALUE_SUM: AtomicUsize = AtomicUsize::new(0);
+
 fn func() {
     println!("func triggered");
     sigreturn();
 }
 
+fn rt_func(_sig: usize, value: usize) {
+    RT_RECEIVED.fetch_add(1, Ordering::SeqCst);
+    RT_VALUE_SUM.fetch_add(value, Ordering::SeqCst);
+    sigreturn();
+}
+
 fn user_sig_test_failsignum() {
     let mut new = SignalAction::default();
     let mut old = SignalAction::default();
     new.handler = func as usize;
-    if sigaction(50, Some(&new), Some(&mut old)) >= 0 {
+    if sigaction(SIGRTMAX + 1, Some(&new), Some(&mut old)) >= 0 {
         panic!("Wrong sigaction but successed!");
     }
 }
 
+// 旧屏蔽集合通过指针写回，最高位的 SIGRTMAX 不会被当成错误码
+fn user_sig_test_mask_rtmax() {
+    let rtmax = 1u64 << SIGRTMAX;
+    assert_eq!(sigprocmask(rtmax, None), 0);
+    let mut old = 0u64;
+    assert_eq!(sigprocmask(0, Some(&mut old)), 0);
+    assert_eq!(old, rtmax);
+    assert_eq!(sigprocmask(0, Some(&mut old)), 0);
+    assert_eq!(old, 0);
+}
+
+// 不在信号处理程序中调用 sigreturn 只返回错误
+fn user_sig_test_stray_sigreturn() {
+    assert_eq!(sigreturn(), -EINVAL);
+}
+
 fn user_sig_test_kill() {
     let mut new = SignalAction::default();
     let mut old = SignalAction::default();
@@ -73,11 +99,87 @@ fn user_sig_test_restore() {
 }
 
 fn kernel_sig_test_ignore() {
-    sigprocmask(SignalFlags::SIGSTOP.bits() as u32);
-    if kill(pid() as usize, SignalFlags::SIGSTOP.bits()) < 0 {
+    sigprocmask(SignalFlags::SIGUSR1.bits(), None);
+    if kill(pid() as usize, SIGUSR1) < 0 {
         println!("kill faild\n");
         exit(-1);
     }
+    // 已待决的标准信号再次发送时合并，不应失败
+    if kill(pid() as usize, SIGUSR1) < 0 {
+        println!("kill of pending signal failed\n");
+        exit(-1);
+    }
+}
+
+fn kernel_sig_test_default_ignore() {
+    // SIGCHLD 与 SIGWINCH 默认忽略，进程应继续运行
+    if kill(pid() as usize, SIGCHLD) < 0 || kill(pid() as usize, SIGWINCH) < 0 {
+        println!("kill failed!");
+        exit(-1);
+    }
+    yield_();
+}
+
+fn kernel_sig_test_sig_ign() {
+    let mut new = SignalAction::default();
+    let mut old = SignalAction::default();
+    new.handler = SIG_IGN;
+    if sigaction(SIGTERM, Some(&new), Some(&mut old)) < 0 {
+        panic!("Sigaction failed!");
+    }
+    if kill(pid() as usize, SIGTERM) < 0 {
+        println!("kill failed!");
+        exit(-1);
+    }
+    yield_();
+}
+
+fn kernel_sig_test_default_terminate() {
+    let child = fork();
+    if child == 0 {
+        loop {
+            yield_();
+        }
+    } else {
+        sleep(10);
+        if kill(child as usize, SIGTERM) < 0 {
+            println!("kill failed!");
+            exit(-1);
+        }
+        let mut exit_code = 0;
+        waitpid(child as usize, &mut exit_code);
+        if exit_code != -SIGTERM {
+            println!("unexpected exit code {}", exit_code);
+            exit(-1);
+        }
+    }
+}
+
+fn user_sig_test_rt_queue() {
+    let mut new = SignalAction::default();
+    let mut old = SignalAction::default();
+    new.handler = rt_func as usize;
+    if sigaction(SIGRTMIN, Some(&new), Some(&mut old)) < 0 {
+        panic!("Sigaction failed!");
+    }
+    // 屏蔽期间连续发送三次，实时信号应逐个排队而不是合并
+    sigprocmask(1 << SIGRTMIN, None);
+    for value in 1..=3 {
+        if sigqueue(pid() as usize, SIGRTMIN, value) < 0 {
+            println!("sigqueue failed!");
+            exit(-1);
+        }
+    }
+    sigprocmask(0, None);
+    yield_();
+    if RT_RECEIVED.load(Ordering::SeqCst) != 3 || RT_VALUE_SUM.load(Ordering::SeqCst) != 6 {
+        println!(
+            "rt signals lost: received {}, value sum {}",
+            RT_RECEIVED.load(Ordering::SeqCst),
+            RT_VALUE_SUM.load(Ordering::SeqCst)
+        );
+        exit(-1);
+    }
 }
 
 fn kernel_sig_test_stop_cont() {
@@ -162,7 +264,7 @@ fn run(f: fn()) -> bool {
 
 #[unsafe(no_mangle)]
 pub fn main() -> i32 {
-    let tests: [(fn(), &str); 8] = [
+    let tests: [(fn(), &str); 14] = [
         (user_sig_test_failsignum, "user_sig_test_failsignum"),
         (user_sig_test_kill, "user_sig_test_kill"),
         (
@@ -171,11 +273,26 @@ pub fn main() -> i32 {
         ),
         (user_sig_test_restore, "user_sig_test_restore"),
         (kernel_sig_test_ignore, "kernel_sig_test_ignore"),
+        (
+            kernel_sig_test_default_ignore,
+            "kernel_sig_test_default_ignore",
+        ),
+        (kernel_sig_test_sig_ign, "kernel_sig_test_sig_ign"),
+        (
+            kernel_sig_test_default_terminate,
+            "kernel_sig_test_default_terminate",
+        ),
+        (user_sig_test_rt_queue, "user_sig_test_rt_queue"),
         (kernel_sig_test_stop_cont, "kernel_sig_test_stop_cont"),
         (
             kernel_sig_test_failignorekill,
             "kernel_sig_test_failignorekill",
         ),
+        (user_sig_test_mask_rtmax, "user_sig_test_mask_rtmax"),
+        (
+            user_sig_test_stray_sigreturn,
+            "user_sig_test_stray_sigreturn",
+        ),
         (final_sig_test, "final_sig_test"),
     ];
     let mut fail_num = 0;
@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+extern crate user_lib;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use user_lib::{exec, exit, fork, strerror, trace, waitpid, write_stderr};
+
+// 用法：strace <program> [args...]，系统调用记录写到标准错误
+#[unsafe(no_mangle)]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    if argc < 2 {
+        write_stderr(b"usage: strace <program> [args...]\n");
+        return 1;
+    }
+    let args: Vec<String> = argv[1..].iter().map(|arg| format!("{}\0", arg)).collect();
+    let pid = fork();
+    if pid == 0 {
+        // 先跟踪自己再 exec，记录从 execve 开始
+        let ret = trace(0, Some(2));
+        if ret < 0 {
+            write_stderr(format!("strace: trace: {}\n", strerror(-ret)).as_bytes());
+            exit(1);
+        }
+        let mut args_addr: Vec<*const u8> = args.iter().map(|arg| arg.as_ptr()).collect();
+        args_addr.push(core::ptr::null::<u8>());
+        let ret = exec(args[0].as_str(), &args_addr);
+        write_stderr(format!("strace: {}: {}\n", argv[1], strerror(-ret)).as_bytes());
+        exit(127);
+    }
+    let mut exit_code = 0;
+    waitpid(pid as usize, &mut exit_code);
+    write_stderr(format!("+++ exited with {} +++\n", exit_code).as_bytes());
+    exit_code
+}
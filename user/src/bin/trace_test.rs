@@ -0,0 +1,120 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+#[macro_use]
+extern crate user_lib;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use user_lib::*;
+
+fn trace_test_errors() {
+    assert_eq!(trace(0, Some(100)), -EBADF);
+    assert_eq!(trace(0, Some(0)), -EBADF);
+    assert_eq!(trace(1_000_000, Some(1)), -ESRCH);
+    // initproc 不是本进程的子进程
+    assert_eq!(trace(1, Some(1)), -EPERM);
+    assert_eq!(trace(0, None), 0);
+}
+
+// 子进程跟踪自己，记录写入管道；返回子进程 PID、管道写端与读到的全部记录
+fn trace_test_child() -> (usize, usize, String) {
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    let child = fork();
+    if child == 0 {
+        close(pipe_fd[0]);
+        assert_eq!(trace(0, Some(pipe_fd[1])), 0);
+        // 跟踪持有文件的引用，关闭描述符后仍然输出
+        close(pipe_fd[1]);
+        pid();
+        close(100);
+        write(100, b"hi\n");
+        open("no_such_file\0", OpenFlags::RDONLY);
+        // fork 出的子进程不被跟踪
+        let grandchild = fork();
+        if grandchild == 0 {
+            close(101);
+            exit(0);
+        }
+        waitpid(grandchild as usize, &mut 0);
+        exit(3);
+    }
+    close(pipe_fd[1]);
+    let mut log = Vec::new();
+    let mut buf = [0u8; 256];
+    loop {
+        let len = read(pipe_fd[0], &mut buf);
+        assert!(len >= 0);
+        if len == 0 {
+            break;
+        }
+        log.extend_from_slice(&buf[..len as usize]);
+    }
+    close(pipe_fd[0]);
+    let mut exit_code = 0;
+    assert_eq!(waitpid(child as usize, &mut exit_code), child);
+    assert_eq!(exit_code, 3);
+    (child as usize, pipe_fd[1], String::from_utf8(log).unwrap())
+}
+
+// 跟踪者不读取或关闭了读端：被跟踪的进程既不阻塞也不收到 SIGPIPE，跟踪随之关闭
+fn trace_test_stalled_reader() {
+    for close_reader in [false, true] {
+        let mut pipe_fd = [0usize; 2];
+        assert_eq!(pipe(&mut pipe_fd), 0);
+        if close_reader {
+            close(pipe_fd[0]);
+        }
+        let child = fork();
+        if child == 0 {
+            assert_eq!(trace(0, Some(pipe_fd[1])), 0);
+            // 远超管道容量的记录
+            for _ in 0..8000 {
+                pid();
+            }
+            exit(5);
+        }
+        close(pipe_fd[1]);
+        let mut exit_code = 0;
+        assert_eq!(waitpid(child as usize, &mut exit_code), child);
+        assert_eq!(exit_code, 5);
+        if !close_reader {
+            // 管道写满前的记录完整保留
+            let expected = format!("[{}] pid() = {}\n", child, child);
+            let mut buf = [0u8; 64];
+            assert!(read(pipe_fd[0], &mut buf) >= expected.len() as isize);
+            assert!(buf.starts_with(expected.as_bytes()));
+            close(pipe_fd[0]);
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    trace_test_errors();
+    let (child, write_fd, log) = trace_test_child();
+    for expected in [
+        format!("[{}] close({}) = 0\n", child, write_fd),
+        format!("[{}] pid() = {}\n", child, child),
+        format!("[{}] close(100) = -9 (EBADF)\n", child),
+        format!("[{}] write(100, \"hi\\n\", 3) = -9 (EBADF)\n", child),
+        format!("[{}] open(\"no_such_file\", 0x0) = -2 (ENOENT)\n", child),
+        format!("[{}] exit(3) = ?\n", child),
+    ] {
+        assert!(
+            log.contains(&expected),
+            "missing {:?} in\n{}",
+            expected,
+            log
+        );
+    }
+    assert!(!log.contains("close(101)"));
+    assert!(!log.contains("trace("));
+    trace_test_stalled_reader();
+    println!("trace_test passed!");
+    0
+}
@@ -0,0 +1,80 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::*;
+
+const ESRCH: isize = 3;
+
+fn tty_test_attr() {
+    assert!(isatty(0));
+    assert!(isatty(1));
+    let mut orig = Termios::default();
+    assert_eq!(tcgetattr(0, &mut orig), 0);
+    assert!(orig.c_lflag & ICANON != 0);
+    assert!(orig.c_lflag & ECHO != 0);
+    assert!(orig.c_lflag & ISIG != 0);
+    assert_eq!(orig.c_cc[VINTR], 0x03);
+    assert_eq!(orig.c_cc[VEOF], 0x04);
+
+    let mut raw = orig;
+    raw.make_raw();
+    assert_eq!(tcsetattr(0, TCSANOW, &raw), 0);
+    let mut cur = Termios::default();
+    assert_eq!(tcgetattr(0, &mut cur), 0);
+    assert_eq!(cur.c_lflag & (ICANON | ECHO | ISIG), 0);
+
+    assert_eq!(tcsetattr(0, TCSADRAIN, &orig), 0);
+    assert_eq!(tcgetattr(0, &mut cur), 0);
+    assert_eq!(cur.c_lflag, orig.c_lflag);
+    assert_eq!(tcsetattr(0, 42, &orig), -EINVAL);
+}
+
+// 原始模式且 VMIN=0 时读取不阻塞
+fn tty_test_nonblocking_read() {
+    let mut orig = Termios::default();
+    assert_eq!(tcgetattr(0, &mut orig), 0);
+    let mut raw = orig;
+    raw.make_raw();
+    raw.c_cc[VMIN] = 0;
+    assert_eq!(tcsetattr(0, TCSANOW, &raw), 0);
+    let mut buf = [0u8; 16];
+    let len = read(0, &mut buf);
+    assert_eq!(tcsetattr(0, TCSANOW, &orig), 0);
+    assert!(len >= 0);
+}
+
+fn tty_test_notty() {
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    let mut termios = Termios::default();
+    assert_eq!(tcgetattr(pipe_fd[0], &mut termios), -ENOTTY);
+    assert!(!isatty(pipe_fd[1]));
+    close(pipe_fd[0]);
+    close(pipe_fd[1]);
+}
+
+fn tty_test_foreground() {
+    let old = tcgetpgrp(0);
+    assert!(old >= 0);
+    let me = pid() as usize;
+    assert_eq!(tcsetpgrp(0, me), 0);
+    assert_eq!(tcgetpgrp(0), me as isize);
+    assert_eq!(tcsetpgrp(0, 0), -EINVAL);
+    assert_eq!(tcsetpgrp(0, 99999), -ESRCH);
+    if old > 0 {
+        assert_eq!(tcsetpgrp(0, old as usize), 0);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    tty_test_attr();
+    tty_test_nonblocking_read();
+    tty_test_notty();
+    tty_test_foreground();
+    println!("tty_test passed!");
+    0
+}
@@ -0,0 +1,169 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::*;
+
+fn new_socket(type_: usize) -> usize {
+    let fd = socket(AF_UNIX, type_, 0);
+    assert!(fd >= 0);
+    fd as usize
+}
+
+// 流式套接字：连接、accept 得到对端地址、read/write 与 dup、对端关闭后 EOF 与 EPIPE
+fn unix_test_stream() {
+    let listener = new_socket(SOCK_STREAM);
+    let addr = SockAddrUn::new("sock_test_stream");
+    assert_eq!(bind(listener, &addr), 0);
+    assert_eq!(listen(listener, 4), 0);
+
+    let pid = fork();
+    if pid == 0 {
+        close(listener);
+        let client = new_socket(SOCK_STREAM);
+        assert_eq!(bind(client, &SockAddrUn::new("sock_test_client")), 0);
+        assert_eq!(connect(client, &addr), 0);
+        assert_eq!(connect(client, &addr), -EISCONN);
+        assert_eq!(write(client, b"hello"), 5);
+        let mut buf = [0u8; 16];
+        assert_eq!(read(client, &mut buf), 5);
+        assert_eq!(&buf[..5], b"world");
+        exit(0);
+    }
+
+    let mut peer = SockAddrUn::default();
+    let mut peer_len = core::mem::size_of::<SockAddrUn>() as u32;
+    let conn = accept(listener, Some((&mut peer, &mut peer_len)));
+    assert!(conn >= 0);
+    let conn = conn as usize;
+    assert_eq!(peer.path(peer_len), "sock_test_client");
+    let mut buf = [0u8; 16];
+    assert_eq!(recv(conn, &mut buf, 0), 5);
+    assert_eq!(&buf[..5], b"hello");
+    let dup_fd = dup(conn) as usize;
+    assert_eq!(send(dup_fd, b"world", 0), 5);
+    close(dup_fd);
+
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(read(conn, &mut buf), 0);
+    assert_eq!(send(conn, b"lost", MSG_NOSIGNAL), -EPIPE);
+    close(conn);
+    close(listener);
+}
+
+// 监听者关闭后地址可以重新绑定，连接被拒绝；非阻塞 accept 没有连接时返回 EAGAIN
+fn unix_test_listener() {
+    let listener = new_socket(SOCK_STREAM | SOCK_NONBLOCK);
+    let addr = SockAddrUn::new("sock_test_stream");
+    assert_eq!(bind(listener, &addr), 0);
+    let other = new_socket(SOCK_STREAM);
+    assert_eq!(bind(other, &addr), -EADDRINUSE);
+    let client = new_socket(SOCK_STREAM);
+    assert_eq!(connect(client, &addr), -ECONNREFUSED);
+    assert_eq!(listen(listener, 1), 0);
+    assert_eq!(accept::<SockAddrUn>(listener, None), -EAGAIN);
+
+    // 连接在 accept 之前建立，监听者关闭后客户端读到 EOF
+    assert_eq!(connect(client, &addr), 0);
+    assert_eq!(write(client, b"pending"), 7);
+    close(listener);
+    let mut buf = [0u8; 8];
+    assert_eq!(read(client, &mut buf), 0);
+    assert_eq!(connect(other, &addr), -ECONNREFUSED);
+    close(client);
+    close(other);
+}
+
+// 数据报套接字：保留消息边界、返回发送方地址、超出缓冲区的部分被丢弃
+fn unix_test_dgram() {
+    let a = new_socket(SOCK_DGRAM);
+    let b = new_socket(SOCK_DGRAM | SOCK_CLOEXEC);
+    assert_eq!(fcntl(b, F_GETFD, 0), FD_CLOEXEC as isize);
+    let addr_a = SockAddrUn::new("sock_test_dgram_a");
+    let addr_b = SockAddrUn::new("sock_test_dgram_b");
+    assert_eq!(bind(a, &addr_a), 0);
+    assert_eq!(bind(b, &addr_b), 0);
+    assert_eq!(send(a, b"no peer", 0), -ENOTCONN);
+    assert_eq!(sendto(a, b"one", 0, &addr_b), 3);
+    assert_eq!(sendto(a, b"three", 0, &addr_b), 5);
+
+    let mut from = SockAddrUn::default();
+    let mut from_len = core::mem::size_of::<SockAddrUn>() as u32;
+    let mut buf = [0u8; 16];
+    assert_eq!(
+        recvfrom(b, &mut buf, 0, Some((&mut from, &mut from_len))),
+        3
+    );
+    assert_eq!(&buf[..3], b"one");
+    assert_eq!(from.path(from_len), "sock_test_dgram_a");
+    assert_eq!(recv(b, &mut buf[..2], 0), 2);
+    assert_eq!(&buf[..2], b"th");
+    assert_eq!(recv(b, &mut buf, MSG_DONTWAIT), -EAGAIN);
+
+    // connect 设置默认目的地址，之后可以直接 write；匿名发送方的地址只有地址族
+    assert_eq!(connect(b, &addr_a), 0);
+    assert_eq!(write(b, b"reply"), 5);
+    assert_eq!(read(a, &mut buf), 5);
+    assert_eq!(&buf[..5], b"reply");
+    let anon = new_socket(SOCK_DGRAM);
+    assert_eq!(sendto(anon, b"x", 0, &addr_a), 1);
+    from_len = core::mem::size_of::<SockAddrUn>() as u32;
+    assert_eq!(
+        recvfrom(a, &mut buf, 0, Some((&mut from, &mut from_len))),
+        1
+    );
+    assert_eq!(from_len, 2);
+    close(anon);
+
+    // 接收方关闭后发往它的数据报被拒绝
+    close(a);
+    assert_eq!(write(b, b"gone"), -ECONNREFUSED);
+    close(b);
+}
+
+// 错误：路径不存在、不是套接字、类型不匹配、对非套接字操作
+fn unix_test_errors() {
+    let stream = new_socket(SOCK_STREAM);
+    let dgram = new_socket(SOCK_DGRAM);
+    assert_eq!(
+        connect(stream, &SockAddrUn::new("sock_test_missing")),
+        -ENOENT
+    );
+    let file = open("sock_test_file\0", OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(file >= 0);
+    assert_eq!(send(file as usize, b"x", 0), -ENOTSOCK);
+    close(file as usize);
+    assert_eq!(
+        connect(stream, &SockAddrUn::new("sock_test_file")),
+        -ECONNREFUSED
+    );
+    assert_eq!(send(stream, b"x", 0), -ENOTCONN);
+    assert_eq!(listen(stream, 1), -EINVAL);
+    assert_eq!(listen(dgram, 1), -EOPNOTSUPP);
+
+    assert_eq!(bind(dgram, &SockAddrUn::new("sock_test_dgram_a")), 0);
+    assert_eq!(
+        connect(stream, &SockAddrUn::new("sock_test_dgram_a")),
+        -EPROTOTYPE
+    );
+    assert_eq!(open("sock_test_dgram_a\0", OpenFlags::RDONLY), -ENXIO);
+    assert_eq!(socket(AF_UNIX, 3, 0), -EINVAL);
+    assert_eq!(socket(AF_UNIX, SOCK_STREAM, 1), -93);
+    assert_eq!(socket(10, SOCK_STREAM, 0), -97);
+    close(stream);
+    close(dgram);
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> i32 {
+    unix_test_stream();
+    unix_test_listener();
+    unix_test_dgram();
+    unix_test_errors();
+    println!("unix_socket_test passed!");
+    0
+}
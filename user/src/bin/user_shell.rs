@@ -7,17 +7,13 @@ extern crate alloc;
 #[macro_use]
 extern crate user_lib;
 
-const LF: u8 = 0x0au8;
-const CR: u8 = 0x0du8;
-const DL: u8 = 0x7fu8;
-const BS: u8 = 0x08u8;
-
 use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
-use user_lib::console::getchar;
 use user_lib::{
-    OpenFlags, close, dup, exec, exit, fork, open, pid, pipe, read, time, waitpid, write, yield_,
+    ENOEXEC, F_SETFD, FD_CLOEXEC, OpenFlags, SIGINT, SIGQUIT, SIGTSTP, SignalAction, close, dup2,
+    environ, exec, exit, fcntl, fork, getenv, open, pid, pipe, read, setenv, sigaction, sigreturn,
+    tcsetpgrp, time, waitpid, write, yield_,
 };
 
 // ANSI 颜色常量
@@ -134,6 +130,10 @@ fn execute_builtin_command(args: &[String]) -> bool {
             builtin_programs();
             true
         }
+        "export" => {
+            builtin_export(args);
+            true
+        }
         _ => false,
     }
 }
@@ -185,6 +185,10 @@ fn builtin_help() {
         "  {}   - List available programs",
         colored("programs", C_GREEN)
     );
+    println!(
+        "  {} - Set environment variable",
+        colored("export K=V", C_GREEN)
+    );
 
     println!(
         "\n{}:",
@@ -228,6 +232,15 @@ fn builtin_help() {
         colored("cmd > file", C_BLUE)
     );
     println!("  {}    - Input redirection", colored("cmd < file", C_BLUE));
+    println!("  {}   - Error redirection", colored("cmd 2> file", C_BLUE));
+    println!(
+        "  {}   - Merge stderr into stdout",
+        colored("cmd 2>&1", C_BLUE)
+    );
+    println!(
+        "  {}      - Expand environment variable",
+        colored("$NAME", C_BLUE)
+    );
 
     println!("\n{}:", colored("Hotkeys", &format!("{}", C_BOLD)));
     println!(
@@ -253,6 +266,23 @@ fn builtin_exit(args: &[String]) {
     exit(code);
 }
 
+/// export 命令 - 设置或列出环境变量
+fn builtin_export(args: &[String]) {
+    if args.len() == 1 {
+        for var in environ() {
+            println!("export {}", var);
+        }
+        return;
+    }
+    for arg in args[1..].iter() {
+        let arg = arg.trim_end_matches('\0');
+        match arg.split_once('=') {
+            Some((name, value)) if setenv(name, value, true) == 0 => {}
+            _ => eprintln_error(&format!("export: invalid assignment: {}", arg)),
+        }
+    }
+}
+
 /// pwd 命令 - 显示当前工作目录
 fn builtin_pwd() {
     // 由于我们的文件系统比较简单，暂时只显示根目录
@@ -603,7 +633,7 @@ fn builtin_programs() {
     println!("\n{}:", colored("Built-in Commands", C_GREEN));
     let builtins = [
         "help", "exit", "pwd", "echo", "clear", "history", "ps", "time", "sleep", "test",
-        "version", "ls", "programs",
+        "version", "ls", "programs", "export",
     ];
 
     for (i, cmd) in builtins.iter().enumerate() {
@@ -688,61 +718,157 @@ fn builtin_programs() {
 }
 
 #[derive(Debug)]
+/// 重定向，按在命令行中出现的顺序依次执行
+enum Redirect {
+    /// `[n]< file`，`n` 默认为 0
+    Input(usize, String),
+    /// `[n]> file`，`n` 默认为 1
+    Output(usize, String),
+    /// `[n]>&m`，`n` 默认为 1
+    Dup(usize, usize),
+}
+
+impl Redirect {
+    /// 重定向的目标描述符
+    fn fd(&self) -> usize {
+        match self {
+            Redirect::Input(fd, _) | Redirect::Output(fd, _) | Redirect::Dup(fd, _) => *fd,
+        }
+    }
+
+    /// 在子进程中执行重定向
+    fn apply(&self) -> Result<(), String> {
+        match self {
+            Redirect::Input(fd, path) | Redirect::Output(fd, path) => {
+                let flags = if matches!(self, Redirect::Input(..)) {
+                    OpenFlags::RDONLY
+                } else {
+                    OpenFlags::CREATE | OpenFlags::WRONLY | OpenFlags::TRUNC
+                };
+                let file = open(path.as_str(), flags);
+                if file < 0 {
+                    return Err(format!(
+                        "when opening file: {}",
+                        path.trim_end_matches('\0')
+                    ));
+                }
+                let file = file as usize;
+                if file != *fd {
+                    dup2(file, *fd);
+                    close(file);
+                }
+                Ok(())
+            }
+            Redirect::Dup(fd, target) => {
+                if dup2(*target, *fd) < 0 {
+                    return Err(format!("bad file descriptor: {}", target));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 识别重定向记号，返回 `(n, 操作符)`；`n` 省略时为 `None`
+fn split_redirect(token: &str) -> Option<(Option<usize>, &str)> {
+    let digits = token.len() - token.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let (fd, op) = token.split_at(digits);
+    if !op.starts_with('<') && !op.starts_with('>') {
+        return None;
+    }
+    Some((fd.parse().ok(), op))
+}
+
 struct ProcessArguments {
-    input: String,
-    output: String,
+    redirects: Vec<Redirect>,
     args_copy: Vec<String>,
     args_addr: Vec<*const u8>,
 }
 
-impl ProcessArguments {
-    pub fn new(command: &str) -> Self {
-        let args: Vec<_> = command.split(' ').collect();
-        let mut args_copy: Vec<String> = args
-            .iter()
-            .filter(|&arg| !arg.is_empty())
-            .map(|&arg| {
-                let mut string = String::new();
-                string.push_str(arg);
-                string.push('\0');
-                string
-            })
-            .collect();
-
-        // redirect input
-        let mut input = String::new();
-        if let Some((idx, _)) = args_copy
-            .iter()
-            .enumerate()
-            .find(|(_, arg)| arg.as_str() == "<\0")
+/// 展开参数中的 `$NAME` 环境变量引用，未定义的变量展开为空
+fn expand_vars(arg: &str) -> String {
+    let mut expanded = String::new();
+    let mut chars = arg.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$'
+            || !chars
+                .peek()
+                .is_some_and(|c| c.is_ascii_alphabetic() || *c == '_')
         {
-            input = args_copy[idx + 1].clone();
-            args_copy.drain(idx..=idx + 1);
+            expanded.push(c);
+            continue;
+        }
+        let mut name = String::new();
+        while let Some(&c) = chars.peek() {
+            if !c.is_ascii_alphanumeric() && c != '_' {
+                break;
+            }
+            name.push(c);
+            chars.next();
+        }
+        if let Some(value) = getenv(&name) {
+            expanded.push_str(&value);
         }
+    }
+    expanded
+}
 
-        // redirect output
-        let mut output = String::new();
-        if let Some((idx, _)) = args_copy
-            .iter()
-            .enumerate()
-            .find(|(_, arg)| arg.as_str() == ">\0")
-        {
-            output = args_copy[idx + 1].clone();
-            args_copy.drain(idx..=idx + 1);
+impl ProcessArguments {
+    pub fn new(command: &str) -> Result<Self, String> {
+        let mut tokens = command
+            .split(' ')
+            .map(expand_vars)
+            .filter(|arg| !arg.is_empty());
+        let mut args_copy: Vec<String> = Vec::new();
+        let mut redirects = Vec::new();
+        while let Some(token) = tokens.next() {
+            let Some((fd, op)) = split_redirect(&token) else {
+                args_copy.push(token + "\0");
+                continue;
+            };
+            let redirect = if let Some(target) = op.strip_prefix(">&") {
+                let target = target
+                    .parse()
+                    .map_err(|_| format!("bad redirection: {}", token))?;
+                Redirect::Dup(fd.unwrap_or(1), target)
+            } else {
+                let Some(mut path) = tokens.next() else {
+                    return Err(format!("missing file name after {}", token));
+                };
+                path.push('\0');
+                match op {
+                    "<" => Redirect::Input(fd.unwrap_or(0), path),
+                    ">" => Redirect::Output(fd.unwrap_or(1), path),
+                    _ => return Err(format!("bad redirection: {}", token)),
+                }
+            };
+            redirects.push(redirect);
+        }
+        if args_copy.is_empty() {
+            return Err(String::from("empty command"));
         }
 
         let mut args_addr: Vec<*const u8> = args_copy.iter().map(|arg| arg.as_ptr()).collect();
         args_addr.push(core::ptr::null::<u8>());
 
-        Self {
-            input,
-            output,
+        Ok(Self {
+            redirects,
             args_copy,
             args_addr,
-        }
+        })
+    }
+
+    /// 是否重定向了描述符 `fd`
+    fn redirects(&self, fd: usize) -> bool {
+        self.redirects.iter().any(|redirect| redirect.fd() == fd)
     }
 }
 
+// 终端信号只打断当前的 read，read 返回 EINTR
+fn on_terminal_signal() {
+    sigreturn();
+}
+
 #[unsafe(no_mangle)]
 pub fn main() -> i32 {
     // 打印NimlothOS Logo
@@ -761,158 +887,136 @@ pub fn main() -> i32 {
     println_info("Use Ctrl+A then X to exit QEMU.");
     println_success("Ready to serve! Try 'test system' to check system status.");
     println!("");
+    // 终端信号发给前台进程及其后代；shell 自身捕捉它们以免被终止，
+    // 捕捉的处理函数在 exec 后恢复为默认动作，子进程不受影响
+    let mut action = SignalAction::default();
+    action.handler = on_terminal_signal as usize;
+    for signum in [SIGINT, SIGQUIT, SIGTSTP] {
+        sigaction(signum, Some(&action), None);
+    }
+    tcsetpgrp(0, pid() as usize);
+
     let mut line: String = String::new();
+    let mut buf = [0u8; 256];
     print_prompt();
     loop {
-        let c = getchar();
-        match c {
-            LF | CR => {
-                println!("");
-                if !line.is_empty() {
-                    let splited: Vec<_> = line.as_str().split('|').collect();
-                    let process_arguments_list: Vec<_> = splited
-                        .iter()
-                        .map(|&cmd| ProcessArguments::new(cmd))
-                        .collect();
-                    let mut valid = true;
-                    for (i, process_args) in process_arguments_list.iter().enumerate() {
-                        if i == 0 {
-                            if !process_args.output.is_empty() {
-                                valid = false;
-                            }
-                        } else if i == process_arguments_list.len() - 1 {
-                            if !process_args.input.is_empty() {
-                                valid = false;
-                            }
-                        } else if !process_args.output.is_empty() || !process_args.input.is_empty()
-                        {
-                            valid = false;
-                        }
+        let len = read(0, &mut buf);
+        if len == 0 {
+            // 行首 ^D 或终端挂断：与 exit 命令相同
+            println!("exit");
+            exit(0);
+        }
+        if len < 0 {
+            // 被终端信号打断（EINTR）：放弃当前输入，重新提示
+            line.clear();
+            println!("");
+            print_prompt();
+            continue;
+        }
+        line.push_str(core::str::from_utf8(&buf[..len as usize]).unwrap_or(""));
+        if !line.ends_with('\n') {
+            continue;
+        }
+        line.pop();
+        if !line.is_empty() {
+            let splited: Vec<_> = line.as_str().split('|').collect();
+            let process_arguments_list: Vec<_> = match splited
+                .iter()
+                .map(|&cmd| ProcessArguments::new(cmd))
+                .collect::<Result<_, _>>()
+            {
+                Ok(list) => list,
+                Err(msg) => {
+                    eprintln_error(&msg);
+                    line.clear();
+                    print_prompt();
+                    continue;
+                }
+            };
+            // 管道中间的命令不能再重定向与管道相连的描述符
+            let last = process_arguments_list.len() - 1;
+            let valid = process_arguments_list
+                .iter()
+                .enumerate()
+                .all(|(i, process_args)| {
+                    (i == 0 || !process_args.redirects(0))
+                        && (i == last || !process_args.redirects(1))
+                });
+            if !valid {
+                eprintln_error("Invalid command: Inputs/Outputs cannot be correctly binded!");
+            } else {
+                // 检查是否为单个内置命令（不支持管道中的内置命令）
+                if process_arguments_list.len() == 1 {
+                    let args_copy = &process_arguments_list[0].args_copy;
+                    if execute_builtin_command(args_copy) {
+                        line.clear();
+                        print_prompt();
+                        continue;
                     }
-                    if process_arguments_list.len() == 1 {
-                        valid = true;
+                }
+                // create pipes; exec closes the inherited pipe ends in every child
+                let mut pipes_fd: Vec<[usize; 2]> = Vec::new();
+                for _ in 0..last {
+                    let mut pipe_fd = [0usize; 2];
+                    pipe(&mut pipe_fd);
+                    for fd in pipe_fd {
+                        fcntl(fd, F_SETFD, FD_CLOEXEC);
                     }
-                    if !valid {
-                        eprintln_error(
-                            "Invalid command: Inputs/Outputs cannot be correctly binded!",
-                        );
-                    } else {
-                        // 检查是否为单个内置命令（不支持管道中的内置命令）
-                        if process_arguments_list.len() == 1 {
-                            let args_copy = &process_arguments_list[0].args_copy;
-                            if execute_builtin_command(args_copy) {
-                                line.clear();
-                                print_prompt();
-                                continue;
-                            }
+                    pipes_fd.push(pipe_fd);
+                }
+                let mut children: Vec<_> = Vec::new();
+                for (i, process_argument) in process_arguments_list.iter().enumerate() {
+                    let pid = fork();
+                    if pid == 0 {
+                        let args_copy = &process_argument.args_copy;
+                        let args_addr = &process_argument.args_addr;
+                        // connect to the neighbouring processes first, then apply
+                        // redirections from left to right
+                        if i > 0 {
+                            dup2(pipes_fd[i - 1][0], 0);
                         }
-                        // create pipes
-                        let mut pipes_fd: Vec<[usize; 2]> = Vec::new();
-                        if !process_arguments_list.is_empty() {
-                            for _ in 0..process_arguments_list.len() - 1 {
-                                let mut pipe_fd = [0usize; 2];
-                                pipe(&mut pipe_fd);
-                                pipes_fd.push(pipe_fd);
-                            }
+                        if i < last {
+                            dup2(pipes_fd[i][1], 1);
                         }
-                        let mut children: Vec<_> = Vec::new();
-                        for (i, process_argument) in process_arguments_list.iter().enumerate() {
-                            let pid = fork();
-                            if pid == 0 {
-                                let input = &process_argument.input;
-                                let output = &process_argument.output;
-                                let args_copy = &process_argument.args_copy;
-                                let args_addr = &process_argument.args_addr;
-                                // redirect input
-                                if !input.is_empty() {
-                                    let input_fd = open(input.as_str(), OpenFlags::RDONLY);
-                                    if input_fd == -1 {
-                                        eprintln_error(&format!(
-                                            "when opening input file: {}",
-                                            input.trim_end_matches('\0')
-                                        ));
-                                        return -4;
-                                    }
-                                    let input_fd = input_fd as usize;
-                                    close(0);
-                                    assert_eq!(dup(input_fd), 0);
-                                    close(input_fd);
-                                }
-                                // redirect output
-                                if !output.is_empty() {
-                                    let output_fd = open(
-                                        output.as_str(),
-                                        OpenFlags::CREATE | OpenFlags::WRONLY,
-                                    );
-                                    if output_fd == -1 {
-                                        eprintln_error(&format!(
-                                            "when opening output file: {}",
-                                            output.trim_end_matches('\0')
-                                        ));
-                                        return -4;
-                                    }
-                                    let output_fd = output_fd as usize;
-                                    close(1);
-                                    assert_eq!(dup(output_fd), 1);
-                                    close(output_fd);
-                                }
-                                // receive input from the previous process
-                                if i > 0 {
-                                    close(0);
-                                    let read_end = pipes_fd.get(i - 1).unwrap()[0];
-                                    assert_eq!(dup(read_end), 0);
-                                }
-                                // send output to the next process
-                                if i < process_arguments_list.len() - 1 {
-                                    close(1);
-                                    let write_end = pipes_fd.get(i).unwrap()[1];
-                                    assert_eq!(dup(write_end), 1);
-                                }
-                                // close all pipe ends inherited from the parent process
-                                for pipe_fd in pipes_fd.iter() {
-                                    close(pipe_fd[0]);
-                                    close(pipe_fd[1]);
-                                }
-                                // execute new application
-                                if exec(args_copy[0].as_str(), args_addr.as_slice()) == -1 {
-                                    eprintln_error(&format!(
-                                        "when executing: {}",
-                                        args_copy[0].trim_end_matches('\0')
-                                    ));
-                                    return -4;
-                                }
-                                unreachable!();
-                            } else {
-                                children.push(pid);
+                        for redirect in process_argument.redirects.iter() {
+                            if let Err(msg) = redirect.apply() {
+                                eprintln_error(&msg);
+                                return -4;
                             }
                         }
-                        for pipe_fd in pipes_fd.iter() {
-                            close(pipe_fd[0]);
-                            close(pipe_fd[1]);
-                        }
-                        let mut exit_code: i32 = 0;
-                        for pid in children.into_iter() {
-                            let exit_pid = waitpid(pid as usize, &mut exit_code);
-                            assert_eq!(pid, exit_pid);
-                            //println!("Shell: Process {} exited with code {}", pid, exit_code);
+                        // execute new application
+                        let ret = exec(args_copy[0].as_str(), args_addr.as_slice());
+                        if ret < 0 {
+                            let reason = if ret == -ENOEXEC {
+                                "exec format error"
+                            } else {
+                                "when executing"
+                            };
+                            eprintln_error(&format!(
+                                "{}: {}",
+                                reason,
+                                args_copy[0].trim_end_matches('\0')
+                            ));
+                            return -4;
                         }
+                        unreachable!();
+                    } else {
+                        children.push(pid);
                     }
-                    line.clear();
                 }
-                print_prompt();
-            }
-            BS | DL => {
-                if !line.is_empty() {
-                    print!("{}", BS as char);
-                    print!(" ");
-                    print!("{}", BS as char);
-                    line.pop();
+                for pipe_fd in pipes_fd.iter() {
+                    close(pipe_fd[0]);
+                    close(pipe_fd[1]);
+                }
+                let mut exit_code: i32 = 0;
+                for pid in children.into_iter() {
+                    let exit_pid = waitpid(pid as usize, &mut exit_code);
+                    assert_eq!(pid, exit_pid);
+                    //println!("Shell: Process {} exited with code {}", pid, exit_code);
                 }
             }
-            _ => {
-                print!("{}", c as char);
-                line.push(c as char);
-            }
+            line.clear();
         }
+        print_prompt();
     }
 }
@@ -12,6 +12,9 @@ static SUCC_TESTS: &[(&str, &str, &str, &str, i32)] = &[
     ("filetest_simple\0", "\0", "\0", "\0", 0),
     ("cat\0", "filea\0", "\0", "\0", 0),
     ("cmdline_args\0", "1\0", "2\0", "3\0", 0),
+    ("elf_test\0", "\0", "\0", "\0", 0),
+    ("env_test\0", "\0", "\0", "\0", 0),
+    ("shebang_test\0", "\0", "\0", "\0", 0),
     ("exit\0", "\0", "\0", "\0", 0),
     ("fantastic_text\0", "\0", "\0", "\0", 0),
     ("forktest_simple\0", "\0", "\0", "\0", 0),
@@ -23,12 +26,30 @@ static SUCC_TESTS: &[(&str, &str, &str, &str, i32)] = &[
     ("matrix\0", "\0", "\0", "\0", 0),
     ("pipe_large_test\0", "\0", "\0", "\0", 0),
     ("pipetest\0", "\0", "\0", "\0", 0),
+    ("pipe_flags_test\0", "\0", "\0", "\0", 0),
+    ("fifo_test\0", "\0", "\0", "\0", 0),
+    ("unix_socket_test\0", "\0", "\0", "\0", 0),
+    ("inet_socket_test\0", "\0", "\0", "\0", 0),
+    ("shm_test\0", "\0", "\0", "\0", 0),
+    ("ipc_test\0", "\0", "\0", "\0", 0),
+    ("efault_test\0", "\0", "\0", "\0", 0),
+    ("errno_test\0", "\0", "\0", "\0", 0),
+    ("trace_test\0", "\0", "\0", "\0", 0),
+    ("ptrace_test\0", "\0", "\0", "\0", 0),
+    ("coredump_test\0", "\0", "\0", "\0", 0),
+    ("rlimit_test\0", "\0", "\0", "\0", 0),
     ("run_pipe_test\0", "\0", "\0", "\0", 0),
     ("sleep_simple\0", "\0", "\0", "\0", 0),
     ("sleep\0", "\0", "\0", "\0", 0),
     ("sig_simple\0", "\0", "\0", "\0", 0),
     ("sig_simple2\0", "\0", "\0", "\0", 0),
     ("sig_tests\0", "\0", "\0", "\0", 0),
+    ("tty_test\0", "\0", "\0", "\0", 0),
+    ("pty_test\0", "\0", "\0", "\0", 0),
+    ("fcntl_test\0", "\0", "\0", "\0", 0),
+    ("dup_test\0", "\0", "\0", "\0", 0),
+    ("poll_test\0", "\0", "\0", "\0", 0),
+    ("epoll_test\0", "\0", "\0", "\0", 0),
     ("yield\0", "\0", "\0", "\0", 0),
 ];
 
@@ -0,0 +1,125 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+// 用户程序单线程运行，环境变量表无需加锁
+struct Environ(UnsafeCell<Vec<String>>);
+
+unsafe impl Sync for Environ {}
+
+static ENVIRON: Environ = Environ(UnsafeCell::new(Vec::new()));
+
+// 内核放置的辅助向量起始地址（紧跟 envp 的 NULL 之后）
+static AUXV: AtomicUsize = AtomicUsize::new(0);
+
+pub const AT_NULL: usize = 0;
+pub const AT_PHDR: usize = 3;
+pub const AT_PHENT: usize = 4;
+pub const AT_PHNUM: usize = 5;
+pub const AT_PAGESZ: usize = 6;
+pub const AT_ENTRY: usize = 9;
+pub const AT_RANDOM: usize = 25;
+
+fn vars() -> &'static mut Vec<String> {
+    unsafe { &mut *ENVIRON.0.get() }
+}
+
+fn find(name: &str) -> Option<usize> {
+    vars()
+        .iter()
+        .position(|var| var.split_once('=').is_some_and(|(key, _)| key == name))
+}
+
+pub(crate) fn init(envp: usize) {
+    if envp == 0 {
+        return;
+    }
+    let mut i = 0;
+    loop {
+        let str_start =
+            unsafe { ((envp + i * core::mem::size_of::<usize>()) as *const usize).read_volatile() };
+        if str_start == 0 {
+            break;
+        }
+        let len = (0usize..)
+            .find(|i| unsafe { ((str_start + *i) as *const u8).read_volatile() == 0 })
+            .unwrap();
+        let bytes = unsafe { core::slice::from_raw_parts(str_start as *const u8, len) };
+        if let Ok(var) = core::str::from_utf8(bytes) {
+            vars().push(String::from(var));
+        }
+        i += 1;
+    }
+    AUXV.store(
+        envp + (i + 1) * core::mem::size_of::<usize>(),
+        Ordering::Relaxed,
+    );
+}
+
+pub fn getauxval(key: usize) -> usize {
+    let mut entry = AUXV.load(Ordering::Relaxed);
+    if entry == 0 {
+        return 0;
+    }
+    loop {
+        let (k, v) = unsafe {
+            (
+                (entry as *const usize).read_volatile(),
+                ((entry + core::mem::size_of::<usize>()) as *const usize).read_volatile(),
+            )
+        };
+        if k == AT_NULL {
+            return 0;
+        }
+        if k == key {
+            return v;
+        }
+        entry += 2 * core::mem::size_of::<usize>();
+    }
+}
+
+// 以 `\0` 结尾的 `NAME=VALUE` 字符串，供 exec 构造 envp
+pub(crate) fn environ_cstrings() -> Vec<String> {
+    vars()
+        .iter()
+        .map(|var| {
+            let mut cstring = var.clone();
+            cstring.push('\0');
+            cstring
+        })
+        .collect()
+}
+
+pub fn environ() -> Vec<String> {
+    vars().clone()
+}
+
+pub fn getenv(name: &str) -> Option<String> {
+    find(name).map(|idx| String::from(vars()[idx].split_once('=').unwrap().1))
+}
+
+pub fn setenv(name: &str, value: &str, overwrite: bool) -> isize {
+    if name.is_empty() || name.contains('=') || name.contains('\0') || value.contains('\0') {
+        return -1;
+    }
+    let mut var = String::from(name);
+    var.push('=');
+    var.push_str(value);
+    match find(name) {
+        Some(idx) if overwrite => vars()[idx] = var,
+        Some(_) => {}
+        None => vars().push(var),
+    }
+    0
+}
+
+pub fn unsetenv(name: &str) -> isize {
+    if name.is_empty() || name.contains('=') {
+        return -1;
+    }
+    if let Some(idx) = find(name) {
+        vars().remove(idx);
+    }
+    0
+}
@@ -0,0 +1,118 @@
+use core::fmt;
+
+// 错误码，编号与 Linux 一致；系统调用失败时返回其相反数
+pub const EPERM: isize = 1;
+pub const ENOENT: isize = 2;
+pub const ESRCH: isize = 3;
+pub const EINTR: isize = 4;
+pub const EIO: isize = 5;
+pub const ENXIO: isize = 6;
+pub const E2BIG: isize = 7;
+pub const ENOEXEC: isize = 8;
+pub const EBADF: isize = 9;
+pub const ECHILD: isize = 10;
+pub const EAGAIN: isize = 11;
+pub const ENOMEM: isize = 12;
+pub const EFAULT: isize = 14;
+pub const EBUSY: isize = 16;
+pub const EEXIST: isize = 17;
+pub const EINVAL: isize = 22;
+pub const EMFILE: isize = 24;
+pub const ENOTTY: isize = 25;
+pub const EFBIG: isize = 27;
+pub const ENOSPC: isize = 28;
+pub const EPIPE: isize = 32;
+pub const ENAMETOOLONG: isize = 36;
+pub const ENOSYS: isize = 38;
+pub const ENOTSOCK: isize = 88;
+pub const EDESTADDRREQ: isize = 89;
+pub const EMSGSIZE: isize = 90;
+pub const EPROTOTYPE: isize = 91;
+pub const EPROTONOSUPPORT: isize = 93;
+pub const EOPNOTSUPP: isize = 95;
+pub const EAFNOSUPPORT: isize = 97;
+pub const EADDRINUSE: isize = 98;
+pub const EADDRNOTAVAIL: isize = 99;
+pub const ENETUNREACH: isize = 101;
+pub const ECONNRESET: isize = 104;
+pub const EISCONN: isize = 106;
+pub const ENOTCONN: isize = 107;
+pub const ETIMEDOUT: isize = 110;
+pub const ECONNREFUSED: isize = 111;
+pub const EALREADY: isize = 114;
+pub const EINPROGRESS: isize = 115;
+
+// 错误码的描述，措辞与 glibc 的 strerror 相同
+pub fn strerror(errno: isize) -> &'static str {
+    match errno {
+        0 => "Success",
+        EPERM => "Operation not permitted",
+        ENOENT => "No such file or directory",
+        ESRCH => "No such process",
+        EINTR => "Interrupted system call",
+        EIO => "Input/output error",
+        ENXIO => "No such device or address",
+        E2BIG => "Argument list too long",
+        ENOEXEC => "Exec format error",
+        EBADF => "Bad file descriptor",
+        ECHILD => "No child processes",
+        EAGAIN => "Resource temporarily unavailable",
+        ENOMEM => "Cannot allocate memory",
+        EFAULT => "Bad address",
+        EBUSY => "Device or resource busy",
+        EEXIST => "File exists",
+        EINVAL => "Invalid argument",
+        EMFILE => "Too many open files",
+        ENOTTY => "Inappropriate ioctl for device",
+        EFBIG => "File too large",
+        ENOSPC => "No space left on device",
+        EPIPE => "Broken pipe",
+        ENAMETOOLONG => "File name too long",
+        ENOSYS => "Function not implemented",
+        ENOTSOCK => "Socket operation on non-socket",
+        EDESTADDRREQ => "Destination address required",
+        EMSGSIZE => "Message too long",
+        EPROTOTYPE => "Protocol wrong type for socket",
+        EPROTONOSUPPORT => "Protocol not supported",
+        EOPNOTSUPP => "Operation not supported",
+        EAFNOSUPPORT => "Address family not supported by protocol",
+        EADDRINUSE => "Address already in use",
+        EADDRNOTAVAIL => "Cannot assign requested address",
+        ENETUNREACH => "Network is unreachable",
+        ECONNRESET => "Connection reset by peer",
+        EISCONN => "Transport endpoint is already connected",
+        ENOTCONN => "Transport endpoint is not connected",
+        ETIMEDOUT => "Connection timed out",
+        ECONNREFUSED => "Connection refused",
+        EALREADY => "Operation already in progress",
+        EINPROGRESS => "Operation now in progress",
+        _ => "Unknown error",
+    }
+}
+
+// 系统调用失败时的错误码（正数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Errno(pub isize);
+
+impl Errno {
+    pub fn strerror(self) -> &'static str {
+        strerror(self.0)
+    }
+}
+
+impl fmt::Display for Errno {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.strerror())
+    }
+}
+
+pub type SysResult<T = usize> = Result<T, Errno>;
+
+// 把系统调用的原始返回值转换为 Result：负数为错误码，非负数为结果
+pub fn check(ret: isize) -> SysResult {
+    if ret < 0 {
+        Err(Errno(-ret))
+    } else {
+        Ok(ret as usize)
+    }
+}
@@ -0,0 +1,78 @@
+use super::{
+    sys_ipc_call, sys_ipc_close, sys_ipc_create, sys_ipc_recv, sys_ipc_reply, sys_ipc_send,
+};
+
+pub const IPC_MSG_WORDS: usize = 4;
+pub const IPC_MAX_PAGES: usize = 16;
+pub const IPC_PAGE_SIZE: usize = 4096;
+
+pub const IPC_NONBLOCK: usize = 1;
+
+// 按页转移的数据必须按页对齐
+#[repr(C, align(4096))]
+pub struct IpcPage(pub [u8; IPC_PAGE_SIZE]);
+
+impl Default for IpcPage {
+    fn default() -> Self {
+        Self([0; IPC_PAGE_SIZE])
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IpcBuffer {
+    pub addr: usize,
+    pub len: usize,
+    pub cap: usize,
+}
+
+impl IpcBuffer {
+    // 发送时 len 为数据字节数；接收时内核写回收到的字节数，cap 为整个 pages 的大小
+    pub fn new(pages: &mut [IpcPage], len: usize) -> Self {
+        Self {
+            addr: pages.as_mut_ptr() as usize,
+            len,
+            cap: pages.len() * IPC_PAGE_SIZE,
+        }
+    }
+}
+
+pub fn ipc_create() -> isize {
+    sys_ipc_create()
+}
+
+pub fn ipc_close(handle: usize) -> isize {
+    sys_ipc_close(handle)
+}
+
+// 发送后 buf 所在的页被移交给接收方，本地读到的是全零
+pub fn ipc_send(handle: usize, words: [usize; IPC_MSG_WORDS], buf: Option<&IpcBuffer>) -> isize {
+    let buf = buf.map_or(core::ptr::null(), |buf| buf as *const _);
+    sys_ipc_send(handle, &words, buf)
+}
+
+// 成功时返回发送方 pid
+pub fn ipc_recv(
+    handle: usize,
+    words: &mut [usize; IPC_MSG_WORDS],
+    buf: Option<&mut IpcBuffer>,
+    flags: usize,
+) -> isize {
+    let buf = buf.map_or(core::ptr::null_mut(), |buf| buf as *mut _);
+    sys_ipc_recv(handle, words, buf, flags)
+}
+
+// words 与 buf 发送请求，返回时换成应答
+pub fn ipc_call(
+    handle: usize,
+    words: &mut [usize; IPC_MSG_WORDS],
+    buf: Option<&mut IpcBuffer>,
+) -> isize {
+    let buf = buf.map_or(core::ptr::null_mut(), |buf| buf as *mut _);
+    sys_ipc_call(handle, words, buf)
+}
+
+pub fn ipc_reply(words: [usize; IPC_MSG_WORDS], buf: Option<&IpcBuffer>) -> isize {
+    let buf = buf.map_or(core::ptr::null(), |buf| buf as *const _);
+    sys_ipc_reply(&words, buf)
+}
@@ -9,8 +9,27 @@ use syscall::*;
 
 #[macro_use]
 pub mod console;
+mod env;
+mod errno;
+mod ipc;
 mod lang_items;
+mod poll;
+mod shm;
+mod socket;
 mod syscall;
+mod termios;
+
+pub use env::{
+    AT_ENTRY, AT_PAGESZ, AT_PHDR, AT_PHENT, AT_PHNUM, AT_RANDOM, environ, getauxval, getenv,
+    setenv, unsetenv,
+};
+pub use errno::*;
+pub use ipc::*;
+pub use poll::*;
+pub use shm::*;
+pub use socket::*;
+pub use syscall::syscall6;
+pub use termios::*;
 
 extern crate alloc;
 #[macro_use]
@@ -50,6 +69,34 @@ pub const SIGWINCH: i32 = 28;
 pub const SIGIO: i32 = 29;
 pub const SIGPWR: i32 = 30;
 pub const SIGSYS: i32 = 31;
+pub const SIGRTMIN: i32 = 32;
+pub const SIGRTMAX: i32 = 63;
+
+pub const SIG_DFL: usize = 0;
+pub const SIG_IGN: usize = 1;
+
+pub const RLIMIT_CPU: usize = 0;
+pub const RLIMIT_FSIZE: usize = 1;
+pub const RLIMIT_STACK: usize = 3;
+pub const RLIMIT_NPROC: usize = 6;
+pub const RLIMIT_NOFILE: usize = 7;
+pub const RLIMIT_AS: usize = 9;
+pub const RLIM_INFINITY: usize = usize::MAX;
+
+pub const PTRACE_TRACEME: usize = 0;
+pub const PTRACE_PEEKTEXT: usize = 1;
+pub const PTRACE_PEEKDATA: usize = 2;
+pub const PTRACE_POKETEXT: usize = 4;
+pub const PTRACE_POKEDATA: usize = 5;
+pub const PTRACE_CONT: usize = 7;
+pub const PTRACE_KILL: usize = 8;
+pub const PTRACE_SINGLESTEP: usize = 9;
+pub const PTRACE_GETREGS: usize = 12;
+pub const PTRACE_SETREGS: usize = 13;
+pub const PTRACE_ATTACH: usize = 16;
+pub const PTRACE_DETACH: usize = 17;
+// GETREGS/SETREGS 的寄存器个数：[0] 为 pc，[i] 为 xi
+pub const PTRACE_NREGS: usize = 32;
 
 static mut HEAP_SPACE: [u8; USER_HEAP_SIZE] = [0; USER_HEAP_SIZE];
 
@@ -63,11 +110,12 @@ pub fn handle_alloc_error(layout: core::alloc::Layout) -> ! {
 
 #[unsafe(no_mangle)]
 #[unsafe(link_section = ".text.entry")]
-pub extern "C" fn _start(argc: usize, argv: usize) -> ! {
+pub extern "C" fn _start(argc: usize, argv: usize, envp: usize) -> ! {
     unsafe {
         HEAP.lock()
             .init(addr_of_mut!(HEAP_SPACE) as usize, USER_HEAP_SIZE);
     }
+    env::init(envp);
     let mut v: Vec<&'static str> = Vec::new();
     for i in 0..argc {
         let str_start =
@@ -98,12 +146,15 @@ bitflags! {
         const RDWR = 1 << 1;
         const CREATE = 1 << 9;
         const TRUNC = 1 << 10;
+        const NONBLOCK = 1 << 11;
+        const APPEND = 1 << 12;
+        const CLOEXEC = 1 << 19;
     }
 }
 
 bitflags! {
     #[derive(Debug, Clone, Copy)]
-    pub struct SignalFlags: i32 {
+    pub struct SignalFlags: u64 {
         const SIGDEF = 1;
         const SIGHUP = 1 << 1;
         const SIGINT = 1 << 2;
@@ -136,6 +187,7 @@ bitflags! {
         const SIGIO = 1 << 29;
         const SIGPWR = 1 << 30;
         const SIGSYS = 1 << 31;
+        const SIGRT = 0xffff_ffff_0000_0000;
     }
 }
 
@@ -143,16 +195,63 @@ pub fn dup(fd: usize) -> isize {
     sys_dup(fd)
 }
 
+pub fn dup2(old_fd: usize, new_fd: usize) -> isize {
+    if old_fd == new_fd {
+        // dup3 拒绝相同的描述符，dup2 只需检查其有效性
+        return if fcntl(old_fd, F_GETFD, 0) < 0 {
+            -1
+        } else {
+            new_fd as isize
+        };
+    }
+    sys_dup3(old_fd, new_fd, 0)
+}
+
+pub fn dup3(old_fd: usize, new_fd: usize, flags: OpenFlags) -> isize {
+    sys_dup3(old_fd, new_fd, flags.bits())
+}
+
+pub const F_DUPFD: usize = 0;
+pub const F_GETFD: usize = 1;
+pub const F_SETFD: usize = 2;
+pub const F_GETFL: usize = 3;
+pub const F_SETFL: usize = 4;
+pub const F_DUPFD_CLOEXEC: usize = 1030;
+pub const F_SETPIPE_SZ: usize = 1031;
+pub const F_GETPIPE_SZ: usize = 1032;
+pub const FD_CLOEXEC: usize = 1;
+
+pub fn fcntl(fd: usize, cmd: usize, arg: usize) -> isize {
+    sys_fcntl(fd, cmd, arg)
+}
+
 pub fn open(path: &str, flags: OpenFlags) -> isize {
     sys_open(path, flags.bits())
 }
 
+pub const AT_FDCWD: isize = -100;
+pub const S_IFIFO: u32 = 0o010000;
+pub const S_IFREG: u32 = 0o100000;
+
+pub fn mknod(path: &str, mode: u32) -> isize {
+    sys_mknodat(AT_FDCWD, path, mode, 0)
+}
+
+pub fn mkfifo(path: &str) -> isize {
+    mknod(path, S_IFIFO | 0o644)
+}
+
 pub fn close(fd: usize) -> isize {
     sys_close(fd)
 }
 
 pub fn pipe(pipe_fd: &mut [usize]) -> isize {
-    sys_pipe(pipe_fd)
+    sys_pipe(pipe_fd, 0)
+}
+
+// 只接受 OpenFlags::NONBLOCK 与 OpenFlags::CLOEXEC
+pub fn pipe2(pipe_fd: &mut [usize], flags: OpenFlags) -> isize {
+    sys_pipe(pipe_fd, flags.bits())
 }
 
 pub fn read(fd: usize, buf: &mut [u8]) -> isize {
@@ -184,13 +283,20 @@ pub fn fork() -> isize {
 }
 
 pub fn exec(path: &str, args: &[*const u8]) -> isize {
-    sys_exec(path, args)
+    let envs = env::environ_cstrings();
+    let mut envp: Vec<*const u8> = envs.iter().map(|env| env.as_ptr()).collect();
+    envp.push(core::ptr::null::<u8>());
+    sys_execve(path, args, &envp)
+}
+
+pub fn execve(path: &str, args: &[*const u8], envs: &[*const u8]) -> isize {
+    sys_execve(path, args, envs)
 }
 
 pub fn wait(exit_code: &mut i32) -> isize {
     loop {
         match sys_waitpid(-1, exit_code as *mut _) {
-            -2 => {
+            ret if ret == -EAGAIN => {
                 yield_();
             }
             exit_pid => return exit_pid,
@@ -201,7 +307,7 @@ pub fn wait(exit_code: &mut i32) -> isize {
 pub fn waitpid(pid: usize, exit_code: &mut i32) -> isize {
     loop {
         match sys_waitpid(pid as isize, exit_code as *mut _) {
-            -2 => {
+            ret if ret == -EAGAIN => {
                 yield_();
             }
             exit_pid => return exit_pid,
@@ -269,10 +375,61 @@ pub fn sigaction(
     )
 }
 
-pub fn sigprocmask(mask: u32) -> isize {
-    sys_sigprocmask(mask)
+pub fn sigqueue(pid: usize, signum: i32, value: usize) -> isize {
+    sys_sigqueue(pid, signum, value)
+}
+
+pub fn sigprocmask(mask: u64, old_mask: Option<&mut u64>) -> isize {
+    sys_sigprocmask(mask, old_mask.map_or(core::ptr::null_mut(), |m| m))
 }
 
 pub fn sigreturn() -> isize {
     sys_sigreturn()
 }
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RLimit {
+    pub rlim_cur: usize,
+    pub rlim_max: usize,
+}
+
+pub fn prlimit(
+    pid: usize,
+    resource: usize,
+    new_limit: Option<&RLimit>,
+    old_limit: Option<&mut RLimit>,
+) -> isize {
+    sys_prlimit64(
+        pid,
+        resource,
+        new_limit.map_or(core::ptr::null(), |l| l),
+        old_limit.map_or(core::ptr::null_mut(), |l| l),
+    )
+}
+
+pub fn getrlimit(resource: usize, limit: &mut RLimit) -> isize {
+    prlimit(0, resource, None, Some(limit))
+}
+
+pub fn setrlimit(resource: usize, limit: &RLimit) -> isize {
+    prlimit(0, resource, Some(limit), None)
+}
+
+// 把 pid（0 为自己，或自己的子进程）的系统调用记录写到 fd；fd 为 None 时关闭跟踪
+pub fn trace(pid: usize, fd: Option<usize>) -> isize {
+    sys_trace(pid, fd.unwrap_or(usize::MAX))
+}
+
+pub fn ptrace(request: usize, pid: usize, addr: usize, data: usize) -> isize {
+    sys_ptrace(request, pid, addr, data)
+}
+
+// waitpid 报告跟踪停止时的状态为 (sig << 8) | 0x7f
+pub fn wifstopped(status: i32) -> bool {
+    status & 0xff == 0x7f && (1..=SIGRTMAX).contains(&(status >> 8))
+}
+
+pub fn wstopsig(status: i32) -> i32 {
+    status >> 8
+}
@@ -0,0 +1,176 @@
+use super::{
+    OpenFlags, sys_epoll_create1, sys_epoll_ctl, sys_epoll_pwait, sys_ppoll, sys_pselect6,
+};
+use core::ptr::{null, null_mut};
+
+pub const POLLIN: i16 = 0x001;
+pub const POLLPRI: i16 = 0x002;
+pub const POLLOUT: i16 = 0x004;
+pub const POLLERR: i16 = 0x008;
+pub const POLLHUP: i16 = 0x010;
+pub const POLLNVAL: i16 = 0x020;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PollFd {
+    pub fd: i32,
+    pub events: i16,
+    pub revents: i16,
+}
+
+impl PollFd {
+    pub fn new(fd: usize, events: i16) -> Self {
+        Self {
+            fd: fd as i32,
+            events,
+            revents: 0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeSpec {
+    pub tv_sec: usize,
+    pub tv_nsec: usize,
+}
+
+impl TimeSpec {
+    pub fn from_ms(ms: usize) -> Self {
+        Self {
+            tv_sec: ms / 1000,
+            tv_nsec: ms % 1000 * 1_000_000,
+        }
+    }
+}
+
+pub const FD_SETSIZE: usize = 1024;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FdSet {
+    bits: [u64; FD_SETSIZE / 64],
+}
+
+impl Default for FdSet {
+    fn default() -> Self {
+        Self {
+            bits: [0; FD_SETSIZE / 64],
+        }
+    }
+}
+
+impl FdSet {
+    pub fn insert(&mut self, fd: usize) {
+        self.bits[fd / 64] |= 1 << (fd % 64);
+    }
+
+    pub fn remove(&mut self, fd: usize) {
+        self.bits[fd / 64] &= !(1 << (fd % 64));
+    }
+
+    pub fn contains(&self, fd: usize) -> bool {
+        self.bits[fd / 64] & (1 << (fd % 64)) != 0
+    }
+}
+
+pub fn ppoll(fds: &mut [PollFd], timeout: Option<&TimeSpec>, sigmask: Option<&u64>) -> isize {
+    sys_ppoll(
+        fds,
+        timeout.map_or(null(), |timeout| timeout as *const _),
+        sigmask.map_or(null(), |sigmask| sigmask as *const _),
+    )
+}
+
+// timeout_ms 为负数时一直等待
+pub fn poll(fds: &mut [PollFd], timeout_ms: isize) -> isize {
+    let timeout = TimeSpec::from_ms(timeout_ms.max(0) as usize);
+    ppoll(fds, (timeout_ms >= 0).then_some(&timeout), None)
+}
+
+pub fn pselect(
+    nfds: usize,
+    readfds: Option<&mut FdSet>,
+    writefds: Option<&mut FdSet>,
+    exceptfds: Option<&mut FdSet>,
+    timeout: Option<&TimeSpec>,
+    sigmask: Option<&u64>,
+) -> isize {
+    let as_ptr = |set: Option<&mut FdSet>| set.map_or(null_mut(), |set| set as *mut _);
+    sys_pselect6(
+        nfds,
+        as_ptr(readfds),
+        as_ptr(writefds),
+        as_ptr(exceptfds),
+        timeout.map_or(null(), |timeout| timeout as *const _),
+        sigmask.map_or(null(), |sigmask| sigmask as *const _),
+    )
+}
+
+pub fn select(
+    nfds: usize,
+    readfds: Option<&mut FdSet>,
+    writefds: Option<&mut FdSet>,
+    exceptfds: Option<&mut FdSet>,
+    timeout: Option<&TimeSpec>,
+) -> isize {
+    pselect(nfds, readfds, writefds, exceptfds, timeout, None)
+}
+
+pub const EPOLLIN: u32 = POLLIN as u32;
+pub const EPOLLPRI: u32 = POLLPRI as u32;
+pub const EPOLLOUT: u32 = POLLOUT as u32;
+pub const EPOLLERR: u32 = POLLERR as u32;
+pub const EPOLLHUP: u32 = POLLHUP as u32;
+pub const EPOLLONESHOT: u32 = 1 << 30;
+pub const EPOLLET: u32 = 1 << 31;
+
+pub const EPOLL_CTL_ADD: usize = 1;
+pub const EPOLL_CTL_DEL: usize = 2;
+pub const EPOLL_CTL_MOD: usize = 3;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EpollEvent {
+    pub events: u32,
+    pub data: u64,
+}
+
+impl EpollEvent {
+    pub fn new(events: u32, data: u64) -> Self {
+        Self { events, data }
+    }
+}
+
+// 只接受 OpenFlags::CLOEXEC
+pub fn epoll_create1(flags: OpenFlags) -> isize {
+    sys_epoll_create1(flags.bits())
+}
+
+pub fn epoll_ctl(epfd: usize, op: usize, fd: usize, event: Option<&EpollEvent>) -> isize {
+    sys_epoll_ctl(
+        epfd,
+        op,
+        fd,
+        event.map_or(null(), |event| event as *const _),
+    )
+}
+
+pub fn epoll_pwait(
+    epfd: usize,
+    events: &mut [EpollEvent],
+    timeout_ms: isize,
+    sigmask: Option<&u64>,
+) -> isize {
+    sys_epoll_pwait(
+        epfd,
+        events,
+        timeout_ms,
+        sigmask.map_or(null(), |sigmask| sigmask as *const _),
+    )
+}
+
+// timeout_ms 为负数时一直等待
+pub fn epoll_wait(epfd: usize, events: &mut [EpollEvent], timeout_ms: isize) -> isize {
+    epoll_pwait(epfd, events, timeout_ms, None)
+}
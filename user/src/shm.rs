@@ -0,0 +1,60 @@
+use super::{sys_shmat, sys_shmctl, sys_shmdt, sys_shmget};
+
+pub const IPC_PRIVATE: i32 = 0;
+pub const IPC_CREAT: usize = 0o1000;
+pub const IPC_EXCL: usize = 0o2000;
+
+pub const IPC_RMID: usize = 0;
+pub const IPC_SET: usize = 1;
+pub const IPC_STAT: usize = 2;
+
+pub const SHM_RDONLY: usize = 0o10000;
+pub const SHM_RND: usize = 0o20000;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IpcPerm {
+    pub key: i32,
+    pub uid: u32,
+    pub gid: u32,
+    pub cuid: u32,
+    pub cgid: u32,
+    pub mode: u32,
+    pub seq: u16,
+    pub pad: u16,
+    pub unused: [usize; 2],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShmIdDs {
+    pub shm_perm: IpcPerm,
+    pub shm_segsz: usize,
+    pub shm_atime: usize,
+    pub shm_dtime: usize,
+    pub shm_ctime: usize,
+    pub shm_cpid: i32,
+    pub shm_lpid: i32,
+    pub shm_nattch: usize,
+    pub unused: [usize; 2],
+}
+
+pub fn shmget(key: i32, size: usize, shmflg: usize) -> isize {
+    sys_shmget(key, size, shmflg)
+}
+
+// 成功时返回附加地址，失败时返回负的错误码
+pub fn shmat(shmid: usize, shmaddr: usize, shmflg: usize) -> isize {
+    sys_shmat(shmid, shmaddr, shmflg)
+}
+
+pub fn shmdt(shmaddr: usize) -> isize {
+    sys_shmdt(shmaddr)
+}
+
+pub fn shmctl(shmid: usize, cmd: usize, buf: Option<&mut ShmIdDs>) -> isize {
+    match buf {
+        Some(buf) => sys_shmctl(shmid, cmd, buf as *mut _),
+        None => sys_shmctl(shmid, cmd, core::ptr::null_mut()),
+    }
+}
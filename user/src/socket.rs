@@ -0,0 +1,147 @@
+use super::{
+    OpenFlags, sys_accept, sys_bind, sys_connect, sys_listen, sys_recvfrom, sys_sendto, sys_socket,
+};
+use core::ptr::{null, null_mut};
+
+pub const AF_UNIX: usize = 1;
+pub const AF_INET: usize = 2;
+
+pub const SOCK_STREAM: usize = 1;
+pub const SOCK_DGRAM: usize = 2;
+pub const SOCK_NONBLOCK: usize = OpenFlags::NONBLOCK.bits() as usize;
+pub const SOCK_CLOEXEC: usize = OpenFlags::CLOEXEC.bits() as usize;
+
+pub const MSG_DONTWAIT: u32 = 0x40;
+pub const MSG_NOSIGNAL: u32 = 0x4000;
+
+// 可以传给 bind/connect/sendto 等的套接字地址
+pub trait SockAddr {
+    fn len(&self) -> usize;
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SockAddrUn {
+    pub sun_family: u16,
+    pub sun_path: [u8; 108],
+}
+
+impl SockAddrUn {
+    // 路径不含结尾的 `\0`
+    pub fn new(path: &str) -> Self {
+        let mut addr = Self::default();
+        addr.sun_path[..path.len()].copy_from_slice(path.as_bytes());
+        addr
+    }
+
+    // `len` 为内核返回的地址长度，匿名地址返回空串
+    pub fn path(&self, len: u32) -> &str {
+        let path = &self.sun_path[..(len as usize).saturating_sub(2).min(108)];
+        let end = path.iter().position(|&b| b == 0).unwrap_or(path.len());
+        core::str::from_utf8(&path[..end]).unwrap()
+    }
+}
+
+impl SockAddr for SockAddrUn {
+    fn len(&self) -> usize {
+        let end = self.sun_path.iter().position(|&b| b == 0).unwrap_or(108);
+        2 + end + 1
+    }
+}
+
+impl Default for SockAddrUn {
+    fn default() -> Self {
+        Self {
+            sun_family: AF_UNIX as u16,
+            sun_path: [0; 108],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SockAddrIn {
+    pub sin_family: u16,
+    // 网络字节序
+    pub sin_port: u16,
+    pub sin_addr: [u8; 4],
+    pub sin_zero: [u8; 8],
+}
+
+impl SockAddrIn {
+    pub fn new(addr: [u8; 4], port: u16) -> Self {
+        Self {
+            sin_family: AF_INET as u16,
+            sin_port: port.to_be(),
+            sin_addr: addr,
+            sin_zero: [0; 8],
+        }
+    }
+
+    pub fn addr(&self) -> [u8; 4] {
+        self.sin_addr
+    }
+
+    pub fn port(&self) -> u16 {
+        u16::from_be(self.sin_port)
+    }
+}
+
+impl Default for SockAddrIn {
+    fn default() -> Self {
+        Self::new([0; 4], 0)
+    }
+}
+
+impl SockAddr for SockAddrIn {
+    fn len(&self) -> usize {
+        core::mem::size_of::<Self>()
+    }
+}
+
+pub fn socket(domain: usize, type_: usize, protocol: usize) -> isize {
+    sys_socket(domain, type_, protocol)
+}
+
+pub fn bind<A: SockAddr>(fd: usize, addr: &A) -> isize {
+    sys_bind(fd, addr as *const _ as *const u8, addr.len())
+}
+
+pub fn listen(fd: usize, backlog: usize) -> isize {
+    sys_listen(fd, backlog)
+}
+
+pub fn accept<A: SockAddr>(fd: usize, addr: Option<(&mut A, &mut u32)>) -> isize {
+    match addr {
+        Some((addr, len)) => sys_accept(fd, addr as *mut _ as *mut u8, len),
+        None => sys_accept(fd, null_mut(), null_mut()),
+    }
+}
+
+pub fn connect<A: SockAddr>(fd: usize, addr: &A) -> isize {
+    sys_connect(fd, addr as *const _ as *const u8, addr.len())
+}
+
+pub fn send(fd: usize, buf: &[u8], flags: u32) -> isize {
+    sys_sendto(fd, buf, flags, null(), 0)
+}
+
+pub fn sendto<A: SockAddr>(fd: usize, buf: &[u8], flags: u32, addr: &A) -> isize {
+    sys_sendto(fd, buf, flags, addr as *const _ as *const u8, addr.len())
+}
+
+pub fn recv(fd: usize, buf: &mut [u8], flags: u32) -> isize {
+    sys_recvfrom(fd, buf, flags, null_mut(), null_mut())
+}
+
+pub fn recvfrom<A: SockAddr>(
+    fd: usize,
+    buf: &mut [u8],
+    flags: u32,
+    addr: Option<(&mut A, &mut u32)>,
+) -> isize {
+    match addr {
+        Some((addr, len)) => sys_recvfrom(fd, buf, flags, addr as *mut _ as *mut u8, len),
+        None => sys_recvfrom(fd, buf, flags, null_mut(), null_mut()),
+    }
+}
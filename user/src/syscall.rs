@@ -1,32 +1,93 @@
-use crate::SignalAction;
+use crate::{EpollEvent, FdSet, IpcBuffer, PollFd, RLimit, ShmIdDs, SignalAction, TimeSpec};
 use core::arch::asm;
 
-const SYSCALL_DUP: usize = 24;
+const SYSCALL_EPOLL_CREATE1: usize = 20;
+const SYSCALL_EPOLL_CTL: usize = 21;
+const SYSCALL_EPOLL_PWAIT: usize = 22;
+const SYSCALL_DUP: usize = 23;
+const SYSCALL_DUP3: usize = 24;
+const SYSCALL_FCNTL: usize = 25;
+const SYSCALL_IOCTL: usize = 29;
+const SYSCALL_MKNODAT: usize = 33;
 const SYSCALL_OPEN: usize = 56;
 const SYSCALL_CLOSE: usize = 57;
 const SYSCALL_PIPE: usize = 59;
 const SYSCALL_READ: usize = 63;
 const SYSCALL_WRITE: usize = 64;
+const SYSCALL_PSELECT6: usize = 72;
+const SYSCALL_PPOLL: usize = 73;
 const SYSCALL_EXIT: usize = 93;
+const SYSCALL_PTRACE: usize = 117;
 const SYSCALL_YIELD: usize = 124;
 const SYSCALL_KILL: usize = 129;
 const SYSCALL_SIGACTION: usize = 134;
 const SYSCALL_SIGPROCMASK: usize = 135;
+const SYSCALL_SIGQUEUE: usize = 138;
 const SYSCALL_SIGRETURN: usize = 139;
 const SYSCALL_TIME: usize = 169;
 const SYSCALL_PID: usize = 172;
+const SYSCALL_SHMGET: usize = 194;
+const SYSCALL_SHMCTL: usize = 195;
+const SYSCALL_SHMAT: usize = 196;
+const SYSCALL_SHMDT: usize = 197;
+const SYSCALL_SOCKET: usize = 198;
+const SYSCALL_BIND: usize = 200;
+const SYSCALL_LISTEN: usize = 201;
+const SYSCALL_ACCEPT: usize = 202;
+const SYSCALL_CONNECT: usize = 203;
+const SYSCALL_SENDTO: usize = 206;
+const SYSCALL_RECVFROM: usize = 207;
 const SYSCALL_FORK: usize = 220;
-const SYSCALL_EXEC: usize = 221;
+const SYSCALL_EXECVE: usize = 221;
 const SYSCALL_WAITPID: usize = 260;
+const SYSCALL_PRLIMIT64: usize = 261;
+const SYSCALL_IPC_CREATE: usize = 500;
+const SYSCALL_IPC_CLOSE: usize = 501;
+const SYSCALL_IPC_SEND: usize = 502;
+const SYSCALL_IPC_RECV: usize = 503;
+const SYSCALL_IPC_CALL: usize = 504;
+const SYSCALL_IPC_REPLY: usize = 505;
+const SYSCALL_TRACE: usize = 506;
 
+// 内核按 a0~a5 六个寄存器取参数，参数较少的调用把其余寄存器置零
 fn syscall(id: usize, args: [usize; 3]) -> isize {
+    syscall6(id, [args[0], args[1], args[2], 0, 0, 0])
+}
+
+fn syscall4(id: usize, args: [usize; 4]) -> isize {
+    syscall6(id, [args[0], args[1], args[2], args[3], 0, 0])
+}
+
+// 原始系统调用：a7 为编号，a0~a5 为参数；IPC 系统调用会改写 a1~a4
+pub fn syscall6(id: usize, args: [usize; 6]) -> isize {
     let mut ret: isize;
     unsafe {
         asm! {
             "ecall",
             inlateout("a0") args[0] => ret,
-            in("a1") args[1],
-            in("a2") args[2],
+            inlateout("a1") args[1] => _,
+            inlateout("a2") args[2] => _,
+            inlateout("a3") args[3] => _,
+            inlateout("a4") args[4] => _,
+            in("a5") args[5],
+            in("a7") id
+        };
+    }
+    ret
+}
+
+// IPC 系统调用在 a1~a4 中返回消息字
+fn syscall_ipc(id: usize, args: [usize; 6], words: &mut [usize; 4]) -> isize {
+    let mut ret: isize;
+    unsafe {
+        asm! {
+            "ecall",
+            inlateout("a0") args[0] => ret,
+            inlateout("a1") args[1] => words[0],
+            inlateout("a2") args[2] => words[1],
+            inlateout("a3") args[3] => words[2],
+            inlateout("a4") args[4] => words[3],
+            in("a5") args[5],
             in("a7") id
         };
     }
@@ -37,6 +98,13 @@ pub fn sys_open(path: &str, flags: u32) -> isize {
     syscall(SYSCALL_OPEN, [path.as_ptr() as usize, flags as usize, 0])
 }
 
+pub fn sys_mknodat(dirfd: isize, path: &str, mode: u32, dev: usize) -> isize {
+    syscall4(
+        SYSCALL_MKNODAT,
+        [dirfd as usize, path.as_ptr() as usize, mode as usize, dev],
+    )
+}
+
 pub fn sys_close(fd: usize) -> isize {
     syscall(SYSCALL_CLOSE, [fd, 0, 0])
 }
@@ -73,10 +141,14 @@ pub fn sys_fork() -> isize {
     syscall(SYSCALL_FORK, [0, 0, 0])
 }
 
-pub fn sys_exec(path: &str, args: &[*const u8]) -> isize {
+pub fn sys_execve(path: &str, args: &[*const u8], envs: &[*const u8]) -> isize {
     syscall(
-        SYSCALL_EXEC,
-        [path.as_ptr() as usize, args.as_ptr() as usize, 0],
+        SYSCALL_EXECVE,
+        [
+            path.as_ptr() as usize,
+            args.as_ptr() as usize,
+            envs.as_ptr() as usize,
+        ],
     )
 }
 
@@ -84,14 +156,89 @@ pub fn sys_waitpid(pid: isize, exit_code: *mut i32) -> isize {
     syscall(SYSCALL_WAITPID, [pid as usize, exit_code as usize, 0])
 }
 
-pub fn sys_pipe(pipe_fd: &mut [usize]) -> isize {
-    syscall(SYSCALL_PIPE, [pipe_fd.as_mut_ptr() as usize, 0, 0])
+pub fn sys_pipe(pipe_fd: &mut [usize], flags: u32) -> isize {
+    syscall(
+        SYSCALL_PIPE,
+        [pipe_fd.as_mut_ptr() as usize, flags as usize, 0],
+    )
 }
 
 pub fn sys_dup(fd: usize) -> isize {
     syscall(SYSCALL_DUP, [fd, 0, 0])
 }
 
+pub fn sys_dup3(old_fd: usize, new_fd: usize, flags: u32) -> isize {
+    syscall(SYSCALL_DUP3, [old_fd, new_fd, flags as usize])
+}
+
+pub fn sys_fcntl(fd: usize, cmd: usize, arg: usize) -> isize {
+    syscall(SYSCALL_FCNTL, [fd, cmd, arg])
+}
+
+pub fn sys_ioctl(fd: usize, cmd: usize, arg: usize) -> isize {
+    syscall(SYSCALL_IOCTL, [fd, cmd, arg])
+}
+
+pub fn sys_ppoll(fds: &mut [PollFd], timeout: *const TimeSpec, sigmask: *const u64) -> isize {
+    syscall4(
+        SYSCALL_PPOLL,
+        [
+            fds.as_mut_ptr() as usize,
+            fds.len(),
+            timeout as usize,
+            sigmask as usize,
+        ],
+    )
+}
+
+pub fn sys_pselect6(
+    nfds: usize,
+    readfds: *mut FdSet,
+    writefds: *mut FdSet,
+    exceptfds: *mut FdSet,
+    timeout: *const TimeSpec,
+    sigmask: *const u64,
+) -> isize {
+    syscall6(
+        SYSCALL_PSELECT6,
+        [
+            nfds,
+            readfds as usize,
+            writefds as usize,
+            exceptfds as usize,
+            timeout as usize,
+            sigmask as usize,
+        ],
+    )
+}
+
+pub fn sys_epoll_create1(flags: u32) -> isize {
+    syscall(SYSCALL_EPOLL_CREATE1, [flags as usize, 0, 0])
+}
+
+pub fn sys_epoll_ctl(epfd: usize, op: usize, fd: usize, event: *const EpollEvent) -> isize {
+    syscall4(SYSCALL_EPOLL_CTL, [epfd, op, fd, event as usize])
+}
+
+pub fn sys_epoll_pwait(
+    epfd: usize,
+    events: &mut [EpollEvent],
+    timeout: isize,
+    sigmask: *const u64,
+) -> isize {
+    syscall6(
+        SYSCALL_EPOLL_PWAIT,
+        [
+            epfd,
+            events.as_mut_ptr() as usize,
+            events.len(),
+            timeout as usize,
+            sigmask as usize,
+            8,
+        ],
+    )
+}
+
 pub fn sys_kill(pid: usize, signal: i32) -> isize {
     syscall(SYSCALL_KILL, [pid, signal as usize, 0])
 }
@@ -107,10 +254,144 @@ pub fn sys_sigaction(
     )
 }
 
-pub fn sys_sigprocmask(mask: u32) -> isize {
-    syscall(SYSCALL_SIGPROCMASK, [mask as usize, 0, 0])
+pub fn sys_sigprocmask(mask: u64, old_mask: *mut u64) -> isize {
+    syscall(SYSCALL_SIGPROCMASK, [mask as usize, old_mask as usize, 0])
+}
+
+pub fn sys_sigqueue(pid: usize, signal: i32, value: usize) -> isize {
+    syscall(SYSCALL_SIGQUEUE, [pid, signal as usize, value])
 }
 
 pub fn sys_sigreturn() -> isize {
     syscall(SYSCALL_SIGRETURN, [0, 0, 0])
 }
+
+pub fn sys_prlimit64(
+    pid: usize,
+    resource: usize,
+    new_limit: *const RLimit,
+    old_limit: *mut RLimit,
+) -> isize {
+    syscall4(
+        SYSCALL_PRLIMIT64,
+        [pid, resource, new_limit as usize, old_limit as usize],
+    )
+}
+
+pub fn sys_shmget(key: i32, size: usize, shmflg: usize) -> isize {
+    syscall(SYSCALL_SHMGET, [key as usize, size, shmflg])
+}
+
+pub fn sys_shmctl(shmid: usize, cmd: usize, buf: *mut ShmIdDs) -> isize {
+    syscall(SYSCALL_SHMCTL, [shmid, cmd, buf as usize])
+}
+
+pub fn sys_shmat(shmid: usize, shmaddr: usize, shmflg: usize) -> isize {
+    syscall(SYSCALL_SHMAT, [shmid, shmaddr, shmflg])
+}
+
+pub fn sys_shmdt(shmaddr: usize) -> isize {
+    syscall(SYSCALL_SHMDT, [shmaddr, 0, 0])
+}
+
+pub fn sys_socket(domain: usize, type_: usize, protocol: usize) -> isize {
+    syscall(SYSCALL_SOCKET, [domain, type_, protocol])
+}
+
+pub fn sys_bind(fd: usize, addr: *const u8, addrlen: usize) -> isize {
+    syscall(SYSCALL_BIND, [fd, addr as usize, addrlen])
+}
+
+pub fn sys_listen(fd: usize, backlog: usize) -> isize {
+    syscall(SYSCALL_LISTEN, [fd, backlog, 0])
+}
+
+pub fn sys_accept(fd: usize, addr: *mut u8, addrlen: *mut u32) -> isize {
+    syscall(SYSCALL_ACCEPT, [fd, addr as usize, addrlen as usize])
+}
+
+pub fn sys_connect(fd: usize, addr: *const u8, addrlen: usize) -> isize {
+    syscall(SYSCALL_CONNECT, [fd, addr as usize, addrlen])
+}
+
+pub fn sys_sendto(fd: usize, buf: &[u8], flags: u32, addr: *const u8, addrlen: usize) -> isize {
+    syscall6(
+        SYSCALL_SENDTO,
+        [
+            fd,
+            buf.as_ptr() as usize,
+            buf.len(),
+            flags as usize,
+            addr as usize,
+            addrlen,
+        ],
+    )
+}
+
+pub fn sys_recvfrom(
+    fd: usize,
+    buf: &mut [u8],
+    flags: u32,
+    addr: *mut u8,
+    addrlen: *mut u32,
+) -> isize {
+    syscall6(
+        SYSCALL_RECVFROM,
+        [
+            fd,
+            buf.as_mut_ptr() as usize,
+            buf.len(),
+            flags as usize,
+            addr as usize,
+            addrlen as usize,
+        ],
+    )
+}
+
+pub fn sys_ipc_create() -> isize {
+    syscall(SYSCALL_IPC_CREATE, [0, 0, 0])
+}
+
+pub fn sys_ipc_close(handle: usize) -> isize {
+    syscall(SYSCALL_IPC_CLOSE, [handle, 0, 0])
+}
+
+pub fn sys_ipc_send(handle: usize, words: &[usize; 4], buf: *const IpcBuffer) -> isize {
+    syscall6(
+        SYSCALL_IPC_SEND,
+        [handle, words[0], words[1], words[2], words[3], buf as usize],
+    )
+}
+
+pub fn sys_ipc_recv(
+    handle: usize,
+    words: &mut [usize; 4],
+    buf: *mut IpcBuffer,
+    flags: usize,
+) -> isize {
+    syscall_ipc(
+        SYSCALL_IPC_RECV,
+        [handle, buf as usize, flags, 0, 0, 0],
+        words,
+    )
+}
+
+pub fn sys_ipc_call(handle: usize, words: &mut [usize; 4], buf: *mut IpcBuffer) -> isize {
+    let args = [handle, words[0], words[1], words[2], words[3], buf as usize];
+    syscall_ipc(SYSCALL_IPC_CALL, args, words)
+}
+
+pub fn sys_ipc_reply(words: &[usize; 4], buf: *const IpcBuffer) -> isize {
+    syscall6(
+        SYSCALL_IPC_REPLY,
+        [words[0], words[1], words[2], words[3], buf as usize, 0],
+    )
+}
+
+pub fn sys_trace(pid: usize, fd: usize) -> isize {
+    syscall(SYSCALL_TRACE, [pid, fd, 0])
+}
+
+pub fn sys_ptrace(request: usize, pid: usize, addr: usize, data: usize) -> isize {
+    syscall4(SYSCALL_PTRACE, [request, pid, addr, data])
+}
@@ -0,0 +1,146 @@
+use super::{OpenFlags, close, open, sys_ioctl};
+use alloc::format;
+
+pub const NCCS: usize = 19;
+
+// c_cc 下标
+pub const VINTR: usize = 0;
+pub const VQUIT: usize = 1;
+pub const VERASE: usize = 2;
+pub const VKILL: usize = 3;
+pub const VEOF: usize = 4;
+pub const VTIME: usize = 5;
+pub const VMIN: usize = 6;
+pub const VSUSP: usize = 10;
+pub const VEOL: usize = 11;
+
+// c_iflag
+pub const INLCR: u32 = 0o100;
+pub const IGNCR: u32 = 0o200;
+pub const ICRNL: u32 = 0o400;
+
+// c_oflag
+pub const OPOST: u32 = 0o1;
+pub const ONLCR: u32 = 0o4;
+
+// c_lflag
+pub const ISIG: u32 = 0o1;
+pub const ICANON: u32 = 0o2;
+pub const ECHO: u32 = 0o10;
+pub const ECHOE: u32 = 0o20;
+pub const ECHOK: u32 = 0o40;
+pub const ECHONL: u32 = 0o100;
+pub const NOFLSH: u32 = 0o200;
+pub const ECHOCTL: u32 = 0o1000;
+
+// ioctl 命令
+pub const TCGETS: usize = 0x5401;
+pub const TCSETS: usize = 0x5402;
+pub const TCSETSW: usize = 0x5403;
+pub const TCSETSF: usize = 0x5404;
+pub const TIOCGPGRP: usize = 0x540F;
+pub const TIOCSPGRP: usize = 0x5410;
+pub const TIOCGWINSZ: usize = 0x5413;
+pub const TIOCSWINSZ: usize = 0x5414;
+pub const TIOCGPTN: usize = 0x8004_5430;
+pub const TIOCSPTLCK: usize = 0x4004_5431;
+
+// tcsetattr 的 optional_actions
+pub const TCSANOW: i32 = 0;
+pub const TCSADRAIN: i32 = 1;
+pub const TCSAFLUSH: i32 = 2;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Termios {
+    pub c_iflag: u32,
+    pub c_oflag: u32,
+    pub c_cflag: u32,
+    pub c_lflag: u32,
+    pub c_line: u8,
+    pub c_cc: [u8; NCCS],
+}
+
+impl Termios {
+    /// 与 cfmakeraw 相同：关闭行缓冲、回显、信号字符与输入输出转换
+    pub fn make_raw(&mut self) {
+        self.c_iflag &= !(INLCR | IGNCR | ICRNL);
+        self.c_oflag &= !OPOST;
+        self.c_lflag &= !(ISIG | ICANON | ECHO | ECHOE | ECHOK | ECHONL | ECHOCTL);
+        self.c_cc[VMIN] = 1;
+        self.c_cc[VTIME] = 0;
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Winsize {
+    pub ws_row: u16,
+    pub ws_col: u16,
+    pub ws_xpixel: u16,
+    pub ws_ypixel: u16,
+}
+
+pub fn ioctl(fd: usize, cmd: usize, arg: usize) -> isize {
+    sys_ioctl(fd, cmd, arg)
+}
+
+pub fn tcgetattr(fd: usize, termios: &mut Termios) -> isize {
+    ioctl(fd, TCGETS, termios as *mut Termios as usize)
+}
+
+pub fn tcsetattr(fd: usize, optional_actions: i32, termios: &Termios) -> isize {
+    let cmd = match optional_actions {
+        TCSANOW => TCSETS,
+        TCSADRAIN => TCSETSW,
+        TCSAFLUSH => TCSETSF,
+        _ => return -crate::EINVAL,
+    };
+    ioctl(fd, cmd, termios as *const Termios as usize)
+}
+
+pub fn tcgetpgrp(fd: usize) -> isize {
+    let mut pgrp: i32 = 0;
+    let ret = ioctl(fd, TIOCGPGRP, &mut pgrp as *mut i32 as usize);
+    if ret < 0 { ret } else { pgrp as isize }
+}
+
+pub fn tcsetpgrp(fd: usize, pgrp: usize) -> isize {
+    let pgrp = pgrp as i32;
+    ioctl(fd, TIOCSPGRP, &pgrp as *const i32 as usize)
+}
+
+pub fn tcgetwinsize(fd: usize, winsize: &mut Winsize) -> isize {
+    ioctl(fd, TIOCGWINSZ, winsize as *mut Winsize as usize)
+}
+
+pub fn tcsetwinsize(fd: usize, winsize: &Winsize) -> isize {
+    ioctl(fd, TIOCSWINSZ, winsize as *const Winsize as usize)
+}
+
+pub fn isatty(fd: usize) -> bool {
+    let mut termios = Termios::default();
+    tcgetattr(fd, &mut termios) == 0
+}
+
+/// 分配一对伪终端，成功时通过参数返回主端与从端的文件描述符
+pub fn openpty(master: &mut usize, slave: &mut usize) -> isize {
+    let fd = open("/dev/ptmx\0", OpenFlags::RDWR);
+    if fd < 0 {
+        return fd;
+    }
+    let mut index: u32 = 0;
+    let ret = ioctl(fd as usize, TIOCGPTN, &mut index as *mut u32 as usize);
+    if ret < 0 {
+        close(fd as usize);
+        return ret;
+    }
+    let slave_fd = open(format!("/dev/pts/{}\0", index).as_str(), OpenFlags::RDWR);
+    if slave_fd < 0 {
+        close(fd as usize);
+        return slave_fd;
+    }
+    *master = fd as usize;
+    *slave = slave_fd as usize;
+    0
+}